@@ -0,0 +1,328 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Binary string queries
+//!
+//! These look inside (or change a part of) a value, so that a client doesn't have to fetch
+//! or send the whole value. They work on models whose values are `binary` or `string`; since
+//! a change may split a character, the values in `string` models are checked again before
+//! they're stored
+//!
+//! Bits are numbered from the most significant bit of the first byte, so bit `0` is the
+//! `0x80` bit of the first byte and bit `8` is the `0x80` bit of the second
+
+use crate::{
+    actions::ActionResult,
+    corestore::SharedSlice,
+    dbnet::prelude::*,
+    kvengine::{types::FieldType, KVEStandard},
+    util::compiler,
+};
+
+/// The longest that `APPEND`, `SETRANGE` and `SETBIT` can make a value
+const MAX_VALUE_LEN: usize = 512 * 1024 * 1024;
+
+/// Why a value couldn't be changed
+enum EditError {
+    /// The new value isn't valid for the model
+    Encoding,
+    /// The new value would be longer than [`MAX_VALUE_LEN`]
+    TooLong,
+}
+
+action! {
+    /// Run an `APPEND` query
+    ///
+    /// `APPEND <key> <value>` appends the value to the one stored at the key (starting from an
+    /// empty value if the key doesn't exist) and returns the new length of the value
+    fn append(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len == 2)?;
+        let (key, value) = unsafe {
+            // SAFETY: We have checked for there to be two args
+            (act.next_unchecked(), act.next_unchecked())
+        };
+        let kve = self::binstr_table::<P>(handle)?;
+        // valid UTF-8 followed by valid UTF-8 is still valid UTF-8, so only the new part
+        // needs a check
+        if compiler::unlikely(!kve.is_val_ok(value)) {
+            return util::err(P::RCODE_ENCODING_ERROR);
+        }
+        let len = self::edit::<P, _>(handle, kve, key, false, |old| {
+            let new = [old, value].concat();
+            let len = new.len();
+            Ok((new, len))
+        })?;
+        con.write_usize(len).await?;
+        Ok(())
+    }
+
+    /// Run a `GETRANGE` query
+    ///
+    /// `GETRANGE <key> <start> <optional stop>` returns the bytes of the value from `start` upto
+    /// (but not including) `stop` or the end of the value. Negative indices count from the end
+    /// of the value and the range is cut down to fit the value, so this never fails because of
+    /// a bad index
+    fn getrange(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len == 2 || len == 3)?;
+        let key = unsafe {
+            // SAFETY: We have checked for there to be at least two args
+            act.next_unchecked()
+        };
+        let kve = self::binstr_table::<P>(handle)?;
+        let (start, stop) = self::parse_range::<P>(&mut act)?;
+        let value = match kve.get_cloned(key) {
+            Ok(Some(value)) => value,
+            Ok(None) => return util::err(P::RCODE_NIL),
+            Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+        };
+        let (start, stop) = self::clamp_range(start, stop, value.len());
+        let part = &value[start..stop];
+        // a part of a string may have been cut in the middle of a character
+        let tsymbol = if kve.is_val_ok(part) {
+            kve.get_value_tsymbol()
+        } else {
+            P::TSYMBOL_BINARY
+        };
+        con.write_mono_length_prefixed_with_tsymbol(part, tsymbol).await?;
+        Ok(())
+    }
+
+    /// Run a `SETRANGE` query
+    ///
+    /// `SETRANGE <key> <offset> <value>` overwrites the value stored at the key with the given
+    /// value, starting at `offset`. If the stored value is shorter than that (or the key doesn't
+    /// exist), it is first padded with zero bytes. Returns the new length of the value
+    fn setrange(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len == 3)?;
+        let key = unsafe {
+            // SAFETY: We have checked for there to be three args
+            act.next_unchecked()
+        };
+        let kve = self::binstr_table::<P>(handle)?;
+        let offset = self::parse_offset::<P>(&mut act)?;
+        let value = unsafe {
+            // SAFETY: We have checked for there to be three args
+            act.next_unchecked()
+        };
+        let len = self::edit::<P, _>(handle, kve, key, true, |old| {
+            let end = offset.checked_add(value.len()).ok_or(EditError::TooLong)?;
+            let mut new = self::resized(old, end.max(old.len()))?;
+            new[offset..end].copy_from_slice(value);
+            let len = new.len();
+            Ok((new, len))
+        })?;
+        con.write_usize(len).await?;
+        Ok(())
+    }
+
+    /// Run a `GETBIT` query
+    ///
+    /// `GETBIT <key> <offset>` returns the bit at `offset` in the value stored at the key. Bits
+    /// past the end of the value (or in a key that doesn't exist) are zero
+    fn getbit(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len == 2)?;
+        let key = unsafe {
+            // SAFETY: We have checked for there to be two args
+            act.next_unchecked()
+        };
+        let kve = self::binstr_table::<P>(handle)?;
+        let offset = self::parse_offset::<P>(&mut act)?;
+        let bit = match kve.get(key) {
+            Ok(value) => value.map_or(0, |value| self::bit_at(&value, offset)),
+            Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+        };
+        con.write_usize(bit as usize).await?;
+        Ok(())
+    }
+
+    /// Run a `SETBIT` query
+    ///
+    /// `SETBIT <key> <offset> <0|1>` sets or clears the bit at `offset` in the value stored at
+    /// the key, padding the value with zero bytes if it's too short, and returns the bit that
+    /// was there before
+    fn setbit(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len == 3)?;
+        let key = unsafe {
+            // SAFETY: We have checked for there to be three args
+            act.next_unchecked()
+        };
+        let kve = self::binstr_table::<P>(handle)?;
+        let offset = self::parse_offset::<P>(&mut act)?;
+        let set = match unsafe { act.next_unchecked() } {
+            b"0" => false,
+            b"1" => true,
+            _ => return util::err(P::RCODE_WRONGTYPE_ERR),
+        };
+        let (byte, mask) = (offset / 8, 0x80 >> (offset % 8));
+        let old = self::edit::<P, _>(handle, kve, key, true, |old| {
+            let mut new = self::resized(old, (byte + 1).max(old.len()))?;
+            let was = self::bit_at(&new, offset);
+            if set {
+                new[byte] |= mask;
+            } else {
+                new[byte] &= !mask;
+            }
+            Ok((new, was))
+        })?;
+        con.write_usize(old as usize).await?;
+        Ok(())
+    }
+
+    /// Run a `BITCOUNT` query
+    ///
+    /// `BITCOUNT <key> <optional start> <optional stop>` returns the number of bits that are
+    /// set in the value stored at the key (zero if the key doesn't exist). With `start` (and
+    /// `stop`), only the bytes that `GETRANGE` would return are counted
+    fn bitcount(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| (1..=3).contains(&len))?;
+        let key = unsafe {
+            // SAFETY: We have checked for there to be at least one arg
+            act.next_unchecked()
+        };
+        let kve = self::binstr_table::<P>(handle)?;
+        let (start, stop) = if act.is_empty() {
+            (0, None)
+        } else {
+            self::parse_range::<P>(&mut act)?
+        };
+        let count = match kve.get(key) {
+            Ok(Some(value)) => {
+                let (start, stop) = self::clamp_range(start, stop, value.len());
+                value[start..stop]
+                    .iter()
+                    .map(|byte| byte.count_ones() as usize)
+                    .sum()
+            }
+            Ok(None) => 0,
+            Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+        };
+        con.write_usize(count).await?;
+        Ok(())
+    }
+}
+
+/// Returns the current table if its values are binary strings (`binary` or `string`)
+fn binstr_table<P: ProtocolSpec>(handle: &Corestore) -> ActionResult<&KVEStandard> {
+    let kve = handle.get_table_with::<P, KVEBlob>()?;
+    match kve.get_type_tuple().1 {
+        FieldType::Binary | FieldType::String => Ok(kve),
+        _ => util::err(P::RSTRING_WRONG_MODEL),
+    }
+}
+
+/// Change the value of `key` with `f` and log the new value. If `recheck` is set, the new value
+/// is checked against the model before it's stored
+fn edit<P: ProtocolSpec, R>(
+    handle: &Corestore,
+    kve: &KVEStandard,
+    key: &[u8],
+    recheck: bool,
+    f: impl FnOnce(&[u8]) -> Result<(Vec<u8>, R), EditError>,
+) -> ActionResult<R> {
+    if compiler::unlikely(!kve.is_key_ok(key)) {
+        return util::err(P::RCODE_ENCODING_ERROR);
+    }
+    handle.ensure_room::<P>()?;
+    if !registry::state_okay() {
+        return util::err(P::RCODE_SERVER_ERR);
+    }
+    let ret = handle.wal_log(|batch| {
+        let ret = kve.edit_unchecked(SharedSlice::new(key), |old| {
+            let (new, ret) = f(old)?;
+            if new.len() > MAX_VALUE_LEN {
+                Err(EditError::TooLong)
+            } else if recheck && !kve.is_val_ok(&new) {
+                Err(EditError::Encoding)
+            } else {
+                Ok((SharedSlice::from(new), ret))
+            }
+        });
+        if ret.is_ok() {
+            batch.kv(kve, key);
+        }
+        ret
+    });
+    match ret {
+        Ok(ret) => Ok(ret),
+        Err(EditError::Encoding) => util::err(P::RCODE_ENCODING_ERROR),
+        Err(EditError::TooLong) => util::err(P::RSTRING_VALUE_OVERFLOW),
+    }
+}
+
+/// Copy `value`, padding it with zero bytes upto `len`
+fn resized(value: &[u8], len: usize) -> Result<Vec<u8>, EditError> {
+    if len > MAX_VALUE_LEN {
+        return Err(EditError::TooLong);
+    }
+    let mut new = Vec::with_capacity(len);
+    new.extend_from_slice(value);
+    new.resize(len, 0);
+    Ok(new)
+}
+
+/// Returns the bit at `offset` in `value`, or zero if the value is too short
+fn bit_at(value: &[u8], offset: usize) -> u8 {
+    value
+        .get(offset / 8)
+        .map_or(0, |byte| (byte >> (7 - offset % 8)) & 1)
+}
+
+/// Parse the next argument as an offset into a value
+fn parse_offset<P: ProtocolSpec>(act: &mut ActionIter<'_>) -> ActionResult<usize> {
+    match act
+        .next()
+        .map(|offset| String::from_utf8_lossy(offset).parse())
+    {
+        Some(Ok(offset)) => Ok(offset),
+        _ => util::err(P::RCODE_WRONGTYPE_ERR),
+    }
+}
+
+/// Parse the next argument and the one after it (if there is one) as the start and stop of a
+/// range in a value
+fn parse_range<P: ProtocolSpec>(act: &mut ActionIter<'_>) -> ActionResult<(isize, Option<isize>)> {
+    let mut indices = act.map(|idx| String::from_utf8_lossy(idx).parse::<isize>());
+    match (indices.next(), indices.next()) {
+        (Some(Ok(start)), None) => Ok((start, None)),
+        (Some(Ok(start)), Some(Ok(stop))) => Ok((start, Some(stop))),
+        _ => util::err(P::RCODE_WRONGTYPE_ERR),
+    }
+}
+
+/// Turn a range (where negative indices count from the end) into one that fits a value that
+/// is `len` bytes long. A range that doesn't fit is cut down, possibly to nothing at all
+fn clamp_range(start: isize, stop: Option<isize>, len: usize) -> (usize, usize) {
+    let clamp = |idx: isize| {
+        if idx < 0 {
+            len.saturating_sub(idx.unsigned_abs())
+        } else {
+            (idx as usize).min(len)
+        }
+    };
+    let start = clamp(start);
+    let stop = stop.map_or(len, clamp);
+    (start, stop.max(start))
+}
@@ -27,10 +27,7 @@
 //! # `DEL` queries
 //! This module provides functions to work with `DEL` queries
 
-use crate::{
-    corestore::table::DataModel, dbnet::prelude::*,
-    kvengine::encoding::ENCODING_LUT_ITER, util::compiler,
-};
+use crate::{corestore::table::DataModel, dbnet::prelude::*, util::compiler};
 
 action!(
     /// Run a `DEL` query
@@ -42,14 +39,19 @@ action!(
         let table = get_tbl_ref!(handle, con);
         macro_rules! remove {
             ($engine:expr) => {{
-                let encoding_is_okay = ENCODING_LUT_ITER[$engine.is_key_encoded()](act.as_ref());
+                let encoding_is_okay = $engine.is_key_iter_ok(act.as_ref());
                 if compiler::likely(encoding_is_okay) {
                     let done_howmany: Option<usize>;
                     {
                         if registry::state_okay() {
                             let mut many = 0;
-                            act.for_each(|key| {
-                                many += $engine.remove_unchecked(key) as usize;
+                            handle.wal_log(|batch| {
+                                act.for_each(|key| {
+                                    if $engine.remove_unchecked(key) {
+                                        many += 1;
+                                        batch.del(key);
+                                    }
+                                })
                             });
                             done_howmany = Some(many);
                         } else {
@@ -67,12 +69,27 @@ action!(
             }};
         }
         match table.get_model_ref() {
-            DataModel::KV(kve) => {
+            DataModel::KV(kve) | DataModel::KVOrdered(kve) => {
                 remove!(kve)
             }
             DataModel::KVExtListmap(kvlmap) => {
                 remove!(kvlmap)
             }
+            DataModel::Rowmap(rm) => {
+                remove!(rm.rows())
+            }
+            DataModel::Hashmap(hm) => {
+                remove!(hm.maps())
+            }
+            DataModel::Set(set) => {
+                remove!(set)
+            }
+            DataModel::SortedSet(zset) => {
+                remove!(zset)
+            }
+            DataModel::Queue(queue) => {
+                remove!(queue)
+            }
             #[allow(unreachable_patterns)]
             _ => return util::err(P::RSTRING_WRONG_MODEL),
         }
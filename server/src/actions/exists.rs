@@ -28,8 +28,7 @@
 //! This module provides functions to work with `EXISTS` queries
 
 use crate::{
-    corestore::table::DataModel, dbnet::prelude::*,
-    kvengine::encoding::ENCODING_LUT_ITER, queryengine::ActionIter, util::compiler,
+    corestore::table::DataModel, dbnet::prelude::*, queryengine::ActionIter, util::compiler,
 };
 
 action!(
@@ -39,7 +38,7 @@ action!(
         let mut how_many_of_them_exist = 0usize;
         macro_rules! exists {
             ($engine:expr) => {{
-                let encoding_is_okay = ENCODING_LUT_ITER[$engine.is_key_encoded()](act.as_ref());
+                let encoding_is_okay = $engine.is_key_iter_ok(act.as_ref());
                 if compiler::likely(encoding_is_okay) {
                     act.for_each(|key| {
                         how_many_of_them_exist += $engine.exists_unchecked(key) as usize;
@@ -52,8 +51,13 @@ action!(
         }
         let tbl = get_tbl_ref!(handle, con);
         match tbl.get_model_ref() {
-            DataModel::KV(kve) => exists!(kve),
+            DataModel::KV(kve) | DataModel::KVOrdered(kve) => exists!(kve),
             DataModel::KVExtListmap(kve) => exists!(kve),
+            DataModel::Rowmap(rm) => exists!(rm.rows()),
+            DataModel::Hashmap(hm) => exists!(hm.maps()),
+            DataModel::Set(set) => exists!(set),
+            DataModel::SortedSet(zset) => exists!(zset),
+            DataModel::Queue(queue) => exists!(queue),
             #[allow(unreachable_patterns)]
             _ => return util::err(P::RSTRING_WRONG_MODEL),
         }
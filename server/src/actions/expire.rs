@@ -0,0 +1,65 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # `EXPIRE` queries
+//! This module provides functions to work with `EXPIRE` queries
+
+use crate::dbnet::prelude::*;
+
+action!(
+    /// Run an `EXPIRE` query
+    ///
+    /// `EXPIRE <key> <seconds>` sets a TTL on an existing key
+    fn expire(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len == 2)?;
+        let (key, secs) = unsafe {
+            // SAFETY: We have checked for there to be two args
+            (act.next_unchecked(), act.next_unchecked())
+        };
+        let secs = match String::from_utf8_lossy(secs).parse::<u64>() {
+            Ok(secs) => secs,
+            Err(_) => return util::err(P::RCODE_WRONGTYPE_ERR),
+        };
+        if registry::state_okay() {
+            let kve = handle.get_table_with::<P, KVEBlob>()?;
+            let ret = handle.wal_log(|batch| {
+                let ret = kve.expire(key, secs);
+                if let Ok(true) = ret {
+                    batch.kv(kve, key);
+                }
+                ret
+            });
+            match ret {
+                Ok(true) => con._write_raw(P::RCODE_OKAY).await?,
+                Ok(false) => con._write_raw(P::RCODE_NIL).await?,
+                Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+            }
+        } else {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        Ok(())
+    }
+);
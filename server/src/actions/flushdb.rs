@@ -33,12 +33,20 @@ action!(
         if registry::state_okay() {
             if act.is_empty() {
                 // flush the current table
-                get_tbl_ref!(handle, con).truncate_table();
+                let tbl = get_tbl_ref!(handle, con);
+                handle.wal_log(|batch| {
+                    tbl.truncate_table();
+                    batch.truncate();
+                });
             } else {
                 // flush the entity
                 let raw_entity = unsafe { act.next_unchecked() };
                 let entity = handle_entity!(con, raw_entity);
-                get_tbl!(&entity, handle, con).truncate_table();
+                let tbl = get_tbl!(&entity, handle, con);
+                handle.wal_log_for(&entity, &tbl, |batch| {
+                    tbl.truncate_table();
+                    batch.truncate();
+                });
             }
             con._write_raw(P::RCODE_OKAY).await?;
         } else {
@@ -0,0 +1,110 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # `INCR`, `DECR` and `INCRBY` queries
+//! This module provides functions to work with numeric values
+
+use crate::{
+    actions::ActionResult, corestore::SharedSlice, dbnet::prelude::*, kvengine::types::NumError,
+};
+
+action!(
+    /// Run an `INCR` query
+    ///
+    /// `INCR <key>` adds one to the number stored at the key
+    fn incr(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len == 1)?;
+        let key = unsafe {
+            // SAFETY: We have checked for there to be one arg
+            act.next_unchecked()
+        };
+        let new = self::incr_by::<P>(handle, key, b"1")?;
+        con.write_mono_length_prefixed_with_tsymbol(&new, b'+')
+            .await?;
+        Ok(())
+    }
+
+    /// Run a `DECR` query
+    ///
+    /// `DECR <key>` subtracts one from the number stored at the key
+    fn decr(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len == 1)?;
+        let key = unsafe {
+            // SAFETY: We have checked for there to be one arg
+            act.next_unchecked()
+        };
+        let new = self::incr_by::<P>(handle, key, b"-1")?;
+        con.write_mono_length_prefixed_with_tsymbol(&new, b'+')
+            .await?;
+        Ok(())
+    }
+
+    /// Run an `INCRBY` query
+    ///
+    /// `INCRBY <key> <delta>` adds `delta` (which may be negative) to the number stored at
+    /// the key
+    fn incrby(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len == 2)?;
+        let (key, delta) = unsafe {
+            // SAFETY: We have checked for there to be two args
+            (act.next_unchecked(), act.next_unchecked())
+        };
+        let new = self::incr_by::<P>(handle, key, delta)?;
+        con.write_mono_length_prefixed_with_tsymbol(&new, b'+')
+            .await?;
+        Ok(())
+    }
+);
+
+/// Add `delta` to the value of `key` in the current table and log the new value. A missing
+/// key counts as zero
+fn incr_by<P: ProtocolSpec>(
+    handle: &Corestore,
+    key: &[u8],
+    delta: &[u8],
+) -> ActionResult<SharedSlice> {
+    let kve = handle.get_table_with::<P, KVEBlob>()?;
+    handle.ensure_room::<P>()?;
+    if !kve.get_type_tuple().1.is_numeric() {
+        return util::err(P::RSTRING_WRONG_MODEL);
+    }
+    if !registry::state_okay() {
+        return util::err(P::RCODE_SERVER_ERR);
+    }
+    let ret = handle.wal_log(|batch| {
+        let ret = kve.incr_by(SharedSlice::new(key), delta);
+        if let Ok(Ok(_)) = ret {
+            batch.kv(kve, key);
+        }
+        ret
+    });
+    match ret {
+        Ok(Ok(new)) => Ok(new),
+        Ok(Err(NumError::Overflow)) => util::err(P::RSTRING_VALUE_OVERFLOW),
+        Ok(Err(NumError::BadDelta | NumError::NotNumeric)) => util::err(P::RCODE_WRONGTYPE_ERR),
+        Err(()) => util::err(P::RCODE_ENCODING_ERROR),
+    }
+}
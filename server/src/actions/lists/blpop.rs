@@ -0,0 +1,130 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Blocking pops
+//!
+//! A blocked pop doesn't poll. Every write to a list (a push included) notifies the watchers
+//! of the list's key, so `BLPOP` watches the lists it is waiting on for as long as it waits and
+//! looks at them again whenever one of them changes. Since the watches are set up before the
+//! lists are first looked at, an element that is pushed in between is never missed
+
+use {
+    crate::{
+        corestore::SharedSlice,
+        dbnet::prelude::*,
+        kvengine::KVEListmap,
+        pubsub::{KeyEvent, Kind, Subscriptions},
+        util::compiler,
+    },
+    core::future,
+    std::time::Duration,
+    tokio::time::{self, Instant},
+};
+
+action! {
+    /// Handle a `BLPOP` query
+    /// Syntax: `BLPOP <list> ... <timeout>`
+    ///
+    /// Pops the last element of the first of the lists that isn't empty (just like
+    /// `LMOD <list> pop`) and returns the name of that list followed by the element. If all
+    /// of them are empty, this waits for upto `<timeout>` seconds (or forever if it is `0`) for
+    /// an element to be pushed to any of them, returning nil if none was
+    fn blpop(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len > 1)?;
+        let listmap = handle.get_table_with::<P, KVEList>()?;
+        let timeout = match act.next_back().map(|t| String::from_utf8_lossy(t).parse::<u64>()) {
+            Some(Ok(timeout)) => timeout,
+            _ => return util::err(P::RCODE_WRONGTYPE_ERR),
+        };
+        let listnames: Vec<&[u8]> = act.collect();
+        if compiler::unlikely(!listnames.iter().all(|list| listmap.is_key_ok(list))) {
+            return util::err(P::RCODE_ENCODING_ERROR);
+        }
+        let mut changes = Subscriptions::new();
+        for listname in listnames.iter() {
+            changes.subscribe(Kind::Key, listmap.get_watchers(), listname);
+        }
+        let deadline = match timeout {
+            0 => None,
+            // a deadline that is too far out to be represented is as good as none at all
+            secs => Instant::now().checked_add(Duration::from_secs(secs)),
+        };
+        loop {
+            if !registry::state_okay() {
+                return util::err(P::RCODE_SERVER_ERR);
+            }
+            if let Some((listname, element)) = pop_first(handle, listmap, &listnames) {
+                let tsymbol = if listmap.get_key_tsymbol() == listmap.get_value_tsymbol() {
+                    listmap.get_value_tsymbol()
+                } else {
+                    P::TSYMBOL_BINARY
+                };
+                let body: [&[u8]; 2] = [listname, &element];
+                con.write_typed_non_null_array(body, tsymbol).await?;
+                return Ok(());
+            }
+            tokio::select! {
+                // one of the lists changed, so look again
+                _ = changes.recv() => {}
+                _ = sleep_until(deadline) => {
+                    con._write_raw(P::RCODE_NIL).await?;
+                    return Ok(());
+                }
+                _ = con.interrupted() => {
+                    con._write_raw(P::RSTRING_ERR_ACCESS_AFTER_TERMSIG).await?;
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+/// Pop the last element of the first of the given lists that isn't empty, returning the name
+/// of that list and the element
+fn pop_first<'a>(
+    handle: &Corestore,
+    listmap: &KVEListmap,
+    listnames: &[&'a [u8]],
+) -> Option<(&'a [u8], SharedSlice)> {
+    handle.wal_log(|batch| {
+        listnames.iter().find_map(|listname| {
+            let list = listmap.get_inner_ref().get(*listname)?;
+            let mut wlock = list.write();
+            let element = wlock.pop()?;
+            batch.list(listname, &wlock);
+            listmap.notify(listname, KeyEvent::Update);
+            Some((*listname, element))
+        })
+    })
+}
+
+/// Sleep until the deadline, if there is one
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => time::sleep_until(deadline).await,
+        None => future::pending().await,
+    }
+}
@@ -24,7 +24,7 @@
  *
 */
 
-use crate::{corestore::SharedSlice, dbnet::prelude::*};
+use {super::Range, crate::dbnet::prelude::*};
 
 const LEN: &[u8] = "LEN".as_bytes();
 const LIMIT: &[u8] = "LIMIT".as_bytes();
@@ -33,25 +33,6 @@ const LAST: &[u8] = "LAST".as_bytes();
 const FIRST: &[u8] = "FIRST".as_bytes();
 const RANGE: &[u8] = "RANGE".as_bytes();
 
-struct Range {
-    start: usize,
-    stop: Option<usize>,
-}
-
-impl Range {
-    pub fn new(start: usize) -> Self {
-        Self { start, stop: None }
-    }
-    pub fn set_stop(&mut self, stop: usize) {
-        self.stop = Some(stop);
-    }
-    pub fn into_vec(self, slice: &[SharedSlice]) -> Option<Vec<SharedSlice>> {
-        slice
-            .get(self.start..self.stop.unwrap_or(slice.len()))
-            .map(|slc| slc.to_vec())
-    }
-}
-
 action! {
     /// Handle an `LGET` query for the list model (KVExt)
     /// ## Syntax
@@ -62,6 +43,10 @@ action! {
     /// - `LGET <mylist> FIRST` will return the first item
     /// - `LGET <mylist> LAST` will return the last item
     /// if it exists
+    /// - `LGET <mylist> RANGE <start> <optional stop>` will return the items in the range
+    ///
+    /// Negative indices count from the end of the list, so `LGET <mylist> VALUEAT -1` returns
+    /// the last item
     fn lget(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
         ensure_length::<P>(act.len(), |len| len != 0)?;
         let listmap = handle.get_table_with::<P, KVEList>()?;
@@ -76,6 +61,14 @@ action! {
                 }
             };
         }
+        macro_rules! get_index {
+            () => {
+                match unsafe { String::from_utf8_lossy(act.next_unchecked()) }.parse::<isize>() {
+                    Ok(idx) => idx,
+                    Err(_) => return util::err(P::RCODE_WRONGTYPE_ERR),
+                }
+            };
+        }
         match act.next_uppercase().as_ref() {
             None => {
                 // just return everything in the list
@@ -107,9 +100,13 @@ action! {
                     }
                     VALUEAT => {
                         ensure_length::<P>(act.len(), |len| len == 1)?;
-                        let idx = get_numeric_count!();
+                        let idx = get_index!();
                         let maybe_value = listmap.get(listname).map(|list| {
-                            list.map(|lst| lst.read().get(idx).cloned())
+                            list.map(|lst| {
+                                let lst = lst.read();
+                                super::resolve_index(idx, lst.len())
+                                    .and_then(|idx| lst.get(idx).cloned())
+                            })
                         });
                         match maybe_value {
                             Ok(v) => match v {
@@ -169,13 +166,13 @@ action! {
                     RANGE => {
                         match act.next_string_owned() {
                             Some(start) => {
-                                let start: usize = match start.parse() {
+                                let start: isize = match start.parse() {
                                     Ok(v) => v,
                                     Err(_) => return util::err(P::RCODE_WRONGTYPE_ERR),
                                 };
                                 let mut range = Range::new(start);
                                 if let Some(stop) = act.next_string_owned() {
-                                    let stop: usize = match stop.parse() {
+                                    let stop: isize = match stop.parse() {
                                         Ok(v) => v,
                                         Err(_) => return util::err(P::RCODE_WRONGTYPE_ERR),
                                     };
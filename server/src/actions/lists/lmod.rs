@@ -24,13 +24,35 @@
  *
 */
 
-use crate::{corestore::SharedSlice, dbnet::prelude::*, util::compiler};
+use {
+    super::Range,
+    crate::{corestore::SharedSlice, dbnet::prelude::*, pubsub::KeyEvent, util::compiler},
+};
 
 const CLEAR: &[u8] = "CLEAR".as_bytes();
 const PUSH: &[u8] = "PUSH".as_bytes();
 const REMOVE: &[u8] = "REMOVE".as_bytes();
 const INSERT: &[u8] = "INSERT".as_bytes();
 const POP: &[u8] = "POP".as_bytes();
+const TRIM: &[u8] = "TRIM".as_bytes();
+const LAST: &[u8] = "LAST".as_bytes();
+
+/// What `LMOD <mylist> trim` keeps of a list
+enum Trim {
+    /// the items that `LGET <mylist> RANGE` would return
+    Range(Range),
+    /// the last few items (or all of them, if the list has fewer)
+    Last(usize),
+}
+
+impl Trim {
+    fn bounds(&self, len: usize) -> Option<(usize, usize)> {
+        match self {
+            Self::Range(range) => range.bounds(len),
+            Self::Last(count) => Some((len.saturating_sub(*count), len)),
+        }
+    }
+}
 
 action! {
     /// Handle `LMOD` queries
@@ -40,6 +62,11 @@ action! {
     /// - `LMOD <mylist> insert <index> <value>`
     /// - `LMOD <mylist> remove <index>`
     /// - `LMOD <mylist> clear`
+    /// - `LMOD <mylist> trim <start> <optional stop>`
+    /// - `LMOD <mylist> trim last <count>`
+    ///
+    /// Lists in a model with a maximum length drop their first items when a push or an insert
+    /// makes them any longer
     fn lmod(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
         ensure_length::<P>(act.len(), |len| len > 1)?;
         let listmap = handle.get_table_with::<P, KVEList>()?;
@@ -57,13 +84,19 @@ action! {
         match unsafe { act.next_uppercase_unchecked() }.as_ref() {
             CLEAR => {
                 ensure_length::<P>(act.len(), |len| len == 0)?;
-                let list = match listmap.get_inner_ref().get(listname) {
-                    Some(l) => l,
-                    _ => return Err(P::RCODE_NIL.into()),
-                };
                 let okay = if registry::state_okay() {
-                    list.write().clear();
-                    P::RCODE_OKAY
+                    let cleared = handle.wal_log(|batch| {
+                        listmap.get_inner_ref().get(listname).map(|list| {
+                            let mut wlock = list.write();
+                            wlock.clear();
+                            batch.list(listname, &wlock);
+                            listmap.notify(listname, KeyEvent::Update);
+                        })
+                    });
+                    match cleared {
+                        Some(()) => P::RCODE_OKAY,
+                        None => P::RCODE_NIL,
+                    }
                 } else {
                     P::RCODE_SERVER_ERR
                 };
@@ -71,15 +104,23 @@ action! {
             }
             PUSH => {
                 ensure_boolean_or_aerr::<P>(!act.is_empty())?;
-                let list = match listmap.get_inner_ref().get(listname) {
-                    Some(l) => l,
-                    _ => return Err(P::RCODE_NIL.into()),
-                };
+                handle.ensure_room::<P>()?;
                 let venc_ok = listmap.get_val_encoder();
                 let ret = if compiler::likely(act.as_ref().all(venc_ok)) {
                     if registry::state_okay() {
-                        list.write().extend(act.map(SharedSlice::new));
-                        P::RCODE_OKAY
+                        let pushed = handle.wal_log(|batch| {
+                            listmap.get_inner_ref().get(listname).map(|list| {
+                                let mut wlock = list.write();
+                                wlock.extend(act.map(SharedSlice::new));
+                                listmap.cap(&mut wlock);
+                                batch.list(listname, &wlock);
+                                listmap.notify(listname, KeyEvent::Update);
+                            })
+                        });
+                        match pushed {
+                            Some(()) => P::RCODE_OKAY,
+                            None => P::RCODE_NIL,
+                        }
                     } else {
                         P::RCODE_SERVER_ERR
                     }
@@ -92,14 +133,18 @@ action! {
                 ensure_length::<P>(act.len(), |len| len == 1)?;
                 let idx_to_remove = get_numeric_count!();
                 if registry::state_okay() {
-                    let maybe_value = listmap.get_inner_ref().get(listname).map(|list| {
-                        let mut wlock = list.write();
-                        if idx_to_remove < wlock.len() {
-                            wlock.remove(idx_to_remove);
-                            true
-                        } else {
-                            false
-                        }
+                    let maybe_value = handle.wal_log(|batch| {
+                        listmap.get_inner_ref().get(listname).map(|list| {
+                            let mut wlock = list.write();
+                            if idx_to_remove < wlock.len() {
+                                wlock.remove(idx_to_remove);
+                                batch.list(listname, &wlock);
+                                listmap.notify(listname, KeyEvent::Update);
+                                true
+                            } else {
+                                false
+                            }
+                        })
                     });
                     con._write_raw(P::OKAY_BADIDX_NIL_NLUT[maybe_value]).await?
                 } else {
@@ -108,23 +153,32 @@ action! {
             }
             INSERT => {
                 ensure_length::<P>(act.len(), |len| len == 2)?;
+                handle.ensure_room::<P>()?;
                 let idx_to_insert_at = get_numeric_count!();
                 let bts = unsafe { act.next_unchecked() };
                 let ret = if compiler::likely(listmap.is_val_ok(bts)) {
                     if registry::state_okay() {
                         // okay state, good to insert
-                        let maybe_insert = match listmap.get(listname) {
-                            Ok(lst) => lst.map(|list| {
-                                let mut wlock = list.write();
-                                if idx_to_insert_at < wlock.len() {
-                                    // we can insert
-                                    wlock.insert(idx_to_insert_at, SharedSlice::new(bts));
-                                    true
-                                } else {
-                                    // oops, out of bounds
-                                    false
-                                }
-                            }),
+                        let maybe_insert = handle.wal_log(|batch| {
+                            listmap.get(listname).map(|lst| {
+                                lst.map(|list| {
+                                    let mut wlock = list.write();
+                                    if idx_to_insert_at < wlock.len() {
+                                        // we can insert
+                                        wlock.insert(idx_to_insert_at, SharedSlice::new(bts));
+                                        listmap.cap(&mut wlock);
+                                        batch.list(listname, &wlock);
+                                        listmap.notify(listname, KeyEvent::Update);
+                                        true
+                                    } else {
+                                        // oops, out of bounds
+                                        false
+                                    }
+                                })
+                            })
+                        });
+                        let maybe_insert = match maybe_insert {
+                            Ok(maybe_insert) => maybe_insert,
                             Err(()) => return Err(P::RCODE_ENCODING_ERROR.into()),
                         };
                         P::OKAY_BADIDX_NIL_NLUT[maybe_insert]
@@ -148,20 +202,30 @@ action! {
                     None
                 };
                 if registry::state_okay() {
-                    let maybe_pop = match listmap.get(listname) {
-                        Ok(lst) => lst.map(|list| {
-                            let mut wlock = list.write();
-                            if let Some(idx) = idx {
-                                if idx < wlock.len() {
-                                    // so we can pop
-                                    Some(wlock.remove(idx))
+                    let maybe_pop = handle.wal_log(|batch| {
+                        listmap.get(listname).map(|lst| {
+                            lst.map(|list| {
+                                let mut wlock = list.write();
+                                let popped = if let Some(idx) = idx {
+                                    if idx < wlock.len() {
+                                        // so we can pop
+                                        Some(wlock.remove(idx))
+                                    } else {
+                                        None
+                                    }
                                 } else {
-                                    None
+                                    wlock.pop()
+                                };
+                                if popped.is_some() {
+                                    batch.list(listname, &wlock);
+                                    listmap.notify(listname, KeyEvent::Update);
                                 }
-                            } else {
-                                wlock.pop()
-                            }
-                        }),
+                                popped
+                            })
+                        })
+                    });
+                    let maybe_pop = match maybe_pop {
+                        Ok(maybe_pop) => maybe_pop,
                         Err(()) => return Err(P::RCODE_ENCODING_ERROR.into()),
                     };
                     match maybe_pop {
@@ -179,6 +243,50 @@ action! {
                     con._write_raw(P::RCODE_SERVER_ERR).await?
                 }
             }
+            TRIM => {
+                ensure_length::<P>(act.len(), |len| len == 1 || len == 2)?;
+                macro_rules! get_index {
+                    ($idx:expr) => {
+                        match String::from_utf8_lossy($idx).parse::<isize>() {
+                            Ok(idx) => idx,
+                            Err(_) => return Err(P::RCODE_WRONGTYPE_ERR.into()),
+                        }
+                    };
+                }
+                let first = unsafe { act.next_unchecked() };
+                let trim = if first.eq_ignore_ascii_case(LAST) {
+                    ensure_length::<P>(act.len(), |len| len == 1)?;
+                    Trim::Last(get_numeric_count!())
+                } else {
+                    let mut range = Range::new(get_index!(first));
+                    if let Some(stop) = act.next() {
+                        range.set_stop(get_index!(stop));
+                    }
+                    Trim::Range(range)
+                };
+                if registry::state_okay() {
+                    let trimmed = handle.wal_log(|batch| {
+                        listmap.get_inner_ref().get(listname).map(|list| {
+                            let mut wlock = list.write();
+                            match trim.bounds(wlock.len()) {
+                                Some((start, stop)) => {
+                                    if (start, stop) != (0, wlock.len()) {
+                                        wlock.truncate(stop);
+                                        wlock.drain(..start);
+                                        batch.list(listname, &wlock);
+                                        listmap.notify(listname, KeyEvent::Update);
+                                    }
+                                    true
+                                }
+                                None => false,
+                            }
+                        })
+                    });
+                    con._write_raw(P::OKAY_BADIDX_NIL_NLUT[trimmed]).await?
+                } else {
+                    return Err(P::RCODE_SERVER_ERR.into());
+                }
+            }
             _ => con._write_raw(P::RCODE_UNKNOWN_ACTION).await?,
         }
         Ok(())
@@ -27,10 +27,11 @@
 #[macro_use]
 mod macros;
 // modules
+pub mod blpop;
 pub mod lget;
 pub mod lmod;
 
-use crate::{corestore::SharedSlice, dbnet::prelude::*, kvengine::LockedVec};
+use crate::{corestore::SharedSlice, dbnet::prelude::*, kvengine::LockedVec, pubsub::KeyEvent};
 
 action! {
     /// Handle an `LSET` query for the list model
@@ -38,16 +39,22 @@ action! {
     fn lset(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
         ensure_length::<P>(act.len(), |len| len > 0)?;
         let listmap = handle.get_table_with::<P, KVEList>()?;
+        handle.ensure_room::<P>()?;
         let listname = unsafe { act.next_unchecked_bytes() };
         let list = listmap.get_inner_ref();
         if registry::state_okay() {
-            let did = if let Some(entry) = list.fresh_entry(listname) {
-                let v: Vec<SharedSlice> = act.map(SharedSlice::new).collect();
-                entry.insert(LockedVec::new(v));
-                true
-            } else {
-                false
-            };
+            let did = handle.wal_log(|batch| {
+                if let Some(entry) = list.fresh_entry(listname.clone()) {
+                    let mut v: Vec<SharedSlice> = act.map(SharedSlice::new).collect();
+                    listmap.cap(&mut v);
+                    batch.list(&listname, &v);
+                    entry.insert(LockedVec::new(v));
+                    listmap.notify(&listname, KeyEvent::Set);
+                    true
+                } else {
+                    false
+                }
+            });
             con._write_raw(P::OKAY_OVW_BLUT[did]).await?
         } else {
             con._write_raw(P::RCODE_SERVER_ERR).await?
@@ -55,3 +62,47 @@ action! {
         Ok(())
     }
 }
+
+/// A range of the elements of a list, from `start` upto (but not including) `stop` or the end
+/// of the list. Negative indices count from the end of the list, so `-1` is the last element
+pub struct Range {
+    start: isize,
+    stop: Option<isize>,
+}
+
+impl Range {
+    pub fn new(start: isize) -> Self {
+        Self { start, stop: None }
+    }
+    pub fn set_stop(&mut self, stop: isize) {
+        self.stop = Some(stop);
+    }
+    /// Returns the bounds of the range in a list of `len` elements or `None` if the range
+    /// isn't in the list
+    pub fn bounds(&self, len: usize) -> Option<(usize, usize)> {
+        let start = self::resolve_index(self.start, len)?;
+        let stop = match self.stop {
+            Some(stop) => self::resolve_index(stop, len)?,
+            None => len,
+        };
+        if start <= stop && stop <= len {
+            Some((start, stop))
+        } else {
+            None
+        }
+    }
+    pub fn into_vec(self, slice: &[SharedSlice]) -> Option<Vec<SharedSlice>> {
+        let (start, stop) = self.bounds(slice.len())?;
+        Some(slice[start..stop].to_vec())
+    }
+}
+
+/// Turn an index that may count from the end of a list of `len` elements into one that counts
+/// from the start. Returns `None` if it goes past the start of the list
+pub fn resolve_index(idx: isize, len: usize) -> Option<usize> {
+    if idx < 0 {
+        len.checked_sub(idx.unsigned_abs())
+    } else {
+        Some(idx as usize)
+    }
+}
@@ -66,12 +66,22 @@ action!(
             (get_tbl!(&entity, handle, con), count)
         };
         let tsymbol = match table.get_model_ref() {
-            DataModel::KV(kv) => kv.get_value_tsymbol(),
+            DataModel::KV(kv) | DataModel::KVOrdered(kv) => kv.get_value_tsymbol(),
             DataModel::KVExtListmap(kv) => kv.get_value_tsymbol(),
+            DataModel::Rowmap(rm) => rm.rows().get_key_tsymbol(),
+            DataModel::Hashmap(hm) => hm.maps().get_key_tsymbol(),
+            DataModel::Set(set) => set.get_key_tsymbol(),
+            DataModel::SortedSet(zset) => zset.get_key_tsymbol(),
+            DataModel::Queue(queue) => queue.get_key_tsymbol(),
         };
         let items: Vec<SharedSlice> = match table.get_model_ref() {
-            DataModel::KV(kv) => kv.get_inner_ref().get_keys(count),
-            DataModel::KVExtListmap(kv) => kv.get_inner_ref().get_keys(count),
+            DataModel::KV(kv) | DataModel::KVOrdered(kv) => kv.get_keys(count),
+            DataModel::KVExtListmap(kv) => kv.get_keys(count),
+            DataModel::Rowmap(rm) => rm.rows().get_keys(count),
+            DataModel::Hashmap(hm) => hm.maps().get_keys(count),
+            DataModel::Set(set) => set.get_keys(count),
+            DataModel::SortedSet(zset) => zset.get_keys(count),
+            DataModel::Queue(queue) => queue.get_keys(count),
         };
         con.write_typed_non_null_array_header(items.len(), tsymbol)
             .await?;
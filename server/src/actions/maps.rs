@@ -0,0 +1,170 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Map queries
+//! This module provides functions to work with the fields of maps in hashmap models
+
+use crate::{
+    corestore::{table::KVEMap, SharedSlice},
+    dbnet::prelude::*,
+    util::compiler,
+};
+
+action!(
+    /// Run an `HSET` query
+    ///
+    /// `HSET <key> <field> <value> ...` sets the given fields of the map at the key, creating
+    /// the map if it doesn't exist. Returns the number of fields that were added
+    fn hset(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len >= 3 && len % 2 == 1)?;
+        let hm = handle.get_table_with::<P, KVEMap>()?;
+        handle.ensure_room::<P>()?;
+        let key = unsafe {
+            // SAFETY: We have checked that there are atleast three args
+            act.next_unchecked()
+        };
+        let mut pairs = Vec::with_capacity(act.len() / 2);
+        while let (Some(field), Some(value)) = (act.next(), act.next()) {
+            pairs.push((SharedSlice::new(field), SharedSlice::new(value)));
+        }
+        let encoding_is_okay = hm.maps().is_key_ok(key)
+            && pairs
+                .iter()
+                .all(|(field, value)| hm.is_field_ok(field) && hm.maps().is_val_ok(value));
+        if compiler::unlikely(!encoding_is_okay) {
+            return util::err(P::RCODE_ENCODING_ERROR);
+        }
+        if !registry::state_okay() {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        let added =
+            handle.wal_log(|batch| hm.set_fields(key, &pairs, |pairs| batch.map_set(key, pairs)));
+        con.write_usize(added).await?;
+        Ok(())
+    }
+
+    /// Run an `HGET` query
+    ///
+    /// `HGET <key> <field>` returns the value of the field in the map at the key
+    fn hget(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len == 2)?;
+        let hm = handle.get_table_with::<P, KVEMap>()?;
+        let (key, field) = unsafe {
+            // SAFETY: We have checked that there are two args
+            (act.next_unchecked(), act.next_unchecked())
+        };
+        if compiler::unlikely(!(hm.maps().is_key_ok(key) && hm.is_field_ok(field))) {
+            return util::err(P::RCODE_ENCODING_ERROR);
+        }
+        match hm.get_field(key, field) {
+            Some(value) => {
+                let tsymbol = hm.maps().get_value_tsymbol();
+                con.write_mono_length_prefixed_with_tsymbol(&value, tsymbol)
+                    .await?
+            }
+            None => con._write_raw(P::RCODE_NIL).await?,
+        }
+        Ok(())
+    }
+
+    /// Run an `HDEL` query
+    ///
+    /// `HDEL <key> <field> ...` removes the given fields from the map at the key, removing the
+    /// map if it has no fields left. Returns the number of fields that were removed
+    fn hdel(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len >= 2)?;
+        let hm = handle.get_table_with::<P, KVEMap>()?;
+        let key = unsafe {
+            // SAFETY: We have checked that there are atleast two args
+            act.next_unchecked()
+        };
+        let fields: Vec<&[u8]> = act.collect();
+        let encoding_is_okay =
+            hm.maps().is_key_ok(key) && fields.iter().all(|field| hm.is_field_ok(field));
+        if compiler::unlikely(!encoding_is_okay) {
+            return util::err(P::RCODE_ENCODING_ERROR);
+        }
+        if !registry::state_okay() {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        let removed = handle
+            .wal_log(|batch| hm.remove_fields(key, &fields, |removed| batch.map_del(key, removed)));
+        con.write_usize(removed).await?;
+        Ok(())
+    }
+
+    /// Run an `HGETALL` query
+    ///
+    /// `HGETALL <key>` returns every field in the map at the key followed by its value, sorted
+    /// by field
+    fn hgetall(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len == 1)?;
+        let hm = handle.get_table_with::<P, KVEMap>()?;
+        let key = unsafe {
+            // SAFETY: We have checked that there is one arg
+            act.next_unchecked()
+        };
+        if compiler::unlikely(!hm.maps().is_key_ok(key)) {
+            return util::err(P::RCODE_ENCODING_ERROR);
+        }
+        let pairs = match hm.get_all(key) {
+            Some(pairs) => pairs,
+            None => return util::err(P::RCODE_NIL),
+        };
+        let tsymbol = if hm.get_field_tsymbol() == hm.maps().get_value_tsymbol() {
+            hm.get_field_tsymbol()
+        } else {
+            P::TSYMBOL_BINARY
+        };
+        con.write_typed_non_null_array_header(pairs.len() * 2, tsymbol)
+            .await?;
+        for (field, value) in pairs {
+            con.write_typed_non_null_array_element(&field).await?;
+            con.write_typed_non_null_array_element(&value).await?;
+        }
+        Ok(())
+    }
+
+    /// Run an `HLEN` query
+    ///
+    /// `HLEN <key>` returns the number of fields in the map at the key
+    fn hlen(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len == 1)?;
+        let hm = handle.get_table_with::<P, KVEMap>()?;
+        let key = unsafe {
+            // SAFETY: We have checked that there is one arg
+            act.next_unchecked()
+        };
+        if compiler::unlikely(!hm.maps().is_key_ok(key)) {
+            return util::err(P::RCODE_ENCODING_ERROR);
+        }
+        match hm.map_len(key) {
+            Some(len) => con.write_usize(len).await?,
+            None => con._write_raw(P::RCODE_NIL).await?,
+        }
+        Ok(())
+    }
+);
@@ -24,10 +24,7 @@
  *
 */
 
-use crate::{
-    dbnet::prelude::*, kvengine::encoding::ENCODING_LUT_ITER, queryengine::ActionIter,
-    util::compiler,
-};
+use crate::{dbnet::prelude::*, queryengine::ActionIter, util::compiler};
 
 action!(
     /// Run an `MGET` query
@@ -35,7 +32,7 @@ action!(
     fn mget(handle: &crate::corestore::Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
         ensure_length::<P>(act.len(), |size| size != 0)?;
         let kve = handle.get_table_with::<P, KVEBlob>()?;
-        let encoding_is_okay = ENCODING_LUT_ITER[kve.is_key_encoded()](act.as_ref());
+        let encoding_is_okay = kve.is_key_iter_ok(act.as_ref());
         if compiler::likely(encoding_is_okay) {
             con.write_typed_array_header(act.len(), kve.get_value_tsymbol())
                 .await?;
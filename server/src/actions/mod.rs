@@ -32,26 +32,42 @@
 
 #[macro_use]
 mod macros;
+pub mod binstr;
 pub mod dbsize;
 pub mod del;
 pub mod exists;
+pub mod expire;
 pub mod flushdb;
 pub mod get;
+pub mod incr;
 pub mod keylen;
 pub mod lists;
 pub mod lskeys;
+pub mod maps;
 pub mod mget;
 pub mod mpop;
 pub mod mset;
 pub mod mupdate;
+pub mod persist;
 pub mod pop;
+pub mod pubsub;
+pub mod queue;
+pub mod range;
+pub mod scan;
 pub mod set;
+pub mod sets;
 pub mod strong;
+pub mod ttl;
+pub mod txn;
 pub mod update;
 pub mod uset;
+pub mod watch;
 pub mod whereami;
 use {
-    crate::{corestore::memstore::DdlError, protocol::interface::ProtocolSpec, util},
+    crate::{
+        auth::grants::Access, corestore::memstore::DdlError, protocol::interface::ProtocolSpec,
+        registry, util,
+    },
     std::io::Error as IoError,
 };
 
@@ -128,6 +144,17 @@ pub fn ensure_boolean_or_aerr<P: ProtocolSpec>(boolean: bool) -> ActionResult<()
     }
 }
 
+/// Followers only run queries that need no more than read access
+pub fn ensure_writable<P: ProtocolSpec>(access: Access) -> ActionResult<()> {
+    if util::compiler::likely(!registry::is_follower())
+        || matches!(access, Access::Any | Access::Read)
+    {
+        Ok(())
+    } else {
+        util::err(P::RSTRING_READ_ONLY_FOLLOWER)
+    }
+}
+
 pub mod heya {
     //! Respond to `HEYA` queries
     use crate::dbnet::prelude::*;
@@ -24,10 +24,7 @@
  *
 */
 
-use crate::{
-    corestore, dbnet::prelude::*, kvengine::encoding::ENCODING_LUT_ITER,
-    queryengine::ActionIter, util::compiler,
-};
+use crate::{corestore, dbnet::prelude::*, queryengine::ActionIter, util::compiler};
 
 action!(
     /// Run an MPOP action
@@ -35,12 +32,22 @@ action!(
         ensure_length::<P>(act.len(), |len| len != 0)?;
         if registry::state_okay() {
             let kve = handle.get_table_with::<P, KVEBlob>()?;
-            let encoding_is_okay = ENCODING_LUT_ITER[kve.is_key_encoded()](act.as_ref());
+            let encoding_is_okay = kve.is_key_iter_ok(act.as_ref());
             if compiler::likely(encoding_is_okay) {
-                con.write_typed_array_header(act.len(), kve.get_value_tsymbol())
+                let popped: Vec<_> = handle.wal_log(|batch| {
+                    act.map(|key| {
+                        let popped = kve.pop_unchecked(key);
+                        if popped.is_some() {
+                            batch.del(key);
+                        }
+                        popped
+                    })
+                    .collect()
+                });
+                con.write_typed_array_header(popped.len(), kve.get_value_tsymbol())
                     .await?;
-                for key in act {
-                    match kve.pop_unchecked(key) {
+                for val in popped {
+                    match val {
                         Some(val) => con.write_typed_array_element(&val).await?,
                         None => con.write_typed_array_element_null().await?,
                     }
@@ -24,10 +24,7 @@
  *
 */
 
-use crate::{
-    corestore::SharedSlice, dbnet::prelude::*,
-    kvengine::encoding::ENCODING_LUT_ITER_PAIR, util::compiler,
-};
+use crate::{corestore::SharedSlice, dbnet::prelude::*, util::compiler};
 
 action!(
     /// Run an `MSET` query
@@ -35,14 +32,19 @@ action!(
         let howmany = act.len();
         ensure_length::<P>(howmany, |size| size & 1 == 0 && size != 0)?;
         let kve = handle.get_table_with::<P, KVEBlob>()?;
-        let encoding_is_okay = ENCODING_LUT_ITER_PAIR[kve.get_encoding_tuple()](&act);
+        handle.ensure_room::<P>()?;
+        let encoding_is_okay = kve.is_pair_iter_ok(&act);
         if compiler::likely(encoding_is_okay) {
             let done_howmany: Option<usize> = if registry::state_okay() {
                 let mut didmany = 0;
-                while let (Some(key), Some(val)) = (act.next(), act.next()) {
-                    didmany +=
-                        kve.set_unchecked(SharedSlice::new(key), SharedSlice::new(val)) as usize;
-                }
+                handle.wal_log(|batch| {
+                    while let (Some(key), Some(val)) = (act.next(), act.next()) {
+                        if kve.set_unchecked(SharedSlice::new(key), SharedSlice::new(val)) {
+                            didmany += 1;
+                            batch.kv(kve, key);
+                        }
+                    }
+                });
                 Some(didmany)
             } else {
                 None
@@ -24,10 +24,7 @@
  *
 */
 
-use crate::{
-    corestore::SharedSlice, dbnet::prelude::*,
-    kvengine::encoding::ENCODING_LUT_ITER_PAIR, util::compiler,
-};
+use crate::{corestore::SharedSlice, dbnet::prelude::*, util::compiler};
 
 action!(
     /// Run an `MUPDATE` query
@@ -35,15 +32,20 @@ action!(
         let howmany = act.len();
         ensure_length::<P>(howmany, |size| size & 1 == 0 && size != 0)?;
         let kve = handle.get_table_with::<P, KVEBlob>()?;
-        let encoding_is_okay = ENCODING_LUT_ITER_PAIR[kve.get_encoding_tuple()](&act);
+        handle.ensure_room::<P>()?;
+        let encoding_is_okay = kve.is_pair_iter_ok(&act);
         let done_howmany: Option<usize>;
         if compiler::likely(encoding_is_okay) {
             if registry::state_okay() {
                 let mut didmany = 0;
-                while let (Some(key), Some(val)) = (act.next(), act.next()) {
-                    didmany +=
-                        kve.update_unchecked(SharedSlice::new(key), SharedSlice::new(val)) as usize;
-                }
+                handle.wal_log(|batch| {
+                    while let (Some(key), Some(val)) = (act.next(), act.next()) {
+                        if kve.update_unchecked(SharedSlice::new(key), SharedSlice::new(val)) {
+                            didmany += 1;
+                            batch.kv(kve, key);
+                        }
+                    }
+                });
                 done_howmany = Some(didmany);
             } else {
                 done_howmany = None;
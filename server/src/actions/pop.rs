@@ -35,7 +35,14 @@ action! {
         };
         if registry::state_okay() {
             let kve = handle.get_table_with::<P, KVEBlob>()?;
-            match kve.pop(key) {
+            let popped = handle.wal_log(|batch| {
+                let popped = kve.pop(key);
+                if let Ok(Some(_)) = popped {
+                    batch.del(key);
+                }
+                popped
+            });
+            match popped {
                 Ok(Some(val)) => con.write_mono_length_prefixed_with_tsymbol(
                     &val, kve.get_value_tsymbol()
                 ).await?,
@@ -0,0 +1,75 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Pub/Sub queries
+//!
+//! A connection that subscribes to a channel switches into push mode: every message published to
+//! any of its channels is pushed to it as a typed array of `message`, the channel and the message.
+//! In push mode, the connection can only run `SUBSCRIBE`, `UNSUBSCRIBE`, `WATCH` and `UNWATCH` and
+//! it switches back once it has left all channels and stopped watching all keys
+
+use crate::{dbnet::prelude::*, pubsub::Kind};
+
+action!(
+    /// Run a `SUBSCRIBE` query, returning the number of channels the connection is subscribed to
+    fn subscribe(handle: &Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len != 0)?;
+        let pubsub = handle.get_pubsub();
+        let subscriptions = con.subscriptions_mut();
+        act.for_each(|channel| subscriptions.subscribe(Kind::Channel, pubsub, channel));
+        let count = subscriptions.count(Kind::Channel);
+        con.write_usize(count).await?;
+        Ok(())
+    }
+    /// Run an `UNSUBSCRIBE` query, returning the number of channels the connection is still
+    /// subscribed to. Without any arguments, the connection leaves all channels
+    fn unsubscribe(handle: &Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
+        let pubsub = handle.get_pubsub();
+        let subscriptions = con.subscriptions_mut();
+        if act.is_empty() {
+            subscriptions.unsubscribe_all(Kind::Channel);
+        } else {
+            act.for_each(|channel| subscriptions.unsubscribe(pubsub, channel));
+        }
+        let count = subscriptions.count(Kind::Channel);
+        con.write_usize(count).await?;
+        Ok(())
+    }
+);
+
+action!(
+    /// Run a `PUBLISH` query, returning the number of connections that will receive the message
+    fn publish(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len == 2)?;
+        let (channel, message) = unsafe {
+            // UNSAFE(@ohsayan): We just checked the length
+            (act.next_unchecked(), act.next_unchecked())
+        };
+        let count = handle.get_pubsub().publish(channel, message);
+        con.write_usize(count).await?;
+        Ok(())
+    }
+);
@@ -0,0 +1,201 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Queue queries
+//! This module provides functions to work with reliable queues. An item is first reserved,
+//! which hands it out without removing it, and is then either acknowledged (`QACK`) once it
+//! has been dealt with or given back (`QNACK`). An item whose reservation runs out goes back
+//! on the queue
+
+use crate::{
+    actions::ActionResult,
+    corestore::{table::KVEQueue, SharedSlice},
+    dbnet::{prelude::*, BufferedSocketStream},
+    kvengine::queue::{KVEQueuemap, Queue, DEFAULT_VISIBILITY_TIMEOUT},
+    util::compiler,
+};
+
+const RESERVED: &[u8] = "RESERVED".as_bytes();
+
+/// Parse a reservation ID or a timeout
+fn parse_u64(bytes: &[u8]) -> Option<u64> {
+    String::from_utf8_lossy(bytes).parse().ok()
+}
+
+action!(
+    /// Run a `QPUSH` query
+    ///
+    /// `QPUSH <key> <item> ...` appends the given items to the queue at the key, creating the
+    /// queue if it doesn't exist. Returns the number of items waiting to be reserved
+    fn qpush(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len >= 2)?;
+        let queues = handle.get_table_with::<P, KVEQueue>()?;
+        handle.ensure_room::<P>()?;
+        let key = unsafe {
+            // SAFETY: We have checked that there are atleast two args
+            act.next_unchecked()
+        };
+        let items: Vec<SharedSlice> = act.map(SharedSlice::new).collect();
+        let encoding_is_okay =
+            queues.is_key_ok(key) && items.iter().all(|item| queues.is_val_ok(item));
+        if compiler::unlikely(!encoding_is_okay) {
+            return util::err(P::RCODE_ENCODING_ERROR);
+        }
+        if !registry::state_okay() {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        let waiting =
+            handle.wal_log(|batch| queues.push_items(key, &items, |queue| batch.queue(key, queue)));
+        con.write_usize(waiting).await?;
+        Ok(())
+    }
+
+    /// Run a `QRESERVE` query
+    ///
+    /// `QRESERVE <key> [<seconds>]` reserves the first item of the queue at the key and
+    /// returns the reservation ID followed by the item. The item goes back on the queue if it
+    /// isn't acknowledged within the given number of seconds (30 by default)
+    fn qreserve(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len == 1 || len == 2)?;
+        let queues = handle.get_table_with::<P, KVEQueue>()?;
+        let key = unsafe {
+            // SAFETY: We have checked that there is atleast one arg
+            act.next_unchecked()
+        };
+        let timeout = match act.next().map(parse_u64) {
+            None => DEFAULT_VISIBILITY_TIMEOUT,
+            Some(Some(timeout)) if timeout != 0 => timeout,
+            Some(_) => return util::err(P::RCODE_WRONGTYPE_ERR),
+        };
+        if compiler::unlikely(!queues.is_key_ok(key)) {
+            return util::err(P::RCODE_ENCODING_ERROR);
+        }
+        if !registry::state_okay() {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        let reserved =
+            handle.wal_log(|batch| queues.reserve(key, timeout, |queue| batch.queue(key, queue)));
+        match reserved {
+            Some((id, item)) => {
+                let id = id.to_string();
+                let body: [&[u8]; 2] = [id.as_bytes(), &item];
+                con.write_typed_non_null_array(body, queues.get_value_tsymbol())
+                    .await?
+            }
+            None => con._write_raw(P::RCODE_NIL).await?,
+        }
+        Ok(())
+    }
+
+    /// Run a `QACK` query
+    ///
+    /// `QACK <key> <id>` acknowledges a reservation, removing its item from the queue at the
+    /// key for good. Returns nil if there is no such reservation (or it ran out)
+    fn qack(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        let queues = handle.get_table_with::<P, KVEQueue>()?;
+        settle(handle, con, &mut act, queues, false).await
+    }
+
+    /// Run a `QNACK` query
+    ///
+    /// `QNACK <key> <id>` gives up a reservation, putting its item back at the front of the
+    /// queue at the key. Returns nil if there is no such reservation (or it ran out)
+    fn qnack(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        let queues = handle.get_table_with::<P, KVEQueue>()?;
+        settle(handle, con, &mut act, queues, true).await
+    }
+
+    /// Run a `QLEN` query
+    ///
+    /// `QLEN <key>` returns the number of items waiting to be reserved in the queue at the
+    /// key. `QLEN <key> RESERVED` returns the number of reserved items instead
+    fn qlen(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len == 1 || len == 2)?;
+        let queues = handle.get_table_with::<P, KVEQueue>()?;
+        let key = unsafe {
+            // SAFETY: We have checked that there is atleast one arg
+            act.next_unchecked()
+        };
+        let reserved = match act.next_uppercase() {
+            Some(option) if option.as_ref() == RESERVED => true,
+            Some(_) => return util::err(P::RCODE_ACTION_ERR),
+            None => false,
+        };
+        if compiler::unlikely(!queues.is_key_ok(key)) {
+            return util::err(P::RCODE_ENCODING_ERROR);
+        }
+        match queues.counts(key) {
+            Some((_, count)) if reserved => con.write_usize(count).await?,
+            Some((count, _)) => con.write_usize(count).await?,
+            None => con._write_raw(P::RCODE_NIL).await?,
+        }
+        Ok(())
+    }
+);
+
+/// Acknowledge the reservation named by the query (`<key> <id>`), or give it up if `nack` is
+/// set
+async fn settle<'a, C, P>(
+    handle: &Corestore,
+    con: &mut Connection<C, P>,
+    act: &mut ActionIter<'a>,
+    queues: &KVEQueuemap,
+    nack: bool,
+) -> ActionResult<()>
+where
+    C: BufferedSocketStream,
+    P: ProtocolSpec,
+{
+    ensure_length::<P>(act.len(), |len| len == 2)?;
+    let (key, id) = unsafe {
+        // SAFETY: We have checked that there are two args
+        (act.next_unchecked(), act.next_unchecked())
+    };
+    let id = match parse_u64(id) {
+        Some(id) => id,
+        None => return util::err(P::RCODE_WRONGTYPE_ERR),
+    };
+    if compiler::unlikely(!queues.is_key_ok(key)) {
+        return util::err(P::RCODE_ENCODING_ERROR);
+    }
+    if !registry::state_okay() {
+        return util::err(P::RCODE_SERVER_ERR);
+    }
+    let settled = handle.wal_log(|batch| {
+        let log = |queue: &Queue| batch.queue(key, queue);
+        if nack {
+            queues.nack(key, id, log)
+        } else {
+            queues.ack(key, id, log)
+        }
+    });
+    if settled {
+        con._write_raw(P::RCODE_OKAY).await?;
+    } else {
+        con._write_raw(P::RCODE_NIL).await?;
+    }
+    Ok(())
+}
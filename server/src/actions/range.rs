@@ -0,0 +1,109 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # `RANGE` queries
+//! This module provides functions to query ranges of keys in ordered models
+
+use {
+    crate::{corestore::table::KVEOrdered, dbnet::prelude::*},
+    core::ops::Bound,
+};
+
+const PREFIX: &[u8] = "PREFIX".as_bytes();
+const LIMIT: &[u8] = "LIMIT".as_bytes();
+const REV: &[u8] = "REV".as_bytes();
+const WITHVALUES: &[u8] = "WITHVALUES".as_bytes();
+
+action!(
+    /// Run a `RANGE` query on an ordered model
+    ///
+    /// - `RANGE <min> <max>` returns the keys between `min` and `max` in order, where each is
+    /// `[key` (inclusive), `(key` (exclusive), `-` (from the first key) or `+` (to the last key)
+    /// - `RANGE PREFIX <prefix>` returns the keys that start with `prefix` in order
+    ///
+    /// Either can be followed by `LIMIT <n>` to return atmost `n` keys, `REV` to go from the last
+    /// key to the first and `WITHVALUES` to return every key followed by its value
+    fn range(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len >= 2)?;
+        let kve = handle.get_table_with::<P, KVEOrdered>()?;
+        let (first, second) = unsafe {
+            // SAFETY: We have checked that there are atleast two args
+            (act.next_unchecked(), act.next_unchecked())
+        };
+        let mut limit = usize::MAX;
+        let mut reverse = false;
+        let mut with_values = false;
+        while let Some(option) = act.next_uppercase() {
+            match option.as_ref() {
+                LIMIT => match act.next().map(|n| String::from_utf8_lossy(n).parse()) {
+                    Some(Ok(n)) => limit = n,
+                    Some(Err(_)) => return util::err(P::RCODE_WRONGTYPE_ERR),
+                    None => return util::err(P::RCODE_ACTION_ERR),
+                },
+                REV => reverse = true,
+                WITHVALUES => with_values = true,
+                _ => return util::err(P::RCODE_ACTION_ERR),
+            }
+        }
+        let items = if first.eq_ignore_ascii_case(PREFIX) {
+            kve.prefix(second, limit, reverse)
+        } else {
+            match (parse_bound(first, b'-'), parse_bound(second, b'+')) {
+                (Some(start), Some(end)) => kve.range((start, end), limit, reverse),
+                _ => return util::err(P::RCODE_ACTION_ERR),
+            }
+        };
+        let tsymbol = if with_values && kve.get_key_tsymbol() != kve.get_value_tsymbol() {
+            P::TSYMBOL_BINARY
+        } else {
+            kve.get_key_tsymbol()
+        };
+        let len = if with_values {
+            items.len() * 2
+        } else {
+            items.len()
+        };
+        con.write_typed_non_null_array_header(len, tsymbol).await?;
+        for (key, value) in items {
+            con.write_typed_non_null_array_element(&key).await?;
+            if with_values {
+                con.write_typed_non_null_array_element(&value).await?;
+            }
+        }
+        Ok(())
+    }
+);
+
+/// Parse one end of a range: `[key` includes the key and `(key` excludes it, while the
+/// `unbounded` byte (`-` for the start and `+` for the end) leaves that end open
+fn parse_bound(bound: &[u8], unbounded: u8) -> Option<Bound<&[u8]>> {
+    match bound {
+        [b'[', key @ ..] => Some(Bound::Included(key)),
+        [b'(', key @ ..] => Some(Bound::Excluded(key)),
+        [byte] if *byte == unbounded => Some(Bound::Unbounded),
+        _ => None,
+    }
+}
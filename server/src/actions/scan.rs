@@ -0,0 +1,99 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # `SCAN` queries
+//! This module provides functions to iterate over all the keys in a table, a few at a time
+
+use crate::{
+    corestore::{table::DataModel, SharedSlice},
+    dbnet::prelude::*,
+    util::glob,
+};
+
+const MATCH: &[u8] = "MATCH".as_bytes();
+const COUNT: &[u8] = "COUNT".as_bytes();
+const DEFAULT_COUNT: usize = 10;
+
+action!(
+    /// Run a `SCAN` query
+    ///
+    /// `SCAN <cursor> [MATCH <glob>] [COUNT <n>]` returns the next cursor followed by the keys
+    /// found at roughly the next `n` positions that match the glob. Scanning starts with
+    /// cursor `0` and is complete once the returned cursor is `0` again
+    fn scan(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len == 1 || len == 3 || len == 5)?;
+        let cursor = match String::from_utf8_lossy(unsafe { act.next_unchecked() }).parse::<u64>() {
+            Ok(cursor) => cursor,
+            Err(_) => return util::err(P::RCODE_WRONGTYPE_ERR),
+        };
+        let mut pattern = None;
+        let mut count = DEFAULT_COUNT;
+        while let Some(option) = act.next_uppercase() {
+            let value = unsafe {
+                // SAFETY: We have checked that options always come with a value
+                act.next_unchecked()
+            };
+            match option.as_ref() {
+                MATCH => pattern = Some(value),
+                COUNT => match String::from_utf8_lossy(value).parse::<usize>() {
+                    Ok(n) if n != 0 => count = n,
+                    _ => return util::err(P::RCODE_WRONGTYPE_ERR),
+                },
+                _ => return util::err(P::RCODE_ACTION_ERR),
+            }
+        }
+        let table = get_tbl!(handle, con);
+        let (next, mut keys): (u64, Vec<SharedSlice>) = match table.get_model_ref() {
+            DataModel::KV(kv) | DataModel::KVOrdered(kv) => kv.scan(cursor, count),
+            DataModel::KVExtListmap(kv) => kv.scan(cursor, count),
+            DataModel::Rowmap(rm) => rm.rows().scan(cursor, count),
+            DataModel::Hashmap(hm) => hm.maps().scan(cursor, count),
+            DataModel::Set(set) => set.scan(cursor, count),
+            DataModel::SortedSet(zset) => zset.scan(cursor, count),
+            DataModel::Queue(queue) => queue.scan(cursor, count),
+        };
+        let tsymbol = match table.get_model_ref() {
+            DataModel::KV(kv) | DataModel::KVOrdered(kv) => kv.get_key_tsymbol(),
+            DataModel::KVExtListmap(kv) => kv.get_key_tsymbol(),
+            DataModel::Rowmap(rm) => rm.rows().get_key_tsymbol(),
+            DataModel::Hashmap(hm) => hm.maps().get_key_tsymbol(),
+            DataModel::Set(set) => set.get_key_tsymbol(),
+            DataModel::SortedSet(zset) => zset.get_key_tsymbol(),
+            DataModel::Queue(queue) => queue.get_key_tsymbol(),
+        };
+        if let Some(pattern) = pattern {
+            keys.retain(|key| glob::matches(pattern, key));
+        }
+        con.write_typed_non_null_array_header(keys.len() + 1, tsymbol)
+            .await?;
+        con.write_typed_non_null_array_element(next.to_string().as_bytes())
+            .await?;
+        for key in keys {
+            con.write_typed_non_null_array_element(&key).await?;
+        }
+        Ok(())
+    }
+);
@@ -29,21 +29,50 @@
 
 use crate::{corestore::SharedSlice, dbnet::prelude::*, queryengine::ActionIter};
 
+const EX: &[u8] = "EX".as_bytes();
+
 action!(
     /// Run a `SET` query
+    ///
+    /// `SET <key> <value> [EX <seconds>]`
     fn set(handle: &crate::corestore::Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
-        ensure_length::<P>(act.len(), |len| len == 2)?;
+        ensure_length::<P>(act.len(), |len| len == 2 || len == 4)?;
         if registry::state_okay() {
             let did_we = {
                 let writer = handle.get_table_with::<P, KVEBlob>()?;
-                match unsafe {
+                handle.ensure_room::<P>()?;
+                let (key, value) = unsafe {
                     // UNSAFE(@ohsayan): This is completely safe as we've already checked
-                    // that there are exactly 2 arguments
-                    writer.set(
+                    // that there are atleast 2 arguments
+                    (
                         SharedSlice::new(act.next().unsafe_unwrap()),
                         SharedSlice::new(act.next().unsafe_unwrap()),
                     )
-                } {
+                };
+                let ttl = if act.is_empty() {
+                    None
+                } else {
+                    let (ex, secs) = unsafe {
+                        // UNSAFE(@ohsayan): We checked that we have exactly 4 arguments
+                        (act.next_uppercase_unchecked(), act.next_unchecked())
+                    };
+                    ensure_boolean_or_aerr::<P>(ex.as_ref() == EX)?;
+                    match String::from_utf8_lossy(secs).parse::<u64>() {
+                        Ok(secs) => Some(secs),
+                        Err(_) => return util::err(P::RCODE_WRONGTYPE_ERR),
+                    }
+                };
+                let ret = handle.wal_log(|batch| {
+                    let ret = match ttl {
+                        Some(secs) => writer.set_with_ttl(key.clone(), value, secs),
+                        None => writer.set(key.clone(), value),
+                    };
+                    if let Ok(true) = ret {
+                        batch.kv(writer, &key);
+                    }
+                    ret
+                });
+                match ret {
                     Ok(true) => Some(true),
                     Ok(false) => Some(false),
                     Err(()) => None,
@@ -0,0 +1,370 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Set queries
+//! This module provides functions to work with the members of sets and sorted sets
+
+use {
+    crate::{
+        actions::ActionResult,
+        corestore::{
+            table::{KVESet, KVESortedSet},
+            SharedSlice,
+        },
+        dbnet::{prelude::*, BufferedSocketStream},
+        kvengine::{
+            sets::{Score, SetLike},
+            KVEngine,
+        },
+        util::compiler,
+    },
+    core::ops::Bound,
+    parking_lot::RwLock,
+};
+
+const LIMIT: &[u8] = "LIMIT".as_bytes();
+const REV: &[u8] = "REV".as_bytes();
+const WITHSCORES: &[u8] = "WITHSCORES".as_bytes();
+
+action!(
+    /// Run an `SADD` query
+    ///
+    /// `SADD <key> <member> ...` adds the given members to the set at the key, creating the set
+    /// if it doesn't exist. Returns the number of members that were added
+    fn sadd(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len >= 2)?;
+        let sets = handle.get_table_with::<P, KVESet>()?;
+        handle.ensure_room::<P>()?;
+        let key = unsafe {
+            // SAFETY: We have checked that there are atleast two args
+            act.next_unchecked()
+        };
+        let members: Vec<SharedSlice> = act.map(SharedSlice::new).collect();
+        let encoding_is_okay =
+            sets.is_key_ok(key) && members.iter().all(|member| sets.is_val_ok(member));
+        if compiler::unlikely(!encoding_is_okay) {
+            return util::err(P::RCODE_ENCODING_ERROR);
+        }
+        if !registry::state_okay() {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        let added = handle
+            .wal_log(|batch| sets.add_members(key, &members, |added| batch.set_add(key, added)));
+        con.write_usize(added).await?;
+        Ok(())
+    }
+
+    /// Run an `SREM` query
+    ///
+    /// `SREM <key> <member> ...` removes the given members from the set at the key, removing
+    /// the set if it has no members left. Returns the number of members that were removed
+    fn srem(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        let sets = handle.get_table_with::<P, KVESet>()?;
+        remove_members(handle, con, &mut act, sets).await
+    }
+
+    /// Run an `SISMEMBER` query
+    ///
+    /// `SISMEMBER <key> <member>` returns 1 if the member is in the set at the key and 0
+    /// otherwise
+    fn sismember(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len == 2)?;
+        let sets = handle.get_table_with::<P, KVESet>()?;
+        let (key, member) = unsafe {
+            // SAFETY: We have checked that there are two args
+            (act.next_unchecked(), act.next_unchecked())
+        };
+        if compiler::unlikely(!(sets.is_key_ok(key) && sets.is_val_ok(member))) {
+            return util::err(P::RCODE_ENCODING_ERROR);
+        }
+        con.write_usize(sets.is_member(key, member) as usize)
+            .await?;
+        Ok(())
+    }
+
+    /// Run an `SCARD` query
+    ///
+    /// `SCARD <key>` returns the number of members in the set at the key
+    fn scard(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        let sets = handle.get_table_with::<P, KVESet>()?;
+        cardinality(con, &mut act, sets).await
+    }
+
+    /// Run an `SMEMBERS` query
+    ///
+    /// `SMEMBERS <key>` returns the members of the set at the key, in byte order
+    fn smembers(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len == 1)?;
+        let sets = handle.get_table_with::<P, KVESet>()?;
+        let key = unsafe {
+            // SAFETY: We have checked that there is one arg
+            act.next_unchecked()
+        };
+        if compiler::unlikely(!sets.is_key_ok(key)) {
+            return util::err(P::RCODE_ENCODING_ERROR);
+        }
+        match sets.members(key) {
+            Some(members) => {
+                con.write_typed_non_null_array(&members, sets.get_value_tsymbol())
+                    .await?
+            }
+            None => con._write_raw(P::RCODE_NIL).await?,
+        }
+        Ok(())
+    }
+
+    /// Run a `ZADD` query
+    ///
+    /// `ZADD <key> <score> <member> ...` sets the scores of the given members in the sorted set
+    /// at the key, adding the members that aren't in it and creating the set if it doesn't
+    /// exist. Returns the number of members that were added
+    fn zadd(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len >= 3 && len % 2 == 1)?;
+        let zsets = handle.get_table_with::<P, KVESortedSet>()?;
+        handle.ensure_room::<P>()?;
+        let key = unsafe {
+            // SAFETY: We have checked that there are atleast three args
+            act.next_unchecked()
+        };
+        let mut members = Vec::with_capacity(act.len() / 2);
+        while let (Some(score), Some(member)) = (act.next(), act.next()) {
+            match Score::parse(score) {
+                Some(score) => members.push((SharedSlice::new(member), score)),
+                None => return util::err(P::RCODE_WRONGTYPE_ERR),
+            }
+        }
+        let encoding_is_okay =
+            zsets.is_key_ok(key) && members.iter().all(|(member, _)| zsets.is_val_ok(member));
+        if compiler::unlikely(!encoding_is_okay) {
+            return util::err(P::RCODE_ENCODING_ERROR);
+        }
+        if !registry::state_okay() {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        let added = handle.wal_log(|batch| {
+            zsets.add_scored(key, &members, |members| batch.zset_add(key, members))
+        });
+        con.write_usize(added).await?;
+        Ok(())
+    }
+
+    /// Run a `ZREM` query
+    ///
+    /// `ZREM <key> <member> ...` removes the given members from the sorted set at the key,
+    /// removing the set if it has no members left. Returns the number of members that were
+    /// removed
+    fn zrem(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        let zsets = handle.get_table_with::<P, KVESortedSet>()?;
+        remove_members(handle, con, &mut act, zsets).await
+    }
+
+    /// Run a `ZSCORE` query
+    ///
+    /// `ZSCORE <key> <member>` returns the score of the member in the sorted set at the key
+    fn zscore(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len == 2)?;
+        let zsets = handle.get_table_with::<P, KVESortedSet>()?;
+        let (key, member) = unsafe {
+            // SAFETY: We have checked that there are two args
+            (act.next_unchecked(), act.next_unchecked())
+        };
+        if compiler::unlikely(!(zsets.is_key_ok(key) && zsets.is_val_ok(member))) {
+            return util::err(P::RCODE_ENCODING_ERROR);
+        }
+        match zsets.score(key, member) {
+            Some(score) => con.write_string(&score.to_string()).await?,
+            None => con._write_raw(P::RCODE_NIL).await?,
+        }
+        Ok(())
+    }
+
+    /// Run a `ZCARD` query
+    ///
+    /// `ZCARD <key>` returns the number of members in the sorted set at the key
+    fn zcard(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        let zsets = handle.get_table_with::<P, KVESortedSet>()?;
+        cardinality(con, &mut act, zsets).await
+    }
+
+    /// Run a `ZRANK` query
+    ///
+    /// `ZRANK <key> <member>` returns the position of the member in the sorted set at the key,
+    /// starting from 0 for the lowest score. With `REV`, the position starts from the highest
+    /// score instead
+    fn zrank(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len == 2 || len == 3)?;
+        let zsets = handle.get_table_with::<P, KVESortedSet>()?;
+        let (key, member) = unsafe {
+            // SAFETY: We have checked that there are atleast two args
+            (act.next_unchecked(), act.next_unchecked())
+        };
+        let reverse = match act.next_uppercase() {
+            Some(option) if option.as_ref() == REV => true,
+            Some(_) => return util::err(P::RCODE_ACTION_ERR),
+            None => false,
+        };
+        if compiler::unlikely(!(zsets.is_key_ok(key) && zsets.is_val_ok(member))) {
+            return util::err(P::RCODE_ENCODING_ERROR);
+        }
+        match zsets.rank(key, member, reverse) {
+            Some(rank) => con.write_usize(rank).await?,
+            None => con._write_raw(P::RCODE_NIL).await?,
+        }
+        Ok(())
+    }
+
+    /// Run a `ZRANGEBYSCORE` query
+    ///
+    /// `ZRANGEBYSCORE <key> <min> <max>` returns the members of the sorted set at the key whose
+    /// scores are between `min` and `max`, ordered by score. Each of `min` and `max` is
+    /// `[score` (inclusive), `(score` (exclusive), `-` (no lower bound) or `+` (no upper bound)
+    ///
+    /// This can be followed by `LIMIT <n>` to return atmost `n` members, `REV` to go from the
+    /// highest score to the lowest and `WITHSCORES` to return every member followed by its score
+    fn zrangebyscore(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len >= 3)?;
+        let zsets = handle.get_table_with::<P, KVESortedSet>()?;
+        let (key, min, max) = unsafe {
+            // SAFETY: We have checked that there are atleast three args
+            (
+                act.next_unchecked(),
+                act.next_unchecked(),
+                act.next_unchecked(),
+            )
+        };
+        let mut limit = usize::MAX;
+        let mut reverse = false;
+        let mut with_scores = false;
+        while let Some(option) = act.next_uppercase() {
+            match option.as_ref() {
+                LIMIT => match act.next().map(|n| String::from_utf8_lossy(n).parse()) {
+                    Some(Ok(n)) => limit = n,
+                    Some(Err(_)) => return util::err(P::RCODE_WRONGTYPE_ERR),
+                    None => return util::err(P::RCODE_ACTION_ERR),
+                },
+                REV => reverse = true,
+                WITHSCORES => with_scores = true,
+                _ => return util::err(P::RCODE_ACTION_ERR),
+            }
+        }
+        let range = match (parse_score_bound(min, b'-'), parse_score_bound(max, b'+')) {
+            (Some(start), Some(end)) => (start, end),
+            _ => return util::err(P::RCODE_ACTION_ERR),
+        };
+        if compiler::unlikely(!zsets.is_key_ok(key)) {
+            return util::err(P::RCODE_ENCODING_ERROR);
+        }
+        let members = match zsets.range_by_score(key, range, limit, reverse) {
+            Some(members) => members,
+            None => return util::err(P::RCODE_NIL),
+        };
+        // scores are valid UTF-8, so they can go wherever the members go
+        let len = if with_scores {
+            members.len() * 2
+        } else {
+            members.len()
+        };
+        con.write_typed_non_null_array_header(len, zsets.get_value_tsymbol())
+            .await?;
+        for (member, score) in members {
+            con.write_typed_non_null_array_element(&member).await?;
+            if with_scores {
+                con.write_typed_non_null_array_element(score.to_string().as_bytes())
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+);
+
+/// Remove the members in `act` (after the key) from the set at the key, for `SREM` and `ZREM`
+async fn remove_members<'a, C, P, T>(
+    handle: &Corestore,
+    con: &mut Connection<C, P>,
+    act: &mut ActionIter<'a>,
+    sets: &KVEngine<RwLock<T>>,
+) -> ActionResult<()>
+where
+    C: BufferedSocketStream,
+    P: ProtocolSpec,
+    T: SetLike,
+{
+    ensure_length::<P>(act.len(), |len| len >= 2)?;
+    let key = unsafe {
+        // SAFETY: We have checked that there are atleast two args
+        act.next_unchecked()
+    };
+    let members: Vec<&[u8]> = act.collect();
+    let encoding_is_okay =
+        sets.is_key_ok(key) && members.iter().all(|member| sets.is_val_ok(member));
+    if compiler::unlikely(!encoding_is_okay) {
+        return util::err(P::RCODE_ENCODING_ERROR);
+    }
+    if !registry::state_okay() {
+        return util::err(P::RCODE_SERVER_ERR);
+    }
+    let removed = handle
+        .wal_log(|batch| sets.remove_members(key, &members, |removed| batch.set_del(key, removed)));
+    con.write_usize(removed).await?;
+    Ok(())
+}
+
+/// Write the number of members in the set at the key in `act`, for `SCARD` and `ZCARD`
+async fn cardinality<'a, C, P, T>(
+    con: &mut Connection<C, P>,
+    act: &mut ActionIter<'a>,
+    sets: &KVEngine<RwLock<T>>,
+) -> ActionResult<()>
+where
+    C: BufferedSocketStream,
+    P: ProtocolSpec,
+    T: SetLike,
+{
+    ensure_length::<P>(act.len(), |len| len == 1)?;
+    let key = unsafe {
+        // SAFETY: We have checked that there is one arg
+        act.next_unchecked()
+    };
+    if compiler::unlikely(!sets.is_key_ok(key)) {
+        return util::err(P::RCODE_ENCODING_ERROR);
+    }
+    match sets.set_len(key) {
+        Some(len) => con.write_usize(len).await?,
+        None => con._write_raw(P::RCODE_NIL).await?,
+    }
+    Ok(())
+}
+
+/// Parse one end of a range of scores: `[score` includes the score and `(score` excludes it,
+/// while the `unbounded` byte (`-` for the start and `+` for the end) leaves that end open
+fn parse_score_bound(bound: &[u8], unbounded: u8) -> Option<Bound<Score>> {
+    match bound {
+        [b'[', score @ ..] => Score::parse(score).map(Bound::Included),
+        [b'(', score @ ..] => Score::parse(score).map(Bound::Excluded),
+        [byte] if *byte == unbounded => Some(Bound::Unbounded),
+        _ => None,
+    }
+}
@@ -30,6 +30,7 @@ use {
         dbnet::prelude::*,
         kvengine::{KVEStandard, SingleEncoder},
         protocol::iter::DerefUnsafeSlice,
+        pubsub::KeyEvent,
         util::compiler,
     },
     core::slice::Iter,
@@ -46,11 +47,20 @@ action! {
         if registry::state_okay() {
             // guarantee one check: consistency
             let key_encoder = kve.get_key_encoder();
-            let outcome = unsafe {
+            let args = unsafe {
                 // UNSAFE(@ohsayan): The lifetime of `act` ensures that the
                 // pointers are still valid
-                self::snapshot_and_del(kve, key_encoder, act.into_inner())
+                act.into_inner()
             };
+            let outcome = handle.wal_log(|batch| {
+                let outcome = self::snapshot_and_del(kve, key_encoder, args.clone());
+                if let StrongActionResult::Okay = outcome {
+                    args.as_slice()
+                        .iter()
+                        .for_each(|key| batch.kv(kve, unsafe { key.deref_slice() }));
+                }
+                outcome
+            });
             match outcome {
                 StrongActionResult::Okay => con._write_raw(P::RCODE_OKAY).await?,
                 StrongActionResult::Nil => {
@@ -127,7 +137,10 @@ pub(super) fn snapshot_and_del<'a, T: 'a + DerefUnsafeSlice>(
                 // value after we snapshotted it. In that case, let this key
                 // be whatever the "newer" value is. Since our snapshot is a "happens-before"
                 // thing, this is absolutely fine
-                let _ = lowtable.remove_if(key, |_, val| val.eq(&snapshot));
+                if lowtable.true_remove_if(key, |_, val| val.eq(&snapshot)) {
+                    kve.get_expiry_ref().remove(key);
+                    kve.notify(key, KeyEvent::Del);
+                }
             });
             StrongActionResult::Okay
         } else {
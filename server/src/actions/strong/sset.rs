@@ -29,8 +29,9 @@ use {
         actions::strong::StrongActionResult,
         corestore::SharedSlice,
         dbnet::prelude::*,
-        kvengine::{DoubleEncoder, KVEStandard},
+        kvengine::KVEStandard,
         protocol::iter::DerefUnsafeSlice,
+        pubsub::KeyEvent,
         util::compiler,
     },
     core::slice::Iter,
@@ -45,13 +46,23 @@ action! {
         let howmany = act.len();
         ensure_length::<P>(howmany, |size| size & 1 == 0 && size != 0)?;
         let kve = handle.get_table_with::<P, KVEBlob>()?;
+        handle.ensure_room::<P>()?;
         if registry::state_okay() {
             let encoder = kve.get_double_encoder();
-            let outcome = unsafe {
+            let args = unsafe {
                 // UNSAFE(@ohsayan): The lifetime of `act` guarantees that the
                 // pointers remain valid
-                self::snapshot_and_insert(kve, encoder, act.into_inner())
+                act.into_inner()
             };
+            let outcome = handle.wal_log(|batch| {
+                let outcome = self::snapshot_and_insert(kve, encoder, args.clone());
+                if let StrongActionResult::Okay = outcome {
+                    args.as_slice()
+                        .chunks_exact(2)
+                        .for_each(|kv| batch.kv(kve, unsafe { ucidx!(kv, 0).deref_slice() }));
+                }
+                outcome
+            });
             match outcome {
                 StrongActionResult::Okay => con._write_raw(P::RCODE_OKAY).await?,
                 StrongActionResult::OverwriteError => return util::err(P::RCODE_OVERWRITE_ERR),
@@ -76,7 +87,7 @@ action! {
 /// and then mutate the entries, respecting concurrency guarantees
 pub(super) fn snapshot_and_insert<'a, T: 'a + DerefUnsafeSlice>(
     kve: &'a KVEStandard,
-    encoder: DoubleEncoder,
+    encoder: impl Fn(&[u8], &[u8]) -> bool,
     mut act: Iter<'a, T>,
 ) -> StrongActionResult {
     let mut enc_err = false;
@@ -87,7 +98,7 @@ pub(super) fn snapshot_and_insert<'a, T: 'a + DerefUnsafeSlice>(
             let key = ucidx!(kv, 0).deref_slice();
             let value = ucidx!(kv, 1).deref_slice();
             if compiler::likely(encoder(key, value)) {
-                lowtable.get(key).is_none()
+                !kve.exists_unchecked(key)
             } else {
                 enc_err = true;
                 false
@@ -112,6 +123,7 @@ pub(super) fn snapshot_and_insert<'a, T: 'a + DerefUnsafeSlice>(
                         lowtable.fresh_entry(SharedSlice::new(key.deref_slice()))
                     {
                         fresh.insert(SharedSlice::new(value.deref_slice()));
+                        kve.notify(key.deref_slice(), KeyEvent::Set);
                     }
                     // we don't care if some other thread initialized the value we checked
                     // it. We expected a fresh entry, so that's what we'll check and use
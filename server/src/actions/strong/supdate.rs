@@ -26,12 +26,8 @@
 
 use {
     crate::{
-        actions::strong::StrongActionResult,
-        corestore::SharedSlice,
-        dbnet::prelude::*,
-        kvengine::{DoubleEncoder, KVEStandard},
-        protocol::iter::DerefUnsafeSlice,
-        util::compiler,
+        actions::strong::StrongActionResult, corestore::SharedSlice, dbnet::prelude::*,
+        kvengine::KVEStandard, protocol::iter::DerefUnsafeSlice, pubsub::KeyEvent, util::compiler,
     },
     core::slice::Iter,
 };
@@ -45,12 +41,22 @@ action! {
         let howmany = act.len();
         ensure_length::<P>(howmany, |size| size & 1 == 0 && size != 0)?;
         let kve = handle.get_table_with::<P, KVEBlob>()?;
+        handle.ensure_room::<P>()?;
         if registry::state_okay() {
             let encoder = kve.get_double_encoder();
-            let outcome = unsafe {
+            let args = unsafe {
                 // UNSAFE(@ohsayan): the lifetime of `act` ensure ptr validity
-                self::snapshot_and_update(kve, encoder, act.into_inner())
+                act.into_inner()
             };
+            let outcome = handle.wal_log(|batch| {
+                let outcome = self::snapshot_and_update(kve, encoder, args.clone());
+                if let StrongActionResult::Okay = outcome {
+                    args.as_slice()
+                        .chunks_exact(2)
+                        .for_each(|kv| batch.kv(kve, unsafe { ucidx!(kv, 0).deref_slice() }));
+                }
+                outcome
+            });
             match outcome {
                 StrongActionResult::Okay => con._write_raw(P::RCODE_OKAY).await?,
                 StrongActionResult::Nil => {
@@ -79,7 +85,7 @@ action! {
 /// `(all_okay, enc_err)`
 pub(super) fn snapshot_and_update<'a, T: 'a + DerefUnsafeSlice>(
     kve: &'a KVEStandard,
-    encoder: DoubleEncoder,
+    encoder: impl Fn(&[u8], &[u8]) -> bool,
     mut act: Iter<'a, T>,
 ) -> StrongActionResult {
     let mut enc_err = false;
@@ -128,6 +134,8 @@ pub(super) fn snapshot_and_update<'a, T: 'a + DerefUnsafeSlice>(
                     {
                         if mutable.value().eq(&snapshot) {
                             mutable.insert(SharedSlice::new(value.deref_slice()));
+                            drop(mutable);
+                            kve.notify(key.deref_slice(), KeyEvent::Update);
                         } else {
                             drop(mutable);
                         }
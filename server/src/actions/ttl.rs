@@ -0,0 +1,51 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # `TTL` queries
+//! This module provides functions to work with `TTL` queries
+
+use crate::dbnet::prelude::*;
+
+action!(
+    /// Run a `TTL` query
+    ///
+    /// Returns the number of seconds remaining before the key expires (rounded up)
+    fn ttl(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len == 1)?;
+        let key = unsafe {
+            // SAFETY: We have checked for there to be one arg
+            act.next_unchecked()
+        };
+        let kve = handle.get_table_with::<P, KVEBlob>()?;
+        match kve.ttl(key) {
+            Ok(Some(Some(millis))) => con.write_int64(millis.div_ceil(1000)).await?,
+            Ok(Some(None)) => return util::err(P::RSTRING_NO_EXPIRY),
+            Ok(None) => return util::err(P::RCODE_NIL),
+            Err(()) => return util::err(P::RCODE_ENCODING_ERROR),
+        }
+        Ok(())
+    }
+);
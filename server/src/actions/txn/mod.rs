@@ -0,0 +1,554 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Transactions
+//!
+//! `MULTI` starts a transaction on the connection. Until it is ended with `EXEC` or `DISCARD`,
+//! `SET`, `UPDATE`, `DEL` and `LMOD` are queued instead of being run, along with `CHECK`
+//! preconditions:
+//! - `CHECK <key> EQ <value>`: the key has the given value
+//! - `CHECK <key> EXISTS`: the key exists
+//! - `CHECK <key> ABSENT`: the key doesn't exist
+//!
+//! `EXEC` write locks the shards of every key that the transaction touches in the current table
+//! and runs the statements in order, with each statement seeing the changes made by the ones
+//! before it. The changes are only applied (and logged as a single frame) if every precondition
+//! held and every statement could be applied: `SET` needs an absent key, `UPDATE` and `LMOD`
+//! need an existing key and list indices need to be in bounds. Otherwise, nothing is changed and
+//! `err-txn-aborted` is returned. The same happens if a statement couldn't be queued
+//!
+//! Since the locks are taken on the table's shards, other connections never see a transaction
+//! that is only partly applied
+
+use {
+    crate::{
+        actions::{ensure_writable, ActionResult},
+        auth::grants::Access,
+        corestore::{map::bref::ShardLocks, table::DataModel, SharedSlice},
+        dbnet::{prelude::*, BufferedSocketStream},
+        kvengine::{self, KVEListmap, KVEStandard, KVEngine, LockedVec},
+        pubsub::KeyEvent,
+        queryengine::ActionIter as Iter,
+        util::compiler,
+    },
+    ahash::RandomState,
+    parking_lot::RwLock,
+    std::collections::HashMap,
+};
+
+#[cfg(test)]
+mod tests;
+
+const SET: &[u8] = b"SET";
+const UPDATE: &[u8] = b"UPDATE";
+const DEL: &[u8] = b"DEL";
+const LMOD: &[u8] = b"LMOD";
+const CHECK: &[u8] = b"CHECK";
+const EXEC: &[u8] = b"EXEC";
+const DISCARD: &[u8] = b"DISCARD";
+// conditions
+const EQ: &[u8] = b"EQ";
+const EXISTS: &[u8] = b"EXISTS";
+const ABSENT: &[u8] = b"ABSENT";
+// list operations
+const PUSH: &[u8] = b"PUSH";
+const INSERT: &[u8] = b"INSERT";
+const REMOVE: &[u8] = b"REMOVE";
+const CLEAR: &[u8] = b"CLEAR";
+
+/// The statements queued on a connection since `MULTI`
+#[derive(Debug, Default)]
+pub struct Transaction {
+    stmts: Vec<Stmt>,
+    /// set if a statement couldn't be queued, in which case `EXEC` aborts
+    failed: bool,
+}
+
+#[derive(Debug)]
+enum Stmt {
+    Check(SharedSlice, Cond),
+    Set(SharedSlice, SharedSlice),
+    Update(SharedSlice, SharedSlice),
+    Del(Vec<SharedSlice>),
+    Lmod(SharedSlice, ListOp),
+}
+
+#[derive(Debug)]
+enum Cond {
+    Eq(SharedSlice),
+    Exists,
+    Absent,
+}
+
+#[derive(Debug)]
+enum ListOp {
+    Push(Vec<SharedSlice>),
+    Insert(usize, SharedSlice),
+    Remove(usize),
+    Clear,
+}
+
+impl Stmt {
+    /// Returns true if the statement can make the table use more memory
+    fn may_grow(&self) -> bool {
+        matches!(
+            self,
+            Self::Set(..) | Self::Update(..) | Self::Lmod(_, ListOp::Push(_) | ListOp::Insert(..))
+        )
+    }
+    /// Parse a statement. `action` must be uppercased
+    fn parse<P: ProtocolSpec>(action: &[u8], mut act: Iter) -> ActionResult<Self> {
+        // UNSAFE(@ohsayan): every call is preceded by a length check
+        macro_rules! next {
+            () => {
+                unsafe { act.next_unchecked_bytes() }
+            };
+        }
+        let stmt = match action {
+            SET | UPDATE => {
+                ensure_length::<P>(act.len(), |len| len == 2)?;
+                let (key, value) = (next!(), next!());
+                if action == SET {
+                    Self::Set(key, value)
+                } else {
+                    Self::Update(key, value)
+                }
+            }
+            DEL => {
+                ensure_length::<P>(act.len(), |len| len != 0)?;
+                Self::Del(act.map(SharedSlice::new).collect())
+            }
+            CHECK => {
+                ensure_length::<P>(act.len(), |len| len == 2 || len == 3)?;
+                let key = next!();
+                let cond = match (
+                    act.next_uppercase().unwrap_or_aerr::<P>()?.as_ref(),
+                    act.len(),
+                ) {
+                    (EQ, 1) => Cond::Eq(next!()),
+                    (EXISTS, 0) => Cond::Exists,
+                    (ABSENT, 0) => Cond::Absent,
+                    _ => return util::err(P::RCODE_ACTION_ERR),
+                };
+                Self::Check(key, cond)
+            }
+            LMOD => {
+                ensure_length::<P>(act.len(), |len| len > 1)?;
+                let key = next!();
+                macro_rules! index {
+                    () => {
+                        match unsafe { String::from_utf8_lossy(act.next_unchecked()) }.parse() {
+                            Ok(idx) => idx,
+                            Err(_) => return util::err(P::RCODE_WRONGTYPE_ERR),
+                        }
+                    };
+                }
+                let op = match unsafe { act.next_uppercase_unchecked() }.as_ref() {
+                    PUSH => {
+                        ensure_boolean_or_aerr::<P>(!act.is_empty())?;
+                        ListOp::Push(act.map(SharedSlice::new).collect())
+                    }
+                    INSERT => {
+                        ensure_length::<P>(act.len(), |len| len == 2)?;
+                        let idx = index!();
+                        ListOp::Insert(idx, next!())
+                    }
+                    REMOVE => {
+                        ensure_length::<P>(act.len(), |len| len == 1)?;
+                        ListOp::Remove(index!())
+                    }
+                    CLEAR => {
+                        ensure_length::<P>(act.len(), |len| len == 0)?;
+                        ListOp::Clear
+                    }
+                    // `POP` returns the popped value, which a transaction can't
+                    _ => return util::err(P::RSTRING_TXN_UNSUPPORTED),
+                };
+                Self::Lmod(key, op)
+            }
+            _ => return util::err(P::RSTRING_TXN_UNSUPPORTED),
+        };
+        Ok(stmt)
+    }
+    /// The keys that the statement reads or writes
+    fn keys(&self) -> &[SharedSlice] {
+        match self {
+            Self::Check(key, _) | Self::Set(key, _) | Self::Update(key, _) | Self::Lmod(key, _) => {
+                core::slice::from_ref(key)
+            }
+            Self::Del(keys) => keys,
+        }
+    }
+}
+
+/// Why a transaction wasn't committed
+#[derive(Debug, PartialEq, Eq)]
+enum TxnError {
+    /// A precondition didn't hold or a statement couldn't be applied
+    Aborted,
+    /// A statement can't be run on this model
+    WrongModel,
+    /// A key or value doesn't match the types of the table
+    Encoding,
+}
+
+type TxnResult<T> = Result<T, TxnError>;
+
+/// Values that can be staged while a transaction runs. A staged value is a copy, so that nothing
+/// is changed until the transaction commits
+trait Stage {
+    type Staged;
+    fn stage(&self) -> Self::Staged;
+    fn unstage(staged: Self::Staged) -> Self;
+}
+
+impl Stage for SharedSlice {
+    type Staged = SharedSlice;
+    fn stage(&self) -> Self::Staged {
+        self.clone()
+    }
+    fn unstage(staged: Self::Staged) -> Self {
+        staged
+    }
+}
+
+impl Stage for LockedVec {
+    type Staged = Vec<SharedSlice>;
+    fn stage(&self) -> Self::Staged {
+        self.read().clone()
+    }
+    fn unstage(staged: Self::Staged) -> Self {
+        RwLock::new(staged)
+    }
+}
+
+/// A key as seen by a running transaction
+struct Slot<S> {
+    existed: bool,
+    value: Option<S>,
+    written: bool,
+}
+
+/// The keys touched by a running transaction, whose shards stay locked until it's done
+struct Staged<'a, T: Stage> {
+    kve: &'a KVEngine<T>,
+    locks: ShardLocks<'a, SharedSlice, T, RandomState>,
+    slots: HashMap<SharedSlice, Slot<T::Staged>>,
+    now: u64,
+}
+
+impl<'a, T: Stage> Staged<'a, T> {
+    fn new(kve: &'a KVEngine<T>, stmts: &[Stmt]) -> Self {
+        let keys = stmts.iter().flat_map(Stmt::keys).map(|key| &**key);
+        Self {
+            kve,
+            locks: kve.get_inner_ref().lock_keys::<[u8]>(keys),
+            slots: HashMap::new(),
+            now: kvengine::unix_millis(),
+        }
+    }
+    fn entry(&mut self, key: &SharedSlice) -> &mut Slot<T::Staged> {
+        let Self {
+            kve,
+            locks,
+            slots,
+            now,
+        } = self;
+        slots.entry(key.clone()).or_insert_with(|| {
+            let expiry = kve.get_expiry_ref();
+            if expiry
+                .get(key.as_ref())
+                .is_some_and(|deadline| *deadline <= *now)
+            {
+                // the key is as good as gone
+                expiry.remove(key.as_ref());
+                if locks.remove(key.as_ref()).is_some() {
+                    kve.notify(key, KeyEvent::Expired);
+                }
+            }
+            let value = locks.get(key.as_ref()).map(Stage::stage);
+            Slot {
+                existed: value.is_some(),
+                value,
+                written: false,
+            }
+        })
+    }
+    /// Returns the (staged) value of the key
+    fn slot(&mut self, key: &SharedSlice) -> &mut Option<T::Staged> {
+        &mut self.entry(key).value
+    }
+    /// Same as [`Staged::slot`], but marks the key as written
+    fn slot_mut(&mut self, key: &SharedSlice) -> &mut Option<T::Staged> {
+        let slot = self.entry(key);
+        slot.written = true;
+        &mut slot.value
+    }
+    /// Run the statements that are common to all models
+    fn run(&mut self, stmt: &Stmt) -> TxnResult<()> {
+        let ok = match stmt {
+            Stmt::Check(key, Cond::Exists) => self.slot(key).is_some(),
+            Stmt::Check(key, Cond::Absent) => self.slot(key).is_none(),
+            Stmt::Del(keys) => {
+                keys.iter().for_each(|key| *self.slot_mut(key) = None);
+                true
+            }
+            _ => return Err(TxnError::WrongModel),
+        };
+        ensure(ok)
+    }
+    /// Apply the changes, returning the keys that were written
+    fn commit(mut self) -> Vec<SharedSlice> {
+        let expiry = self.kve.get_expiry_ref();
+        let mut written = Vec::new();
+        for (key, slot) in self.slots.drain().filter(|(_, slot)| slot.written) {
+            let event = match (slot.existed, slot.value) {
+                (_, Some(value)) => {
+                    if !slot.existed {
+                        // a previous incarnation of this key may have left a deadline behind
+                        expiry.remove(key.as_ref());
+                    }
+                    self.locks.insert(key.clone(), T::unstage(value));
+                    if slot.existed {
+                        KeyEvent::Update
+                    } else {
+                        KeyEvent::Set
+                    }
+                }
+                (true, None) => {
+                    expiry.remove(key.as_ref());
+                    self.locks.remove(key.as_ref());
+                    KeyEvent::Del
+                }
+                (false, None) => continue,
+            };
+            self.kve.notify(&key, event);
+            written.push(key);
+        }
+        written
+    }
+}
+
+fn ensure(ok: bool) -> TxnResult<()> {
+    if compiler::likely(ok) {
+        Ok(())
+    } else {
+        Err(TxnError::Aborted)
+    }
+}
+
+/// Run the transaction on a key/value table, returning the keys that were written
+fn commit_kv(kve: &KVEStandard, stmts: &[Stmt]) -> TxnResult<Vec<SharedSlice>> {
+    let encoding_ok = stmts.iter().all(|stmt| {
+        stmt.keys().iter().all(|key| kve.is_key_ok(key))
+            && match stmt {
+                Stmt::Check(_, Cond::Eq(value)) | Stmt::Set(_, value) | Stmt::Update(_, value) => {
+                    kve.is_val_ok(value)
+                }
+                _ => true,
+            }
+    });
+    if compiler::unlikely(!encoding_ok) {
+        return Err(TxnError::Encoding);
+    }
+    let mut staged = Staged::new(kve, stmts);
+    for stmt in stmts {
+        match stmt {
+            Stmt::Check(key, Cond::Eq(value)) => ensure(staged.slot(key).as_ref() == Some(value))?,
+            Stmt::Set(key, value) => {
+                ensure(staged.slot(key).is_none())?;
+                *staged.slot_mut(key) = Some(value.clone());
+            }
+            Stmt::Update(key, value) => {
+                ensure(staged.slot(key).is_some())?;
+                *staged.slot_mut(key) = Some(value.clone());
+            }
+            stmt => staged.run(stmt)?,
+        }
+    }
+    Ok(staged.commit())
+}
+
+/// Run the transaction on a list table, returning the keys that were written
+fn commit_list(listmap: &KVEListmap, stmts: &[Stmt]) -> TxnResult<Vec<SharedSlice>> {
+    let encoding_ok = stmts.iter().all(|stmt| {
+        stmt.keys().iter().all(|key| listmap.is_key_ok(key))
+            && match stmt {
+                Stmt::Lmod(_, ListOp::Push(values)) => {
+                    values.iter().all(|value| listmap.is_val_ok(value))
+                }
+                Stmt::Lmod(_, ListOp::Insert(_, value)) => listmap.is_val_ok(value),
+                _ => true,
+            }
+    });
+    if compiler::unlikely(!encoding_ok) {
+        return Err(TxnError::Encoding);
+    }
+    let mut staged = Staged::new(listmap, stmts);
+    for stmt in stmts {
+        match stmt {
+            Stmt::Lmod(key, op) => {
+                let list = staged.slot_mut(key).as_mut().ok_or(TxnError::Aborted)?;
+                let ok = match op {
+                    ListOp::Push(values) => {
+                        list.extend(values.iter().cloned());
+                        listmap.cap(list);
+                        true
+                    }
+                    ListOp::Insert(idx, value) => {
+                        let ok = *idx < list.len();
+                        if ok {
+                            list.insert(*idx, value.clone());
+                            listmap.cap(list);
+                        }
+                        ok
+                    }
+                    ListOp::Remove(idx) => {
+                        let ok = *idx < list.len();
+                        if ok {
+                            list.remove(*idx);
+                        }
+                        ok
+                    }
+                    ListOp::Clear => {
+                        list.clear();
+                        true
+                    }
+                };
+                ensure(ok)?;
+            }
+            stmt => staged.run(stmt)?,
+        }
+    }
+    Ok(staged.commit())
+}
+
+/// Queue a statement in the connection's transaction, or end it with `EXEC` or `DISCARD`.
+/// Queueing needs the same access as running the statement
+pub async fn execute_in_txn<'a, P: ProtocolSpec, C: 'a + BufferedSocketStream>(
+    handle: &Corestore,
+    con: &mut Connection<C, P>,
+    auth: &mut AuthProviderHandle,
+    mut act: Iter<'a>,
+) -> ActionResult<()> {
+    let action = act
+        .next_uppercase()
+        .unwrap_or_custom_aerr(P::RCODE_PACKET_ERR)?;
+    match action.as_ref() {
+        EXEC => self::exec(handle, con, act).await,
+        DISCARD => {
+            ensure_length::<P>(act.len(), |len| len == 0)?;
+            *con.transaction_mut() = None;
+            con._write_raw(P::RCODE_OKAY).await?;
+            Ok(())
+        }
+        action => {
+            let access = if action == CHECK {
+                Access::Read
+            } else {
+                Access::Write
+            };
+            let space = handle.get_ids().0.map(|ks| ks.as_slice());
+            let stmt = auth
+                .provider()
+                .ensure_access::<P>(space, access)
+                .and_then(|_| ensure_writable::<P>(access))
+                .and_then(|_| Stmt::parse::<P>(action, act));
+            let txn = con.transaction_mut().as_mut().unwrap();
+            match stmt {
+                Ok(stmt) => txn.stmts.push(stmt),
+                Err(e) => {
+                    txn.failed = true;
+                    return Err(e);
+                }
+            }
+            con._write_raw(P::RCODE_OKAY).await?;
+            Ok(())
+        }
+    }
+}
+
+action! {
+    /// Run a `MULTI` query, which starts a transaction
+    fn multi(_handle: &Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len == 0)?;
+        *con.transaction_mut() = Some(Transaction::default());
+        con._write_raw(P::RCODE_OKAY).await?;
+        Ok(())
+    }
+    /// Run `EXEC`, `DISCARD` or `CHECK` outside a transaction
+    fn not_in_txn(_handle: &Corestore, _con: &mut Connection<C, P>, _act: ActionIter<'a>) {
+        util::err(P::RSTRING_NOT_IN_TXN)
+    }
+    /// Run an `EXEC` query, which ends the transaction and commits it
+    fn exec(handle: &Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len == 0)?;
+        let txn = match con.transaction_mut().take() {
+            Some(txn) => txn,
+            None => return util::err(P::RSTRING_NOT_IN_TXN),
+        };
+        if compiler::unlikely(txn.failed) {
+            return util::err(P::RSTRING_TXN_ABORTED);
+        }
+        let table = get_tbl_ref!(handle, con);
+        if !registry::state_okay() {
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        if txn.stmts.iter().any(Stmt::may_grow) {
+            handle.ensure_room::<P>()?;
+        }
+        let outcome = handle.wal_log(|batch| match table.get_model_ref() {
+            DataModel::KV(kve) | DataModel::KVOrdered(kve) => {
+                commit_kv(kve, &txn.stmts).map(|written| {
+                    // the locks are gone, so read back what was written
+                    written.iter().for_each(|key| batch.kv(kve, key));
+                })
+            }
+            DataModel::KVExtListmap(listmap) => {
+                commit_list(listmap, &txn.stmts).map(|written| {
+                    written.iter().for_each(|key| {
+                        match listmap.get_inner_ref().get(key.as_ref()) {
+                            Some(list) => batch.list(key, &list.read()),
+                            None => batch.del(key),
+                        }
+                    })
+                })
+            }
+            DataModel::Rowmap(_)
+            | DataModel::Hashmap(_)
+            | DataModel::Set(_)
+            | DataModel::SortedSet(_)
+            | DataModel::Queue(_) => Err(TxnError::WrongModel),
+        });
+        match outcome {
+            Ok(()) => con._write_raw(P::RCODE_OKAY).await?,
+            Err(TxnError::Aborted) => return util::err(P::RSTRING_TXN_ABORTED),
+            Err(TxnError::WrongModel) => return util::err(P::RSTRING_WRONG_MODEL),
+            Err(TxnError::Encoding) => return util::err(P::RCODE_ENCODING_ERROR),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,170 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use {
+    super::{commit_kv, commit_list, Cond, ListOp, Stmt, TxnError},
+    crate::{
+        corestore::SharedSlice,
+        kvengine::{types::FieldType, KVEListmap, KVEStandard, LockedVec},
+    },
+};
+
+fn s(slice: &str) -> SharedSlice {
+    SharedSlice::from(slice)
+}
+
+fn kve_with(pairs: &[(&str, &str)]) -> KVEStandard {
+    let kve = KVEStandard::default();
+    for (key, value) in pairs {
+        kve.set(s(key), s(value)).unwrap();
+    }
+    kve
+}
+
+fn get(kve: &KVEStandard, key: &str) -> Option<SharedSlice> {
+    kve.get_cloned(key).unwrap()
+}
+
+#[test]
+fn commit_kv_applies_everything() {
+    let kve = kve_with(&[("balance-a", "100"), ("balance-b", "0"), ("stale", "x")]);
+    let stmts = [
+        Stmt::Check(s("balance-a"), Cond::Eq(s("100"))),
+        Stmt::Check(s("lock"), Cond::Absent),
+        Stmt::Update(s("balance-a"), s("50")),
+        Stmt::Update(s("balance-b"), s("50")),
+        Stmt::Set(s("lock"), s("held")),
+        Stmt::Del(vec![s("stale"), s("missing")]),
+    ];
+    let mut written = commit_kv(&kve, &stmts).unwrap();
+    written.sort_by(|a, b| a.as_ref().cmp(b.as_ref()));
+    assert_eq!(
+        written,
+        [s("balance-a"), s("balance-b"), s("lock"), s("stale")]
+    );
+    assert_eq!(get(&kve, "balance-a"), Some(s("50")));
+    assert_eq!(get(&kve, "balance-b"), Some(s("50")));
+    assert_eq!(get(&kve, "lock"), Some(s("held")));
+    assert_eq!(get(&kve, "stale"), None);
+}
+
+#[test]
+fn commit_kv_sees_earlier_statements() {
+    let kve = kve_with(&[]);
+    let stmts = [
+        Stmt::Set(s("x"), s("1")),
+        Stmt::Check(s("x"), Cond::Eq(s("1"))),
+        Stmt::Update(s("x"), s("2")),
+        Stmt::Set(s("y"), s("1")),
+        Stmt::Del(vec![s("y")]),
+        Stmt::Check(s("y"), Cond::Absent),
+    ];
+    assert_eq!(commit_kv(&kve, &stmts).unwrap(), [s("x")]);
+    assert_eq!(get(&kve, "x"), Some(s("2")));
+    assert_eq!(get(&kve, "y"), None);
+}
+
+#[test]
+fn commit_kv_abort_changes_nothing() {
+    let stmts = [
+        Stmt::Set(s("x"), s("1")),
+        Stmt::Del(vec![s("y")]),
+        Stmt::Check(s("z"), Cond::Eq(s("1"))),
+    ];
+    let kve = kve_with(&[("y", "1"), ("z", "2")]);
+    assert_eq!(commit_kv(&kve, &stmts), Err(TxnError::Aborted));
+    assert_eq!(get(&kve, "x"), None);
+    assert_eq!(get(&kve, "y"), Some(s("1")));
+    // a failing statement aborts just like a failing precondition
+    let kve = kve_with(&[("x", "1"), ("y", "1")]);
+    let stmts = [Stmt::Del(vec![s("y")]), Stmt::Set(s("x"), s("2"))];
+    assert_eq!(commit_kv(&kve, &stmts), Err(TxnError::Aborted));
+    assert_eq!(get(&kve, "x"), Some(s("1")));
+    assert_eq!(get(&kve, "y"), Some(s("1")));
+    let stmts = [Stmt::Update(s("missing"), s("1"))];
+    assert_eq!(commit_kv(&kve, &stmts), Err(TxnError::Aborted));
+}
+
+#[test]
+fn commit_kv_errors() {
+    let kve = KVEStandard::init_typed(FieldType::String, FieldType::UInt64);
+    let stmts = [Stmt::Lmod(s("x"), ListOp::Clear)];
+    assert_eq!(commit_kv(&kve, &stmts), Err(TxnError::WrongModel));
+    let stmts = [Stmt::Set(s("x"), s("1")), Stmt::Set(s("y"), s("one"))];
+    assert_eq!(commit_kv(&kve, &stmts), Err(TxnError::Encoding));
+    assert!(!kve.exists("x").unwrap());
+}
+
+#[test]
+fn commit_kv_expired_keys_are_absent() {
+    let kve = kve_with(&[]);
+    kve.set_with_ttl(s("x"), s("1"), 0).unwrap();
+    let stmts = [Stmt::Check(s("x"), Cond::Absent), Stmt::Set(s("x"), s("2"))];
+    commit_kv(&kve, &stmts).unwrap();
+    assert_eq!(get(&kve, "x"), Some(s("2")));
+    assert_eq!(kve.ttl("x").unwrap(), Some(None));
+}
+
+fn list(listmap: &KVEListmap, key: &str) -> Option<Vec<SharedSlice>> {
+    listmap
+        .get_inner_ref()
+        .get(key.as_bytes())
+        .map(|list| list.read().clone())
+}
+
+#[test]
+fn commit_list_ops() {
+    let listmap = KVEListmap::default();
+    listmap
+        .set(s("l"), LockedVec::new(vec![s("a"), s("b")]))
+        .unwrap();
+    let stmts = [
+        Stmt::Check(s("l"), Cond::Exists),
+        Stmt::Lmod(s("l"), ListOp::Push(vec![s("c"), s("d")])),
+        Stmt::Lmod(s("l"), ListOp::Remove(0)),
+        Stmt::Lmod(s("l"), ListOp::Insert(1, s("x"))),
+    ];
+    assert_eq!(commit_list(&listmap, &stmts).unwrap(), [s("l")]);
+    assert_eq!(
+        list(&listmap, "l").unwrap(),
+        [s("b"), s("x"), s("c"), s("d")]
+    );
+    // out of bounds
+    let stmts = [
+        Stmt::Lmod(s("l"), ListOp::Clear),
+        Stmt::Lmod(s("l"), ListOp::Insert(0, s("x"))),
+    ];
+    assert_eq!(commit_list(&listmap, &stmts), Err(TxnError::Aborted));
+    assert_eq!(list(&listmap, "l").unwrap().len(), 4);
+    // missing list
+    let stmts = [Stmt::Lmod(s("missing"), ListOp::Clear)];
+    assert_eq!(commit_list(&listmap, &stmts), Err(TxnError::Aborted));
+    let stmts = [Stmt::Lmod(s("l"), ListOp::Clear), Stmt::Del(vec![s("l")])];
+    commit_list(&listmap, &stmts).unwrap();
+    assert!(list(&listmap, "l").is_none());
+    let stmts = [Stmt::Set(s("l"), s("x"))];
+    assert_eq!(commit_list(&listmap, &stmts), Err(TxnError::WrongModel));
+}
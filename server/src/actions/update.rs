@@ -37,14 +37,23 @@ action!(
         if registry::state_okay() {
             let did_we = {
                 let writer = handle.get_table_with::<P, KVEBlob>()?;
-                match unsafe {
+                handle.ensure_room::<P>()?;
+                let (key, value) = unsafe {
                     // UNSAFE(@ohsayan): This is completely safe as we've already checked
                     // that there are exactly 2 arguments
-                    writer.update(
+                    (
                         SharedSlice::new(act.next_unchecked()),
                         SharedSlice::new(act.next_unchecked()),
                     )
-                } {
+                };
+                let ret = handle.wal_log(|batch| {
+                    let ret = writer.update(key.clone(), value);
+                    if let Ok(true) = ret {
+                        batch.kv(writer, &key);
+                    }
+                    ret
+                });
+                match ret {
                     Ok(true) => Some(true),
                     Ok(false) => Some(false),
                     Err(()) => None,
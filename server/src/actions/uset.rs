@@ -24,25 +24,30 @@
  *
 */
 
-use crate::{
-    corestore::SharedSlice, dbnet::prelude::*,
-    kvengine::encoding::ENCODING_LUT_ITER_PAIR, queryengine::ActionIter, util::compiler,
-};
+use crate::{corestore::SharedSlice, dbnet::prelude::*, queryengine::ActionIter, util::compiler};
 
 action!(
     /// Run an `USET` query
     ///
     /// This is like "INSERT or UPDATE"
-    fn uset(handle: &crate::corestore::Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+    fn uset(
+        handle: &crate::corestore::Corestore,
+        con: &mut Connection<C, P>,
+        mut act: ActionIter<'a>,
+    ) {
         let howmany = act.len();
         ensure_length::<P>(howmany, |size| size & 1 == 0 && size != 0)?;
         let kve = handle.get_table_with::<P, KVEBlob>()?;
-        let encoding_is_okay = ENCODING_LUT_ITER_PAIR[kve.get_encoding_tuple()](&act);
+        handle.ensure_room::<P>()?;
+        let encoding_is_okay = kve.is_pair_iter_ok(&act);
         if compiler::likely(encoding_is_okay) {
             if registry::state_okay() {
-                while let (Some(key), Some(val)) = (act.next(), act.next()) {
-                    kve.upsert_unchecked(SharedSlice::new(key), SharedSlice::new(val));
-                }
+                handle.wal_log(|batch| {
+                    while let (Some(key), Some(val)) = (act.next(), act.next()) {
+                        kve.upsert_unchecked(SharedSlice::new(key), SharedSlice::new(val));
+                        batch.kv(kve, key);
+                    }
+                });
                 con.write_usize(howmany / 2).await?;
             } else {
                 return util::err(P::RCODE_SERVER_ERR);
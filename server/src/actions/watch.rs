@@ -0,0 +1,60 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # `WATCH` queries
+//!
+//! A connection that watches a key in the current table is notified whenever the key is set,
+//! updated, deleted or expired. Notifications are pushed as a typed array of `watch`, the key and
+//! the event (`set`, `update`, `del` or `expired`), just like pub/sub messages
+
+use crate::{dbnet::prelude::*, pubsub::Kind};
+
+action!(
+    /// Run a `WATCH` query, returning the number of keys the connection is watching
+    fn watch(handle: &Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len != 0)?;
+        let watchers = get_tbl_ref!(handle, con).get_watchers();
+        let subscriptions = con.subscriptions_mut();
+        act.for_each(|key| subscriptions.subscribe(Kind::Key, watchers, key));
+        let count = subscriptions.count(Kind::Key);
+        con.write_usize(count).await?;
+        Ok(())
+    }
+    /// Run an `UNWATCH` query, returning the number of keys the connection is still watching.
+    /// Without any arguments, the connection stops watching all keys (in all tables)
+    fn unwatch(handle: &Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
+        let subscriptions = con.subscriptions_mut();
+        if act.is_empty() {
+            subscriptions.unsubscribe_all(Kind::Key);
+        } else {
+            let watchers = get_tbl_ref!(handle, con).get_watchers();
+            act.for_each(|key| subscriptions.unsubscribe(watchers, key));
+        }
+        let count = subscriptions.count(Kind::Key);
+        con.write_usize(count).await?;
+        Ok(())
+    }
+);
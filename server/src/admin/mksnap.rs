@@ -24,14 +24,7 @@
  *
 */
 
-use {
-    crate::{
-        dbnet::prelude::*, kvengine::encoding,
-        storage::v1::sengine::SnapshotActionResult,
-    },
-    core::str,
-    std::path::{Component, PathBuf},
-};
+use crate::{dbnet::prelude::*, storage::v1::sengine::SnapshotActionResult};
 
 action!(
     /// Create a snapshot
@@ -53,30 +46,7 @@ action!(
                 // SAFETY: We have already checked that there is one item
                 act.next_unchecked_bytes()
             };
-            if !encoding::is_utf8(&name) {
-                return util::err(P::RCODE_ENCODING_ERROR);
-            }
-
-            // SECURITY: Check for directory traversal syntax
-            let st = unsafe {
-                // SAFETY: We have already checked for UTF-8 validity
-                str::from_utf8_unchecked(&name)
-            };
-            let path = PathBuf::from(st);
-            let illegal_snapshot = path
-                .components()
-                .filter(|dir| {
-                    // Sanitize snapshot name, to avoid directory traversal attacks
-                    // If the snapshot name has any root directory or parent directory, then
-                    // we'll allow it to pass through this adaptor.
-                    // As a result, this iterator will give us a count of the 'bad' components
-                    dir == &Component::RootDir || dir == &Component::ParentDir
-                })
-                .count()
-                != 0;
-            if illegal_snapshot {
-                return util::err(P::RSTRING_SNAPSHOT_ILLEGAL_NAME);
-            }
+            super::snapshots::ensure_legal_name::<P>(&name)?;
 
             // now make the snapshot
             match engine.mkrsnap(&name, handle.clone_store()).await {
@@ -27,4 +27,6 @@
 //! Modules for administration of Skytable
 
 pub mod mksnap;
+pub mod snapshots;
+pub mod sync;
 pub mod sys;
@@ -0,0 +1,129 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! Listing, removing and restoring snapshots
+
+use {
+    crate::{
+        actions::ActionResult,
+        dbnet::prelude::*,
+        kvengine::encoding,
+        storage::v1::sengine::{self, SnapshotActionResult},
+    },
+    core::str,
+};
+
+/// Check that a snapshot name is valid UTF-8 and that it names a single snapshot, so that it
+/// can't be used to get out of the snapshot directories, to point at them or to point into a
+/// snapshot. Returns the name as a string
+pub(super) fn ensure_legal_name<P: ProtocolSpec>(name: &[u8]) -> ActionResult<&str> {
+    if name.is_empty() {
+        // this also keeps the (unchecked) UTF-8 validation away from empty slices
+        return util::err(P::RSTRING_SNAPSHOT_ILLEGAL_NAME);
+    }
+    if !encoding::is_utf8(name) {
+        return util::err(P::RCODE_ENCODING_ERROR);
+    }
+    let name = unsafe {
+        // SAFETY: We have already checked for UTF-8 validity
+        str::from_utf8_unchecked(name)
+    };
+    // SECURITY: Check for directory traversal syntax
+    if sengine::is_snapshot_name(name) {
+        Ok(name)
+    } else {
+        util::err(P::RSTRING_SNAPSHOT_ILLEGAL_NAME)
+    }
+}
+
+/// Turn the outcome of removing or restoring a snapshot into an error, if it failed
+fn ensure_done<P: ProtocolSpec>(ret: SnapshotActionResult) -> ActionResult<()> {
+    match ret {
+        SnapshotActionResult::Ok => Ok(()),
+        SnapshotActionResult::Failure => util::err(P::RCODE_SERVER_ERR),
+        SnapshotActionResult::Busy => util::err(P::RSTRING_SNAPSHOT_BUSY),
+        SnapshotActionResult::NotFound => util::err(P::RSTRING_SNAPSHOT_NOT_FOUND),
+        _ => unsafe { impossible!() },
+    }
+}
+
+action!(
+    /// List the local snapshots and then the remote snapshots. Every snapshot takes four
+    /// elements of the array: its name, `local` or `remote`, the time it was last modified
+    /// (in seconds since the epoch) and its size in bytes
+    fn lssnap(handle: &Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len == 0)?;
+        let snapshots = match handle.get_engine().list().await {
+            Ok(snapshots) => snapshots,
+            Err(e) => {
+                log::error!("Failed to list snapshots: {e}");
+                return util::err(P::RCODE_SERVER_ERR);
+            }
+        };
+        con.write_typed_non_null_array_header(snapshots.len() * 4, b'+')
+            .await?;
+        for snapshot in snapshots {
+            let kind: &[u8] = if snapshot.remote { b"remote" } else { b"local" };
+            con.write_typed_non_null_array_element(snapshot.name.as_bytes())
+                .await?;
+            con.write_typed_non_null_array_element(kind).await?;
+            con.write_typed_non_null_array_element(snapshot.modified.to_string().as_bytes())
+                .await?;
+            con.write_typed_non_null_array_element(snapshot.size.to_string().as_bytes())
+                .await?;
+        }
+        Ok(())
+    }
+);
+
+action!(
+    /// Remove a local or a remote snapshot
+    fn rmsnap(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len == 1)?;
+        let name = unsafe {
+            // SAFETY: We have already checked that there is one item
+            act.next_unchecked_bytes()
+        };
+        let name = self::ensure_legal_name::<P>(&name)?;
+        self::ensure_done::<P>(handle.get_engine().rmsnap(name).await)?;
+        con._write_raw(P::RCODE_OKAY).await?;
+        Ok(())
+    }
+    /// Replace all the data with a local or a remote snapshot, while the server keeps running.
+    /// Users and grants aren't restored
+    fn restoresnap(handle: &Corestore, con: &mut Connection<C, P>, mut act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len == 1)?;
+        let name = unsafe {
+            // SAFETY: We have already checked that there is one item
+            act.next_unchecked_bytes()
+        };
+        let name = self::ensure_legal_name::<P>(&name)?;
+        let ret = handle.get_engine().restore(name, handle.clone()).await;
+        self::ensure_done::<P>(ret)?;
+        con._write_raw(P::RCODE_OKAY).await?;
+        Ok(())
+    }
+);
@@ -0,0 +1,120 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Replication (leader side)
+//!
+//! A follower switches its connection into a replication stream with `SYNC`. It gets a
+//! response code, then a snapshot of the store, then an empty frame marking the end of
+//! the snapshot and then every frame that is appended to the write-ahead log from then on.
+//! Empty frames are also sent whenever the log is idle so that followers can tell a quiet
+//! leader from a dead one. See [`crate::storage::v1::wal`] for the format of the frames
+
+use {
+    crate::{dbnet::prelude::*, storage::v1::wal},
+    core::sync::atomic::Ordering,
+    std::io::Error as IoError,
+    tokio::{
+        sync::{broadcast::error::RecvError, mpsc},
+        time::{self, Duration},
+    },
+};
+
+/// An empty frame is sent if the log was idle for this long
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+/// The number of snapshot frames that can be encoded before the follower has been sent them
+const SNAPSHOT_BACKLOG: usize = 16;
+
+/// Counts a follower for as long as its stream is alive
+struct FollowerGuard;
+
+impl FollowerGuard {
+    fn new() -> Self {
+        registry::follower_count().fetch_add(1, Ordering::Relaxed);
+        Self
+    }
+}
+
+impl Drop for FollowerGuard {
+    fn drop(&mut self) {
+        registry::follower_count().fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+action!(
+    /// Stream a snapshot and then every mutation to a follower. This only returns once the
+    /// follower goes away or falls behind, or when the server shuts down
+    fn sync(handle: &Corestore, con: &mut Connection<C, P>, act: ActionIter<'a>) {
+        ensure_length::<P>(act.len(), |len| len == 0)?;
+        // subscribe before taking the snapshot so that nothing is lost in between
+        let mut feed = match handle.get_wal().subscribe() {
+            Some(feed) => feed,
+            None => return util::err(P::RSTRING_ERR_ACCESS_AFTER_TERMSIG),
+        };
+        let _guard = FollowerGuard::new();
+        let store = handle.clone_store();
+        // the snapshot is encoded on another thread and streamed frame by frame, so that
+        // we never hold all of it
+        let (tx, mut frames) = mpsc::channel(SNAPSHOT_BACKLOG);
+        let encoder = tokio::task::spawn_blocking(move || {
+            wal::snapshot_with(&store, |frame| tx.blocking_send(frame).is_ok())
+        });
+        con._write_raw(P::RCODE_OKAY).await?;
+        while let Some(frame) = frames.recv().await {
+            con._write_raw(&frame).await?;
+        }
+        encoder
+            .await
+            .expect("Something caused the snapshot task to panic");
+        let heartbeat = wal::heartbeat();
+        con._write_raw(&heartbeat).await?;
+        con.flush_stream().await?;
+        loop {
+            match time::timeout(HEARTBEAT_INTERVAL, feed.recv()).await {
+                Ok(Ok(frame)) => con._write_raw(&frame).await?,
+                Ok(Err(RecvError::Lagged(_))) => {
+                    // the follower will reconnect and pull a fresh snapshot
+                    return Err(IoError::other(
+                        "Disconnected a follower that fell behind the write-ahead log",
+                    )
+                    .into());
+                }
+                // the feed was closed since we're shutting down
+                Ok(Err(RecvError::Closed)) if handle.get_wal().subscribe().is_none() => {
+                    return Ok(())
+                }
+                Ok(Err(RecvError::Closed)) => {
+                    // the feed was replaced after a restore
+                    return Err(IoError::other(
+                        "Disconnected a follower since the store was restored from a snapshot",
+                    )
+                    .into());
+                }
+                Err(_) => con._write_raw(&heartbeat).await?,
+            }
+            con.flush_stream().await?;
+        }
+    }
+);
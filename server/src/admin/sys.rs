@@ -29,6 +29,7 @@ use {
         corestore::booltable::BoolTable, dbnet::prelude::*,
         storage::v1::interface::DIR_ROOT,
     },
+    core::sync::atomic::Ordering,
     libsky::VERSION,
 };
 
@@ -39,18 +40,25 @@ const INFO_PROTOVER: &[u8] = b"protover";
 const INFO_VERSION: &[u8] = b"version";
 const METRIC_HEALTH: &[u8] = b"health";
 const METRIC_STORAGE_USAGE: &[u8] = b"storage";
+const METRIC_REPLICATION: &[u8] = b"replication";
+const METRIC_FOLLOWERS: &[u8] = b"followers";
+const METRIC_REPLICATED: &[u8] = b"replicated";
+const METRIC_CHANNELS: &[u8] = b"channels";
+const METRIC_MEMORY: &[u8] = b"memory";
+const METRIC_MAXMEMORY: &[u8] = b"maxmemory";
+const METRIC_EVICTIONS: &[u8] = b"evictions";
 const ERR_UNKNOWN_PROPERTY: &[u8] = b"!16\nunknown-property\n";
 const ERR_UNKNOWN_METRIC: &[u8] = b"!14\nunknown-metric\n";
 
 const HEALTH_TABLE: BoolTable<&str> = BoolTable::new("good", "critical");
 
 action! {
-    fn sys(_handle: &Corestore, con: &mut Connection<C, P>, iter: ActionIter<'_>) {
+    fn sys(handle: &Corestore, con: &mut Connection<C, P>, iter: ActionIter<'_>) {
         let mut iter = iter;
         ensure_boolean_or_aerr::<P>(iter.len() == 2)?;
         match unsafe { iter.next_lowercase_unchecked() }.as_ref() {
             INFO => sys_info(con, &mut iter).await,
-            METRIC => sys_metric(con, &mut iter).await,
+            METRIC => sys_metric(handle, con, &mut iter).await,
             _ => util::err(P::RCODE_UNKNOWN_ACTION),
         }
     }
@@ -63,7 +71,7 @@ action! {
         }
         Ok(())
     }
-    fn sys_metric(con: &mut Connection<C, P>, iter: &mut ActionIter<'_>) {
+    fn sys_metric(handle: &Corestore, con: &mut Connection<C, P>, iter: &mut ActionIter<'_>) {
         match unsafe { iter.next_lowercase_unchecked() }.as_ref() {
             METRIC_HEALTH => {
                 con.write_string(HEALTH_TABLE[registry::state_okay()]).await?
@@ -77,6 +85,23 @@ action! {
                     },
                 }
             }
+            METRIC_REPLICATION => {
+                con.write_string(registry::replica_state().name()).await?
+            }
+            METRIC_FOLLOWERS => {
+                con.write_usize(registry::follower_count().load(Ordering::Relaxed)).await?
+            }
+            METRIC_REPLICATED => {
+                con.write_int64(registry::replicated_records().load(Ordering::Relaxed)).await?
+            }
+            METRIC_CHANNELS => con.write_usize(handle.get_pubsub().channel_count()).await?,
+            METRIC_MEMORY => {
+                con.write_usize(registry::used_memory().load(Ordering::Relaxed)).await?
+            }
+            METRIC_MAXMEMORY => con.write_usize(registry::max_memory()).await?,
+            METRIC_EVICTIONS => {
+                con.write_int64(registry::evicted_keys().load(Ordering::Relaxed)).await?
+            }
             _ => return util::err(ERR_UNKNOWN_METRIC),
         }
         Ok(())
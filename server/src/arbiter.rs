@@ -27,12 +27,18 @@
 use {
     crate::{
         auth::AuthProvider,
-        config::{ConfigurationSet, SnapshotConfig, SnapshotPref},
+        config::{
+            ConfigurationSet, EncryptionConfig, ReplicationConfig, SnapshotConfig, SnapshotPref,
+        },
         corestore::Corestore,
         dbnet,
         diskstore::flock::FileLock,
+        registry::{self, ReplicaState},
         services,
-        storage::v1::sengine::SnapshotEngine,
+        storage::v1::{
+            encryption::{self, Key},
+            sengine::SnapshotEngine,
+        },
         util::{
             error::{Error, SkyResult},
             os::TerminationSignal,
@@ -58,8 +64,12 @@ pub async fn run(
         bgsave,
         snapshot,
         maxcon,
+        maxmemory,
         auth,
         protocol,
+        wal,
+        replication,
+        encryption,
         ..
     }: ConfigurationSet,
     restore_filepath: Option<String>,
@@ -71,17 +81,30 @@ pub async fn run(
         SnapshotConfig::Disabled => SnapshotEngine::new_disabled(),
     };
     let engine = Arc::new(engine);
+    // load the encryption key before anything is read from (or written to) the disk
+    if let EncryptionConfig::Enabled(keyfile) = encryption {
+        let key = Key::from_file(&keyfile).map_err(|e| {
+            Error::ioerror_extra(e, format!("reading the encryption key from `{keyfile}`"))
+        })?;
+        encryption::set_key(key);
+        log::info!("Data files will be encrypted at rest");
+    }
+    registry::set_max_memory(maxmemory);
+    if maxmemory != 0 {
+        log::info!("Volatile models are limited to {maxmemory} bytes");
+    }
     // restore data
     services::restore_data(restore_filepath)
         .map_err(|e| Error::ioerror_extra(e, "restoring data from backup"))?;
     // init the store
-    let db = Corestore::init_with_snapcfg(engine.clone())?;
+    let db = Corestore::init_with_snapcfg(engine.clone(), wal)?;
     // refresh the snapshotengine state
     engine.parse_dir()?;
     let auth_provider = match auth.origin_key {
         Some(key) => {
             let authref = db.get_store().setup_auth();
-            AuthProvider::new(authref, Some(key.into_inner()))
+            let grantref = db.get_store().setup_grants();
+            AuthProvider::new(authref, grantref, Some(key.into_inner()))
         }
         None => AuthProvider::new_disabled(),
     };
@@ -98,6 +121,23 @@ pub async fn run(
         snapshot,
         signal.subscribe(),
     ));
+    let expiry_handle = tokio::spawn(services::expiry::expiry_reaper(
+        db.clone(),
+        signal.subscribe(),
+    ));
+    let wal_handle = tokio::spawn(services::wal::wal_syncer(db.clone(), signal.subscribe()));
+    let replica_handle = match replication {
+        ReplicationConfig::Follower(pref) => {
+            log::info!("Running as a read-only follower of {}", pref.leader);
+            registry::set_replica_state(ReplicaState::Offline);
+            Some(tokio::spawn(services::replica::replicate(
+                db.clone(),
+                pref,
+                signal.subscribe(),
+            )))
+        }
+        ReplicationConfig::Leader => None,
+    };
 
     // bind to signals
     let termsig =
@@ -121,11 +161,18 @@ pub async fn run(
     log::info!("Signalling all workers to shut down");
     // drop the signal and let others exit
     drop(signal);
+    // end the streams of our followers (if any)
+    db.get_wal().close_feed();
     server.finish_with_termsig().await;
 
     // wait for the background services to terminate
     let _ = snapshot_handle.await;
     let _ = bgsave_handle.await;
+    let _ = expiry_handle.await;
+    let _ = wal_handle.await;
+    if let Some(replica_handle) = replica_handle {
+        let _ = replica_handle.await;
+    }
     Ok(db)
 }
 
@@ -201,7 +248,7 @@ pub fn finalize_shutdown(corestore: Corestore, pid_file: FileLock) {
             }
         }
     });
-    okay &= services::pre_shutdown_cleanup(pid_file, Some(corestore.get_store()));
+    okay &= services::pre_shutdown_cleanup(pid_file, Some(&corestore.clone_store()));
     if okay {
         log::info!("Goodbye :)");
     } else {
@@ -0,0 +1,249 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Grants
+//!
+//! Users other than root can only do what they have been granted. A grant is a [`Role`]
+//! which (except for `admin`) is scoped to either a single keyspace or to every keyspace
+//! (`*`):
+//! - `read`: actions and statements that don't modify data
+//! - `write`: everything `read` allows, and modifying data
+//! - `admin`: everything, including DDL, `FLUSHDB` and managing snapshots
+//!
+//! Only root can manage users and grants. Users that were created before grants existed
+//! have no entry in the grantmap and are treated as admins, while users created with
+//! `AUTH ADDUSER` start out with no grants at all
+
+/// The scope that matches every keyspace
+const SCOPE_ALL: &[u8] = b"*";
+/// The maximum length of a keyspace name
+const MAX_SCOPE_LEN: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u8)]
+/// A role that can be granted to a user. A higher role includes all the lower ones
+pub enum Role {
+    Read = 0,
+    Write = 1,
+    Admin = 2,
+}
+
+impl Role {
+    pub fn from_name(name: &[u8]) -> Option<Self> {
+        match name.to_ascii_lowercase().as_slice() {
+            b"read" => Some(Self::Read),
+            b"write" => Some(Self::Write),
+            b"admin" => Some(Self::Admin),
+            _ => None,
+        }
+    }
+    const fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(Self::Read),
+            1 => Some(Self::Write),
+            2 => Some(Self::Admin),
+            _ => None,
+        }
+    }
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::Write => "write",
+            Self::Admin => "admin",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The access that a query needs before it can be dispatched
+pub enum Access {
+    /// Anyone who is logged in
+    Any,
+    Read,
+    Write,
+    Admin,
+}
+
+impl Access {
+    const fn min_role(self) -> Option<Role> {
+        match self {
+            Self::Any => None,
+            Self::Read => Some(Role::Read),
+            Self::Write => Some(Role::Write),
+            Self::Admin => Some(Role::Admin),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A role granted for a keyspace, or for every keyspace if `space` is `None`
+pub struct Grant<'a> {
+    role: Role,
+    space: Option<&'a [u8]>,
+}
+
+/// `admin` for every keyspace; this is what users without an entry in the grantmap have
+const GRANT_ADMIN: Grant<'static> = Grant {
+    role: Role::Admin,
+    space: None,
+};
+
+impl<'a> Grant<'a> {
+    /// Parse a grant from a role name and a keyspace. `read` and `write` need a keyspace (or
+    /// `*`) while `admin` doesn't take one
+    pub fn parse(role: &[u8], space: Option<&'a [u8]>) -> Option<Self> {
+        let role = Role::from_name(role)?;
+        let space = match (role, space) {
+            (Role::Admin, None) => None,
+            (Role::Admin, Some(_)) | (_, None) => return None,
+            (_, Some(SCOPE_ALL)) => None,
+            (_, Some(space)) if Self::is_valid_scope(space) => Some(space),
+            _ => return None,
+        };
+        Some(Self { role, space })
+    }
+    fn is_valid_scope(space: &[u8]) -> bool {
+        !space.is_empty()
+            && space.len() <= MAX_SCOPE_LEN
+            && space
+                .iter()
+                .all(|b| b.is_ascii_alphanumeric() || *b == b'_')
+    }
+    fn permits(&self, space: Option<&[u8]>, access: Access) -> bool {
+        match access.min_role() {
+            None => true,
+            Some(needed) => self.role >= needed && (self.space.is_none() || self.space == space),
+        }
+    }
+    /// Returns `admin`, `<role>:*` or `<role>:<keyspace>`
+    pub fn describe(&self) -> String {
+        match (self.role, self.space) {
+            (Role::Admin, _) => self.role.name().to_owned(),
+            (role, None) => format!("{}:*", role.name()),
+            (role, Some(space)) => format!("{}:{}", role.name(), String::from_utf8_lossy(space)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// The grants of a user. These are kept encoded as `([1B role][1B scope len][scope])*`
+/// (where a zero length scope means every keyspace) so that they can be stored as-is
+pub struct Grants {
+    raw: Vec<u8>,
+}
+
+impl Grants {
+    /// The grants of a user that doesn't have an entry in the grantmap
+    pub fn legacy() -> Self {
+        let mut slf = Self::default();
+        slf.insert(GRANT_ADMIN);
+        slf
+    }
+    pub fn from_raw(raw: Vec<u8>) -> Self {
+        Self { raw }
+    }
+    /// Returns true if every encoded grant is well-formed
+    pub fn is_valid(&self) -> bool {
+        let mut raw = self.raw.as_slice();
+        while let [role, len, rest @ ..] = raw {
+            let len = *len as usize;
+            let role_okay = match Role::from_code(*role) {
+                Some(Role::Admin) => len == 0,
+                Some(_) => {
+                    len == 0
+                        || matches!(rest.get(..len), Some(scope) if Grant::is_valid_scope(scope))
+                }
+                None => false,
+            };
+            if !role_okay || rest.len() < len {
+                return false;
+            }
+            raw = &rest[len..];
+        }
+        raw.is_empty()
+    }
+    pub fn iter(&self) -> GrantIter<'_> {
+        GrantIter {
+            raw: self.raw.as_slice(),
+        }
+    }
+    /// Returns true if any of the grants gives the required access to the given keyspace
+    pub fn permits(&self, space: Option<&[u8]>, access: Access) -> bool {
+        access == Access::Any || self.iter().any(|grant| grant.permits(space, access))
+    }
+    /// Returns true if the grant wasn't already present
+    pub fn insert(&mut self, grant: Grant<'_>) -> bool {
+        if self.iter().any(|g| g == grant) {
+            return false;
+        }
+        let space = grant.space.unwrap_or_default();
+        self.raw.push(grant.role as u8);
+        self.raw.push(space.len() as u8);
+        self.raw.extend_from_slice(space);
+        true
+    }
+    /// Returns true if the grant was present
+    pub fn remove(&mut self, grant: Grant<'_>) -> bool {
+        let mut retained = Self::default();
+        self.iter().filter(|g| *g != grant).for_each(|g| {
+            retained.insert(g);
+        });
+        let removed = retained.raw.len() != self.raw.len();
+        *self = retained;
+        removed
+    }
+    pub fn describe(&self) -> Vec<String> {
+        self.iter().map(|grant| grant.describe()).collect()
+    }
+}
+
+impl AsRef<[u8]> for Grants {
+    fn as_ref(&self) -> &[u8] {
+        &self.raw
+    }
+}
+
+/// An iterator over encoded grants. Stops early on malformed data
+pub struct GrantIter<'a> {
+    raw: &'a [u8],
+}
+
+impl<'a> Iterator for GrantIter<'a> {
+    type Item = Grant<'a>;
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.raw {
+            [role, len, rest @ ..] if rest.len() >= *len as usize => {
+                let (space, rest) = rest.split_at(*len as usize);
+                self.raw = rest;
+                Some(Grant {
+                    role: Role::from_code(*role)?,
+                    space: if space.is_empty() { None } else { Some(space) },
+                })
+            }
+            _ => None,
+        }
+    }
+}
@@ -33,17 +33,22 @@
  * accounts. On claiming the root account, this key is issued
  *
  * When the root account is claimed, it can be used to create "standard users". standard
- * users have access to everything but the ability to create/revoke other users
+ * users can only do what root has granted them (see the `grants` module), and can never
+ * create/revoke other users
 */
 
+pub mod grants;
 mod keys;
 pub mod provider;
-pub use provider::{AuthProvider, Authmap};
+pub use provider::{AuthProvider, Authmap, Grantmap};
 
 #[cfg(test)]
 mod tests;
 
-use crate::dbnet::prelude::*;
+use {
+    self::grants::Grant,
+    crate::{actions::ActionResult, dbnet::prelude::*},
+};
 
 const AUTH_CLAIM: &[u8] = b"claim";
 const AUTH_LOGIN: &[u8] = b"login";
@@ -53,6 +58,8 @@ const AUTH_DELUSER: &[u8] = b"deluser";
 const AUTH_RESTORE: &[u8] = b"restore";
 const AUTH_LISTUSER: &[u8] = b"listuser";
 const AUTH_WHOAMI: &[u8] = b"whoami";
+const AUTH_GRANT: &[u8] = b"grant";
+const AUTH_REVOKE: &[u8] = b"revoke";
 
 action! {
     /// Handle auth. Should have passed the `auth` token
@@ -88,6 +95,18 @@ action! {
             AUTH_RESTORE => self::auth_restore(con, auth, &mut iter).await,
             AUTH_LISTUSER => self::auth_listuser(con, auth, &mut iter).await,
             AUTH_WHOAMI => self::auth_whoami(con, auth, &mut iter).await,
+            AUTH_GRANT => {
+                let (user, grant) = self::parse_grant::<P>(&mut iter)?;
+                auth.provider().grant::<P>(user, grant)?;
+                con._write_raw(P::RCODE_OKAY).await?;
+                Ok(())
+            }
+            AUTH_REVOKE => {
+                let (user, grant) = self::parse_grant::<P>(&mut iter)?;
+                auth.provider().revoke::<P>(user, grant)?;
+                con._write_raw(P::RCODE_OKAY).await?;
+                Ok(())
+            }
             _ => util::err(P::RCODE_UNKNOWN_ACTION),
         }
     }
@@ -97,11 +116,15 @@ action! {
         Ok(())
     }
     fn auth_listuser(con: &mut Connection<C, P>, auth: &mut AuthProviderHandle, iter: &mut ActionIter<'_>) {
-        ensure_boolean_or_aerr::<P>(ActionIter::is_empty(iter))?;
-        let usernames = auth.provider().collect_usernames::<P>()?;
-        con.write_typed_non_null_array_header(usernames.len(), b'+').await?;
-        for username in usernames {
-            con.write_typed_non_null_array_element(username.as_bytes()).await?;
+        // either list all the users, or list the grants of a single user
+        let items = match iter.len() {
+            0 => auth.provider().collect_usernames::<P>()?,
+            1 => auth.provider().collect_grants::<P>(unsafe { iter.next_unchecked() })?,
+            _ => return util::err(P::RCODE_ACTION_ERR),
+        };
+        con.write_typed_non_null_array_header(items.len(), b'+').await?;
+        for item in items {
+            con.write_typed_non_null_array_element(item.as_bytes()).await?;
         }
         Ok(())
     }
@@ -157,3 +180,15 @@ action! {
         Ok(())
     }
 }
+
+/// Parse the `<user> <role> [<space>]` arguments of `AUTH GRANT` and `AUTH REVOKE`
+fn parse_grant<'a, P: ProtocolSpec>(
+    iter: &mut ActionIter<'a>,
+) -> ActionResult<(&'a [u8], Grant<'a>)> {
+    ensure_boolean_or_aerr::<P>(iter.len() == 2 || iter.len() == 3)?;
+    let (user, role) = unsafe { (iter.next_unchecked(), iter.next_unchecked()) };
+    match Grant::parse(role, iter.next()) {
+        Some(grant) => Ok((user, grant)),
+        None => util::err(P::AUTH_ERROR_ILLEGAL_GRANT),
+    }
+}
@@ -25,10 +25,13 @@
 */
 
 use {
-    super::keys,
+    super::{
+        grants::{Access, Grant, Grants},
+        keys,
+    },
     crate::{
         actions::{ActionError, ActionResult},
-        corestore::{array::Array, htable::Coremap},
+        corestore::{array::Array, htable::Coremap, map::bref::Entry},
         protocol::interface::ProtocolSpec,
         util::err,
     },
@@ -55,6 +58,8 @@ uninit_array! {
 }
 /// The root user
 const USER_ROOT: AuthID = unsafe { AuthID::from_const(USER_ROOT_ARRAY, 4) };
+/// What `AUTH LISTUSER` shows for root's grants
+const USER_ROOT_GRANTS: &str = "root";
 
 /// An authn ID
 type AuthID = Array<u8, AUTHID_SIZE>;
@@ -62,6 +67,8 @@ type AuthID = Array<u8, AUTHID_SIZE>;
 pub type Authkey = [u8; AUTHKEY_SIZE];
 /// Authmap
 pub type Authmap = Arc<Coremap<AuthID, Authkey>>;
+/// Grantmap
+pub type Grantmap = Arc<Coremap<AuthID, Grants>>;
 
 /// The authn/authz provider
 ///
@@ -71,32 +78,40 @@ pub struct AuthProvider {
     whoami: Option<AuthID>,
     /// a map of users
     authmap: Authmap,
+    /// a map of the grants of each user
+    grantmap: Grantmap,
 }
 
 impl AuthProvider {
-    fn _new(authmap: Authmap, whoami: Option<AuthID>, origin: Option<Authkey>) -> Self {
+    fn _new(
+        authmap: Authmap,
+        grantmap: Grantmap,
+        whoami: Option<AuthID>,
+        origin: Option<Authkey>,
+    ) -> Self {
         Self {
             authmap,
+            grantmap,
             whoami,
             origin,
         }
     }
     /// New provider with no origin-key
     pub fn new_disabled() -> Self {
-        Self::_new(Default::default(), None, None)
+        Self::_new(Default::default(), Default::default(), None, None)
     }
     /// New provider with zero users
     #[cfg(test)]
     pub fn new_blank(origin: Option<Authkey>) -> Self {
-        Self::_new(Default::default(), None, origin)
+        Self::_new(Default::default(), Default::default(), None, origin)
     }
     /// New provider with users from the provided map
     ///
     /// ## Test suite
     /// The testsuite creates users `root` and `testuser`; this **does not** apply to
     /// release mode
-    pub fn new(authmap: Arc<Coremap<AuthID, Authkey>>, origin: Option<Authkey>) -> Self {
-        let slf = Self::_new(authmap, None, origin);
+    pub fn new(authmap: Authmap, grantmap: Grantmap, origin: Option<Authkey>) -> Self {
+        let slf = Self::_new(authmap, grantmap, None, origin);
         #[cfg(debug_assertions)]
         {
             // 'root' user in test mode
@@ -148,10 +163,10 @@ impl AuthProvider {
     }
     pub fn _claim_user<P: ProtocolSpec>(&self, claimant: &[u8]) -> ActionResult<String> {
         let (key, store) = keys::generate_full();
-        if self
-            .authmap
-            .true_if_insert(Self::try_auth_id::<P>(claimant)?, store)
-        {
+        let id = Self::try_auth_id::<P>(claimant)?;
+        if self.authmap.true_if_insert(id.clone(), store) {
+            // new users can't do anything until they're granted something
+            self.grantmap.upsert(id, Grants::default());
             Ok(key)
         } else {
             err(P::AUTH_ERROR_ALREADYCLAIMED)
@@ -246,6 +261,7 @@ impl AuthProvider {
             // can't delete root!
             err(P::AUTH_ERROR_FAILED_TO_DELETE_USER)
         } else if self.authmap.true_if_removed(user) {
+            self.grantmap.remove(user);
             Ok(())
         } else {
             err(P::AUTH_CODE_BAD_CREDENTIALS)
@@ -260,6 +276,81 @@ impl AuthProvider {
             .map(|kv| String::from_utf8_lossy(kv.key()).to_string())
             .collect())
     }
+    /// List the grants of the given user
+    pub fn collect_grants<P: ProtocolSpec>(&self, user: &[u8]) -> ActionResult<Vec<String>> {
+        self.ensure_root::<P>()?;
+        if user.eq(&USER_ROOT) {
+            Ok(vec![USER_ROOT_GRANTS.to_owned()])
+        } else if self.authmap.contains_key(user) {
+            Ok(self
+                .grantmap
+                .get(user)
+                .map(|grants| grants.describe())
+                .unwrap_or_else(|| Grants::legacy().describe()))
+        } else {
+            err(P::AUTH_CODE_BAD_CREDENTIALS)
+        }
+    }
+    pub fn grant<P: ProtocolSpec>(&self, user: &[u8], grant: Grant<'_>) -> ActionResult<()> {
+        self.update_grants::<P>(user, |grants| {
+            grants.insert(grant);
+        })
+    }
+    pub fn revoke<P: ProtocolSpec>(&self, user: &[u8], grant: Grant<'_>) -> ActionResult<()> {
+        self.update_grants::<P>(user, |grants| {
+            grants.remove(grant);
+        })
+    }
+    fn update_grants<P: ProtocolSpec>(
+        &self,
+        user: &[u8],
+        update: impl FnOnce(&mut Grants),
+    ) -> ActionResult<()> {
+        self.ensure_root::<P>()?;
+        if user.eq(&USER_ROOT) {
+            // root can always do everything
+            return err(P::AUTH_ERROR_ILLEGAL_GRANT);
+        }
+        let id = Self::try_auth_id::<P>(user)?;
+        if !self.authmap.contains_key(user) {
+            return err(P::AUTH_CODE_BAD_CREDENTIALS);
+        }
+        match self.grantmap.entry(id) {
+            Entry::Occupied(mut oe) => {
+                let mut grants = oe.value().clone();
+                update(&mut grants);
+                oe.insert(grants);
+            }
+            Entry::Vacant(ve) => {
+                let mut grants = Grants::legacy();
+                update(&mut grants);
+                ve.insert(grants);
+            }
+        }
+        Ok(())
+    }
+    /// Check if the current user has the given access to the given keyspace. This always
+    /// succeeds if authn is disabled or if the current user is root
+    pub fn ensure_access<P: ProtocolSpec>(
+        &self,
+        space: Option<&[u8]>,
+        access: Access,
+    ) -> ActionResult<()> {
+        if access == Access::Any || !self.is_enabled() {
+            return Ok(());
+        }
+        match self.whoami.as_ref() {
+            Some(user) if user.eq(&USER_ROOT) => Ok(()),
+            Some(user) => match self.grantmap.get(user) {
+                Some(grants) if grants.permits(space, access) => Ok(()),
+                // users without grants are from before grants existed, so they're admins. A
+                // session can outlive its user though, so make sure the user still exists
+                None if self.authmap.contains_key(user) => Ok(()),
+                _ => err(P::AUTH_CODE_PERMS),
+            },
+            None => err(P::AUTH_CODE_PERMS),
+        }
+    }
     /// Return the AuthID of the current user
     pub fn whoami<P: ProtocolSpec>(&self) -> ActionResult<String> {
         self.ensure_enabled::<P>()?;
@@ -274,6 +365,7 @@ impl Clone for AuthProvider {
     fn clone(&self) -> Self {
         Self {
             authmap: self.authmap.clone(),
+            grantmap: self.grantmap.clone(),
             whoami: None,
             origin: self.origin,
         }
@@ -122,3 +122,199 @@ mod authn {
         );
     }
 }
+
+mod grants {
+    use crate::auth::grants::{Access, Grant, Grants};
+
+    #[test]
+    fn parse_grant() {
+        assert!(Grant::parse(b"admin", None).is_some());
+        assert!(Grant::parse(b"READ", Some(b"*")).is_some());
+        assert!(Grant::parse(b"write", Some(b"twitter")).is_some());
+        // admin is never scoped; read and write always are
+        assert!(Grant::parse(b"admin", Some(b"twitter")).is_none());
+        assert!(Grant::parse(b"read", None).is_none());
+        // bad roles and bad keyspace names
+        assert!(Grant::parse(b"superuser", None).is_none());
+        assert!(Grant::parse(b"read", Some(b"")).is_none());
+        assert!(Grant::parse(b"read", Some(b"twitter:tweets")).is_none());
+        assert!(Grant::parse(b"read", Some(&[b'a'; 65])).is_none());
+    }
+    #[test]
+    fn grants_permits() {
+        let mut grants = Grants::default();
+        assert!(grants.permits(Some(b"twitter"), Access::Any));
+        assert!(!grants.permits(Some(b"twitter"), Access::Read));
+        assert!(grants.insert(Grant::parse(b"write", Some(b"twitter")).unwrap()));
+        assert!(grants.insert(Grant::parse(b"read", Some(b"*")).unwrap()));
+        assert!(!grants.insert(Grant::parse(b"read", Some(b"*")).unwrap()));
+        assert!(grants.permits(Some(b"twitter"), Access::Write));
+        assert!(grants.permits(Some(b"default"), Access::Read));
+        assert!(grants.permits(None, Access::Read));
+        assert!(!grants.permits(Some(b"default"), Access::Write));
+        assert!(!grants.permits(None, Access::Write));
+        assert!(!grants.permits(Some(b"twitter"), Access::Admin));
+        assert_eq!(grants.describe(), vec!["write:twitter", "read:*"]);
+        assert!(grants.remove(Grant::parse(b"write", Some(b"twitter")).unwrap()));
+        assert!(!grants.remove(Grant::parse(b"write", Some(b"twitter")).unwrap()));
+        assert!(!grants.permits(Some(b"twitter"), Access::Write));
+        assert_eq!(grants.describe(), vec!["read:*"]);
+    }
+    #[test]
+    fn grants_legacy_is_admin() {
+        let grants = Grants::legacy();
+        assert!(grants.permits(None, Access::Admin));
+        assert!(grants.permits(Some(b"twitter"), Access::Write));
+        assert_eq!(grants.describe(), vec!["admin"]);
+    }
+    #[test]
+    fn grants_validity() {
+        assert!(Grants::default().is_valid());
+        assert!(Grants::legacy().is_valid());
+        assert!(Grants::from_raw(vec![0, 0, 1, 2, b'k', b's']).is_valid());
+        // bad role
+        assert!(!Grants::from_raw(vec![3, 0]).is_valid());
+        // scoped admin
+        assert!(!Grants::from_raw(vec![2, 1, b'k']).is_valid());
+        // truncated scope
+        assert!(!Grants::from_raw(vec![1, 4, b'k']).is_valid());
+        // trailing byte
+        assert!(!Grants::from_raw(vec![0, 0, 1]).is_valid());
+    }
+}
+
+mod authz {
+    use crate::{
+        actions::ActionError,
+        auth::{
+            grants::{Access, Grant},
+            AuthProvider,
+        },
+        protocol::{interface::ProtocolSpec, Skyhash2},
+    };
+
+    const ORIG: &[u8; 40] = b"c4299d190fb9a00626797fcc138c56eae9971664";
+
+    /// Returns a provider logged in as root, root's key and the key for `user`
+    fn provider_with_user(user: &[u8]) -> (AuthProvider, String, String) {
+        let mut provider = AuthProvider::new_blank(Some(*ORIG));
+        let rootkey = provider.claim_root::<Skyhash2>(ORIG).unwrap();
+        let userkey = provider.claim_user::<Skyhash2>(user).unwrap();
+        (provider, rootkey, userkey)
+    }
+    fn perms_err<T: std::fmt::Debug>(ret: Result<T, ActionError>) {
+        assert_eq!(
+            ret.unwrap_err(),
+            ActionError::ActionError(Skyhash2::AUTH_CODE_PERMS)
+        );
+    }
+    #[test]
+    fn disabled_allows_everything() {
+        let provider = AuthProvider::new_disabled();
+        provider
+            .ensure_access::<Skyhash2>(None, Access::Admin)
+            .unwrap();
+    }
+    #[test]
+    fn root_allows_everything() {
+        let (provider, _, _) = provider_with_user(b"sayan");
+        provider
+            .ensure_access::<Skyhash2>(None, Access::Admin)
+            .unwrap();
+        assert_eq!(
+            provider.collect_grants::<Skyhash2>(b"root").unwrap(),
+            vec!["root"]
+        );
+    }
+    #[test]
+    fn new_user_has_no_grants() {
+        let (mut provider, _, userkey) = provider_with_user(b"sayan");
+        assert!(provider
+            .collect_grants::<Skyhash2>(b"sayan")
+            .unwrap()
+            .is_empty());
+        provider
+            .login::<Skyhash2>(b"sayan", userkey.as_bytes())
+            .unwrap();
+        provider
+            .ensure_access::<Skyhash2>(Some(b"default"), Access::Any)
+            .unwrap();
+        perms_err(provider.ensure_access::<Skyhash2>(Some(b"default"), Access::Read));
+    }
+    #[test]
+    fn grant_and_revoke() {
+        let (mut provider, rootkey, userkey) = provider_with_user(b"sayan");
+        let write_twitter = Grant::parse(b"write", Some(b"twitter")).unwrap();
+        provider.grant::<Skyhash2>(b"sayan", write_twitter).unwrap();
+        assert_eq!(
+            provider.collect_grants::<Skyhash2>(b"sayan").unwrap(),
+            vec!["write:twitter"]
+        );
+        provider
+            .login::<Skyhash2>(b"sayan", userkey.as_bytes())
+            .unwrap();
+        provider
+            .ensure_access::<Skyhash2>(Some(b"twitter"), Access::Write)
+            .unwrap();
+        perms_err(provider.ensure_access::<Skyhash2>(Some(b"default"), Access::Read));
+        perms_err(provider.ensure_access::<Skyhash2>(Some(b"twitter"), Access::Admin));
+        // only root can manage grants
+        perms_err(provider.grant::<Skyhash2>(b"sayan", Grant::parse(b"admin", None).unwrap()));
+        // now revoke it as root
+        provider
+            .login::<Skyhash2>(b"root", rootkey.as_bytes())
+            .unwrap();
+        provider
+            .revoke::<Skyhash2>(b"sayan", write_twitter)
+            .unwrap();
+        provider
+            .login::<Skyhash2>(b"sayan", userkey.as_bytes())
+            .unwrap();
+        perms_err(provider.ensure_access::<Skyhash2>(Some(b"twitter"), Access::Read));
+    }
+    #[test]
+    fn grant_fail_bad_user() {
+        let (provider, _, _) = provider_with_user(b"sayan");
+        let admin = Grant::parse(b"admin", None).unwrap();
+        assert_eq!(
+            provider.grant::<Skyhash2>(b"nobody", admin).unwrap_err(),
+            ActionError::ActionError(Skyhash2::AUTH_CODE_BAD_CREDENTIALS)
+        );
+        assert_eq!(
+            provider.grant::<Skyhash2>(b"root", admin).unwrap_err(),
+            ActionError::ActionError(Skyhash2::AUTH_ERROR_ILLEGAL_GRANT)
+        );
+    }
+    #[test]
+    fn deluser_removes_grants() {
+        let (provider, _, _) = provider_with_user(b"sayan");
+        provider
+            .grant::<Skyhash2>(b"sayan", Grant::parse(b"admin", None).unwrap())
+            .unwrap();
+        provider.delete_user::<Skyhash2>(b"sayan").unwrap();
+        let _ = provider.claim_user::<Skyhash2>(b"sayan").unwrap();
+        assert!(provider
+            .collect_grants::<Skyhash2>(b"sayan")
+            .unwrap()
+            .is_empty());
+    }
+    #[test]
+    fn deluser_ends_access_for_open_sessions() {
+        let (provider, _, userkey) = provider_with_user(b"sayan");
+        provider
+            .grant::<Skyhash2>(b"sayan", Grant::parse(b"read", Some(b"*")).unwrap())
+            .unwrap();
+        // another connection, that shares the maps
+        let mut session = provider.clone();
+        session
+            .login::<Skyhash2>(b"sayan", userkey.as_bytes())
+            .unwrap();
+        session
+            .ensure_access::<Skyhash2>(Some(b"default"), Access::Read)
+            .unwrap();
+        provider.delete_user::<Skyhash2>(b"sayan").unwrap();
+        perms_err(session.ensure_access::<Skyhash2>(Some(b"default"), Access::Read));
+        perms_err(session.ensure_access::<Skyhash2>(Some(b"default"), Access::Write));
+        perms_err(session.ensure_access::<Skyhash2>(None, Access::Admin));
+    }
+}
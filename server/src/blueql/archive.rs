@@ -0,0 +1,153 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Archive statements
+//!
+//! `export model <model> to '<path>'` and `export space <space> to '<path>'` write a single
+//! model or space to an archive, while `import model <model> from '<path>'` and
+//! `import space <space> from '<path>'` create a new model or space (under any name) from one.
+//! Paths are relative to the archive directory, which is where archives from another
+//! instance should be copied to
+
+use {
+    super::{
+        ast::{Archived, Entity, Value},
+        dml,
+    },
+    crate::{
+        actions::{self, ActionResult},
+        corestore::memstore::ObjectID,
+        dbnet::{prelude::*, BufferedSocketStream},
+        storage::v1::{
+            archive::{self, Archive},
+            error::StorageEngineError,
+        },
+    },
+    core::str,
+    std::{io::ErrorKind, path::PathBuf},
+};
+
+/// Get the path of the archive in the archive directory
+fn resolve_path<P: ProtocolSpec>(path: &Value, params: &[&[u8]]) -> ActionResult<PathBuf> {
+    let path = dml::resolve(path, params);
+    let path = match str::from_utf8(&path) {
+        Ok(path) => path,
+        Err(_) => return util::err(P::RCODE_ENCODING_ERROR),
+    };
+    match archive::resolve_path(path) {
+        Some(path) => Ok(path),
+        None => util::err(P::RSTRING_ARCHIVE_ILLEGAL_PATH),
+    }
+}
+
+fn space_id<P: ProtocolSpec>(space: &[u8]) -> ActionResult<ObjectID> {
+    match ObjectID::try_from_slice(space) {
+        Some(space) => Ok(space),
+        None => util::err(P::RSTRING_CONTAINER_NAME_TOO_LONG),
+    }
+}
+
+/// Run an `export` statement
+pub async fn export<P: ProtocolSpec, C: BufferedSocketStream>(
+    handle: &Corestore,
+    con: &mut Connection<C, P>,
+    object: &Archived,
+    path: &Value,
+    params: &[&[u8]],
+) -> ActionResult<()> {
+    let path = self::resolve_path::<P>(path, params)?;
+    let ret = match object {
+        Archived::Model(entity) => {
+            let table = actions::translate_ddl_error::<P, _>(handle.get_table(entity))?;
+            let tblid = match entity {
+                Entity::Full(_, tblid) | Entity::Current(tblid) => unsafe {
+                    // SAFETY: the table exists, so the name is short enough
+                    ObjectID::from_slice(tblid.as_slice())
+                },
+            };
+            tokio::task::spawn_blocking(move || archive::export_model(&path, &tblid, table)).await
+        }
+        Archived::Space(space) => {
+            let space = self::space_id::<P>(unsafe { space.as_slice() })?;
+            let keyspace = match handle.get_keyspace(&space) {
+                Some(keyspace) => keyspace,
+                None => return util::err(P::RSTRING_CONTAINER_NOT_FOUND),
+            };
+            tokio::task::spawn_blocking(move || archive::export_space(&path, &keyspace)).await
+        }
+    };
+    match ret.expect("Something caused the export task to panic") {
+        Ok(()) => con._write_raw(P::RCODE_OKAY).await?,
+        Err(e) => {
+            log::error!("Failed to write archive: {e}");
+            con._write_raw(P::RCODE_SERVER_ERR).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Run an `import` statement
+pub async fn import<P: ProtocolSpec, C: BufferedSocketStream>(
+    handle: &Corestore,
+    con: &mut Connection<C, P>,
+    object: &Archived,
+    path: &Value,
+    params: &[&[u8]],
+) -> ActionResult<()> {
+    let path = self::resolve_path::<P>(path, params)?;
+    let space = match object {
+        Archived::Space(space) => Some(self::space_id::<P>(unsafe { space.as_slice() })?),
+        Archived::Model(_) => None,
+    };
+    let archive = tokio::task::spawn_blocking(move || archive::import(&path))
+        .await
+        .expect("Something caused the import task to panic");
+    let archive = match archive {
+        Ok(archive) => archive,
+        Err(StorageEngineError::IoErrorExtra(e, _)) if e.kind() == ErrorKind::NotFound => {
+            return util::err(P::RSTRING_ARCHIVE_NOT_FOUND)
+        }
+        Err(e @ (StorageEngineError::IoError(_) | StorageEngineError::IoErrorExtra(..))) => {
+            log::error!("Failed to read archive: {e}");
+            return util::err(P::RCODE_SERVER_ERR);
+        }
+        Err(e) => {
+            log::warn!("Refused to import archive: {e}");
+            return util::err(P::RSTRING_BAD_ARCHIVE);
+        }
+    };
+    let ret = match (object, space, archive) {
+        (Archived::Model(entity), _, Archive::Model(table)) => {
+            handle.create_table_from(entity, table)
+        }
+        (_, Some(space), Archive::Space(keyspace)) => handle.create_keyspace_from(space, keyspace),
+        // a model archive can't be imported as a space, and the other way round
+        _ => return util::err(P::RSTRING_BAD_ARCHIVE),
+    };
+    actions::translate_ddl_error::<P, ()>(ret)?;
+    con._write_raw(P::RCODE_OKAY).await?;
+    Ok(())
+}
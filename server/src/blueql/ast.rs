@@ -30,8 +30,16 @@ use {
         lexer::{Keyword, Lexer, Token, Type, TypeExpression},
         RawSlice,
     },
-    crate::util::{compiler, Life},
-    core::{marker::PhantomData, mem::transmute, ptr},
+    crate::{
+        corestore::table::Table,
+        kvengine::{
+            eviction::EvictionPolicy,
+            rowmap::{Field, Schema},
+            types::FieldType,
+        },
+        util::{compiler, Life},
+    },
+    core::{marker::PhantomData, mem::transmute},
 };
 
 #[derive(Debug)]
@@ -41,11 +49,16 @@ use {
 pub enum Statement {
     /// Create a new space with the provided ID
     CreateSpace(RawSlice),
-    /// Create a new model with the provided configuration
+    /// Create a new model with the provided configuration. Only volatile models can have
+    /// an eviction policy other than `noeviction`, only key/value models can be ordered and
+    /// only list models can have a maximum (list) length
     CreateModel {
         entity: Entity,
         model: FieldConfig,
+        ordered: bool,
+        max_len: Option<usize>,
         volatile: bool,
+        eviction: EvictionPolicy,
     },
     /// Drop the given model
     DropModel { entity: Entity, force: bool },
@@ -59,6 +72,27 @@ pub enum Statement {
     InspectSpaces,
     /// Switch to the given entity
     Use(Entity),
+    /// Insert a new row into the given model
+    Insert { entity: Entity, values: Vec<Value> },
+    /// Select a field from the row matching the clause
+    Select {
+        entity: Entity,
+        field: RawSlice,
+        clause: WhereClause,
+    },
+    /// Set a field of the row matching the clause
+    Update {
+        entity: Entity,
+        field: RawSlice,
+        values: Vec<Value>,
+        clause: WhereClause,
+    },
+    /// Delete the row matching the clause
+    Delete { entity: Entity, clause: WhereClause },
+    /// Write a space or a model to the archive at the given path
+    Export { object: Archived, path: Value },
+    /// Create a space or a model from the archive at the given path
+    Import { object: Archived, path: Value },
 }
 
 pub type StatementLT<'a> = Life<'a, Statement>;
@@ -77,6 +111,34 @@ impl Entity {
     }
 }
 
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+/// What an archive holds
+pub enum Archived {
+    Space(RawSlice),
+    Model(Entity),
+}
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+/// A value used in a DML statement
+pub enum Value {
+    /// A string literal
+    String(String),
+    /// A numeric literal
+    Number(u64),
+    /// A `?` placeholder, bound to the n-th argument sent along with the query
+    Param(usize),
+}
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+/// A `where <field> = <value>` clause
+pub struct WhereClause {
+    pub field: RawSlice,
+    pub value: Value,
+}
+
 #[derive(Debug)]
 #[cfg_attr(test, derive(PartialEq))]
 /// The field configuration used when declaring the fields for a model
@@ -98,23 +160,53 @@ impl FieldConfig {
     // TODO(@ohsayan): Completely deprecate the model-code based API
     pub fn get_model_code(&self) -> LangResult<u8> {
         let Self { types, names } = self;
+        if names.is_empty() && types.len() == 2 && is_collection(types[1].0[0]) {
+            // the key, the fields and the values of a map, the members of a set and the items of
+            // a queue can only be strings or binaries
+            return match (types[0].0.as_slice(), types[1].0.as_slice()) {
+                ([key], [Type::Map, field, value])
+                    if is_blob(*key) && is_blob(*field) && is_blob(*value) =>
+                {
+                    Ok(Table::map_model_code(
+                        *key == Type::String,
+                        *field == Type::String,
+                        *value == Type::String,
+                    ))
+                }
+                ([key], [Type::Set, member]) if is_blob(*key) && is_blob(*member) => Ok(
+                    Table::set_model_code(*key == Type::String, *member == Type::String),
+                ),
+                ([key], [Type::SortedSet, member]) if is_blob(*key) && is_blob(*member) => Ok(
+                    Table::sorted_set_model_code(*key == Type::String, *member == Type::String),
+                ),
+                ([key], [Type::Queue, item]) if is_blob(*key) && is_blob(*item) => Ok(
+                    Table::queue_model_code(*key == Type::String, *item == Type::String),
+                ),
+                _ => Err(LangError::UnsupportedModelDeclaration),
+            };
+        }
         let invalid_expr = {
-            // the model API doesn't support named fields (it's super limited; we need to drop it)
+            // the model API doesn't support named fields (it's super limited; we need to drop it).
+            // models with named fields are described by a schema instead
             !names.is_empty()
             || types.len() != 2
             // the key type cannot be compound
             || types[0].0.len() != 1
-            // the key type cannot be a list
+            // the key type cannot be a list, a map, a set or a queue
             || types[0].0[0] == Type::List
+            || is_collection(types[0].0[0])
             // the value cannot have a depth more than two
             || types[1].0.len() > 2
-            // if the value is a string or binary, it cannot have a depth more than 1
-            || ((types[1].0[0] == Type::Binary || types[1].0[0] == Type::String) && types[1].0.len() != 1)
+            // if the value is a scalar, it cannot have a depth more than 1
+            || (types[1].0[0] != Type::List && types[1].0.len() != 1)
             // if the value is a list, it must have a depth of two
             || (types[1].0[0] == Type::List && types[1].0.len() != 2)
             // if the value is a list, the type argument cannot be a list (it's stupid, I know; that's exactly
             // why I'll be ditching this API in the next two PRs)
             || (types[1].0[0] == Type::List && types[1].0[1] == Type::List)
+            // lists can only hold strings or binaries and can't be keyed by numbers
+            || (types[1].0[0] == Type::List
+                && (!is_blob(types[1].0[1]) || is_numeric(types[0].0[0])))
         };
         if compiler::unlikely(invalid_expr) {
             // the value type cannot have a depth more than 2
@@ -126,6 +218,12 @@ impl FieldConfig {
             let k_enc = key_expr[0] == Type::String;
             let v_enc = value_expr[1] == Type::String;
             Ok(((k_enc as u8) << 1) + (v_enc as u8) + 4)
+        } else if is_numeric(key_expr[0]) || is_numeric(value_expr[0]) {
+            let (t_k, t_v) = (scalar_type(key_expr[0]), scalar_type(value_expr[0]));
+            match (t_k, t_v) {
+                (Some(t_k), Some(t_v)) => Ok(Table::typed_kv_model_code(t_k, t_v)),
+                _ => Err(LangError::UnsupportedModelDeclaration),
+            }
         } else {
             let k_enc = key_expr[0] == Type::String;
             let v_enc = value_expr[0] == Type::String;
@@ -133,6 +231,53 @@ impl FieldConfig {
             Ok((ret & 1) + ((k_enc as u8) << 1))
         }
     }
+    /// Returns the schema for a model with named fields. The first field is the primary key
+    /// and no field can be a `list`
+    pub fn get_schema(&self) -> LangResult<Schema> {
+        let Self { types, names } = self;
+        if compiler::unlikely(names.is_empty() || names.len() != types.len()) {
+            return Err(LangError::UnsupportedModelDeclaration);
+        }
+        let mut fields = Vec::with_capacity(names.len());
+        for (name, ty) in names.iter().zip(types) {
+            let ty = match ty.0.as_slice() {
+                [ty] => scalar_type(*ty).ok_or(LangError::UnsupportedModelDeclaration)?,
+                _ => return Err(LangError::UnsupportedModelDeclaration),
+            };
+            let name = core::str::from_utf8(unsafe { name.as_slice() })
+                .map_err(|_| LangError::BadExpression)?;
+            fields.push(Field::new(name, ty));
+        }
+        // two fields with the same name
+        Schema::new(fields).ok_or(LangError::BadExpression)
+    }
+}
+
+/// Returns the type of a key, a value or a field declared as `ty`. Returns `None` for lists
+/// and the other collections
+const fn scalar_type(ty: Type) -> Option<FieldType> {
+    match ty {
+        Type::String => Some(FieldType::String),
+        Type::Binary => Some(FieldType::Binary),
+        Type::UInt64 => Some(FieldType::UInt64),
+        Type::SInt64 => Some(FieldType::SInt64),
+        Type::Float64 => Some(FieldType::Float64),
+        Type::List | Type::Map | Type::Set | Type::SortedSet | Type::Queue => None,
+    }
+}
+
+const fn is_numeric(ty: Type) -> bool {
+    matches!(ty, Type::UInt64 | Type::SInt64 | Type::Float64)
+}
+
+/// Returns true for a map, either kind of set or a queue
+const fn is_collection(ty: Type) -> bool {
+    matches!(ty, Type::Map | Type::Set | Type::SortedSet | Type::Queue)
+}
+
+/// Returns true for a string or a binary
+const fn is_blob(ty: Type) -> bool {
+    matches!(ty, Type::String | Type::Binary)
 }
 
 // expect state
@@ -154,6 +299,7 @@ enum Expect {
 pub struct Compiler<'a> {
     cursor: *const Token,
     end_ptr: *const Token,
+    params: usize,
     _lt: PhantomData<&'a [u8]>,
 }
 
@@ -212,7 +358,8 @@ impl<'a> Compiler<'a> {
     /// will forward the cursor
     fn next(&mut self) -> Option<Token> {
         if self.not_exhausted() {
-            let r = Some(unsafe { ptr::read(self.cursor) });
+            // tokens may own their data (string literals), so we clone instead of reading out
+            let r = Some(unsafe { self.deref_cursor() }.clone());
             unsafe { self.incr_cursor() };
             r
         } else {
@@ -222,7 +369,7 @@ impl<'a> Compiler<'a> {
     #[inline(always)]
     fn next_result(&mut self) -> LangResult<Token> {
         if compiler::likely(self.not_exhausted()) {
-            let r = unsafe { ptr::read(self.cursor) };
+            let r = unsafe { self.deref_cursor() }.clone();
             unsafe { self.incr_cursor() };
             Ok(r)
         } else {
@@ -238,6 +385,17 @@ impl<'a> Compiler<'a> {
         }
     }
     #[inline(always)]
+    /// Move past the given token, failing if the token ahead is something else
+    fn expect_next(&mut self, tok: &Token) -> LangResult<()> {
+        if compiler::likely(self.next_eq(tok)) {
+            Ok(())
+        } else if self.not_exhausted() {
+            Err(LangError::InvalidSyntax)
+        } else {
+            Err(LangError::UnexpectedEOF)
+        }
+    }
+    #[inline(always)]
     /// Returns the remaining number of tokens
     fn remaining(&self) -> usize {
         self.end_ptr as usize - self.cursor as usize
@@ -252,8 +410,8 @@ impl<'a> Compiler<'a> {
         Self::compile_with_extra(src, 0)
     }
     #[inline(always)]
-    /// Compile the given BlueQL source with optionally supplied extra arguments
-    /// HACK: Just helps us omit an additional check
+    /// Compile the given BlueQL source with optionally supplied extra arguments. Every extra
+    /// argument must be bound to a `?` placeholder in the statement
     pub fn compile_with_extra(src: &'a [u8], len: usize) -> LangResult<Life<'a, Statement>> {
         let tokens = Lexer::lex(src)?;
         Self::new(&tokens).eval(len).map(Life::new)
@@ -264,6 +422,7 @@ impl<'a> Compiler<'a> {
             Self {
                 cursor: tokens.as_ptr(),
                 end_ptr: tokens.as_ptr().add(tokens.len()),
+                params: 0,
                 _lt: PhantomData,
             }
         }
@@ -277,11 +436,17 @@ impl<'a> Compiler<'a> {
                 Token::Keyword(Keyword::Drop) => self.parse_drop0(),
                 Token::Keyword(Keyword::Inspect) => self.parse_inspect0(),
                 Token::Keyword(Keyword::Use) => self.parse_use0(),
+                Token::Keyword(Keyword::Insert) => self.parse_insert0(),
+                Token::Keyword(Keyword::Select) => self.parse_select0(),
+                Token::Keyword(Keyword::Update) => self.parse_update0(),
+                Token::Keyword(Keyword::Delete) => self.parse_delete0(),
+                Token::Keyword(Keyword::Export) => self.parse_export0(),
+                Token::Keyword(Keyword::Import) => self.parse_import0(),
                 _ => Err(LangError::ExpectedStatement),
             },
             None => Err(LangError::UnexpectedEOF),
         };
-        if compiler::likely(self.remaining() == 0 && extra_len == 0) {
+        if compiler::likely(self.remaining() == 0 && extra_len == self.params) {
             stmt
         } else {
             Err(LangError::InvalidSyntax)
@@ -394,12 +559,36 @@ impl<'a> Compiler<'a> {
         // without introducing some funky naming conventions ($<field_number> if you don't have the
         // right name sounds like an outrageous idea)
         is_good_expr &= fc.names.is_empty() || fc.names.len() == fc.types.len();
+        let ordered = self.next_eq(&Token::Keyword(Keyword::Ordered));
+        let mut max_len = None;
+        if self.next_eq(&Token::Keyword(Keyword::MaxLen)) {
+            if let Some(Token::Number(len)) = self.next() {
+                max_len = usize::try_from(len).ok().filter(|len| *len != 0);
+            }
+            is_good_expr &= max_len.is_some();
+        }
         let volatile = self.next_eq(&Token::Keyword(Keyword::Volatile));
+        let mut eviction = EvictionPolicy::NoEviction;
+        if volatile && self.next_eq(&Token::Keyword(Keyword::Evict)) {
+            match self.next() {
+                Some(Token::Identifier(policy)) => {
+                    let policy = unsafe { policy.as_slice() }.to_ascii_lowercase();
+                    match EvictionPolicy::from_name(&policy) {
+                        Some(policy) => eviction = policy,
+                        None => is_good_expr = false,
+                    }
+                }
+                _ => is_good_expr = false,
+            }
+        }
         if compiler::likely(is_good_expr) {
             Ok(Statement::CreateModel {
                 entity,
                 model: fc,
+                ordered,
+                max_len,
                 volatile,
+                eviction,
             })
         } else {
             Err(LangError::BadExpression)
@@ -408,6 +597,9 @@ impl<'a> Compiler<'a> {
     #[inline(always)]
     /// Parse a type expression return a `TypeExpression`
     fn parse_type_expression(&mut self, first_type: Type) -> LangResult<TypeExpression> {
+        if first_type == Type::Map {
+            return self.parse_map_type_expression();
+        }
         let mut expr = Vec::with_capacity(2);
         expr.push(first_type);
 
@@ -450,6 +642,27 @@ impl<'a> Compiler<'a> {
             Err(LangError::InvalidSyntax)
         }
     }
+    /// Parse the type arguments of a map (`map<ty, ty>`), which can't be compound
+    fn parse_map_type_expression(&mut self) -> LangResult<TypeExpression> {
+        let mut is_good_expr = self.next_eq(&Token::OpenAngular);
+        let field = self.next_type();
+        is_good_expr &= self.next_eq(&Token::Comma);
+        let value = self.next_type();
+        is_good_expr &= self.next_eq(&Token::CloseAngular);
+        match (field, value) {
+            (Some(field), Some(value)) if is_good_expr => {
+                Ok(TypeExpression(vec![Type::Map, field, value]))
+            }
+            _ => Err(LangError::InvalidSyntax),
+        }
+    }
+    /// Returns the next token if it's a type
+    fn next_type(&mut self) -> Option<Type> {
+        match self.next() {
+            Some(Token::Keyword(Keyword::Type(ty))) => Some(ty),
+            _ => None,
+        }
+    }
     #[inline(always)]
     /// Parse a `create space` statement
     fn parse_create_space0(&mut self) -> LangResult<Statement> {
@@ -460,6 +673,125 @@ impl<'a> Compiler<'a> {
         }
     }
     #[inline(always)]
+    /// Parse `insert into <entity> (<value>, ...)`
+    fn parse_insert0(&mut self) -> LangResult<Statement> {
+        self.expect_next(&Token::Keyword(Keyword::Into))?;
+        let entity = self.parse_entity_name()?;
+        let values = self.parse_tuple()?;
+        Ok(Statement::Insert { entity, values })
+    }
+    #[inline(always)]
+    /// Parse `select <field> from <entity> where <field> = <value>`
+    fn parse_select0(&mut self) -> LangResult<Statement> {
+        let field = self.next_ident()?;
+        self.expect_next(&Token::Keyword(Keyword::From))?;
+        let entity = self.parse_entity_name()?;
+        let clause = self.parse_where_clause()?;
+        Ok(Statement::Select {
+            entity,
+            field,
+            clause,
+        })
+    }
+    #[inline(always)]
+    /// Parse `update <entity> set <field> = <value or tuple> where <field> = <value>`
+    fn parse_update0(&mut self) -> LangResult<Statement> {
+        let entity = self.parse_entity_name()?;
+        self.expect_next(&Token::Keyword(Keyword::Type(Type::Set)))?;
+        let field = self.next_ident()?;
+        self.expect_next(&Token::Equals)?;
+        let values = if self.peek_eq(&Token::OpenParen) {
+            self.parse_tuple()?
+        } else {
+            vec![self.parse_value()?]
+        };
+        let clause = self.parse_where_clause()?;
+        Ok(Statement::Update {
+            entity,
+            field,
+            values,
+            clause,
+        })
+    }
+    #[inline(always)]
+    /// Parse `delete from <entity> where <field> = <value>`
+    fn parse_delete0(&mut self) -> LangResult<Statement> {
+        self.expect_next(&Token::Keyword(Keyword::From))?;
+        let entity = self.parse_entity_name()?;
+        let clause = self.parse_where_clause()?;
+        Ok(Statement::Delete { entity, clause })
+    }
+    #[inline(always)]
+    /// Parse `export model <model> to <path>` or `export space <space> to <path>`
+    fn parse_export0(&mut self) -> LangResult<Statement> {
+        let object = self.parse_archived()?;
+        match self.next_result()? {
+            Token::Identifier(to) if unsafe { to.as_slice() }.eq_ignore_ascii_case(b"to") => {}
+            _ => return Err(LangError::InvalidSyntax),
+        }
+        let path = self.parse_value()?;
+        Ok(Statement::Export { object, path })
+    }
+    #[inline(always)]
+    /// Parse `import model <model> from <path>` or `import space <space> from <path>`
+    fn parse_import0(&mut self) -> LangResult<Statement> {
+        let object = self.parse_archived()?;
+        self.expect_next(&Token::Keyword(Keyword::From))?;
+        let path = self.parse_value()?;
+        Ok(Statement::Import { object, path })
+    }
+    #[inline(always)]
+    /// Parse `model <model>` or `space <space>`
+    fn parse_archived(&mut self) -> LangResult<Archived> {
+        match self.next_result()? {
+            Token::Keyword(Keyword::Model) => Ok(Archived::Model(self.parse_entity_name()?)),
+            Token::Keyword(Keyword::Space) => Ok(Archived::Space(self.next_ident()?)),
+            _ => Err(LangError::InvalidSyntax),
+        }
+    }
+    #[inline(always)]
+    /// Parse `where <field> = <value>`
+    fn parse_where_clause(&mut self) -> LangResult<WhereClause> {
+        self.expect_next(&Token::Keyword(Keyword::Where))?;
+        let field = self.next_ident()?;
+        self.expect_next(&Token::Equals)?;
+        Ok(WhereClause {
+            field,
+            value: self.parse_value()?,
+        })
+    }
+    #[inline(always)]
+    /// Parse a non-empty tuple of values: `(<value>, ...)`
+    fn parse_tuple(&mut self) -> LangResult<Vec<Value>> {
+        let mut values = Vec::new();
+        let mut is_good_expr = self.next_eq(&Token::OpenParen);
+        while is_good_expr && self.peek_neq(&Token::CloseParen) {
+            values.push(self.parse_value()?);
+            is_good_expr &= self.peek_eq(&Token::CloseParen) || self.next_eq(&Token::Comma);
+        }
+        is_good_expr &= self.next_eq(&Token::CloseParen);
+        is_good_expr &= !values.is_empty();
+        if compiler::likely(is_good_expr) {
+            Ok(values)
+        } else {
+            Err(LangError::BadExpression)
+        }
+    }
+    #[inline(always)]
+    /// Parse a literal or a `?` placeholder
+    fn parse_value(&mut self) -> LangResult<Value> {
+        let value = match self.next_result()? {
+            Token::QuotedString(string) => Value::String(string),
+            Token::Number(num) => Value::Number(num),
+            Token::QuestionMark => {
+                self.params += 1;
+                Value::Param(self.params - 1)
+            }
+            _ => return Err(LangError::InvalidSyntax),
+        };
+        Ok(value)
+    }
+    #[inline(always)]
     fn parse_entity_name_with_start(&mut self, start: RawSlice) -> LangResult<Entity> {
         if self.peek_eq(&Token::Period) {
             unsafe { self.incr_cursor() };
@@ -0,0 +1,376 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # DML statements
+//!
+//! The two fields of a key/value model are unnamed, so DML statements address them as `k`
+//! (the key) and `v` (the value). Models with named fields use the names from their schema,
+//! where the first field is the key. Rows are always located by their key
+
+use {
+    super::{
+        ast::{Entity, Value, WhereClause},
+        RawSlice,
+    },
+    crate::{
+        actions::ActionResult,
+        corestore::{table::DataModel, SharedSlice},
+        dbnet::{prelude::*, BufferedSocketStream},
+        kvengine::LockedVec,
+        pubsub::KeyEvent,
+        util::compiler,
+    },
+    std::borrow::Cow,
+};
+
+/// The name of the key field of a key/value model
+const FIELD_KEY: &[u8] = b"k";
+/// The name of the value field of a key/value model
+const FIELD_VALUE: &[u8] = b"v";
+
+/// Get the bytes for a value, looking up placeholders in `params`
+pub(super) fn resolve<'a>(value: &'a Value, params: &[&'a [u8]]) -> Cow<'a, [u8]> {
+    match value {
+        Value::String(string) => Cow::Borrowed(string.as_bytes()),
+        Value::Number(num) => Cow::Owned(num.to_string().into_bytes()),
+        // the compiler ensures that every placeholder has an argument
+        Value::Param(idx) => Cow::Borrowed(params[*idx]),
+    }
+}
+
+/// Get the bytes for a list of values, looking up placeholders in `params`
+fn resolve_all(values: &[Value], params: &[&[u8]]) -> Vec<SharedSlice> {
+    values
+        .iter()
+        .map(|value| SharedSlice::new(&resolve(value, params)))
+        .collect()
+}
+
+fn ensure_field<P: ProtocolSpec>(field: &RawSlice, expected: &[u8]) -> ActionResult<()> {
+    if compiler::likely(unsafe { field.as_slice() } == expected) {
+        Ok(())
+    } else {
+        util::err(P::BQL_UNKNOWN_FIELD)
+    }
+}
+
+/// Returns the name of the key field for the model
+fn key_field(model: &DataModel) -> &[u8] {
+    match model {
+        DataModel::Rowmap(rm) => rm.schema().key().name().as_bytes(),
+        _ => FIELD_KEY,
+    }
+}
+
+/// Get the key that the where clause selects
+fn resolve_key<'a, P: ProtocolSpec>(
+    model: &DataModel,
+    clause: &'a WhereClause,
+    params: &[&'a [u8]],
+) -> ActionResult<Cow<'a, [u8]>> {
+    ensure_field::<P>(&clause.field, key_field(model))?;
+    Ok(resolve(&clause.value, params))
+}
+
+/// Run an `insert` statement
+///
+/// - KV: `insert into <entity> (<key>, <value>)`
+/// - Lists: `insert into <entity> (<key>, <item>, ...)`
+/// - Named fields: `insert into <entity> (<field 1>, <field 2>, ...)`, in schema order
+pub async fn insert<P: ProtocolSpec, C: BufferedSocketStream>(
+    handle: &Corestore,
+    con: &mut Connection<C, P>,
+    entity: &Entity,
+    values: &[Value],
+    params: &[&[u8]],
+) -> ActionResult<()> {
+    let table = get_tbl!(entity, handle, con);
+    Corestore::ensure_room_in::<P>(&table)?;
+    macro_rules! insert_fresh {
+        ($engine:expr, $key:expr, $items:expr) => {{
+            let (key, items) = ($key, $items);
+            let did = handle.wal_log_for(entity, &table, |batch| {
+                match $engine.get_inner_ref().fresh_entry(key.clone()) {
+                    Some(entry) => {
+                        batch.list(&key, &items);
+                        entry.insert(LockedVec::new(items));
+                        $engine.notify(&key, KeyEvent::Set);
+                        true
+                    }
+                    None => false,
+                }
+            });
+            P::OKAY_OVW_BLUT[did]
+        }};
+    }
+    let ret = match table.get_model_ref() {
+        DataModel::KV(kve) | DataModel::KVOrdered(kve) => {
+            ensure_length::<P>(values.len(), |len| len == 2)?;
+            let key = SharedSlice::new(&resolve(&values[0], params));
+            let value = SharedSlice::new(&resolve(&values[1], params));
+            let ret = handle.wal_log_for(entity, &table, |batch| {
+                let ret = kve.set(key.clone(), value);
+                if let Ok(true) = ret {
+                    batch.kv(kve, &key);
+                }
+                ret
+            });
+            P::SET_NLUT[ret.ok()]
+        }
+        DataModel::KVExtListmap(kvl) => {
+            ensure_length::<P>(values.len(), |len| len != 0)?;
+            let key = SharedSlice::new(&resolve(&values[0], params));
+            let mut items = resolve_all(&values[1..], params);
+            if compiler::likely(kvl.is_key_ok(&key) && items.iter().all(|i| kvl.is_val_ok(i))) {
+                kvl.cap(&mut items);
+                insert_fresh!(kvl, key, items)
+            } else {
+                P::RCODE_ENCODING_ERROR
+            }
+        }
+        DataModel::Rowmap(rm) => {
+            ensure_boolean_or_aerr::<P>(values.len() == rm.schema().fields().len())?;
+            let key = SharedSlice::new(&resolve(&values[0], params));
+            let row = resolve_all(&values[1..], params);
+            if compiler::likely(rm.rows().is_key_ok(&key) && rm.schema().is_row_ok(&row)) {
+                insert_fresh!(rm.rows(), key, row)
+            } else {
+                P::RCODE_ENCODING_ERROR
+            }
+        }
+        #[allow(unreachable_patterns)]
+        _ => return util::err(P::RSTRING_WRONG_MODEL),
+    };
+    con._write_raw(ret).await?;
+    Ok(())
+}
+
+/// Run a `select <field> from <entity> where <key field> = <key>` statement
+pub async fn select<P: ProtocolSpec, C: BufferedSocketStream>(
+    handle: &Corestore,
+    con: &mut Connection<C, P>,
+    entity: &Entity,
+    field: &RawSlice,
+    clause: &WhereClause,
+    params: &[&[u8]],
+) -> ActionResult<()> {
+    let table = get_tbl!(entity, handle, con);
+    let key = resolve_key::<P>(table.get_model_ref(), clause, params)?;
+    let field = unsafe { field.as_slice() };
+    match table.get_model_ref() {
+        DataModel::Rowmap(rm) => {
+            let pos = match rm.schema().position(field) {
+                Some(pos) => pos,
+                None => return util::err(P::BQL_UNKNOWN_FIELD),
+            };
+            if compiler::unlikely(!rm.rows().is_key_ok(&key)) {
+                return util::err(P::RCODE_ENCODING_ERROR);
+            }
+            // as with the other models, selecting the key only tells us if the row exists
+            let value = rm.rows().get_inner_ref().get(key.as_ref()).map(|row| {
+                if pos == 0 {
+                    SharedSlice::new(&key)
+                } else {
+                    row.read()[pos - 1].clone()
+                }
+            });
+            match value {
+                Some(value) => {
+                    let tsymbol = rm.schema().fields()[pos].ty().tsymbol();
+                    con.write_mono_length_prefixed_with_tsymbol(&value, tsymbol)
+                        .await?
+                }
+                None => con._write_raw(P::RCODE_NIL).await?,
+            }
+        }
+        _ if compiler::unlikely(field != FIELD_KEY && field != FIELD_VALUE) => {
+            return util::err(P::BQL_UNKNOWN_FIELD);
+        }
+        DataModel::KV(kve) | DataModel::KVOrdered(kve) if field == FIELD_VALUE => {
+            match kve.get_cloned(&key) {
+                Ok(Some(val)) => {
+                    con.write_mono_length_prefixed_with_tsymbol(&val, kve.get_value_tsymbol())
+                        .await?
+                }
+                Ok(None) => con._write_raw(P::RCODE_NIL).await?,
+                Err(()) => compiler::cold_err(con._write_raw(P::RCODE_ENCODING_ERROR)).await?,
+            }
+        }
+        DataModel::KVExtListmap(kvl) if field == FIELD_VALUE => match kvl.list_cloned_full(&key) {
+            Ok(Some(items)) => {
+                con.write_typed_non_null_array_header(items.len(), kvl.get_value_tsymbol())
+                    .await?;
+                for item in items {
+                    con.write_typed_non_null_array_element(&item).await?;
+                }
+            }
+            Ok(None) => con._write_raw(P::RCODE_NIL).await?,
+            Err(()) => compiler::cold_err(con._write_raw(P::RCODE_ENCODING_ERROR)).await?,
+        },
+        model => {
+            // selecting the key only tells us if the row exists
+            let (exists, tsymbol) = match model {
+                DataModel::KV(kve) | DataModel::KVOrdered(kve) => {
+                    (kve.exists(&key), kve.get_key_tsymbol())
+                }
+                DataModel::KVExtListmap(kvl) => (kvl.exists(&key), kvl.get_key_tsymbol()),
+                #[allow(unreachable_patterns)]
+                _ => return util::err(P::RSTRING_WRONG_MODEL),
+            };
+            match exists {
+                Ok(true) => {
+                    con.write_mono_length_prefixed_with_tsymbol(&key, tsymbol)
+                        .await?
+                }
+                Ok(false) => con._write_raw(P::RCODE_NIL).await?,
+                Err(()) => compiler::cold_err(con._write_raw(P::RCODE_ENCODING_ERROR)).await?,
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Run an `update` statement. The key can't be updated
+///
+/// - KV: `update <entity> set v = <value> where k = <key>`
+/// - Lists: `update <entity> set v = (<item>, ...) where k = <key>` (replaces the list)
+/// - Named fields: `update <entity> set <field> = <value> where <key field> = <key>`
+pub async fn update<P: ProtocolSpec, C: BufferedSocketStream>(
+    handle: &Corestore,
+    con: &mut Connection<C, P>,
+    entity: &Entity,
+    field: &RawSlice,
+    values: &[Value],
+    clause: &WhereClause,
+    params: &[&[u8]],
+) -> ActionResult<()> {
+    let table = get_tbl!(entity, handle, con);
+    let key = resolve_key::<P>(table.get_model_ref(), clause, params)?;
+    Corestore::ensure_room_in::<P>(&table)?;
+    let did = match table.get_model_ref() {
+        DataModel::KV(kve) | DataModel::KVOrdered(kve) => {
+            ensure_field::<P>(field, FIELD_VALUE)?;
+            ensure_length::<P>(values.len(), |len| len == 1)?;
+            let key = SharedSlice::new(&key);
+            let value = SharedSlice::new(&resolve(&values[0], params));
+            let ret = handle.wal_log_for(entity, &table, |batch| {
+                let ret = kve.update(key.clone(), value);
+                if let Ok(true) = ret {
+                    batch.kv(kve, &key);
+                }
+                ret
+            });
+            ret.ok()
+        }
+        DataModel::KVExtListmap(kvl) => {
+            ensure_field::<P>(field, FIELD_VALUE)?;
+            let mut items = resolve_all(values, params);
+            if compiler::likely(kvl.is_key_ok(&key) && items.iter().all(|i| kvl.is_val_ok(i))) {
+                kvl.cap(&mut items);
+                let did = handle.wal_log_for(entity, &table, |batch| {
+                    kvl.get_inner_ref().get(key.as_ref()).map(|list| {
+                        let mut wlock = list.write();
+                        *wlock = items;
+                        batch.list(&key, &wlock);
+                        kvl.notify(&key, KeyEvent::Update);
+                    })
+                });
+                Some(did.is_some())
+            } else {
+                None
+            }
+        }
+        DataModel::Rowmap(rm) => {
+            ensure_length::<P>(values.len(), |len| len == 1)?;
+            // position in the row; the key isn't a part of the row
+            let pos = match rm.schema().position(unsafe { field.as_slice() }) {
+                Some(pos) if pos != 0 => pos - 1,
+                _ => return util::err(P::BQL_UNKNOWN_FIELD),
+            };
+            let value = SharedSlice::new(&resolve(&values[0], params));
+            let is_okay =
+                rm.rows().is_key_ok(&key) && rm.schema().row_fields()[pos].ty().is_ok(&value);
+            if compiler::likely(is_okay) {
+                let did = handle.wal_log_for(entity, &table, |batch| {
+                    rm.rows().get_inner_ref().get(key.as_ref()).map(|row| {
+                        let mut wlock = row.write();
+                        wlock[pos] = value;
+                        batch.list(&key, &wlock);
+                        rm.rows().notify(&key, KeyEvent::Update);
+                    })
+                });
+                Some(did.is_some())
+            } else {
+                None
+            }
+        }
+        #[allow(unreachable_patterns)]
+        _ => return util::err(P::RSTRING_WRONG_MODEL),
+    };
+    con._write_raw(P::UPDATE_NLUT[did]).await?;
+    Ok(())
+}
+
+/// Run a `delete from <entity> where <key field> = <key>` statement
+pub async fn delete<P: ProtocolSpec, C: BufferedSocketStream>(
+    handle: &Corestore,
+    con: &mut Connection<C, P>,
+    entity: &Entity,
+    clause: &WhereClause,
+    params: &[&[u8]],
+) -> ActionResult<()> {
+    let table = get_tbl!(entity, handle, con);
+    let key = resolve_key::<P>(table.get_model_ref(), clause, params)?;
+    macro_rules! remove {
+        ($engine:expr) => {{
+            if compiler::likely($engine.is_key_ok(&key)) {
+                let did = handle.wal_log_for(entity, &table, |batch| {
+                    let did = $engine.remove_unchecked(&key);
+                    if did {
+                        batch.del(&key);
+                    }
+                    did
+                });
+                Some(did)
+            } else {
+                None
+            }
+        }};
+    }
+    let did = match table.get_model_ref() {
+        DataModel::KV(kve) | DataModel::KVOrdered(kve) => remove!(kve),
+        DataModel::KVExtListmap(kvl) => remove!(kvl),
+        DataModel::Rowmap(rm) => remove!(rm.rows()),
+        DataModel::Hashmap(hm) => remove!(hm.maps()),
+        DataModel::Set(set) => remove!(set),
+        DataModel::SortedSet(zset) => remove!(zset),
+        DataModel::Queue(queue) => remove!(queue),
+        #[allow(unreachable_patterns)]
+        _ => return util::err(P::RSTRING_WRONG_MODEL),
+    };
+    con._write_raw(P::UPDATE_NLUT[did]).await?;
+    Ok(())
+}
@@ -28,29 +28,39 @@ use crate::dbnet::BufferedSocketStream;
 
 use {
     super::{
-        ast::{Statement, StatementLT},
-        error,
+        archive,
+        ast::{Entity, Statement, StatementLT},
+        dml,
+        error::{self, LangError},
     },
     crate::{
         actions::{self, ActionError, ActionResult},
+        auth::{grants::Access, AuthProvider},
         blueql,
-        corestore::memstore::ObjectID,
+        corestore::{memstore::ObjectID, table::Table},
         dbnet::prelude::*,
+        queryengine::ActionIter,
     },
 };
 
 pub async fn execute<'a, P, C>(
     handle: &'a mut Corestore,
     con: &mut Connection<C, P>,
+    auth: &AuthProvider,
     maybe_statement: &[u8],
-    extra: usize,
+    args: ActionIter<'_>,
 ) -> ActionResult<()>
 where
     P: ProtocolSpec,
     C: BufferedSocketStream,
 {
     let statement =
-        error::map_ql_err_to_resp::<StatementLT, P>(blueql::compile(maybe_statement, extra))?;
+        error::map_ql_err_to_resp::<StatementLT, P>(blueql::compile(maybe_statement, args.len()))?;
+    let (space, access) = self::required_access(handle, statement.as_ref());
+    auth.ensure_access::<P>(space, access)?;
+    actions::ensure_writable::<P>(access)?;
+    // the extra arguments are bound to the placeholders
+    let params: Vec<&[u8]> = args.collect();
     let system_health_okay = registry::state_okay();
     let result = match statement.as_ref() {
         Statement::Use(entity) => handle.swap_entity(entity),
@@ -74,11 +84,43 @@ where
         Statement::CreateModel {
             entity,
             model,
+            ordered,
+            max_len,
             volatile,
+            eviction,
         } if system_health_okay => {
-            match model.get_model_code() {
+            let ret = if model.names.is_empty() {
+                model
+                    .get_model_code()
+                    .and_then(|code| {
+                        if *ordered {
+                            Table::ordered_kv_model_code(code)
+                                .ok_or(LangError::UnsupportedModelDeclaration)
+                        } else {
+                            Ok(code)
+                        }
+                    })
+                    .and_then(|code| match max_len {
+                        // only list models can be capped
+                        Some(max_len) => Table::from_model_code(code, *volatile)
+                            .and_then(|table| table.with_max_len(*max_len))
+                            .map(|table| {
+                                handle.create_table_from(entity, table.with_eviction(*eviction))
+                            })
+                            .ok_or(LangError::UnsupportedModelDeclaration),
+                        None => Ok(handle.create_table(entity, code, *volatile, *eviction)),
+                    })
+            } else if *ordered || max_len.is_some() {
+                Err(LangError::UnsupportedModelDeclaration)
+            } else {
+                model.get_schema().map(|schema| {
+                    let table = Table::new_rowmap(schema, *volatile).with_eviction(*eviction);
+                    handle.create_table_from(entity, table)
+                })
+            };
+            match ret {
                 // ret okay
-                Ok(code) => handle.create_table(entity, code, *volatile),
+                Ok(ret) => ret,
                 Err(e) => return Err(ActionError::ActionError(error::cold_err::<P>(e))),
             }
         }
@@ -103,6 +145,39 @@ where
                 .await?;
             return Ok(());
         }
+        Statement::Insert { entity, values } if system_health_okay => {
+            // ret directly
+            return dml::insert(handle, con, entity, values, &params).await;
+        }
+        Statement::Update {
+            entity,
+            field,
+            values,
+            clause,
+        } if system_health_okay => {
+            // ret directly
+            return dml::update(handle, con, entity, field, values, clause, &params).await;
+        }
+        Statement::Delete { entity, clause } if system_health_okay => {
+            // ret directly
+            return dml::delete(handle, con, entity, clause, &params).await;
+        }
+        Statement::Export { object, path } if system_health_okay => {
+            // ret directly
+            return archive::export(handle, con, object, path, &params).await;
+        }
+        Statement::Import { object, path } if system_health_okay => {
+            // ret directly
+            return archive::import(handle, con, object, path, &params).await;
+        }
+        Statement::Select {
+            entity,
+            field,
+            clause,
+        } => {
+            // ret directly
+            return dml::select(handle, con, entity, field, clause, &params).await;
+        }
         _ => {
             // the server is broken
             con._write_raw(P::RCODE_SERVER_ERR).await?;
@@ -113,3 +188,38 @@ where
     con._write_raw(P::RCODE_OKAY).await?;
     Ok(())
 }
+
+/// Returns the keyspace that the statement is run against and the access it needs there
+fn required_access<'a>(
+    handle: &'a Corestore,
+    statement: &'a Statement,
+) -> (Option<&'a [u8]>, Access) {
+    let current = || handle.get_ids().0.map(|ks| ks.as_slice());
+    let space_of = |entity: &'a Entity| match entity {
+        Entity::Full(space, _) => Some(unsafe { space.as_slice() }),
+        Entity::Current(_) => current(),
+    };
+    match statement {
+        Statement::Use(_) | Statement::InspectSpaces => (None, Access::Any),
+        Statement::CreateSpace(_)
+        | Statement::DropSpace { .. }
+        | Statement::CreateModel { .. }
+        | Statement::DropModel { .. }
+        | Statement::Export { .. }
+        | Statement::Import { .. } => (None, Access::Admin),
+        Statement::InspectSpace(space) => (
+            space
+                .as_ref()
+                .map(|space| unsafe { space.as_slice() })
+                .or_else(current),
+            Access::Read,
+        ),
+        Statement::InspectModel(model) => {
+            (model.as_ref().map_or_else(current, space_of), Access::Read)
+        }
+        Statement::Select { entity, .. } => (space_of(entity), Access::Read),
+        Statement::Insert { entity, .. }
+        | Statement::Update { entity, .. }
+        | Statement::Delete { entity, .. } => (space_of(entity), Access::Write),
+    }
+}
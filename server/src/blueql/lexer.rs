@@ -33,7 +33,7 @@ use {
     core::{marker::PhantomData, slice, str},
 };
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 #[repr(u8)]
 /// BQL tokens
 pub enum Token {
@@ -44,6 +44,8 @@ pub enum Token {
     Comma,        // ,
     Colon,        // :
     Period,       // .
+    Equals,       // =
+    QuestionMark, // ?
     QuotedString(String),
     Identifier(RawSlice),
     Number(u64),
@@ -86,7 +88,19 @@ pub enum Keyword {
     Model,
     Space,
     Volatile,
+    Evict,
+    Ordered,
+    MaxLen,
     Force,
+    Insert,
+    Into,
+    Select,
+    From,
+    Where,
+    Update,
+    Delete,
+    Export,
+    Import,
     Type(Type),
 }
 
@@ -97,10 +111,18 @@ pub enum Type {
     String,
     Binary,
     List,
+    UInt64,
+    SInt64,
+    Float64,
+    Map,
+    /// `set` is also the keyword in `update <entity> set ...`
+    Set,
+    SortedSet,
+    Queue,
 }
 
 #[derive(Debug, PartialEq)]
-/// Type expression (ty<ty<...>>)
+/// Type expression (ty<ty<...>>). A map is `[Map, field type, value type]`
 pub struct TypeExpression(pub Vec<Type>);
 
 impl Keyword {
@@ -114,11 +136,30 @@ impl Keyword {
             b"model" => Keyword::Model,
             b"space" => Keyword::Space,
             b"volatile" => Keyword::Volatile,
+            b"evict" => Keyword::Evict,
+            b"ordered" => Keyword::Ordered,
+            b"maxlen" => Keyword::MaxLen,
             b"string" => Keyword::Type(Type::String),
             b"binary" => Keyword::Type(Type::Binary),
             b"list" => Keyword::Type(Type::List),
+            b"map" => Keyword::Type(Type::Map),
+            b"set" => Keyword::Type(Type::Set),
+            b"zset" => Keyword::Type(Type::SortedSet),
+            b"queue" => Keyword::Type(Type::Queue),
+            b"uint64" => Keyword::Type(Type::UInt64),
+            b"sint64" => Keyword::Type(Type::SInt64),
+            b"float64" => Keyword::Type(Type::Float64),
             b"force" => Keyword::Force,
             b"use" => Keyword::Use,
+            b"insert" => Keyword::Insert,
+            b"into" => Keyword::Into,
+            b"select" => Keyword::Select,
+            b"from" => Keyword::From,
+            b"where" => Keyword::Where,
+            b"update" => Keyword::Update,
+            b"delete" => Keyword::Delete,
+            b"export" => Keyword::Export,
+            b"import" => Keyword::Import,
             _ => return None,
         };
         Some(r)
@@ -225,13 +266,6 @@ impl<'a> Lexer<'a> {
         did_peek
     }
     #[inline(always)]
-    /// Same as `peek_eq_or_eof` but forwards the cursor on match
-    fn peek_eq_or_eof_and_forward(&mut self, eq: u8) -> bool {
-        let did_forward = self.peek_eq_and_forward(eq);
-        unsafe { self.incr_cursor_if(did_forward) };
-        did_forward | self.exhausted()
-    }
-    #[inline(always)]
     /// Trim the whitespace ahead
     fn trim_ahead(&mut self) {
         while self.peek_eq_and_forward(b' ') {}
@@ -263,9 +297,12 @@ impl<'a> Lexer<'a> {
                 find_ptr_distance(start, self.cursor()),
             ))
         };
-        let next_is_ws_or_eof = self.peek_eq_or_eof_and_forward(b' ');
+        // a number can be followed by whitespace or by the punctuation that ends a value in a
+        // tuple (the main loop will take care of these)
+        let next_is_delim =
+            self.exhausted() || self.peek_is(|byte| matches!(byte, b' ' | b',' | b')'));
         match slice.parse() {
-            Ok(num) if compiler::likely(next_is_ws_or_eof) => {
+            Ok(num) if compiler::likely(next_is_delim) => {
                 // this is a good number; push it in
                 self.push_token(Token::Number(num));
             }
@@ -351,6 +388,8 @@ impl<'a> Lexer<'a> {
             b',' => Token::Comma,
             b':' => Token::Colon,
             b'.' => Token::Period,
+            b'=' => Token::Equals,
+            b'?' => Token::QuestionMark,
             _ => {
                 self.last_error = Some(LangError::UnexpectedChar);
                 return;
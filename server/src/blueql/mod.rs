@@ -24,7 +24,9 @@
  *
 */
 
+mod archive;
 mod ast;
+mod dml;
 mod error;
 mod executor;
 mod lexer;
@@ -49,6 +51,7 @@ pub fn compile<'a>(src: &'a [u8], extra: usize) -> LangResult<Life<'a, Statement
     Compiler::compile_with_extra(src, extra)
 }
 
+#[derive(Clone)]
 #[cfg_attr(not(test), derive(Debug))]
 #[cfg_attr(not(test), derive(PartialEq))]
 pub struct RawSlice {
@@ -24,10 +24,20 @@
  *
 */
 
-use super::{
-    ast::{Compiler, Entity, FieldConfig, Statement},
-    error::LangError,
-    lexer::{Keyword, Lexer, Token, Type, TypeExpression},
+use {
+    super::{
+        ast::{Archived, Compiler, Entity, FieldConfig, Statement, Value, WhereClause},
+        error::{LangError, LangResult},
+        lexer::{Keyword, Lexer, Token, Type, TypeExpression},
+    },
+    crate::{
+        corestore::table::Table,
+        kvengine::{
+            eviction::EvictionPolicy,
+            rowmap::{Field, Schema},
+            types::FieldType,
+        },
+    },
 };
 
 macro_rules! src {
@@ -59,6 +69,19 @@ mod lexer {
         )
     }
 
+    #[test]
+    fn lex_numeric_types() {
+        let src = b"uint64 SINT64 float64";
+        assert_eq!(
+            Lexer::lex(src).unwrap(),
+            vec![
+                Type::UInt64.into(),
+                Type::SInt64.into(),
+                Type::Float64.into()
+            ]
+        )
+    }
+
     #[test]
     fn lex_number() {
         let src = b"123456";
@@ -130,13 +153,52 @@ mod lexer {
     fn lex_fail_unknown_chars() {
         const SOURCES: &[&[u8]] = &[
             b"!", b"@", b"#", b"$", b"%", b"^", b"&", b"*", b"[", b"]", b"{", b"}", b"|", b"\\",
-            b"/", b"~", b"`", b";", b"hello?",
+            b"/", b"~", b"`", b";",
         ];
         for source in SOURCES {
             assert_eq!(Lexer::lex(source).unwrap_err(), LangError::UnexpectedChar);
         }
     }
 
+    #[test]
+    fn lex_dml_tokens() {
+        let src = b"select v from tweets where k = ?";
+        assert_eq!(
+            Lexer::lex(src).unwrap(),
+            vec![
+                Keyword::Select.into(),
+                "v".into(),
+                Keyword::From.into(),
+                "tweets".into(),
+                Keyword::Where.into(),
+                "k".into(),
+                Token::Equals,
+                Token::QuestionMark
+            ]
+        );
+        assert_eq!(
+            Lexer::lex(b"hello?").unwrap(),
+            vec!["hello".into(), Token::QuestionMark]
+        );
+    }
+
+    #[test]
+    fn lex_number_in_tuple() {
+        let src = b"(1,2, 3)";
+        assert_eq!(
+            Lexer::lex(src).unwrap(),
+            vec![
+                Token::OpenParen,
+                1.into(),
+                Token::Comma,
+                2.into(),
+                Token::Comma,
+                3.into(),
+                Token::CloseParen
+            ]
+        );
+    }
+
     #[test]
     fn lex_fail_unclosed_litstring() {
         const SOURCES: &[&[u8]] = &[b"'hello, world", br#""hello, world"#];
@@ -213,7 +275,10 @@ mod ast {
                 ],
                 names: vec!["username".into(), "password".into(), "posts".into()],
             },
+            ordered: false,
+            max_len: None,
             volatile: true,
+            eviction: EvictionPolicy::NoEviction,
         };
         (src, stmt)
     }
@@ -238,11 +303,110 @@ mod ast {
                     TypeExpression(vec![Type::Binary]),
                 ],
             },
+            ordered: false,
+            max_len: None,
             volatile: false,
+            eviction: EvictionPolicy::NoEviction,
         };
         assert_eq!(Compiler::compile(&src).unwrap(), expected);
     }
     #[test]
+    fn stmt_create_volatile_with_eviction() {
+        let src = b"create model cache.pages(string, binary) volatile evict LRU".to_vec();
+        let expected = Statement::CreateModel {
+            entity: Entity::Full("cache".into(), "pages".into()),
+            model: FieldConfig {
+                names: vec![],
+                types: vec![
+                    TypeExpression(vec![Type::String]),
+                    TypeExpression(vec![Type::Binary]),
+                ],
+            },
+            ordered: false,
+            max_len: None,
+            volatile: true,
+            eviction: EvictionPolicy::Lru,
+        };
+        assert_eq!(Compiler::compile(&src).unwrap(), expected);
+    }
+    #[test]
+    fn stmt_create_map() {
+        let src = b"create model app.users(string, map<string, binary>) volatile".to_vec();
+        let expected = Statement::CreateModel {
+            entity: Entity::Full("app".into(), "users".into()),
+            model: FieldConfig {
+                names: vec![],
+                types: vec![
+                    TypeExpression(vec![Type::String]),
+                    TypeExpression(vec![Type::Map, Type::String, Type::Binary]),
+                ],
+            },
+            ordered: false,
+            max_len: None,
+            volatile: true,
+            eviction: EvictionPolicy::NoEviction,
+        };
+        assert_eq!(Compiler::compile(&src).unwrap(), expected);
+    }
+    #[test]
+    fn stmt_create_ordered() {
+        let src = b"create model shop.orders(string, binary) ordered volatile".to_vec();
+        let expected = Statement::CreateModel {
+            entity: Entity::Full("shop".into(), "orders".into()),
+            model: FieldConfig {
+                names: vec![],
+                types: vec![
+                    TypeExpression(vec![Type::String]),
+                    TypeExpression(vec![Type::Binary]),
+                ],
+            },
+            ordered: true,
+            max_len: None,
+            volatile: true,
+            eviction: EvictionPolicy::NoEviction,
+        };
+        assert_eq!(Compiler::compile(&src).unwrap(), expected);
+    }
+    #[test]
+    fn stmt_create_capped_list() {
+        let src = b"create model app.feed(string, list<string>) maxlen 100 volatile".to_vec();
+        let expected = Statement::CreateModel {
+            entity: Entity::Full("app".into(), "feed".into()),
+            model: FieldConfig {
+                names: vec![],
+                types: vec![
+                    TypeExpression(vec![Type::String]),
+                    TypeExpression(vec![Type::List, Type::String]),
+                ],
+            },
+            ordered: false,
+            max_len: Some(100),
+            volatile: true,
+            eviction: EvictionPolicy::NoEviction,
+        };
+        assert_eq!(Compiler::compile(&src).unwrap(), expected);
+        for src in [
+            &b"create model app.feed(string, list<string>) maxlen"[..],
+            b"create model app.feed(string, list<string>) maxlen 0",
+            b"create model app.feed(string, list<string>) maxlen ten",
+            // properties go in order
+            b"create model app.feed(string, list<string>) volatile maxlen 100",
+        ] {
+            assert!(Compiler::compile(src).is_err());
+        }
+    }
+    #[test]
+    fn stmt_create_bad_eviction() {
+        for src in [
+            &b"create model cache.pages(string, binary) volatile evict"[..],
+            b"create model cache.pages(string, binary) volatile evict oldest",
+            // persistent models are never evicted
+            b"create model cache.pages(string, binary) evict lru",
+        ] {
+            assert!(Compiler::compile(src).is_err());
+        }
+    }
+    #[test]
     fn stmt_drop_space() {
         assert_eq!(
             Compiler::compile(b"drop space twitter force").unwrap(),
@@ -277,6 +441,63 @@ mod ast {
         );
     }
     #[test]
+    fn stmt_export() {
+        assert_eq!(
+            Compiler::compile(b"export model twitter.tweet to 'tweets.skya'").unwrap(),
+            Statement::Export {
+                object: Archived::Model(Entity::Full("twitter".into(), "tweet".into())),
+                path: Value::String("tweets.skya".into())
+            }
+        );
+        assert_eq!(
+            Compiler::compile(b"EXPORT SPACE twitter TO 'twitter.skya'").unwrap(),
+            Statement::Export {
+                object: Archived::Space("twitter".into()),
+                path: Value::String("twitter.skya".into())
+            }
+        );
+    }
+    #[test]
+    fn stmt_import() {
+        assert_eq!(
+            Compiler::compile(b"import model tweet from 'tweets.skya'").unwrap(),
+            Statement::Import {
+                object: Archived::Model(Entity::Current("tweet".into())),
+                path: Value::String("tweets.skya".into())
+            }
+        );
+        assert_eq!(
+            Compiler::compile_with_extra(b"import space twitter2 from ?", 1).unwrap(),
+            Statement::Import {
+                object: Archived::Space("twitter2".into()),
+                path: Value::Param(0)
+            }
+        );
+    }
+    #[test]
+    fn stmt_archive_bad_syntax() {
+        src!(
+            SRC,
+            "export twitter to 'twitter.skya'",
+            "export space twitter from 'twitter.skya'",
+            "import space twitter to 'twitter.skya'",
+            "import model twitter.tweet from tweets",
+            "export model twitter.tweet to 'tweets.skya' force",
+        );
+        for src in SRC {
+            assert_eq!(
+                Compiler::compile(src).unwrap_err(),
+                LangError::InvalidSyntax,
+                "{}",
+                String::from_utf8_lossy(src)
+            );
+        }
+        assert_eq!(
+            Compiler::compile(b"export space twitter to").unwrap_err(),
+            LangError::UnexpectedEOF
+        );
+    }
+    #[test]
     fn compile_full() {
         let (src, stmt) = setup_src_stmt();
         assert_eq!(Compiler::compile(&src).unwrap(), stmt)
@@ -309,7 +530,29 @@ mod ast {
             // rule: fields can't be named
             "(id: string, posts: list<string>)",
             // rule: nested lists are disallowed
-            "(string, list<list<string>>)"
+            "(string, list<list<string>>)",
+            // rule: lists can't hold numbers or be keyed by numbers
+            "(string, list<uint64>)",
+            "(sint64, list<string>)",
+            // rule: numbers can't have type arguments
+            "(string, float64<string>)",
+            // rule: maps can only hold strings or binaries and can't be keyed by numbers
+            "(string, map<string, uint64>)",
+            "(uint64, map<string, string>)",
+            // rule: maps can't be keys or list elements
+            "(map<string, string>, string)",
+            "(string, list<map>)",
+            // rule: sets need a member type, which can only be a string or a binary
+            "(string, set)",
+            "(string, set<uint64>)",
+            "(string, zset<list<string>>)",
+            // rule: sets can't be keys or list elements
+            "(set<string>, string)",
+            "(zset<string>, string)",
+            "(string, list<set>)",
+            // rule: queues hold strings or binaries and can't be keys
+            "(string, queue<sint64>)",
+            "(queue<string>, string)"
         );
         for src in SRC {
             assert_eq!(
@@ -320,4 +563,293 @@ mod ast {
             );
         }
     }
+    #[test]
+    fn model_code_numeric() {
+        let get_model_code = |src: &[u8]| {
+            let l = Lexer::lex(src).unwrap();
+            match Compiler::new(&l)
+                .parse_create_model1(Entity::Current("jotsy".into()))
+                .unwrap()
+            {
+                Statement::CreateModel { model, .. } => model.get_model_code().unwrap(),
+                x => panic!("Expected model found {:?}", x),
+            }
+        };
+        assert_eq!(
+            get_model_code(b"(string, uint64)"),
+            Table::typed_kv_model_code(FieldType::String, FieldType::UInt64)
+        );
+        assert_eq!(
+            get_model_code(b"(sint64, float64)"),
+            Table::typed_kv_model_code(FieldType::SInt64, FieldType::Float64)
+        );
+        assert_eq!(
+            Table::types_from_typed_kv_model_code(get_model_code(b"(binary, sint64)")),
+            Some((FieldType::Binary, FieldType::SInt64))
+        );
+        // models without numbers keep their old codes
+        assert_eq!(get_model_code(b"(string, string)"), 2);
+        assert_eq!(Table::types_from_typed_kv_model_code(2), None);
+    }
+    #[test]
+    fn model_code_map() {
+        let get_model_code = |src: &[u8]| {
+            let l = Lexer::lex(src).unwrap();
+            match Compiler::new(&l)
+                .parse_create_model1(Entity::Current("jotsy".into()))
+                .unwrap()
+            {
+                Statement::CreateModel { model, .. } => model.get_model_code().unwrap(),
+                x => panic!("Expected model found {:?}", x),
+            }
+        };
+        assert_eq!(
+            get_model_code(b"(string, map<string, binary>)"),
+            Table::map_model_code(true, true, false)
+        );
+        assert_eq!(
+            get_model_code(b"(binary, map<binary, string>)"),
+            Table::map_model_code(false, false, true)
+        );
+    }
+    #[test]
+    fn model_code_set() {
+        let get_model_code = |src: &[u8]| {
+            let l = Lexer::lex(src).unwrap();
+            match Compiler::new(&l)
+                .parse_create_model1(Entity::Current("jotsy".into()))
+                .unwrap()
+            {
+                Statement::CreateModel { model, .. } => model.get_model_code().unwrap(),
+                x => panic!("Expected model found {:?}", x),
+            }
+        };
+        assert_eq!(
+            get_model_code(b"(string, set<binary>)"),
+            Table::set_model_code(true, false)
+        );
+        assert_eq!(
+            get_model_code(b"(binary, zset<string>)"),
+            Table::sorted_set_model_code(false, true)
+        );
+        assert_eq!(
+            get_model_code(b"(string, queue<binary>)"),
+            Table::queue_model_code(true, false)
+        );
+        // `set` is still a keyword in updates
+        assert!(Compiler::compile(b"update tags set v = 'a' where k = 'b'").is_ok());
+    }
+    #[test]
+    fn map_bad_syntax() {
+        src!(
+            SRC,
+            "create model users(string, map)",
+            "create model users(string, map<string>)",
+            "create model users(string, map<string, binary)",
+            "create model users(string, map<string binary>)",
+            "create model users(string, map<string, list<string>>)"
+        );
+        for src in SRC {
+            assert_eq!(
+                Compiler::compile(src).unwrap_err(),
+                LangError::InvalidSyntax,
+                "{}",
+                String::from_utf8_lossy(src)
+            );
+        }
+    }
+    fn get_schema(src: &[u8]) -> LangResult<Schema> {
+        let l = Lexer::lex(src).unwrap();
+        let stmt = Compiler::new(&l)
+            .parse_create_model1(Entity::Current("jotsy".into()))
+            .unwrap_or_else(|_| panic!("Failed for payload: {}", String::from_utf8_lossy(src)));
+        match stmt {
+            Statement::CreateModel { model, .. } => model.get_schema(),
+            x => panic!("Expected model found {:?}", x),
+        }
+    }
+    #[test]
+    fn model_schema() {
+        let schema = get_schema(b"(username: string, email: string, avatar: binary)").unwrap();
+        assert_eq!(
+            schema.fields(),
+            [
+                Field::new("username", FieldType::String),
+                Field::new("email", FieldType::String),
+                Field::new("avatar", FieldType::Binary),
+            ]
+        );
+        assert_eq!(schema.key().name(), "username");
+        assert_eq!(schema.position(b"avatar"), Some(2));
+        assert_eq!(schema.describe(), "(username:str,email:str,avatar:binstr)");
+    }
+    #[test]
+    fn model_schema_numeric() {
+        let schema = get_schema(b"(id: uint64, balance: sint64, rate: float64)").unwrap();
+        assert_eq!(schema.key().ty(), FieldType::UInt64);
+        assert_eq!(schema.describe(), "(id:uint64,balance:sint64,rate:float64)");
+        assert!(schema.is_row_ok(&["-10", "0.5"]));
+        assert!(!schema.is_row_ok(&["10.5", "0.5"]));
+    }
+    #[test]
+    fn bad_model_schema() {
+        // fields must be scalars
+        src!(
+            SRC,
+            "(username: string, posts: list<string>)",
+            "(username: list<string>, email: string)"
+        );
+        for src in SRC {
+            assert_eq!(
+                get_schema(src).unwrap_err(),
+                LangError::UnsupportedModelDeclaration,
+                "{}",
+                String::from_utf8_lossy(src)
+            );
+        }
+        // unnamed fields have no schema
+        assert_eq!(
+            get_schema(b"(string, string)").unwrap_err(),
+            LangError::UnsupportedModelDeclaration
+        );
+        // no two fields can have the same name
+        assert_eq!(
+            get_schema(b"(username: string, username: binary)").unwrap_err(),
+            LangError::BadExpression
+        );
+    }
+}
+
+mod dml {
+    //! DML statement tests
+
+    use super::*;
+
+    #[test]
+    fn stmt_insert() {
+        assert_eq!(
+            Compiler::compile(b"insert into twitter.passwords ('sayan', 'pass123')").unwrap(),
+            Statement::Insert {
+                entity: Entity::Full("twitter".into(), "passwords".into()),
+                values: vec![
+                    Value::String("sayan".into()),
+                    Value::String("pass123".into())
+                ]
+            }
+        );
+    }
+    #[test]
+    fn stmt_insert_params() {
+        assert_eq!(
+            Compiler::compile_with_extra(b"insert into passwords (?, 100, ?)", 2).unwrap(),
+            Statement::Insert {
+                entity: Entity::Current("passwords".into()),
+                values: vec![Value::Param(0), Value::Number(100), Value::Param(1)]
+            }
+        );
+    }
+    #[test]
+    fn stmt_select() {
+        assert_eq!(
+            Compiler::compile_with_extra(b"select v from twitter.passwords where k = ?", 1)
+                .unwrap(),
+            Statement::Select {
+                entity: Entity::Full("twitter".into(), "passwords".into()),
+                field: "v".into(),
+                clause: WhereClause {
+                    field: "k".into(),
+                    value: Value::Param(0)
+                }
+            }
+        );
+    }
+    #[test]
+    fn stmt_update() {
+        assert_eq!(
+            Compiler::compile(b"update passwords set v = 'newpass' where k = 'sayan'").unwrap(),
+            Statement::Update {
+                entity: Entity::Current("passwords".into()),
+                field: "v".into(),
+                values: vec![Value::String("newpass".into())],
+                clause: WhereClause {
+                    field: "k".into(),
+                    value: Value::String("sayan".into())
+                }
+            }
+        );
+        assert_eq!(
+            Compiler::compile(b"update lists set v = ('a', 'b') where k = 1").unwrap(),
+            Statement::Update {
+                entity: Entity::Current("lists".into()),
+                field: "v".into(),
+                values: vec![Value::String("a".into()), Value::String("b".into())],
+                clause: WhereClause {
+                    field: "k".into(),
+                    value: Value::Number(1)
+                }
+            }
+        );
+    }
+    #[test]
+    fn stmt_delete() {
+        assert_eq!(
+            Compiler::compile(b"delete from twitter.passwords where k = 'sayan'").unwrap(),
+            Statement::Delete {
+                entity: Entity::Full("twitter".into(), "passwords".into()),
+                clause: WhereClause {
+                    field: "k".into(),
+                    value: Value::String("sayan".into())
+                }
+            }
+        );
+    }
+    #[test]
+    fn stmt_params_must_match_args() {
+        src!(
+            SRC,
+            "select v from passwords where k = ?",
+            "insert into passwords (?, ?)",
+        );
+        for src in SRC {
+            assert_eq!(
+                Compiler::compile_with_extra(src, 3).unwrap_err(),
+                LangError::InvalidSyntax
+            );
+        }
+        assert_eq!(
+            Compiler::compile_with_extra(b"delete from passwords where k = 'sayan'", 1)
+                .unwrap_err(),
+            LangError::InvalidSyntax
+        );
+    }
+    #[test]
+    fn stmt_dml_bad_syntax() {
+        src!(
+            SRC,
+            "insert passwords ('a', 'b')",
+            "select 'v' from passwords where k = 'a'",
+            "select v passwords where k = 'a'",
+            "update passwords v = 'b' where k = 'a'",
+            "delete from passwords where k 'a'",
+            "delete from passwords where k = v",
+            // tokens remain after the bad tuple
+            "insert into passwords ('a' 'b')",
+        );
+        for src in SRC {
+            assert_eq!(
+                Compiler::compile(src).unwrap_err(),
+                LangError::InvalidSyntax,
+                "{}",
+                String::from_utf8_lossy(src)
+            );
+        }
+        assert_eq!(
+            Compiler::compile(b"insert into passwords ()").unwrap_err(),
+            LangError::BadExpression
+        );
+        assert_eq!(
+            Compiler::compile(b"delete from passwords where k =").unwrap_err(),
+            LangError::UnexpectedEOF
+        );
+    }
 }
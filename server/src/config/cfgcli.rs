@@ -95,6 +95,11 @@ pub(super) fn parse_cli_args(matches: ArgMatches) -> Configset {
     );
     fcli!(server_mode, matches.value_of("mode"), "--mode");
     fcli!(server_maxcon, matches.value_of("maxcon"), "--maxcon");
+    fcli!(
+        server_maxmemory,
+        matches.value_of("maxmemory"),
+        "--maxmemory"
+    );
     // bgsave settings
     fcli!(
         bgsave_settings,
@@ -133,5 +138,23 @@ pub(super) fn parse_cli_args(matches: ArgMatches) -> Configset {
         matches.value_of("authkey"),
         "--auth-origin-key"
     );
+    // WAL settings
+    fcli!(wal_settings, matches.value_of("walfsync"), "--wal-fsync");
+    // replication settings
+    fcli!(
+        replication_settings,
+        matches.value_of("replicateof"),
+        "--replicate-of",
+        matches.value_of("replicationuser"),
+        "--replication-user",
+        matches.value_of("replicationpassword"),
+        "--replication-password"
+    );
+    // encryption settings
+    fcli!(
+        encryption_settings,
+        matches.value_of("encryptionkeyfile"),
+        "--encryption-key-file"
+    );
     defset
 }
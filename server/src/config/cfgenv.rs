@@ -50,6 +50,7 @@ pub(super) fn parse_env_config() -> Configset {
     fenv!(server_tcp, SKY_SYSTEM_HOST, SKY_SYSTEM_PORT);
     fenv!(server_noart, SKY_SYSTEM_NOART);
     fenv!(server_maxcon, SKY_SYSTEM_MAXCON);
+    fenv!(server_maxmemory, SKY_SYSTEM_MAXMEMORY);
     fenv!(server_mode, SKY_DEPLOY_MODE);
     // bgsave settings
     fenv!(bgsave_settings, SKY_BGSAVE_ENABLED, SKY_BGSAVE_DURATION);
@@ -70,5 +71,16 @@ pub(super) fn parse_env_config() -> Configset {
         SKY_TLS_PASSIN
     );
     fenv!(auth_settings, SKY_AUTH_ORIGIN_KEY);
+    // WAL settings
+    fenv!(wal_settings, SKY_WAL_FSYNC);
+    // replication settings
+    fenv!(
+        replication_settings,
+        SKY_REPLICATION_LEADER,
+        SKY_REPLICATION_USERNAME,
+        SKY_REPLICATION_PASSWORD
+    );
+    // encryption settings
+    fenv!(encryption_settings, SKY_ENCRYPTION_KEY_FILE);
     defset
 }
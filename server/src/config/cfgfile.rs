@@ -27,10 +27,10 @@
 use {
     super::{
         AuthSettings, ConfigSourceParseResult, Configset, Modeset, OptString, ProtocolVersion,
-        TryFromConfigSource,
+        TryFromConfigSource, WalFsync,
     },
     serde::Deserialize,
-    std::net::IpAddr,
+    std::net::{IpAddr, SocketAddr},
 };
 
 /// This struct is an _object representation_ used for parsing the TOML file
@@ -46,6 +46,12 @@ pub struct Config {
     pub(super) ssl: Option<KeySslOpts>,
     /// auth settings
     pub(super) auth: Option<AuthSettings>,
+    /// WAL settings
+    pub(super) wal: Option<ConfigKeyWal>,
+    /// replication settings
+    pub(super) replication: Option<ConfigKeyReplication>,
+    /// encryption settings
+    pub(super) encryption: Option<ConfigKeyEncryption>,
 }
 
 /// This struct represents the `server` key in the TOML file
@@ -60,6 +66,8 @@ pub struct ConfigKeyServer {
     pub(super) noart: Option<bool>,
     /// The maximum number of clients
     pub(super) maxclient: Option<usize>,
+    /// The memory limit for volatile models
+    pub(super) maxmemory: Option<usize>,
     /// The deployment mode
     pub(super) mode: Option<Modeset>,
     pub(super) protocol: Option<ProtocolVersion>,
@@ -92,6 +100,31 @@ pub struct ConfigKeySnapshot {
     pub(super) failsafe: Option<bool>,
 }
 
+/// The WAL section in the TOML file
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct ConfigKeyWal {
+    /// The fsync policy for the write-ahead log
+    pub(super) fsync: Option<WalFsync>,
+}
+
+/// The replication section in the TOML file. Setting it makes this node a follower
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct ConfigKeyReplication {
+    /// The address of the leader
+    pub(super) leader: SocketAddr,
+    /// The user to log in to the leader as
+    pub(super) username: Option<String>,
+    /// The token of the user
+    pub(super) password: Option<String>,
+}
+
+/// The encryption section in the TOML file. Setting it encrypts data files at rest
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct ConfigKeyEncryption {
+    /// The path to the key file
+    pub(super) keyfile: String,
+}
+
 #[derive(Deserialize, Debug, PartialEq)]
 pub struct KeySslOpts {
     pub(super) key: String,
@@ -171,6 +204,9 @@ pub fn from_file(file: ConfigFile) -> Configset {
         snapshot,
         ssl,
         auth,
+        wal,
+        replication,
+        encryption,
     } = file;
     // server settings
     set.server_tcp(
@@ -181,6 +217,7 @@ pub fn from_file(file: ConfigFile) -> Configset {
     );
     set.protocol_settings(server.protocol, "server.protocol");
     set.server_maxcon(Optional::from(server.maxclient), "server.maxcon");
+    set.server_maxmemory(Optional::from(server.maxmemory), "server.maxmemory");
     set.server_noart(Optional::from(server.noart), "server.noart");
     set.server_mode(Optional::from(server.mode), "server.mode");
     // bgsave settings
@@ -235,5 +272,31 @@ pub fn from_file(file: ConfigFile) -> Configset {
         let AuthSettings { origin_key } = auth;
         set.auth_settings(Optional::from(origin_key), "auth.origin")
     }
+    // WAL settings
+    if let Some(wal) = wal {
+        let ConfigKeyWal { fsync } = wal;
+        set.wal_settings(Optional::from(fsync), "wal.fsync");
+    }
+    // replication settings
+    if let Some(replication) = replication {
+        let ConfigKeyReplication {
+            leader,
+            username,
+            password,
+        } = replication;
+        set.replication_settings(
+            NonNull::from(leader),
+            "replication.leader",
+            OptString::from(username),
+            "replication.username",
+            OptString::from(password),
+            "replication.password",
+        );
+    }
+    // encryption settings
+    if let Some(encryption) = encryption {
+        let ConfigKeyEncryption { keyfile } = encryption;
+        set.encryption_settings(OptString::from(Some(keyfile)), "encryption.keyfile");
+    }
     set
 }
@@ -32,7 +32,7 @@ use {
         de::{self, Deserializer, Visitor},
         Deserialize,
     },
-    std::net::IpAddr,
+    std::net::{IpAddr, SocketAddr},
 };
 
 /// The BGSAVE configuration
@@ -116,6 +116,103 @@ impl<'de> Deserialize<'de> for ProtocolVersion {
     }
 }
 
+/// The fsync policy for the write-ahead log
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum WalFsync {
+    /// fsync after every append
+    Always,
+    /// fsync once every second (at most one second of writes can be lost)
+    #[default]
+    EverySecond,
+    /// never fsync; leave it to the OS
+    Never,
+}
+
+impl FromStr for WalFsync {
+    type Err = ();
+    fn from_str(st: &str) -> Result<Self, Self::Err> {
+        match st {
+            "always" => Ok(Self::Always),
+            "everysec" => Ok(Self::EverySecond),
+            "never" => Ok(Self::Never),
+            _ => Err(()),
+        }
+    }
+}
+
+struct WalFsyncVisitor;
+
+impl<'de> Visitor<'de> for WalFsyncVisitor {
+    type Value = WalFsync;
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a string with 'always', 'everysec' or 'never'")
+    }
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        value.parse().map_err(|_| {
+            E::custom("Invalid value for WAL fsync policy. Valid inputs: always, everysec, never")
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for WalFsync {
+    fn deserialize<D>(deserializer: D) -> Result<WalFsync, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(WalFsyncVisitor)
+    }
+}
+
+/// The replication configuration
+#[derive(Debug, PartialEq)]
+pub enum ReplicationConfig {
+    /// Accept writes (and serve followers)
+    Leader,
+    /// Replicate everything from a leader and reject writes
+    Follower(FollowerPref),
+}
+
+impl ReplicationConfig {
+    /// Every node is a leader unless it is configured to follow another node
+    pub const fn default() -> Self {
+        Self::Leader
+    }
+}
+
+/// The settings of a follower
+#[derive(Debug, PartialEq)]
+pub struct FollowerPref {
+    /// The address of the leader
+    pub leader: SocketAddr,
+    /// The username and token to log in to the leader with (if it has authn enabled)
+    pub login: Option<(String, String)>,
+}
+
+impl FollowerPref {
+    pub const fn new(leader: SocketAddr, login: Option<(String, String)>) -> Self {
+        Self { leader, login }
+    }
+}
+
+/// The encryption at rest configuration
+#[derive(Debug, PartialEq)]
+pub enum EncryptionConfig {
+    /// Data files are written unencrypted
+    Disabled,
+    /// Data files are encrypted with the key in this key file
+    Enabled(String),
+}
+
+impl EncryptionConfig {
+    /// Encryption is disabled unless a key file is configured
+    pub const fn default() -> Self {
+        Self::Disabled
+    }
+}
+
 /// A `ConfigurationSet` which can be used by main::check_args_or_connect() to bind
 /// to a `TcpListener` and show the corresponding terminal output for the given
 /// configuration
@@ -131,12 +228,20 @@ pub struct ConfigurationSet {
     pub ports: PortConfig,
     /// The maximum number of connections
     pub maxcon: usize,
+    /// The number of bytes that volatile models may hold in total (`0` for no limit)
+    pub maxmemory: usize,
     /// The deployment mode
     pub mode: Modeset,
     /// The auth settings
     pub auth: AuthSettings,
     /// The protocol version
     pub protocol: ProtocolVersion,
+    /// The write-ahead log fsync policy
+    pub wal: WalFsync,
+    /// The replication configuration
+    pub replication: ReplicationConfig,
+    /// The encryption at rest configuration
+    pub encryption: EncryptionConfig,
 }
 
 impl ConfigurationSet {
@@ -147,9 +252,13 @@ impl ConfigurationSet {
         snapshot: SnapshotConfig,
         ports: PortConfig,
         maxcon: usize,
+        maxmemory: usize,
         mode: Modeset,
         auth: AuthSettings,
         protocol: ProtocolVersion,
+        wal: WalFsync,
+        replication: ReplicationConfig,
+        encryption: EncryptionConfig,
     ) -> Self {
         Self {
             noart,
@@ -157,9 +266,13 @@ impl ConfigurationSet {
             snapshot,
             ports,
             maxcon,
+            maxmemory,
             mode,
             auth,
             protocol,
+            wal,
+            replication,
+            encryption,
         }
     }
     /// Create a default `ConfigurationSet` with the following setup defaults:
@@ -169,6 +282,10 @@ impl ConfigurationSet {
     /// - `bgsave_enabled` : true
     /// - `bgsave_duration` : 120
     /// - `ssl` : disabled
+    /// - `maxmemory` : no limit
+    /// - `wal.fsync` : everysec
+    /// - `replication` : leader
+    /// - `encryption` : disabled
     pub const fn default() -> Self {
         Self::new(
             false,
@@ -176,9 +293,13 @@ impl ConfigurationSet {
             SnapshotConfig::default(),
             PortConfig::new_insecure_only(DEFAULT_IPV4, 2003),
             MAXIMUM_CONNECTION_LIMIT,
+            0,
             Modeset::Dev,
             AuthSettings::default(),
             ProtocolVersion::V2,
+            WalFsync::EverySecond,
+            ReplicationConfig::default(),
+            EncryptionConfig::default(),
         )
     }
     /// Returns `false` if `noart` is enabled. Otherwise it returns `true`
@@ -31,7 +31,7 @@ use {
     std::{
         env::VarError,
         fs,
-        net::{IpAddr, Ipv4Addr},
+        net::{IpAddr, Ipv4Addr, SocketAddr},
     },
 };
 
@@ -429,6 +429,90 @@ impl Configset {
     }
 }
 
+// WAL settings
+impl Configset {
+    pub fn wal_settings(
+        &mut self,
+        nfsync: impl TryFromConfigSource<WalFsync>,
+        nfsync_key: StaticStr,
+    ) {
+        let mut fsync = WalFsync::default();
+        self.try_mutate(
+            nfsync,
+            &mut fsync,
+            nfsync_key,
+            "a string with 'always', 'everysec' or 'never'",
+        );
+        if fsync == WalFsync::Never {
+            self.wstack
+                .push("WAL fsync is disabled. Recent writes may be lost if the host crashes");
+        }
+        self.cfg.wal = fsync;
+    }
+}
+
+// replication settings
+impl Configset {
+    pub fn replication_settings(
+        &mut self,
+        nleader: impl TryFromConfigSource<SocketAddr>,
+        nleader_key: StaticStr,
+        nuser: impl TryFromConfigSource<OptString>,
+        nuser_key: StaticStr,
+        npass: impl TryFromConfigSource<OptString>,
+        npass_key: StaticStr,
+    ) {
+        let has_leader = nleader.is_present();
+        let mut leader = SocketAddr::new(DEFAULT_IPV4, DEFAULT_PORT);
+        let mut user = OptString::new_null();
+        let mut pass = OptString::new_null();
+        self.try_mutate(
+            nleader,
+            &mut leader,
+            nleader_key,
+            "a socket address like 127.0.0.1:2003",
+        );
+        self.try_mutate(nuser, &mut user, nuser_key, "a username");
+        self.try_mutate(npass, &mut pass, npass_key, "a token");
+        if !has_leader {
+            for (key, value) in [(nuser_key, &user), (npass_key, &pass)] {
+                if value.base.is_some() {
+                    self.wstack.push(format!(
+                        "Specifying `{key}` is pointless when `{nleader_key}` is not set"
+                    ));
+                }
+            }
+            return;
+        }
+        let login = match (user.base, pass.base) {
+            (Some(user), Some(pass)) => Some((user, pass)),
+            (None, None) => None,
+            _ => {
+                self.estack.push(format!(
+                    "To log in to the leader, pass values for both `{nuser_key}` and `{npass_key}`"
+                ));
+                None
+            }
+        };
+        self.cfg.replication = ReplicationConfig::Follower(FollowerPref::new(leader, login));
+    }
+}
+
+// encryption settings
+impl Configset {
+    pub fn encryption_settings(
+        &mut self,
+        nkeyfile: impl TryFromConfigSource<OptString>,
+        nkeyfile_key: StaticStr,
+    ) {
+        let mut keyfile = OptString::new_null();
+        self.try_mutate(nkeyfile, &mut keyfile, nkeyfile_key, "a path to a key file");
+        if let Some(keyfile) = keyfile.base {
+            self.cfg.encryption = EncryptionConfig::Enabled(keyfile);
+        }
+    }
+}
+
 // server settings
 impl Configset {
     pub fn server_tcp(
@@ -464,6 +548,20 @@ impl Configset {
         );
         self.cfg.maxcon = maxcon;
     }
+    pub fn server_maxmemory(
+        &mut self,
+        nmaxmemory: impl TryFromConfigSource<usize>,
+        nmaxmemory_key: StaticStr,
+    ) {
+        let mut maxmemory = 0;
+        self.try_mutate(
+            nmaxmemory,
+            &mut maxmemory,
+            nmaxmemory_key,
+            "a number of bytes (or 0 for no limit)",
+        );
+        self.cfg.maxmemory = maxmemory;
+    }
     pub fn server_mode(&mut self, nmode: impl TryFromConfigSource<Modeset>, nmode_key: StaticStr) {
         let mut modeset = Modeset::Dev;
         self.try_mutate(
@@ -669,6 +767,12 @@ pub fn get_config() -> Result<ConfigType, ConfigError> {
     let cfg_layout = load_yaml!("../cli.yml");
     let matches = App::from_yaml(cfg_layout).get_matches();
     let restore_file = matches.value_of("restore").map(|v| v.to_string());
+    if let Some(datadir) = matches.value_of("verify") {
+        // this is an offline check; there's no server to configure (but encrypted files
+        // can only be checked with their key)
+        let keyfile = matches.value_of("encryptionkeyfile");
+        crate::storage::v1::verify::verify_and_exit(datadir, keyfile);
+    }
 
     // get config from file
     let cfg_from_file = if let Some(file) = matches.value_of("config") {
@@ -25,7 +25,10 @@
 */
 
 use {
-    super::{BGSave, Configset, PortConfig, SnapshotConfig, SnapshotPref, SslOpts, DEFAULT_IPV4},
+    super::{
+        BGSave, Configset, EncryptionConfig, FollowerPref, PortConfig, ReplicationConfig,
+        SnapshotConfig, SnapshotPref, SslOpts, WalFsync, DEFAULT_IPV4,
+    },
     crate::ROOT_DIR,
     std::fs,
 };
@@ -161,6 +164,28 @@ fn server_maxcon_fail() {
     assert_eq!(cfgset.cfg.maxcon, 50000);
 }
 
+#[test]
+fn server_maxmemory_okay() {
+    let mut cfgset = Configset::new_env();
+    cfgset.server_maxmemory(Some("1048576"), "SKY_SYSTEM_MAXMEMORY");
+    assert!(cfgset.is_mutated());
+    assert!(cfgset.is_okay());
+    assert_eq!(cfgset.cfg.maxmemory, 1048576);
+}
+
+#[test]
+fn server_maxmemory_fail() {
+    let mut cfgset = Configset::new_env();
+    cfgset.server_maxmemory(Some("1mb"), "SKY_SYSTEM_MAXMEMORY");
+    assert!(cfgset.is_mutated());
+    assert!(!cfgset.is_okay());
+    assert_eq!(
+        cfgset.estack[0],
+        "Bad value for `SKY_SYSTEM_MAXMEMORY`. Expected a number of bytes (or 0 for no limit)"
+    );
+    assert_eq!(cfgset.cfg.maxmemory, 0);
+}
+
 // bgsave settings
 #[test]
 fn bgsave_okay() {
@@ -194,6 +219,130 @@ fn bgsave_fail() {
     assert_eq!(cfgset.cfg.bgsave, BGSave::Enabled(128));
 }
 
+// WAL settings
+#[test]
+fn wal_okay() {
+    let mut cfgset = Configset::new_env();
+    cfgset.wal_settings(Some("always"), "SKY_WAL_FSYNC");
+    assert!(cfgset.is_mutated());
+    assert!(cfgset.is_okay());
+    assert_eq!(cfgset.cfg.wal, WalFsync::Always);
+}
+
+#[test]
+fn wal_fail() {
+    let mut cfgset = Configset::new_env();
+    cfgset.wal_settings(Some("sometimes"), "SKY_WAL_FSYNC");
+    assert!(cfgset.is_mutated());
+    assert!(!cfgset.is_okay());
+    assert_eq!(
+        cfgset.estack[0],
+        "Bad value for `SKY_WAL_FSYNC`. Expected a string with 'always', 'everysec' or 'never'"
+    );
+    assert_eq!(cfgset.cfg.wal, WalFsync::EverySecond);
+}
+
+// replication settings
+#[test]
+fn replication_okay() {
+    let mut cfgset = Configset::new_env();
+    cfgset.replication_settings(
+        Some("127.0.0.1:2005"),
+        "SKY_REPLICATION_LEADER",
+        Some("replicator"),
+        "SKY_REPLICATION_USERNAME",
+        Some("token"),
+        "SKY_REPLICATION_PASSWORD",
+    );
+    assert!(cfgset.is_mutated());
+    assert!(cfgset.is_okay());
+    assert_eq!(
+        cfgset.cfg.replication,
+        ReplicationConfig::Follower(FollowerPref::new(
+            "127.0.0.1:2005".parse().unwrap(),
+            Some(("replicator".to_owned(), "token".to_owned()))
+        ))
+    );
+}
+
+#[test]
+fn replication_fail_bad_leader() {
+    let mut cfgset = Configset::new_env();
+    cfgset.replication_settings(
+        Some("localhost"),
+        "SKY_REPLICATION_LEADER",
+        None,
+        "SKY_REPLICATION_USERNAME",
+        None,
+        "SKY_REPLICATION_PASSWORD",
+    );
+    assert!(cfgset.is_mutated());
+    assert!(!cfgset.is_okay());
+    assert_eq!(
+        cfgset.estack[0],
+        "Bad value for `SKY_REPLICATION_LEADER`. Expected a socket address like 127.0.0.1:2003"
+    );
+}
+
+#[test]
+fn replication_fail_user_without_token() {
+    let mut cfgset = Configset::new_env();
+    cfgset.replication_settings(
+        Some("127.0.0.1:2005"),
+        "SKY_REPLICATION_LEADER",
+        Some("replicator"),
+        "SKY_REPLICATION_USERNAME",
+        None,
+        "SKY_REPLICATION_PASSWORD",
+    );
+    assert!(!cfgset.is_okay());
+    assert_eq!(
+        cfgset.estack[0],
+        "To log in to the leader, pass values for both `SKY_REPLICATION_USERNAME` and `SKY_REPLICATION_PASSWORD`"
+    );
+}
+
+#[test]
+fn replication_login_without_leader() {
+    let mut cfgset = Configset::new_env();
+    cfgset.replication_settings(
+        None,
+        "SKY_REPLICATION_LEADER",
+        Some("replicator"),
+        "SKY_REPLICATION_USERNAME",
+        None,
+        "SKY_REPLICATION_PASSWORD",
+    );
+    assert!(cfgset.is_mutated());
+    assert!(cfgset.is_okay());
+    assert_eq!(
+        cfgset.wstack[0],
+        "Specifying `SKY_REPLICATION_USERNAME` is pointless when `SKY_REPLICATION_LEADER` is not set"
+    );
+    assert_eq!(cfgset.cfg.replication, ReplicationConfig::Leader);
+}
+
+// encryption settings
+#[test]
+fn encryption_okay() {
+    let mut cfgset = Configset::new_env();
+    cfgset.encryption_settings(Some("/path/to/skyd.key"), "SKY_ENCRYPTION_KEY_FILE");
+    assert!(cfgset.is_mutated());
+    assert!(cfgset.is_okay());
+    assert_eq!(
+        cfgset.cfg.encryption,
+        EncryptionConfig::Enabled("/path/to/skyd.key".to_owned())
+    );
+}
+
+#[test]
+fn encryption_disabled_by_default() {
+    let mut cfgset = Configset::new_env();
+    cfgset.encryption_settings(None, "SKY_ENCRYPTION_KEY_FILE");
+    assert!(!cfgset.is_mutated());
+    assert_eq!(cfgset.cfg.encryption, EncryptionConfig::Disabled);
+}
+
 // snapshot settings
 #[test]
 fn snapshot_okay() {
@@ -346,8 +495,9 @@ mod cfg_file_tests {
     use super::get_toml_from_examples_dir;
     use crate::config::AuthkeyWrapper;
     use crate::config::{
-        cfgfile, AuthSettings, BGSave, Configset, ConfigurationSet, Modeset, PortConfig,
-        ProtocolVersion, SnapshotConfig, SnapshotPref, SslOpts, DEFAULT_IPV4, DEFAULT_PORT,
+        cfgfile, AuthSettings, BGSave, Configset, ConfigurationSet, EncryptionConfig, Modeset,
+        PortConfig, ProtocolVersion, ReplicationConfig, SnapshotConfig, SnapshotPref, SslOpts,
+        WalFsync, DEFAULT_IPV4, DEFAULT_PORT,
     };
     use crate::dbnet::MAXIMUM_CONNECTION_LIMIT;
     use std::net::{IpAddr, Ipv6Addr};
@@ -401,9 +551,13 @@ mod cfg_file_tests {
                 snapshot: SnapshotConfig::default(),
                 ports: PortConfig::default(),
                 maxcon: MAXIMUM_CONNECTION_LIMIT,
+                maxmemory: 0,
                 mode: Modeset::Dev,
                 auth: AuthSettings::default(),
                 protocol: ProtocolVersion::default(),
+                wal: WalFsync::default(),
+                replication: ReplicationConfig::default(),
+                encryption: EncryptionConfig::default(),
             }
         );
     }
@@ -423,9 +577,13 @@ mod cfg_file_tests {
                     DEFAULT_PORT
                 ),
                 maxcon: MAXIMUM_CONNECTION_LIMIT,
+                maxmemory: 0,
                 mode: Modeset::Dev,
                 auth: AuthSettings::default(),
                 protocol: ProtocolVersion::default(),
+                wal: WalFsync::default(),
+                replication: ReplicationConfig::default(),
+                encryption: EncryptionConfig::default(),
             }
         );
     }
@@ -450,9 +608,13 @@ mod cfg_file_tests {
                     )
                 ),
                 MAXIMUM_CONNECTION_LIMIT,
+                0,
                 Modeset::Dev,
                 AuthSettings::new(AuthkeyWrapper::try_new(crate::TEST_AUTH_ORIGIN_KEY).unwrap()),
-                ProtocolVersion::default()
+                ProtocolVersion::default(),
+                WalFsync::default(),
+                ReplicationConfig::default(),
+                EncryptionConfig::default()
             )
         );
     }
@@ -476,9 +638,13 @@ mod cfg_file_tests {
                 snapshot: SnapshotConfig::default(),
                 ports: PortConfig::default(),
                 maxcon: MAXIMUM_CONNECTION_LIMIT,
+                maxmemory: 0,
                 mode: Modeset::Dev,
                 auth: AuthSettings::default(),
                 protocol: ProtocolVersion::default(),
+                wal: WalFsync::default(),
+                replication: ReplicationConfig::default(),
+                encryption: EncryptionConfig::default(),
             }
         );
     }
@@ -499,9 +665,13 @@ mod cfg_file_tests {
                 snapshot: SnapshotConfig::default(),
                 ports: PortConfig::default(),
                 maxcon: MAXIMUM_CONNECTION_LIMIT,
+                maxmemory: 0,
                 mode: Modeset::Dev,
                 auth: AuthSettings::default(),
                 protocol: ProtocolVersion::default(),
+                wal: WalFsync::default(),
+                replication: ReplicationConfig::default(),
+                encryption: EncryptionConfig::default(),
             }
         )
     }
@@ -522,9 +692,13 @@ mod cfg_file_tests {
                 snapshot: SnapshotConfig::default(),
                 ports: PortConfig::default(),
                 maxcon: MAXIMUM_CONNECTION_LIMIT,
+                maxmemory: 0,
                 mode: Modeset::Dev,
                 auth: AuthSettings::default(),
                 protocol: ProtocolVersion::default(),
+                wal: WalFsync::default(),
+                replication: ReplicationConfig::default(),
+                encryption: EncryptionConfig::default(),
             }
         )
     }
@@ -541,12 +715,33 @@ mod cfg_file_tests {
                 noart: false,
                 ports: PortConfig::default(),
                 maxcon: MAXIMUM_CONNECTION_LIMIT,
+                maxmemory: 0,
                 mode: Modeset::Dev,
                 auth: AuthSettings::default(),
                 protocol: ProtocolVersion::default(),
+                wal: WalFsync::default(),
+                replication: ReplicationConfig::default(),
+                encryption: EncryptionConfig::default(),
             }
         );
     }
+
+    #[test]
+    fn test_config_file_encryption() {
+        let file = r#"
+            [server]
+            host = "127.0.0.1"
+            port = 2003
+            [encryption]
+            keyfile = "/path/to/skyd.key"
+        "#;
+        let cfg = cfgset_from_toml_str(file.to_owned()).unwrap();
+        assert!(cfg.is_okay());
+        assert_eq!(
+            cfg.cfg.encryption,
+            EncryptionConfig::Enabled("/path/to/skyd.key".to_owned())
+        );
+    }
 }
 
 mod cli_arg_tests {
@@ -28,7 +28,7 @@
 
 use {
     crate::corestore::map::{
-        bref::{Entry, OccupiedEntry, Ref, VacantEntry},
+        bref::{Entry, OccupiedEntry, Ref, ShardLocks, VacantEntry},
         iter::{BorrowedIter, OwnedIter},
         Skymap,
     },
@@ -156,6 +156,23 @@ where
             false
         }
     }
+    /// Returns the entry for the given key. The entry holds the write lock on its shard until
+    /// it is dropped
+    pub fn entry(&self, key: K) -> Entry<'_, K, V, RandomState> {
+        self.inner.entry(key)
+    }
+    /// Write lock the shards that the provided keys belong to, so that all of them can be read
+    /// and changed atomically
+    pub fn lock_keys<'q, Q>(
+        &self,
+        keys: impl IntoIterator<Item = &'q Q>,
+    ) -> ShardLocks<'_, K, V, RandomState>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized + 'q,
+    {
+        self.inner.lock_shards(keys)
+    }
     pub fn mut_entry(&self, key: K) -> Option<OccupiedEntry<K, V, RandomState>> {
         if let Entry::Occupied(oe) = self.inner.entry(key) {
             Some(oe)
@@ -192,6 +209,15 @@ impl<K: Eq + Hash + Clone, V> Coremap<K, V> {
             .for_each(|key| v.push(key));
         v
     }
+    /// Returns atmost `count` keys from a part of the hashtable picked by `seed`
+    pub fn sample_keys(&self, seed: usize, count: usize) -> Vec<K> {
+        self.inner.sample_keys(seed, count)
+    }
+    /// Returns the keys at the next `count` positions from `cursor` along with the cursor to
+    /// resume from (`0` once every key has been visited). See [`Skymap::scan`]
+    pub fn scan(&self, cursor: u64, count: usize) -> (u64, Vec<K>) {
+        self.inner.scan(cursor, count)
+    }
 }
 
 impl<K: Eq + Hash, V> IntoIterator for Coremap<K, V> {
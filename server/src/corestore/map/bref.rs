@@ -25,9 +25,10 @@
 */
 
 use {
-    super::LowMap,
+    super::{LowMap, Skymap},
     crate::util::{compiler, Unwrappable},
     core::{
+        borrow::Borrow,
         hash::{BuildHasher, Hash},
         mem,
         ops::{Deref, DerefMut},
@@ -180,6 +181,79 @@ impl<'a, K: Hash + Eq, V, S: BuildHasher> VacantEntry<'a, K, V, S> {
     }
 }
 
+/// Write locks on a set of shards that are held until this is dropped. Only the keys that
+/// belong to one of the locked shards can be accessed through it
+pub struct ShardLocks<'a, K, V, S> {
+    map: &'a Skymap<K, V, S>,
+    /// the guards, sorted by the index of their shard
+    guards: Vec<(usize, RwLockWriteGuard<'a, LowMap<K, V>>)>,
+}
+
+impl<'a, K: Hash + Eq, V, S: BuildHasher + Clone> ShardLocks<'a, K, V, S> {
+    pub(super) fn new(
+        map: &'a Skymap<K, V, S>,
+        guards: Vec<(usize, RwLockWriteGuard<'a, LowMap<K, V>>)>,
+    ) -> Self {
+        Self { map, guards }
+    }
+    /// Returns the position of the guard for the shard that `hash` belongs to
+    fn position(&self, hash: u64) -> usize {
+        let shard = self.map.determine_shard(hash as usize);
+        match self.guards.binary_search_by_key(&shard, |(idx, _)| *idx) {
+            Ok(pos) => pos,
+            Err(_) => panic!("the shard for this key wasn't locked"),
+        }
+    }
+    /// Get a ref to the value of a key
+    pub fn get<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = super::make_hash::<K, Q, S>(self.map.h(), k);
+        let pos = self.position(hash);
+        self.guards[pos].1.get(hash, super::ceq(k)).map(|(_, v)| v)
+    }
+    /// Get a mutable ref to the value of a key
+    pub fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = super::make_hash::<K, Q, S>(self.map.h(), k);
+        let pos = self.position(hash);
+        self.guards[pos]
+            .1
+            .get_mut(hash, super::ceq(k))
+            .map(|(_, v)| v)
+    }
+    /// Insert a key/value, returning the previous value (if any)
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        let hash = super::make_insert_hash::<K, S>(self.map.h(), &k);
+        let pos = self.position(hash);
+        let lowtable = &mut self.guards[pos].1;
+        if let Some((_, item)) = lowtable.get_mut(hash, super::ceq(&k)) {
+            Some(mem::replace(item, v))
+        } else {
+            lowtable.insert(hash, (k, v), super::make_hasher::<K, _, V, S>(self.map.h()));
+            None
+        }
+    }
+    /// Remove a key/value
+    pub fn remove<Q>(&mut self, k: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = super::make_hash::<K, Q, S>(self.map.h(), k);
+        let pos = self.position(hash);
+        self.guards[pos].1.remove_entry(hash, super::ceq(k))
+    }
+}
+
+unsafe impl<'a, K: Send, V: Send, S: Sync> Send for ShardLocks<'a, K, V, S> {}
+unsafe impl<'a, K: Sync, V: Sync, S: Sync> Sync for ShardLocks<'a, K, V, S> {}
+
 /// An entry, either occupied or vacant
 pub enum Entry<'a, K, V, S = RandomState> {
     Occupied(OccupiedEntry<'a, K, V, S>),
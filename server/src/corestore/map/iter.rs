@@ -63,7 +63,7 @@ impl<K, V, S> Iterator for OwnedIter<K, V, S> {
             }
             let mut wshard = unsafe { self.map.get_wshard_unchecked(self.cs) };
             // get the next map's iterator
-            let current_map = mem::replace(&mut *wshard, LowMap::new());
+            let current_map = mem::take(&mut *wshard);
             drop(wshard);
             let iter = current_map.into_iter();
             self.current = Some(iter);
@@ -29,7 +29,7 @@
 
 use {
     self::{
-        bref::{Entry, OccupiedEntry, Ref, RefMut, VacantEntry},
+        bref::{Entry, OccupiedEntry, Ref, RefMut, ShardLocks, VacantEntry},
         iter::{BorrowedIter, OwnedIter},
     },
     crate::util::compiler,
@@ -40,18 +40,22 @@ use {
         iter::FromIterator,
         mem,
         num::NonZeroUsize,
+        ops::Deref,
     },
+    hashbrown::raw::{Bucket, RawIntoIter, RawTable},
     parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard},
-    std::{collections::hash_map::RandomState, thread::available_parallelism},
+    std::{
+        collections::{hash_map::RandomState, BTreeMap},
+        thread::available_parallelism,
+    },
 };
 
 pub mod bref;
 pub mod iter;
 
-type LowMap<K, V> = hashbrown::raw::RawTable<(K, V)>;
 type ShardSlice<K, V> = [RwLock<LowMap<K, V>>];
-type SRlock<'a, K, V> = RwLockReadGuard<'a, hashbrown::raw::RawTable<(K, V)>>;
-type SWlock<'a, K, V> = RwLockWriteGuard<'a, hashbrown::raw::RawTable<(K, V)>>;
+type SRlock<'a, K, V> = RwLockReadGuard<'a, LowMap<K, V>>;
+type SWlock<'a, K, V> = RwLockWriteGuard<'a, LowMap<K, V>>;
 const BITS_IN_USIZE: usize = mem::size_of::<usize>() * 8;
 const DEFAULT_CAP: usize = 128;
 
@@ -148,6 +152,101 @@ impl<K, V> Skymap<K, V, ahash::RandomState> {
     }
 }
 
+/// A shard: the table itself along with the hashes of the keys in it, in ascending order (and
+/// the number of keys sharing each hash). The ordered hashes let a scan resume from a hash
+/// without going over the whole table, so all changes to the keys must go through the methods
+/// here; reads go to the table directly
+pub struct LowMap<K, V> {
+    table: RawTable<(K, V)>,
+    hashes: BTreeMap<u64, usize>,
+}
+
+impl<K, V> LowMap<K, V> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+    pub fn with_capacity(cap: usize) -> Self {
+        Self {
+            table: RawTable::with_capacity(cap),
+            hashes: BTreeMap::new(),
+        }
+    }
+    /// Returns the hashes that are at least `from`, in ascending order
+    fn hashes_from(&self, from: u64) -> impl Iterator<Item = u64> + '_ {
+        self.hashes.range(from..).map(|(hash, _)| *hash)
+    }
+    fn add_hash(&mut self, hash: u64) {
+        *self.hashes.entry(hash).or_insert(0) += 1;
+    }
+    fn remove_hash(&mut self, hash: u64) {
+        if let Some(count) = self.hashes.get_mut(&hash) {
+            *count -= 1;
+            if *count == 0 {
+                self.hashes.remove(&hash);
+            }
+        }
+    }
+    pub fn get_mut(&mut self, hash: u64, eq: impl FnMut(&(K, V)) -> bool) -> Option<&mut (K, V)> {
+        self.table.get_mut(hash, eq)
+    }
+    pub fn insert(
+        &mut self,
+        hash: u64,
+        value: (K, V),
+        hasher: impl Fn(&(K, V)) -> u64,
+    ) -> Bucket<(K, V)> {
+        self.add_hash(hash);
+        self.table.insert(hash, value, hasher)
+    }
+    pub fn insert_entry(
+        &mut self,
+        hash: u64,
+        value: (K, V),
+        hasher: impl Fn(&(K, V)) -> u64,
+    ) -> &mut (K, V) {
+        self.add_hash(hash);
+        self.table.insert_entry(hash, value, hasher)
+    }
+    pub fn remove_entry(&mut self, hash: u64, eq: impl FnMut(&(K, V)) -> bool) -> Option<(K, V)> {
+        let removed = self.table.remove_entry(hash, eq);
+        if removed.is_some() {
+            self.remove_hash(hash);
+        }
+        removed
+    }
+    /// Remove the element in `bucket`, which must belong to this table and hold a key with
+    /// the provided hash
+    pub unsafe fn remove(&mut self, hash: u64, bucket: Bucket<(K, V)>) -> (K, V) {
+        self.remove_hash(hash);
+        self.table.remove(bucket)
+    }
+    pub fn clear(&mut self) {
+        self.hashes.clear();
+        self.table.clear();
+    }
+}
+
+impl<K, V> Default for LowMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> Deref for LowMap<K, V> {
+    type Target = RawTable<(K, V)>;
+    fn deref(&self) -> &Self::Target {
+        &self.table
+    }
+}
+
+impl<K, V> IntoIterator for LowMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = RawIntoIter<(K, V)>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.table.into_iter()
+    }
+}
+
 // basic impls
 impl<K, V, S> Skymap<K, V, S>
 where
@@ -277,7 +376,7 @@ where
                 Some(bucket) => {
                     let (kptr, vptr) = bucket.as_ref();
                     if f(kptr, vptr) {
-                        Some(lowtable.remove(bucket))
+                        Some(lowtable.remove(hash, bucket))
                     } else {
                         None
                     }
@@ -359,6 +458,28 @@ impl<'a, K: 'a + Hash + Eq, V: 'a, S: BuildHasher + Clone> Skymap<K, V, S> {
             // end critical section
         }
     }
+    /// Write lock the shards that the provided keys belong to. Shards are always locked in
+    /// ascending order, so two callers locking overlapping sets of keys can't deadlock
+    pub fn lock_shards<'q, Q>(
+        &'a self,
+        keys: impl IntoIterator<Item = &'q Q>,
+    ) -> ShardLocks<'a, K, V, S>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized + 'q,
+    {
+        let mut shards: Vec<usize> = keys
+            .into_iter()
+            .map(|k| self.determine_shard(make_hash::<K, Q, S>(self.h(), k) as usize))
+            .collect();
+        shards.sort_unstable();
+        shards.dedup();
+        let guards = shards
+            .into_iter()
+            .map(|idx| (idx, unsafe { self.get_wshard_unchecked(idx) }))
+            .collect();
+        ShardLocks::new(self, guards)
+    }
     /// Check if the Skymap contains the provided key
     pub fn contains_key<Q>(&self, key: &Q) -> bool
     where
@@ -394,6 +515,92 @@ impl<'a, K, V: Clone, S: BuildHasher> Skymap<K, V, S> {
     }
 }
 
+impl<K: Clone, V, S> Skymap<K, V, S> {
+    /// Returns atmost `count` keys, starting at a shard (and a position in it) picked by
+    /// `seed`. This is much cheaper than going over the whole map when a handful of keys
+    /// picked at random will do
+    pub fn sample_keys(&self, seed: usize, count: usize) -> Vec<K> {
+        let shard_count = self.shards.len();
+        let mut ret = Vec::with_capacity(count);
+        for i in 0..shard_count {
+            if ret.len() == count {
+                break;
+            }
+            let shard = unsafe { self.get_rshard_unchecked((seed + i) % shard_count) };
+            if shard.is_empty() {
+                continue;
+            }
+            let start = (seed / shard_count) % shard.len();
+            let buckets = unsafe {
+                // the shard is locked for as long as we hold the buckets
+                shard.iter()
+            };
+            let (tail, head): (Vec<_>, Vec<_>) = buckets
+                .enumerate()
+                .partition(|(position, _)| *position >= start);
+            for (_, bucket) in tail.into_iter().chain(head).take(count - ret.len()) {
+                ret.push(unsafe { bucket.as_ref() }.0.clone());
+            }
+        }
+        ret
+    }
+}
+
+impl<K: Hash + Clone, V, S: BuildHasher> Skymap<K, V, S> {
+    /// The number of low bits in a scan position that are left for the hash (the high bits
+    /// hold the shard)
+    const fn scan_hash_bits(&self) -> u32 {
+        64 - (BITS_IN_USIZE - self.shift) as u32
+    }
+    /// Returns the position of a key with the provided hash in the scan order: the shard in the
+    /// high bits followed by the hash, so that positions in one shard are all smaller than the
+    /// positions in the next one. Since it doesn't depend on where the key sits in the shard,
+    /// growing or shrinking a shard never moves a key to an earlier position
+    fn scan_position(&self, hash: u64) -> u64 {
+        let shard = self.determine_shard(hash as usize) as u64;
+        let hash_bits = self.scan_hash_bits();
+        (shard << hash_bits) | (hash >> (64 - hash_bits))
+    }
+    /// Returns the keys at the next `count` positions starting from `cursor` (a value returned by
+    /// a previous call or `0` to begin), along with the cursor to resume from. A returned cursor
+    /// of `0` means that the scan is complete.
+    ///
+    /// Every key present for the entire scan is returned at least once; keys added or removed
+    /// during the scan may or may not be. Keys sharing a position are always returned together
+    /// so a call may return a few more than `count` keys. Shards keep the hashes of their keys
+    /// in order, so a call only looks at the keys that it returns (and the shards it passes)
+    pub fn scan(&self, cursor: u64, count: usize) -> (u64, Vec<K>) {
+        let count = count.max(1);
+        let hash_bits = self.scan_hash_bits();
+        let mut ret = Vec::with_capacity(count);
+        let first_shard = (cursor >> hash_bits) as usize;
+        // the smallest hash at the cursor's position (the shift drops the shard bits)
+        let mut from = cursor << (64 - hash_bits);
+        for shard_id in first_shard..self.shards.len() {
+            let shard = unsafe { self.get_rshard_unchecked(shard_id) };
+            let mut last = None;
+            for hash in shard.hashes_from(from) {
+                let position = self.scan_position(hash);
+                if ret.len() >= count && last != Some(position) {
+                    // positions only grow, so this is past the last returned key (and not 0)
+                    return (position, ret);
+                }
+                last = Some(position);
+                let keys = unsafe {
+                    // the shard is locked for as long as we hold the buckets
+                    shard.iter_hash(hash)
+                }
+                .map(|bucket| &unsafe { bucket.as_ref() }.0)
+                // buckets are only matched on a few bits of the hash
+                .filter(|key| make_insert_hash(self.h(), *key) == hash);
+                ret.extend(keys.cloned());
+            }
+            from = 0;
+        }
+        (0, ret)
+    }
+}
+
 // inner impls
 impl<'a, K: 'a, V: 'a, S> Skymap<K, V, S> {
     /// Get a rlock to a certain stripe
@@ -430,6 +637,118 @@ fn test_insert_get() {
     assert_eq!(*_ref, "likes computational dark arts")
 }
 
+#[test]
+fn test_sample_keys() {
+    let map = Skymap::default();
+    for i in 0..100 {
+        map.insert(i, i);
+    }
+    for seed in [0, 7, 12345] {
+        let mut sample = map.sample_keys(seed, 5);
+        assert_eq!(sample.len(), 5);
+        sample.sort_unstable();
+        sample.dedup();
+        assert_eq!(sample.len(), 5);
+    }
+    assert_eq!(map.sample_keys(3, 500).len(), 100);
+}
+
+#[test]
+fn test_scan_complete() {
+    let map = Skymap::default();
+    for i in 0..1000 {
+        map.insert(i, i);
+    }
+    let (mut cursor, mut seen) = (0, Vec::new());
+    loop {
+        let (next, keys) = map.scan(cursor, 7);
+        assert!(next == 0 || keys.len() >= 7);
+        seen.extend(keys);
+        if next == 0 {
+            break;
+        }
+        cursor = next;
+    }
+    seen.sort_unstable();
+    assert_eq!(seen, (0..1000).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_scan_survives_growth() {
+    let map: Skymap<_, _> = Skymap::with_capacity(0);
+    for i in 0..100 {
+        map.insert(i, i);
+    }
+    let (mut cursor, mut seen) = (0, Vec::new());
+    let mut extra = 100;
+    loop {
+        let (next, keys) = map.scan(cursor, 10);
+        seen.extend(keys);
+        // force the shards to rehash between calls
+        for _ in 0..100 {
+            map.insert(extra, extra);
+            extra += 1;
+        }
+        if next == 0 {
+            break;
+        }
+        cursor = next;
+    }
+    seen.sort_unstable();
+    seen.dedup();
+    assert!((0..100).all(|key| seen.binary_search(&key).is_ok()));
+}
+
+#[test]
+fn test_scan_after_removals() {
+    let map = Skymap::default();
+    for i in 0..200 {
+        map.insert(i, i);
+    }
+    for i in 0..50 {
+        map.remove(&i);
+    }
+    for i in 50..100 {
+        map.remove_if(&i, |_, _| true);
+    }
+    for i in 100..150 {
+        if let Entry::Occupied(entry) = map.entry(i) {
+            entry.remove();
+        }
+    }
+    let mut locks = map.lock_shards([150, 151].iter());
+    locks.remove(&150);
+    locks.remove(&151);
+    drop(locks);
+    let (mut cursor, mut seen) = (0, Vec::new());
+    loop {
+        let (next, keys) = map.scan(cursor, 3);
+        seen.extend(keys);
+        if next == 0 {
+            break;
+        }
+        cursor = next;
+    }
+    seen.sort_unstable();
+    assert_eq!(seen, (152..200).collect::<Vec<_>>());
+    map.clear();
+    assert_eq!(map.scan(0, 10), (0, vec![]));
+}
+
+#[test]
+fn test_lock_shards() {
+    let map = Skymap::default();
+    map.insert("hello", "world");
+    let mut locks = map.lock_shards(["hello", "world", "hello"].iter());
+    assert_eq!(locks.get("hello"), Some(&"world"));
+    assert_eq!(locks.insert("world", "hello"), None);
+    *locks.get_mut("hello").unwrap() = "universe";
+    assert_eq!(locks.remove("world"), Some(("world", "hello")));
+    drop(locks);
+    assert_eq!(*map.get("hello").unwrap(), "universe");
+    assert!(!map.contains_key("world"));
+}
+
 #[test]
 fn test_entry() {
     let map = Skymap::default();
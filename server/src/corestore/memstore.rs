@@ -57,7 +57,7 @@
 use {
     super::KeyspaceResult,
     crate::{
-        auth::Authmap,
+        auth::{Authmap, Grantmap},
         corestore::{
             array::Array,
             htable::Coremap,
@@ -74,6 +74,7 @@ uninit_array! {
     const DEFAULT_ARRAY: [u8; 64] = [b'd', b'e', b'f', b'a', b'u', b'l', b't'];
     const SYSTEM_ARRAY: [u8; 64] = [b's', b'y', b's', b't', b'e', b'm'];
     const SYSTEM_AUTH_ARRAY: [u8; 64] = [b'a', b'u', b't', b'h'];
+    const SYSTEM_GRANTS_ARRAY: [u8; 64] = [b'g', b'r', b'a', b'n', b't', b's'];
 }
 
 /// typedef for the keyspace/table IDs. We don't need too much fancy here,
@@ -93,6 +94,10 @@ pub const AUTH: ObjectID = unsafe {
     // SAFETY: known init len
     Array::from_const(SYSTEM_AUTH_ARRAY, 4)
 };
+pub const GRANTS: ObjectID = unsafe {
+    // SAFETY: known init len
+    Array::from_const(SYSTEM_GRANTS_ARRAY, 6)
+};
 
 #[test]
 fn test_def_macro_sanity() {
@@ -119,19 +124,6 @@ mod cluster {
             Self::SingleNode
         }
     }
-
-    /// This is for the future for determining the replication strategy
-    #[derive(Debug)]
-    pub enum ReplicationStrategy {
-        /// Single node, no replica sets
-        Default,
-    }
-
-    impl Default for ReplicationStrategy {
-        fn default() -> Self {
-            Self::Default
-        }
-    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -226,7 +218,20 @@ impl Memstore {
             }
             None => match self.system.tables.get(&AUTH).unwrap().data {
                 SystemDataModel::Auth(ref am) => am.clone(),
-                #[allow(unreachable_patterns)]
+                _ => unsafe { impossible!() },
+            },
+        }
+    }
+    pub fn setup_grants(&self) -> Grantmap {
+        match self.system.tables.fresh_entry(GRANTS) {
+            Some(fresh) => {
+                // created afresh, fine
+                let r = Grantmap::default();
+                fresh.insert(Wrapper::new(SystemTable::new_grants(r.clone())));
+                r
+            }
+            None => match self.system.tables.get(&GRANTS).unwrap().data {
+                SystemDataModel::Grants(ref gm) => gm.clone(),
                 _ => unsafe { impossible!() },
             },
         }
@@ -241,8 +246,11 @@ impl Memstore {
     }
     /// Returns true if a new keyspace was created
     pub fn create_keyspace(&self, keyspace_identifier: ObjectID) -> bool {
-        self.keyspaces
-            .true_if_insert(keyspace_identifier, Arc::new(Keyspace::empty()))
+        self.create_keyspace_from(keyspace_identifier, Arc::new(Keyspace::empty()))
+    }
+    /// Same as [`Memstore::create_keyspace`], but adds the provided keyspace
+    pub fn create_keyspace_from(&self, keyspace_identifier: ObjectID, ks: Arc<Keyspace>) -> bool {
+        self.keyspaces.true_if_insert(keyspace_identifier, ks)
     }
     /// Drop a keyspace only if it is empty and has no clients connected to it
     ///
@@ -345,6 +353,15 @@ impl SystemKeyspace {
     pub const fn new(tables: Coremap<ObjectID, Wrapper<SystemTable>>) -> Self {
         Self { tables }
     }
+    /// Get a system keyspace with the same tables, for a store that replaces this one. The
+    /// auth provider holds on to the tables, so they must never be swapped out
+    pub fn share(&self) -> Self {
+        let tables = Coremap::with_capacity(self.tables.len());
+        for table in self.tables.iter() {
+            tables.true_if_insert(table.key().clone(), Wrapper::new(table.value().share()));
+        }
+        Self::new(tables)
+    }
 }
 
 #[derive(Debug)]
@@ -352,9 +369,6 @@ impl SystemKeyspace {
 pub struct Keyspace {
     /// the tables
     pub tables: Coremap<ObjectID, Arc<Table>>,
-    /// the replication strategy for this keyspace
-    #[allow(dead_code)] // TODO: Remove this once we're ready with replication
-    replication_strategy: cluster::ReplicationStrategy,
 }
 
 #[cfg(test)]
@@ -374,20 +388,15 @@ impl Keyspace {
                 ht.true_if_insert(DEFAULT, Arc::new(Table::new_default_kve()));
                 ht
             },
-            replication_strategy: cluster::ReplicationStrategy::default(),
         }
     }
-    pub fn init_with_all_def_strategy(tables: Coremap<ObjectID, Arc<Table>>) -> Self {
-        Self {
-            tables,
-            replication_strategy: cluster::ReplicationStrategy::default(),
-        }
+    pub fn init_with_tables(tables: Coremap<ObjectID, Arc<Table>>) -> Self {
+        Self { tables }
     }
     /// Create a new empty keyspace with zero tables
     pub fn empty() -> Self {
         Self {
             tables: Coremap::new(),
-            replication_strategy: cluster::ReplicationStrategy::default(),
         }
     }
     pub fn table_count(&self) -> usize {
@@ -28,20 +28,34 @@ use {
     crate::{
         actions::{translate_ddl_error, ActionResult},
         blueql::Entity,
+        config::WalFsync,
         corestore::{
+            lock::QuickLock,
             memstore::{DdlError, Keyspace, Memstore, ObjectID, DEFAULT},
             table::{DescribeTable, Table},
         },
+        kvengine::eviction::EvictionPolicy,
         protocol::interface::ProtocolSpec,
+        pubsub::PubSub,
         registry,
         storage::{
             self,
-            v1::{error::StorageEngineResult, sengine::SnapshotEngine},
+            v1::{
+                error::{ErrorContext, StorageEngineResult},
+                flush::{self, Autoflush},
+                sengine::SnapshotEngine,
+                unflush,
+                wal::{WalBatch, WriteAheadLog},
+            },
         },
         util::{self, Unwrappable},
     },
-    core::{borrow::Borrow, hash::Hash},
-    std::sync::Arc,
+    core::{
+        borrow::Borrow,
+        hash::Hash,
+        sync::atomic::{AtomicU64, Ordering},
+    },
+    std::{path::Path, sync::Arc},
 };
 
 pub mod array;
@@ -79,6 +93,21 @@ impl ConnectionEntityState {
             ks: Some((DEFAULT, ks)),
         }
     }
+    /// Resolve the same keyspace and table in `store`, dropping whatever doesn't exist there
+    fn resolve_in(&self, store: &Memstore) -> Self {
+        let ks = self.ks.as_ref().and_then(|(ksid, _)| {
+            store
+                .get_keyspace_atomic_ref(ksid)
+                .map(|ks| (ksid.clone(), ks))
+        });
+        let table = match (&ks, &self.table) {
+            (Some((_, ks)), Some((tblid, _))) => ks
+                .get_table_atomic_ref(tblid)
+                .map(|tbl| (tblid.clone(), tbl)),
+            _ => None,
+        };
+        Self { table, ks }
+    }
     fn set_ks(&mut self, ks: Arc<Keyspace>, ksid: ObjectID) {
         self.ks = Some((ksid, ks));
         self.table = None;
@@ -95,6 +124,15 @@ impl ConnectionEntityState {
     }
 }
 
+/// The store that is shared by all instances of [`Corestore`]. It's only ever replaced when
+/// a snapshot is restored online
+#[derive(Debug)]
+struct SharedStore {
+    store: QuickLock<Arc<Memstore>>,
+    /// the number of times the store was replaced
+    generation: AtomicU64,
+}
+
 /// The top level abstraction for the in-memory store. This is free to be shared across
 /// threads, cloned and well, whatever. Most importantly, clones have an independent container
 /// state that is the state of one connection and its container state preferences are never
@@ -102,34 +140,201 @@ impl ConnectionEntityState {
 #[derive(Debug, Clone)]
 pub struct Corestore {
     estate: ConnectionEntityState,
-    /// an atomic reference to the actual backing storage
+    /// an atomic reference to the actual backing storage (as of the last [`Corestore::refresh`])
     store: Arc<Memstore>,
+    /// the generation of `store`
+    generation: u64,
+    /// the latest store
+    shared: Arc<SharedStore>,
     /// the snapshot engine
     sengine: Arc<SnapshotEngine>,
+    /// the write-ahead log
+    wal: Arc<WriteAheadLog>,
+    /// the pub/sub channels
+    pubsub: Arc<PubSub>,
 }
 
 impl Corestore {
     /// This is the only function you'll ever need to either create a new database instance
     /// or restore from an earlier instance
-    pub fn init_with_snapcfg(sengine: Arc<SnapshotEngine>) -> StorageEngineResult<Self> {
+    pub fn init_with_snapcfg(
+        sengine: Arc<SnapshotEngine>,
+        wal_fsync: WalFsync,
+    ) -> StorageEngineResult<Self> {
         let store = storage::unflush::read_full()?;
-        Ok(Self::default_with_store(store, sengine))
+        let wal = WriteAheadLog::open(wal_fsync).map_err_context("opening write-ahead log")?;
+        Ok(Self::default_with_store(store, sengine, Arc::new(wal)))
     }
+    /// Get the latest store. Unlike [`Corestore::get_store`], this is never stale, so this is
+    /// what anything that doesn't run on behalf of a connection should use
     pub fn clone_store(&self) -> Arc<Memstore> {
-        self.store.clone()
+        self.shared.store.lock().clone()
+    }
+    /// Catch up with a store that was restored after this instance last looked at it. The
+    /// current keyspace and table are looked up again in the new store
+    pub fn refresh(&mut self) {
+        if self.shared.generation.load(Ordering::Acquire) == self.generation {
+            return;
+        }
+        let store = self.shared.store.lock();
+        self.generation = self.shared.generation.load(Ordering::Acquire);
+        self.store = store.clone();
+        drop(store);
+        self.estate = self.estate.resolve_in(&self.store);
+    }
+    /// Returns true if the store was replaced since this instance last looked at it
+    fn is_stale(&self) -> bool {
+        self.shared.generation.load(Ordering::Acquire) != self.generation
     }
-    pub fn default_with_store(store: Memstore, sengine: Arc<SnapshotEngine>) -> Self {
+    /// Replace all the user data with the snapshot at `root`, leaving users and grants as they
+    /// are. The data directory is rewritten with the restored data before this returns
+    ///
+    /// The global flush lock is held while the store is replaced and written so that a
+    /// `BGSAVE` never sees the old store after the log was rotated for the new one
+    pub fn restore_from(&self, root: &Path) -> StorageEngineResult<()> {
+        // read everything first so that a bad snapshot leaves the store untouched
+        let keyspaces = unflush::read_snapshot(root)?;
+        let flush_lock = registry::lock_flush_state();
+        let store = Arc::new(Memstore::init_with_all(
+            keyspaces,
+            self.clone_store().system.share(),
+        ));
+        let ret = self
+            .wal
+            .rotate_with(|| {
+                // the log is locked: writes to the old store that finish after this aren't
+                // logged (see `Corestore::unless_stale`)
+                let mut current = self.shared.store.lock();
+                *current = store.clone();
+                self.shared.generation.fetch_add(1, Ordering::AcqRel);
+            })
+            .map_err_context("rotating write-ahead log");
+        let ret = ret.and_then(|_| {
+            // followers have to pull a snapshot of the new store
+            self.wal.reset_feed();
+            registry::get_preload_tripswitch().trip();
+            registry::get_cleanup_tripswitch().trip();
+            flush::flush_full(Autoflush, &store)
+                .and_then(|_| self.wal.discard_rotated())
+                .map_err_context("writing restored data")
+        });
+        if ret.is_err() {
+            // refuse writes until a BGSAVE gets the data directory in shape
+            registry::poison();
+        }
+        drop(flush_lock);
+        ret
+    }
+    /// Record `f`'s changes in `batch` unless the store was replaced while `f` ran (the
+    /// changes were then made to a store that's gone)
+    fn unless_stale<T>(&self, batch: &mut WalBatch, f: impl FnOnce(&mut WalBatch) -> T) -> T {
+        let ret = f(batch);
+        if self.is_stale() {
+            batch.discard();
+        }
+        ret
+    }
+    fn log_ddl<T>(&self, f: impl FnOnce(&mut WalBatch) -> T) -> T {
+        self.wal.log_ddl(|batch| self.unless_stale(batch, f))
+    }
+    pub fn default_with_store(
+        store: Memstore,
+        sengine: Arc<SnapshotEngine>,
+        wal: Arc<WriteAheadLog>,
+    ) -> Self {
         let cks = unsafe { store.get_keyspace_atomic_ref(&DEFAULT).unsafe_unwrap() };
         let ctable = unsafe { cks.get_table_atomic_ref(&DEFAULT).unsafe_unwrap() };
+        let store = Arc::new(store);
         Self {
             estate: ConnectionEntityState::default(cks, ctable),
-            store: Arc::new(store),
+            store: store.clone(),
+            generation: 0,
+            shared: Arc::new(SharedStore {
+                store: QuickLock::new(store),
+                generation: AtomicU64::new(0),
+            }),
             sengine,
+            wal,
+            pubsub: Arc::new(PubSub::new()),
         }
     }
     pub fn get_engine(&self) -> &SnapshotEngine {
         &self.sengine
     }
+    pub fn get_pubsub(&self) -> &Arc<PubSub> {
+        &self.pubsub
+    }
+    pub fn get_wal(&self) -> &WriteAheadLog {
+        &self.wal
+    }
+    /// Run a mutation on the current table and append the after-images it records
+    /// to the write-ahead log (unless the table is volatile)
+    pub fn wal_log<T>(&self, f: impl FnOnce(&mut WalBatch) -> T) -> T {
+        let entity = match (&self.estate.ks, &self.estate.table) {
+            (Some((ksid, _)), Some((tblid, tbl))) if !tbl.is_volatile() => Some((ksid, tblid)),
+            _ => None,
+        };
+        let ret = self
+            .wal
+            .log_table(entity, |batch| self.unless_stale(batch, f));
+        if let Some((_, tbl)) = &self.estate.table {
+            // `f` no longer holds any locks, so what it changed can be accounted for
+            tbl.settle();
+        }
+        ret
+    }
+    /// Same as [`Corestore::wal_log`], but for the given `table` which was resolved
+    /// from `entity`
+    pub fn wal_log_for<T>(
+        &self,
+        entity: &Entity,
+        table: &Table,
+        f: impl FnOnce(&mut WalBatch) -> T,
+    ) -> T {
+        let ret = match self.resolve_ids(entity) {
+            Ok((ksid, tblid)) if !table.is_volatile() => self
+                .wal
+                .log_table(Some((&ksid, &tblid)), |batch| self.unless_stale(batch, f)),
+            _ => self.wal.log_table(None, f),
+        };
+        table.settle();
+        ret
+    }
+    /// Make room in the current table for a write that may need more memory: a volatile table
+    /// evicts keys (if its eviction policy allows it) until volatile tables are within the
+    /// memory limit. The write must be refused if this returns an error
+    pub fn ensure_room<P: ProtocolSpec>(&self) -> ActionResult<()> {
+        match self.get_ctable_ref() {
+            Some(table) => Self::ensure_room_in::<P>(table),
+            None => Ok(()),
+        }
+    }
+    /// Same as [`Corestore::ensure_room`], but for the given table
+    pub fn ensure_room_in<P: ProtocolSpec>(table: &Table) -> ActionResult<()> {
+        if table.make_room() {
+            Ok(())
+        } else {
+            util::err(P::RSTRING_OUT_OF_MEMORY)
+        }
+    }
+    /// Get the keyspace and table IDs that an entity refers to
+    fn resolve_ids(&self, entity: &Entity) -> KeyspaceResult<(ObjectID, ObjectID)> {
+        let (ksid, tblid) = match entity {
+            Entity::Current(tblid) => match &self.estate.ks {
+                Some((ksid, _)) => (ksid.clone(), unsafe { tblid.as_slice() }),
+                None => return Err(DdlError::DefaultNotFound),
+            },
+            Entity::Full(ksid, tblid) => unsafe {
+                (
+                    ObjectID::try_from_slice(ksid.as_slice()).ok_or(DdlError::ObjectNotFound)?,
+                    tblid.as_slice(),
+                )
+            },
+        };
+        let tblid = ObjectID::try_from_slice(tblid).ok_or(DdlError::ObjectNotFound)?;
+        Ok((ksid, tblid))
+    }
+    /// Get the store as of the last [`Corestore::refresh`]
     pub fn get_store(&self) -> &Memstore {
         &self.store
     }
@@ -236,59 +441,65 @@ impl Corestore {
         entity: &Entity,
         modelcode: u8,
         volatile: bool,
+        eviction: EvictionPolicy,
     ) -> KeyspaceResult<()> {
+        match Table::from_model_code(modelcode, volatile) {
+            Some(tbl) => self.create_table_from(entity, tbl.with_eviction(eviction)),
+            None => Err(DdlError::WrongModel),
+        }
+    }
+    /// Same as [`Corestore::create_table`], but adds the provided table. This is what models
+    /// that can't be described by a model code alone (like models with named fields) use
+    ///
+    /// **Trip switch handled:** Yes
+    /// The table may already hold data (like an imported one), which is logged along with it
+    pub fn create_table_from(&self, entity: &Entity, tbl: Table) -> KeyspaceResult<()> {
         // first lock the global flush state
         let flush_lock = registry::lock_flush_state();
-        let ret = match entity {
+        let ret = self.log_ddl(|batch| match entity {
             // Important: create table <tblname> is only ks
-            Entity::Current(tblid) => {
-                match &self.estate.ks {
-                    Some((_, ks)) => {
-                        let tbl = Table::from_model_code(modelcode, volatile);
-                        if let Some(tbl) = tbl {
-                            if ks.create_table(
-                                unsafe { ObjectID::from_slice(tblid.as_slice()) },
-                                tbl,
-                            ) {
-                                // we need to re-init tree; so trip
-                                registry::get_preload_tripswitch().trip();
-                                Ok(())
-                            } else {
-                                Err(DdlError::AlreadyExists)
-                            }
-                        } else {
-                            Err(DdlError::WrongModel)
-                        }
+            Entity::Current(tblid) => match &self.estate.ks {
+                Some((ksid, ks)) => {
+                    let tblid = unsafe { ObjectID::from_slice(tblid.as_slice()) };
+                    // log first; the table is moved into the keyspace
+                    batch.create_model_with_data(ksid, &tblid, &tbl);
+                    if ks.create_table(tblid, tbl) {
+                        // we need to re-init tree; so trip
+                        registry::get_preload_tripswitch().trip();
+                        Ok(())
+                    } else {
+                        batch.discard();
+                        Err(DdlError::AlreadyExists)
                     }
-                    None => Err(DdlError::DefaultNotFound),
                 }
-            }
+                None => Err(DdlError::DefaultNotFound),
+            },
             Entity::Full(ksid, tblid) => {
                 match self
                     .store
                     .get_keyspace_atomic_ref(unsafe { ksid.as_slice() })
                 {
                     Some(kspace) => {
-                        let tbl = Table::from_model_code(modelcode, volatile);
-                        if let Some(tbl) = tbl {
-                            if kspace.create_table(
-                                unsafe { ObjectID::from_slice(tblid.as_slice()) },
-                                tbl,
-                            ) {
-                                // trip the preload switch
-                                registry::get_preload_tripswitch().trip();
-                                Ok(())
-                            } else {
-                                Err(DdlError::AlreadyExists)
-                            }
+                        let (ksid, tblid) = unsafe {
+                            (
+                                ObjectID::from_slice(ksid.as_slice()),
+                                ObjectID::from_slice(tblid.as_slice()),
+                            )
+                        };
+                        batch.create_model_with_data(&ksid, &tblid, &tbl);
+                        if kspace.create_table(tblid, tbl) {
+                            // trip the preload switch
+                            registry::get_preload_tripswitch().trip();
+                            Ok(())
                         } else {
-                            Err(DdlError::WrongModel)
+                            batch.discard();
+                            Err(DdlError::AlreadyExists)
                         }
                     }
                     None => Err(DdlError::ObjectNotFound),
                 }
             }
-        };
+        });
         // free the global flush lock
         drop(flush_lock);
         ret
@@ -296,6 +507,16 @@ impl Corestore {
 
     /// Drop a table
     pub fn drop_table(&self, entity: &Entity, force: bool) -> KeyspaceResult<()> {
+        let (ksid, tblid) = self.resolve_ids(entity)?;
+        self.log_ddl(|batch| {
+            let ret = self.drop_table_inner(entity, force);
+            if ret.is_ok() {
+                batch.drop_model(&ksid, &tblid);
+            }
+            ret
+        })
+    }
+    fn drop_table_inner(&self, entity: &Entity, force: bool) -> KeyspaceResult<()> {
         match entity {
             Entity::Current(tblid) => match &self.estate.ks {
                 Some((_, ks)) => ks.drop_table(unsafe { tblid.as_slice() }, force),
@@ -317,17 +538,31 @@ impl Corestore {
     ///
     /// **Trip switch handled:** Yes
     pub fn create_keyspace(&self, ksid: ObjectID) -> KeyspaceResult<()> {
+        self.create_keyspace_from(ksid, Keyspace::empty())
+    }
+    /// Same as [`Corestore::create_keyspace`], but adds the provided keyspace. Its tables
+    /// may already hold data (like imported ones), which is logged along with them
+    ///
+    /// **Trip switch handled:** Yes
+    pub fn create_keyspace_from(&self, ksid: ObjectID, ks: Keyspace) -> KeyspaceResult<()> {
         // lock the global flush lock (see comment in create_table to know why)
         let flush_lock = registry::lock_flush_state();
-        let ret = if self.store.create_keyspace(ksid) {
-            // woo, created
-            // trip the preload switch
-            registry::get_preload_tripswitch().trip();
-            Ok(())
-        } else {
-            // ugh, already exists
-            Err(DdlError::AlreadyExists)
-        };
+        let ret = self.log_ddl(|batch| {
+            batch.create_space(&ksid);
+            for table in ks.tables.iter() {
+                batch.create_model_with_data(&ksid, table.key(), table.value());
+            }
+            if self.store.create_keyspace_from(ksid.clone(), Arc::new(ks)) {
+                // woo, created
+                // trip the preload switch
+                registry::get_preload_tripswitch().trip();
+                Ok(())
+            } else {
+                // ugh, already exists
+                batch.discard();
+                Err(DdlError::AlreadyExists)
+            }
+        });
         drop(flush_lock);
         ret
     }
@@ -335,16 +570,29 @@ impl Corestore {
     /// Drop a keyspace
     pub fn drop_keyspace(&self, ksid: ObjectID) -> KeyspaceResult<()> {
         // trip switch is handled by memstore here
-        self.store.drop_keyspace(ksid)
+        self.log_ddl(|batch| {
+            let ret = self.store.drop_keyspace(ksid.clone());
+            if ret.is_ok() {
+                batch.drop_space(&ksid);
+            }
+            ret
+        })
     }
 
     /// Force drop a keyspace
     pub fn force_drop_keyspace(&self, ksid: ObjectID) -> KeyspaceResult<()> {
         // trip switch is handled by memstore here
-        self.store.force_drop_keyspace(ksid)
+        self.log_ddl(|batch| {
+            let ret = self.store.force_drop_keyspace(ksid.clone());
+            if ret.is_ok() {
+                batch.drop_space(&ksid);
+            }
+            ret
+        })
     }
+    /// Returns the number of instances
     pub fn strong_count(&self) -> usize {
-        Arc::strong_count(&self.store)
+        Arc::strong_count(&self.shared)
     }
     pub fn get_ids(&self) -> (Option<&ObjectID>, Option<&ObjectID>) {
         self.estate.get_id_pack()
@@ -376,6 +624,6 @@ impl Corestore {
             Some(tbl) => translate_ddl_error::<P, Arc<Table>>(self.get_table(tbl))?.describe_self(),
             None => translate_ddl_error::<P, &Table>(self.get_ctable_result())?.describe_self(),
         };
-        Ok(r.to_owned())
+        Ok(r.into_owned())
     }
 }
@@ -27,6 +27,7 @@
 use std::{
     alloc::{alloc, dealloc, Layout},
     borrow::Borrow,
+    cmp,
     fmt::Debug,
     hash::Hash,
     ops::Deref,
@@ -205,6 +206,22 @@ impl From<Vec<u8>> for SharedSlice {
 
 impl Eq for SharedSlice {}
 
+impl PartialOrd for SharedSlice {
+    #[inline(always)]
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Slices are ordered by their bytes, just like `[u8]` (which ordered collections rely on
+/// when they're searched with a `&[u8]`)
+impl Ord for SharedSlice {
+    #[inline(always)]
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.as_slice().cmp(other.as_slice())
+    }
+}
+
 /// The shared state structure
 struct SharedSliceInner {
     /// data ptr
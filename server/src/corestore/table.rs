@@ -28,13 +28,30 @@
 use crate::corestore::{memstore::DdlError, KeyspaceResult};
 use crate::{
     actions::ActionResult,
-    auth::Authmap,
+    auth::{Authmap, Grantmap},
     corestore::{htable::Coremap, SharedSlice},
     dbnet::prelude::Corestore,
-    kvengine::{KVEListmap, KVEStandard, LockedVec},
+    kvengine::{
+        eviction::EvictionPolicy,
+        hashmap::{Hashmap, LockedMap},
+        queue::{KVEQueuemap, LockedQueue},
+        rowmap::{Rowmap, Schema},
+        sets::{KVESetmap, KVESortedSetmap, LockedSet, LockedSortedSet},
+        types::FieldType,
+        KVEListmap, KVEStandard, LockedVec,
+    },
     protocol::interface::ProtocolSpec,
+    pubsub::PubSub,
+    storage::v1::bytemarks,
     util,
 };
+use std::{
+    borrow::Cow,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
 
 pub trait DescribeTable {
     type Table;
@@ -55,10 +72,23 @@ pub trait DescribeTable {
 
 pub struct KVEBlob;
 
+/// Ordered tables are also key/value tables
 impl DescribeTable for KVEBlob {
     type Table = KVEStandard;
     fn try_get(table: &Table) -> Option<&Self::Table> {
-        if let DataModel::KV(ref kve) = table.model_store {
+        match table.model_store {
+            DataModel::KV(ref kve) | DataModel::KVOrdered(ref kve) => Some(kve),
+            _ => None,
+        }
+    }
+}
+
+pub struct KVEOrdered;
+
+impl DescribeTable for KVEOrdered {
+    type Table = KVEStandard;
+    fn try_get(table: &Table) -> Option<&Self::Table> {
+        if let DataModel::KVOrdered(ref kve) = table.model_store {
             Some(kve)
         } else {
             None
@@ -79,9 +109,62 @@ impl DescribeTable for KVEList {
     }
 }
 
+pub struct KVEMap;
+
+impl DescribeTable for KVEMap {
+    type Table = Hashmap;
+    fn try_get(table: &Table) -> Option<&Self::Table> {
+        if let DataModel::Hashmap(ref hm) = table.model_store {
+            Some(hm)
+        } else {
+            None
+        }
+    }
+}
+
+pub struct KVESet;
+
+impl DescribeTable for KVESet {
+    type Table = KVESetmap;
+    fn try_get(table: &Table) -> Option<&Self::Table> {
+        if let DataModel::Set(ref set) = table.model_store {
+            Some(set)
+        } else {
+            None
+        }
+    }
+}
+
+pub struct KVESortedSet;
+
+impl DescribeTable for KVESortedSet {
+    type Table = KVESortedSetmap;
+    fn try_get(table: &Table) -> Option<&Self::Table> {
+        if let DataModel::SortedSet(ref zset) = table.model_store {
+            Some(zset)
+        } else {
+            None
+        }
+    }
+}
+
+pub struct KVEQueue;
+
+impl DescribeTable for KVEQueue {
+    type Table = KVEQueuemap;
+    fn try_get(table: &Table) -> Option<&Self::Table> {
+        if let DataModel::Queue(ref queue) = table.model_store {
+            Some(queue)
+        } else {
+            None
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum SystemDataModel {
     Auth(Authmap),
+    Grants(Grantmap),
 }
 
 #[derive(Debug)]
@@ -100,16 +183,54 @@ impl SystemTable {
     pub fn new_auth(authmap: Authmap) -> Self {
         Self::new(SystemDataModel::Auth(authmap))
     }
+    pub fn new_grants(grantmap: Grantmap) -> Self {
+        Self::new(SystemDataModel::Grants(grantmap))
+    }
+    /// Get another handle to the same data
+    pub fn share(&self) -> Self {
+        match &self.data {
+            SystemDataModel::Auth(authmap) => Self::new_auth(authmap.clone()),
+            SystemDataModel::Grants(grantmap) => Self::new_grants(grantmap.clone()),
+        }
+    }
 }
 
 #[derive(Debug)]
 pub enum DataModel {
     KV(KVEStandard),
     KVExtListmap(KVEListmap),
+    Rowmap(Rowmap),
+    /// A key/value store that keeps its keys sorted
+    KVOrdered(KVEStandard),
+    /// A key/value store whose values are maps
+    Hashmap(Hashmap),
+    /// A key/value store whose values are sets
+    Set(KVESetmap),
+    /// A key/value store whose values are sets of scored members
+    SortedSet(KVESortedSetmap),
+    /// A key/value store whose values are reliable queues
+    Queue(KVEQueuemap),
+}
+
+impl DataModel {
+    fn track_memory(&mut self, policy: EvictionPolicy) {
+        match self {
+            Self::KV(kv) | Self::KVOrdered(kv) => kv.track_memory(policy),
+            Self::KVExtListmap(kv) => kv.track_memory(policy),
+            Self::Rowmap(rm) => rm.rows_mut().track_memory(policy),
+            Self::Hashmap(hm) => hm.maps_mut().track_memory(policy),
+            Self::Set(set) => set.track_memory(policy),
+            Self::SortedSet(zset) => zset.track_memory(policy),
+            Self::Queue(queue) => queue.track_memory(policy),
+        }
+    }
 }
 
 // same 8 byte ptrs; any chance of optimizations?
 
+/// The saved mutation count of a table that was never written to the data directory
+const NEVER_SAVED: u64 = u64::MAX;
+
 #[derive(Debug)]
 /// The underlying table type. This is the place for the other data models (soon!)
 pub struct Table {
@@ -117,28 +238,35 @@ pub struct Table {
     model_store: DataModel,
     /// is the table volatile
     volatile: bool,
+    /// the mutation count of the table when its files in the data directory were last written
+    saved: AtomicU64,
 }
 
 impl Table {
-    #[cfg(test)]
-    pub const fn from_kve(kve: KVEStandard, volatile: bool) -> Self {
+    fn with_model(mut model_store: DataModel, volatile: bool) -> Self {
+        if volatile {
+            // volatile tables are caches that count towards the memory limit
+            model_store.track_memory(EvictionPolicy::NoEviction);
+        }
         Self {
-            model_store: DataModel::KV(kve),
+            model_store,
             volatile,
+            saved: AtomicU64::new(NEVER_SAVED),
         }
     }
     #[cfg(test)]
-    pub const fn from_kve_listmap(kve: KVEListmap, volatile: bool) -> Self {
-        Self {
-            model_store: DataModel::KVExtListmap(kve),
-            volatile,
-        }
+    pub fn from_kve(kve: KVEStandard, volatile: bool) -> Self {
+        Self::with_model(DataModel::KV(kve), volatile)
+    }
+    #[cfg(test)]
+    pub fn from_kve_listmap(kve: KVEListmap, volatile: bool) -> Self {
+        Self::with_model(DataModel::KVExtListmap(kve), volatile)
     }
     /// Get the key/value store if the table is a key/value store
     #[cfg(test)]
     pub const fn get_kvstore(&self) -> KeyspaceResult<&KVEStandard> {
         #[allow(irrefutable_let_patterns)]
-        if let DataModel::KV(kvs) = &self.model_store {
+        if let DataModel::KV(kvs) | DataModel::KVOrdered(kvs) = &self.model_store {
             Ok(kvs)
         } else {
             Err(DdlError::WrongModel)
@@ -146,13 +274,40 @@ impl Table {
     }
     pub fn count(&self) -> usize {
         match &self.model_store {
-            DataModel::KV(kv) => kv.len(),
+            DataModel::KV(kv) | DataModel::KVOrdered(kv) => kv.len(),
             DataModel::KVExtListmap(kv) => kv.len(),
+            DataModel::Rowmap(rm) => rm.len(),
+            DataModel::Hashmap(hm) => hm.len(),
+            DataModel::Set(set) => set.len(),
+            DataModel::SortedSet(zset) => zset.len(),
+            DataModel::Queue(queue) => queue.len(),
         }
     }
     /// Returns this table's _description_
-    pub fn describe_self(&self) -> &'static str {
-        match self.get_model_code() {
+    pub fn describe_self(&self) -> Cow<'static, str> {
+        let description = self.describe_model();
+        let mut properties = Vec::new();
+        if let Some(max_len) = self.max_len() {
+            properties.push(format!("maxlen:{max_len}"));
+        }
+        if let Some(policy) = self
+            .eviction_policy()
+            .filter(|policy| *policy != EvictionPolicy::NoEviction)
+        {
+            properties.push(format!("eviction:{}", policy.name()));
+        }
+        if properties.is_empty() {
+            description
+        } else {
+            Cow::Owned(format!(
+                "{}, {} }}",
+                description.trim_end_matches(" }"),
+                properties.join(", ")
+            ))
+        }
+    }
+    fn describe_model(&self) -> Cow<'static, str> {
+        let ret = match self.get_model_code() {
             // pure KV
             0 if self.is_volatile() => "Keymap { data:(binstr,binstr), volatile:true }",
             0 if !self.is_volatile() => "Keymap { data:(binstr,binstr), volatile:false }",
@@ -171,21 +326,213 @@ impl Table {
             6 if !self.is_volatile() => "Keymap { data:(str,list<binstr>), volatile:false }",
             7 if self.is_volatile() => "Keymap { data:(str,list<str>), volatile:true }",
             7 if !self.is_volatile() => "Keymap { data:(str,list<str>), volatile:false }",
+            8 => {
+                return Cow::Owned(format!(
+                    "Rowmap {{ data:{}, volatile:{} }}",
+                    self.get_schema().map(Schema::describe).unwrap_or_default(),
+                    self.is_volatile()
+                ))
+            }
+            // ordered KV
+            9..=12 => {
+                let (t_k, t_v) = match self.model_store {
+                    DataModel::KVOrdered(ref kvs) => kvs.get_type_tuple(),
+                    _ => unsafe { impossible!() },
+                };
+                return Cow::Owned(format!(
+                    "Keymap {{ data:({},{}), volatile:{}, ordered:true }}",
+                    t_k.name(),
+                    t_v.name(),
+                    self.is_volatile()
+                ));
+            }
+            // sets and queues
+            code if code >= bytemarks::BYTEMARK_MODEL_KV_SET => {
+                let (kind, (t_k, t_m)) = match self.model_store {
+                    DataModel::Set(ref set) => ("set", set.get_type_tuple()),
+                    DataModel::SortedSet(ref zset) => ("zset", zset.get_type_tuple()),
+                    DataModel::Queue(ref queue) => ("queue", queue.get_type_tuple()),
+                    _ => unsafe { impossible!() },
+                };
+                return Cow::Owned(format!(
+                    "Keymap {{ data:({},{}<{}>), volatile:{} }}",
+                    t_k.name(),
+                    kind,
+                    t_m.name(),
+                    self.is_volatile()
+                ));
+            }
+            // hashmap
+            code if code >= bytemarks::BYTEMARK_MODEL_KV_MAP => {
+                let hm = match self.model_store {
+                    DataModel::Hashmap(ref hm) => hm,
+                    _ => unsafe { impossible!() },
+                };
+                let (t_k, t_v) = hm.maps().get_type_tuple();
+                return Cow::Owned(format!(
+                    "Keymap {{ data:({},map<{},{}>), volatile:{} }}",
+                    t_k.name(),
+                    hm.get_field_type().name(),
+                    t_v.name(),
+                    self.is_volatile()
+                ));
+            }
+            // typed KV
+            code if code >= bytemarks::BYTEMARK_MODEL_KV_TYPED => {
+                let (t_k, t_v) = match self.model_store {
+                    DataModel::KV(ref kvs) => kvs.get_type_tuple(),
+                    _ => unsafe { impossible!() },
+                };
+                return Cow::Owned(format!(
+                    "Keymap {{ data:({},{}), volatile:{} }}",
+                    t_k.name(),
+                    t_v.name(),
+                    self.is_volatile()
+                ));
+            }
             _ => unsafe { impossible!() },
-        }
+        };
+        Cow::Borrowed(ret)
     }
     pub fn truncate_table(&self) {
         match self.model_store {
-            DataModel::KV(ref kv) => kv.truncate_table(),
+            DataModel::KV(ref kv) | DataModel::KVOrdered(ref kv) => kv.truncate_table(),
             DataModel::KVExtListmap(ref kv) => kv.truncate_table(),
+            DataModel::Rowmap(ref rm) => rm.truncate_table(),
+            DataModel::Hashmap(ref hm) => hm.truncate_table(),
+            DataModel::Set(ref set) => set.truncate_table(),
+            DataModel::SortedSet(ref zset) => zset.truncate_table(),
+            DataModel::Queue(ref queue) => queue.truncate_table(),
         }
     }
     pub fn is_empty(&self) -> bool {
         self.count() == 0
     }
-    /// Returns the storage type as an 8-bit uint
-    pub const fn storage_type(&self) -> u8 {
-        self.volatile as u8
+    /// Returns the number of changes made to the table's data so far
+    pub fn mutation_count(&self) -> u64 {
+        match self.model_store {
+            DataModel::KV(ref kv) | DataModel::KVOrdered(ref kv) => kv.mutation_count(),
+            DataModel::KVExtListmap(ref kv) => kv.mutation_count(),
+            DataModel::Rowmap(ref rm) => rm.rows().mutation_count(),
+            DataModel::Hashmap(ref hm) => hm.maps().mutation_count(),
+            DataModel::Set(ref set) => set.mutation_count(),
+            DataModel::SortedSet(ref zset) => zset.mutation_count(),
+            DataModel::Queue(ref queue) => queue.mutation_count(),
+        }
+    }
+    /// Returns true if the table's files in the data directory were written when its
+    /// mutation count was `count`
+    pub fn is_saved_at(&self, count: u64) -> bool {
+        self.saved.load(Ordering::Acquire) == count
+    }
+    /// Record that the table's files in the data directory hold its data as of the
+    /// mutation count `count`
+    pub fn mark_saved(&self, count: u64) {
+        self.saved.store(count, Ordering::Release)
+    }
+    /// Forget when the table was last written, so that it's written on the next flush
+    pub fn mark_unsaved(&self) {
+        self.saved.store(NEVER_SAVED, Ordering::Release)
+    }
+    /// Returns the registry of watched keys
+    pub fn get_watchers(&self) -> &Arc<PubSub> {
+        match self.model_store {
+            DataModel::KV(ref kv) | DataModel::KVOrdered(ref kv) => kv.get_watchers(),
+            DataModel::KVExtListmap(ref kv) => kv.get_watchers(),
+            DataModel::Rowmap(ref rm) => rm.rows().get_watchers(),
+            DataModel::Hashmap(ref hm) => hm.maps().get_watchers(),
+            DataModel::Set(ref set) => set.get_watchers(),
+            DataModel::SortedSet(ref zset) => zset.get_watchers(),
+            DataModel::Queue(ref queue) => queue.get_watchers(),
+        }
+    }
+    /// Set the eviction policy of a volatile table. This does nothing for a persistent table
+    pub fn with_eviction(mut self, policy: EvictionPolicy) -> Self {
+        if self.volatile {
+            self.model_store.track_memory(policy);
+        }
+        self
+    }
+    /// Cap the lists of a list model at `max_len` elements. Returns `None` if this isn't a list
+    /// model or if one of its lists is already longer than that
+    pub fn with_max_len(mut self, max_len: usize) -> Option<Self> {
+        match self.model_store {
+            DataModel::KVExtListmap(ref mut kvl) if max_len != 0 => {
+                let fits = kvl
+                    .get_inner_ref()
+                    .iter()
+                    .all(|list| list.value().read().len() <= max_len);
+                if fits {
+                    kvl.set_max_len(max_len);
+                    Some(self)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+    /// Returns the maximum length of the lists if this is a capped list model
+    pub fn max_len(&self) -> Option<usize> {
+        match self.model_store {
+            DataModel::KVExtListmap(ref kvl) => kvl.max_len(),
+            _ => None,
+        }
+    }
+    /// Returns the eviction policy if the table is volatile
+    pub fn eviction_policy(&self) -> Option<EvictionPolicy> {
+        match self.model_store {
+            DataModel::KV(ref kv) | DataModel::KVOrdered(ref kv) => kv.eviction_policy(),
+            DataModel::KVExtListmap(ref kv) => kv.eviction_policy(),
+            DataModel::Rowmap(ref rm) => rm.rows().eviction_policy(),
+            DataModel::Hashmap(ref hm) => hm.maps().eviction_policy(),
+            DataModel::Set(ref set) => set.eviction_policy(),
+            DataModel::SortedSet(ref zset) => zset.eviction_policy(),
+            DataModel::Queue(ref queue) => queue.eviction_policy(),
+        }
+    }
+    /// Account for the writes made to a volatile or ordered table
+    pub fn settle(&self) {
+        match self.model_store {
+            DataModel::KV(ref kv) | DataModel::KVOrdered(ref kv) => kv.settle(),
+            DataModel::KVExtListmap(ref kv) => kv.settle(),
+            DataModel::Rowmap(ref rm) => rm.rows().settle(),
+            DataModel::Hashmap(ref hm) => hm.maps().settle(),
+            DataModel::Set(ref set) => set.settle(),
+            DataModel::SortedSet(ref zset) => zset.settle(),
+            DataModel::Queue(ref queue) => queue.settle(),
+        }
+    }
+    /// Evict keys from a volatile table until volatile tables are within the memory limit.
+    /// Returns `false` if the table can't make enough room
+    pub fn make_room(&self) -> bool {
+        match self.model_store {
+            DataModel::KV(ref kv) | DataModel::KVOrdered(ref kv) => kv.make_room(),
+            DataModel::KVExtListmap(ref kv) => kv.make_room(),
+            DataModel::Rowmap(ref rm) => rm.rows().make_room(),
+            DataModel::Hashmap(ref hm) => hm.maps().make_room(),
+            DataModel::Set(ref set) => set.make_room(),
+            DataModel::SortedSet(ref zset) => zset.make_room(),
+            DataModel::Queue(ref queue) => queue.make_room(),
+        }
+    }
+    /// Returns the storage type as an 8-bit uint. The eviction policy of a volatile table is
+    /// stored in the bits above the storage bytemark
+    pub fn storage_type(&self) -> u8 {
+        let policy = self.eviction_policy().map_or(0, |policy| policy.code());
+        self.volatile as u8 | policy << bytemarks::BYTEMARK_STORAGE_EVICTION_SHIFT
+    }
+    /// Returns the volatility and the eviction policy stored in a storage type (see
+    /// [`Table::storage_type`]). Returns `None` if the storage type is invalid
+    pub fn from_storage_type(storage: u8) -> Option<(bool, EvictionPolicy)> {
+        let volatile = storage & 1 == bytemarks::BYTEMARK_STORAGE_VOLATILE;
+        let policy =
+            EvictionPolicy::from_code(storage >> bytemarks::BYTEMARK_STORAGE_EVICTION_SHIFT)?;
+        if volatile || policy == EvictionPolicy::NoEviction {
+            Some((volatile, policy))
+        } else {
+            None
+        }
     }
     /// Returns the volatility of the table
     pub const fn is_volatile(&self) -> bool {
@@ -198,10 +545,34 @@ impl Table {
         k_enc: bool,
         v_enc: bool,
     ) -> Self {
-        Self {
+        Self::with_model(
+            DataModel::KV(KVEStandard::new(k_enc, v_enc, data)),
             volatile,
-            model_store: DataModel::KV(KVEStandard::new(k_enc, v_enc, data)),
-        }
+        )
+    }
+    /// Create a new ordered KVEBlob Table with the provided settings
+    pub fn new_ordered_kve_with_data(
+        data: Coremap<SharedSlice, SharedSlice>,
+        volatile: bool,
+        k_enc: bool,
+        v_enc: bool,
+    ) -> Self {
+        Self::with_model(
+            DataModel::KVOrdered(KVEStandard::new(k_enc, v_enc, data).into_ordered()),
+            volatile,
+        )
+    }
+    /// Create a new KVEBlob Table whose key or value is a number
+    pub fn new_typed_kve_with_data(
+        data: Coremap<SharedSlice, SharedSlice>,
+        volatile: bool,
+        t_k: FieldType,
+        t_v: FieldType,
+    ) -> Self {
+        Self::with_model(
+            DataModel::KV(KVEStandard::new_typed(t_k, t_v, data)),
+            volatile,
+        )
     }
     pub fn new_kve_listmap_with_data(
         data: Coremap<SharedSlice, LockedVec>,
@@ -209,10 +580,69 @@ impl Table {
         k_enc: bool,
         payload_enc: bool,
     ) -> Self {
-        Self {
+        Self::with_model(
+            DataModel::KVExtListmap(KVEListmap::new(k_enc, payload_enc, data)),
             volatile,
-            model_store: DataModel::KVExtListmap(KVEListmap::new(k_enc, payload_enc, data)),
-        }
+        )
+    }
+    /// Create a new hashmap Table with the provided maps
+    pub fn new_hashmap_with_data(
+        data: Coremap<SharedSlice, LockedMap>,
+        volatile: bool,
+        k_enc: bool,
+        f_enc: bool,
+        v_enc: bool,
+    ) -> Self {
+        Self::with_model(
+            DataModel::Hashmap(Hashmap::new(k_enc, f_enc, v_enc, data)),
+            volatile,
+        )
+    }
+    /// Create a new set Table with the provided sets
+    pub fn new_set_with_data(
+        data: Coremap<SharedSlice, LockedSet>,
+        volatile: bool,
+        k_enc: bool,
+        m_enc: bool,
+    ) -> Self {
+        Self::with_model(DataModel::Set(KVESetmap::new(k_enc, m_enc, data)), volatile)
+    }
+    /// Create a new sorted set Table with the provided sorted sets
+    pub fn new_sorted_set_with_data(
+        data: Coremap<SharedSlice, LockedSortedSet>,
+        volatile: bool,
+        k_enc: bool,
+        m_enc: bool,
+    ) -> Self {
+        Self::with_model(
+            DataModel::SortedSet(KVESortedSetmap::new(k_enc, m_enc, data)),
+            volatile,
+        )
+    }
+    /// Create a new queue Table with the provided queues
+    pub fn new_queue_with_data(
+        data: Coremap<SharedSlice, LockedQueue>,
+        volatile: bool,
+        k_enc: bool,
+        i_enc: bool,
+    ) -> Self {
+        Self::with_model(
+            DataModel::Queue(KVEQueuemap::new(k_enc, i_enc, data)),
+            volatile,
+        )
+    }
+    /// Create a new rowmap Table with the provided schema and rows
+    pub fn new_rowmap_with_data(
+        schema: Schema,
+        data: Coremap<SharedSlice, LockedVec>,
+        volatile: bool,
+    ) -> Self {
+        Self::with_model(DataModel::Rowmap(Rowmap::new(schema, data)), volatile)
+    }
+    /// Create a new empty rowmap Table. Unlike the other models, a rowmap can't be created
+    /// from its model code alone since it also needs a schema
+    pub fn new_rowmap(schema: Schema, volatile: bool) -> Self {
+        Self::new_rowmap_with_data(schema, Coremap::new(), volatile)
     }
     pub fn from_model_code(code: u8, volatile: bool) -> Option<Self> {
         macro_rules! pkve {
@@ -225,6 +655,11 @@ impl Table {
                 Self::new_kve_listmap_with_data(Coremap::new(), volatile, $kenc, $penc)
             };
         }
+        macro_rules! okve {
+            ($kenc:expr, $venc:expr) => {
+                Self::new_ordered_kve_with_data(Coremap::new(), volatile, $kenc, $venc)
+            };
+        }
         let ret = match code {
             // pure kve
             0 => pkve!(false, false),
@@ -236,7 +671,36 @@ impl Table {
             5 => listmap!(false, true),
             6 => listmap!(true, false),
             7 => listmap!(true, true),
-            _ => return None,
+            // ordered kve
+            9 => okve!(false, false),
+            10 => okve!(false, true),
+            11 => okve!(true, true),
+            12 => okve!(true, false),
+            // queue
+            code if code >= bytemarks::BYTEMARK_MODEL_KV_QUEUE => {
+                let (k_enc, i_enc) = Self::encodings_from_queue_model_code(code)?;
+                Self::new_queue_with_data(Coremap::new(), volatile, k_enc, i_enc)
+            }
+            // sorted set
+            code if code >= bytemarks::BYTEMARK_MODEL_KV_SORTED_SET => {
+                let (k_enc, m_enc) = Self::encodings_from_sorted_set_model_code(code)?;
+                Self::new_sorted_set_with_data(Coremap::new(), volatile, k_enc, m_enc)
+            }
+            // set
+            code if code >= bytemarks::BYTEMARK_MODEL_KV_SET => {
+                let (k_enc, m_enc) = Self::encodings_from_set_model_code(code)?;
+                Self::new_set_with_data(Coremap::new(), volatile, k_enc, m_enc)
+            }
+            // hashmap
+            code if code >= bytemarks::BYTEMARK_MODEL_KV_MAP => {
+                let (k_enc, f_enc, v_enc) = Self::encodings_from_map_model_code(code)?;
+                Self::new_hashmap_with_data(Coremap::new(), volatile, k_enc, f_enc, v_enc)
+            }
+            // typed kve
+            code => {
+                let (t_k, t_v) = Self::types_from_typed_kv_model_code(code)?;
+                Self::new_typed_kve_with_data(Coremap::new(), volatile, t_k, t_v)
+            }
         };
         Some(ret)
     }
@@ -252,9 +716,84 @@ impl Table {
     pub fn new_default_kve() -> Self {
         Self::new_pure_kve_with_data(Coremap::new(), false, false, false)
     }
+    /// Returns the model code for a KVEBlob whose key or value is a number
+    pub const fn typed_kv_model_code(t_k: FieldType, t_v: FieldType) -> u8 {
+        bytemarks::BYTEMARK_MODEL_KV_TYPED + (t_k.code() << 3) + t_v.code()
+    }
+    /// Returns the model code for the ordered version of a pure KVEBlob model code. Returns
+    /// `None` for other models (numbers would be sorted by their text, which isn't useful)
+    pub fn ordered_kv_model_code(code: u8) -> Option<u8> {
+        if code <= bytemarks::BYTEMARK_MODEL_KV_STR_BIN {
+            Some(code + bytemarks::BYTEMARK_MODEL_KV_ORDERED_BIN_BIN)
+        } else {
+            None
+        }
+    }
+    /// Returns the model code for a hashmap with the given key, field and value encodings
+    pub const fn map_model_code(k_enc: bool, f_enc: bool, v_enc: bool) -> u8 {
+        bytemarks::BYTEMARK_MODEL_KV_MAP + ((k_enc as u8) << 2) + ((f_enc as u8) << 1) + v_enc as u8
+    }
+    /// Returns the key, field and value encodings for a hashmap model code. Returns `None` if
+    /// the code isn't one of a hashmap
+    pub fn encodings_from_map_model_code(code: u8) -> Option<(bool, bool, bool)> {
+        match code.checked_sub(bytemarks::BYTEMARK_MODEL_KV_MAP)? {
+            code @ 0..=7 => Some((code & 0b100 != 0, code & 0b010 != 0, code & 0b001 != 0)),
+            _ => None,
+        }
+    }
+    /// Returns the model code for a set with the given key and member encodings
+    pub const fn set_model_code(k_enc: bool, m_enc: bool) -> u8 {
+        bytemarks::BYTEMARK_MODEL_KV_SET + ((k_enc as u8) << 1) + m_enc as u8
+    }
+    /// Returns the model code for a sorted set with the given key and member encodings
+    pub const fn sorted_set_model_code(k_enc: bool, m_enc: bool) -> u8 {
+        bytemarks::BYTEMARK_MODEL_KV_SORTED_SET + ((k_enc as u8) << 1) + m_enc as u8
+    }
+    /// Returns the model code for a queue with the given key and item encodings
+    pub const fn queue_model_code(k_enc: bool, i_enc: bool) -> u8 {
+        bytemarks::BYTEMARK_MODEL_KV_QUEUE + ((k_enc as u8) << 1) + i_enc as u8
+    }
+    /// Returns the key and member encodings for a set model code. Returns `None` if the code
+    /// isn't one of a set
+    pub fn encodings_from_set_model_code(code: u8) -> Option<(bool, bool)> {
+        Self::encodings_from_packed(code.checked_sub(bytemarks::BYTEMARK_MODEL_KV_SET)?)
+    }
+    /// Returns the key and member encodings for a sorted set model code. Returns `None` if
+    /// the code isn't one of a sorted set
+    pub fn encodings_from_sorted_set_model_code(code: u8) -> Option<(bool, bool)> {
+        Self::encodings_from_packed(code.checked_sub(bytemarks::BYTEMARK_MODEL_KV_SORTED_SET)?)
+    }
+    /// Returns the key and item encodings for a queue model code. Returns `None` if the code
+    /// isn't one of a queue
+    pub fn encodings_from_queue_model_code(code: u8) -> Option<(bool, bool)> {
+        Self::encodings_from_packed(code.checked_sub(bytemarks::BYTEMARK_MODEL_KV_QUEUE)?)
+    }
+    fn encodings_from_packed(packed: u8) -> Option<(bool, bool)> {
+        match packed {
+            0..=3 => Some((packed & 0b10 != 0, packed & 0b01 != 0)),
+            _ => None,
+        }
+    }
+    /// Returns the key and value types for a typed KVEBlob model code. Returns `None` if the
+    /// code isn't one of a typed KVEBlob
+    pub fn types_from_typed_kv_model_code(code: u8) -> Option<(FieldType, FieldType)> {
+        let code = code.checked_sub(bytemarks::BYTEMARK_MODEL_KV_TYPED)?;
+        let t_k = FieldType::from_code(code >> 3)?;
+        let t_v = FieldType::from_code(code & 0b111)?;
+        // models without numbers have their own codes
+        if t_k.is_numeric() || t_v.is_numeric() {
+            Some((t_k, t_v))
+        } else {
+            None
+        }
+    }
     /// Returns the model code. See [`bytemarks`] for more info
     pub fn get_model_code(&self) -> u8 {
         match self.model_store {
+            DataModel::KV(ref kvs) if kvs.has_numeric_type() => {
+                let (t_k, t_v) = kvs.get_type_tuple();
+                Self::typed_kv_model_code(t_k, t_v)
+            }
             DataModel::KV(ref kvs) => {
                 /*
                 bin,bin => 0
@@ -277,6 +816,36 @@ impl Table {
                 let (kenc, venc) = kvlistmap.get_encoding_tuple();
                 ((kenc as u8) << 1) + (venc as u8) + 4
             }
+            DataModel::Rowmap(_) => 8,
+            DataModel::KVOrdered(ref kvs) => {
+                // same as a pure KVEBlob, starting from 9
+                let (kenc, venc) = kvs.get_encoding_tuple();
+                let ret = kenc as u8 + venc as u8;
+                (ret & 1) + ((kenc as u8) << 1) + bytemarks::BYTEMARK_MODEL_KV_ORDERED_BIN_BIN
+            }
+            DataModel::Hashmap(ref hm) => {
+                let (kenc, fenc, venc) = hm.get_encoding_triple();
+                Self::map_model_code(kenc, fenc, venc)
+            }
+            DataModel::Set(ref set) => {
+                let (kenc, menc) = set.get_encoding_tuple();
+                Self::set_model_code(kenc, menc)
+            }
+            DataModel::SortedSet(ref zset) => {
+                let (kenc, menc) = zset.get_encoding_tuple();
+                Self::sorted_set_model_code(kenc, menc)
+            }
+            DataModel::Queue(ref queue) => {
+                let (kenc, ienc) = queue.get_encoding_tuple();
+                Self::queue_model_code(kenc, ienc)
+            }
+        }
+    }
+    /// Returns the schema if this table has named fields
+    pub fn get_schema(&self) -> Option<&Schema> {
+        match self.model_store {
+            DataModel::Rowmap(ref rm) => Some(rm.schema()),
+            _ => None,
         }
     }
     /// Returns the inner data model
@@ -27,8 +27,10 @@
 use {
     super::{BufferedSocketStream, QueryResult},
     crate::{
+        actions::txn::Transaction,
         corestore::buffers::Integer64,
         protocol::{interface::ProtocolSpec, ParseError},
+        pubsub::{Push, Subscriptions},
         IoResult,
     },
     bytes::BytesMut,
@@ -36,7 +38,10 @@ use {
         io::{Error as IoError, ErrorKind},
         marker::PhantomData,
     },
-    tokio::io::{AsyncReadExt, AsyncWriteExt, BufWriter},
+    tokio::{
+        io::{AsyncReadExt, AsyncWriteExt, BufWriter},
+        sync::broadcast,
+    },
 };
 
 const BUF_WRITE_CAP: usize = 8192;
@@ -50,14 +55,30 @@ const BUF_READ_CAP: usize = 8192;
 pub struct Connection<T, P> {
     pub(super) stream: BufWriter<T>,
     pub(super) buffer: BytesMut,
+    /// the channels this connection is subscribed to
+    pub(super) subscriptions: Subscriptions,
+    /// the transaction that this connection started (if any)
+    transaction: Option<Transaction>,
+    /// check for termination signals
+    termination_signal: broadcast::Receiver<()>,
+    /// whatever the client sent while a blocking action was waiting (it can't go into the
+    /// buffer because the running query points into it)
+    backlog: BytesMut,
+    /// set once a blocking action sees the client leave or the server shut down
+    pub(super) closed: bool,
     _marker: PhantomData<P>,
 }
 
 impl<T: BufferedSocketStream, P: ProtocolSpec> Connection<T, P> {
-    pub fn new(stream: T) -> Self {
+    pub fn new(stream: T, termination_signal: broadcast::Receiver<()>) -> Self {
         Connection {
             stream: BufWriter::with_capacity(BUF_WRITE_CAP, stream),
             buffer: BytesMut::with_capacity(BUF_READ_CAP),
+            subscriptions: Subscriptions::new(),
+            transaction: None,
+            termination_signal,
+            backlog: BytesMut::new(),
+            closed: false,
             _marker: PhantomData,
         }
     }
@@ -65,10 +86,36 @@ impl<T: BufferedSocketStream, P: ProtocolSpec> Connection<T, P> {
 
 // protocol read
 impl<T: BufferedSocketStream, P: ProtocolSpec> Connection<T, P> {
-    /// Attempt to read a query
+    /// Attempt to read a query. In push mode, this also returns messages published to the
+    /// channels and keys that this connection is subscribed to
     pub(super) async fn read_query(&mut self) -> IoResult<QueryResult> {
+        if self.closed {
+            return Ok(QueryResult::Disconnected);
+        }
+        if !self.backlog.is_empty() {
+            let backlog = self.backlog.split();
+            self.buffer.extend_from_slice(&backlog);
+        }
+        // the client may have sent more than one query at a time, so look at whatever is left
+        // over before waiting for more
+        let mut leftover = !self.buffer.is_empty();
         loop {
-            match self.stream.read_buf(&mut self.buffer).await {
+            let read = if leftover {
+                leftover = false;
+                Ok(self.buffer.len())
+            } else if self.subscriptions.is_empty() {
+                tokio::select! {
+                    read = self.stream.read_buf(&mut self.buffer) => read,
+                    _ = self.termination_signal.recv() => return Ok(QueryResult::Disconnected),
+                }
+            } else {
+                tokio::select! {
+                    read = self.stream.read_buf(&mut self.buffer) => read,
+                    push = self.subscriptions.recv() => return Ok(QueryResult::Push(push)),
+                    _ = self.termination_signal.recv() => return Ok(QueryResult::Disconnected),
+                }
+            };
+            match read {
                 Ok(0) => {
                     if self.buffer.is_empty() {
                         // buffer is empty, and the remote pulled off (simple disconnection)
@@ -86,6 +133,9 @@ impl<T: BufferedSocketStream, P: ProtocolSpec> Connection<T, P> {
                 Ok(query_with_advance) => return Ok(QueryResult::Q(query_with_advance)),
                 Err(ParseError::NotEnough) => {}
                 Err(e) => {
+                    // there's no telling where the next query starts, so drop what we have
+                    // (else we'd just parse it again)
+                    self.buffer.clear();
                     self.write_error(P::SKYHASH_PARSE_ERROR_LUT[e as usize - 1])
                         .await?;
                     return Ok(QueryResult::NextLoop);
@@ -95,6 +145,26 @@ impl<T: BufferedSocketStream, P: ProtocolSpec> Connection<T, P> {
     }
 }
 
+// blocking actions
+impl<T: BufferedSocketStream, P: ProtocolSpec> Connection<T, P> {
+    /// Wait until the client disconnects or the server starts shutting down, after which the
+    /// connection is closed once the running query is done. Anything the client sends in the
+    /// meantime is kept for the next query. This is cancel safe, so a blocking action can race
+    /// it against whatever it is waiting for
+    pub async fn interrupted(&mut self) {
+        loop {
+            tokio::select! {
+                read = self.stream.read_buf(&mut self.backlog) => match read {
+                    Ok(0) | Err(_) => break,
+                    Ok(_) => {}
+                },
+                _ = self.termination_signal.recv() => break,
+            }
+        }
+        self.closed = true;
+    }
+}
+
 // protocol write (metaframe)
 impl<T: BufferedSocketStream, P: ProtocolSpec> Connection<T, P> {
     /// Write a simple query header to the stream
@@ -124,6 +194,37 @@ impl<T: BufferedSocketStream, P: ProtocolSpec> Connection<T, P> {
     pub async fn _write_raw(&mut self, raw: &[u8]) -> IoResult<()> {
         self.stream.write_all(raw).await
     }
+    /// Flush whatever has been written so far (for responses that are streamed)
+    pub async fn flush_stream(&mut self) -> IoResult<()> {
+        self.stream.flush().await
+    }
+    /// Push a message for one of the channels or keys that this connection is subscribed to
+    pub(super) async fn write_push(&mut self, (kind, channel, message): Push) -> IoResult<()> {
+        self.write_simple_query_header().await?;
+        self.write_typed_non_null_array([kind.name(), &channel, &message], P::TSYMBOL_BINARY)
+            .await?;
+        self.stream.flush().await
+    }
+}
+
+// pub/sub
+impl<T, P> Connection<T, P> {
+    pub fn subscriptions(&self) -> &Subscriptions {
+        &self.subscriptions
+    }
+    pub fn subscriptions_mut(&mut self) -> &mut Subscriptions {
+        &mut self.subscriptions
+    }
+}
+
+// transactions
+impl<T, P> Connection<T, P> {
+    pub fn transaction(&self) -> Option<&Transaction> {
+        self.transaction.as_ref()
+    }
+    pub fn transaction_mut(&mut self) -> &mut Option<Transaction> {
+        &mut self.transaction
+    }
 }
 
 // protocol write (dataframe)
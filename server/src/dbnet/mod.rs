@@ -31,6 +31,7 @@ use {
         auth::AuthProvider,
         corestore::Corestore,
         protocol::{interface::ProtocolSpec, Query},
+        pubsub::Push,
         util::compiler,
         IoResult,
     },
@@ -39,7 +40,6 @@ use {
     tokio::{
         io::{AsyncReadExt, AsyncWriteExt},
         sync::{
-            broadcast::{self},
             mpsc::{self},
             Semaphore,
         },
@@ -71,8 +71,10 @@ enum QueryResult {
     Q(QueryWithAdvance),
     /// Simply proceed to the next run loop iter
     NextLoop,
-    /// The client disconnected
+    /// The client disconnected (or the server is shutting down)
     Disconnected,
+    /// A message for a subscribed channel or watched key (only in push mode)
+    Push(Push),
 }
 
 /// A backoff implementation that is meant to be used in connection loops
@@ -145,8 +147,6 @@ pub struct ConnectionHandler<C, P> {
     climit: Arc<Semaphore>,
     /// the authentication handle
     auth: AuthProviderHandle,
-    /// check for termination signals
-    termination_signal: broadcast::Receiver<()>,
     /// the sender that we drop when we're done with handling a connection (used for gracefule exit)
     _term_sig_tx: mpsc::Sender<()>,
 }
@@ -162,7 +162,6 @@ where
         con: Connection<C, P>,
         auth_data: AuthProvider,
         climit: Arc<Semaphore>,
-        termination_signal: broadcast::Receiver<()>,
         _term_sig_tx: mpsc::Sender<()>,
     ) -> Self {
         Self {
@@ -170,19 +169,12 @@ where
             con,
             climit,
             auth: AuthProviderHandle::new(auth_data),
-            termination_signal,
             _term_sig_tx,
         }
     }
     pub async fn run(&mut self) -> IoResult<()> {
         loop {
-            let packet = tokio::select! {
-                pkt = self.con.read_query() => pkt,
-                _ = self.termination_signal.recv() => {
-                    return Ok(());
-                }
-            };
-            match packet {
+            match self.con.read_query().await {
                 Ok(QueryResult::Q((query, advance))) => {
                     // the mutable reference to self ensures that the buffer is not modified
                     // hence ensuring that the pointers will remain valid
@@ -223,6 +215,7 @@ where
                         self.con.buffer.advance(advance);
                     }
                 }
+                Ok(QueryResult::Push(push)) => self.con.write_push(push).await?,
                 Ok(QueryResult::Disconnected) => return Ok(()),
                 Ok(QueryResult::NextLoop) => {}
                 Err(e) => return Err(e),
@@ -231,6 +224,13 @@ where
     }
     async fn execute_query(&mut self, query: Query) -> ActionResult<()> {
         let Self { db, con, auth, .. } = self;
+        // pick up a store that was restored since the last query
+        db.refresh();
+        if !con.subscriptions().is_empty() && !allowed_in_push_mode(&query) {
+            con.write_simple_query_header().await?;
+            con.write_error(P::RSTRING_SUBSCRIBED).await?;
+            return Ok(());
+        }
         match query {
             Query::Simple(q) => {
                 con.write_simple_query_header().await?;
@@ -250,11 +250,32 @@ where
                 }
             }
         }
-        con.stream.flush().await?;
+        if con.closed {
+            // a blocking action gave up, and the client may not be around to read its response
+            let _ = con.stream.flush().await;
+        } else {
+            con.stream.flush().await?;
+        }
         Ok(())
     }
 }
 
+/// A connection in push mode can only change its subscriptions and watched keys
+fn allowed_in_push_mode(query: &Query) -> bool {
+    match query {
+        Query::Simple(q) => q.as_slice().first().is_some_and(|action| {
+            let action = unsafe {
+                // UNSAFE(@ohsayan): The connection's buffer is still around
+                action.as_slice()
+            };
+            [&b"subscribe"[..], b"unsubscribe", b"watch", b"unwatch"]
+                .iter()
+                .any(|allowed| action.eq_ignore_ascii_case(allowed))
+        }),
+        Query::Pipelined(_) => false,
+    }
+}
+
 impl<C, T> Drop for ConnectionHandler<C, T> {
     fn drop(&mut self) {
         // Make sure that the permit is returned to the semaphore
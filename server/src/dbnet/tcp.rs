@@ -89,10 +89,9 @@ impl<P: ProtocolSpec + 'static> RawListener<P> {
             let stream = skip_loop_err!(self.accept().await);
             let mut chandle = ConnectionHandler::<TcpStream, P>::new(
                 self.base.db.clone(),
-                Connection::new(stream),
+                Connection::new(stream, self.base.signal.subscribe()),
                 self.base.auth.clone(),
                 self.base.climit.clone(),
-                self.base.signal.subscribe(),
                 self.base.terminate_tx.clone(),
             );
             tokio::spawn(async move {
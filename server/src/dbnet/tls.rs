@@ -126,10 +126,9 @@ impl<P: ProtocolSpec + 'static> SslListenerRaw<P> {
             let stream = skip_loop_err!(self.accept().await);
             let mut sslhandle = ConnectionHandler::<SslStream<TcpStream>, P>::new(
                 self.base.db.clone(),
-                Connection::new(stream),
+                Connection::new(stream, self.base.signal.subscribe()),
                 self.base.auth.clone(),
                 self.base.climit.clone(),
-                self.base.signal.subscribe(),
                 self.base.terminate_tx.clone(),
             );
             tokio::spawn(async move {
@@ -0,0 +1,263 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Eviction
+//!
+//! Volatile models are caches: their data never makes it to disk. So that they can't grow
+//! without bound, the server can be given a memory limit (`maxmemory`) that all volatile
+//! models share. Each volatile model keeps track of the bytes held by its keys and values
+//! and before a write to a model, keys are evicted from it (as picked by its
+//! [`EvictionPolicy`]) until volatile models are back within the limit.
+//!
+//! Changes to a key are usually made while its entry (or its list) is locked, so they only
+//! mark the key as dirty. Dirty keys are measured again once the write is done (see
+//! [`KVEngine::settle_memory`](super::KVEngine::settle_memory)).
+
+use {
+    crate::{
+        corestore::{htable::Coremap, map::bref::Entry, SharedSlice},
+        registry,
+    },
+    core::{
+        hash::{BuildHasher, Hasher},
+        sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+    parking_lot::Mutex,
+    std::collections::hash_map::RandomState,
+};
+
+/// The number of keys looked at to pick a victim
+const SAMPLE_SIZE: usize = 5;
+/// The access count of a key is halved every time it goes unused for this many milliseconds,
+/// so that keys that were popular a long time ago can be evicted too
+const LFU_DECAY_MS: u64 = 60_000;
+
+/// How keys are picked for eviction when volatile models go over the memory limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum EvictionPolicy {
+    /// Never evict keys; reject writes instead
+    NoEviction = 0,
+    /// Evict the least recently used key
+    Lru = 1,
+    /// Evict the least frequently used key
+    Lfu = 2,
+    /// Evict any key
+    Random = 3,
+}
+
+impl EvictionPolicy {
+    pub const fn code(&self) -> u8 {
+        *self as u8
+    }
+    pub const fn from_code(code: u8) -> Option<Self> {
+        let ret = match code {
+            0 => Self::NoEviction,
+            1 => Self::Lru,
+            2 => Self::Lfu,
+            3 => Self::Random,
+            _ => return None,
+        };
+        Some(ret)
+    }
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::NoEviction => "noeviction",
+            Self::Lru => "lru",
+            Self::Lfu => "lfu",
+            Self::Random => "random",
+        }
+    }
+    pub fn from_name(name: &[u8]) -> Option<Self> {
+        let ret = match name {
+            b"noeviction" => Self::NoEviction,
+            b"lru" => Self::Lru,
+            b"lfu" => Self::Lfu,
+            b"random" => Self::Random,
+            _ => return None,
+        };
+        Some(ret)
+    }
+}
+
+/// What we know about a key
+#[derive(Debug)]
+struct KeyStat {
+    /// bytes held by the key and its value
+    size: AtomicUsize,
+    /// UNIX time (in ms) when the key was last used
+    last_access: AtomicU64,
+    /// the number of times the key was used
+    hits: AtomicU64,
+}
+
+impl KeyStat {
+    fn new(size: usize) -> Self {
+        Self {
+            size: AtomicUsize::new(size),
+            last_access: AtomicU64::new(super::unix_millis()),
+            hits: AtomicU64::new(1),
+        }
+    }
+    /// The access count after the decay for the time the key went unused
+    fn decayed_hits(&self, now: u64) -> u64 {
+        let idle = now.saturating_sub(self.last_access.load(Ordering::Relaxed));
+        let halvings = (idle / LFU_DECAY_MS).min(63);
+        self.hits.load(Ordering::Relaxed) >> halvings
+    }
+}
+
+/// The memory used by a volatile table and how its keys were used
+#[derive(Debug)]
+pub struct Tracker {
+    policy: EvictionPolicy,
+    stats: Coremap<SharedSlice, KeyStat>,
+    /// keys that changed since they were last measured
+    dirty: Mutex<Vec<SharedSlice>>,
+    /// bytes held by the keys and values in the table
+    memory: AtomicUsize,
+    /// the state of the generator used to sample keys
+    seed: AtomicU64,
+}
+
+impl Tracker {
+    pub fn new(policy: EvictionPolicy) -> Self {
+        Self {
+            policy,
+            stats: Coremap::new(),
+            dirty: Mutex::new(Vec::new()),
+            memory: AtomicUsize::new(0),
+            seed: AtomicU64::new(RandomState::new().build_hasher().finish() | 1),
+        }
+    }
+    pub const fn policy(&self) -> EvictionPolicy {
+        self.policy
+    }
+    pub fn set_policy(&mut self, policy: EvictionPolicy) {
+        self.policy = policy
+    }
+    /// Returns the number of bytes held by the table's keys and values
+    pub fn memory(&self) -> usize {
+        self.memory.load(Ordering::Acquire)
+    }
+    /// Record a read of the key
+    pub fn touch(&self, key: &[u8]) {
+        if let Some(stat) = self.stats.get(key) {
+            stat.last_access
+                .store(super::unix_millis(), Ordering::Relaxed);
+            stat.hits.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    /// Record that the key changed and has to be measured again
+    pub fn mark_dirty(&self, key: &[u8]) {
+        self.dirty.lock().push(SharedSlice::new(key));
+    }
+    /// Returns the keys that changed since the last call
+    pub fn take_dirty(&self) -> Vec<SharedSlice> {
+        std::mem::take(&mut *self.dirty.lock())
+    }
+    /// Set the number of bytes held by the key and its value, or forget the key if `size`
+    /// is `None`
+    pub fn resize(&self, key: SharedSlice, size: Option<usize>) {
+        let old = match size {
+            Some(size) => match self.stats.entry(key) {
+                Entry::Occupied(entry) => entry.value().size.swap(size, Ordering::Relaxed),
+                Entry::Vacant(entry) => {
+                    entry.insert(KeyStat::new(size));
+                    0
+                }
+            },
+            None => match self.stats.remove(&key) {
+                Some((_, stat)) => stat.size.into_inner(),
+                None => return,
+            },
+        };
+        let new = size.unwrap_or(0);
+        if new > old {
+            self.grow(new - old);
+        } else {
+            self.shrink(old - new);
+        }
+    }
+    /// Forget all the keys
+    pub fn clear(&self) {
+        self.dirty.lock().clear();
+        self.stats.clear();
+        self.shrink(self.memory());
+    }
+    fn grow(&self, by: usize) {
+        self.memory.fetch_add(by, Ordering::AcqRel);
+        registry::used_memory().fetch_add(by, Ordering::AcqRel);
+    }
+    fn shrink(&self, by: usize) {
+        self.memory.fetch_sub(by, Ordering::AcqRel);
+        registry::used_memory().fetch_sub(by, Ordering::AcqRel);
+    }
+    /// Returns a new seed for sampling keys (xorshift)
+    fn next_seed(&self) -> u64 {
+        let mut x = self.seed.load(Ordering::Relaxed);
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.seed.store(x, Ordering::Relaxed);
+        x
+    }
+    /// Pick a key to evict from a sample of the keys. Returns `None` if the policy doesn't
+    /// allow evicting keys or if there are no keys
+    pub fn pick_victim(&self) -> Option<SharedSlice> {
+        if self.policy == EvictionPolicy::NoEviction {
+            return None;
+        }
+        let sample = self
+            .stats
+            .sample_keys(self.next_seed() as usize, SAMPLE_SIZE);
+        let now = super::unix_millis();
+        let score = |key: &SharedSlice| {
+            self.stats.get(key).map(|stat| {
+                let last_access = stat.last_access.load(Ordering::Relaxed);
+                match self.policy {
+                    EvictionPolicy::Lfu => (stat.decayed_hits(now), last_access),
+                    _ => (last_access, 0),
+                }
+            })
+        };
+        match self.policy {
+            EvictionPolicy::Random => sample.into_iter().next(),
+            _ => sample
+                .into_iter()
+                .filter_map(|key| score(&key).map(|score| (score, key)))
+                .min_by_key(|(score, _)| *score)
+                .map(|(_, key)| key),
+        }
+    }
+}
+
+impl Drop for Tracker {
+    fn drop(&mut self) {
+        // the table is gone, and so is the memory it held
+        registry::used_memory().fetch_sub(self.memory(), Ordering::AcqRel);
+    }
+}
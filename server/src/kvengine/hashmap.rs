@@ -0,0 +1,220 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Hashmaps
+//!
+//! A hashmap is the engine behind models whose values are maps, like:
+//! ```text
+//! create model users(string, map<string, binary>)
+//! ```
+//! Every key holds a map of fields to values, so that a single field can be read or changed
+//! without touching the rest of the map. The fields of a map are kept sorted, and a map is
+//! removed along with its last field
+
+use {
+    super::{types::FieldType, EncodingResult, KVEValue, KVEngine},
+    crate::{
+        corestore::{htable::Coremap, map::bref::Entry, SharedSlice},
+        pubsub::KeyEvent,
+    },
+    parking_lot::RwLock,
+    std::collections::BTreeMap,
+};
+
+/// The fields and values of a single map
+pub type FieldMap = BTreeMap<SharedSlice, SharedSlice>;
+pub type LockedMap = RwLock<FieldMap>;
+pub type KVEMapmap = KVEngine<LockedMap>;
+
+impl KVEValue for LockedMap {
+    fn verify_encoding(&self, t_v: FieldType) -> EncodingResult<()> {
+        if self.read().values().all(|value| t_v.is_ok(value)) {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+    fn footprint(&self) -> usize {
+        self.read()
+            .iter()
+            .map(|(field, value)| field.len() + value.len())
+            .sum()
+    }
+}
+
+#[derive(Debug)]
+/// A map of keys to maps of fields and values
+pub struct Hashmap {
+    /// the type of the fields in every map
+    t_f: FieldType,
+    /// the maps, keyed by their key. The engine checks the values
+    maps: KVEMapmap,
+}
+
+impl Hashmap {
+    /// Create a new hashmap with the provided maps
+    pub fn new(
+        k_enc: bool,
+        f_enc: bool,
+        v_enc: bool,
+        data: Coremap<SharedSlice, LockedMap>,
+    ) -> Self {
+        Self {
+            t_f: FieldType::from_encoding(f_enc),
+            maps: KVEMapmap::new(k_enc, v_enc, data),
+        }
+    }
+    /// Create a new empty hashmap
+    pub fn init(k_enc: bool, f_enc: bool, v_enc: bool) -> Self {
+        Self::new(k_enc, f_enc, v_enc, Coremap::new())
+    }
+    /// Returns the maps, keyed by their key
+    pub fn maps(&self) -> &KVEMapmap {
+        &self.maps
+    }
+    pub fn maps_mut(&mut self) -> &mut KVEMapmap {
+        &mut self.maps
+    }
+    /// Number of maps
+    pub fn len(&self) -> usize {
+        self.maps.len()
+    }
+    /// Delete all the maps
+    pub fn truncate_table(&self) {
+        self.maps.truncate_table()
+    }
+    /// Check the encoding of a field
+    pub fn is_field_ok(&self, field: &[u8]) -> bool {
+        self.t_f.is_ok(field)
+    }
+    /// Returns true if every field and every value in `map` has the right encoding
+    pub fn is_map_ok(&self, map: &FieldMap) -> bool {
+        map.iter()
+            .all(|(field, value)| self.is_field_ok(field) && self.maps.is_val_ok(value))
+    }
+    /// Get the field tsymbol
+    pub fn get_field_tsymbol(&self) -> u8 {
+        self.t_f.tsymbol()
+    }
+    /// Returns (k_enc, f_enc, v_enc)
+    pub fn get_encoding_triple(&self) -> (bool, bool, bool) {
+        let (k_enc, v_enc) = self.maps.get_encoding_tuple();
+        (k_enc, self.t_f.is_encoded(), v_enc)
+    }
+    /// Returns the type of the fields
+    pub fn get_field_type(&self) -> FieldType {
+        self.t_f
+    }
+    /// Set the given fields in the map at `key`, creating the map if it doesn't exist.
+    /// `log` is called with the fields before the map is unlocked. Returns the number of
+    /// fields that didn't exist before. The caller must check the encoding
+    pub fn set_fields(
+        &self,
+        key: &[u8],
+        pairs: &[(SharedSlice, SharedSlice)],
+        log: impl FnOnce(&[(SharedSlice, SharedSlice)]),
+    ) -> usize {
+        let (added, event) = match self.maps.get_inner_ref().entry(SharedSlice::new(key)) {
+            Entry::Occupied(oe) => {
+                let mut map = oe.value().write();
+                let added = pairs
+                    .iter()
+                    .filter(|(field, value)| map.insert(field.clone(), value.clone()).is_none())
+                    .count();
+                log(pairs);
+                (added, KeyEvent::Update)
+            }
+            Entry::Vacant(ve) => {
+                let map: FieldMap = pairs.iter().cloned().collect();
+                let added = map.len();
+                log(pairs);
+                ve.insert(RwLock::new(map));
+                (added, KeyEvent::Set)
+            }
+        };
+        self.maps.notify(key, event);
+        added
+    }
+    /// Remove the given fields from the map at `key`, removing the map if it has no fields
+    /// left. `log` is called with the removed fields before the map is unlocked. Returns the
+    /// number of fields that were removed
+    pub fn remove_fields(
+        &self,
+        key: &[u8],
+        fields: &[&[u8]],
+        log: impl FnOnce(&[SharedSlice]),
+    ) -> usize {
+        let oe = match self.maps.get_inner_ref().entry(SharedSlice::new(key)) {
+            Entry::Occupied(oe) => oe,
+            Entry::Vacant(_) => return 0,
+        };
+        let mut map = oe.value().write();
+        let removed: Vec<SharedSlice> = fields
+            .iter()
+            .filter_map(|field| map.remove_entry(*field).map(|(field, _)| field))
+            .collect();
+        if removed.is_empty() {
+            return 0;
+        }
+        log(&removed);
+        let event = if map.is_empty() {
+            drop(map);
+            oe.remove();
+            KeyEvent::Del
+        } else {
+            drop(map);
+            drop(oe);
+            KeyEvent::Update
+        };
+        self.maps.notify(key, event);
+        removed.len()
+    }
+    /// Returns the value of `field` in the map at `key`
+    pub fn get_field(&self, key: &[u8], field: &[u8]) -> Option<SharedSlice> {
+        self.maps.touch(key);
+        self.maps
+            .get_inner_ref()
+            .get(key)
+            .and_then(|map| map.read().get(field).cloned())
+    }
+    /// Returns the fields and values of the map at `key`, sorted by field
+    pub fn get_all(&self, key: &[u8]) -> Option<Vec<(SharedSlice, SharedSlice)>> {
+        self.maps.touch(key);
+        self.maps.get_inner_ref().get(key).map(|map| {
+            map.read()
+                .iter()
+                .map(|(field, value)| (field.clone(), value.clone()))
+                .collect()
+        })
+    }
+    /// Returns the number of fields in the map at `key`
+    pub fn map_len(&self, key: &[u8]) -> Option<usize> {
+        self.maps
+            .get_inner_ref()
+            .get(key)
+            .map(|map| map.read().len())
+    }
+}
@@ -27,23 +27,50 @@
 #![allow(dead_code)] // TODO(@ohsayan): Clean this up later
 
 pub mod encoding;
+pub mod eviction;
+pub mod hashmap;
+pub mod ordered;
+pub mod queue;
+pub mod rowmap;
+pub mod sets;
 #[cfg(test)]
 mod tests;
+pub mod types;
 
 use {
-    self::encoding::{ENCODING_LUT, ENCODING_LUT_PAIR},
+    self::{
+        encoding::{ENCODING_LUT_ITER, ENCODING_LUT_ITER_PAIR},
+        eviction::{EvictionPolicy, Tracker},
+        ordered::{KeyRange, OrderedKeys},
+        types::{FieldType, NumError},
+    },
     crate::{
-        corestore::{booltable::BoolTable, htable::Coremap, map::bref::Ref, SharedSlice},
+        corestore::{
+            booltable::BoolTable,
+            htable::Coremap,
+            map::bref::{Entry, Ref},
+            SharedSlice,
+        },
+        protocol::iter::{AnyArrayIter, BorrowedAnyArrayIter},
+        pubsub::{KeyEvent, PubSub},
+        registry,
         util::compiler,
     },
+    core::ops::Bound,
     parking_lot::RwLock,
+    std::{
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc,
+        },
+        time::{SystemTime, UNIX_EPOCH},
+    },
 };
 
 pub type KVEStandard = KVEngine<SharedSlice>;
 pub type KVEListmap = KVEngine<LockedVec>;
 pub type LockedVec = RwLock<Vec<SharedSlice>>;
 pub type SingleEncoder = fn(&[u8]) -> bool;
-pub type DoubleEncoder = fn(&[u8], &[u8]) -> bool;
 type EntryRef<'a, T> = Ref<'a, SharedSlice, T>;
 type EncodingResult<T> = Result<T, ()>;
 type OptionRef<'a, T> = Option<Ref<'a, SharedSlice, T>>;
@@ -52,112 +79,352 @@ type EncodingResultRef<'a, T> = EncodingResult<OptionRef<'a, T>>;
 const TSYMBOL_LUT: BoolTable<u8> = BoolTable::new(b'+', b'?');
 
 pub trait KVEValue {
-    fn verify_encoding(&self, e_v: bool) -> EncodingResult<()>;
+    fn verify_encoding(&self, t_v: FieldType) -> EncodingResult<()>;
+    /// Returns the number of bytes held by the value
+    fn footprint(&self) -> usize;
 }
 
 impl KVEValue for SharedSlice {
-    fn verify_encoding(&self, e_v: bool) -> EncodingResult<()> {
-        if ENCODING_LUT[e_v](self) {
+    fn verify_encoding(&self, t_v: FieldType) -> EncodingResult<()> {
+        if t_v.is_ok(self) {
             Ok(())
         } else {
             Err(())
         }
     }
+    fn footprint(&self) -> usize {
+        self.len()
+    }
 }
 
 impl KVEValue for LockedVec {
-    fn verify_encoding(&self, e_v: bool) -> EncodingResult<()> {
-        let func = ENCODING_LUT[e_v];
+    fn verify_encoding(&self, t_v: FieldType) -> EncodingResult<()> {
+        let func = t_v.checker();
         if self.read().iter().all(|v| func(v)) {
             Ok(())
         } else {
             Err(())
         }
     }
+    fn footprint(&self) -> usize {
+        self.read().iter().map(|v| v.len()).sum()
+    }
+}
+
+/// Returns the current UNIX time in milliseconds. Expiry deadlines are stored as absolute
+/// wall-clock times so that they remain meaningful across restarts
+pub fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
 }
 
 #[derive(Debug)]
 pub struct KVEngine<T> {
     data: Coremap<SharedSlice, T>,
-    e_k: bool,
-    e_v: bool,
+    /// absolute expiry deadlines (UNIX ms) for keys that have a TTL set
+    expiry: Coremap<SharedSlice, u64>,
+    /// connections watching keys in this table
+    watchers: Arc<PubSub>,
+    /// number of changes made to the data and the expiry deadlines
+    mutations: AtomicU64,
+    /// memory accounting for volatile tables
+    tracker: Option<Box<Tracker>>,
+    /// the sorted keys of ordered tables
+    ordered: Option<Box<OrderedKeys>>,
+    /// the maximum length of the lists in a capped list table
+    max_len: Option<usize>,
+    t_k: FieldType,
+    t_v: FieldType,
 }
 
 // basic method impls
 impl<T> KVEngine<T> {
     /// Create a new KVEBlob
     pub fn new(e_k: bool, e_v: bool, data: Coremap<SharedSlice, T>) -> Self {
-        Self { data, e_k, e_v }
+        Self::new_typed(
+            FieldType::from_encoding(e_k),
+            FieldType::from_encoding(e_v),
+            data,
+        )
+    }
+    /// Create a new KVEBlob with the given key and value types
+    pub fn new_typed(t_k: FieldType, t_v: FieldType, data: Coremap<SharedSlice, T>) -> Self {
+        Self {
+            data,
+            expiry: Coremap::new(),
+            watchers: Arc::new(PubSub::new()),
+            mutations: AtomicU64::new(0),
+            tracker: None,
+            ordered: None,
+            max_len: None,
+            t_k,
+            t_v,
+        }
     }
     /// Create a new empty KVEBlob
     pub fn init(e_k: bool, e_v: bool) -> Self {
         Self::new(e_k, e_v, Default::default())
     }
+    /// Create a new empty KVEBlob with the given key and value types
+    pub fn init_typed(t_k: FieldType, t_v: FieldType) -> Self {
+        Self::new_typed(t_k, t_v, Default::default())
+    }
     /// Number of KV pairs
     pub fn len(&self) -> usize {
         self.data.len()
     }
     /// Delete all the key/value pairs
     pub fn truncate_table(&self) {
-        self.data.clear()
+        match self.ordered {
+            Some(ref ordered) => ordered.clear_with(|| self.data.clear()),
+            None => self.data.clear(),
+        }
+        self.expiry.clear();
+        if let Some(ref tracker) = self.tracker {
+            tracker.clear();
+        }
+        self.mutated();
+        self.watchers.notify_all(KeyEvent::Del);
     }
     /// Returns a reference to the inner structure
     pub fn get_inner_ref(&self) -> &Coremap<SharedSlice, T> {
         &self.data
     }
+    /// Returns a reference to the expiry deadlines
+    pub fn get_expiry_ref(&self) -> &Coremap<SharedSlice, u64> {
+        &self.expiry
+    }
+    /// Returns the registry of watched keys
+    pub fn get_watchers(&self) -> &Arc<PubSub> {
+        &self.watchers
+    }
+    /// Record a change to the key and notify the connections watching it. Anything that
+    /// changes the data without going through the engine's own methods must call this
+    pub fn notify(&self, key: &[u8], event: KeyEvent) {
+        self.mutated();
+        if let Some(ref tracker) = self.tracker {
+            tracker.mark_dirty(key);
+        }
+        if let Some(ref ordered) = self.ordered {
+            ordered.mark_dirty(key);
+        }
+        self.watchers.notify(key, event)
+    }
+    /// Record a read of the key (for the eviction policy)
+    pub fn touch(&self, key: &[u8]) {
+        if let Some(ref tracker) = self.tracker {
+            tracker.touch(key);
+        }
+    }
+    /// Returns the number of changes made to this table so far. The storage engine compares
+    /// this against the count it last wrote to skip rewriting tables that haven't changed
+    pub fn mutation_count(&self) -> u64 {
+        self.mutations.load(Ordering::Acquire)
+    }
+    fn mutated(&self) {
+        self.mutations.fetch_add(1, Ordering::AcqRel);
+    }
     /// Check the encoding of the key
     pub fn is_key_ok(&self, key: &[u8]) -> bool {
-        self._check_encoding(key, self.e_k)
+        self.t_k.is_ok(key)
     }
     /// Check the encoding of the value
     pub fn is_val_ok(&self, val: &[u8]) -> bool {
-        self._check_encoding(val, self.e_v)
+        self.t_v.is_ok(val)
     }
     #[inline(always)]
     fn check_key_encoding(&self, item: &[u8]) -> Result<(), ()> {
-        self.check_encoding(item, self.e_k)
+        self.check_encoding(item, self.t_k)
     }
     #[inline(always)]
     fn check_value_encoding(&self, item: &[u8]) -> Result<(), ()> {
-        self.check_encoding(item, self.e_v)
-    }
-    #[inline(always)]
-    fn _check_encoding(&self, item: &[u8], encoded: bool) -> bool {
-        ENCODING_LUT[encoded](item)
+        self.check_encoding(item, self.t_v)
     }
     #[inline(always)]
-    fn check_encoding(&self, item: &[u8], encoded: bool) -> Result<(), ()> {
-        if compiler::likely(self._check_encoding(item, encoded)) {
+    fn check_encoding(&self, item: &[u8], ty: FieldType) -> Result<(), ()> {
+        if compiler::likely(ty.is_ok(item)) {
             Ok(())
         } else {
             Err(())
         }
     }
+    /// Check the encoding of every key in the iterator
+    pub fn is_key_iter_ok(&self, mut iter: BorrowedAnyArrayIter) -> bool {
+        if compiler::likely(!self.t_k.is_numeric()) {
+            ENCODING_LUT_ITER[self.t_k.is_encoded()](iter)
+        } else {
+            iter.all(|key| self.t_k.is_ok(key))
+        }
+    }
+    /// Check the encoding of every key and value in an iterator that yields keys and
+    /// values alternately
+    pub fn is_pair_iter_ok(&self, iter: &AnyArrayIter) -> bool {
+        if compiler::likely(!self.has_numeric_type()) {
+            ENCODING_LUT_ITER_PAIR[self.get_encoding_tuple()](iter)
+        } else {
+            let (mut keys, mut values) =
+                (iter.as_ref().step_by(2), iter.as_ref().skip(1).step_by(2));
+            keys.all(|key| self.t_k.is_ok(key)) && values.all(|value| self.t_v.is_ok(value))
+        }
+    }
     pub fn is_key_encoded(&self) -> bool {
-        self.e_k
+        self.t_k.is_encoded()
     }
     pub fn is_val_encoded(&self) -> bool {
-        self.e_v
+        self.t_v.is_encoded()
+    }
+    /// Returns true if either the key or the value is a number
+    pub fn has_numeric_type(&self) -> bool {
+        self.t_k.is_numeric() || self.t_v.is_numeric()
     }
     /// Get the key tsymbol
     pub fn get_key_tsymbol(&self) -> u8 {
-        TSYMBOL_LUT[self.e_k]
+        self.t_k.tsymbol()
     }
     /// Get the value tsymbol
     pub fn get_value_tsymbol(&self) -> u8 {
-        TSYMBOL_LUT[self.e_v]
+        self.t_v.tsymbol()
     }
     /// Returns (k_enc, v_enc)
     pub fn get_encoding_tuple(&self) -> (bool, bool) {
-        (self.e_k, self.e_v)
+        (self.is_key_encoded(), self.is_val_encoded())
+    }
+    /// Returns (k_type, v_type)
+    pub fn get_type_tuple(&self) -> (FieldType, FieldType) {
+        (self.t_k, self.t_v)
     }
     /// Returns an encoder fnptr for the key
     pub fn get_key_encoder(&self) -> SingleEncoder {
-        ENCODING_LUT[self.e_k]
+        self.t_k.checker()
     }
     /// Returns an encoder fnptr for the value
     pub fn get_val_encoder(&self) -> SingleEncoder {
-        ENCODING_LUT[self.e_v]
+        self.t_v.checker()
+    }
+}
+
+// expiry impls
+impl<T> KVEngine<T> {
+    /// Returns true if the key has a deadline that has already passed
+    fn is_expired_unchecked(&self, key: &[u8]) -> bool {
+        match self.expiry.get(key) {
+            Some(deadline) => *deadline <= unix_millis(),
+            None => false,
+        }
+    }
+    /// Remove the key if its deadline has passed. Returns true if the key was expired and
+    /// this call removed it; a key that was given a new deadline (or set again) in the
+    /// meantime is left alone
+    fn purge_if_expired(&self, key: &[u8]) -> bool {
+        if compiler::unlikely(self.is_expired_unchecked(key)) {
+            let now = unix_millis();
+            let purged = self
+                .expiry
+                .true_remove_if(key, |_, deadline| *deadline <= now)
+                && self.data.true_if_removed(key);
+            if purged {
+                self.notify(key, KeyEvent::Expired);
+            }
+            purged
+        } else {
+            false
+        }
+    }
+    /// Set a TTL (in seconds) on an existing key. Returns `true` if the key exists
+    pub fn expire<Q: AsRef<[u8]>>(&self, key: Q, secs: u64) -> EncodingResult<bool> {
+        self.check_key_encoding(key.as_ref())?;
+        Ok(self.expire_unchecked(key, secs))
+    }
+    /// Set a TTL (in seconds) on an existing key without encoding checks
+    pub fn expire_unchecked<Q: AsRef<[u8]>>(&self, key: Q, secs: u64) -> bool {
+        let key = key.as_ref();
+        if self.purge_if_expired(key) {
+            return false;
+        }
+        match self.data.get(key) {
+            Some(entry) => {
+                let deadline = unix_millis().saturating_add(secs.saturating_mul(1000));
+                self.expiry.upsert(entry.key().clone(), deadline);
+                self.mutated();
+                true
+            }
+            None => false,
+        }
+    }
+    /// Returns the remaining time to live (in milliseconds) for the given key. Returns
+    /// `None` if the key doesn't exist and `Some(None)` if the key has no TTL
+    pub fn ttl<Q: AsRef<[u8]>>(&self, key: Q) -> EncodingResult<Option<Option<u64>>> {
+        let key = key.as_ref();
+        self.check_key_encoding(key)?;
+        if self.purge_if_expired(key) || !self.data.contains_key(key) {
+            return Ok(None);
+        }
+        let now = unix_millis();
+        Ok(Some(
+            self.expiry
+                .get(key)
+                .map(|deadline| deadline.saturating_sub(now)),
+        ))
+    }
+    /// Remove the TTL for the given key. Returns `true` if a TTL was removed
+    pub fn persist<Q: AsRef<[u8]>>(&self, key: Q) -> EncodingResult<bool> {
+        let key = key.as_ref();
+        self.check_key_encoding(key)?;
+        let persisted = !self.purge_if_expired(key) && self.expiry.true_if_removed(key);
+        if persisted {
+            self.mutated();
+        }
+        Ok(persisted)
+    }
+    /// Remove all keys whose deadlines have passed. Returns the number of removed keys
+    pub fn purge_expired(&self) -> usize {
+        let now = unix_millis();
+        let expired: Vec<SharedSlice> = self
+            .expiry
+            .iter()
+            .filter(|kv| *kv.value() <= now)
+            .map(|kv| kv.key().clone())
+            .collect();
+        let mut purged = 0;
+        for key in expired {
+            // the key may have been persisted or re-set in the meantime
+            if self
+                .expiry
+                .true_remove_if(&key, |_, deadline| *deadline <= now)
+                && self.data.true_if_removed(&key)
+            {
+                self.notify(&key, KeyEvent::Expired);
+                purged += 1;
+            }
+        }
+        purged
+    }
+    /// Returns atmost `count` keys, skipping any keys that have expired
+    pub fn get_keys(&self, count: usize) -> Vec<SharedSlice> {
+        let now = unix_millis();
+        let mut v = Vec::with_capacity(count);
+        self.data
+            .iter()
+            .filter(|kv| match self.expiry.get(kv.key()) {
+                Some(deadline) => *deadline > now,
+                None => true,
+            })
+            .take(count)
+            .for_each(|kv| v.push(kv.key().clone()));
+        v
+    }
+    /// Returns the keys at the next `count` positions from `cursor` (skipping any keys that have
+    /// expired) along with the cursor to resume from, which is `0` once the scan is complete
+    pub fn scan(&self, cursor: u64, count: usize) -> (u64, Vec<SharedSlice>) {
+        let now = unix_millis();
+        let (next, mut keys) = self.data.scan(cursor, count);
+        keys.retain(|key| match self.expiry.get(key) {
+            Some(deadline) => *deadline > now,
+            None => true,
+        });
+        (next, keys)
     }
 }
 
@@ -170,17 +437,37 @@ impl<T: KVEValue> KVEngine<T> {
     }
     /// Get the value of the given key without any encoding checks
     pub fn get_unchecked<Q: AsRef<[u8]>>(&self, key: Q) -> OptionRef<T> {
+        if self.purge_if_expired(key.as_ref()) {
+            return None;
+        }
+        self.touch(key.as_ref());
         self.data.get(key.as_ref())
     }
     /// Set the value of the given key
     pub fn set(&self, key: SharedSlice, val: T) -> EncodingResult<bool> {
         self.check_key_encoding(&key)
-            .and_then(|_| val.verify_encoding(self.e_v))
+            .and_then(|_| val.verify_encoding(self.t_v))
             .map(|_| self.set_unchecked(key, val))
     }
     /// Same as set, but doesn't check encoding. Caller must check encoding
     pub fn set_unchecked(&self, key: SharedSlice, val: T) -> bool {
-        self.data.true_if_insert(key, val)
+        self.purge_if_expired(&key);
+        let key_for_expiry = key.clone();
+        let did = self.data.true_if_insert(key, val);
+        if did {
+            // a previous incarnation of this key may have left a deadline behind
+            self.expiry.remove(&key_for_expiry);
+            self.notify(&key_for_expiry, KeyEvent::Set);
+        }
+        did
+    }
+    /// Set the value of the given key, expiring it after `secs` seconds
+    pub fn set_with_ttl(&self, key: SharedSlice, val: T, secs: u64) -> EncodingResult<bool> {
+        let did = self.set(key.clone(), val)?;
+        if did {
+            self.expire_unchecked(key, secs);
+        }
+        Ok(did)
     }
     /// Check if the provided key exists
     pub fn exists<Q: AsRef<[u8]>>(&self, key: Q) -> EncodingResult<bool> {
@@ -188,28 +475,35 @@ impl<T: KVEValue> KVEngine<T> {
         Ok(self.exists_unchecked(key.as_ref()))
     }
     pub fn exists_unchecked<Q: AsRef<[u8]>>(&self, key: Q) -> bool {
-        self.data.contains_key(key.as_ref())
+        !self.purge_if_expired(key.as_ref()) && self.data.contains_key(key.as_ref())
     }
     /// Update the value of an existing key. Returns `true` if updated
     pub fn update(&self, key: SharedSlice, val: T) -> EncodingResult<bool> {
         self.check_key_encoding(&key)?;
-        val.verify_encoding(self.e_v)?;
+        val.verify_encoding(self.t_v)?;
         Ok(self.update_unchecked(key, val))
     }
-    /// Update the value of an existing key without encoding checks
+    /// Update the value of an existing key without encoding checks. The TTL (if any) is
+    /// retained
     pub fn update_unchecked(&self, key: SharedSlice, val: T) -> bool {
-        self.data.true_if_update(key, val)
+        if self.purge_if_expired(&key) || !self.data.true_if_update(key.clone(), val) {
+            return false;
+        }
+        self.notify(&key, KeyEvent::Update);
+        true
     }
     /// Update or insert an entry
     pub fn upsert(&self, key: SharedSlice, val: T) -> EncodingResult<()> {
         self.check_key_encoding(&key)?;
-        val.verify_encoding(self.e_v)?;
+        val.verify_encoding(self.t_v)?;
         self.upsert_unchecked(key, val);
         Ok(())
     }
-    /// Update or insert an entry without encoding checks
+    /// Update or insert an entry without encoding checks. This will clear any TTL
     pub fn upsert_unchecked(&self, key: SharedSlice, val: T) {
-        self.data.upsert(key, val)
+        self.expiry.remove(&key);
+        self.data.upsert(key.clone(), val);
+        self.notify(&key, KeyEvent::Set);
     }
     /// Remove an entry
     pub fn remove<Q: AsRef<[u8]>>(&self, key: Q) -> EncodingResult<bool> {
@@ -218,7 +512,15 @@ impl<T: KVEValue> KVEngine<T> {
     }
     /// Remove an entry without encoding checks
     pub fn remove_unchecked<Q: AsRef<[u8]>>(&self, key: Q) -> bool {
-        self.data.true_if_removed(key.as_ref())
+        if self.purge_if_expired(key.as_ref()) {
+            return false;
+        }
+        self.expiry.remove(key.as_ref());
+        let did = self.data.true_if_removed(key.as_ref());
+        if did {
+            self.notify(key.as_ref(), KeyEvent::Del);
+        }
+        did
     }
     /// Pop an entry
     pub fn pop<Q: AsRef<[u8]>>(&self, key: Q) -> EncodingResult<Option<T>> {
@@ -227,7 +529,105 @@ impl<T: KVEValue> KVEngine<T> {
     }
     /// Pop an entry without encoding checks
     pub fn pop_unchecked<Q: AsRef<[u8]>>(&self, key: Q) -> Option<T> {
-        self.data.remove(key.as_ref()).map(|(_, v)| v)
+        if self.purge_if_expired(key.as_ref()) {
+            return None;
+        }
+        self.expiry.remove(key.as_ref());
+        let popped = self.data.remove(key.as_ref()).map(|(_, v)| v);
+        if popped.is_some() {
+            self.notify(key.as_ref(), KeyEvent::Del);
+        }
+        popped
+    }
+}
+
+// eviction impls
+impl<T: KVEValue> KVEngine<T> {
+    /// Keep track of the memory held by the table's keys and values, evicting keys as
+    /// picked by `policy` when volatile models go over the memory limit
+    pub fn track_memory(&mut self, policy: EvictionPolicy) {
+        match self.tracker {
+            Some(ref mut tracker) => tracker.set_policy(policy),
+            None => {
+                let tracker = Box::new(Tracker::new(policy));
+                self.data.iter().for_each(|kv| {
+                    tracker.resize(
+                        kv.key().clone(),
+                        Some(kv.key().len() + kv.value().footprint()),
+                    )
+                });
+                self.tracker = Some(tracker);
+            }
+        }
+    }
+    /// Returns the eviction policy if the table's memory is tracked
+    pub fn eviction_policy(&self) -> Option<EvictionPolicy> {
+        self.tracker.as_ref().map(|tracker| tracker.policy())
+    }
+    /// Returns the number of bytes held by the table's keys and values (if tracked)
+    pub fn memory_usage(&self) -> Option<usize> {
+        self.tracker.as_ref().map(|tracker| tracker.memory())
+    }
+    /// Measure the keys that changed since they were last measured. This must not be called
+    /// while holding a lock on the data
+    pub fn settle_memory(&self) {
+        if let Some(ref tracker) = self.tracker {
+            for key in tracker.take_dirty() {
+                let size = self.data.get(&key).map(|v| key.len() + v.footprint());
+                tracker.resize(key, size);
+            }
+        }
+    }
+    /// Catch up with the writes made since the last call: measures the keys of volatile
+    /// tables and sorts in the keys of ordered tables. This must not be called while holding
+    /// a lock on the data
+    pub fn settle(&self) {
+        self.settle_memory();
+        self.settle_order();
+    }
+    /// Evict keys from this table until volatile models are within the memory limit.
+    /// Returns `false` if that isn't possible because the eviction policy doesn't allow it
+    /// or because the table has nothing left to evict
+    pub fn make_room(&self) -> bool {
+        let tracker = match self.tracker {
+            Some(ref tracker) => tracker,
+            None => return true,
+        };
+        self.settle_memory();
+        while registry::is_over_max_memory() {
+            let victim = match tracker.pick_victim() {
+                Some(victim) => victim,
+                None => return false,
+            };
+            self.expiry.remove(&victim);
+            if self.data.true_if_removed(&victim) {
+                self.notify(&victim, KeyEvent::Evicted);
+                registry::evicted_keys().fetch_add(1, Ordering::AcqRel);
+            }
+            tracker.resize(victim, None);
+        }
+        true
+    }
+}
+
+// ordered impls
+impl<T> KVEngine<T> {
+    /// Keep the keys sorted so that the table can be queried by ranges of keys
+    pub fn into_ordered(mut self) -> Self {
+        let keys = self.data.iter().map(|kv| kv.key().clone());
+        self.ordered = Some(Box::new(OrderedKeys::new(keys)));
+        self
+    }
+    /// Returns true if the keys are kept sorted
+    pub fn is_ordered(&self) -> bool {
+        self.ordered.is_some()
+    }
+    /// Sort in the keys that changed since they were last sorted in. This must not be called
+    /// while holding a lock on the data
+    pub fn settle_order(&self) {
+        if let Some(ref ordered) = self.ordered {
+            ordered.settle(|key| self.data.contains_key(key));
+        }
     }
 }
 
@@ -237,13 +637,118 @@ impl<T: Clone> KVEngine<T> {
         Ok(self.get_cloned_unchecked(key.as_ref()))
     }
     pub fn get_cloned_unchecked<Q: AsRef<[u8]>>(&self, key: Q) -> Option<T> {
+        if self.purge_if_expired(key.as_ref()) {
+            return None;
+        }
+        self.touch(key.as_ref());
         self.data.get_cloned(key.as_ref())
     }
+    /// Returns atmost `limit` keys in the range along with their values, in ascending order
+    /// of the keys (or descending, if `reverse` is set). Keys that have expired are skipped.
+    /// Tables that aren't ordered have nothing in any range
+    pub fn range(&self, range: KeyRange, limit: usize, reverse: bool) -> Vec<(SharedSlice, T)> {
+        let mut ret = Vec::new();
+        let ordered = match self.ordered {
+            Some(ref ordered) if limit != 0 => ordered,
+            _ => return ret,
+        };
+        self.settle_order();
+        let now = unix_millis();
+        ordered.visit(range, reverse, |key| {
+            let live = match self.expiry.get(key) {
+                Some(deadline) => *deadline > now,
+                None => true,
+            };
+            if live {
+                if let Some(value) = self.data.get_cloned(key) {
+                    ret.push((key.clone(), value));
+                }
+            }
+            ret.len() < limit
+        });
+        ret
+    }
+    /// Same as [`KVEngine::range`], but for the keys that start with `prefix`
+    pub fn prefix(&self, prefix: &[u8], limit: usize, reverse: bool) -> Vec<(SharedSlice, T)> {
+        let end = ordered::prefix_end(prefix);
+        let end = match end {
+            Some(ref end) => Bound::Excluded(end.as_slice()),
+            None => Bound::Unbounded,
+        };
+        self.range((Bound::Included(prefix), end), limit, reverse)
+    }
 }
 
 impl KVEStandard {
     pub fn take_snapshot_unchecked<Q: AsRef<[u8]>>(&self, key: Q) -> Option<SharedSlice> {
-        self.data.get_cloned(key.as_ref())
+        self.get_cloned_unchecked(key.as_ref())
+    }
+    /// Add `delta` to the number stored at `key`, starting from zero if the key doesn't exist.
+    /// Returns the new value
+    pub fn incr_by(
+        &self,
+        key: SharedSlice,
+        delta: &[u8],
+    ) -> EncodingResult<Result<SharedSlice, NumError>> {
+        self.check_key_encoding(&key)?;
+        Ok(self.incr_by_unchecked(key, delta))
+    }
+    /// Same as `incr_by`, but without encoding checks. The value is read and written while
+    /// holding the entry, so concurrent increments on the same key never lose an update. The
+    /// TTL (if any) is retained
+    pub fn incr_by_unchecked(
+        &self,
+        key: SharedSlice,
+        delta: &[u8],
+    ) -> Result<SharedSlice, NumError> {
+        self.purge_if_expired(&key);
+        let key_for_expiry = key.clone();
+        match self.data.entry(key) {
+            Entry::Occupied(mut oe) => {
+                let new = SharedSlice::from(self.t_v.add(oe.value(), delta)?);
+                oe.insert(new.clone());
+                drop(oe);
+                self.notify(&key_for_expiry, KeyEvent::Update);
+                Ok(new)
+            }
+            Entry::Vacant(ve) => {
+                let new = SharedSlice::from(self.t_v.add(b"0", delta)?);
+                ve.insert(new.clone());
+                // a previous incarnation of this key may have left a deadline behind
+                self.expiry.remove(&key_for_expiry);
+                self.notify(&key_for_expiry, KeyEvent::Set);
+                Ok(new)
+            }
+        }
+    }
+    /// Replace the value at `key` with what `edit` makes of it, where a key that doesn't exist
+    /// has an empty value. `edit` returns the new value along with the result, and if it fails
+    /// the value is left alone. Just like `incr_by_unchecked`, the entry is held throughout and
+    /// the TTL (if any) is retained. No encoding checks are done
+    pub fn edit_unchecked<R, E>(
+        &self,
+        key: SharedSlice,
+        edit: impl FnOnce(&[u8]) -> Result<(SharedSlice, R), E>,
+    ) -> Result<R, E> {
+        self.purge_if_expired(&key);
+        let key_for_expiry = key.clone();
+        match self.data.entry(key) {
+            Entry::Occupied(mut oe) => {
+                let (new, ret) = edit(oe.value())?;
+                oe.insert(new);
+                drop(oe);
+                self.notify(&key_for_expiry, KeyEvent::Update);
+                Ok(ret)
+            }
+            Entry::Vacant(ve) => {
+                let (new, ret) = edit(b"")?;
+                ve.insert(new);
+                // a previous incarnation of this key may have left a deadline behind
+                self.expiry.remove(&key_for_expiry);
+                self.notify(&key_for_expiry, KeyEvent::Set);
+                Ok(ret)
+            }
+        }
     }
     /// Returns an encoder that checks each key and each value in turn
     /// Usual usage:
@@ -252,8 +757,9 @@ impl KVEStandard {
     ///     assert!(kve.get_double_encoder(k, v))
     /// }
     /// ```
-    pub fn get_double_encoder(&self) -> DoubleEncoder {
-        ENCODING_LUT_PAIR[(self.e_k, self.e_v)]
+    pub fn get_double_encoder(&self) -> impl Fn(&[u8], &[u8]) -> bool + Copy + Send + 'static {
+        let (key_encoder, val_encoder) = (self.get_key_encoder(), self.get_val_encoder());
+        move |key: &[u8], val: &[u8]| key_encoder(key) && val_encoder(val)
     }
 }
 
@@ -286,6 +792,25 @@ impl KVEListmap {
             .get(listname)
             .map(|list| list.read().iter().cloned().collect()))
     }
+    /// Returns the maximum length of the lists in this table, if they're capped
+    pub fn max_len(&self) -> Option<usize> {
+        self.max_len
+    }
+    /// Cap the lists in this table at `max_len` elements. This is only done when the table is
+    /// created, so the lists that it already has must fit
+    pub fn set_max_len(&mut self, max_len: usize) {
+        self.max_len = Some(max_len);
+    }
+    /// Drop elements from the front of the list until it fits the table's maximum length (so
+    /// that the elements that were added last are kept). This does nothing if the lists in
+    /// this table aren't capped
+    pub fn cap(&self, list: &mut Vec<SharedSlice>) {
+        if let Some(max_len) = self.max_len {
+            if list.len() > max_len {
+                list.drain(..list.len() - max_len);
+            }
+        }
+    }
 }
 
 impl<T> Default for KVEngine<T> {
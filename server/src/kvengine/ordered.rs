@@ -0,0 +1,167 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Ordered keys
+//!
+//! Ordered models keep a sorted copy of their keys next to the hashtable that holds the data,
+//! so that they can answer range and prefix queries. Keys are sorted by their bytes.
+//!
+//! The hashtable is changed from all over the place (and usually while an entry in it is
+//! locked), so changes only mark a key as dirty. The sorted keys catch up with the dirty keys
+//! before they are read and after every write (see
+//! [`KVEngine::settle_order`](super::KVEngine::settle_order)).
+
+use {
+    crate::corestore::SharedSlice,
+    core::{mem, ops::Bound},
+    parking_lot::{Mutex, RwLock},
+    std::collections::BTreeSet,
+};
+
+/// A range of keys
+pub type KeyRange<'a> = (Bound<&'a [u8]>, Bound<&'a [u8]>);
+
+/// The sorted keys of an ordered table
+#[derive(Debug, Default)]
+pub struct OrderedKeys {
+    keys: RwLock<BTreeSet<SharedSlice>>,
+    /// keys that changed since they were last sorted in
+    dirty: Mutex<Vec<SharedSlice>>,
+}
+
+impl OrderedKeys {
+    pub fn new(keys: impl IntoIterator<Item = SharedSlice>) -> Self {
+        Self {
+            keys: RwLock::new(keys.into_iter().collect()),
+            dirty: Mutex::new(Vec::new()),
+        }
+    }
+    /// Record a change to the key
+    pub fn mark_dirty(&self, key: &[u8]) {
+        self.dirty.lock().push(SharedSlice::new(key));
+    }
+    /// Sort in the keys that changed, where `exists` tells if a key is still in the table.
+    /// The dirty keys are taken while holding the sorted keys, so that two calls can't apply
+    /// what they saw out of order
+    pub fn settle(&self, exists: impl Fn(&[u8]) -> bool) {
+        let mut keys = self.keys.write();
+        let dirty = mem::take(&mut *self.dirty.lock());
+        for key in dirty {
+            if exists(&key) {
+                keys.insert(key);
+            } else {
+                keys.remove(&key);
+            }
+        }
+    }
+    /// Forget all the keys while `clear` empties the table. Keys added to the table while it's
+    /// being cleared are still dirty, so they are sorted in later
+    pub fn clear_with(&self, clear: impl FnOnce()) {
+        let mut keys = self.keys.write();
+        clear();
+        keys.clear();
+    }
+    /// Call `f` for the keys in `range` in ascending order (or descending, if `reverse` is set)
+    /// until it returns false
+    pub fn visit(&self, range: KeyRange, reverse: bool, mut f: impl FnMut(&SharedSlice) -> bool) {
+        if is_empty_range(range) {
+            return;
+        }
+        let keys = self.keys.read();
+        let iter = keys.range::<[u8], _>(range);
+        if reverse {
+            iter.rev().take_while(|key| f(key)).for_each(drop);
+        } else {
+            iter.take_while(|key| f(key)).for_each(drop);
+        }
+    }
+}
+
+/// Returns true if nothing can be in the range. Ordered collections panic if they're asked
+/// for such a range
+fn is_empty_range((start, end): KeyRange) -> bool {
+    match (start, end) {
+        (Bound::Included(start), Bound::Included(end)) => start > end,
+        (Bound::Included(start) | Bound::Excluded(start), Bound::Excluded(end))
+        | (Bound::Excluded(start), Bound::Included(end)) => start >= end,
+        _ => false,
+    }
+}
+
+/// Returns the smallest key that's larger than every key starting with `prefix`, or `None` if
+/// there's no such key (the prefix is empty or all `0xFF`s)
+pub fn prefix_end(prefix: &[u8]) -> Option<Vec<u8>> {
+    let last = prefix.iter().rposition(|byte| *byte != u8::MAX)?;
+    let mut end = prefix[..=last].to_vec();
+    end[last] += 1;
+    Some(end)
+}
+
+#[test]
+fn test_visit_ranges() {
+    let keys = OrderedKeys::new(["a", "b", "c", "d"].into_iter().map(SharedSlice::from));
+    let collect = |range: KeyRange, reverse: bool, limit: usize| {
+        let mut ret = Vec::new();
+        keys.visit(range, reverse, |key| {
+            ret.push(String::from_utf8(key.to_vec()).unwrap());
+            ret.len() < limit
+        });
+        ret
+    };
+    let all = (Bound::Unbounded, Bound::Unbounded);
+    assert_eq!(collect(all, false, 10), ["a", "b", "c", "d"]);
+    assert_eq!(collect(all, true, 2), ["d", "c"]);
+    let some = (Bound::Excluded(&b"a"[..]), Bound::Included(&b"c"[..]));
+    assert_eq!(collect(some, false, 10), ["b", "c"]);
+    assert_eq!(collect(some, true, 10), ["c", "b"]);
+    // these would panic if they made it to the set
+    let empty = (Bound::Excluded(&b"b"[..]), Bound::Excluded(&b"b"[..]));
+    assert!(collect(empty, false, 10).is_empty());
+    let backwards = (Bound::Included(&b"c"[..]), Bound::Included(&b"a"[..]));
+    assert!(collect(backwards, false, 10).is_empty());
+}
+
+#[test]
+fn test_settle() {
+    let keys = OrderedKeys::new(["a", "b"].into_iter().map(SharedSlice::from));
+    keys.mark_dirty(b"b");
+    keys.mark_dirty(b"c");
+    keys.settle(|key| key != b"b");
+    let mut ret = Vec::new();
+    keys.visit((Bound::Unbounded, Bound::Unbounded), false, |key| {
+        ret.push(key.to_vec());
+        true
+    });
+    assert_eq!(ret, [b"a", b"c"]);
+}
+
+#[test]
+fn test_prefix_end() {
+    assert_eq!(prefix_end(b"user:"), Some(b"user;".to_vec()));
+    assert_eq!(prefix_end(b"a\xff\xff"), Some(b"b".to_vec()));
+    assert_eq!(prefix_end(b"\xff"), None);
+    assert_eq!(prefix_end(b""), None);
+}
@@ -0,0 +1,279 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Queues
+//!
+//! The engine behind models whose values are reliable queues, like:
+//! ```text
+//! create model jobs(string, queue<binary>)
+//! ```
+//! Unlike popping an item off a list, reserving an item off a queue doesn't remove it. The
+//! item is handed to the consumer along with a reservation ID and stays reserved until the
+//! consumer acknowledges it (which removes it) or gives it back. If neither happens before
+//! its visibility timeout runs out, the item goes back on the queue so that another consumer
+//! can pick it up. Reservations are persisted along with the queue, so a reserved item isn't
+//! lost if the server restarts before it is acknowledged
+//!
+//! Expired reservations are put back when the queue is next changed. Deadlines are absolute
+//! wall-clock times (like key expiry), so this works out the same after a restart
+
+use {
+    super::{types::FieldType, unix_millis, EncodingResult, KVEValue, KVEngine},
+    crate::{
+        corestore::{map::bref::Entry, SharedSlice},
+        pubsub::KeyEvent,
+    },
+    core::mem,
+    parking_lot::RwLock,
+    std::collections::{BTreeMap, VecDeque},
+};
+
+pub type LockedQueue = RwLock<Queue>;
+pub type KVEQueuemap = KVEngine<LockedQueue>;
+
+/// The visibility timeout of a reservation if the consumer doesn't pick one, in seconds
+pub const DEFAULT_VISIBILITY_TIMEOUT: u64 = 30;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// An item that was handed to a consumer but hasn't been acknowledged yet
+pub struct Reservation {
+    pub item: SharedSlice,
+    /// the UNIX time (in ms) after which the item goes back on the queue
+    pub deadline: u64,
+}
+
+#[derive(Debug, Default)]
+/// The items of a single queue
+pub struct Queue {
+    /// the items waiting to be reserved, in order
+    ready: VecDeque<SharedSlice>,
+    /// the reserved items, by their reservation ID
+    reserved: BTreeMap<u64, Reservation>,
+    /// the ID of the next reservation. IDs are never reused, so that a consumer whose
+    /// reservation expired can't acknowledge somebody else's
+    next_id: u64,
+}
+
+impl Queue {
+    /// Create a queue from its persisted parts. Returns `None` if a reservation has an ID that
+    /// wasn't handed out yet
+    pub fn from_parts(
+        ready: VecDeque<SharedSlice>,
+        reserved: BTreeMap<u64, Reservation>,
+        next_id: u64,
+    ) -> Option<Self> {
+        match reserved.last_key_value() {
+            Some((&id, _)) if id >= next_id => None,
+            _ => Some(Self {
+                ready,
+                reserved,
+                next_id,
+            }),
+        }
+    }
+    /// Returns the items waiting to be reserved, in order
+    pub fn ready(&self) -> &VecDeque<SharedSlice> {
+        &self.ready
+    }
+    /// Returns the reserved items, by their reservation ID
+    pub fn reserved(&self) -> &BTreeMap<u64, Reservation> {
+        &self.reserved
+    }
+    /// Returns the ID of the next reservation
+    pub const fn next_id(&self) -> u64 {
+        self.next_id
+    }
+    /// Returns the number of items waiting to be reserved at `now` and the number of items
+    /// that are reserved
+    pub fn counts(&self, now: u64) -> (usize, usize) {
+        let expired = self
+            .reserved
+            .values()
+            .filter(|reservation| reservation.deadline <= now)
+            .count();
+        (self.ready.len() + expired, self.reserved.len() - expired)
+    }
+    /// Put the items whose reservations ran out by `now` back on the queue, ahead of the
+    /// other items and in the order they were reserved in. Returns true if there were any
+    fn requeue_expired(&mut self, now: u64) -> bool {
+        let expired: Vec<u64> = self
+            .reserved
+            .iter()
+            .filter(|(_, reservation)| reservation.deadline <= now)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in expired.iter().rev() {
+            if let Some(reservation) = self.reserved.remove(id) {
+                self.ready.push_front(reservation.item);
+            }
+        }
+        !expired.is_empty()
+    }
+    fn reserve(&mut self, deadline: u64) -> Option<(u64, SharedSlice)> {
+        let item = self.ready.pop_front()?;
+        let id = self.next_id;
+        self.next_id += 1;
+        let reservation = Reservation {
+            item: item.clone(),
+            deadline,
+        };
+        self.reserved.insert(id, reservation);
+        Some((id, item))
+    }
+    fn nack(&mut self, id: u64) -> bool {
+        match self.reserved.remove(&id) {
+            Some(reservation) => {
+                self.ready.push_front(reservation.item);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl KVEValue for LockedQueue {
+    fn verify_encoding(&self, t_v: FieldType) -> EncodingResult<()> {
+        let queue = self.read();
+        let mut reserved = queue.reserved.values().map(|reservation| &reservation.item);
+        if queue
+            .ready
+            .iter()
+            .chain(&mut reserved)
+            .all(|item| t_v.is_ok(item))
+        {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+    fn footprint(&self) -> usize {
+        let queue = self.read();
+        let reserved: usize = queue
+            .reserved
+            .values()
+            .map(|reservation| reservation.item.len() + mem::size_of::<(u64, u64)>())
+            .sum();
+        queue.ready.iter().map(|item| item.len()).sum::<usize>() + reserved
+    }
+}
+
+impl KVEQueuemap {
+    /// Append the given items to the queue at `key`, creating the queue if it doesn't exist.
+    /// `log` is called with the queue before it is unlocked. Returns the number of items
+    /// waiting to be reserved. The caller must check the encoding
+    pub fn push_items(&self, key: &[u8], items: &[SharedSlice], log: impl FnOnce(&Queue)) -> usize {
+        let now = unix_millis();
+        let (waiting, event) = match self.get_inner_ref().entry(SharedSlice::new(key)) {
+            Entry::Occupied(oe) => {
+                let mut queue = oe.value().write();
+                queue.requeue_expired(now);
+                queue.ready.extend(items.iter().cloned());
+                log(&queue);
+                (queue.ready.len(), KeyEvent::Update)
+            }
+            Entry::Vacant(ve) => {
+                let queue = Queue {
+                    ready: items.iter().cloned().collect(),
+                    ..Default::default()
+                };
+                log(&queue);
+                let waiting = queue.ready.len();
+                ve.insert(RwLock::new(queue));
+                (waiting, KeyEvent::Set)
+            }
+        };
+        self.notify(key, event);
+        waiting
+    }
+    /// Reserve the first item of the queue at `key` for `timeout` seconds. Returns the
+    /// reservation ID and the item, or `None` if no item is waiting
+    pub fn reserve(
+        &self,
+        key: &[u8],
+        timeout: u64,
+        log: impl FnOnce(&Queue),
+    ) -> Option<(u64, SharedSlice)> {
+        self.modify(key, log, |queue, now| {
+            let deadline = now.saturating_add(timeout.saturating_mul(1000));
+            let reserved = queue.reserve(deadline);
+            let changed = reserved.is_some();
+            (reserved, changed)
+        })
+        .flatten()
+    }
+    /// Acknowledge the reservation `id` in the queue at `key`, removing its item for good.
+    /// Returns false if there is no such reservation (or it ran out)
+    pub fn ack(&self, key: &[u8], id: u64, log: impl FnOnce(&Queue)) -> bool {
+        self.modify(key, log, |queue, _| {
+            let acked = queue.reserved.remove(&id).is_some();
+            (acked, acked)
+        })
+        .unwrap_or(false)
+    }
+    /// Give up the reservation `id` in the queue at `key`, putting its item back at the front
+    /// of the queue. Returns false if there is no such reservation (or it ran out)
+    pub fn nack(&self, key: &[u8], id: u64, log: impl FnOnce(&Queue)) -> bool {
+        self.modify(key, log, |queue, _| {
+            let nacked = queue.nack(id);
+            (nacked, nacked)
+        })
+        .unwrap_or(false)
+    }
+    /// Returns the number of items waiting to be reserved in the queue at `key` and the number
+    /// of items that are reserved
+    pub fn counts(&self, key: &[u8]) -> Option<(usize, usize)> {
+        self.touch(key);
+        let now = unix_millis();
+        self.get_inner_ref()
+            .get(key)
+            .map(|queue| queue.read().counts(now))
+    }
+    /// Run `f` on the queue at `key` once the expired reservations are back on the queue. `f`
+    /// also returns whether it changed the queue; if anything changed, `log` is called with
+    /// the queue before it is unlocked. Returns `None` if there is no queue at `key`
+    fn modify<T>(
+        &self,
+        key: &[u8],
+        log: impl FnOnce(&Queue),
+        f: impl FnOnce(&mut Queue, u64) -> (T, bool),
+    ) -> Option<T> {
+        let now = unix_millis();
+        let entry = self.get_inner_ref().get(key)?;
+        let mut queue = entry.write();
+        let requeued = queue.requeue_expired(now);
+        let (ret, changed) = f(&mut queue, now);
+        let changed = changed || requeued;
+        if changed {
+            log(&queue);
+        }
+        drop(queue);
+        drop(entry);
+        if changed {
+            self.notify(key, KeyEvent::Update);
+        }
+        Some(ret)
+    }
+}
@@ -0,0 +1,162 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Rowmaps
+//!
+//! A rowmap is the engine behind models declared with named fields, like:
+//! ```text
+//! create model users(username: string, email: string, avatar: binary)
+//! ```
+//! The first field is the primary key. Every row holds the values of the remaining fields in
+//! the order in which they were declared, so a row is stored exactly like a list in a listmap.
+//! The [`Schema`] is what gives the positions in a row a name and a type
+
+use {
+    super::{types::FieldType, KVEListmap, LockedVec},
+    crate::corestore::{htable::Coremap, SharedSlice},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A named field
+pub struct Field {
+    name: Box<str>,
+    ty: FieldType,
+}
+
+impl Field {
+    pub fn new(name: impl Into<Box<str>>, ty: FieldType) -> Self {
+        Self {
+            name: name.into(),
+            ty,
+        }
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub const fn ty(&self) -> FieldType {
+        self.ty
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The fields of a rowmap. The first field is the primary key
+pub struct Schema {
+    fields: Box<[Field]>,
+}
+
+impl Schema {
+    /// Create a new schema. Returns `None` unless there are at least two fields and no two
+    /// fields share the same name
+    pub fn new(fields: Vec<Field>) -> Option<Self> {
+        let names_are_unique = fields
+            .iter()
+            .enumerate()
+            .all(|(i, field)| fields[..i].iter().all(|f| f.name != field.name));
+        if fields.len() >= 2 && names_are_unique {
+            Some(Self {
+                fields: fields.into_boxed_slice(),
+            })
+        } else {
+            None
+        }
+    }
+    /// All the fields, starting with the key
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+    /// The primary key
+    pub fn key(&self) -> &Field {
+        &self.fields[0]
+    }
+    /// The fields that are stored in a row (every field but the key)
+    pub fn row_fields(&self) -> &[Field] {
+        &self.fields[1..]
+    }
+    /// Returns the position of the field called `name` in [`Schema::fields`]
+    pub fn position(&self, name: &[u8]) -> Option<usize> {
+        self.fields
+            .iter()
+            .position(|field| field.name.as_bytes() == name)
+    }
+    /// Check if `row` has a value of the right type for every non-key field
+    pub fn is_row_ok<T: AsRef<[u8]>>(&self, row: &[T]) -> bool {
+        row.len() == self.row_fields().len()
+            && self
+                .row_fields()
+                .iter()
+                .zip(row)
+                .all(|(field, val)| field.ty.is_ok(val.as_ref()))
+    }
+    /// Returns the schema as `(name:type,...)`
+    pub fn describe(&self) -> String {
+        let fields: Vec<String> = self
+            .fields
+            .iter()
+            .map(|field| format!("{}:{}", field.name, field.ty.name()))
+            .collect();
+        format!("({})", fields.join(","))
+    }
+}
+
+#[derive(Debug)]
+/// A map of primary keys to rows
+pub struct Rowmap {
+    schema: Schema,
+    rows: KVEListmap,
+}
+
+impl Rowmap {
+    /// Create a new rowmap with the provided rows
+    pub fn new(schema: Schema, data: Coremap<SharedSlice, LockedVec>) -> Self {
+        // the values in a row are checked against the schema, so the listmap only checks keys
+        Self {
+            rows: KVEListmap::new_typed(schema.key().ty, FieldType::Binary, data),
+            schema,
+        }
+    }
+    /// Create a new empty rowmap
+    pub fn init(schema: Schema) -> Self {
+        Self::new(schema, Coremap::new())
+    }
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+    /// Returns the rows, keyed by their primary key
+    pub fn rows(&self) -> &KVEListmap {
+        &self.rows
+    }
+    pub fn rows_mut(&mut self) -> &mut KVEListmap {
+        &mut self.rows
+    }
+    /// Number of rows
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+    /// Delete all the rows
+    pub fn truncate_table(&self) {
+        self.rows.truncate_table()
+    }
+}
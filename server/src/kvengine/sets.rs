@@ -0,0 +1,420 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Sets
+//!
+//! The engines behind models whose values are sets, like:
+//! ```text
+//! create model tags(string, set<string>)
+//! create model leaderboards(string, zset<string>)
+//! ```
+//! Unlike with a list, adding, removing or looking for a member doesn't have to go through
+//! every member. The members of a set are kept in byte order, while the members of a sorted
+//! (`zset`) set are ordered by their score and then by their bytes. Either is removed along
+//! with its last member
+
+use {
+    super::{types::FieldType, EncodingResult, KVEValue, KVEngine},
+    crate::{
+        corestore::{map::bref::Entry, SharedSlice},
+        pubsub::KeyEvent,
+    },
+    core::{cmp::Ordering, fmt, mem, ops::Bound, str},
+    parking_lot::RwLock,
+    std::collections::{BTreeMap, BTreeSet},
+};
+
+/// The members of a single set
+pub type MemberSet = BTreeSet<SharedSlice>;
+pub type LockedSet = RwLock<MemberSet>;
+pub type KVESetmap = KVEngine<LockedSet>;
+pub type LockedSortedSet = RwLock<SortedSet>;
+pub type KVESortedSetmap = KVEngine<LockedSortedSet>;
+/// The (start, end) bounds of a range of scores
+pub type ScoreRange = (Bound<Score>, Bound<Score>);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// The score of a member in a sorted set. Scores are always finite
+pub struct Score(f64);
+
+impl Eq for Score {}
+
+impl PartialOrd for Score {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Score {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Score {
+    /// Parse a score. Like deltas, scores don't have to be in canonical form
+    pub fn parse(score: &[u8]) -> Option<Self> {
+        let score: f64 = str::from_utf8(score).ok()?.parse().ok()?;
+        Self::new(score)
+    }
+    /// Returns the score for its on-disk form. Returns `None` if it isn't finite
+    pub fn from_le_bytes(bytes: [u8; 8]) -> Option<Self> {
+        Self::new(f64::from_le_bytes(bytes))
+    }
+    /// Returns the on-disk form of this score
+    pub fn to_le_bytes(self) -> [u8; 8] {
+        self.0.to_le_bytes()
+    }
+    fn new(score: f64) -> Option<Self> {
+        // -0 and 0 are the same score
+        score.is_finite().then_some(Self(score + 0.0))
+    }
+    /// Returns the smallest score that is greater than this one. This may not be finite, so
+    /// it's only used to search for scores
+    fn next_up(self) -> Self {
+        Self(self.0.next_up())
+    }
+}
+
+#[derive(Debug, Default)]
+/// The members of a single sorted set
+pub struct SortedSet {
+    /// the score of every member
+    scores: BTreeMap<SharedSlice, Score>,
+    /// the members ordered by their score
+    ranked: BTreeSet<(Score, SharedSlice)>,
+}
+
+impl SortedSet {
+    /// Set the score of `member`, adding it if it isn't a member yet. Returns true if it was
+    /// added
+    pub fn insert(&mut self, member: SharedSlice, score: Score) -> bool {
+        let added = match self.scores.insert(member.clone(), score) {
+            Some(old) => {
+                self.ranked.remove(&(old, member.clone()));
+                false
+            }
+            None => true,
+        };
+        self.ranked.insert((score, member));
+        added
+    }
+    /// Returns the score of `member`
+    pub fn score(&self, member: &[u8]) -> Option<Score> {
+        self.scores.get(member).copied()
+    }
+    /// Returns the position of `member` when the members are ordered by score, starting
+    /// from 0. This goes through every member with a lower score
+    pub fn rank(&self, member: &[u8]) -> Option<usize> {
+        let score = self.score(member)?;
+        Some(
+            self.ranked
+                .range(..(score, SharedSlice::new(member)))
+                .count(),
+        )
+    }
+    /// Returns at most `limit` members whose scores are within `range`, along with their
+    /// scores. Members are ordered by score, from the highest if `reverse` is set
+    pub fn range_by_score(
+        &self,
+        (start, end): ScoreRange,
+        limit: usize,
+        reverse: bool,
+    ) -> Vec<(SharedSlice, Score)> {
+        // turn both ends into an inclusive start and an exclusive end, so that `ranked` can be
+        // searched with the lowest member (the empty one) for either score
+        let start = match start {
+            Bound::Included(score) => Some(score),
+            Bound::Excluded(score) => Some(score.next_up()),
+            Bound::Unbounded => None,
+        };
+        let end = match end {
+            Bound::Included(score) => Some(score.next_up()),
+            Bound::Excluded(score) => Some(score),
+            Bound::Unbounded => None,
+        };
+        if matches!((start, end), (Some(start), Some(end)) if start >= end) {
+            return Vec::new();
+        }
+        let lowest = |score| (score, SharedSlice::new(&[]));
+        let range = (
+            start.map_or(Bound::Unbounded, |score| Bound::Included(lowest(score))),
+            end.map_or(Bound::Unbounded, |score| Bound::Excluded(lowest(score))),
+        );
+        let members = self
+            .ranked
+            .range(range)
+            .map(|(score, member)| (member.clone(), *score));
+        if reverse {
+            members.rev().take(limit).collect()
+        } else {
+            members.take(limit).collect()
+        }
+    }
+    /// Returns the members along with their scores, ordered by score
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (&SharedSlice, Score)> {
+        self.ranked.iter().map(|(score, member)| (member, *score))
+    }
+}
+
+/// A set of either kind
+pub trait SetLike {
+    fn len(&self) -> usize;
+    fn contains(&self, member: &[u8]) -> bool;
+    /// Remove `member`, returning it if it was a member
+    fn take(&mut self, member: &[u8]) -> Option<SharedSlice>;
+    /// Returns the members, in no particular order
+    fn members(&self) -> Box<dyn Iterator<Item = &SharedSlice> + '_>;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl SetLike for MemberSet {
+    fn len(&self) -> usize {
+        BTreeSet::len(self)
+    }
+    fn contains(&self, member: &[u8]) -> bool {
+        BTreeSet::contains(self, member)
+    }
+    fn take(&mut self, member: &[u8]) -> Option<SharedSlice> {
+        BTreeSet::take(self, member)
+    }
+    fn members(&self) -> Box<dyn Iterator<Item = &SharedSlice> + '_> {
+        Box::new(self.iter())
+    }
+}
+
+impl SetLike for SortedSet {
+    fn len(&self) -> usize {
+        self.scores.len()
+    }
+    fn contains(&self, member: &[u8]) -> bool {
+        self.scores.contains_key(member)
+    }
+    fn take(&mut self, member: &[u8]) -> Option<SharedSlice> {
+        let (member, score) = self.scores.remove_entry(member)?;
+        self.ranked.remove(&(score, member.clone()));
+        Some(member)
+    }
+    fn members(&self) -> Box<dyn Iterator<Item = &SharedSlice> + '_> {
+        Box::new(self.scores.keys())
+    }
+}
+
+impl KVEValue for LockedSet {
+    fn verify_encoding(&self, t_v: FieldType) -> EncodingResult<()> {
+        verify_members(&*self.read(), t_v)
+    }
+    fn footprint(&self) -> usize {
+        self.read().iter().map(|member| member.len()).sum()
+    }
+}
+
+impl KVEValue for LockedSortedSet {
+    fn verify_encoding(&self, t_v: FieldType) -> EncodingResult<()> {
+        verify_members(&*self.read(), t_v)
+    }
+    fn footprint(&self) -> usize {
+        self.read()
+            .members()
+            .map(|member| member.len() + mem::size_of::<Score>())
+            .sum()
+    }
+}
+
+fn verify_members(set: &dyn SetLike, t_v: FieldType) -> EncodingResult<()> {
+    if set.members().all(|member| t_v.is_ok(member)) {
+        Ok(())
+    } else {
+        Err(())
+    }
+}
+
+impl<T: SetLike> KVEngine<RwLock<T>> {
+    /// Remove the given members from the set at `key`, removing the set if it has no members
+    /// left. `log` is called with the removed members before the set is unlocked. Returns the
+    /// number of members that were removed
+    pub fn remove_members(
+        &self,
+        key: &[u8],
+        members: &[&[u8]],
+        log: impl FnOnce(&[SharedSlice]),
+    ) -> usize {
+        let oe = match self.get_inner_ref().entry(SharedSlice::new(key)) {
+            Entry::Occupied(oe) => oe,
+            Entry::Vacant(_) => return 0,
+        };
+        let mut set = oe.value().write();
+        let removed: Vec<SharedSlice> = members
+            .iter()
+            .filter_map(|member| set.take(member))
+            .collect();
+        if removed.is_empty() {
+            return 0;
+        }
+        log(&removed);
+        let event = if set.is_empty() {
+            drop(set);
+            oe.remove();
+            KeyEvent::Del
+        } else {
+            drop(set);
+            drop(oe);
+            KeyEvent::Update
+        };
+        self.notify(key, event);
+        removed.len()
+    }
+    /// Returns true if `member` is in the set at `key`
+    pub fn is_member(&self, key: &[u8], member: &[u8]) -> bool {
+        self.touch(key);
+        self.get_inner_ref()
+            .get(key)
+            .is_some_and(|set| set.read().contains(member))
+    }
+    /// Returns the number of members in the set at `key`
+    pub fn set_len(&self, key: &[u8]) -> Option<usize> {
+        self.get_inner_ref().get(key).map(|set| set.read().len())
+    }
+}
+
+impl KVESetmap {
+    /// Add the given members to the set at `key`, creating the set if it doesn't exist. `log`
+    /// is called with the added members before the set is unlocked. Returns the number of
+    /// members that were added. The caller must check the encoding
+    pub fn add_members(
+        &self,
+        key: &[u8],
+        members: &[SharedSlice],
+        log: impl FnOnce(&[SharedSlice]),
+    ) -> usize {
+        let (added, event) = match self.get_inner_ref().entry(SharedSlice::new(key)) {
+            Entry::Occupied(oe) => {
+                let mut set = oe.value().write();
+                let added: Vec<SharedSlice> = members
+                    .iter()
+                    .filter(|member| set.insert((*member).clone()))
+                    .cloned()
+                    .collect();
+                if added.is_empty() {
+                    return 0;
+                }
+                log(&added);
+                (added.len(), KeyEvent::Update)
+            }
+            Entry::Vacant(ve) => {
+                let set: MemberSet = members.iter().cloned().collect();
+                let added = set.len();
+                log(members);
+                ve.insert(RwLock::new(set));
+                (added, KeyEvent::Set)
+            }
+        };
+        self.notify(key, event);
+        added
+    }
+    /// Returns the members of the set at `key`, in byte order
+    pub fn members(&self, key: &[u8]) -> Option<Vec<SharedSlice>> {
+        self.touch(key);
+        self.get_inner_ref()
+            .get(key)
+            .map(|set| set.read().iter().cloned().collect())
+    }
+}
+
+impl KVESortedSetmap {
+    /// Set the scores of the given members in the sorted set at `key`, adding the members
+    /// that aren't in it and creating the set if it doesn't exist. `log` is called with the
+    /// members before the set is unlocked. Returns the number of members that were added.
+    /// The caller must check the encoding
+    pub fn add_scored(
+        &self,
+        key: &[u8],
+        members: &[(SharedSlice, Score)],
+        log: impl FnOnce(&[(SharedSlice, Score)]),
+    ) -> usize {
+        let (added, event) = match self.get_inner_ref().entry(SharedSlice::new(key)) {
+            Entry::Occupied(oe) => {
+                let mut set = oe.value().write();
+                let added = members
+                    .iter()
+                    .filter(|(member, score)| set.insert(member.clone(), *score))
+                    .count();
+                log(members);
+                (added, KeyEvent::Update)
+            }
+            Entry::Vacant(ve) => {
+                let mut set = SortedSet::default();
+                let added = members
+                    .iter()
+                    .filter(|(member, score)| set.insert(member.clone(), *score))
+                    .count();
+                log(members);
+                ve.insert(RwLock::new(set));
+                (added, KeyEvent::Set)
+            }
+        };
+        self.notify(key, event);
+        added
+    }
+    /// Returns the score of `member` in the sorted set at `key`
+    pub fn score(&self, key: &[u8], member: &[u8]) -> Option<Score> {
+        self.touch(key);
+        self.get_inner_ref()
+            .get(key)
+            .and_then(|set| set.read().score(member))
+    }
+    /// Returns the rank of `member` in the sorted set at `key` (see [`SortedSet::rank`]),
+    /// counting from the highest score if `reverse` is set
+    pub fn rank(&self, key: &[u8], member: &[u8], reverse: bool) -> Option<usize> {
+        self.touch(key);
+        let set = self.get_inner_ref().get(key)?;
+        let set = set.read();
+        let rank = set.rank(member)?;
+        Some(if reverse { set.len() - rank - 1 } else { rank })
+    }
+    /// Returns the members of the sorted set at `key` whose scores are within `range` (see
+    /// [`SortedSet::range_by_score`])
+    pub fn range_by_score(
+        &self,
+        key: &[u8],
+        range: ScoreRange,
+        limit: usize,
+        reverse: bool,
+    ) -> Option<Vec<(SharedSlice, Score)>> {
+        self.touch(key);
+        self.get_inner_ref()
+            .get(key)
+            .map(|set| set.read().range_by_score(range, limit, reverse))
+    }
+}
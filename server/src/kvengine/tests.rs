@@ -24,7 +24,32 @@
  *
 */
 
-use super::{KVEStandard, SharedSlice};
+use {
+    super::{
+        eviction::EvictionPolicy,
+        hashmap::Hashmap,
+        queue::KVEQueuemap,
+        sets::{KVESortedSetmap, Score},
+        types::{FieldType, NumError},
+        KVEListmap, KVEStandard, SharedSlice,
+    },
+    crate::{
+        pubsub::{Kind, Subscriptions},
+        registry,
+    },
+    parking_lot::RwLock,
+    std::{
+        ops::Bound,
+        sync::{atomic::Ordering, Mutex, PoisonError},
+        thread,
+        time::Duration,
+    },
+    tokio::{runtime::Builder, time},
+};
+
+/// The memory used by volatile tables is counted across the process, so tests that track
+/// memory can't run alongside each other
+static MEMORY_TESTS: Mutex<()> = Mutex::new(());
 
 #[test]
 fn test_ignore_encoding() {
@@ -109,3 +134,611 @@ fn test_encoder_validate_with_non_unicode() {
     let encoder = tbl.get_double_encoder();
     assert!(!encoder("hello".as_bytes(), b"Hello \xF0\x90\x80World"));
 }
+
+#[test]
+fn test_expiry_hides_and_purges() {
+    let tbl = KVEStandard::default();
+    assert!(tbl.set_with_ttl("hello".into(), "world".into(), 0).unwrap());
+    assert!(tbl.set("persistent".into(), "value".into()).unwrap());
+    assert!(tbl.get_cloned("hello").unwrap().is_none());
+    assert!(!tbl.exists("hello").unwrap());
+    assert_eq!(tbl.get_keys(10), vec![SharedSlice::from("persistent")]);
+    // the expired key can be set again and it'll lose its old deadline
+    assert!(tbl.set("hello".into(), "world".into()).unwrap());
+    assert_eq!(tbl.ttl("hello").unwrap(), Some(None));
+    assert_eq!(tbl.purge_expired(), 0);
+    assert_eq!(tbl.len(), 2);
+}
+
+#[test]
+fn test_expire_ttl_persist() {
+    let tbl = KVEStandard::default();
+    assert!(!tbl.expire("hello", 10).unwrap());
+    assert_eq!(tbl.ttl("hello").unwrap(), None);
+    assert!(tbl.set("hello".into(), "world".into()).unwrap());
+    assert!(tbl.expire("hello", 10).unwrap());
+    let remaining = tbl.ttl("hello").unwrap().unwrap().unwrap();
+    assert!(remaining > 9_000 && remaining <= 10_000);
+    assert!(tbl.persist("hello").unwrap());
+    assert!(!tbl.persist("hello").unwrap());
+    assert_eq!(tbl.ttl("hello").unwrap(), Some(None));
+}
+
+#[test]
+fn test_purge_expired() {
+    let tbl = KVEStandard::default();
+    for i in 0..10 {
+        let key = format!("key{i}");
+        tbl.set(key.as_str().into(), "value".into()).unwrap();
+        tbl.expire(key.as_str(), (i % 2) * 100).unwrap();
+    }
+    assert_eq!(tbl.purge_expired(), 5);
+    assert_eq!(tbl.len(), 5);
+    assert_eq!(tbl.get_expiry_ref().len(), 5);
+}
+
+#[test]
+fn test_scan_skips_expired() {
+    let tbl = KVEStandard::default();
+    for i in 0..10 {
+        let key = format!("key{i}");
+        tbl.set(key.as_str().into(), "value".into()).unwrap();
+        tbl.expire(key.as_str(), (i % 2) * 100).unwrap();
+    }
+    let (mut cursor, mut seen) = (0, Vec::new());
+    loop {
+        let (next, keys) = tbl.scan(cursor, 3);
+        seen.extend(keys.iter().map(|key| key.to_vec()));
+        if next == 0 {
+            break;
+        }
+        cursor = next;
+    }
+    seen.sort_unstable();
+    assert_eq!(seen, [b"key1", b"key3", b"key5", b"key7", b"key9"]);
+}
+
+fn ordered_keys(pairs: Vec<(SharedSlice, SharedSlice)>) -> Vec<Vec<u8>> {
+    pairs.into_iter().map(|(key, _)| key.to_vec()).collect()
+}
+
+#[test]
+fn test_ordered_range() {
+    let tbl = KVEStandard::default().into_ordered();
+    assert!(tbl.is_ordered());
+    for key in ["b", "d", "a", "c", "e"] {
+        tbl.set(key.into(), "value".into()).unwrap();
+    }
+    let everything = (Bound::Unbounded, Bound::Unbounded);
+    assert_eq!(
+        ordered_keys(tbl.range(everything, usize::MAX, false)),
+        [b"a", b"b", b"c", b"d", b"e"]
+    );
+    assert_eq!(ordered_keys(tbl.range(everything, 2, true)), [b"e", b"d"]);
+    let bounded = (Bound::Excluded(&b"a"[..]), Bound::Included(&b"c"[..]));
+    assert_eq!(
+        ordered_keys(tbl.range(bounded, usize::MAX, false)),
+        [b"b", b"c"]
+    );
+    // backwards ranges are empty rather than a panic
+    let backwards = (Bound::Included(&b"d"[..]), Bound::Included(&b"b"[..]));
+    assert!(tbl.range(backwards, usize::MAX, false).is_empty());
+    // deletes and expiry drop out of the index
+    tbl.remove("b").unwrap();
+    tbl.expire("c", 0).unwrap();
+    assert_eq!(
+        ordered_keys(tbl.range(everything, usize::MAX, false)),
+        [b"a", b"d", b"e"]
+    );
+    tbl.truncate_table();
+    assert!(tbl.range(everything, usize::MAX, false).is_empty());
+    tbl.set("z".into(), "value".into()).unwrap();
+    assert_eq!(ordered_keys(tbl.range(everything, 10, false)), [b"z"]);
+}
+
+#[test]
+fn test_ordered_prefix() {
+    let tbl = KVEStandard::default().into_ordered();
+    for key in ["user:1", "user:2", "users", "user;", "admin:1"] {
+        tbl.set(key.into(), "value".into()).unwrap();
+    }
+    assert_eq!(
+        ordered_keys(tbl.prefix(b"user:", usize::MAX, false)),
+        [&b"user:1"[..], b"user:2"]
+    );
+    assert_eq!(
+        ordered_keys(tbl.prefix(b"user", 2, true)),
+        [&b"users"[..], b"user;"]
+    );
+    // an unordered table has no index to walk
+    let unordered = KVEStandard::default();
+    unordered.set("user:1".into(), "value".into()).unwrap();
+    assert!(unordered.prefix(b"user", usize::MAX, false).is_empty());
+}
+
+#[test]
+fn test_hashmap_fields() {
+    let hm = Hashmap::init(true, true, false);
+    let mut logged = 0;
+    let pairs = [("a".into(), "1".into()), ("b".into(), "2".into())];
+    assert_eq!(hm.set_fields(b"key", &pairs, |set| logged += set.len()), 2);
+    // overwriting a field doesn't count as an addition
+    let pairs = [("b".into(), "3".into()), ("c".into(), "4".into())];
+    assert_eq!(hm.set_fields(b"key", &pairs, |set| logged += set.len()), 1);
+    assert_eq!(logged, 4);
+    assert_eq!(hm.get_field(b"key", b"b").unwrap(), "3");
+    assert_eq!(hm.map_len(b"key"), Some(3));
+    // nothing is logged if nothing was removed
+    assert_eq!(hm.remove_fields(b"key", &[b"x"], |_| panic!("logged")), 0);
+    assert_eq!(hm.remove_fields(b"key", &[b"a", b"b"], |_| {}), 2);
+    assert_eq!(hm.remove_fields(b"key", &[b"c"], |_| {}), 1);
+    // the last field takes the key with it
+    assert_eq!(hm.len(), 0);
+    assert!(hm.get_all(b"key").is_none());
+    assert!(!hm.is_field_ok(b"\xFF"));
+}
+
+#[test]
+fn test_sorted_set_ranges() {
+    let zsets = KVESortedSetmap::init(false, false);
+    let score = |score: &str| Score::parse(score.as_bytes()).unwrap();
+    let members = [
+        ("a".into(), score("1")),
+        ("b".into(), score("2")),
+        ("c".into(), score("2")),
+        ("d".into(), score("-0")),
+    ];
+    assert_eq!(zsets.add_scored(b"z", &members, |_| {}), 4);
+    // rescoring isn't an addition, but it's still logged
+    let mut logged = 0;
+    assert_eq!(
+        zsets.add_scored(b"z", &[("d".into(), score("3"))], |m| logged += m.len()),
+        0
+    );
+    assert_eq!(logged, 1);
+    assert_eq!(zsets.rank(b"z", b"a", false), Some(0));
+    assert_eq!(zsets.rank(b"z", b"c", false), Some(2));
+    assert_eq!(zsets.rank(b"z", b"d", true), Some(0));
+    let range = |start, end, limit, reverse| -> Vec<SharedSlice> {
+        zsets
+            .range_by_score(b"z", (start, end), limit, reverse)
+            .unwrap()
+            .into_iter()
+            .map(|(member, _)| member)
+            .collect()
+    };
+    let members = |members: &[&str]| -> Vec<SharedSlice> {
+        members.iter().map(|&member| member.into()).collect()
+    };
+    assert_eq!(
+        range(
+            Bound::Included(score("2")),
+            Bound::Unbounded,
+            usize::MAX,
+            false
+        ),
+        members(&["b", "c", "d"])
+    );
+    assert_eq!(
+        range(
+            Bound::Excluded(score("1")),
+            Bound::Excluded(score("3")),
+            usize::MAX,
+            false
+        ),
+        members(&["b", "c"])
+    );
+    assert_eq!(
+        range(Bound::Unbounded, Bound::Unbounded, 2, true),
+        members(&["d", "c"])
+    );
+    // an empty range
+    assert!(range(
+        Bound::Excluded(score("2")),
+        Bound::Included(score("2")),
+        1,
+        false
+    )
+    .is_empty());
+    assert!(score("0") == score("-0") && Score::parse(b"inf").is_none());
+    assert_eq!(
+        zsets.remove_members(b"z", &[b"a", b"b", b"c", b"d"], |_| {}),
+        4
+    );
+    assert_eq!(zsets.len(), 0);
+}
+
+#[test]
+fn test_queue_reservations() {
+    let queues = KVEQueuemap::init(false, false);
+    let mut logged = 0;
+    let items = ["a".into(), "b".into(), "c".into()];
+    assert_eq!(queues.push_items(b"q", &items, |_| logged += 1), 3);
+    let (a, item) = queues.reserve(b"q", 100, |_| logged += 1).unwrap();
+    assert_eq!(item, "a");
+    let (b, _) = queues.reserve(b"q", 100, |_| logged += 1).unwrap();
+    assert_eq!(queues.counts(b"q"), Some((1, 2)));
+    // a given up item is the next one to be reserved
+    assert!(queues.nack(b"q", b, |_| logged += 1));
+    assert!(!queues.ack(b"q", b, |_| panic!("logged")));
+    let (b, item) = queues.reserve(b"q", 100, |_| logged += 1).unwrap();
+    assert_eq!(item, "b");
+    assert!(queues.ack(b"q", a, |_| logged += 1));
+    assert!(queues.ack(b"q", b, |_| logged += 1));
+    assert_eq!(logged, 7);
+    // IDs aren't reused and the queue stays around when it's empty
+    let (c, item) = queues.reserve(b"q", 100, |_| {}).unwrap();
+    assert_eq!(item, "c");
+    assert!(c > b);
+    assert!(queues.ack(b"q", c, |_| {}));
+    assert!(queues.reserve(b"q", 100, |_| panic!("logged")).is_none());
+    assert_eq!(queues.counts(b"q"), Some((0, 0)));
+    assert!(queues
+        .reserve(b"nosuchqueue", 100, |_| panic!("logged"))
+        .is_none());
+}
+
+#[test]
+fn test_queue_visibility_timeout() {
+    let queues = KVEQueuemap::init(false, false);
+    queues.push_items(b"q", &["a".into(), "b".into()], |_| {});
+    // a zero timeout runs out right away
+    let (a, _) = queues.reserve(b"q", 0, |_| {}).unwrap();
+    assert_eq!(queues.counts(b"q"), Some((2, 0)));
+    // the item goes back to the front of the queue, so it can't be acknowledged anymore
+    let (_, item) = queues
+        .reserve(b"q", 100, |queue| {
+            assert!(queue
+                .reserved()
+                .values()
+                .all(|reservation| reservation.item == "a"));
+        })
+        .unwrap();
+    assert_eq!(item, "a");
+    assert!(!queues.ack(b"q", a, |_| panic!("logged")));
+}
+
+#[test]
+fn test_typed_rejects_non_canonical() {
+    let tbl = KVEStandard::init_typed(FieldType::UInt64, FieldType::SInt64);
+    assert!(tbl.set("1".into(), "-7".into()).unwrap());
+    // not numbers
+    assert!(tbl.set("one".into(), "1".into()).is_err());
+    assert!(tbl.set("2".into(), "seven".into()).is_err());
+    // numbers, but not in canonical form
+    assert!(tbl.set("02".into(), "1".into()).is_err());
+    assert!(tbl.set("2".into(), "+7".into()).is_err());
+    // out of range
+    assert!(tbl.set("-2".into(), "1".into()).is_err());
+    assert!(tbl.set("2".into(), "9223372036854775808".into()).is_err());
+    let tbl = KVEStandard::init_typed(FieldType::String, FieldType::Float64);
+    assert!(tbl.set("pi".into(), "3.14".into()).unwrap());
+    // floats can be in any form, as long as they're finite
+    assert!(tbl.set("e".into(), "2.710".into()).unwrap());
+    assert!(tbl.set("big".into(), "1e300".into()).unwrap());
+    assert!(tbl.set("kilo".into(), "1.5E3".into()).unwrap());
+    assert!(tbl.set("inf".into(), "inf".into()).is_err());
+    assert!(tbl.set("nan".into(), "NaN".into()).is_err());
+    assert!(tbl.set("huge".into(), "1e400".into()).is_err());
+}
+
+#[test]
+fn test_incr_by() {
+    let tbl = KVEStandard::init_typed(FieldType::String, FieldType::SInt64);
+    // missing keys start at zero
+    assert_eq!(tbl.incr_by("ctr".into(), b"1").unwrap().unwrap(), "1");
+    assert_eq!(tbl.incr_by("ctr".into(), b"-10").unwrap().unwrap(), "-9");
+    assert_eq!(tbl.get_cloned("ctr").unwrap().unwrap(), "-9");
+    assert_eq!(
+        tbl.incr_by("ctr".into(), b"ten").unwrap(),
+        Err(NumError::BadDelta)
+    );
+    tbl.upsert("max".into(), i64::MAX.to_string().into())
+        .unwrap();
+    assert_eq!(
+        tbl.incr_by("max".into(), b"1").unwrap(),
+        Err(NumError::Overflow)
+    );
+    // the value is left untouched on errors
+    assert_eq!(
+        tbl.get_cloned("max").unwrap().unwrap(),
+        i64::MAX.to_string()
+    );
+}
+
+#[test]
+fn test_incr_by_uint64_below_zero() {
+    let tbl = KVEStandard::init_typed(FieldType::String, FieldType::UInt64);
+    assert_eq!(tbl.incr_by("ctr".into(), b"2").unwrap().unwrap(), "2");
+    assert_eq!(tbl.incr_by("ctr".into(), b"-2").unwrap().unwrap(), "0");
+    assert_eq!(
+        tbl.incr_by("ctr".into(), b"-1").unwrap(),
+        Err(NumError::Overflow)
+    );
+}
+
+#[test]
+fn test_incr_by_float64() {
+    let tbl = KVEStandard::init_typed(FieldType::String, FieldType::Float64);
+    assert_eq!(tbl.incr_by("x".into(), b"1.5").unwrap().unwrap(), "1.5");
+    assert_eq!(tbl.incr_by("x".into(), b"-0.5").unwrap().unwrap(), "1");
+    assert_eq!(
+        tbl.incr_by("x".into(), b"1e308").unwrap().unwrap(),
+        (1e308f64 + 1.0).to_string()
+    );
+    assert_eq!(
+        tbl.incr_by("x".into(), b"1e308").unwrap(),
+        Err(NumError::Overflow)
+    );
+    for delta in [&b"NaN"[..], b"inf", b"-inf", b"1e400"] {
+        assert_eq!(
+            tbl.incr_by("x".into(), delta).unwrap(),
+            Err(NumError::BadDelta)
+        );
+    }
+    // stored values don't have to be in canonical form either
+    tbl.upsert("y".into(), "1.5E3".into()).unwrap();
+    assert_eq!(tbl.incr_by("y".into(), b"1").unwrap().unwrap(), "1501");
+}
+
+#[test]
+fn test_incr_by_keeps_ttl() {
+    let tbl = KVEStandard::init_typed(FieldType::String, FieldType::UInt64);
+    tbl.incr_by("ctr".into(), b"1").unwrap().unwrap();
+    assert!(tbl.expire("ctr", 100).unwrap());
+    tbl.incr_by("ctr".into(), b"1").unwrap().unwrap();
+    assert!(tbl.ttl("ctr").unwrap().unwrap().is_some());
+}
+
+#[test]
+fn test_incr_by_is_atomic() {
+    use std::{sync::Arc, thread};
+    let tbl = Arc::new(KVEStandard::init_typed(
+        FieldType::String,
+        FieldType::UInt64,
+    ));
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let tbl = tbl.clone();
+            thread::spawn(move || {
+                for _ in 0..1000 {
+                    tbl.incr_by("ctr".into(), b"1").unwrap().unwrap();
+                }
+            })
+        })
+        .collect();
+    handles.into_iter().for_each(|h| h.join().unwrap());
+    assert_eq!(tbl.get_cloned("ctr").unwrap().unwrap(), "8000");
+}
+
+#[test]
+fn test_edit_keeps_ttl() {
+    let tbl = KVEStandard::default();
+    let appended = |old: &[u8]| Ok::<_, ()>((SharedSlice::from([old, b"!"].concat()), old.len()));
+    // a key that doesn't exist starts out empty
+    assert_eq!(tbl.edit_unchecked("x".into(), appended), Ok(0));
+    assert!(tbl.expire("x", 100).unwrap());
+    assert_eq!(tbl.edit_unchecked("x".into(), appended), Ok(1));
+    assert_eq!(tbl.get_cloned("x").unwrap().unwrap(), "!!");
+    assert!(tbl.ttl("x").unwrap().unwrap().is_some());
+    // a failed edit changes nothing
+    assert_eq!(
+        tbl.edit_unchecked("x".into(), |_| Err::<(SharedSlice, ()), _>(())),
+        Err(())
+    );
+    assert_eq!(tbl.get_cloned("x").unwrap().unwrap(), "!!");
+    assert_eq!(
+        tbl.edit_unchecked("y".into(), |_| Err::<(SharedSlice, ()), _>(())),
+        Err(())
+    );
+    assert!(!tbl.exists("y").unwrap());
+}
+
+/// Watch `key`, run `f` and return the events that the watcher got
+fn watch_events(tbl: &KVEStandard, key: &[u8], f: impl FnOnce()) -> Vec<String> {
+    let mut watcher = Subscriptions::new();
+    watcher.subscribe(Kind::Key, tbl.get_watchers(), key);
+    f();
+    let mut events = vec![];
+    Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(async {
+            while let Ok((_, _, event)) =
+                time::timeout(Duration::from_millis(10), watcher.recv()).await
+            {
+                events.push(String::from_utf8_lossy(&event).to_string());
+            }
+        });
+    events
+}
+
+#[test]
+fn test_watch_events() {
+    let tbl = KVEStandard::init_typed(FieldType::String, FieldType::UInt64);
+    let events = watch_events(&tbl, b"hello", || {
+        tbl.set("hello".into(), "1".into()).unwrap();
+        // a key that isn't watched
+        tbl.set("other".into(), "1".into()).unwrap();
+        // nothing happened
+        tbl.set("hello".into(), "2".into()).unwrap();
+        tbl.update("hello".into(), "2".into()).unwrap();
+        tbl.incr_by("hello".into(), b"1").unwrap().unwrap();
+        tbl.remove("hello").unwrap();
+        tbl.remove("hello").unwrap();
+        tbl.incr_by("hello".into(), b"1").unwrap().unwrap();
+        tbl.pop("hello").unwrap();
+        tbl.upsert("hello".into(), "1".into()).unwrap();
+        tbl.truncate_table();
+    });
+    assert_eq!(
+        events,
+        ["set", "update", "update", "del", "set", "del", "set", "del"]
+    );
+}
+
+#[test]
+fn test_watch_expiry() {
+    let tbl = KVEStandard::default();
+    let events = watch_events(&tbl, b"hello", || {
+        tbl.set_with_ttl("hello".into(), "world".into(), 0).unwrap();
+        assert!(!tbl.exists("hello").unwrap());
+        tbl.set_with_ttl("hello".into(), "world".into(), 0).unwrap();
+        assert_eq!(tbl.purge_expired(), 1);
+    });
+    assert_eq!(events, ["set", "expired", "set", "expired"]);
+}
+
+fn tracked(policy: EvictionPolicy) -> KVEStandard {
+    let mut tbl = KVEStandard::default();
+    tbl.track_memory(policy);
+    tbl
+}
+
+#[test]
+fn test_memory_accounting() {
+    let _lock = MEMORY_TESTS.lock().unwrap_or_else(PoisonError::into_inner);
+    let used = registry::used_memory().load(Ordering::Acquire);
+    let tbl = tracked(EvictionPolicy::NoEviction);
+    tbl.set("a".into(), "hello".into()).unwrap();
+    tbl.set("bb".into(), "world!".into()).unwrap();
+    // changes are measured once the write is done
+    assert_eq!(tbl.memory_usage(), Some(0));
+    tbl.settle_memory();
+    assert_eq!(tbl.memory_usage(), Some(1 + 5 + 2 + 6));
+    assert_eq!(registry::used_memory().load(Ordering::Acquire), used + 14);
+    tbl.update("a".into(), "hi".into()).unwrap();
+    tbl.remove("bb").unwrap();
+    tbl.settle_memory();
+    assert_eq!(tbl.memory_usage(), Some(1 + 2));
+    tbl.set("c".into(), "3".into()).unwrap();
+    tbl.truncate_table();
+    tbl.settle_memory();
+    assert_eq!(tbl.memory_usage(), Some(0));
+    // dropping the table releases what it held
+    tbl.set("a".into(), "hello".into()).unwrap();
+    tbl.settle_memory();
+    drop(tbl);
+    assert_eq!(registry::used_memory().load(Ordering::Acquire), used);
+}
+
+#[test]
+fn test_memory_accounting_lists() {
+    let _lock = MEMORY_TESTS.lock().unwrap_or_else(PoisonError::into_inner);
+    let mut tbl = KVEListmap::init(false, false);
+    tbl.set("list".into(), RwLock::new(vec!["a".into()]))
+        .unwrap();
+    // tables that hold data when tracking starts are measured right away
+    tbl.track_memory(EvictionPolicy::Lru);
+    assert_eq!(tbl.memory_usage(), Some(4 + 1));
+    // lists are changed in place
+    tbl.get_inner_ref()
+        .get("list".as_bytes())
+        .unwrap()
+        .write()
+        .extend(["bb".into(), "ccc".into()]);
+    tbl.notify(b"list", crate::pubsub::KeyEvent::Update);
+    tbl.settle_memory();
+    assert_eq!(tbl.memory_usage(), Some(4 + 1 + 2 + 3));
+}
+
+#[test]
+fn test_capped_lists() {
+    let mut tbl = KVEListmap::init(false, false);
+    let mut list: Vec<SharedSlice> = vec!["a".into(), "b".into(), "c".into()];
+    // lists aren't capped by default
+    tbl.cap(&mut list);
+    assert_eq!(list.len(), 3);
+    tbl.set_max_len(2);
+    assert_eq!(tbl.max_len(), Some(2));
+    // the first items are dropped
+    tbl.cap(&mut list);
+    assert_eq!(list, vec![SharedSlice::from("b"), SharedSlice::from("c")]);
+    // shorter lists are left alone
+    list.truncate(1);
+    tbl.cap(&mut list);
+    assert_eq!(list, vec![SharedSlice::from("b")]);
+}
+
+#[test]
+fn test_untracked_table() {
+    let tbl = KVEStandard::default();
+    tbl.set("a".into(), "hello".into()).unwrap();
+    tbl.settle_memory();
+    assert_eq!(tbl.memory_usage(), None);
+    assert_eq!(tbl.eviction_policy(), None);
+    assert!(tbl.make_room());
+}
+
+/// Fill a table with `old` and `new` (in that order), then limit the memory so that only one
+/// of them fits
+fn fill_over_limit(tbl: &KVEStandard, touch: impl FnOnce()) {
+    tbl.set("old".into(), "value".into()).unwrap();
+    thread::sleep(Duration::from_millis(5));
+    tbl.set("new".into(), "value".into()).unwrap();
+    tbl.settle_memory();
+    thread::sleep(Duration::from_millis(5));
+    touch();
+    registry::set_max_memory(registry::used_memory().load(Ordering::Acquire) - 1);
+}
+
+#[test]
+fn test_eviction_policies() {
+    let _lock = MEMORY_TESTS.lock().unwrap_or_else(PoisonError::into_inner);
+    let evicted = || registry::evicted_keys().load(Ordering::Acquire);
+    // the least recently used key goes
+    let tbl = tracked(EvictionPolicy::Lru);
+    fill_over_limit(&tbl, || {
+        tbl.get("old").unwrap();
+    });
+    let before = evicted();
+    assert!(tbl.make_room());
+    assert!(tbl.exists("old").unwrap() && !tbl.exists("new").unwrap());
+    assert_eq!(evicted(), before + 1);
+    drop(tbl);
+    // the least frequently used key goes
+    let tbl = tracked(EvictionPolicy::Lfu);
+    fill_over_limit(&tbl, || {
+        (0..3).for_each(|_| {
+            tbl.get("old").unwrap();
+        });
+        tbl.get("new").unwrap();
+    });
+    assert!(tbl.make_room());
+    assert!(tbl.exists("old").unwrap() && !tbl.exists("new").unwrap());
+    drop(tbl);
+    // any key goes
+    let tbl = tracked(EvictionPolicy::Random);
+    fill_over_limit(&tbl, || {});
+    assert!(tbl.make_room());
+    assert_eq!(tbl.len(), 1);
+    drop(tbl);
+    // nothing goes
+    let tbl = tracked(EvictionPolicy::NoEviction);
+    fill_over_limit(&tbl, || {});
+    let before = evicted();
+    assert!(!tbl.make_room());
+    assert_eq!(tbl.len(), 2);
+    assert_eq!(evicted(), before);
+    // and a table can't make room for what other tables hold
+    let other = tracked(EvictionPolicy::Lru);
+    assert!(!other.make_room());
+    drop(tbl);
+    assert!(other.make_room());
+    registry::set_max_memory(0);
+}
+
+#[test]
+fn test_watch_eviction() {
+    let _lock = MEMORY_TESTS.lock().unwrap_or_else(PoisonError::into_inner);
+    let tbl = tracked(EvictionPolicy::Random);
+    let events = watch_events(&tbl, b"hello", || {
+        tbl.set("hello".into(), "world".into()).unwrap();
+        tbl.settle_memory();
+        registry::set_max_memory(registry::used_memory().load(Ordering::Acquire) - 1);
+        assert!(tbl.make_room());
+        registry::set_max_memory(0);
+    });
+    assert_eq!(events, ["set", "evicted"]);
+}
@@ -0,0 +1,226 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Types
+//!
+//! The types that the keys and values of a model can have. Every value is held as bytes:
+//! strings are checked to be valid UTF-8 while integers are kept in their canonical text form
+//! (`42`, `-7`), so that actions can hand them out just like strings. Integers that aren't in
+//! canonical form (`042` or `+7`) are rejected, which also means that two spellings of the same
+//! integer can never end up as two different keys. Floats can be written in any form that parses
+//! to a finite double (`1.5`, `1.5E3`, `1e300`), but since only the number is kept on disk they
+//! come back in canonical form after a restart.
+//!
+//! On disk, numbers are stored natively: as 8-byte little-endian integers or IEEE 754 doubles
+
+use {
+    super::{
+        encoding::{is_okay_encoded, is_okay_no_encoding},
+        SingleEncoder, TSYMBOL_LUT,
+    },
+    crate::corestore::SharedSlice,
+    core::str::{self, FromStr},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+/// The type of a key, a value or a field
+pub enum FieldType {
+    Binary = 0,
+    String = 1,
+    UInt64 = 2,
+    SInt64 = 3,
+    Float64 = 4,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+/// Errors while doing arithmetic on a stored number
+pub enum NumError {
+    /// The value isn't a number
+    NotNumeric,
+    /// The delta isn't a valid number for the type of the value
+    BadDelta,
+    /// The result doesn't fit into the type of the value
+    Overflow,
+}
+
+impl FieldType {
+    /// Get a type from its storage code
+    pub const fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(Self::Binary),
+            1 => Some(Self::String),
+            2 => Some(Self::UInt64),
+            3 => Some(Self::SInt64),
+            4 => Some(Self::Float64),
+            _ => None,
+        }
+    }
+    /// Get the type of a key or value that is either a string (`encoded`) or binary
+    pub const fn from_encoding(encoded: bool) -> Self {
+        if encoded {
+            Self::String
+        } else {
+            Self::Binary
+        }
+    }
+    /// Returns the storage code for this type
+    pub const fn code(self) -> u8 {
+        self as u8
+    }
+    /// Returns the name used to describe this type
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Binary => "binstr",
+            Self::String => "str",
+            Self::UInt64 => "uint64",
+            Self::SInt64 => "sint64",
+            Self::Float64 => "float64",
+        }
+    }
+    pub const fn is_numeric(self) -> bool {
+        matches!(self, Self::UInt64 | Self::SInt64 | Self::Float64)
+    }
+    /// Values of every type but binary are valid UTF-8
+    pub const fn is_encoded(self) -> bool {
+        !matches!(self, Self::Binary)
+    }
+    /// Returns a function that checks if a value can be stored in a field of this type
+    pub const fn checker(self) -> SingleEncoder {
+        match self {
+            Self::Binary => is_okay_no_encoding,
+            Self::String => is_okay_encoded,
+            Self::UInt64 => is_canonical_uint64,
+            Self::SInt64 => is_canonical_sint64,
+            Self::Float64 => is_finite_float64,
+        }
+    }
+    /// Check if the value can be stored in a field of this type
+    pub fn is_ok(self, val: &[u8]) -> bool {
+        self.checker()(val)
+    }
+    /// Returns the tsymbol used when a value of this type is sent to the client
+    pub fn tsymbol(self) -> u8 {
+        TSYMBOL_LUT[self.is_encoded()]
+    }
+    /// Returns the on-disk form of a number. Returns `None` if this isn't a numeric type or
+    /// if the value isn't a number
+    pub fn to_native(self, val: &[u8]) -> Option<[u8; 8]> {
+        match self {
+            Self::UInt64 => parse_canonical::<u64>(val).map(u64::to_le_bytes),
+            Self::SInt64 => parse_canonical::<i64>(val).map(i64::to_le_bytes),
+            Self::Float64 => parse_float(val).map(f64::to_le_bytes),
+            Self::Binary | Self::String => None,
+        }
+    }
+    /// Returns the number for its on-disk form. This is the inverse of [`FieldType::to_native`]
+    pub fn parse_native(self, native: &[u8]) -> Option<SharedSlice> {
+        let native: [u8; 8] = native.try_into().ok()?;
+        let ret = match self {
+            Self::UInt64 => u64::from_le_bytes(native).to_string(),
+            Self::SInt64 => i64::from_le_bytes(native).to_string(),
+            Self::Float64 => {
+                let float = f64::from_le_bytes(native);
+                if !float.is_finite() {
+                    return None;
+                }
+                float.to_string()
+            }
+            Self::Binary | Self::String => return None,
+        };
+        Some(SharedSlice::from(ret))
+    }
+    /// Add `delta` to `val` and return the result in canonical form. `val` must be a number
+    /// of this type and `delta` can be negative (even for `uint64`)
+    pub fn add(self, val: &[u8], delta: &[u8]) -> Result<String, NumError> {
+        match self {
+            Self::UInt64 => {
+                let val = parse_canonical::<u64>(val).ok_or(NumError::NotNumeric)?;
+                let delta = parse_delta::<i64>(delta)?;
+                val.checked_add_signed(delta)
+                    .map(|v| v.to_string())
+                    .ok_or(NumError::Overflow)
+            }
+            Self::SInt64 => {
+                let val = parse_canonical::<i64>(val).ok_or(NumError::NotNumeric)?;
+                let delta = parse_delta::<i64>(delta)?;
+                val.checked_add(delta)
+                    .map(|v| v.to_string())
+                    .ok_or(NumError::Overflow)
+            }
+            Self::Float64 => {
+                let val = parse_float(val).ok_or(NumError::NotNumeric)?;
+                let delta = parse_delta::<f64>(delta)?;
+                if !delta.is_finite() {
+                    return Err(NumError::BadDelta);
+                }
+                let ret = val + delta;
+                if ret.is_finite() {
+                    Ok(ret.to_string())
+                } else {
+                    Err(NumError::Overflow)
+                }
+            }
+            Self::Binary | Self::String => Err(NumError::NotNumeric),
+        }
+    }
+}
+
+/// Parse a number that must be in canonical form
+fn parse_canonical<T: FromStr + ToString>(val: &[u8]) -> Option<T> {
+    let string = str::from_utf8(val).ok()?;
+    let num = string.parse::<T>().ok()?;
+    (num.to_string() == string).then_some(num)
+}
+
+/// Parse a float, which can be in any form as long as it's finite
+fn parse_float(val: &[u8]) -> Option<f64> {
+    str::from_utf8(val)
+        .ok()?
+        .parse::<f64>()
+        .ok()
+        .filter(|float| float.is_finite())
+}
+
+/// Parse a delta. Unlike stored values, deltas don't have to be in canonical form
+fn parse_delta<T: FromStr>(delta: &[u8]) -> Result<T, NumError> {
+    str::from_utf8(delta)
+        .ok()
+        .and_then(|delta| delta.parse().ok())
+        .ok_or(NumError::BadDelta)
+}
+
+fn is_canonical_uint64(val: &[u8]) -> bool {
+    parse_canonical::<u64>(val).is_some()
+}
+
+fn is_canonical_sint64(val: &[u8]) -> bool {
+    parse_canonical::<i64>(val).is_some()
+}
+
+fn is_finite_float64(val: &[u8]) -> bool {
+    parse_float(val).is_some()
+}
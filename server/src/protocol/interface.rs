@@ -107,6 +107,14 @@ pub trait ProtocolSpec: Send + Sync {
     const RSTRING_SNAPSHOT_DUPLICATE: &'static [u8];
     /// Respstring when snapshot has illegal chars
     const RSTRING_SNAPSHOT_ILLEGAL_NAME: &'static [u8];
+    /// Respstring when a snapshot doesn't exist
+    const RSTRING_SNAPSHOT_NOT_FOUND: &'static [u8];
+    /// Respstring when an archive path leaves the archive directory
+    const RSTRING_ARCHIVE_ILLEGAL_PATH: &'static [u8];
+    /// Respstring when an archive doesn't exist
+    const RSTRING_ARCHIVE_NOT_FOUND: &'static [u8];
+    /// Respstring when an archive is corrupted or holds the wrong kind of object
+    const RSTRING_BAD_ARCHIVE: &'static [u8];
     /// Respstring when a **very bad error** happens (use after termsig)
     const RSTRING_ERR_ACCESS_AFTER_TERMSIG: &'static [u8];
     /// Respstring when the default container is unset
@@ -150,6 +158,26 @@ pub trait ProtocolSpec: Send + Sync {
     const RSTRING_LISTMAP_BAD_INDEX: &'static [u8];
     /// Respstring when a list is empty and we attempt to access/modify it
     const RSTRING_LISTMAP_LIST_IS_EMPTY: &'static [u8];
+    /// Respstring when a key exists but has no TTL set
+    const RSTRING_NO_EXPIRY: &'static [u8];
+    /// Respstring when the result of an arithmetic operation doesn't fit into the type of
+    /// the value, or when a value would grow past the longest that a value can be
+    const RSTRING_VALUE_OVERFLOW: &'static [u8];
+    /// Respstring when a write is attempted on a follower
+    const RSTRING_READ_ONLY_FOLLOWER: &'static [u8];
+    /// Respstring when a connection in push mode runs anything but a change to its subscriptions
+    /// or watched keys
+    const RSTRING_SUBSCRIBED: &'static [u8];
+    /// Respstring when a transaction is aborted because a precondition didn't hold or one of its
+    /// statements couldn't be applied
+    const RSTRING_TXN_ABORTED: &'static [u8];
+    /// Respstring when `EXEC`, `DISCARD` or `CHECK` are run outside a transaction
+    const RSTRING_NOT_IN_TXN: &'static [u8];
+    /// Respstring when an action can't be queued in a transaction
+    const RSTRING_TXN_UNSUPPORTED: &'static [u8];
+    /// Respstring when a write to a volatile model is refused because volatile models are
+    /// over the memory limit and the model can't evict keys
+    const RSTRING_OUT_OF_MEMORY: &'static [u8];
 
     // element responses
     /// A string element containing the text "HEY!"
@@ -203,6 +231,8 @@ pub trait ProtocolSpec: Send + Sync {
     const AUTH_ERROR_ILLEGAL_USERNAME: &'static [u8];
     /// respstring: ID is protected/in use
     const AUTH_ERROR_FAILED_TO_DELETE_USER: &'static [u8];
+    /// respstring: Unknown role, or a role that can't be granted in this scope
+    const AUTH_ERROR_ILLEGAL_GRANT: &'static [u8];
 
     // BlueQL respstrings
     const BQL_BAD_EXPRESSION: &'static [u8];
@@ -214,6 +244,7 @@ pub trait ProtocolSpec: Send + Sync {
     const BQL_UNKNOWN_CREATE_QUERY: &'static [u8];
     const BQL_UNSUPPORTED_MODEL_DECL: &'static [u8];
     const BQL_UNEXPECTED_CHAR: &'static [u8];
+    const BQL_UNKNOWN_FIELD: &'static [u8];
 
     /// The body is terminated by a linefeed
     const NEEDS_TERMINAL_LF: bool;
@@ -72,6 +72,10 @@ impl ProtocolSpec for Skyhash1 {
     const RSTRING_SNAPSHOT_DISABLED: &'static [u8] = eresp!("err-snapshot-disabled");
     const RSTRING_SNAPSHOT_DUPLICATE: &'static [u8] = eresp!("duplicate-snapshot");
     const RSTRING_SNAPSHOT_ILLEGAL_NAME: &'static [u8] = eresp!("err-invalid-snapshot-name");
+    const RSTRING_SNAPSHOT_NOT_FOUND: &'static [u8] = eresp!("err-snapshot-not-found");
+    const RSTRING_ARCHIVE_ILLEGAL_PATH: &'static [u8] = eresp!("err-invalid-archive-path");
+    const RSTRING_ARCHIVE_NOT_FOUND: &'static [u8] = eresp!("err-archive-not-found");
+    const RSTRING_BAD_ARCHIVE: &'static [u8] = eresp!("err-bad-archive");
     const RSTRING_ERR_ACCESS_AFTER_TERMSIG: &'static [u8] = eresp!("err-access-after-termsig");
 
     // keyspace related resps
@@ -95,6 +99,14 @@ impl ProtocolSpec for Skyhash1 {
     const RSTRING_BAD_TYPE_FOR_KEY: &'static [u8] = eresp!("bad-type-for-key");
     const RSTRING_LISTMAP_BAD_INDEX: &'static [u8] = eresp!("bad-list-index");
     const RSTRING_LISTMAP_LIST_IS_EMPTY: &'static [u8] = eresp!("list-is-empty");
+    const RSTRING_NO_EXPIRY: &'static [u8] = eresp!("no-expiry");
+    const RSTRING_VALUE_OVERFLOW: &'static [u8] = eresp!("value-overflow");
+    const RSTRING_READ_ONLY_FOLLOWER: &'static [u8] = eresp!("err-read-only-follower");
+    const RSTRING_SUBSCRIBED: &'static [u8] = eresp!("err-subscribed");
+    const RSTRING_TXN_ABORTED: &'static [u8] = eresp!("err-txn-aborted");
+    const RSTRING_NOT_IN_TXN: &'static [u8] = eresp!("err-not-in-txn");
+    const RSTRING_TXN_UNSUPPORTED: &'static [u8] = eresp!("err-txn-unsupported");
+    const RSTRING_OUT_OF_MEMORY: &'static [u8] = eresp!("err-out-of-memory");
 
     // elements
     const ELEMRESP_HEYA: &'static [u8] = b"+4\nHEY!\n";
@@ -110,6 +122,7 @@ impl ProtocolSpec for Skyhash1 {
     const AUTH_CODE_PERMS: &'static [u8] = eresp!("11");
     const AUTH_ERROR_ILLEGAL_USERNAME: &'static [u8] = eresp!("err-auth-illegal-username");
     const AUTH_ERROR_FAILED_TO_DELETE_USER: &'static [u8] = eresp!("err-auth-deluser-fail");
+    const AUTH_ERROR_ILLEGAL_GRANT: &'static [u8] = eresp!("err-auth-illegal-grant");
 
     // bql respstrings
     const BQL_BAD_EXPRESSION: &'static [u8] = eresp!("bql-bad-expression");
@@ -121,6 +134,7 @@ impl ProtocolSpec for Skyhash1 {
     const BQL_UNKNOWN_CREATE_QUERY: &'static [u8] = eresp!("bql-unknown-create-query");
     const BQL_UNSUPPORTED_MODEL_DECL: &'static [u8] = eresp!("bql-unsupported-model-decl");
     const BQL_UNEXPECTED_CHAR: &'static [u8] = eresp!("bql-unexpected-char");
+    const BQL_UNKNOWN_FIELD: &'static [u8] = eresp!("bql-unknown-field");
 
     const NEEDS_TERMINAL_LF: bool = true;
 
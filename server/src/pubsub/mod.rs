@@ -0,0 +1,281 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Pub/Sub
+//!
+//! Channels are created when the first connection subscribes to them and are removed when their
+//! last subscriber leaves. Every channel is a broadcast channel (just like the termination signal
+//! that listeners hand out to connections), so publishing is a single send no matter how many
+//! connections are listening. A subscriber that falls more than [`CHANNEL_CAPACITY`] messages
+//! behind skips the ones it missed.
+//!
+//! Watched keys work the same way: every table has its own [`PubSub`] registry where the channels
+//! are keys and the messages are [`KeyEvent`]s.
+//!
+//! Channels are not scoped to spaces and nothing published is ever stored or replicated
+
+use {
+    crate::corestore::{htable::Coremap, map::bref::Entry},
+    core::{
+        future::{self, Future},
+        pin::Pin,
+        sync::atomic::{AtomicUsize, Ordering},
+        task::Poll,
+    },
+    std::{collections::HashMap, sync::Arc},
+    tokio::sync::broadcast::{self, error::RecvError, Receiver, Sender},
+};
+
+#[cfg(test)]
+mod tests;
+
+/// The number of messages a channel buffers for its slowest subscriber
+pub const CHANNEL_CAPACITY: usize = 1024;
+
+/// A published message
+pub type Message = Arc<[u8]>;
+
+/// A change to a watched key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyEvent {
+    Set,
+    Update,
+    Del,
+    Expired,
+    Evicted,
+}
+
+impl KeyEvent {
+    pub const fn name(&self) -> &'static [u8] {
+        match self {
+            Self::Set => b"set",
+            Self::Update => b"update",
+            Self::Del => b"del",
+            Self::Expired => b"expired",
+            Self::Evicted => b"evicted",
+        }
+    }
+}
+
+/// A registry of channels. The server has one for pub/sub channels that is shared across all
+/// connections and every table has one for its watched keys
+#[derive(Debug, Default)]
+pub struct PubSub {
+    channels: Coremap<Box<[u8]>, Sender<Message>>,
+    /// the number of channels (so that writes to tables nobody watches stay cheap)
+    count: AtomicUsize,
+}
+
+impl PubSub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Returns the number of channels with atleast one subscriber
+    pub fn channel_count(&self) -> usize {
+        self.count.load(Ordering::Acquire)
+    }
+    /// Publish a message, returning the number of subscribers that will receive it
+    pub fn publish(&self, channel: &[u8], message: &[u8]) -> usize {
+        match self.channels.get(channel) {
+            Some(tx) => tx.send(message.into()).unwrap_or(0),
+            None => 0,
+        }
+    }
+    /// Notify the watchers of a key
+    pub fn notify(&self, key: &[u8], event: KeyEvent) {
+        if self.channel_count() != 0 {
+            self.publish(key, event.name());
+        }
+    }
+    /// Notify the watchers of every key
+    pub fn notify_all(&self, event: KeyEvent) {
+        if self.channel_count() != 0 {
+            self.channels.iter().for_each(|kv| {
+                let _ = kv.value().send(event.name().into());
+            });
+        }
+    }
+    fn subscribe(&self, channel: &[u8]) -> Receiver<Message> {
+        // the entry holds the lock, so a channel can't be released while we subscribe to it
+        match self.channels.entry(channel.into()) {
+            Entry::Occupied(oe) => oe.value().subscribe(),
+            Entry::Vacant(ve) => {
+                let (tx, rx) = broadcast::channel(CHANNEL_CAPACITY);
+                ve.insert(tx);
+                self.count.fetch_add(1, Ordering::Release);
+                rx
+            }
+        }
+    }
+    /// Remove the channel if nobody is subscribed to it anymore
+    fn release(&self, channel: &[u8]) {
+        if self
+            .channels
+            .remove_if(channel, |_, tx| tx.receiver_count() == 0)
+            .is_some()
+        {
+            self.count.fetch_sub(1, Ordering::Release);
+        }
+    }
+}
+
+/// What a subscription is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// A pub/sub channel
+    Channel,
+    /// A watched key
+    Key,
+}
+
+impl Kind {
+    /// The first element of the pushed array
+    pub const fn name(&self) -> &'static [u8] {
+        match self {
+            Self::Channel => b"message",
+            Self::Key => b"watch",
+        }
+    }
+}
+
+struct Subscription {
+    kind: Kind,
+    registry: Arc<PubSub>,
+    rx: Receiver<Message>,
+}
+
+/// A pushed message: the kind of subscription, the channel (or key) and the message
+pub type Push = (Kind, Box<[u8]>, Message);
+
+type NextMessage<'a> =
+    Pin<Box<dyn Future<Output = (Kind, &'a [u8], Result<Message, RecvError>)> + Send + 'a>>;
+
+/// The channels and keys that a connection is subscribed to. A connection with atleast one
+/// subscription is in push mode. Dropping this leaves all channels
+#[derive(Default)]
+pub struct Subscriptions {
+    /// subscriptions by registry (its address) and channel
+    channels: HashMap<(usize, Box<[u8]>), Subscription>,
+    /// the channel that is polled first (so that a busy channel can't starve the others)
+    turn: usize,
+}
+
+impl Subscriptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.channels.is_empty()
+    }
+    /// Returns the number of subscriptions of the given kind
+    pub fn count(&self, kind: Kind) -> usize {
+        self.channels
+            .values()
+            .filter(|sub| sub.kind == kind)
+            .count()
+    }
+    pub fn subscribe(&mut self, kind: Kind, registry: &Arc<PubSub>, channel: &[u8]) {
+        self.channels
+            .entry((Arc::as_ptr(registry) as usize, channel.into()))
+            .or_insert_with(|| Subscription {
+                kind,
+                registry: registry.clone(),
+                rx: registry.subscribe(channel),
+            });
+    }
+    pub fn unsubscribe(&mut self, registry: &Arc<PubSub>, channel: &[u8]) {
+        let id = (Arc::as_ptr(registry) as usize, channel.into());
+        if let Some(sub) = self.channels.remove(&id) {
+            Self::release(id, sub);
+        }
+    }
+    /// Leave all channels of the given kind
+    pub fn unsubscribe_all(&mut self, kind: Kind) {
+        let ids: Vec<_> = self
+            .channels
+            .iter()
+            .filter(|(_, sub)| sub.kind == kind)
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in ids {
+            if let Some(sub) = self.channels.remove(&id) {
+                Self::release(id, sub);
+            }
+        }
+    }
+    fn release((_, channel): (usize, Box<[u8]>), sub: Subscription) {
+        let Subscription { registry, rx, .. } = sub;
+        drop(rx);
+        registry.release(&channel);
+    }
+    /// Wait for the next message on any of the subscribed channels. This never returns if
+    /// there are no subscriptions, and is cancel safe
+    pub async fn recv(&mut self) -> Push {
+        loop {
+            let mut pending: Vec<NextMessage> = self
+                .channels
+                .iter_mut()
+                .map(|((_, channel), sub)| {
+                    let kind = sub.kind;
+                    let rx = &mut sub.rx;
+                    Box::pin(async move { (kind, channel.as_ref(), rx.recv().await) })
+                        as NextMessage
+                })
+                .collect();
+            if !pending.is_empty() {
+                let first = self.turn % pending.len();
+                pending.rotate_left(first);
+                self.turn = self.turn.wrapping_add(1);
+            }
+            let (kind, channel, message) = future::poll_fn(|cx| {
+                pending
+                    .iter_mut()
+                    .find_map(|next| match next.as_mut().poll(cx) {
+                        Poll::Ready(ret) => Some(ret),
+                        Poll::Pending => None,
+                    })
+                    .map_or(Poll::Pending, Poll::Ready)
+            })
+            .await;
+            match message {
+                Ok(message) => return (kind, channel.into(), message),
+                // skip whatever we missed
+                Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => {
+                    unreachable!("a channel is only released after its last subscriber leaves")
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Subscriptions {
+    fn drop(&mut self) {
+        for (id, sub) in self.channels.drain() {
+            Self::release(id, sub);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use {
+    super::{KeyEvent, Kind, PubSub, Subscriptions, CHANNEL_CAPACITY},
+    core::future::Future,
+    std::{sync::Arc, time::Duration},
+    tokio::{runtime::Builder, time},
+};
+
+fn block_on<F: Future>(f: F) -> F::Output {
+    Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(f)
+}
+
+#[test]
+fn publish_without_subscribers() {
+    let pubsub = Arc::new(PubSub::new());
+    assert_eq!(pubsub.publish(b"news", b"hello"), 0);
+    assert_eq!(pubsub.channel_count(), 0);
+}
+
+#[test]
+fn publish_fan_out() {
+    let pubsub = Arc::new(PubSub::new());
+    let (mut sub1, mut sub2) = (Subscriptions::new(), Subscriptions::new());
+    sub1.subscribe(Kind::Channel, &pubsub, b"news");
+    // subscribing twice is a no-op
+    sub1.subscribe(Kind::Channel, &pubsub, b"news");
+    sub2.subscribe(Kind::Channel, &pubsub, b"news");
+    sub2.subscribe(Kind::Channel, &pubsub, b"sports");
+    assert_eq!(
+        (sub1.count(Kind::Channel), sub2.count(Kind::Channel)),
+        (1, 2)
+    );
+    assert_eq!(pubsub.channel_count(), 2);
+    assert_eq!(pubsub.publish(b"news", b"hello"), 2);
+    assert_eq!(pubsub.publish(b"sports", b"goal"), 1);
+    block_on(async {
+        let (kind, channel, message) = sub1.recv().await;
+        assert_eq!(
+            (kind, channel.as_ref(), message.as_ref()),
+            (Kind::Channel, &b"news"[..], &b"hello"[..])
+        );
+        let mut got = vec![sub2.recv().await, sub2.recv().await];
+        got.sort_by(|a, b| a.1.cmp(&b.1));
+        assert_eq!(
+            got,
+            vec![
+                (Kind::Channel, b"news"[..].into(), b"hello"[..].into()),
+                (Kind::Channel, b"sports"[..].into(), b"goal"[..].into())
+            ]
+        );
+    });
+}
+
+#[test]
+fn unsubscribe_releases_channels() {
+    let pubsub = Arc::new(PubSub::new());
+    let (mut sub1, mut sub2) = (Subscriptions::new(), Subscriptions::new());
+    sub1.subscribe(Kind::Channel, &pubsub, b"news");
+    sub2.subscribe(Kind::Channel, &pubsub, b"news");
+    sub2.subscribe(Kind::Channel, &pubsub, b"sports");
+    sub1.unsubscribe(&pubsub, b"news");
+    // unsubscribing from a channel we never subscribed to is a no-op
+    sub1.unsubscribe(&pubsub, b"sports");
+    assert!(sub1.is_empty());
+    assert_eq!(pubsub.channel_count(), 2);
+    assert_eq!(pubsub.publish(b"news", b"hello"), 1);
+    sub2.unsubscribe_all(Kind::Channel);
+    assert!(sub2.is_empty());
+    assert_eq!(pubsub.channel_count(), 0);
+}
+
+#[test]
+fn recv_without_subscriptions_waits() {
+    let mut sub = Subscriptions::new();
+    block_on(async {
+        assert!(time::timeout(Duration::from_millis(10), sub.recv())
+            .await
+            .is_err());
+    });
+}
+
+#[test]
+fn lagging_subscriber_skips_messages() {
+    let pubsub = Arc::new(PubSub::new());
+    let mut sub = Subscriptions::new();
+    sub.subscribe(Kind::Channel, &pubsub, b"news");
+    for i in 0..=CHANNEL_CAPACITY {
+        pubsub.publish(b"news", i.to_string().as_bytes());
+    }
+    block_on(async {
+        // the first message was overwritten
+        let (_, _, message) = sub.recv().await;
+        assert_eq!(message.as_ref(), b"1");
+    });
+}
+
+#[test]
+fn dropping_subscriptions_releases_channels() {
+    let pubsub = Arc::new(PubSub::new());
+    let mut sub = Subscriptions::new();
+    sub.subscribe(Kind::Channel, &pubsub, b"news");
+    assert_eq!(pubsub.channel_count(), 1);
+    drop(sub);
+    assert_eq!(pubsub.channel_count(), 0);
+}
+
+#[test]
+fn channels_and_keys_are_separate() {
+    let (pubsub, watchers) = (Arc::new(PubSub::new()), Arc::new(PubSub::new()));
+    let mut sub = Subscriptions::new();
+    sub.subscribe(Kind::Channel, &pubsub, b"x");
+    sub.subscribe(Kind::Key, &watchers, b"x");
+    assert_eq!((sub.count(Kind::Channel), sub.count(Kind::Key)), (1, 1));
+    watchers.notify(b"x", KeyEvent::Update);
+    block_on(async {
+        let (kind, key, message) = sub.recv().await;
+        assert_eq!(kind, Kind::Key);
+        assert_eq!(
+            (key.as_ref(), message.as_ref()),
+            (&b"x"[..], &b"update"[..])
+        );
+    });
+    sub.unsubscribe_all(Kind::Key);
+    assert_eq!((sub.count(Kind::Channel), sub.count(Kind::Key)), (1, 0));
+    assert_eq!((pubsub.channel_count(), watchers.channel_count()), (1, 0));
+}
@@ -28,7 +28,9 @@
 
 use crate::{
     actions::{self, ActionError, ActionResult},
-    admin, auth, blueql,
+    admin,
+    auth::{self, grants::Access},
+    blueql,
     corestore::Corestore,
     dbnet::{prelude::*, BufferedSocketStream},
     protocol::{iter::AnyArrayIter, PipelinedQuery, SimpleQuery, UnsafeSlice},
@@ -38,9 +40,21 @@ pub type ActionIter<'a> = AnyArrayIter<'a>;
 
 const ACTION_AUTH: &[u8] = b"auth";
 
+/// The function that finds the keyspace an action's access is checked against
+/// (see [`scope`])
+macro_rules! scope_of {
+    () => {
+        self::scope::current
+    };
+    ($scope:ident) => {
+        self::scope::$scope
+    };
+}
+
 macro_rules! gen_constants_and_matches {
     (
-        $con:expr, $buf:ident, $db:ident, $($action:ident => $fns:path),*,
+        $con:expr, $buf:ident, $db:ident, $auth:ident,
+        $($action:ident [$access:ident $(, $scope:ident)?] => $fns:path),*,
         {$($action2:ident => $fns2:expr),*}
     ) => {
         mod tags {
@@ -57,18 +71,52 @@ macro_rules! gen_constants_and_matches {
         let first = first_slice.to_ascii_uppercase();
         match first.as_ref() {
             $(
-                tags::$action => $fns($db, $con, $buf).await?,
+                tags::$action => {
+                    let space = scope_of!($($scope)?)($db, &$buf);
+                    $auth.provider().ensure_access::<P>(space, Access::$access)?;
+                    actions::ensure_writable::<P>(Access::$access)?;
+                    $fns($db, $con, $buf).await?
+                }
             )*
             $(
                 tags::$action2 => $fns2.await?,
             )*
             _ => {
-                blueql::execute($db, $con, first_slice, $buf.len()).await?;
+                blueql::execute($db, $con, $auth.provider(), first_slice, $buf).await?;
             }
         }
     };
 }
 
+mod scope {
+    //! Actions are authorized against a keyspace: usually the current one, but some actions
+    //! can also be run on another entity
+
+    use crate::{blueql::Entity, corestore::Corestore, protocol::iter::AnyArrayIter};
+
+    /// The current keyspace
+    pub fn current<'a>(db: &'a Corestore, _: &AnyArrayIter<'_>) -> Option<&'a [u8]> {
+        db.get_ids().0.map(|ks| ks.as_slice())
+    }
+    /// The keyspace of a `space.model` entity passed as the first argument or the current
+    /// keyspace if there is no such argument
+    pub fn entity<'a>(db: &'a Corestore, iter: &'a AnyArrayIter<'a>) -> Option<&'a [u8]> {
+        match iter
+            .as_ref()
+            .next()
+            .map(|arg| (arg, Entity::from_slice(arg)))
+        {
+            Some((arg, Ok(Entity::Full(space, _)))) => {
+                // the lexer doesn't copy, so the space points into the argument
+                let space = unsafe { space.as_slice() };
+                let offset = space.as_ptr() as usize - arg.as_ptr() as usize;
+                Some(&arg[offset..offset + space.len()])
+            }
+            _ => self::current(db, iter),
+        }
+    }
+}
+
 action! {
     /// Execute queries for an anonymous user
     fn execute_simple_noauth(
@@ -110,34 +158,87 @@ async fn execute_stage<'a, P: ProtocolSpec, C: BufferedSocketStream>(
         // won't suddenly become invalid
         AnyArrayIter::new(buf.iter())
     };
+    if con.transaction().is_some() {
+        return actions::txn::execute_in_txn(db, con, auth, iter).await;
+    }
     {
         gen_constants_and_matches!(
-            con, iter, db,
-            GET => actions::get::get,
-            SET => actions::set::set,
-            UPDATE => actions::update::update,
-            DEL => actions::del::del,
-            HEYA => actions::heya::heya,
-            EXISTS => actions::exists::exists,
-            MSET => actions::mset::mset,
-            MGET => actions::mget::mget,
-            MUPDATE => actions::mupdate::mupdate,
-            SSET => actions::strong::sset,
-            SDEL => actions::strong::sdel,
-            SUPDATE => actions::strong::supdate,
-            DBSIZE => actions::dbsize::dbsize,
-            FLUSHDB => actions::flushdb::flushdb,
-            USET => actions::uset::uset,
-            KEYLEN => actions::keylen::keylen,
-            MKSNAP => admin::mksnap::mksnap,
-            LSKEYS => actions::lskeys::lskeys,
-            POP => actions::pop::pop,
-            MPOP => actions::mpop::mpop,
-            LSET => actions::lists::lset,
-            LGET => actions::lists::lget::lget,
-            LMOD => actions::lists::lmod::lmod,
-            WHEREAMI => actions::whereami::whereami,
-            SYS => admin::sys::sys,
+            con, iter, db, auth,
+            GET [Read] => actions::get::get,
+            SET [Write] => actions::set::set,
+            UPDATE [Write] => actions::update::update,
+            DEL [Write] => actions::del::del,
+            HEYA [Any] => actions::heya::heya,
+            EXISTS [Read] => actions::exists::exists,
+            MSET [Write] => actions::mset::mset,
+            MGET [Read] => actions::mget::mget,
+            MUPDATE [Write] => actions::mupdate::mupdate,
+            SSET [Write] => actions::strong::sset,
+            SDEL [Write] => actions::strong::sdel,
+            SUPDATE [Write] => actions::strong::supdate,
+            DBSIZE [Read, entity] => actions::dbsize::dbsize,
+            FLUSHDB [Admin] => actions::flushdb::flushdb,
+            USET [Write] => actions::uset::uset,
+            KEYLEN [Read] => actions::keylen::keylen,
+            STRLEN [Read] => actions::keylen::keylen,
+            APPEND [Write] => actions::binstr::append,
+            GETRANGE [Read] => actions::binstr::getrange,
+            SETRANGE [Write] => actions::binstr::setrange,
+            GETBIT [Read] => actions::binstr::getbit,
+            SETBIT [Write] => actions::binstr::setbit,
+            BITCOUNT [Read] => actions::binstr::bitcount,
+            MKSNAP [Admin] => admin::mksnap::mksnap,
+            LSSNAP [Admin] => admin::snapshots::lssnap,
+            RMSNAP [Admin] => admin::snapshots::rmsnap,
+            RESTORESNAP [Admin] => admin::snapshots::restoresnap,
+            LSKEYS [Read, entity] => actions::lskeys::lskeys,
+            SCAN [Read] => actions::scan::scan,
+            RANGE [Read] => actions::range::range,
+            POP [Write] => actions::pop::pop,
+            MPOP [Write] => actions::mpop::mpop,
+            LSET [Write] => actions::lists::lset,
+            LGET [Read] => actions::lists::lget::lget,
+            LMOD [Write] => actions::lists::lmod::lmod,
+            BLPOP [Write] => actions::lists::blpop::blpop,
+            HSET [Write] => actions::maps::hset,
+            HGET [Read] => actions::maps::hget,
+            HDEL [Write] => actions::maps::hdel,
+            HGETALL [Read] => actions::maps::hgetall,
+            HLEN [Read] => actions::maps::hlen,
+            SADD [Write] => actions::sets::sadd,
+            SREM [Write] => actions::sets::srem,
+            SISMEMBER [Read] => actions::sets::sismember,
+            SCARD [Read] => actions::sets::scard,
+            SMEMBERS [Read] => actions::sets::smembers,
+            ZADD [Write] => actions::sets::zadd,
+            ZREM [Write] => actions::sets::zrem,
+            ZSCORE [Read] => actions::sets::zscore,
+            ZCARD [Read] => actions::sets::zcard,
+            ZRANK [Read] => actions::sets::zrank,
+            ZRANGEBYSCORE [Read] => actions::sets::zrangebyscore,
+            QPUSH [Write] => actions::queue::qpush,
+            QRESERVE [Write] => actions::queue::qreserve,
+            QACK [Write] => actions::queue::qack,
+            QNACK [Write] => actions::queue::qnack,
+            QLEN [Read] => actions::queue::qlen,
+            WHEREAMI [Any] => actions::whereami::whereami,
+            SYS [Any] => admin::sys::sys,
+            EXPIRE [Write] => actions::expire::expire,
+            TTL [Read] => actions::ttl::ttl,
+            PERSIST [Write] => actions::persist::persist,
+            INCR [Write] => actions::incr::incr,
+            DECR [Write] => actions::incr::decr,
+            INCRBY [Write] => actions::incr::incrby,
+            SYNC [Admin] => admin::sync::sync,
+            SUBSCRIBE [Any] => actions::pubsub::subscribe,
+            UNSUBSCRIBE [Any] => actions::pubsub::unsubscribe,
+            PUBLISH [Any] => actions::pubsub::publish,
+            WATCH [Read] => actions::watch::watch,
+            UNWATCH [Any] => actions::watch::unwatch,
+            MULTI [Any] => actions::txn::multi,
+            EXEC [Any] => actions::txn::not_in_txn,
+            DISCARD [Any] => actions::txn::not_in_txn,
+            CHECK [Any] => actions::txn::not_in_txn,
             {
                 // actions that need other arguments
                 AUTH => auth::auth(con, auth, iter)
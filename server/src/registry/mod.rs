@@ -31,7 +31,7 @@
 
 use {
     crate::corestore::lock::{QLGuard, QuickLock},
-    core::sync::atomic::{AtomicBool, Ordering},
+    core::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, AtomicUsize, Ordering},
 };
 
 const ORD_ACQ: Ordering = Ordering::Acquire;
@@ -81,6 +81,51 @@ static FLUSH_STATE: QuickLock<()> = QuickLock::new(());
 /// The preload trip switch
 static PRELOAD_TRIPSWITCH: Trip = Trip::new_untripped();
 static CLEANUP_TRIPSWITCH: Trip = Trip::new_untripped();
+/// The replication state of this node
+static REPLICA_STATE: AtomicU8 = AtomicU8::new(ReplicaState::Leader as u8);
+/// The number of followers streaming from this node
+static FOLLOWER_COUNT: AtomicUsize = AtomicUsize::new(0);
+/// The number of records this node has applied from its leader
+static REPLICATED_RECORDS: AtomicU64 = AtomicU64::new(0);
+/// The number of bytes that volatile models may hold in total (zero for no limit)
+static MAX_MEMORY: AtomicUsize = AtomicUsize::new(0);
+/// The number of bytes held by volatile models
+static USED_MEMORY: AtomicUsize = AtomicUsize::new(0);
+/// The number of keys evicted from volatile models
+static EVICTED_KEYS: AtomicU64 = AtomicU64::new(0);
+
+/// The replication state of a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ReplicaState {
+    /// Accepts writes (and followers)
+    Leader = 0,
+    /// A follower that isn't connected to its leader
+    Offline = 1,
+    /// A follower that is pulling a snapshot from its leader
+    Syncing = 2,
+    /// A follower that is tailing its leader's mutations
+    Online = 3,
+}
+
+impl ReplicaState {
+    const fn from_raw(raw: u8) -> Self {
+        match raw {
+            0 => Self::Leader,
+            1 => Self::Offline,
+            2 => Self::Syncing,
+            _ => Self::Online,
+        }
+    }
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Leader => "leader",
+            Self::Offline => "follower-offline",
+            Self::Syncing => "follower-syncing",
+            Self::Online => "follower-online",
+        }
+    }
+}
 
 /// Check the global system state
 pub fn state_okay() -> bool {
@@ -112,3 +157,54 @@ pub fn get_preload_tripswitch() -> &'static Trip {
 pub fn get_cleanup_tripswitch() -> &'static Trip {
     &CLEANUP_TRIPSWITCH
 }
+
+/// Get the replication state of this node
+pub fn replica_state() -> ReplicaState {
+    ReplicaState::from_raw(REPLICA_STATE.load(ORD_ACQ))
+}
+
+/// Set the replication state of this node
+pub fn set_replica_state(state: ReplicaState) {
+    REPLICA_STATE.store(state as u8, ORD_REL)
+}
+
+/// Check if this node is a follower (and hence rejects writes)
+pub fn is_follower() -> bool {
+    replica_state() != ReplicaState::Leader
+}
+
+/// Get a static reference to the number of followers streaming from this node
+pub fn follower_count() -> &'static AtomicUsize {
+    &FOLLOWER_COUNT
+}
+
+/// Get a static reference to the number of records applied from the leader
+pub fn replicated_records() -> &'static AtomicU64 {
+    &REPLICATED_RECORDS
+}
+
+/// Get the memory limit for volatile models. Returns zero if there is no limit
+pub fn max_memory() -> usize {
+    MAX_MEMORY.load(ORD_ACQ)
+}
+
+/// Set the memory limit for volatile models (zero for no limit)
+pub fn set_max_memory(bytes: usize) {
+    MAX_MEMORY.store(bytes, ORD_REL)
+}
+
+/// Get a static reference to the number of bytes held by volatile models
+pub fn used_memory() -> &'static AtomicUsize {
+    &USED_MEMORY
+}
+
+/// Check if volatile models hold more than the memory limit
+pub fn is_over_max_memory() -> bool {
+    let max = max_memory();
+    max != 0 && USED_MEMORY.load(ORD_ACQ) > max
+}
+
+/// Get a static reference to the number of keys evicted from volatile models
+pub fn evicted_keys() -> &'static AtomicU64 {
+    &EVICTED_KEYS
+}
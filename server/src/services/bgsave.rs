@@ -78,14 +78,19 @@ pub async fn bgsave_scheduler(handle: Corestore, bgsave_cfg: BGSave, mut termina
 
 /// Run bgsave
 ///
-/// This function just hides away the BGSAVE blocking section from the _public API_
+/// This function just hides away the BGSAVE blocking section from the _public API_. The
+/// write-ahead log is rotated before the flush and the rotated log is discarded once the
+/// flush succeeds (since everything it recorded is now in the data files)
 pub fn run_bgsave(handle: &Corestore) -> IoResult<()> {
-    storage::v1::flush::flush_full(Autoflush, handle.get_store())
+    let wal = handle.get_wal();
+    wal.rotate()?;
+    storage::v1::flush::flush_full(Autoflush, &handle.clone_store())?;
+    wal.discard_rotated()
 }
 
 /// This just wraps around [`_bgsave_blocking_section`] and prints nice log messages depending on the outcome
 fn bgsave_blocking_section(handle: Corestore) -> bool {
-    registry::lock_flush_state();
+    let _flush_lock = registry::lock_flush_state();
     match run_bgsave(&handle) {
         Ok(_) => {
             log::info!("BGSAVE completed successfully");
@@ -0,0 +1,79 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use {
+    crate::corestore::{memstore::Memstore, table::DataModel, Corestore},
+    tokio::{
+        sync::broadcast::Receiver,
+        time::{self, Duration},
+    },
+};
+
+/// The interval after which the reaper wakes up to reclaim expired keys
+const REAPER_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The expiry reaper periodically removes keys whose TTLs have elapsed
+///
+/// Expired keys are already invisible to queries (they're lazily removed on access), but keys
+/// that are never accessed again would otherwise linger in memory and on disk. This service
+/// reclaims them every [`REAPER_INTERVAL`]
+pub async fn expiry_reaper(handle: Corestore, mut terminator: Receiver<()>) {
+    loop {
+        tokio::select! {
+            _ = time::sleep_until(time::Instant::now() + REAPER_INTERVAL) => {
+                let cloned_handle = handle.clone();
+                // a large number of expired keys can take a while to purge; don't block the
+                // async workers while we do that
+                tokio::task::spawn_blocking(move || {
+                    let purged = reap_expired(&cloned_handle.clone_store());
+                    if purged != 0 {
+                        log::trace!("Expiry reaper purged {} keys", purged);
+                    }
+                }).await.expect("Something caused the expiry reaper to panic");
+            }
+            _ = terminator.recv() => {
+                // we got a notification to quit; so break out
+                break;
+            }
+        }
+    }
+    log::info!("Expiry reaper has exited");
+}
+
+/// Remove all expired keys across all keyspaces, returning the number of keys removed
+pub fn reap_expired(store: &Memstore) -> usize {
+    let mut purged = 0;
+    for keyspace in store.keyspaces.iter() {
+        for table in keyspace.value().tables.iter() {
+            if let DataModel::KV(kve) | DataModel::KVOrdered(kve) = table.value().get_model_ref() {
+                purged += kve.purge_expired();
+            }
+            // account for the purged keys (and any other writes not yet accounted for)
+            table.value().settle();
+        }
+    }
+    purged
+}
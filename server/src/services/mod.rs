@@ -25,7 +25,10 @@
 */
 
 pub mod bgsave;
+pub mod expiry;
+pub mod replica;
 pub mod snapshot;
+pub mod wal;
 use crate::{
     corestore::memstore::Memstore, diskstore::flock::FileLock, storage, util::os, IoResult,
 };
@@ -34,6 +37,8 @@ pub fn restore_data(src: Option<String>) -> IoResult<()> {
     if let Some(src) = src {
         // hmm, so restore it
         os::recursive_copy(src, "data")?;
+        // the log describes writes on top of the data we just replaced
+        storage::v1::wal::remove_all()?;
         log::info!("Successfully restored data from snapshot");
     }
     Ok(())
@@ -0,0 +1,171 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Replication (follower side)
+//!
+//! A follower connects to its leader, logs in (if configured to) and sends `SYNC`. It
+//! restores the snapshot that it gets and then applies the leader's mutations as they
+//! arrive. If the connection breaks, it reconnects and starts over with a new snapshot
+
+use {
+    crate::{
+        admin::sync::HEARTBEAT_INTERVAL,
+        config::FollowerPref,
+        corestore::Corestore,
+        registry::{self, ReplicaState},
+        storage::v1::wal::{self, FRAME_FOOTER_SIZE, FRAME_HEADER_SIZE},
+        IoResult,
+    },
+    core::sync::atomic::Ordering,
+    std::io::{Error as IoError, ErrorKind},
+    tokio::{
+        io::{AsyncReadExt, AsyncWriteExt, BufReader},
+        net::TcpStream,
+        sync::broadcast::Receiver,
+        time::{self, Duration},
+    },
+};
+
+/// The leader is assumed to be gone if we don't get a frame for this long
+const LEADER_TIMEOUT: Duration = Duration::from_secs(HEARTBEAT_INTERVAL.as_secs() * 3);
+/// The longest we wait before reconnecting
+const MAX_BACKOFF: Duration = Duration::from_secs(32);
+/// The Skyhash 2.0 response to a query that went through
+const RESPONSE_OKAY: &[u8] = b"*!0\n";
+
+type LeaderConnection = BufReader<TcpStream>;
+
+/// Replicate from the leader until we're asked to shut down
+pub async fn replicate(handle: Corestore, pref: FollowerPref, mut terminator: Receiver<()>) {
+    let mut backoff = Duration::from_secs(1);
+    loop {
+        registry::set_replica_state(ReplicaState::Offline);
+        tokio::select! {
+            ret = self::follow(&handle, &pref) => {
+                if let Err(e) = ret {
+                    log::error!("Lost replication stream from leader {}: {e}", pref.leader);
+                }
+            }
+            _ = terminator.recv() => break,
+        }
+        if registry::replica_state() == ReplicaState::Online {
+            // we were in sync; so it's worth trying again right away
+            backoff = Duration::from_secs(1);
+        }
+        registry::set_replica_state(ReplicaState::Offline);
+        log::info!("Reconnecting to leader in {} second(s)", backoff.as_secs());
+        tokio::select! {
+            _ = time::sleep(backoff) => {}
+            _ = terminator.recv() => break,
+        }
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+    log::info!("Replication service has exited");
+}
+
+async fn follow(handle: &Corestore, pref: &FollowerPref) -> IoResult<()> {
+    let mut con = BufReader::new(TcpStream::connect(pref.leader).await?);
+    if let Some((user, token)) = pref.login.as_ref() {
+        self::query(
+            &mut con,
+            &[b"AUTH", b"LOGIN", user.as_bytes(), token.as_bytes()],
+        )
+        .await?;
+    }
+    registry::set_replica_state(ReplicaState::Syncing);
+    self::query(&mut con, &[b"SYNC"]).await?;
+    let mut snapshot = Vec::new();
+    while self::read_frame(&mut con, &mut snapshot).await? {}
+    let store = handle.clone_store();
+    let restored = tokio::task::spawn_blocking(move || wal::restore_snapshot(&store, &snapshot))
+        .await
+        .expect("Something caused the snapshot restore task to panic")
+        .ok_or_else(self::corrupted)?;
+    registry::replicated_records().fetch_add(restored as u64, Ordering::Relaxed);
+    registry::set_replica_state(ReplicaState::Online);
+    log::info!("Restored {restored} record(s) from leader {}", pref.leader);
+    let mut frame = Vec::new();
+    loop {
+        frame.clear();
+        if self::read_frame(&mut con, &mut frame).await? {
+            let applied =
+                wal::apply_frame(handle.get_store(), &frame).ok_or_else(self::corrupted)?;
+            registry::replicated_records().fetch_add(applied as u64, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Run a simple query on the leader and make sure that it went through
+async fn query(con: &mut LeaderConnection, query: &[&[u8]]) -> IoResult<()> {
+    let mut packet = format!("*{}\n", query.len()).into_bytes();
+    for element in query {
+        packet.extend_from_slice(format!("{}\n", element.len()).as_bytes());
+        packet.extend_from_slice(element);
+    }
+    con.write_all(&packet).await?;
+    con.flush().await?;
+    let mut response = [0u8; RESPONSE_OKAY.len()];
+    con.read_exact(&mut response).await?;
+    if response == RESPONSE_OKAY {
+        Ok(())
+    } else {
+        Err(IoError::other(format!(
+            "`{}` failed with `{}`",
+            String::from_utf8_lossy(query[0]),
+            String::from_utf8_lossy(&response).trim_end()
+        )))
+    }
+}
+
+/// Read the next frame and append it to `buf`. Returns false for empty frames (which
+/// aren't appended)
+async fn read_frame(con: &mut LeaderConnection, buf: &mut Vec<u8>) -> IoResult<bool> {
+    let mut header = [0u8; FRAME_HEADER_SIZE];
+    time::timeout(LEADER_TIMEOUT, con.read_exact(&mut header))
+        .await
+        .map_err(|_| IoError::new(ErrorKind::TimedOut, "the leader stopped responding"))??;
+    let body_len = u64::from_le_bytes(header);
+    let rest = body_len
+        .checked_add(FRAME_FOOTER_SIZE as u64)
+        .filter(|rest| *rest <= wal::MAX_FRAME_SIZE - FRAME_HEADER_SIZE as u64)
+        .ok_or_else(self::corrupted)?;
+    let start = buf.len();
+    buf.extend_from_slice(&header);
+    // the buffer only grows as the frame arrives, so a bad length can't make us allocate
+    // much more than what the leader actually sends
+    let read = (&mut *con).take(rest).read_to_end(buf).await?;
+    if read as u64 != rest {
+        return Err(ErrorKind::UnexpectedEof.into());
+    }
+    if body_len == 0 {
+        buf.truncate(start);
+    }
+    Ok(body_len != 0)
+}
+
+fn corrupted() -> IoError {
+    IoError::new(ErrorKind::InvalidData, "got a corrupted frame")
+}
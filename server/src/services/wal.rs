@@ -0,0 +1,65 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use {
+    crate::corestore::Corestore,
+    tokio::{
+        sync::broadcast::Receiver,
+        time::{self, Duration},
+    },
+};
+
+/// The interval after which pending WAL appends are synced to disk
+const SYNC_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Periodically fsync the write-ahead log when the `everysec` policy is in use. For other
+/// policies, this returns immediately
+pub async fn wal_syncer(handle: Corestore, mut terminator: Receiver<()>) {
+    if !handle.get_wal().needs_background_sync() {
+        return;
+    }
+    loop {
+        tokio::select! {
+            _ = time::sleep_until(time::Instant::now() + SYNC_INTERVAL) => {
+                let cloned_handle = handle.clone();
+                tokio::task::spawn_blocking(move || {
+                    if let Err(e) = cloned_handle.get_wal().sync_if_dirty() {
+                        log::error!("Failed to sync the write-ahead log: {e}");
+                    }
+                }).await.expect("Something caused the WAL sync service to panic");
+            }
+            _ = terminator.recv() => {
+                // we got a notification to quit; so break out
+                break;
+            }
+        }
+    }
+    // whatever was appended since the last tick
+    if let Err(e) = handle.get_wal().sync_if_dirty() {
+        log::error!("Failed to sync the write-ahead log: {e}");
+    }
+    log::info!("WAL sync service has exited");
+}
@@ -0,0 +1,280 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Archives
+//!
+//! An archive holds a single space or a single model, so that it can be moved to another
+//! instance (and loaded there under a different name) without copying the data directory.
+//! It's one file with a header (see the [`header`] module) followed by:
+//! ```text
+//! [4B: Magic "SKYA"][1B: Version][1B: Endian mark][1B: Kind (space or model)]
+//! [8B: Model count]
+//! ([8B: NAMELEN][?B: NAME][1B: Storage type][1B: Model code]
+//!  [8B: DATALEN][?B: DATA][8B: EXPIRYLEN][?B: EXPIRY][8B: SCHEMALEN][?B: SCHEMA])*
+//! ```
+//! The data, expiry deadlines and schema of a model are serialized just like the files of a
+//! table in the data directory, and are left empty if the model doesn't have them. Sizes are
+//! written in the endian of the machine that created the archive, which is what the endian
+//! mark records, so archives can be loaded on machines of either endian
+
+use {
+    super::{
+        header,
+        interface::{DIR_ARCHIVES, EXPIRY_FILE_SUFFIX, SCHEMA_FILE_SUFFIX},
+        iter::{self, RawSliceIter},
+        unflush::{TableSource, UnflushableTable},
+        Coremap,
+    },
+    crate::{
+        corestore::{
+            memstore::{Keyspace, ObjectID},
+            table::Table,
+        },
+        storage::v1::{
+            error::{ErrorContext, StorageEngineError, StorageEngineResult},
+            flush::FlushableTable,
+        },
+        IoResult,
+    },
+    std::{
+        borrow::Cow,
+        fs::{self, File},
+        io::{BufWriter, Write},
+        path::{Component, Path, PathBuf},
+        sync::Arc,
+    },
+};
+
+const MAGIC: [u8; 4] = *b"SKYA";
+const VERSION: u8 = 1;
+const ENDIAN_LITTLE: u8 = 0;
+const ENDIAN_BIG: u8 = 1;
+#[cfg(target_endian = "little")]
+const ENDIAN: u8 = ENDIAN_LITTLE;
+#[cfg(target_endian = "big")]
+const ENDIAN: u8 = ENDIAN_BIG;
+const KIND_SPACE: u8 = 0;
+const KIND_MODEL: u8 = 1;
+/// Magic, version, endian mark and kind
+const PREAMBLE_SIZE: usize = 7;
+
+/// What an archive holds
+pub enum Archive {
+    Space(Keyspace),
+    Model(Table),
+}
+
+/// Returns the path of the archive `path` in [`DIR_ARCHIVES`]. Returns `None` if the path
+/// is empty or could point outside that directory
+pub fn resolve_path(path: &str) -> Option<PathBuf> {
+    let path = Path::new(path);
+    let legal = path.file_name().is_some()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if legal {
+        Some(Path::new(DIR_ARCHIVES).join(path))
+    } else {
+        None
+    }
+}
+
+/// Write all the models in `keyspace` to an archive at `path`
+pub fn export_space(path: &Path, keyspace: &Keyspace) -> IoResult<()> {
+    // take the tables out first, since a table could be added while we're writing
+    let tables: Vec<(ObjectID, Arc<Table>)> = keyspace
+        .tables
+        .iter()
+        .map(|table| (table.key().clone(), table.value().clone()))
+        .collect();
+    self::write_archive(path, KIND_SPACE, &tables)
+}
+
+/// Write the model `tblid` to an archive at `path`
+pub fn export_model(path: &Path, tblid: &ObjectID, table: Arc<Table>) -> IoResult<()> {
+    self::write_archive(path, KIND_MODEL, &[(tblid.clone(), table)])
+}
+
+fn write_archive(path: &Path, kind: u8, tables: &[(ObjectID, Arc<Table>)]) -> IoResult<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // like data files, the archive only replaces an older one once it's complete
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push("_");
+    let mut file = File::create(&tmp_path)?;
+    header::write_with_header(&mut file, None, |writer| {
+        let mut writer = BufWriter::new(writer);
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&[VERSION, ENDIAN, kind])?;
+        self::write_size(&mut writer, tables.len())?;
+        for (tblid, table) in tables {
+            self::write_model(&mut writer, tblid, table)?;
+        }
+        writer.flush()
+    })?;
+    file.sync_all()?;
+    fs::rename(&tmp_path, path)
+}
+
+fn write_model<W: Write>(w: &mut W, tblid: &ObjectID, table: &Table) -> IoResult<()> {
+    self::write_slice(w, tblid)?;
+    w.write_all(&[table.storage_code(), table.model_code()])?;
+    let mut part = Vec::new();
+    if !table.is_volatile() {
+        table.write_table_to(&mut part)?;
+    }
+    self::write_slice(w, &part)?;
+    part.clear();
+    if !table.is_volatile() && table.has_expiry() {
+        table.write_expiry_to(&mut part)?;
+    }
+    self::write_slice(w, &part)?;
+    part.clear();
+    table.write_schema_to(&mut part)?;
+    self::write_slice(w, &part)
+}
+
+fn write_size<W: Write>(w: &mut W, size: usize) -> IoResult<()> {
+    unsafe { w.write_all(super::raw_byte_repr(&to_64bit_native_endian!(size))) }
+}
+
+fn write_slice<W: Write>(w: &mut W, slice: &[u8]) -> IoResult<()> {
+    self::write_size(w, slice.len())?;
+    w.write_all(slice)
+}
+
+/// Read the archive at `path`
+pub fn import(path: &Path) -> StorageEngineResult<Archive> {
+    let name = path.to_string_lossy().to_string();
+    let file = fs::read(path).map_err_context(format!("reading archive {name}"))?;
+    if !header::has_header(&file) {
+        return Err(StorageEngineError::BadMetadata(name));
+    }
+    let payload =
+        header::payload(&file).map_err(|e| StorageEngineError::BadHeader(name.clone(), e))?;
+    if payload.len() < PREAMBLE_SIZE || payload[..4] != MAGIC || payload[4] != VERSION {
+        return Err(StorageEngineError::BadMetadata(name));
+    }
+    let big = match payload[5] {
+        ENDIAN_LITTLE => false,
+        ENDIAN_BIG => true,
+        _ => return Err(StorageEngineError::BadMetadata(name)),
+    };
+    let kind = payload[6];
+    let mut models =
+        iter::with_endian(big, || self::read_models(&name, &payload[PREAMBLE_SIZE..]))?;
+    match kind {
+        KIND_SPACE => {
+            let tables = Coremap::with_capacity(models.len());
+            for (tblid, table) in models {
+                if !tables.true_if_insert(tblid, Arc::new(table)) {
+                    // the same model twice
+                    return Err(StorageEngineError::CorruptedFile(name));
+                }
+            }
+            Ok(Archive::Space(Keyspace::init_with_tables(tables)))
+        }
+        KIND_MODEL if models.len() == 1 => Ok(Archive::Model(models.remove(0).1)),
+        _ => Err(StorageEngineError::BadMetadata(name)),
+    }
+}
+
+fn read_models(name: &str, body: &[u8]) -> StorageEngineResult<Vec<(ObjectID, Table)>> {
+    let corrupted = || StorageEngineError::CorruptedFile(name.to_owned());
+    let mut iter = RawSliceIter::new(body);
+    let count = iter.next_64bit_integer_to_usize().ok_or_else(corrupted)?;
+    let mut models = Vec::new();
+    for _ in 0..count {
+        let (tblid, codes, data, expiry, schema) = match (
+            self::next_slice(&mut iter).and_then(ObjectID::try_from_slice),
+            iter.next_borrowed_slice(2),
+            self::next_slice(&mut iter),
+            self::next_slice(&mut iter),
+            self::next_slice(&mut iter),
+        ) {
+            (Some(tblid), Some(codes), Some(data), Some(expiry), Some(schema)) => {
+                (tblid, codes, data, expiry, schema)
+            }
+            _ => return Err(corrupted()),
+        };
+        let (storage, model_code) = (codes[0], codes[1]);
+        let (volatile, policy) = Table::from_storage_type(storage).ok_or_else(corrupted)?;
+        let source = ArchivedModel {
+            archive: name,
+            model: String::from_utf8_lossy(&tblid).to_string(),
+            data,
+            expiry,
+            schema,
+        };
+        let table = Table::unflush_table_from(&source, model_code, volatile)
+            .map_err(|e| {
+                match e {
+                    // a part that the model needs is missing from the archive
+                    StorageEngineError::IoError(_) | StorageEngineError::IoErrorExtra(..) => {
+                        corrupted()
+                    }
+                    e => e,
+                }
+            })?
+            .with_eviction(policy);
+        models.push((tblid, table));
+    }
+    if iter.end_of_allocation() {
+        Ok(models)
+    } else {
+        Err(corrupted())
+    }
+}
+
+fn next_slice<'a>(iter: &mut RawSliceIter<'a>) -> Option<&'a [u8]> {
+    let len = iter.next_64bit_integer_to_usize()?;
+    iter.next_borrowed_slice(len)
+}
+
+/// A model in an archive
+struct ArchivedModel<'a> {
+    archive: &'a str,
+    model: String,
+    data: &'a [u8],
+    expiry: &'a [u8],
+    schema: &'a [u8],
+}
+
+impl TableSource for ArchivedModel<'_> {
+    fn read_part(&self, suffix: &str) -> StorageEngineResult<Option<Cow<'_, [u8]>>> {
+        let part = match suffix {
+            EXPIRY_FILE_SUFFIX => self.expiry,
+            SCHEMA_FILE_SUFFIX => self.schema,
+            _ => self.data,
+        };
+        // parts that a model doesn't have are left empty
+        Ok(Some(Cow::Borrowed(part)).filter(|part| !part.is_empty()))
+    }
+    fn part_name(&self, suffix: &str) -> String {
+        format!("{}{suffix} in {}", self.model, self.archive)
+    }
+}
@@ -41,6 +41,13 @@
  * KVEBlob:
  * (1) Pure KVEBlob: [0, 3]
  * (2) KVExt/Listmap: [4, 7]
+ * Rowmap (named fields): 8
+ * Ordered KVEBlob: [9, 12]
+ * Typed KVEBlob (the key or the value is a number): 16 + (key type << 3) + value type
+ * Hashmap: 128 + (key enc << 2) + (field enc << 1) + value enc
+ * Set: 136 + (key enc << 1) + member enc
+ * Sorted set: 140 + (key enc << 1) + member enc
+ * Queue: 144 + (key enc << 1) + item enc
 */
 /// KVEBlob model bytemark with key:bin, val:bin
 pub const BYTEMARK_MODEL_KV_BIN_BIN: u8 = 0;
@@ -58,12 +65,38 @@ pub const BYTEMARK_MODEL_KV_BINSTR_LIST_STR: u8 = 5;
 pub const BYTEMARK_MODEL_KV_STR_LIST_BINSTR: u8 = 6;
 /// KVEBlob model bytemark with key:str, val: list<str>
 pub const BYTEMARK_MODEL_KV_STR_LIST_STR: u8 = 7;
+/// Rowmap model bytemark. The field types are stored in the table's schema file
+pub const BYTEMARK_MODEL_ROWMAP: u8 = 8;
+/// Ordered KVEBlob model bytemark with key:bin, val:bin
+pub const BYTEMARK_MODEL_KV_ORDERED_BIN_BIN: u8 = 9;
+/// Ordered KVEBlob model bytemark with key:bin, val:str
+pub const BYTEMARK_MODEL_KV_ORDERED_BIN_STR: u8 = 10;
+/// Ordered KVEBlob model bytemark with key:str, val:str
+pub const BYTEMARK_MODEL_KV_ORDERED_STR_STR: u8 = 11;
+/// Ordered KVEBlob model bytemark with key:str, val:bin
+pub const BYTEMARK_MODEL_KV_ORDERED_STR_BIN: u8 = 12;
+/// The first typed KVEBlob model bytemark. The key and value types are packed into the
+/// bytemark; see [`crate::kvengine::types::FieldType`] for the type codes
+pub const BYTEMARK_MODEL_KV_TYPED: u8 = 16;
+/// The first hashmap model bytemark. The encodings of the key, the fields and the values are
+/// packed into the bytemark, where a set bit means a string
+pub const BYTEMARK_MODEL_KV_MAP: u8 = 128;
+/// The first set model bytemark. The encodings of the key and the members are packed into the
+/// bytemark like for a hashmap
+pub const BYTEMARK_MODEL_KV_SET: u8 = 136;
+/// The first sorted set model bytemark, packed like a set model bytemark
+pub const BYTEMARK_MODEL_KV_SORTED_SET: u8 = 140;
+/// The first queue model bytemark, packed like a set model bytemark
+pub const BYTEMARK_MODEL_KV_QUEUE: u8 = 144;
 
 // storage bym
 /// Persistent storage bytemark
 pub const BYTEMARK_STORAGE_PERSISTENT: u8 = 0;
 /// Volatile storage bytemark
 pub const BYTEMARK_STORAGE_VOLATILE: u8 = 1;
+/// The eviction policy of a volatile table is stored in the storage bits above this offset
+pub const BYTEMARK_STORAGE_EVICTION_SHIFT: u8 = 1;
 
 // system bym
 pub const SYSTEM_TABLE_AUTH: u8 = 0;
+pub const SYSTEM_TABLE_GRANTS: u8 = 1;
@@ -0,0 +1,480 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Offline dumps
+//!
+//! The routines behind `sky-dump`. They read a tree (`data/ks` or a snapshot) with the same
+//! routines that are used on startup, list its models and write their data out as JSON lines
+//! or CSV, all without a running server. The system keyspace is never listed or dumped.
+//!
+//! Keys and values that aren't valid UTF-8 are written as base64: as a `{"base64": "..."}`
+//! object in JSON and with a `base64:` prefix in CSV. Values that are valid UTF-8 but already
+//! start with `base64:` are encoded as well, so the prefix always means that the rest is base64.
+//! CSV output always has the same columns (`space,model,key,field,value,expires_at`), with one
+//! line per list item or named field. The fields of a map are written as the keys of a JSON
+//! object, so they are prefixed just like in CSV. Sets are written just like lists,
+//! while a sorted set is written as a list of `[member, score]` pairs ordered by score (in CSV,
+//! the member goes in the `field` column and the score in the `value` column). The waiting
+//! items of a queue are written like a list, and its reserved items are listed under
+//! `reserved` along with their reservation ID and deadline (in CSV, a reserved item has a
+//! `reserved:<id>` field and its deadline goes in the `expires_at` column).
+
+use {
+    super::{
+        bytemarks,
+        error::{StorageEngineError, StorageEngineResult},
+        unflush::{self, UnflushableTable},
+    },
+    crate::{
+        corestore::{
+            htable::Coremap,
+            memstore::SYSTEM,
+            table::{DataModel, Table},
+            SharedSlice,
+        },
+        kvengine::{hashmap::FieldMap, queue::Queue, rowmap::Field, sets::SortedSet},
+    },
+    std::{
+        borrow::Cow,
+        io::{self, Write},
+        path::Path,
+        str,
+    },
+};
+
+/// The prefix of base64 encoded fields in CSV (and of map fields in JSON)
+const BASE64_PREFIX: &str = "base64:";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// One JSON object per key
+    JsonLines,
+    /// One line per value
+    Csv,
+}
+
+impl Format {
+    /// Get a format from its name: `jsonl` or `csv`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "jsonl" => Some(Self::JsonLines),
+            "csv" => Some(Self::Csv),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+/// A model, as described by the `PARTMAP` of its space
+pub struct ModelInfo {
+    pub space: String,
+    pub model: String,
+    pub model_code: u8,
+    pub volatile: bool,
+    /// the description returned by `inspect model`
+    pub description: String,
+}
+
+/// List the models in the tree at `root`, sorted by space and model
+pub fn list(root: impl AsRef<Path>) -> StorageEngineResult<Vec<ModelInfo>> {
+    let root = root.as_ref();
+    let (keyspaces, _) = unflush::read_preload_in(root)?;
+    let mut models = Vec::new();
+    for ksid in keyspaces.iter().filter(|ksid| **ksid != SYSTEM) {
+        for (tblid, (storage, model_code)) in unflush::read_partmap_in(root, ksid)? {
+            let (volatile, policy) = Table::from_storage_type(storage)
+                .ok_or_else(|| StorageEngineError::bad_metadata_in_table(ksid, &tblid))?;
+            let (space, model) = unsafe { (ksid.as_str().to_owned(), tblid.as_str().to_owned()) };
+            // only the schema is needed to describe the model, not the data
+            let table = if model_code == bytemarks::BYTEMARK_MODEL_ROWMAP {
+                let schema = unflush::read_schema(root.join(&space).join(&model).as_path())?;
+                Table::new_rowmap(schema, volatile)
+            } else {
+                let table = Table::from_model_code(model_code, volatile)
+                    .ok_or_else(|| StorageEngineError::bad_metadata_in_table(ksid, &tblid))?;
+                // capped list models
+                match unflush::read_max_len(root.join(&space).join(&model).as_path())? {
+                    Some(max_len) => table
+                        .with_max_len(max_len)
+                        .ok_or_else(|| StorageEngineError::bad_metadata_in_table(ksid, &tblid))?,
+                    None => table,
+                }
+            }
+            .with_eviction(policy);
+            models.push(ModelInfo {
+                space,
+                model,
+                model_code,
+                volatile,
+                description: table.describe_self().into_owned(),
+            });
+        }
+    }
+    models.sort_by(|a, b| (&a.space, &a.model).cmp(&(&b.space, &b.model)));
+    Ok(models)
+}
+
+/// Write the data of `models` (as returned by [`list`]) in the tree at `root` to `out`. Keys
+/// are written in order. Returns the number of keys that were written
+pub fn dump<W: Write>(
+    root: impl AsRef<Path>,
+    models: &[ModelInfo],
+    format: Format,
+    out: &mut W,
+) -> StorageEngineResult<usize> {
+    let mut records = Records {
+        out,
+        format,
+        count: 0,
+    };
+    if format == Format::Csv {
+        writeln!(records.out, "space,model,key,field,value,expires_at")?;
+    }
+    // volatile models have nothing on disk
+    for info in models.iter().filter(|info| !info.volatile) {
+        let path = root.as_ref().join(&info.space).join(&info.model);
+        let table = Table::unflush_table(&path, info.model_code, false)?;
+        match table.get_model_ref() {
+            DataModel::KV(kve) | DataModel::KVOrdered(kve) => {
+                for key in sorted_keys(kve.get_inner_ref()) {
+                    if let Some(value) = kve.get_inner_ref().get(key.as_ref()) {
+                        let deadline = kve.get_expiry_ref().get(key.as_ref()).map(|d| *d);
+                        records.kv(info, &key, &value, deadline)?;
+                    }
+                }
+            }
+            DataModel::KVExtListmap(kvl) => {
+                for key in sorted_keys(kvl.get_inner_ref()) {
+                    if let Some(list) = kvl.get_inner_ref().get(key.as_ref()) {
+                        records.list(info, &key, &list.read())?;
+                    }
+                }
+            }
+            DataModel::Rowmap(rm) => {
+                let rows = rm.rows().get_inner_ref();
+                for key in sorted_keys(rows) {
+                    if let Some(row) = rows.get(key.as_ref()) {
+                        records.row(info, &key, rm.schema().row_fields(), &row.read())?;
+                    }
+                }
+            }
+            DataModel::Hashmap(hm) => {
+                let maps = hm.maps().get_inner_ref();
+                for key in sorted_keys(maps) {
+                    if let Some(map) = maps.get(key.as_ref()) {
+                        records.map(info, &key, &map.read())?;
+                    }
+                }
+            }
+            DataModel::Set(sets) => {
+                for key in sorted_keys(sets.get_inner_ref()) {
+                    if let Some(set) = sets.get_inner_ref().get(key.as_ref()) {
+                        let members: Vec<SharedSlice> = set.read().iter().cloned().collect();
+                        records.list(info, &key, &members)?;
+                    }
+                }
+            }
+            DataModel::SortedSet(zsets) => {
+                for key in sorted_keys(zsets.get_inner_ref()) {
+                    if let Some(set) = zsets.get_inner_ref().get(key.as_ref()) {
+                        records.sorted_set(info, &key, &set.read())?;
+                    }
+                }
+            }
+            DataModel::Queue(queues) => {
+                for key in sorted_keys(queues.get_inner_ref()) {
+                    if let Some(queue) = queues.get_inner_ref().get(key.as_ref()) {
+                        records.queue(info, &key, &queue.read())?;
+                    }
+                }
+            }
+        }
+    }
+    Ok(records.count)
+}
+
+fn sorted_keys<T>(map: &Coremap<SharedSlice, T>) -> Vec<SharedSlice> {
+    let mut keys: Vec<SharedSlice> = map.iter().map(|kv| kv.key().clone()).collect();
+    keys.sort_by(|a, b| a.as_ref().cmp(b.as_ref()));
+    keys
+}
+
+struct Records<'a, W> {
+    out: &'a mut W,
+    format: Format,
+    count: usize,
+}
+
+impl<'a, W: Write> Records<'a, W> {
+    fn kv(
+        &mut self,
+        info: &ModelInfo,
+        key: &[u8],
+        value: &[u8],
+        deadline: Option<u64>,
+    ) -> io::Result<()> {
+        self.count += 1;
+        match self.format {
+            Format::JsonLines => {
+                self.json_prefix(info, key)?;
+                json_bytes(self.out, value)?;
+                if let Some(deadline) = deadline {
+                    write!(self.out, ",\"expires_at\":{deadline}")?;
+                }
+                writeln!(self.out, "}}")
+            }
+            Format::Csv => {
+                let deadline = deadline.map(|d| d.to_string()).unwrap_or_default();
+                self.csv_line(info, key, b"", value, deadline.as_bytes())
+            }
+        }
+    }
+    fn list(&mut self, info: &ModelInfo, key: &[u8], items: &[SharedSlice]) -> io::Result<()> {
+        self.count += 1;
+        match self.format {
+            Format::JsonLines => {
+                self.json_prefix(info, key)?;
+                self.out.write_all(b"[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i != 0 {
+                        self.out.write_all(b",")?;
+                    }
+                    json_bytes(self.out, item)?;
+                }
+                writeln!(self.out, "]}}")
+            }
+            Format::Csv if items.is_empty() => self.csv_line(info, key, b"", b"", b""),
+            Format::Csv => {
+                for (i, item) in items.iter().enumerate() {
+                    self.csv_line(info, key, i.to_string().as_bytes(), item, b"")?;
+                }
+                Ok(())
+            }
+        }
+    }
+    fn row(
+        &mut self,
+        info: &ModelInfo,
+        key: &[u8],
+        fields: &[Field],
+        row: &[SharedSlice],
+    ) -> io::Result<()> {
+        self.count += 1;
+        match self.format {
+            Format::JsonLines => {
+                self.json_prefix(info, key)?;
+                self.out.write_all(b"{")?;
+                for (i, (field, value)) in fields.iter().zip(row).enumerate() {
+                    if i != 0 {
+                        self.out.write_all(b",")?;
+                    }
+                    json_str(self.out, field.name())?;
+                    self.out.write_all(b":")?;
+                    json_bytes(self.out, value)?;
+                }
+                writeln!(self.out, "}}}}")
+            }
+            Format::Csv => {
+                for (field, value) in fields.iter().zip(row) {
+                    self.csv_line(info, key, field.name().as_bytes(), value, b"")?;
+                }
+                Ok(())
+            }
+        }
+    }
+    fn map(&mut self, info: &ModelInfo, key: &[u8], map: &FieldMap) -> io::Result<()> {
+        self.count += 1;
+        match self.format {
+            Format::JsonLines => {
+                self.json_prefix(info, key)?;
+                self.out.write_all(b"{")?;
+                for (i, (field, value)) in map.iter().enumerate() {
+                    if i != 0 {
+                        self.out.write_all(b",")?;
+                    }
+                    json_str(self.out, &prefixed_text(field))?;
+                    self.out.write_all(b":")?;
+                    json_bytes(self.out, value)?;
+                }
+                writeln!(self.out, "}}}}")
+            }
+            Format::Csv => {
+                for (field, value) in map {
+                    self.csv_line(info, key, field, value, b"")?;
+                }
+                Ok(())
+            }
+        }
+    }
+    fn sorted_set(&mut self, info: &ModelInfo, key: &[u8], set: &SortedSet) -> io::Result<()> {
+        self.count += 1;
+        match self.format {
+            Format::JsonLines => {
+                self.json_prefix(info, key)?;
+                self.out.write_all(b"[")?;
+                for (i, (member, score)) in set.iter().enumerate() {
+                    if i != 0 {
+                        self.out.write_all(b",")?;
+                    }
+                    self.out.write_all(b"[")?;
+                    json_bytes(self.out, member)?;
+                    write!(self.out, ",{score}]")?;
+                }
+                writeln!(self.out, "]}}")
+            }
+            Format::Csv => {
+                for (member, score) in set.iter() {
+                    self.csv_line(info, key, member, score.to_string().as_bytes(), b"")?;
+                }
+                Ok(())
+            }
+        }
+    }
+    fn queue(&mut self, info: &ModelInfo, key: &[u8], queue: &Queue) -> io::Result<()> {
+        self.count += 1;
+        match self.format {
+            Format::JsonLines => {
+                self.json_prefix(info, key)?;
+                self.out.write_all(b"[")?;
+                for (i, item) in queue.ready().iter().enumerate() {
+                    if i != 0 {
+                        self.out.write_all(b",")?;
+                    }
+                    json_bytes(self.out, item)?;
+                }
+                self.out.write_all(b"]")?;
+                if !queue.reserved().is_empty() {
+                    self.out.write_all(b",\"reserved\":[")?;
+                    for (i, (id, reservation)) in queue.reserved().iter().enumerate() {
+                        if i != 0 {
+                            self.out.write_all(b",")?;
+                        }
+                        write!(self.out, "{{\"id\":{id},\"item\":")?;
+                        json_bytes(self.out, &reservation.item)?;
+                        write!(self.out, ",\"deadline\":{}}}", reservation.deadline)?;
+                    }
+                    self.out.write_all(b"]")?;
+                }
+                writeln!(self.out, "}}")
+            }
+            Format::Csv if queue.ready().is_empty() && queue.reserved().is_empty() => {
+                self.csv_line(info, key, b"", b"", b"")
+            }
+            Format::Csv => {
+                for (i, item) in queue.ready().iter().enumerate() {
+                    self.csv_line(info, key, i.to_string().as_bytes(), item, b"")?;
+                }
+                for (id, reservation) in queue.reserved() {
+                    let field = format!("reserved:{id}");
+                    let deadline = reservation.deadline.to_string();
+                    self.csv_line(
+                        info,
+                        key,
+                        field.as_bytes(),
+                        &reservation.item,
+                        deadline.as_bytes(),
+                    )?;
+                }
+                Ok(())
+            }
+        }
+    }
+    /// Write everything in the object for a key up to its value
+    fn json_prefix(&mut self, info: &ModelInfo, key: &[u8]) -> io::Result<()> {
+        self.out.write_all(b"{\"space\":")?;
+        json_str(self.out, &info.space)?;
+        self.out.write_all(b",\"model\":")?;
+        json_str(self.out, &info.model)?;
+        self.out.write_all(b",\"key\":")?;
+        json_bytes(self.out, key)?;
+        self.out.write_all(b",\"value\":")
+    }
+    fn csv_line(
+        &mut self,
+        info: &ModelInfo,
+        key: &[u8],
+        field: &[u8],
+        value: &[u8],
+        expires_at: &[u8],
+    ) -> io::Result<()> {
+        let columns = [
+            info.space.as_bytes(),
+            info.model.as_bytes(),
+            key,
+            field,
+            value,
+            expires_at,
+        ];
+        for (i, column) in columns.into_iter().enumerate() {
+            if i != 0 {
+                self.out.write_all(b",")?;
+            }
+            csv_field(self.out, column)?;
+        }
+        self.out.write_all(b"\n")
+    }
+}
+
+fn json_str<W: Write>(out: &mut W, s: &str) -> io::Result<()> {
+    out.write_all(b"\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => out.write_all(b"\\\"")?,
+            '\\' => out.write_all(b"\\\\")?,
+            '\n' => out.write_all(b"\\n")?,
+            '\r' => out.write_all(b"\\r")?,
+            '\t' => out.write_all(b"\\t")?,
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32)?,
+            c => write!(out, "{c}")?,
+        }
+    }
+    out.write_all(b"\"")
+}
+
+fn json_bytes<W: Write>(out: &mut W, bytes: &[u8]) -> io::Result<()> {
+    match str::from_utf8(bytes) {
+        Ok(s) => json_str(out, s),
+        Err(_) => write!(out, "{{\"base64\":\"{}\"}}", base64::encode(bytes)),
+    }
+}
+
+/// Returns `bytes` as text, or their base64 with a `base64:` prefix if they aren't valid UTF-8
+/// or if they start with the prefix themselves (so that the prefix can't be faked)
+fn prefixed_text(bytes: &[u8]) -> Cow<'_, str> {
+    match str::from_utf8(bytes) {
+        Ok(s) if !s.starts_with(BASE64_PREFIX) => Cow::Borrowed(s),
+        _ => Cow::Owned(format!("{BASE64_PREFIX}{}", base64::encode(bytes))),
+    }
+}
+
+fn csv_field<W: Write>(out: &mut W, bytes: &[u8]) -> io::Result<()> {
+    let text = prefixed_text(bytes);
+    if text.contains([',', '"', '\n', '\r']) {
+        write!(out, "\"{}\"", text.replace('"', "\"\""))
+    } else {
+        out.write_all(text.as_bytes())
+    }
+}
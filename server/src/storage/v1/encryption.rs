@@ -0,0 +1,341 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Encryption at rest
+//!
+//! If a key file is configured, the payload of every data file (see the [`header`] module)
+//! and every frame of the write-ahead log is _sealed_ with AES-256-GCM:
+//! ```text
+//! [8B: Key ID][16B: Salt][?B: Ciphertext][16B: Tag]
+//! ```
+//! Every sealed payload is encrypted with its own key, derived from the configured key and
+//! a random salt (with HMAC-SHA256), so that the nonces of the frequent and small log frames
+//! can never repeat under the same key. The key ID identifies the configured key, which lets
+//! us tell a wrong key apart from a damaged file.
+//!
+//! A key file holds a 256-bit key as 64 hexadecimal characters, like the output of
+//! `openssl rand -hex 32`. To encrypt existing data files, decrypt them or change the key, use
+//! [`rekey`] (`sky-dump rekey`) while the server is stopped. Archives are never encrypted.
+//!
+//! [`header`]: super::header
+
+use {
+    super::{
+        error::{ErrorContext, StorageEngineError, StorageEngineResult},
+        header, verify, wal,
+    },
+    crate::{
+        util::os::{self, EntryKind},
+        IoResult,
+    },
+    core::fmt,
+    openssl::{
+        hash::MessageDigest,
+        pkey::PKey,
+        rand,
+        sign::Signer,
+        symm::{self, Cipher, Crypter, Mode},
+    },
+    std::{
+        fs::{self, File},
+        io::{Cursor, Error as IoError, ErrorKind, Write},
+        path::Path,
+        sync::OnceLock,
+    },
+};
+
+/// The size of a key
+pub const KEY_SIZE: usize = 32;
+/// The size of a key ID
+pub const KEY_ID_SIZE: usize = 8;
+const SALT_SIZE: usize = 16;
+const TAG_SIZE: usize = 16;
+/// The size of everything in a sealed payload except the ciphertext
+pub const SEAL_OVERHEAD: usize = KEY_ID_SIZE + SALT_SIZE + TAG_SIZE;
+/// Every payload has its own key, so the nonce never changes
+const NONCE: [u8; 12] = [0; 12];
+
+/// The configured key, if any
+static KEY: OnceLock<Key> = OnceLock::new();
+
+#[derive(Debug, PartialEq)]
+pub enum EncryptionError {
+    /// The data is encrypted, but no key is configured
+    NoKey,
+    /// The data was encrypted with another key
+    WrongKey,
+    /// The data failed authentication, so it was damaged or tampered with
+    Unauthentic,
+}
+
+impl fmt::Display for EncryptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoKey => write!(f, "encrypted, but no encryption key is configured"),
+            Self::WrongKey => write!(f, "encrypted with a different key"),
+            Self::Unauthentic => write!(f, "failed authentication"),
+        }
+    }
+}
+
+/// A 256-bit key
+pub struct Key {
+    key: [u8; KEY_SIZE],
+    id: [u8; KEY_ID_SIZE],
+}
+
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // never print the key itself
+        f.debug_struct("Key").field("id", &self.id).finish()
+    }
+}
+
+impl Key {
+    pub fn new(key: [u8; KEY_SIZE]) -> Self {
+        let mut id = [0; KEY_ID_SIZE];
+        id.copy_from_slice(&hmac(&key, &[b"key-id"])[..KEY_ID_SIZE]);
+        Self { key, id }
+    }
+    /// Read a key from a key file
+    pub fn from_file(path: impl AsRef<Path>) -> IoResult<Self> {
+        let hex = fs::read_to_string(path)?;
+        Self::from_hex(hex.trim()).ok_or_else(|| {
+            IoError::new(
+                ErrorKind::InvalidData,
+                "expected a 256-bit key as 64 hexadecimal characters",
+            )
+        })
+    }
+    fn from_hex(hex: &str) -> Option<Self> {
+        if hex.len() != KEY_SIZE * 2 || !hex.is_ascii() {
+            return None;
+        }
+        let mut key = [0; KEY_SIZE];
+        for (byte, pair) in key.iter_mut().zip(hex.as_bytes().chunks(2)) {
+            *byte = u8::from_str_radix(core::str::from_utf8(pair).ok()?, 16).ok()?;
+        }
+        Some(Self::new(key))
+    }
+    /// Get a random key
+    pub fn generate() -> Self {
+        let mut key = [0; KEY_SIZE];
+        rand::rand_bytes(&mut key).expect("Failed to generate a key");
+        Self::new(key)
+    }
+    pub fn id(&self) -> &[u8; KEY_ID_SIZE] {
+        &self.id
+    }
+    /// Derive the key for a single payload
+    fn derive(&self, salt: &[u8]) -> Vec<u8> {
+        hmac(&self.key, &[b"seal", salt])
+    }
+}
+
+fn hmac(key: &[u8], parts: &[&[u8]]) -> Vec<u8> {
+    let ret = PKey::hmac(key).and_then(|pkey| {
+        let mut signer = Signer::new(MessageDigest::sha256(), &pkey)?;
+        for part in parts {
+            signer.update(part)?;
+        }
+        signer.sign_to_vec()
+    });
+    ret.expect("HMAC-SHA256 failed")
+}
+
+fn openssl_error(e: openssl::error::ErrorStack) -> IoError {
+    IoError::other(e)
+}
+
+/// Set the key that data files are encrypted with. Returns false if a key was already set
+pub fn set_key(key: Key) -> bool {
+    KEY.set(key).is_ok()
+}
+
+/// Get the key that data files are encrypted with, if one is configured
+pub fn key() -> Option<&'static Key> {
+    KEY.get()
+}
+
+/// Seals data that is streamed through it
+pub struct Sealer {
+    crypter: Crypter,
+    buf: Vec<u8>,
+}
+
+impl Sealer {
+    /// Start sealing with `key`, writing the key ID and the salt to `w`
+    pub fn begin<W: Write>(key: &Key, w: &mut W) -> IoResult<Self> {
+        let mut salt = [0; SALT_SIZE];
+        rand::rand_bytes(&mut salt).map_err(openssl_error)?;
+        let crypter = Crypter::new(
+            Cipher::aes_256_gcm(),
+            Mode::Encrypt,
+            &key.derive(&salt),
+            Some(&NONCE),
+        )
+        .map_err(openssl_error)?;
+        w.write_all(key.id())?;
+        w.write_all(&salt)?;
+        Ok(Self {
+            crypter,
+            buf: Vec::new(),
+        })
+    }
+    /// Encrypt `data` and write it to `w`
+    pub fn seal<W: Write>(&mut self, w: &mut W, data: &[u8]) -> IoResult<()> {
+        // GCM is a stream cipher, so the ciphertext is as long as the plaintext
+        self.buf
+            .resize(data.len() + Cipher::aes_256_gcm().block_size(), 0);
+        let len = self
+            .crypter
+            .update(data, &mut self.buf)
+            .map_err(openssl_error)?;
+        w.write_all(&self.buf[..len])
+    }
+    /// Write the tag to `w`
+    pub fn finish<W: Write>(mut self, w: &mut W) -> IoResult<()> {
+        self.buf.resize(Cipher::aes_256_gcm().block_size(), 0);
+        let len = self
+            .crypter
+            .finalize(&mut self.buf)
+            .map_err(openssl_error)?;
+        w.write_all(&self.buf[..len])?;
+        let mut tag = [0; TAG_SIZE];
+        self.crypter.get_tag(&mut tag).map_err(openssl_error)?;
+        w.write_all(&tag)
+    }
+}
+
+/// Seal `data` with `key`
+pub fn seal(key: &Key, data: &[u8]) -> IoResult<Vec<u8>> {
+    let mut sealed = Vec::with_capacity(data.len() + SEAL_OVERHEAD);
+    let mut sealer = Sealer::begin(key, &mut sealed)?;
+    sealer.seal(&mut sealed, data)?;
+    sealer.finish(&mut sealed)?;
+    Ok(sealed)
+}
+
+/// Returns the ID of the key that `sealed` was sealed with
+pub fn sealed_with(sealed: &[u8]) -> Option<&[u8]> {
+    sealed.get(..KEY_ID_SIZE)
+}
+
+/// Check and decrypt data that was sealed with `key`
+pub fn open(key: Option<&Key>, sealed: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+    let key = key.ok_or(EncryptionError::NoKey)?;
+    if sealed.len() < SEAL_OVERHEAD {
+        return Err(EncryptionError::Unauthentic);
+    }
+    let (id, rest) = sealed.split_at(KEY_ID_SIZE);
+    if id != key.id() {
+        return Err(EncryptionError::WrongKey);
+    }
+    let (salt, rest) = rest.split_at(SALT_SIZE);
+    let (ciphertext, tag) = rest.split_at(rest.len() - TAG_SIZE);
+    symm::decrypt_aead(
+        Cipher::aes_256_gcm(),
+        &key.derive(salt),
+        Some(&NONCE),
+        &[],
+        ciphertext,
+        tag,
+    )
+    .map_err(|_| EncryptionError::Unauthentic)
+}
+
+/// Rewrite every data file (in the keyspaces and the snapshots) and the write-ahead log in
+/// `datadir`, decrypting them with `old` and encrypting them with `new` (or leaving them
+/// unencrypted). Files that are already encrypted with `new` are skipped, so an interrupted
+/// run can simply be repeated. Returns the number of files that were rewritten
+///
+/// The server must not be running
+pub fn rekey(
+    datadir: impl AsRef<Path>,
+    old: Option<&Key>,
+    new: Option<&Key>,
+) -> StorageEngineResult<usize> {
+    let datadir = datadir.as_ref();
+    let mut rewritten = 0;
+    let trees = verify::trees(datadir).map_err_context("listing the data directory")?;
+    for root in trees {
+        let entries =
+            os::rlistdir(&root).map_err_context(format!("listing {}", root.to_string_lossy()))?;
+        for entry in entries {
+            match entry {
+                // skip temporary files left over by an interrupted flush
+                EntryKind::File(path) if !path.ends_with('_') => {
+                    rewritten += self::rekey_file(Path::new(&path), old, new)? as usize;
+                }
+                _ => {}
+            }
+        }
+    }
+    for log in ["WAL.old", "WAL"] {
+        rewritten += wal::rekey(&datadir.join(log), old, new)? as usize;
+    }
+    Ok(rewritten)
+}
+
+/// Rewrite a data file. Returns false if it is already encrypted with `new`
+fn rekey_file(path: &Path, old: Option<&Key>, new: Option<&Key>) -> StorageEngineResult<bool> {
+    let name = || path.to_string_lossy().to_string();
+    let data = fs::read(path).map_err_context(format!("reading {}", name()))?;
+    if header::has_header(&data) {
+        let up_to_date = match new {
+            Some(new) => {
+                header::is_encrypted(&data)
+                    && header::payload(&data)
+                        .is_ok_and(|sealed| self::sealed_with(sealed) == Some(&new.id[..]))
+            }
+            None => !header::is_encrypted(&data),
+        };
+        if up_to_date {
+            return Ok(false);
+        }
+    }
+    let payload = header::open(&data, old).map_err(|e| match e {
+        header::HeaderError::Encryption(e) => StorageEngineError::Encryption(name(), e),
+        e => StorageEngineError::BadHeader(name(), e),
+    })?;
+    let mut file = Cursor::new(Vec::with_capacity(data.len() + SEAL_OVERHEAD));
+    header::write_with_header(&mut file, new, |w| w.write_all(&payload))
+        .map_err_context(format!("sealing {}", name()))?;
+    // this also breaks any hard links between the data directory and the snapshots
+    self::replace_file(path, file.get_ref())?;
+    Ok(true)
+}
+
+/// Atomically replace the file at `path` with `data`
+pub(super) fn replace_file(path: &Path, data: &[u8]) -> StorageEngineResult<()> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push("_");
+    let name = path.to_string_lossy();
+    File::create(&tmp_path)
+        .and_then(|mut f| f.write_all(data).and_then(|_| f.sync_all()))
+        .and_then(|_| fs::rename(&tmp_path, path))
+        .map_err_context(format!("replacing {name}"))
+}
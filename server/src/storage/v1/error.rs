@@ -24,7 +24,12 @@
  *
 */
 
-use {crate::corestore::memstore::ObjectID, core::fmt, std::io::Error as IoError};
+use {
+    super::{encryption::EncryptionError, header::HeaderError},
+    crate::corestore::memstore::ObjectID,
+    core::fmt,
+    std::io::Error as IoError,
+};
 
 pub type StorageEngineResult<T> = Result<T, StorageEngineError>;
 
@@ -49,6 +54,10 @@ pub enum StorageEngineError {
     CorruptedFile(String),
     /// The file contains bad metadata
     BadMetadata(String),
+    /// The file failed the checks in its header
+    BadHeader(String, HeaderError),
+    /// The file couldn't be decrypted
+    Encryption(String, EncryptionError),
 }
 
 impl StorageEngineError {
@@ -85,6 +94,8 @@ impl fmt::Display for StorageEngineError {
             Self::IoErrorExtra(ioe, extra) => write!(f, "I/O error while {extra}: {ioe}"),
             Self::CorruptedFile(cfile) => write!(f, "file `{cfile}` is corrupted"),
             Self::BadMetadata(file) => write!(f, "bad metadata in file `{file}`"),
+            Self::BadHeader(file, e) => write!(f, "file `{file}` is damaged ({e})"),
+            Self::Encryption(file, e) => write!(f, "can't read file `{file}`: it is {e}"),
         }
     }
 }
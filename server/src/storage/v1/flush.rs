@@ -30,7 +30,11 @@
 //! the table level
 
 use {
-    super::{bytemarks, interface},
+    super::{
+        bytemarks,
+        encryption::{self, Key},
+        header, interface,
+    },
     crate::{
         corestore::{
             map::iter::BorrowedIter,
@@ -54,8 +58,16 @@ pub trait StorageTarget {
     ///
     /// Example cases where this doesn't apply: snapshots
     const SHOULD_UNTRIP_PRELOAD_TRIPSWITCH: bool;
+    /// This storage target is the data directory itself. Tables that haven't changed since
+    /// they were last written here are skipped; other targets hard link their files instead
+    const IS_DATA_DIRECTORY: bool;
     /// The root for this storage target. **Must not be separator terminated!**
     fn root(&self) -> String;
+    /// The key that files are encrypted with, if any. Unchanged tables are hard linked from
+    /// the data directory, so every target must use the same key
+    fn key(&self) -> Option<&Key> {
+        encryption::key()
+    }
     /// Returns the path to the `PRELOAD_` **temporary file** ($ROOT/PRELOAD)
     fn preload_target(&self) -> String {
         let mut p = self.root();
@@ -86,6 +98,26 @@ pub trait StorageTarget {
         p.push('_');
         p
     }
+    /// Returns the path to the table's expiry file. **temporary file**
+    /// ($ROOT/{keyspace}/{table}.ttl_)
+    fn expiry_target(&self, keyspace: &str, table: &str) -> String {
+        let mut p = self.keyspace_target(keyspace);
+        p.push('/');
+        p.push_str(table);
+        p.push_str(interface::EXPIRY_FILE_SUFFIX);
+        p.push('_');
+        p
+    }
+    /// Returns the path to the table's schema file. **temporary file**
+    /// ($ROOT/{keyspace}/{table}.schema_)
+    fn schema_target(&self, keyspace: &str, table: &str) -> String {
+        let mut p = self.keyspace_target(keyspace);
+        p.push('/');
+        p.push_str(table);
+        p.push_str(interface::SCHEMA_FILE_SUFFIX);
+        p.push('_');
+        p
+    }
 }
 
 /// The autoflush target (BGSAVE target)
@@ -94,6 +126,7 @@ pub struct Autoflush;
 impl StorageTarget for Autoflush {
     const NEEDS_TREE_INIT: bool = false;
     const SHOULD_UNTRIP_PRELOAD_TRIPSWITCH: bool = true;
+    const IS_DATA_DIRECTORY: bool = true;
     fn root(&self) -> String {
         String::from(interface::DIR_KSROOT)
     }
@@ -113,6 +146,7 @@ impl<'a> RemoteSnapshot<'a> {
 impl<'a> StorageTarget for RemoteSnapshot<'a> {
     const NEEDS_TREE_INIT: bool = true;
     const SHOULD_UNTRIP_PRELOAD_TRIPSWITCH: bool = false;
+    const IS_DATA_DIRECTORY: bool = false;
     fn root(&self) -> String {
         let mut p = String::from(interface::DIR_RSNAPROOT);
         p.push('/');
@@ -135,6 +169,7 @@ impl LocalSnapshot {
 impl StorageTarget for LocalSnapshot {
     const NEEDS_TREE_INIT: bool = true;
     const SHOULD_UNTRIP_PRELOAD_TRIPSWITCH: bool = false;
+    const IS_DATA_DIRECTORY: bool = false;
     fn root(&self) -> String {
         let mut p = String::from(interface::DIR_SNAPROOT);
         p.push('/');
@@ -179,6 +214,23 @@ pub trait FlushableTable {
     fn write_table_to<W: Write>(&self, writer: &mut W) -> IoResult<()>;
     /// Returns the model code bytemark
     fn model_code(&self) -> u8;
+    /// Returns true if any of the keys in this table have a TTL set
+    fn has_expiry(&self) -> bool;
+    /// Serializes the expiry deadlines of the table and writes them to the provided buffer
+    fn write_expiry_to<W: Write>(&self, writer: &mut W) -> IoResult<()>;
+    /// Returns true if this table has named fields
+    fn has_schema(&self) -> bool;
+    /// Serializes the schema of the table and writes it to the provided buffer
+    fn write_schema_to<W: Write>(&self, writer: &mut W) -> IoResult<()>;
+    /// Returns the number of changes made to the table so far, or `None` if changes aren't
+    /// tracked (in which case the table is always written in full)
+    fn mutation_count(&self) -> Option<u64>;
+    /// Returns true if the table's files in the data directory hold its data as of the
+    /// mutation count `count`
+    fn is_saved_at(&self, count: u64) -> bool;
+    /// Record that the table's files in the data directory hold its data as of the
+    /// mutation count `count`
+    fn mark_saved(&self, count: u64);
 }
 
 impl FlushableTable for Table {
@@ -187,10 +239,30 @@ impl FlushableTable for Table {
     }
     fn write_table_to<W: Write>(&self, writer: &mut W) -> IoResult<()> {
         match self.get_model_ref() {
-            DataModel::KV(ref kve) => super::se::raw_serialize_map(kve.get_inner_ref(), writer),
+            DataModel::KV(ref kve) if kve.has_numeric_type() => super::se::raw_serialize_typed_map(
+                kve.get_inner_ref(),
+                kve.get_type_tuple(),
+                writer,
+            ),
+            DataModel::KV(ref kve) | DataModel::KVOrdered(ref kve) => {
+                super::se::raw_serialize_map(kve.get_inner_ref(), writer)
+            }
             DataModel::KVExtListmap(ref kvl) => {
                 super::se::raw_serialize_list_map(kvl.get_inner_ref(), writer)
             }
+            DataModel::Rowmap(ref rm) => {
+                super::se::raw_serialize_rows(rm.rows().get_inner_ref(), rm.schema(), writer)
+            }
+            DataModel::Hashmap(ref hm) => {
+                super::se::raw_serialize_hashmap(hm.maps().get_inner_ref(), writer)
+            }
+            DataModel::Set(ref set) => super::se::raw_serialize_sets(set.get_inner_ref(), writer),
+            DataModel::SortedSet(ref zset) => {
+                super::se::raw_serialize_sorted_sets(zset.get_inner_ref(), writer)
+            }
+            DataModel::Queue(ref queue) => {
+                super::se::raw_serialize_queues(queue.get_inner_ref(), writer)
+            }
         }
     }
     fn storage_code(&self) -> u8 {
@@ -199,6 +271,51 @@ impl FlushableTable for Table {
     fn model_code(&self) -> u8 {
         self.get_model_code()
     }
+    fn has_expiry(&self) -> bool {
+        match self.get_model_ref() {
+            DataModel::KV(ref kve) | DataModel::KVOrdered(ref kve) => {
+                kve.get_expiry_ref().len() != 0
+            }
+            DataModel::KVExtListmap(_)
+            | DataModel::Rowmap(_)
+            | DataModel::Hashmap(_)
+            | DataModel::Set(_)
+            | DataModel::SortedSet(_)
+            | DataModel::Queue(_) => false,
+        }
+    }
+    fn write_expiry_to<W: Write>(&self, writer: &mut W) -> IoResult<()> {
+        match self.get_model_ref() {
+            DataModel::KV(ref kve) | DataModel::KVOrdered(ref kve) => {
+                super::se::raw_serialize_expiry_map(kve.get_expiry_ref(), writer)
+            }
+            DataModel::KVExtListmap(_)
+            | DataModel::Rowmap(_)
+            | DataModel::Hashmap(_)
+            | DataModel::Set(_)
+            | DataModel::SortedSet(_)
+            | DataModel::Queue(_) => Ok(()),
+        }
+    }
+    fn has_schema(&self) -> bool {
+        self.get_schema().is_some() || self.max_len().is_some()
+    }
+    fn write_schema_to<W: Write>(&self, writer: &mut W) -> IoResult<()> {
+        match (self.get_schema(), self.max_len()) {
+            (Some(schema), _) => super::se::raw_serialize_schema(schema, writer),
+            (None, Some(max_len)) => super::se::raw_serialize_max_len(max_len, writer),
+            (None, None) => Ok(()),
+        }
+    }
+    fn mutation_count(&self) -> Option<u64> {
+        Some(self.mutation_count())
+    }
+    fn is_saved_at(&self, count: u64) -> bool {
+        self.is_saved_at(count)
+    }
+    fn mark_saved(&self, count: u64) {
+        self.mark_saved(count)
+    }
 }
 
 impl FlushableTable for SystemTable {
@@ -208,6 +325,7 @@ impl FlushableTable for SystemTable {
     fn write_table_to<W: Write>(&self, writer: &mut W) -> IoResult<()> {
         match self.get_model_ref() {
             SystemDataModel::Auth(amap) => super::se::raw_serialize_map(amap.as_ref(), writer),
+            SystemDataModel::Grants(gmap) => super::se::raw_serialize_map(gmap.as_ref(), writer),
         }
     }
     fn storage_code(&self) -> u8 {
@@ -216,8 +334,29 @@ impl FlushableTable for SystemTable {
     fn model_code(&self) -> u8 {
         match self.get_model_ref() {
             SystemDataModel::Auth(_) => bytemarks::SYSTEM_TABLE_AUTH,
+            SystemDataModel::Grants(_) => bytemarks::SYSTEM_TABLE_GRANTS,
         }
     }
+    fn has_expiry(&self) -> bool {
+        false
+    }
+    fn write_expiry_to<W: Write>(&self, _: &mut W) -> IoResult<()> {
+        Ok(())
+    }
+    fn has_schema(&self) -> bool {
+        false
+    }
+    fn write_schema_to<W: Write>(&self, _: &mut W) -> IoResult<()> {
+        Ok(())
+    }
+    fn mutation_count(&self) -> Option<u64> {
+        // the system tables are small, so we don't bother tracking them
+        None
+    }
+    fn is_saved_at(&self, _: u64) -> bool {
+        false
+    }
+    fn mark_saved(&self, _: u64) {}
 }
 
 /// Flush the entire **preload + keyspaces + their partmaps**
@@ -267,34 +406,149 @@ pub mod oneshot {
     //! files et al are handled
     //!
     use super::*;
-    use std::fs::{self, File};
+    use std::{
+        fs::{self, File},
+        io::ErrorKind,
+        path::Path,
+    };
 
     #[inline(always)]
     fn cowfile(
         cowfile_name: &str,
-        with_open: impl FnOnce(&mut File) -> IoResult<()>,
+        key: Option<&Key>,
+        with_open: impl FnOnce(&mut header::PayloadWriter<&mut File>) -> IoResult<()>,
     ) -> IoResult<()> {
         let mut f = File::create(cowfile_name)?;
-        with_open(&mut f)?;
+        header::write_with_header(&mut f, key, with_open)?;
         f.sync_all()?;
         fs::rename(&cowfile_name, &cowfile_name[..cowfile_name.len() - 1])
     }
 
-    /// No `partmap` handling. Just flushes the table to the expected location
+    /// No `partmap` handling. Just flushes the table to the expected location. If the table
+    /// hasn't changed since it was last written to the data directory, nothing is written
+    /// to the data directory and other targets get hard links to those files instead
     pub fn flush_table<T: StorageTarget, U: FlushableTable>(
         target: &T,
         tableid: &ObjectID,
         ksid: &ObjectID,
         table: &U,
     ) -> IoResult<()> {
+        // read the count before serializing: a change that races with us will leave the
+        // table dirty for the next run
+        let count = table.mutation_count();
+        if count.is_some_and(|count| table.is_saved_at(count)) {
+            return if T::IS_DATA_DIRECTORY {
+                Ok(())
+            } else {
+                self::link_table(target, tableid, ksid, table)
+            };
+        }
+        self::write_table(target, tableid, ksid, table)?;
+        if let (true, Some(count)) = (T::IS_DATA_DIRECTORY, count) {
+            table.mark_saved(count);
+        }
+        Ok(())
+    }
+
+    fn write_table<T: StorageTarget, U: FlushableTable>(
+        target: &T,
+        tableid: &ObjectID,
+        ksid: &ObjectID,
+        table: &U,
+    ) -> IoResult<()> {
+        // volatile tables lose their data, but not their fields
+        self::flush_table_schema(target, tableid, ksid, table)?;
         if table.is_volatile() {
             // no flushing needed
             Ok(())
         } else {
             let path = unsafe { target.table_target(ksid.as_str(), tableid.as_str()) };
-            cowfile(&path, |file| {
+            cowfile(&path, target.key(), |file| {
                 super::interface::serialize_table_into_slow_buffer(file, table)
+            })?;
+            self::flush_table_expiry(target, tableid, ksid, table)
+        }
+    }
+
+    /// Hard link the files of an unchanged table from the data directory. Files are only
+    /// ever replaced by a rename there, so the linked files never change underneath the
+    /// target. If linking isn't possible, the table is written out in full
+    fn link_table<T: StorageTarget, U: FlushableTable>(
+        target: &T,
+        tableid: &ObjectID,
+        ksid: &ObjectID,
+        table: &U,
+    ) -> IoResult<()> {
+        let (ks, tbl) = unsafe { (ksid.as_str(), tableid.as_str()) };
+        let mut files = vec![(
+            Autoflush.schema_target(ks, tbl),
+            target.schema_target(ks, tbl),
+        )];
+        if !table.is_volatile() {
+            files.push((
+                Autoflush.table_target(ks, tbl),
+                target.table_target(ks, tbl),
+            ));
+            files.push((
+                Autoflush.expiry_target(ks, tbl),
+                target.expiry_target(ks, tbl),
+            ));
+        }
+        for (from, to) in files {
+            // strip the trailing `_`: we want the actual files, not the temporary ones
+            let (from, to) = (&from[..from.len() - 1], &to[..to.len() - 1]);
+            match fs::hard_link(from, to) {
+                Ok(()) => {}
+                // the table has no expiry or schema file
+                Err(_) if !Path::new(from).exists() => {}
+                Err(e) => {
+                    log::warn!("Failed to link `{from}` into snapshot, writing table: {e}");
+                    return self::write_table(target, tableid, ksid, table);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Flushes the expiry deadlines for a table. If the table has no keys with a TTL, any
+    /// stale expiry file is removed instead
+    pub fn flush_table_expiry<T: StorageTarget, U: FlushableTable>(
+        target: &T,
+        tableid: &ObjectID,
+        ksid: &ObjectID,
+        table: &U,
+    ) -> IoResult<()> {
+        let path = unsafe { target.expiry_target(ksid.as_str(), tableid.as_str()) };
+        if table.has_expiry() {
+            cowfile(&path, target.key(), |file| {
+                super::interface::serialize_expiry_into_slow_buffer(file, table)
             })
+        } else {
+            match fs::remove_file(&path[..path.len() - 1]) {
+                Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    /// Flushes the schema for a table, if it has one. Otherwise, any stale schema file (like
+    /// one that was left behind by a model of the same name) is removed
+    pub fn flush_table_schema<T: StorageTarget, U: FlushableTable>(
+        target: &T,
+        tableid: &ObjectID,
+        ksid: &ObjectID,
+        table: &U,
+    ) -> IoResult<()> {
+        let path = unsafe { target.schema_target(ksid.as_str(), tableid.as_str()) };
+        if table.has_schema() {
+            cowfile(&path, target.key(), |file| {
+                super::interface::serialize_schema_into_slow_buffer(file, table)
+            })
+        } else {
+            match fs::remove_file(&path[..path.len() - 1]) {
+                Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            }
         }
     }
 
@@ -321,7 +575,7 @@ pub mod oneshot {
         K: FlushableKeyspace<Tbl, U>,
     {
         let path = unsafe { target.partmap_target(ksid.as_str()) };
-        cowfile(&path, |file| {
+        cowfile(&path, target.key(), |file| {
             super::interface::serialize_partmap_into_slow_buffer(file, keyspace)
         })
     }
@@ -329,7 +583,7 @@ pub mod oneshot {
     // Flush the `PRELOAD`
     pub fn flush_preload<T: StorageTarget>(target: &T, store: &Memstore) -> IoResult<()> {
         let preloadtmp = target.preload_target();
-        cowfile(&preloadtmp, |file| {
+        cowfile(&preloadtmp, target.key(), |file| {
             super::interface::serialize_preload_into_slow_buffer(file, store)
         })
     }
@@ -0,0 +1,212 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # File headers
+//!
+//! Every data file written by the storage engine (tables along with their expiry and schema
+//! files, `PARTMAP`s and the `PRELOAD`) starts with a fixed size header:
+//! ```text
+//! [4B: Magic "SKYF"][1B: Format version][1B: Flags][2B: Reserved][8B: Payload length][4B: Payload CRC-32]
+//! ```
+//! The length and the checksum are little endian. Files written by older versions have no
+//! header: they are still read, but can't be checked.
+//!
+//! If the [`FLAG_ENCRYPTED`] flag is set, the payload is sealed (see the [`encryption`]
+//! module). The length and the checksum are those of the sealed payload, so that files can be
+//! checked without the key
+//!
+//! [`encryption`]: super::encryption
+
+use {
+    super::{
+        checksum::Crc32,
+        encryption::{self, EncryptionError, Key, Sealer},
+    },
+    crate::IoResult,
+    core::fmt,
+    std::{
+        borrow::Cow,
+        io::{Seek, SeekFrom, Write},
+    },
+};
+
+/// The size of the header
+pub const HEADER_SIZE: usize = 20;
+/// The version of the on-disk format of the payload
+pub const FORMAT_VERSION: u8 = 1;
+/// The payload is encrypted
+pub const FLAG_ENCRYPTED: u8 = 0b1;
+const MAGIC: [u8; 4] = *b"SKYF";
+
+#[derive(Debug, PartialEq)]
+pub enum HeaderError {
+    /// The file is shorter or longer than its header says
+    BadLength,
+    /// The payload doesn't match its checksum
+    ChecksumMismatch,
+    /// The file was written by a newer version
+    UnknownVersion(u8),
+    /// The payload couldn't be decrypted
+    Encryption(EncryptionError),
+}
+
+impl fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadLength => write!(f, "truncated or has trailing data"),
+            Self::ChecksumMismatch => write!(f, "checksum mismatch"),
+            Self::UnknownVersion(v) => write!(f, "unknown format version {v}"),
+            Self::Encryption(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+/// Returns true if the file starts with a header
+pub fn has_header(file: &[u8]) -> bool {
+    file.starts_with(&MAGIC)
+}
+
+/// Returns true if the file has a header and its payload is encrypted
+pub fn is_encrypted(file: &[u8]) -> bool {
+    has_header(file) && file.len() >= HEADER_SIZE && file[5] & FLAG_ENCRYPTED != 0
+}
+
+/// Check the header of a file and return its payload. Files without a header are returned
+/// as is
+pub fn payload(file: &[u8]) -> Result<&[u8], HeaderError> {
+    if !has_header(file) {
+        return Ok(file);
+    }
+    if file.len() < HEADER_SIZE {
+        return Err(HeaderError::BadLength);
+    }
+    let (header, payload) = file.split_at(HEADER_SIZE);
+    if header[4] != FORMAT_VERSION {
+        return Err(HeaderError::UnknownVersion(header[4]));
+    }
+    let len = u64::from_le_bytes(header[8..16].try_into().unwrap());
+    let crc = u32::from_le_bytes(header[16..20].try_into().unwrap());
+    if len != payload.len() as u64 {
+        return Err(HeaderError::BadLength);
+    }
+    let mut hasher = Crc32::new();
+    hasher.update(payload);
+    if hasher.finish() != crc {
+        return Err(HeaderError::ChecksumMismatch);
+    }
+    Ok(payload)
+}
+
+/// Check the header of a file and return its payload, decrypting it with `key` if it is
+/// encrypted
+pub fn open<'a>(file: &'a [u8], key: Option<&Key>) -> Result<Cow<'a, [u8]>, HeaderError> {
+    let payload = payload(file)?;
+    if is_encrypted(file) {
+        encryption::open(key, payload)
+            .map(Cow::Owned)
+            .map_err(HeaderError::Encryption)
+    } else {
+        Ok(Cow::Borrowed(payload))
+    }
+}
+
+/// A writer that keeps track of the length and the checksum of everything written through it
+pub struct ChecksumWriter<W> {
+    inner: W,
+    len: u64,
+    crc: Crc32,
+}
+
+impl<W: Write> Write for ChecksumWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> IoResult<usize> {
+        let written = self.inner.write(buf)?;
+        self.crc.update(&buf[..written]);
+        self.len += written as u64;
+        Ok(written)
+    }
+    fn flush(&mut self) -> IoResult<()> {
+        self.inner.flush()
+    }
+}
+
+/// A writer for the payload of a file, that seals everything written through it if the file
+/// is encrypted
+pub struct PayloadWriter<W> {
+    inner: ChecksumWriter<W>,
+    sealer: Option<Sealer>,
+}
+
+impl<W: Write> Write for PayloadWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> IoResult<usize> {
+        match self.sealer {
+            Some(ref mut sealer) => {
+                sealer.seal(&mut self.inner, buf)?;
+                Ok(buf.len())
+            }
+            None => self.inner.write(buf),
+        }
+    }
+    fn flush(&mut self) -> IoResult<()> {
+        self.inner.flush()
+    }
+}
+
+/// Write a file with a header, encrypting the payload with `key` if one is given. The payload
+/// is streamed into the file after some space for the header, which is filled in once the
+/// payload is complete
+pub fn write_with_header<W: Write + Seek>(
+    file: &mut W,
+    key: Option<&Key>,
+    with_payload: impl FnOnce(&mut PayloadWriter<&mut W>) -> IoResult<()>,
+) -> IoResult<()> {
+    file.write_all(&[0; HEADER_SIZE])?;
+    let mut inner = ChecksumWriter {
+        inner: &mut *file,
+        len: 0,
+        crc: Crc32::new(),
+    };
+    let sealer = match key {
+        Some(key) => Some(Sealer::begin(key, &mut inner)?),
+        None => None,
+    };
+    let mut writer = PayloadWriter { inner, sealer };
+    with_payload(&mut writer)?;
+    let PayloadWriter { mut inner, sealer } = writer;
+    if let Some(sealer) = sealer {
+        sealer.finish(&mut inner)?;
+    }
+    let (len, crc) = (inner.len, inner.crc.finish());
+    let mut header = [0; HEADER_SIZE];
+    header[..4].copy_from_slice(&MAGIC);
+    header[4] = FORMAT_VERSION;
+    if key.is_some() {
+        header[5] = FLAG_ENCRYPTED;
+    }
+    header[8..16].copy_from_slice(&len.to_le_bytes());
+    header[16..].copy_from_slice(&crc.to_le_bytes());
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(&header)
+}
@@ -48,6 +48,12 @@ pub const DIR_SNAPROOT: &str = "data/snaps";
 pub const DIR_RSNAPROOT: &str = "data/rsnap";
 pub const DIR_BACKUPS: &str = "data/backups";
 pub const DIR_ROOT: &str = "data";
+/// The directory that archives of spaces and models are written to and read from
+pub const DIR_ARCHIVES: &str = "data/archives";
+/// The suffix for the file holding the expiry deadlines of a table
+pub const EXPIRY_FILE_SUFFIX: &str = ".ttl";
+/// The suffix for the file holding the fields of a table with named fields
+pub const SCHEMA_FILE_SUFFIX: &str = ".schema";
 
 /// Creates the directories for the keyspaces
 pub fn create_tree<T: StorageTarget>(target: &T, memroot: &Memstore) -> IoResult<()> {
@@ -135,8 +141,14 @@ pub fn cleanup_tree(memroot: &Memstore) -> IoResult<()> {
             // in the list of directories we collected, remove PARTMAP because we should NOT
             // delete it
             dir_tbls.remove("PARTMAP");
-            // find what tables we should remove
-            let tables_to_remove = dir_tbls.difference(&tables);
+            // find what tables we should remove (along with their expiry and schema files)
+            let tables_to_remove = dir_tbls.iter().filter(|file| {
+                !tables.contains(
+                    file.strip_suffix(EXPIRY_FILE_SUFFIX)
+                        .or_else(|| file.strip_suffix(SCHEMA_FILE_SUFFIX))
+                        .unwrap_or(file.as_str()),
+                )
+            });
             for removed_table in tables_to_remove {
                 let fpath = concat_path!(&ks_path, removed_table);
                 fs::remove_file(&fpath)?;
@@ -159,6 +171,28 @@ pub fn serialize_table_into_slow_buffer<T: Write, U: FlushableTable>(
     Ok(())
 }
 
+/// Same as [`serialize_table_into_slow_buffer`], but writes the table's expiry deadlines
+pub fn serialize_expiry_into_slow_buffer<T: Write, U: FlushableTable>(
+    buffer: &mut T,
+    writable_item: &U,
+) -> IoResult<()> {
+    let mut buffer = BufWriter::new(buffer);
+    writable_item.write_expiry_to(&mut buffer)?;
+    buffer.flush()?;
+    Ok(())
+}
+
+/// Same as [`serialize_table_into_slow_buffer`], but for the table's schema
+pub fn serialize_schema_into_slow_buffer<T: Write, U: FlushableTable>(
+    buffer: &mut T,
+    writable_item: &U,
+) -> IoResult<()> {
+    let mut buffer = BufWriter::new(buffer);
+    writable_item.write_schema_to(&mut buffer)?;
+    buffer.flush()?;
+    Ok(())
+}
+
 pub fn serialize_partmap_into_slow_buffer<T, U, Tbl, K>(buffer: &mut T, ks: &K) -> IoResult<()>
 where
     T: Write,
@@ -35,31 +35,53 @@
 
 use {
     crate::storage::v1::SharedSlice,
-    core::{mem, ptr, slice},
+    core::{cell::Cell, mem, ptr, slice},
 };
 
 const SIZE_64BIT: usize = mem::size_of::<u64>();
 const SIZE_128BIT: usize = SIZE_64BIT * 2;
 
-/// This contains the fn ptr to decode bytes wrt to the host's endian. For example, if you're on an LE machine and
-/// you're reading data from a BE machine, then simply set the endian to big. This only affects the first read and not
-/// subsequent ones (unless you switch between machines of different endian, obviously)
-static mut NATIVE_ENDIAN_READER: unsafe fn(*const u8) -> usize = super::de::transmute_len;
+/// The signature of the functions that decode sizes
+type EndianReader = unsafe fn(*const u8) -> usize;
 
-/// Use this to set the current endian to LE.
-///
-/// ## Safety
-/// Make sure this is run from a single thread only! If not, good luck
-pub(super) unsafe fn endian_set_little() {
-    NATIVE_ENDIAN_READER = super::de::transmute_len_le;
+thread_local! {
+    /// This contains the fn ptr to decode bytes wrt to the host's endian. For example, if you're on an LE machine and
+    /// you're reading data from a BE machine, then simply set the endian to big. This only affects the first read and not
+    /// subsequent ones (unless you switch between machines of different endian, obviously)
+    ///
+    /// This is per thread so that data from a machine with a different endian (like an archive being imported) can be
+    /// read without affecting what other threads are reading
+    static NATIVE_ENDIAN_READER: Cell<EndianReader> = const { Cell::new(super::de::transmute_len) };
 }
 
-/// Use this to set the current endian to BE.
-///
-/// ## Safety
-/// Make sure this is run from a single thread only! If not, good luck
-pub(super) unsafe fn endian_set_big() {
-    NATIVE_ENDIAN_READER = super::de::transmute_len_be;
+/// Use this to set the current endian to LE (for the current thread)
+pub(super) fn endian_set_little() {
+    NATIVE_ENDIAN_READER.with(|reader| reader.set(super::de::transmute_len_le));
+}
+
+/// Use this to set the current endian to BE (for the current thread)
+pub(super) fn endian_set_big() {
+    NATIVE_ENDIAN_READER.with(|reader| reader.set(super::de::transmute_len_be));
+}
+
+/// Run `f` with the endian set to big (or little) for the current thread, and then go back to
+/// the endian that was set before
+pub(super) fn with_endian<T>(big: bool, f: impl FnOnce() -> T) -> T {
+    let previous = NATIVE_ENDIAN_READER.with(Cell::get);
+    if big {
+        self::endian_set_big();
+    } else {
+        self::endian_set_little();
+    }
+    let ret = f();
+    NATIVE_ENDIAN_READER.with(|reader| reader.set(previous));
+    ret
+}
+
+/// Decode a size at `ptr` in the current endian
+#[inline(always)]
+unsafe fn read_size(ptr: *const u8) -> usize {
+    NATIVE_ENDIAN_READER.with(|reader| reader.get()(ptr))
 }
 
 /// A raw slice iterator by using raw pointers
@@ -105,7 +127,7 @@ impl<'a> RawSliceIter<'a> {
         } else {
             unsafe {
                 // sweet, something is left
-                let l = NATIVE_ENDIAN_READER(self.cursor);
+                let l = self::read_size(self.cursor);
                 // now forward the cursor
                 self.incr_cursor_by(SIZE_64BIT);
                 Some(l)
@@ -130,9 +152,9 @@ impl<'a> RawSliceIter<'a> {
             None
         } else {
             unsafe {
-                let v1 = NATIVE_ENDIAN_READER(self.cursor);
+                let v1 = self::read_size(self.cursor);
                 self.incr_cursor_by(SIZE_64BIT);
-                let v2 = NATIVE_ENDIAN_READER(self.cursor);
+                let v2 = self::read_size(self.cursor);
                 self.incr_cursor_by(SIZE_64BIT);
                 Some((v1, v2))
             }
@@ -210,7 +232,7 @@ impl<'a> RawSliceIterBorrowed<'a> {
             None
         } else {
             unsafe {
-                let size = NATIVE_ENDIAN_READER(self.cursor);
+                let size = self::read_size(self.cursor);
                 self.incr_cursor_by(SIZE_64BIT);
                 Some(size)
             }
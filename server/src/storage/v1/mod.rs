@@ -43,6 +43,11 @@ All sizes are stored in native endian. If a dataset is imported from a system fr
 simply translated into the host's native endian. How everything else is stored is not worth
 discussing here. Byte swaps just need one instruction on most architectures
 
+## Integrity
+
+Every data file starts with a header that holds the format version and a checksum of the rest
+of the file (see the [`header`] module), so damaged files are caught when they're read
+
 ## Safety
 
 > Trust me, all methods are bombingly unsafe. They do such crazy things that you might not
@@ -61,14 +66,21 @@ use {
 #[macro_use]
 mod macros;
 // endof do not mess
+pub mod archive;
 pub mod bytemarks;
+pub mod checksum;
+pub mod dump;
+pub mod encryption;
 pub mod error;
 pub mod flush;
+pub mod header;
 pub mod interface;
 pub mod iter;
 pub mod preload;
 pub mod sengine;
 pub mod unflush;
+pub mod verify;
+pub mod wal;
 // test
 #[cfg(test)]
 mod tests;
@@ -184,11 +196,22 @@ unsafe fn raw_byte_repr<'a, T: 'a>(len: &'a T) -> &'a [u8] {
 
 mod se {
     use super::*;
-    use crate::kvengine::LockedVec;
+    use crate::kvengine::{
+        hashmap::LockedMap,
+        queue::LockedQueue,
+        rowmap::Schema,
+        sets::{LockedSet, LockedSortedSet},
+        types::FieldType,
+        LockedVec,
+    };
     use crate::storage::v1::flush::FlushableKeyspace;
     use crate::storage::v1::flush::FlushableTable;
     use crate::IoResult;
     use core::ops::Deref;
+    use std::{
+        borrow::Cow,
+        io::{Error as IoError, ErrorKind},
+    };
 
     macro_rules! unsafe_sz_byte_repr {
         ($e:expr) => {
@@ -234,6 +257,39 @@ mod se {
         Ok(())
     }
 
+    /// Returns the on-disk form of a value of type `ty`: numbers are stored natively while
+    /// everything else is stored as is
+    fn typed_repr(ty: FieldType, val: &[u8]) -> IoResult<Cow<'_, [u8]>> {
+        if ty.is_numeric() {
+            ty.to_native(val)
+                .map(|native| Cow::Owned(native.to_vec()))
+                .ok_or_else(|| IoError::new(ErrorKind::InvalidData, "value is not a number"))
+        } else {
+            Ok(Cow::Borrowed(val))
+        }
+    }
+
+    /// Serialize a map whose key or value is a number. The layout is the same as that of
+    /// [`raw_serialize_map`], except that numbers are written as 8-byte little-endian values
+    pub fn raw_serialize_typed_map<W: Write>(
+        map: &Coremap<SharedSlice, SharedSlice>,
+        (t_k, t_v): (FieldType, FieldType),
+        w: &mut W,
+    ) -> IoResult<()> {
+        unsafe {
+            w.write_all(unsafe_sz_byte_repr!(map.len()))?;
+            for kv in map.iter() {
+                let k = self::typed_repr(t_k, kv.key())?;
+                let v = self::typed_repr(t_v, kv.value())?;
+                w.write_all(unsafe_sz_byte_repr!(k.len()))?;
+                w.write_all(unsafe_sz_byte_repr!(v.len()))?;
+                w.write_all(&k)?;
+                w.write_all(&v)?;
+            }
+        }
+        Ok(())
+    }
+
     /// Serialize a set and write it to a provided buffer
     pub fn raw_serialize_set<W, K, V>(map: &Coremap<K, V>, w: &mut W) -> IoResult<()>
     where
@@ -281,6 +337,27 @@ mod se {
         }
         Ok(())
     }
+    /// Serialize an expiry map (key -> UNIX ms deadline)
+    /// ```text
+    /// [8B: EXTENT]([8B: KLEN][?B: KEY][8B: DEADLINE (LE)])*
+    /// ```
+    /// Unlike sizes, the deadline is not a pointer-width quantity, so it is always written in
+    /// little endian instead of being squeezed through a `usize`
+    pub fn raw_serialize_expiry_map<W: Write>(
+        map: &Coremap<SharedSlice, u64>,
+        w: &mut W,
+    ) -> IoResult<()> {
+        unsafe {
+            w.write_all(unsafe_sz_byte_repr!(map.len()))?;
+            for kv in map.iter() {
+                let k = kv.key();
+                w.write_all(unsafe_sz_byte_repr!(k.len()))?;
+                w.write_all(k)?;
+                w.write_all(&kv.value().to_le_bytes())?;
+            }
+        }
+        Ok(())
+    }
     pub fn raw_serialize_list_map<W>(
         data: &Coremap<SharedSlice, LockedVec>,
         w: &mut W,
@@ -311,6 +388,163 @@ mod se {
         }
         Ok(())
     }
+    /// Serialize the maps of a hashmap. The layout is the same as that of
+    /// [`raw_serialize_list_map`], with the fields and values of every map written alternately
+    pub fn raw_serialize_hashmap<W: Write>(
+        data: &Coremap<SharedSlice, LockedMap>,
+        w: &mut W,
+    ) -> IoResult<()> {
+        unsafe {
+            w.write_all(unsafe_sz_byte_repr!(data.len()))?;
+            for kv in data.iter() {
+                let k = kv.key();
+                w.write_all(unsafe_sz_byte_repr!(k.len()))?;
+                w.write_all(k)?;
+                let map = kv.value().read();
+                let items: Vec<&SharedSlice> = map
+                    .iter()
+                    .flat_map(|(field, value)| [field, value])
+                    .collect();
+                self::raw_serialize_nested_list(w, &items)?;
+            }
+        }
+        Ok(())
+    }
+    /// Serialize the sets of a set model. The layout is the same as that of
+    /// [`raw_serialize_list_map`]
+    pub fn raw_serialize_sets<W: Write>(
+        data: &Coremap<SharedSlice, LockedSet>,
+        w: &mut W,
+    ) -> IoResult<()> {
+        unsafe {
+            w.write_all(unsafe_sz_byte_repr!(data.len()))?;
+            for kv in data.iter() {
+                let k = kv.key();
+                w.write_all(unsafe_sz_byte_repr!(k.len()))?;
+                w.write_all(k)?;
+                let set = kv.value().read();
+                let members: Vec<&SharedSlice> = set.iter().collect();
+                self::raw_serialize_nested_list(w, &members)?;
+            }
+        }
+        Ok(())
+    }
+    /// Serialize the sets of a sorted set model. The layout is the same as that of
+    /// [`raw_serialize_list_map`], with every member followed by its score as an 8-byte
+    /// little-endian double
+    pub fn raw_serialize_sorted_sets<W: Write>(
+        data: &Coremap<SharedSlice, LockedSortedSet>,
+        w: &mut W,
+    ) -> IoResult<()> {
+        unsafe {
+            w.write_all(unsafe_sz_byte_repr!(data.len()))?;
+            for kv in data.iter() {
+                let k = kv.key();
+                w.write_all(unsafe_sz_byte_repr!(k.len()))?;
+                w.write_all(k)?;
+                let set = kv.value().read();
+                let items: Vec<Cow<'_, [u8]>> = set
+                    .iter()
+                    .flat_map(|(member, score)| {
+                        [
+                            Cow::Borrowed(member.as_ref()),
+                            Cow::Owned(score.to_le_bytes().to_vec()),
+                        ]
+                    })
+                    .collect();
+                self::raw_serialize_nested_list(w, &items)?;
+            }
+        }
+        Ok(())
+    }
+    /// Serialize the queues of a queue model. The layout is the same as that of
+    /// [`raw_serialize_list_map`]. Every list starts with the next reservation ID and the
+    /// number of waiting items (as 8-byte little-endian integers in one element), followed by
+    /// the waiting items and then by every reservation's ID and deadline (again in one
+    /// element) and item
+    pub fn raw_serialize_queues<W: Write>(
+        data: &Coremap<SharedSlice, LockedQueue>,
+        w: &mut W,
+    ) -> IoResult<()> {
+        fn pack(a: u64, b: u64) -> Cow<'static, [u8]> {
+            let mut packed = a.to_le_bytes().to_vec();
+            packed.extend_from_slice(&b.to_le_bytes());
+            Cow::Owned(packed)
+        }
+        unsafe {
+            w.write_all(unsafe_sz_byte_repr!(data.len()))?;
+            for kv in data.iter() {
+                let k = kv.key();
+                w.write_all(unsafe_sz_byte_repr!(k.len()))?;
+                w.write_all(k)?;
+                let queue = kv.value().read();
+                let header = pack(queue.next_id(), queue.ready().len() as u64);
+                let ready = queue
+                    .ready()
+                    .iter()
+                    .map(|item| Cow::Borrowed(item.as_ref()));
+                let reserved = queue.reserved().iter().flat_map(|(id, reservation)| {
+                    [
+                        pack(*id, reservation.deadline),
+                        Cow::Borrowed(reservation.item.as_ref()),
+                    ]
+                });
+                let items: Vec<Cow<'_, [u8]>> =
+                    [header].into_iter().chain(ready).chain(reserved).collect();
+                self::raw_serialize_nested_list(w, &items)?;
+            }
+        }
+        Ok(())
+    }
+    /// Serialize the rows of a rowmap. The layout is the same as that of
+    /// [`raw_serialize_list_map`], except that numbers are written as 8-byte little-endian
+    /// values
+    pub fn raw_serialize_rows<W: Write>(
+        rows: &Coremap<SharedSlice, LockedVec>,
+        schema: &Schema,
+        w: &mut W,
+    ) -> IoResult<()> {
+        unsafe {
+            w.write_all(unsafe_sz_byte_repr!(rows.len()))?;
+            for row in rows.iter() {
+                let k = self::typed_repr(schema.key().ty(), row.key())?;
+                w.write_all(unsafe_sz_byte_repr!(k.len()))?;
+                w.write_all(&k)?;
+                let values = row.value().read();
+                let values = schema
+                    .row_fields()
+                    .iter()
+                    .zip(values.iter())
+                    .map(|(field, value)| self::typed_repr(field.ty(), value))
+                    .collect::<IoResult<Vec<_>>>()?;
+                self::raw_serialize_nested_list(w, &values)?;
+            }
+        }
+        Ok(())
+    }
+    /// Serialize a schema
+    /// ```text
+    /// [8B: EXTENT]([1B: TYPE][8B: NAME EXTENT][?B: NAME])*
+    /// ```
+    pub fn raw_serialize_schema<W: Write>(schema: &Schema, w: &mut W) -> IoResult<()> {
+        unsafe {
+            w.write_all(unsafe_sz_byte_repr!(schema.fields().len()))?;
+            for field in schema.fields() {
+                w.write_all(&[field.ty().code()])?;
+                w.write_all(unsafe_sz_byte_repr!(field.name().len()))?;
+                w.write_all(field.name().as_bytes())?;
+            }
+        }
+        Ok(())
+    }
+    /// Serialize the maximum length of the lists in a capped list model. This is stored in
+    /// place of a schema
+    /// ```text
+    /// [8B: MAX LEN]
+    /// ```
+    pub fn raw_serialize_max_len<W: Write>(max_len: usize, w: &mut W) -> IoResult<()> {
+        unsafe { w.write_all(unsafe_sz_byte_repr!(max_len)) }
+    }
     /// Serialize a `[[u8]]` (i.e a slice of slices)
     pub fn raw_serialize_nested_list<'a, W, T: 'a + ?Sized, U: 'a>(
         w: &mut W,
@@ -342,10 +576,20 @@ mod se {
 mod de {
     use super::iter::{RawSliceIter, RawSliceIterBorrowed};
     use super::{Array, Coremap, Hash, HashSet, SharedSlice};
-    use crate::kvengine::LockedVec;
+    use crate::{
+        auth::grants::Grants,
+        kvengine::{
+            hashmap::{FieldMap, LockedMap},
+            queue::{LockedQueue, Queue, Reservation},
+            rowmap::{Field, Schema},
+            sets::{LockedSet, LockedSortedSet, MemberSet, Score, SortedSet},
+            types::FieldType,
+            LockedVec,
+        },
+    };
     use core::ptr;
     use parking_lot::RwLock;
-    use std::collections::HashMap;
+    use std::collections::{BTreeMap, HashMap};
 
     pub trait DeserializeFrom {
         fn is_expected_len(clen: usize) -> bool;
@@ -375,6 +619,42 @@ mod de {
         }
     }
 
+    impl DeserializeInto for Coremap<SharedSlice, LockedMap> {
+        fn new_empty() -> Self {
+            Coremap::new()
+        }
+        fn from_slice(slice: &[u8]) -> Option<Self> {
+            self::deserialize_hashmap(slice)
+        }
+    }
+
+    impl DeserializeInto for Coremap<SharedSlice, LockedSet> {
+        fn new_empty() -> Self {
+            Coremap::new()
+        }
+        fn from_slice(slice: &[u8]) -> Option<Self> {
+            self::deserialize_sets(slice)
+        }
+    }
+
+    impl DeserializeInto for Coremap<SharedSlice, LockedSortedSet> {
+        fn new_empty() -> Self {
+            Coremap::new()
+        }
+        fn from_slice(slice: &[u8]) -> Option<Self> {
+            self::deserialize_sorted_sets(slice)
+        }
+    }
+
+    impl DeserializeInto for Coremap<SharedSlice, LockedQueue> {
+        fn new_empty() -> Self {
+            Coremap::new()
+        }
+        fn from_slice(slice: &[u8]) -> Option<Self> {
+            self::deserialize_queues(slice)
+        }
+    }
+
     impl<T, U> DeserializeInto for Coremap<T, U>
     where
         T: Hash + Eq + DeserializeFrom,
@@ -410,6 +690,16 @@ mod de {
         }
     }
 
+    impl DeserializeFrom for Grants {
+        fn is_expected_len(_: usize) -> bool {
+            // the encoding is verified once the whole map has been read
+            true
+        }
+        fn from_slice(slice: &[u8]) -> Self {
+            Self::from_raw(slice.to_owned())
+        }
+    }
+
     pub fn deserialize_map_ctype<T, U>(data: &[u8]) -> Option<Coremap<T, U>>
     where
         T: Eq + Hash + DeserializeFrom,
@@ -515,6 +805,100 @@ mod de {
         }
     }
 
+    /// Returns the in-memory form of a value of type `ty`. This is the inverse of
+    /// `se::typed_repr`
+    fn from_typed_repr(ty: FieldType, repr: &[u8]) -> Option<SharedSlice> {
+        if ty.is_numeric() {
+            ty.parse_native(repr)
+        } else {
+            Some(SharedSlice::new(repr))
+        }
+    }
+
+    /// Deserialize a map whose key or value is a number. See `se::raw_serialize_typed_map`
+    /// for the layout
+    pub fn deserialize_typed_map(
+        data: &[u8],
+        (t_k, t_v): (FieldType, FieldType),
+    ) -> Option<Coremap<SharedSlice, SharedSlice>> {
+        let mut rawiter = RawSliceIter::new(data);
+        let len = rawiter.next_64bit_integer_to_usize()?;
+        let map = Coremap::try_with_capacity(len).ok()?;
+        for _ in 0..len {
+            let (lenkey, lenval) = rawiter.next_64bit_integer_pair_to_usize()?;
+            let key = self::from_typed_repr(t_k, rawiter.next_borrowed_slice(lenkey)?)?;
+            let val = self::from_typed_repr(t_v, rawiter.next_borrowed_slice(lenval)?)?;
+            if !map.true_if_insert(key, val) {
+                // duplicates
+                return None;
+            }
+        }
+        if rawiter.end_of_allocation() {
+            Some(map)
+        } else {
+            // someone returned more data
+            None
+        }
+    }
+
+    /// Deserialize the rows of a rowmap. See `se::raw_serialize_rows` for the layout
+    pub fn deserialize_rows(
+        data: &[u8],
+        schema: &Schema,
+    ) -> Option<Coremap<SharedSlice, LockedVec>> {
+        let mut rawiter = RawSliceIter::new(data);
+        let len = rawiter.next_64bit_integer_to_usize()?;
+        let map = Coremap::try_with_capacity(len).ok()?;
+        for _ in 0..len {
+            let keylen = rawiter.next_64bit_integer_to_usize()?;
+            let key =
+                self::from_typed_repr(schema.key().ty(), rawiter.next_borrowed_slice(keylen)?)?;
+            let raw_row = self::deserialize_nested_list(rawiter.get_borrowed_iter())?;
+            if raw_row.len() != schema.row_fields().len() {
+                return None;
+            }
+            let row = schema
+                .row_fields()
+                .iter()
+                .zip(raw_row.iter())
+                .map(|(field, value)| self::from_typed_repr(field.ty(), value))
+                .collect::<Option<Vec<_>>>()?;
+            if !map.true_if_insert(key, RwLock::new(row)) {
+                // duplicates
+                return None;
+            }
+        }
+        if rawiter.end_of_allocation() {
+            Some(map)
+        } else {
+            // someone returned more data
+            None
+        }
+    }
+
+    /// Deserialize an expiry map. See `se::raw_serialize_expiry_map` for the layout
+    pub fn deserialize_expiry_map(data: &[u8]) -> Option<Coremap<SharedSlice, u64>> {
+        let mut rawiter = RawSliceIter::new(data);
+        let len = rawiter.next_64bit_integer_to_usize()?;
+        let map = Coremap::try_with_capacity(len).ok()?;
+        for _ in 0..len {
+            let keylen = rawiter.next_64bit_integer_to_usize()?;
+            let key = rawiter.next_owned_data(keylen)?;
+            let deadline = rawiter.next_borrowed_slice(8)?;
+            let deadline = u64::from_le_bytes(deadline.try_into().ok()?);
+            if !map.true_if_insert(key, deadline) {
+                // duplicates
+                return None;
+            }
+        }
+        if rawiter.end_of_allocation() {
+            Some(map)
+        } else {
+            // someone returned more data
+            None
+        }
+    }
+
     pub fn deserialize_list_map(bytes: &[u8]) -> Option<Coremap<SharedSlice, LockedVec>> {
         let mut rawiter = RawSliceIter::new(bytes);
         // get the len
@@ -539,6 +923,184 @@ mod de {
         }
     }
 
+    /// Deserialize the maps of a hashmap. See `se::raw_serialize_hashmap` for the layout
+    pub fn deserialize_hashmap(bytes: &[u8]) -> Option<Coremap<SharedSlice, LockedMap>> {
+        let mut rawiter = RawSliceIter::new(bytes);
+        let len = rawiter.next_64bit_integer_to_usize()?;
+        let map = Coremap::try_with_capacity(len).ok()?;
+        for _ in 0..len {
+            let keylen = rawiter.next_64bit_integer_to_usize()?;
+            let key = rawiter.next_owned_data(keylen)?;
+            let items = self::deserialize_nested_list(rawiter.get_borrowed_iter())?;
+            if items.len() % 2 != 0 {
+                return None;
+            }
+            let mut fields = FieldMap::new();
+            let mut items = items.into_iter();
+            while let (Some(field), Some(value)) = (items.next(), items.next()) {
+                if fields.insert(field, value).is_some() {
+                    // duplicate fields
+                    return None;
+                }
+            }
+            if !map.true_if_insert(key, RwLock::new(fields)) {
+                // duplicates
+                return None;
+            }
+        }
+        if rawiter.end_of_allocation() {
+            Some(map)
+        } else {
+            // someone returned more data
+            None
+        }
+    }
+
+    /// Deserialize the sets of a set model. See `se::raw_serialize_sets` for the layout
+    pub fn deserialize_sets(bytes: &[u8]) -> Option<Coremap<SharedSlice, LockedSet>> {
+        let mut rawiter = RawSliceIter::new(bytes);
+        let len = rawiter.next_64bit_integer_to_usize()?;
+        let map = Coremap::try_with_capacity(len).ok()?;
+        for _ in 0..len {
+            let keylen = rawiter.next_64bit_integer_to_usize()?;
+            let key = rawiter.next_owned_data(keylen)?;
+            let mut set = MemberSet::new();
+            for member in self::deserialize_nested_list(rawiter.get_borrowed_iter())? {
+                if !set.insert(member) {
+                    // duplicate members
+                    return None;
+                }
+            }
+            if !map.true_if_insert(key, RwLock::new(set)) {
+                // duplicates
+                return None;
+            }
+        }
+        if rawiter.end_of_allocation() {
+            Some(map)
+        } else {
+            // someone returned more data
+            None
+        }
+    }
+
+    /// Deserialize the sets of a sorted set model. See `se::raw_serialize_sorted_sets` for
+    /// the layout
+    pub fn deserialize_sorted_sets(bytes: &[u8]) -> Option<Coremap<SharedSlice, LockedSortedSet>> {
+        let mut rawiter = RawSliceIter::new(bytes);
+        let len = rawiter.next_64bit_integer_to_usize()?;
+        let map = Coremap::try_with_capacity(len).ok()?;
+        for _ in 0..len {
+            let keylen = rawiter.next_64bit_integer_to_usize()?;
+            let key = rawiter.next_owned_data(keylen)?;
+            let items = self::deserialize_nested_list(rawiter.get_borrowed_iter())?;
+            if items.len() % 2 != 0 {
+                return None;
+            }
+            let mut set = SortedSet::default();
+            let mut items = items.into_iter();
+            while let (Some(member), Some(score)) = (items.next(), items.next()) {
+                let score = Score::from_le_bytes(score.as_ref().try_into().ok()?)?;
+                if !set.insert(member, score) {
+                    // duplicate members
+                    return None;
+                }
+            }
+            if !map.true_if_insert(key, RwLock::new(set)) {
+                // duplicates
+                return None;
+            }
+        }
+        if rawiter.end_of_allocation() {
+            Some(map)
+        } else {
+            // someone returned more data
+            None
+        }
+    }
+
+    /// Deserialize the queues of a queue model. See `se::raw_serialize_queues` for the layout
+    pub fn deserialize_queues(bytes: &[u8]) -> Option<Coremap<SharedSlice, LockedQueue>> {
+        fn unpack(packed: &[u8]) -> Option<(u64, u64)> {
+            if packed.len() != 16 {
+                return None;
+            }
+            let (a, b) = packed.split_at(8);
+            Some((
+                u64::from_le_bytes(a.try_into().ok()?),
+                u64::from_le_bytes(b.try_into().ok()?),
+            ))
+        }
+        let mut rawiter = RawSliceIter::new(bytes);
+        let len = rawiter.next_64bit_integer_to_usize()?;
+        let map = Coremap::try_with_capacity(len).ok()?;
+        for _ in 0..len {
+            let keylen = rawiter.next_64bit_integer_to_usize()?;
+            let key = rawiter.next_owned_data(keylen)?;
+            let mut items = self::deserialize_nested_list(rawiter.get_borrowed_iter())?.into_iter();
+            let (next_id, ready_len) = unpack(items.next()?.as_ref())?;
+            let ready_len = usize::try_from(ready_len).ok()?;
+            if items.len() < ready_len || (items.len() - ready_len) % 2 != 0 {
+                return None;
+            }
+            let ready = items.by_ref().take(ready_len).collect();
+            let mut reserved = BTreeMap::new();
+            while let (Some(packed), Some(item)) = (items.next(), items.next()) {
+                let (id, deadline) = unpack(packed.as_ref())?;
+                if reserved
+                    .insert(id, Reservation { item, deadline })
+                    .is_some()
+                {
+                    // duplicate reservations
+                    return None;
+                }
+            }
+            let queue = Queue::from_parts(ready, reserved, next_id)?;
+            if !map.true_if_insert(key, RwLock::new(queue)) {
+                // duplicates
+                return None;
+            }
+        }
+        if rawiter.end_of_allocation() {
+            Some(map)
+        } else {
+            // someone returned more data
+            None
+        }
+    }
+
+    /// Deserialize a schema. See `se::raw_serialize_schema` for the layout
+    pub fn deserialize_schema(data: &[u8]) -> Option<Schema> {
+        let mut rawiter = RawSliceIter::new(data);
+        let len = rawiter.next_64bit_integer_to_usize()?;
+        let mut fields = Vec::new();
+        fields.try_reserve(len).ok()?;
+        for _ in 0..len {
+            let ty = FieldType::from_code(rawiter.next_8bit_integer()?)?;
+            let namelen = rawiter.next_64bit_integer_to_usize()?;
+            let name = core::str::from_utf8(rawiter.next_borrowed_slice(namelen)?).ok()?;
+            fields.push(Field::new(name, ty));
+        }
+        if rawiter.end_of_allocation() {
+            Schema::new(fields)
+        } else {
+            // someone returned more data
+            None
+        }
+    }
+
+    /// Deserialize the maximum length of a capped list model. See
+    /// `se::raw_serialize_max_len` for the layout
+    pub fn deserialize_max_len(data: &[u8]) -> Option<usize> {
+        let mut rawiter = RawSliceIter::new(data);
+        let max_len = rawiter.next_64bit_integer_to_usize()?;
+        if rawiter.end_of_allocation() {
+            Some(max_len)
+        } else {
+            None
+        }
+    }
+
     /// Deserialize a nested list: `[EXTENT]([EL_EXT][EL])*`
     ///
     pub fn deserialize_nested_list(mut iter: RawSliceIterBorrowed<'_>) -> Option<Vec<SharedSlice>> {
@@ -73,23 +73,21 @@ pub(super) fn raw_generate_preload<W: Write>(w: &mut W, store: &Memstore) -> IoR
 }
 
 /// Reads the preload file and returns a set
-pub(super) fn read_preload_raw(preload: Vec<u8>) -> StorageEngineResult<HashSet<ObjectID>> {
+pub(super) fn read_preload_raw(preload: &[u8]) -> StorageEngineResult<HashSet<ObjectID>> {
     if preload.len() < 16 {
         // nah, this is a bad disk file
         return Err(StorageEngineError::corrupted_preload());
     }
     // first read in the meta segment
-    unsafe {
-        let meta_segment: u8 = ptr::read(preload.as_ptr());
-        match meta_segment {
-            META_SEGMENT_BE => {
-                super::iter::endian_set_big();
-            }
-            META_SEGMENT_LE => {
-                super::iter::endian_set_little();
-            }
-            _ => return Err(StorageEngineError::BadMetadata("preload".into())),
+    let meta_segment: u8 = unsafe { ptr::read(preload.as_ptr()) };
+    match meta_segment {
+        META_SEGMENT_BE => {
+            super::iter::endian_set_big();
         }
+        META_SEGMENT_LE => {
+            super::iter::endian_set_little();
+        }
+        _ => return Err(StorageEngineError::BadMetadata("preload".into())),
     }
     // all checks complete; time to decode
     super::de::deserialize_set_ctype(&preload[1..])
@@ -28,13 +28,27 @@ use {
     self::queue::Queue,
     super::interface::{DIR_RSNAPROOT, DIR_SNAPROOT},
     crate::{
-        corestore::{iarray::IArray, lazy::Lazy, lock::QuickLock, memstore::Memstore},
+        corestore::{
+            iarray::IArray,
+            lazy::Lazy,
+            lock::{QLGuard, QuickLock},
+            memstore::Memstore,
+            Corestore,
+        },
         storage::v1::flush::{LocalSnapshot, RemoteSnapshot},
+        util,
     },
     chrono::prelude::Utc,
     core::{fmt, str},
     regex::Regex,
-    std::{collections::HashSet, fs, io::Error as IoError, path::Path, sync::Arc},
+    std::{
+        collections::HashSet,
+        fs,
+        io::Error as IoError,
+        path::{self, Component, Path, PathBuf},
+        sync::Arc,
+        time::UNIX_EPOCH,
+    },
 };
 
 type QStore = IArray<[String; 64]>;
@@ -48,6 +62,16 @@ pub static SNAP_MATCH: Lazy<Regex, fn() -> Regex> = Lazy::new(|| {
     Regex::new("^\\d{4}(0[1-9]|1[012])(0[1-9]|[12][0-9]|3[01])(-)(?:(?:([01]?\\d|2[0-3]))?([0-5]?\\d))?([0-5]?\\d)$").unwrap()
 });
 
+/// Check that `name` names a single snapshot: exactly one plain path component, so that it can
+/// neither point at a snapshot directory itself nor into a snapshot
+pub fn is_snapshot_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    ) && !name.chars().any(path::is_separator)
+}
+
 #[derive(Debug)]
 pub enum SnapshotEngineError {
     Io(IoError),
@@ -82,6 +106,12 @@ impl fmt::Display for SnapshotEngineError {
     }
 }
 
+/// A lock on the queue that a snapshot belongs to
+enum SnapshotLock<'a> {
+    Local(QLGuard<'a, Queue>),
+    Remote(QLGuard<'a, HashSet<Box<[u8]>>>),
+}
+
 /// The snapshot engine
 #[derive(Debug)]
 pub struct SnapshotEngine {
@@ -99,6 +129,19 @@ pub enum SnapshotActionResult {
     Disabled,
     Failure,
     AlreadyExists,
+    NotFound,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+/// A snapshot on disk, as listed by `LSSNAP`
+pub struct SnapshotInfo {
+    pub name: String,
+    /// true for snapshots in `data/rsnap`
+    pub remote: bool,
+    /// the last modification time of the snapshot directory, in seconds since the epoch
+    pub modified: u64,
+    /// the total size of the files in the snapshot, in bytes
+    pub size: u64,
 }
 
 impl SnapshotEngine {
@@ -225,6 +268,117 @@ impl SnapshotEngine {
             SnapshotActionResult::Disabled
         }
     }
+    /// List the local and then the remote snapshots, each sorted by name
+    pub async fn list(&self) -> SnapshotResult<Vec<SnapshotInfo>> {
+        tokio::task::spawn_blocking(|| {
+            let mut snapshots = Self::list_dir(DIR_SNAPROOT, false)?;
+            snapshots.extend(Self::list_dir(DIR_RSNAPROOT, true)?);
+            Ok(snapshots)
+        })
+        .await
+        .expect("lssnap thread panicked")
+    }
+    fn list_dir(dir: &str, remote: bool) -> SnapshotResult<Vec<SnapshotInfo>> {
+        let mut snapshots = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+            let modified = entry
+                .metadata()?
+                .modified()?
+                .duration_since(UNIX_EPOCH)
+                .map(|since| since.as_secs())
+                .unwrap_or(0);
+            snapshots.push(SnapshotInfo {
+                name: entry.file_name().to_string_lossy().into_owned(),
+                remote,
+                modified,
+                size: util::os::dirsize(entry.path())?,
+            });
+        }
+        snapshots.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(snapshots)
+    }
+    /// Lock the queue that the local or remote snapshot `name` belongs to (local snapshots are
+    /// looked up first) and get its path
+    fn lock_snapshot(
+        &self,
+        name: &str,
+    ) -> Result<(PathBuf, SnapshotLock<'_>), SnapshotActionResult> {
+        if !is_snapshot_name(name) {
+            return Err(SnapshotActionResult::NotFound);
+        }
+        let local = Path::new(DIR_SNAPROOT).join(name);
+        let (path, lock) = if SNAP_MATCH.is_match(name) && local.is_dir() {
+            let queue = self.local_queue.try_lock();
+            (local, queue.map(SnapshotLock::Local))
+        } else {
+            let queue = self.remote_queue.try_lock();
+            (
+                Path::new(DIR_RSNAPROOT).join(name),
+                queue.map(SnapshotLock::Remote),
+            )
+        };
+        match lock {
+            Some(_) if !path.is_dir() => Err(SnapshotActionResult::NotFound),
+            Some(lock) => Ok((path, lock)),
+            None => Err(SnapshotActionResult::Busy),
+        }
+    }
+    /// Delete the local or remote snapshot `name`. Returns either of:
+    /// - `Ok`
+    /// - `Failure`
+    /// - `Busy`
+    /// - `NotFound`
+    pub async fn rmsnap(&self, name: &str) -> SnapshotActionResult {
+        let (path, lock) = match self.lock_snapshot(name) {
+            Ok(found) => found,
+            Err(e) => return e,
+        };
+        let ret = tokio::task::spawn_blocking(move || fs::remove_dir_all(path))
+            .await
+            .expect("rmsnap thread panicked");
+        if let Err(e) = ret {
+            log::error!("Failed to remove snapshot `{name}`: {e}");
+            return SnapshotActionResult::Failure;
+        }
+        match lock {
+            SnapshotLock::Local(mut queue) => queue.remove(name),
+            SnapshotLock::Remote(mut queue) => {
+                queue.remove(name.as_bytes());
+            }
+        }
+        log::info!("Removed snapshot `{name}`");
+        SnapshotActionResult::Ok
+    }
+    /// Replace the data in `handle` with the local or remote snapshot `name` (see
+    /// [`Corestore::restore_from`]). The snapshot can't be removed until this returns. Returns
+    /// either of:
+    /// - `Ok`
+    /// - `Failure`
+    /// - `Busy`
+    /// - `NotFound`
+    pub async fn restore(&self, name: &str, handle: Corestore) -> SnapshotActionResult {
+        let (path, _lock) = match self.lock_snapshot(name) {
+            Ok(found) => found,
+            Err(e) => return e,
+        };
+        let ret = tokio::task::spawn_blocking(move || handle.restore_from(&path))
+            .await
+            .expect("restore thread panicked");
+        match ret {
+            Ok(()) => {
+                log::info!("Restored snapshot `{name}`");
+                SnapshotActionResult::Ok
+            }
+            Err(e) => {
+                log::error!("Failed to restore snapshot `{name}`: {e}");
+                SnapshotActionResult::Failure
+            }
+        }
+    }
     /// Spawns a blocking task to create a remote snapshot. Returns either of:
     /// - `0` => Okay
     /// - `1` => Error
@@ -321,6 +475,15 @@ mod queue {
         pub fn pop_last(&mut self) -> Option<String> {
             self.queue.pop()
         }
+        /// Remove `item` if it's in the queue
+        pub fn remove(&mut self, item: &str) {
+            if let Some(pos) = self.queue.iter().position(|queued| queued == item) {
+                unsafe {
+                    // SAFETY: `pos` was found in the queue
+                    self.queue.remove(pos);
+                }
+            }
+        }
     }
 
     #[test]
@@ -351,4 +514,32 @@ mod queue {
         assert!(q.add_new(String::from("snap5")).is_none());
         assert!(q.add_new(String::from("snap6")).is_none());
     }
+
+    #[test]
+    fn test_queue_remove() {
+        let mut q = Queue::new(2, false);
+        assert!(q.add_new(String::from("snap1")).is_none());
+        assert!(q.add_new(String::from("snap2")).is_none());
+        q.remove("snap1");
+        q.remove("snap3");
+        // there's room for another snapshot now
+        assert!(q.add_new(String::from("snap3")).is_none());
+        assert_eq!(
+            q.add_new(String::from("snap4")),
+            Some(String::from("snap2"))
+        );
+    }
+}
+
+#[test]
+fn test_snapshot_name() {
+    assert!(is_snapshot_name("20220101-120000"));
+    assert!(is_snapshot_name("myremo"));
+    assert!(!is_snapshot_name(""));
+    assert!(!is_snapshot_name("."));
+    assert!(!is_snapshot_name(".."));
+    assert!(!is_snapshot_name("a/b"));
+    assert!(!is_snapshot_name("a/"));
+    assert!(!is_snapshot_name("./a"));
+    assert!(!is_snapshot_name("/a"));
 }
@@ -53,6 +53,20 @@ fn test_serialize_deserialize_map_with_empty_elements() {
     assert!(cmap.into_iter().all(|(k, v)| de.get(&k).unwrap().eq(&v)));
 }
 
+#[test]
+fn test_ser_de_expiry_map() {
+    let cmap = Coremap::new();
+    cmap.upsert(SharedSlice::from("sayan"), u64::MAX);
+    cmap.upsert(SharedSlice::from(""), 1_650_000_000_000);
+    let mut ser = Vec::new();
+    se::raw_serialize_expiry_map(&cmap, &mut ser).unwrap();
+    let de = de::deserialize_expiry_map(&ser).unwrap();
+    assert_eq!(de.len(), cmap.len());
+    assert!(cmap.into_iter().all(|(k, v)| *de.get(&k).unwrap() == v));
+    // truncated deadline
+    assert!(de::deserialize_expiry_map(&ser[..ser.len() - 1]).is_none());
+}
+
 #[test]
 fn test_ser_de_few_elements() {
     let cmap = Coremap::new();
@@ -204,7 +218,7 @@ mod preload_tests {
         let memstore = Memstore::new_default();
         let mut v = Vec::new();
         preload::raw_generate_preload(&mut v, &memstore).unwrap();
-        let de: Vec<String> = preload::read_preload_raw(v)
+        let de: Vec<String> = preload::read_preload_raw(&v)
             .unwrap()
             .into_iter()
             .map(|each| unsafe { each.as_str().to_owned() })
@@ -423,15 +437,25 @@ mod bytemark_actual_table_restore {
 
 mod flush_routines {
     use crate::{
+        auth::{
+            grants::{Access, Grant, Grants},
+            Grantmap,
+        },
         corestore::{
+            array::Array,
             memstore::{Keyspace, ObjectID},
-            table::{DataModel, Table},
+            table::{DataModel, SystemDataModel, SystemTable, Table},
             SharedSlice,
         },
-        kvengine::LockedVec,
+        kvengine::{
+            rowmap::{Field, Schema},
+            sets::Score,
+            types::FieldType,
+            LockedVec,
+        },
         storage::v1::{bytemarks, flush::Autoflush, Coremap},
     };
-    use std::fs;
+    use std::{fs, ops::Bound};
     #[test]
     fn test_flush_unflush_table_pure_kve() {
         let tbl = Table::new_default_kve();
@@ -463,6 +487,47 @@ mod flush_routines {
         );
     }
 
+    #[test]
+    fn test_flush_unflush_table_pure_kve_with_expiry() {
+        let tbl = Table::new_default_kve();
+        let kve = tbl.get_kvstore().unwrap();
+        kve.set("hello".into(), "world".into()).unwrap();
+        kve.set_with_ttl("session".into(), "token".into(), 100)
+            .unwrap();
+        let deadline = *kve.get_expiry_ref().get("session".as_bytes()).unwrap();
+        let tblid = unsafe { ObjectID::from_slice("mytbl2") };
+        let ksid = unsafe { ObjectID::from_slice("myks2") };
+        // create the temp dir for this test
+        fs::create_dir_all("data/ks/myks2").unwrap();
+        super::flush::oneshot::flush_table(&Autoflush, &tblid, &ksid, &tbl).unwrap();
+        let ret = super::unflush::read_table::<Table>(
+            &ksid,
+            &tblid,
+            false,
+            bytemarks::BYTEMARK_MODEL_KV_BIN_BIN,
+        )
+        .unwrap();
+        let rkve = ret.get_kvstore().unwrap();
+        assert_eq!(rkve.len(), 2);
+        assert_eq!(rkve.get_expiry_ref().len(), 1);
+        assert_eq!(
+            *rkve.get_expiry_ref().get("session".as_bytes()).unwrap(),
+            deadline
+        );
+        // now drop the TTL; the expiry file should be removed on the next flush
+        assert!(kve.persist("session").unwrap());
+        super::flush::oneshot::flush_table(&Autoflush, &tblid, &ksid, &tbl).unwrap();
+        assert!(!std::path::Path::new("data/ks/myks2/mytbl2.ttl").exists());
+        let ret = super::unflush::read_table::<Table>(
+            &ksid,
+            &tblid,
+            false,
+            bytemarks::BYTEMARK_MODEL_KV_BIN_BIN,
+        )
+        .unwrap();
+        assert_eq!(ret.get_kvstore().unwrap().get_expiry_ref().len(), 0);
+    }
+
     #[test]
     fn test_flush_unflush_table_kvext_listmap() {
         let tbl = Table::new_kve_listmap_with_data(Coremap::new(), false, true, true);
@@ -497,6 +562,436 @@ mod flush_routines {
         }
     }
     #[test]
+    fn test_flush_unflush_table_capped_listmap() {
+        let tbl = Table::new_kve_listmap_with_data(Coremap::new(), false, true, true)
+            .with_max_len(10)
+            .unwrap();
+        let tblid = unsafe { ObjectID::from_slice("capped") };
+        let ksid = unsafe { ObjectID::from_slice("mycappedks") };
+        fs::create_dir_all("data/ks/mycappedks").unwrap();
+        super::flush::oneshot::flush_table(&Autoflush, &tblid, &ksid, &tbl).unwrap();
+        let ret = super::unflush::read_table::<Table>(
+            &ksid,
+            &tblid,
+            false,
+            bytemarks::BYTEMARK_MODEL_KV_STR_LIST_STR,
+        )
+        .unwrap();
+        assert_eq!(ret.max_len(), Some(10));
+        assert_eq!(
+            ret.describe_self(),
+            "Keymap { data:(str,list<str>), volatile:false, maxlen:10 }"
+        );
+        // a model that isn't capped doesn't keep the old limit
+        let tbl = Table::new_kve_listmap_with_data(Coremap::new(), false, true, true);
+        super::flush::oneshot::flush_table(&Autoflush, &tblid, &ksid, &tbl).unwrap();
+        assert!(!std::path::Path::new("data/ks/mycappedks/capped.schema").exists());
+        let ret = super::unflush::read_table::<Table>(
+            &ksid,
+            &tblid,
+            false,
+            bytemarks::BYTEMARK_MODEL_KV_STR_LIST_STR,
+        )
+        .unwrap();
+        assert_eq!(ret.max_len(), None);
+    }
+    fn users_schema() -> Schema {
+        Schema::new(vec![
+            Field::new("username", FieldType::String),
+            Field::new("email", FieldType::String),
+            Field::new("avatar", FieldType::Binary),
+        ])
+        .unwrap()
+    }
+    #[test]
+    fn test_flush_unflush_table_rowmap() {
+        let tbl = Table::new_rowmap(users_schema(), false);
+        if let DataModel::Rowmap(rm) = tbl.get_model_ref() {
+            rm.rows().get_inner_ref().upsert(
+                "sayan".into(),
+                LockedVec::new(vec!["sayan@example.com".into(), SharedSlice::new(&[0xFF])]),
+            );
+        } else {
+            panic!("Bad model!");
+        }
+        let tblid = unsafe { ObjectID::from_slice("users") };
+        let ksid = unsafe { ObjectID::from_slice("myrowks") };
+        // create the temp dir for this test
+        fs::create_dir_all("data/ks/myrowks").unwrap();
+        super::flush::oneshot::flush_table(&Autoflush, &tblid, &ksid, &tbl).unwrap();
+        let ret = super::unflush::read_table::<Table>(
+            &ksid,
+            &tblid,
+            false,
+            bytemarks::BYTEMARK_MODEL_ROWMAP,
+        )
+        .unwrap();
+        assert_eq!(ret.get_schema(), Some(&users_schema()));
+        assert_eq!(
+            ret.describe_self(),
+            "Rowmap { data:(username:str,email:str,avatar:binstr), volatile:false }"
+        );
+        if let DataModel::Rowmap(rm) = ret.get_model_ref() {
+            assert_eq!(
+                rm.rows().list_cloned_full(b"sayan").unwrap().unwrap(),
+                vec![
+                    SharedSlice::from("sayan@example.com"),
+                    SharedSlice::new(&[0xFF])
+                ]
+            );
+        } else {
+            panic!("Bad model!");
+        }
+    }
+    #[test]
+    fn test_flush_unflush_table_rowmap_volatile() {
+        // the data is lost but the fields aren't
+        let tbl = Table::new_rowmap(users_schema(), true);
+        let tblid = unsafe { ObjectID::from_slice("users") };
+        let ksid = unsafe { ObjectID::from_slice("myvrowks") };
+        fs::create_dir_all("data/ks/myvrowks").unwrap();
+        super::flush::oneshot::flush_table(&Autoflush, &tblid, &ksid, &tbl).unwrap();
+        assert!(!std::path::Path::new("data/ks/myvrowks/users").exists());
+        let ret = super::unflush::read_table::<Table>(
+            &ksid,
+            &tblid,
+            true,
+            bytemarks::BYTEMARK_MODEL_ROWMAP,
+        )
+        .unwrap();
+        assert!(ret.is_volatile());
+        assert_eq!(ret.get_schema(), Some(&users_schema()));
+    }
+    #[test]
+    fn test_unflush_table_rowmap_bad_row() {
+        let tbl = Table::new_rowmap(users_schema(), false);
+        if let DataModel::Rowmap(rm) = tbl.get_model_ref() {
+            // a row with a missing field
+            rm.rows().get_inner_ref().upsert(
+                "sayan".into(),
+                LockedVec::new(vec!["sayan@example.com".into()]),
+            );
+        }
+        let tblid = unsafe { ObjectID::from_slice("users") };
+        let ksid = unsafe { ObjectID::from_slice("mybadrowks") };
+        fs::create_dir_all("data/ks/mybadrowks").unwrap();
+        super::flush::oneshot::flush_table(&Autoflush, &tblid, &ksid, &tbl).unwrap();
+        assert!(super::unflush::read_table::<Table>(
+            &ksid,
+            &tblid,
+            false,
+            bytemarks::BYTEMARK_MODEL_ROWMAP,
+        )
+        .is_err());
+    }
+    #[test]
+    fn test_flush_unflush_table_typed_kv() {
+        let tbl = Table::new_typed_kve_with_data(
+            Coremap::new(),
+            false,
+            FieldType::UInt64,
+            FieldType::Float64,
+        );
+        let kve = tbl.get_kvstore().unwrap();
+        kve.set("18446744073709551615".into(), "-1.5".into())
+            .unwrap();
+        kve.incr_by("7".into(), b"0.25").unwrap().unwrap();
+        assert!(kve.expire("7", 100).unwrap());
+        let model_code = tbl.get_model_code();
+        assert_eq!(
+            model_code,
+            Table::typed_kv_model_code(FieldType::UInt64, FieldType::Float64)
+        );
+        let tblid = unsafe { ObjectID::from_slice("counters") };
+        let ksid = unsafe { ObjectID::from_slice("mytypedks") };
+        fs::create_dir_all("data/ks/mytypedks").unwrap();
+        super::flush::oneshot::flush_table(&Autoflush, &tblid, &ksid, &tbl).unwrap();
+        // numbers are stored natively: [8B: extent]([8B: KLEN][8B: VLEN][8B: K][8B: V])*
+        let raw = fs::read("data/ks/mytypedks/counters").unwrap();
+        assert_eq!(raw.len(), super::header::HEADER_SIZE + 8 + 2 * 32);
+        let ret = super::unflush::read_table::<Table>(&ksid, &tblid, false, model_code).unwrap();
+        assert_eq!(
+            ret.describe_self(),
+            "Keymap { data:(uint64,float64), volatile:false }"
+        );
+        let rkve = ret.get_kvstore().unwrap();
+        assert_eq!(rkve.len(), 2);
+        assert_eq!(
+            rkve.get_cloned("18446744073709551615").unwrap().unwrap(),
+            "-1.5"
+        );
+        assert_eq!(rkve.get_cloned("7").unwrap().unwrap(), "0.25");
+        assert!(rkve.ttl("7").unwrap().unwrap().is_some());
+        assert!(rkve.set("8".into(), "abc".into()).is_err());
+    }
+    #[test]
+    fn test_flush_unflush_table_hashmap() {
+        let model_code = Table::map_model_code(true, true, false);
+        let tbl = Table::from_model_code(model_code, false).unwrap();
+        if let DataModel::Hashmap(hm) = tbl.get_model_ref() {
+            hm.set_fields(
+                b"sayan",
+                &[
+                    ("email".into(), "a@b".into()),
+                    ("avatar".into(), SharedSlice::new(&[0xFF])),
+                ],
+                |_| {},
+            );
+            hm.set_fields(b"ferris", &[("email".into(), "c@d".into())], |_| {});
+        }
+        let tblid = unsafe { ObjectID::from_slice("users") };
+        let ksid = unsafe { ObjectID::from_slice("mymapks") };
+        fs::create_dir_all("data/ks/mymapks").unwrap();
+        super::flush::oneshot::flush_table(&Autoflush, &tblid, &ksid, &tbl).unwrap();
+        let ret = super::unflush::read_table::<Table>(&ksid, &tblid, false, model_code).unwrap();
+        assert_eq!(
+            ret.describe_self(),
+            "Keymap { data:(str,map<str,binstr>), volatile:false }"
+        );
+        match ret.get_model_ref() {
+            DataModel::Hashmap(hm) => {
+                assert_eq!(hm.len(), 2);
+                assert_eq!(
+                    hm.get_all(b"sayan").unwrap(),
+                    vec![
+                        (SharedSlice::from("avatar"), SharedSlice::new(&[0xFF])),
+                        (SharedSlice::from("email"), SharedSlice::from("a@b"))
+                    ]
+                );
+                assert_eq!(hm.get_field(b"ferris", b"email").unwrap(), "c@d");
+            }
+            _ => panic!("Bad model!"),
+        }
+    }
+    #[test]
+    fn test_unflush_table_hashmap_bad_field() {
+        // the fields are strings, so a binary field makes the file corrupted
+        let tbl =
+            Table::from_model_code(Table::map_model_code(false, false, false), false).unwrap();
+        if let DataModel::Hashmap(hm) = tbl.get_model_ref() {
+            hm.set_fields(
+                b"key",
+                &[(SharedSlice::new(&[0xFF]), "value".into())],
+                |_| {},
+            );
+        }
+        let tblid = unsafe { ObjectID::from_slice("users") };
+        let ksid = unsafe { ObjectID::from_slice("mybadmapks") };
+        fs::create_dir_all("data/ks/mybadmapks").unwrap();
+        super::flush::oneshot::flush_table(&Autoflush, &tblid, &ksid, &tbl).unwrap();
+        let model_code = Table::map_model_code(false, true, false);
+        assert!(super::unflush::read_table::<Table>(&ksid, &tblid, false, model_code).is_err());
+    }
+    #[test]
+    fn test_flush_unflush_table_sets() {
+        let set_code = Table::set_model_code(true, true);
+        let zset_code = Table::sorted_set_model_code(false, false);
+        let sets = Table::from_model_code(set_code, false).unwrap();
+        let zsets = Table::from_model_code(zset_code, false).unwrap();
+        if let DataModel::Set(sets) = sets.get_model_ref() {
+            sets.add_members(b"tags", &["rust".into(), "db".into()], |_| {});
+        }
+        if let DataModel::SortedSet(zsets) = zsets.get_model_ref() {
+            let members = [
+                (SharedSlice::new(&[0xFF]), Score::parse(b"-1.5").unwrap()),
+                ("b".into(), Score::parse(b"2").unwrap()),
+            ];
+            zsets.add_scored(b"board", &members, |_| {});
+        }
+        let ksid = unsafe { ObjectID::from_slice("mysetks") };
+        fs::create_dir_all("data/ks/mysetks").unwrap();
+        let (setid, zsetid) =
+            unsafe { (ObjectID::from_slice("sets"), ObjectID::from_slice("zsets")) };
+        super::flush::oneshot::flush_table(&Autoflush, &setid, &ksid, &sets).unwrap();
+        super::flush::oneshot::flush_table(&Autoflush, &zsetid, &ksid, &zsets).unwrap();
+        let ret = super::unflush::read_table::<Table>(&ksid, &setid, false, set_code).unwrap();
+        assert_eq!(
+            ret.describe_self(),
+            "Keymap { data:(str,set<str>), volatile:false }"
+        );
+        match ret.get_model_ref() {
+            DataModel::Set(sets) => assert_eq!(
+                sets.members(b"tags").unwrap(),
+                vec![SharedSlice::from("db"), SharedSlice::from("rust")]
+            ),
+            _ => panic!("Bad model!"),
+        }
+        let ret = super::unflush::read_table::<Table>(&ksid, &zsetid, false, zset_code).unwrap();
+        assert_eq!(
+            ret.describe_self(),
+            "Keymap { data:(binstr,zset<binstr>), volatile:false }"
+        );
+        match ret.get_model_ref() {
+            DataModel::SortedSet(zsets) => {
+                assert_eq!(zsets.score(b"board", &[0xFF]), Score::parse(b"-1.5"));
+                assert_eq!(zsets.rank(b"board", b"b", false), Some(1));
+            }
+            _ => panic!("Bad model!"),
+        }
+        // a set file can't be read back as a sorted set
+        assert!(super::unflush::read_table::<Table>(&ksid, &setid, false, zset_code).is_err());
+    }
+    #[test]
+    fn test_flush_unflush_table_queue() {
+        let model_code = Table::queue_model_code(true, false);
+        let tbl = Table::from_model_code(model_code, false).unwrap();
+        let reserved = match tbl.get_model_ref() {
+            DataModel::Queue(queues) => {
+                let items = ["a".into(), SharedSlice::new(&[0xFF]), "c".into()];
+                queues.push_items(b"jobs", &items, |_| {});
+                queues.push_items(b"empty", &[], |_| {});
+                queues.reserve(b"jobs", 100, |_| {}).unwrap().0
+            }
+            _ => panic!("Bad model!"),
+        };
+        let tblid = unsafe { ObjectID::from_slice("jobs") };
+        let ksid = unsafe { ObjectID::from_slice("myqueueks") };
+        fs::create_dir_all("data/ks/myqueueks").unwrap();
+        super::flush::oneshot::flush_table(&Autoflush, &tblid, &ksid, &tbl).unwrap();
+        let ret = super::unflush::read_table::<Table>(&ksid, &tblid, false, model_code).unwrap();
+        assert_eq!(
+            ret.describe_self(),
+            "Keymap { data:(str,queue<binstr>), volatile:false }"
+        );
+        match ret.get_model_ref() {
+            DataModel::Queue(queues) => {
+                assert_eq!(queues.len(), 2);
+                assert_eq!(queues.counts(b"jobs"), Some((2, 1)));
+                assert_eq!(queues.counts(b"empty"), Some((0, 0)));
+                // the reservation is still there, and new ones don't reuse its ID
+                let (id, item) = queues.reserve(b"jobs", 100, |_| {}).unwrap();
+                assert_eq!(item, SharedSlice::new(&[0xFF]));
+                assert!(id > reserved);
+                assert!(queues.ack(b"jobs", reserved, |_| {}));
+            }
+            _ => panic!("Bad model!"),
+        }
+    }
+    #[test]
+    fn test_flush_unflush_table_ordered_kv() {
+        let model_code =
+            Table::ordered_kv_model_code(bytemarks::BYTEMARK_MODEL_KV_STR_BIN).unwrap();
+        assert_eq!(model_code, bytemarks::BYTEMARK_MODEL_KV_ORDERED_STR_BIN);
+        let tbl = Table::from_model_code(model_code, false).unwrap();
+        let kve = tbl.get_kvstore().unwrap();
+        for key in ["b", "a", "c"] {
+            kve.set(key.into(), "value".into()).unwrap();
+        }
+        assert!(kve.expire("c", 100).unwrap());
+        assert_eq!(tbl.get_model_code(), model_code);
+        let tblid = unsafe { ObjectID::from_slice("ordered") };
+        let ksid = unsafe { ObjectID::from_slice("myorderedks") };
+        fs::create_dir_all("data/ks/myorderedks").unwrap();
+        super::flush::oneshot::flush_table(&Autoflush, &tblid, &ksid, &tbl).unwrap();
+        let ret = super::unflush::read_table::<Table>(&ksid, &tblid, false, model_code).unwrap();
+        assert_eq!(
+            ret.describe_self(),
+            "Keymap { data:(str,binstr), volatile:false, ordered:true }"
+        );
+        assert!(matches!(ret.get_model_ref(), DataModel::KVOrdered(_)));
+        let rkve = ret.get_kvstore().unwrap();
+        assert!(rkve.ttl("c").unwrap().unwrap().is_some());
+        let keys: Vec<SharedSlice> = rkve
+            .range((Bound::Unbounded, Bound::Unbounded), usize::MAX, true)
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect();
+        assert_eq!(keys, ["c", "b", "a"]);
+    }
+    #[test]
+    fn test_flush_unflush_table_rowmap_numeric() {
+        let schema = Schema::new(vec![
+            Field::new("id", FieldType::UInt64),
+            Field::new("name", FieldType::String),
+            Field::new("balance", FieldType::SInt64),
+        ])
+        .unwrap();
+        let tbl = Table::new_rowmap(schema.clone(), false);
+        if let DataModel::Rowmap(rm) = tbl.get_model_ref() {
+            rm.rows().get_inner_ref().upsert(
+                "42".into(),
+                LockedVec::new(vec!["sayan".into(), "-100".into()]),
+            );
+        } else {
+            panic!("Bad model!");
+        }
+        let tblid = unsafe { ObjectID::from_slice("accounts") };
+        let ksid = unsafe { ObjectID::from_slice("mynumrowks") };
+        fs::create_dir_all("data/ks/mynumrowks").unwrap();
+        super::flush::oneshot::flush_table(&Autoflush, &tblid, &ksid, &tbl).unwrap();
+        let ret = super::unflush::read_table::<Table>(
+            &ksid,
+            &tblid,
+            false,
+            bytemarks::BYTEMARK_MODEL_ROWMAP,
+        )
+        .unwrap();
+        assert_eq!(ret.get_schema(), Some(&schema));
+        if let DataModel::Rowmap(rm) = ret.get_model_ref() {
+            assert_eq!(
+                rm.rows().list_cloned_full(b"42").unwrap().unwrap(),
+                vec![SharedSlice::from("sayan"), SharedSlice::from("-100")]
+            );
+        } else {
+            panic!("Bad model!");
+        }
+    }
+    #[test]
+    fn test_flush_unflush_system_table_grants() {
+        let grantmap = Grantmap::default();
+        let mut grants = Grants::default();
+        grants.insert(Grant::parse(b"read", Some(b"*")).unwrap());
+        grants.insert(Grant::parse(b"write", Some(b"twitter")).unwrap());
+        grantmap.upsert(Array::try_from_slice("sayan").unwrap(), grants.clone());
+        grantmap.upsert(Array::try_from_slice("nobody").unwrap(), Grants::default());
+        let tbl = SystemTable::new_grants(grantmap);
+        let tblid = unsafe { ObjectID::from_slice("grants") };
+        let ksid = unsafe { ObjectID::from_slice("mysysks") };
+        fs::create_dir_all("data/ks/mysysks").unwrap();
+        super::flush::oneshot::flush_table(&Autoflush, &tblid, &ksid, &tbl).unwrap();
+        let ret = super::unflush::read_table::<SystemTable>(
+            &ksid,
+            &tblid,
+            false,
+            bytemarks::SYSTEM_TABLE_GRANTS,
+        )
+        .unwrap();
+        if let SystemDataModel::Grants(rgrantmap) = ret.get_model_ref() {
+            assert_eq!(rgrantmap.len(), 2);
+            let rgrants = rgrantmap.get_cloned(&b"sayan"[..]).unwrap();
+            assert_eq!(rgrants, grants);
+            assert!(rgrants.permits(Some(b"twitter"), Access::Write));
+            assert!(!rgrants.permits(Some(b"default"), Access::Write));
+            assert_eq!(
+                rgrantmap.get_cloned(&b"nobody"[..]).unwrap(),
+                Grants::default()
+            );
+        } else {
+            panic!("Bad model!");
+        }
+    }
+    #[test]
+    fn test_unflush_system_table_bad_grants() {
+        let grantmap = Grantmap::default();
+        // an admin grant can't be scoped to a keyspace
+        grantmap.upsert(
+            Array::try_from_slice("sayan").unwrap(),
+            Grants::from_raw(vec![2, 1, b'x']),
+        );
+        let tbl = SystemTable::new_grants(grantmap);
+        let tblid = unsafe { ObjectID::from_slice("grants") };
+        let ksid = unsafe { ObjectID::from_slice("mybadsysks") };
+        fs::create_dir_all("data/ks/mybadsysks").unwrap();
+        super::flush::oneshot::flush_table(&Autoflush, &tblid, &ksid, &tbl).unwrap();
+        assert!(super::unflush::read_table::<SystemTable>(
+            &ksid,
+            &tblid,
+            false,
+            bytemarks::SYSTEM_TABLE_GRANTS,
+        )
+        .is_err());
+    }
+    #[test]
     fn test_flush_unflush_keyspace() {
         // create the temp dir for this test
         fs::create_dir_all("data/ks/myks_1").unwrap();
@@ -826,3 +1321,1542 @@ mod storage_target_directory_structure {
         fs::remove_dir_all("data/rsnap/wisnap").unwrap();
     }
 }
+
+mod incremental_flush {
+    use crate::{
+        corestore::{
+            memstore::{Memstore, ObjectID, SYSTEM},
+            table::Table,
+        },
+        storage::v1::{
+            bytemarks,
+            flush::{flush_full, oneshot::flush_table, Autoflush, LocalSnapshot},
+            unflush::{read_snapshot, read_table},
+        },
+    };
+    use std::{fs, path::Path};
+    #[test]
+    fn test_clean_table_is_not_rewritten() {
+        fs::create_dir_all("data/ks/incrks1").unwrap();
+        let ksid = unsafe { ObjectID::from_slice("incrks1") };
+        let tblid = unsafe { ObjectID::from_slice("incrtbl") };
+        let tbl = Table::new_default_kve();
+        let kve = tbl.get_kvstore().unwrap();
+        kve.set("hello".into(), "world".into()).unwrap();
+        flush_table(&Autoflush, &tblid, &ksid, &tbl).unwrap();
+        // nothing changed, so the next flush shouldn't touch the file
+        fs::remove_file("data/ks/incrks1/incrtbl").unwrap();
+        flush_table(&Autoflush, &tblid, &ksid, &tbl).unwrap();
+        assert!(!Path::new("data/ks/incrks1/incrtbl").exists());
+        // but a change should
+        kve.set("bye".into(), "world".into()).unwrap();
+        flush_table(&Autoflush, &tblid, &ksid, &tbl).unwrap();
+        let ret = read_table::<Table>(&ksid, &tblid, false, bytemarks::BYTEMARK_MODEL_KV_BIN_BIN)
+            .unwrap();
+        assert_eq!(ret.count(), 2);
+    }
+    #[test]
+    fn test_snapshot_links_clean_table() {
+        fs::create_dir_all("data/ks/incrks2").unwrap();
+        fs::create_dir_all("data/snaps/incrsnap/incrks2").unwrap();
+        let ksid = unsafe { ObjectID::from_slice("incrks2") };
+        let tblid = unsafe { ObjectID::from_slice("incrtbl") };
+        let tbl = Table::new_default_kve();
+        let kve = tbl.get_kvstore().unwrap();
+        kve.set("hello".into(), "world".into()).unwrap();
+        kve.set_with_ttl("session".into(), "token".into(), 100)
+            .unwrap();
+        flush_table(&Autoflush, &tblid, &ksid, &tbl).unwrap();
+        flush_table(
+            &LocalSnapshot::new("incrsnap".to_owned()),
+            &tblid,
+            &ksid,
+            &tbl,
+        )
+        .unwrap();
+        for file in ["incrtbl", "incrtbl.ttl"] {
+            let (data, snap) = (
+                format!("data/ks/incrks2/{file}"),
+                format!("data/snaps/incrsnap/incrks2/{file}"),
+            );
+            assert_eq!(fs::read(&data).unwrap(), fs::read(&snap).unwrap());
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::MetadataExt;
+                assert_eq!(
+                    fs::metadata(&data).unwrap().ino(),
+                    fs::metadata(&snap).unwrap().ino()
+                );
+            }
+        }
+        // writing the table again must leave the snapshot as it was
+        let snapshotted = fs::read("data/snaps/incrsnap/incrks2/incrtbl").unwrap();
+        kve.set("bye".into(), "world".into()).unwrap();
+        flush_table(&Autoflush, &tblid, &ksid, &tbl).unwrap();
+        assert_eq!(
+            fs::read("data/snaps/incrsnap/incrks2/incrtbl").unwrap(),
+            snapshotted
+        );
+        assert_ne!(fs::read("data/ks/incrks2/incrtbl").unwrap(), snapshotted);
+        fs::remove_dir_all("data/snaps/incrsnap").unwrap();
+    }
+    #[test]
+    fn test_read_snapshot() {
+        let store = Memstore::new_default();
+        let ksid = unsafe { ObjectID::from_slice("incrks3") };
+        assert!(store.create_keyspace(ksid.clone()));
+        let ks = store.get_keyspace_atomic_ref("incrks3".as_bytes()).unwrap();
+        assert!(ks.create_table(
+            unsafe { ObjectID::from_slice("incrtbl") },
+            Table::new_default_kve()
+        ));
+        let tbl = ks.get_table_atomic_ref("incrtbl".as_bytes()).unwrap();
+        tbl.get_kvstore()
+            .unwrap()
+            .set("hello".into(), "world".into())
+            .unwrap();
+        flush_full(LocalSnapshot::new("incrsnap3".to_owned()), &store).unwrap();
+        let ksmap = read_snapshot(Path::new("data/snaps/incrsnap3")).unwrap();
+        // the system keyspace is only a placeholder
+        assert_eq!(ksmap.get(&SYSTEM).unwrap().tables.len(), 0);
+        let ks = ksmap.get(&ksid).unwrap();
+        let tbl = ks.get_table_atomic_ref("incrtbl".as_bytes()).unwrap();
+        let kve = tbl.get_kvstore().unwrap();
+        assert_eq!(kve.get_cloned("hello").unwrap().unwrap(), "world");
+        // the data directory doesn't have these tables yet
+        assert!(!tbl.is_saved_at(tbl.mutation_count()));
+        fs::remove_dir_all("data/snaps/incrsnap3").unwrap();
+    }
+}
+
+mod header_tests {
+    use crate::{
+        corestore::{
+            memstore::{Memstore, ObjectID},
+            table::Table,
+        },
+        storage::v1::{
+            bytemarks,
+            flush::{self, oneshot::flush_table, Autoflush, LocalSnapshot},
+            header::{self, HeaderError, HEADER_SIZE},
+            unflush::read_table,
+            verify,
+        },
+        util::os,
+    };
+    use std::{fs, io::Cursor, io::Write, path::PathBuf};
+    fn file_with_header(payload: &[u8]) -> Vec<u8> {
+        let mut file = Cursor::new(Vec::new());
+        header::write_with_header(&mut file, None, |w| w.write_all(payload)).unwrap();
+        file.into_inner()
+    }
+    #[test]
+    fn test_header_roundtrip() {
+        let file = file_with_header(b"hello world");
+        assert_eq!(file.len(), HEADER_SIZE + 11);
+        assert!(header::has_header(&file));
+        assert_eq!(header::payload(&file).unwrap(), b"hello world");
+        // files without a header are read as is
+        assert_eq!(header::payload(b"hello world").unwrap(), b"hello world");
+    }
+    #[test]
+    fn test_header_detects_damage() {
+        let file = file_with_header(b"hello world");
+        let mut flipped = file.clone();
+        flipped[HEADER_SIZE + 3] ^= 1;
+        assert_eq!(
+            header::payload(&flipped),
+            Err(HeaderError::ChecksumMismatch)
+        );
+        assert_eq!(
+            header::payload(&file[..file.len() - 1]),
+            Err(HeaderError::BadLength)
+        );
+        assert_eq!(
+            header::payload(&file[..HEADER_SIZE - 1]),
+            Err(HeaderError::BadLength)
+        );
+        let mut newer = file;
+        newer[4] += 1;
+        assert_eq!(
+            header::payload(&newer),
+            Err(HeaderError::UnknownVersion(header::FORMAT_VERSION + 1))
+        );
+    }
+    #[test]
+    fn test_read_table_without_header() {
+        fs::create_dir_all("data/ks/hdrks").unwrap();
+        let ksid = unsafe { ObjectID::from_slice("hdrks") };
+        let tblid = unsafe { ObjectID::from_slice("legacy") };
+        let tbl = Table::new_default_kve();
+        tbl.get_kvstore()
+            .unwrap()
+            .set("hello".into(), "world".into())
+            .unwrap();
+        flush_table(&Autoflush, &tblid, &ksid, &tbl).unwrap();
+        // strip the header, like a file written by an older version
+        let file = fs::read("data/ks/hdrks/legacy").unwrap();
+        fs::write("data/ks/hdrks/legacy", &file[HEADER_SIZE..]).unwrap();
+        let ret = read_table::<Table>(&ksid, &tblid, false, bytemarks::BYTEMARK_MODEL_KV_BIN_BIN)
+            .unwrap();
+        assert_eq!(ret.count(), 1);
+        // but damage in a file with a header is caught
+        let mut file = file;
+        *file.last_mut().unwrap() ^= 1;
+        fs::write("data/ks/hdrks/legacy", &file).unwrap();
+        assert!(
+            read_table::<Table>(&ksid, &tblid, false, bytemarks::BYTEMARK_MODEL_KV_BIN_BIN)
+                .is_err()
+        );
+    }
+    #[test]
+    fn test_verify_datadir() {
+        let store = Memstore::new_default();
+        flush::flush_full(LocalSnapshot::new("verifysnap".to_owned()), &store).unwrap();
+        os::recursive_copy("data/snaps/verifysnap", "verifydata/ks").unwrap();
+        os::recursive_copy("data/snaps/verifysnap", "verifydata/snaps/s1").unwrap();
+        fs::remove_dir_all("data/snaps/verifysnap").unwrap();
+        let report = verify::verify_datadir("verifydata").unwrap();
+        assert!(report.damaged.is_empty());
+        assert!(report.unchecksummed.is_empty());
+        // damage a table in the snapshot and remove the PARTMAP of the default keyspace
+        let table = "verifydata/snaps/s1/default/default";
+        let mut file = fs::read(table).unwrap();
+        file[HEADER_SIZE] ^= 1;
+        fs::write(table, file).unwrap();
+        fs::remove_file("verifydata/ks/default/PARTMAP").unwrap();
+        let report = verify::verify_datadir("verifydata").unwrap();
+        fs::remove_dir_all("verifydata").unwrap();
+        assert_eq!(
+            report.damaged,
+            vec![
+                (
+                    PathBuf::from("verifydata/ks/default/PARTMAP"),
+                    "missing".to_owned()
+                ),
+                (PathBuf::from(table), "checksum mismatch".to_owned())
+            ]
+        );
+    }
+}
+
+mod dump_tests {
+    use crate::{
+        corestore::{
+            memstore::{Memstore, ObjectID, DEFAULT},
+            table::{DataModel, Table},
+            SharedSlice,
+        },
+        kvengine::{
+            rowmap::{Field, Schema},
+            types::FieldType,
+            LockedVec,
+        },
+        storage::v1::{
+            bytemarks,
+            dump::{self, Format},
+            flush::{self, LocalSnapshot},
+        },
+    };
+    use std::fs;
+    fn dumpsnap() -> &'static str {
+        let store = Memstore::new_default();
+        let ks = store.get_keyspace_atomic_ref(&DEFAULT).unwrap();
+        let kve = ks.get_table_atomic_ref(&DEFAULT).unwrap();
+        let kve = kve.get_kvstore().unwrap();
+        kve.set("hello".into(), "world, \"quoted\"".into()).unwrap();
+        kve.set(SharedSlice::new(&[0xFF]), "binary key".into())
+            .unwrap();
+        kve.set_with_ttl("later".into(), "gone".into(), 60).unwrap();
+        let lists =
+            Table::from_model_code(bytemarks::BYTEMARK_MODEL_KV_STR_LIST_STR, false).unwrap();
+        if let DataModel::KVExtListmap(kvl) = lists.get_model_ref() {
+            kvl.get_inner_ref().upsert(
+                "fruits".into(),
+                LockedVec::new(vec!["apple".into(), "mango".into(), "base64:kiwi".into()]),
+            );
+            kvl.get_inner_ref()
+                .upsert("empty".into(), LockedVec::new(vec![]));
+        }
+        let schema = Schema::new(vec![
+            Field::new("username", FieldType::String),
+            Field::new("email", FieldType::String),
+        ])
+        .unwrap();
+        let users = Table::new_rowmap(schema, false);
+        if let DataModel::Rowmap(rm) = users.get_model_ref() {
+            rm.rows().get_inner_ref().upsert(
+                "sayan".into(),
+                LockedVec::new(vec!["sayan@example.com".into()]),
+            );
+        }
+        ks.create_table(unsafe { ObjectID::from_slice("lists") }, lists);
+        ks.create_table(unsafe { ObjectID::from_slice("users") }, users);
+        ks.create_table(
+            unsafe { ObjectID::from_slice("cache") },
+            Table::new_kve_with_volatile(true),
+        );
+        flush::flush_full(LocalSnapshot::new("dumpsnap".to_owned()), &store).unwrap();
+        "data/snaps/dumpsnap"
+    }
+    #[test]
+    fn test_list_and_dump() {
+        let root = dumpsnap();
+        let models = dump::list(root).unwrap();
+        let names: Vec<String> = models
+            .iter()
+            .map(|m| format!("{}.{}:{}", m.space, m.model, m.volatile))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "default.cache:true",
+                "default.default:false",
+                "default.lists:false",
+                "default.users:false"
+            ]
+        );
+        assert_eq!(
+            models[3].description,
+            "Rowmap { data:(username:str,email:str), volatile:false }"
+        );
+        let mut out = Vec::new();
+        assert_eq!(
+            dump::dump(root, &models, Format::JsonLines, &mut out).unwrap(),
+            6
+        );
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(
+            lines[0],
+            r#"{"space":"default","model":"default","key":"hello","value":"world, \"quoted\""}"#
+        );
+        assert!(lines[1].starts_with(
+            r#"{"space":"default","model":"default","key":"later","value":"gone","expires_at":"#
+        ));
+        assert_eq!(
+            lines[2],
+            r#"{"space":"default","model":"default","key":{"base64":"/w=="},"value":"binary key"}"#
+        );
+        assert_eq!(
+            lines[3],
+            r#"{"space":"default","model":"lists","key":"empty","value":[]}"#
+        );
+        assert_eq!(
+            lines[4],
+            r#"{"space":"default","model":"lists","key":"fruits","value":["apple","mango","base64:kiwi"]}"#
+        );
+        assert_eq!(
+            lines[5],
+            r#"{"space":"default","model":"users","key":"sayan","value":{"email":"sayan@example.com"}}"#
+        );
+        let mut out = Vec::new();
+        dump::dump(root, &models[2..], Format::Csv, &mut out).unwrap();
+        fs::remove_dir_all(root).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "space,model,key,field,value,expires_at\n\
+            default,lists,empty,,,\n\
+            default,lists,fruits,0,apple,\n\
+            default,lists,fruits,1,mango,\n\
+            default,lists,fruits,2,base64:YmFzZTY0Omtpd2k=,\n\
+            default,users,sayan,email,sayan@example.com,\n"
+        );
+    }
+}
+
+mod wal_tests {
+    use crate::{
+        config::WalFsync,
+        corestore::{
+            memstore::{Memstore, ObjectID},
+            table::{DataModel, Table},
+            SharedSlice,
+        },
+        kvengine::{
+            queue::KVEQueuemap,
+            rowmap::{Field, Schema},
+            sets::Score,
+            types::FieldType,
+            KVEStandard, LockedVec,
+        },
+        storage::v1::{bytemarks, wal},
+    };
+    use std::fs::{self, OpenOptions};
+    use std::io::Write;
+    use std::thread;
+
+    fn paths(name: &str) -> (String, String) {
+        fs::create_dir_all("data/waltests").unwrap();
+        let path = format!("data/waltests/{name}");
+        let old_path = format!("{path}.old");
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(&old_path);
+        (path, old_path)
+    }
+    fn store_with_kv(ks: &str, tbl: &str) -> Memstore {
+        let store = Memstore::new_default();
+        assert!(store.create_keyspace(ObjectID::try_from_slice(ks).unwrap()));
+        assert!(store
+            .get_keyspace_atomic_ref(ks.as_bytes())
+            .unwrap()
+            .create_table(
+                ObjectID::try_from_slice(tbl).unwrap(),
+                Table::new_default_kve()
+            ));
+        store
+    }
+    fn with_kv<T>(store: &Memstore, ks: &str, tbl: &str, f: impl FnOnce(&DataModel) -> T) -> T {
+        let table = store
+            .get_keyspace_atomic_ref(ks.as_bytes())
+            .unwrap()
+            .get_table_atomic_ref(tbl.as_bytes())
+            .unwrap();
+        f(table.get_model_ref())
+    }
+
+    #[test]
+    fn test_wal_replay_kv() {
+        let (path, old_path) = paths("kv");
+        let (ksid, tblid) = (
+            ObjectID::try_from_slice("walks").unwrap(),
+            ObjectID::try_from_slice("waltbl").unwrap(),
+        );
+        let store = store_with_kv("walks", "waltbl");
+        let log = wal::WriteAheadLog::open_at(&path, &old_path, WalFsync::Always).unwrap();
+        with_kv(&store, "walks", "waltbl", |model| {
+            let kve = match model {
+                DataModel::KV(kve) => kve,
+                _ => panic!("wrong model"),
+            };
+            log.log_table(Some((&ksid, &tblid)), |batch| {
+                kve.set("hello".into(), "world".into()).unwrap();
+                batch.kv(kve, b"hello");
+                kve.set_with_ttl("session".into(), "token".into(), 100)
+                    .unwrap();
+                batch.kv(kve, b"session");
+                kve.set("gone".into(), "soon".into()).unwrap();
+                batch.kv(kve, b"gone");
+            });
+            log.log_table(Some((&ksid, &tblid)), |batch| {
+                kve.remove("gone").unwrap();
+                batch.del(b"gone");
+                kve.update("hello".into(), "universe".into()).unwrap();
+                batch.kv(kve, b"hello");
+            });
+        });
+        drop(log);
+        // now replay on a store that has never seen these writes
+        let restored = store_with_kv("walks", "waltbl");
+        assert_eq!(wal::replay_at(&path, &old_path, &restored).unwrap(), 5);
+        with_kv(&restored, "walks", "waltbl", |model| {
+            let kve = match model {
+                DataModel::KV(kve) => kve,
+                _ => panic!("wrong model"),
+            };
+            assert_eq!(kve.len(), 2);
+            assert_eq!(
+                kve.get_cloned("hello").unwrap().unwrap(),
+                SharedSlice::from("universe")
+            );
+            assert!(!kve.exists("gone").unwrap());
+            assert_eq!(kve.get_expiry_ref().len(), 1);
+            assert!(kve.get_expiry_ref().contains_key("session".as_bytes()));
+        });
+        // replaying again is harmless
+        assert_eq!(wal::replay_at(&path, &old_path, &restored).unwrap(), 5);
+        with_kv(&restored, "walks", "waltbl", |model| {
+            assert!(matches!(model, DataModel::KV(kve) if kve.len() == 2));
+        });
+    }
+
+    #[test]
+    fn test_wal_concurrent_appends() {
+        let (path, old_path) = paths("concurrent");
+        let ksid = ObjectID::try_from_slice("walconc").unwrap();
+        let names = ["first", "second"];
+        let tables = names.map(|name| ObjectID::try_from_slice(name).unwrap());
+        let store = store_with_kv("walconc", "first");
+        create_table(&store, "walconc", "second", Table::new_default_kve());
+        let log = wal::WriteAheadLog::open_at(&path, &old_path, WalFsync::Always).unwrap();
+        // two writers per table, racing on the same keys
+        thread::scope(|scope| {
+            for writer in 0..4 {
+                let (store, log, ksid) = (&store, &log, &ksid);
+                let (name, tblid) = (names[writer % 2], &tables[writer % 2]);
+                scope.spawn(move || {
+                    with_kv(store, "walconc", name, |model| {
+                        let kve = match model {
+                            DataModel::KV(kve) => kve,
+                            _ => panic!("wrong model"),
+                        };
+                        for i in 0..200 {
+                            let key = format!("key{}", i % 50);
+                            log.log_table(Some((ksid, tblid)), |batch| {
+                                let value = format!("{writer}:{i}");
+                                kve.upsert(key.as_str().into(), value.as_str().into())
+                                    .unwrap();
+                                batch.kv(kve, key.as_bytes());
+                            });
+                        }
+                    });
+                });
+            }
+        });
+        drop(log);
+        let restored = store_with_kv("walconc", "first");
+        create_table(&restored, "walconc", "second", Table::new_default_kve());
+        assert_eq!(wal::replay_at(&path, &old_path, &restored).unwrap(), 800);
+        for tbl in names {
+            let expected = with_kv(&store, "walconc", tbl, |model| match model {
+                DataModel::KV(kve) => (0..50)
+                    .map(|i| kve.get_cloned(format!("key{i}").as_str()).unwrap())
+                    .collect::<Vec<_>>(),
+                _ => panic!("wrong model"),
+            });
+            with_kv(&restored, "walconc", tbl, |model| match model {
+                DataModel::KV(kve) => {
+                    assert_eq!(kve.len(), 50);
+                    for (i, value) in expected.iter().enumerate() {
+                        assert_eq!(&kve.get_cloned(format!("key{i}").as_str()).unwrap(), value);
+                    }
+                }
+                _ => panic!("wrong model"),
+            });
+        }
+    }
+
+    #[test]
+    fn test_wal_replay_ddl_and_list() {
+        let (path, old_path) = paths("ddl");
+        let (ksid, tblid, droppedid) = (
+            ObjectID::try_from_slice("walddl").unwrap(),
+            ObjectID::try_from_slice("lists").unwrap(),
+            ObjectID::try_from_slice("dropped").unwrap(),
+        );
+        let log = wal::WriteAheadLog::open_at(&path, &old_path, WalFsync::Never).unwrap();
+        log.log_ddl(|batch| {
+            batch.create_space(&ksid);
+            batch.create_model(
+                &ksid,
+                &tblid,
+                bytemarks::BYTEMARK_MODEL_KV_BINSTR_LIST_BINSTR,
+                bytemarks::BYTEMARK_STORAGE_PERSISTENT,
+                None,
+                None,
+            );
+            batch.create_model(
+                &ksid,
+                &droppedid,
+                bytemarks::BYTEMARK_MODEL_KV_BIN_BIN,
+                bytemarks::BYTEMARK_STORAGE_PERSISTENT,
+                None,
+                None,
+            );
+        });
+        log.log_table(Some((&ksid, &tblid)), |batch| {
+            batch.list(b"mylist", &["a".into(), "b".into(), "c".into()]);
+            batch.list(b"otherlist", &[]);
+        });
+        log.log_ddl(|batch| batch.drop_model(&ksid, &droppedid));
+        drop(log);
+        let store = Memstore::new_default();
+        assert_eq!(wal::replay_at(&path, &old_path, &store).unwrap(), 6);
+        let keyspace = store.get_keyspace_atomic_ref("walddl".as_bytes()).unwrap();
+        assert!(keyspace
+            .get_table_atomic_ref("dropped".as_bytes())
+            .is_none());
+        let table = keyspace.get_table_atomic_ref("lists".as_bytes()).unwrap();
+        match table.get_model_ref() {
+            DataModel::KVExtListmap(listmap) => {
+                assert_eq!(listmap.len(), 2);
+                assert_eq!(
+                    listmap.list_cloned_full(b"mylist").unwrap().unwrap(),
+                    vec![
+                        SharedSlice::from("a"),
+                        SharedSlice::from("b"),
+                        SharedSlice::from("c")
+                    ]
+                );
+            }
+            _ => panic!("wrong model"),
+        }
+    }
+
+    #[test]
+    fn test_wal_replay_capped_list() {
+        let (path, old_path) = paths("capped");
+        let (ksid, tblid) = (
+            ObjectID::try_from_slice("walcapped").unwrap(),
+            ObjectID::try_from_slice("lists").unwrap(),
+        );
+        let log = wal::WriteAheadLog::open_at(&path, &old_path, WalFsync::Never).unwrap();
+        log.log_ddl(|batch| {
+            batch.create_space(&ksid);
+            batch.create_model(
+                &ksid,
+                &tblid,
+                bytemarks::BYTEMARK_MODEL_KV_STR_LIST_STR,
+                bytemarks::BYTEMARK_STORAGE_PERSISTENT,
+                None,
+                Some(2),
+            );
+        });
+        drop(log);
+        let store = Memstore::new_default();
+        assert_eq!(wal::replay_at(&path, &old_path, &store).unwrap(), 2);
+        let keyspace = store
+            .get_keyspace_atomic_ref("walcapped".as_bytes())
+            .unwrap();
+        let table = keyspace.get_table_atomic_ref("lists".as_bytes()).unwrap();
+        assert_eq!(table.max_len(), Some(2));
+    }
+
+    #[test]
+    fn test_wal_replay_rowmap() {
+        let (path, old_path) = paths("rowmap");
+        let (ksid, tblid) = (
+            ObjectID::try_from_slice("walrows").unwrap(),
+            ObjectID::try_from_slice("users").unwrap(),
+        );
+        let schema = Schema::new(vec![
+            Field::new("username", FieldType::String),
+            Field::new("avatar", FieldType::Binary),
+        ])
+        .unwrap();
+        let log = wal::WriteAheadLog::open_at(&path, &old_path, WalFsync::Never).unwrap();
+        log.log_ddl(|batch| {
+            batch.create_space(&ksid);
+            batch.create_model(
+                &ksid,
+                &tblid,
+                bytemarks::BYTEMARK_MODEL_ROWMAP,
+                bytemarks::BYTEMARK_STORAGE_PERSISTENT,
+                Some(&schema),
+                None,
+            );
+        });
+        log.log_table(Some((&ksid, &tblid)), |batch| {
+            batch.list(b"sayan", &["avatar1".into()]);
+            batch.list(b"gone", &["avatar2".into()]);
+            batch.del(b"gone");
+        });
+        drop(log);
+        let store = Memstore::new_default();
+        assert_eq!(wal::replay_at(&path, &old_path, &store).unwrap(), 5);
+        let table = store
+            .get_keyspace_atomic_ref("walrows".as_bytes())
+            .unwrap()
+            .get_table_atomic_ref("users".as_bytes())
+            .unwrap();
+        assert_eq!(table.get_schema(), Some(&schema));
+        match table.get_model_ref() {
+            DataModel::Rowmap(rm) => {
+                assert_eq!(rm.len(), 1);
+                assert_eq!(
+                    rm.rows().list_cloned_full(b"sayan").unwrap().unwrap(),
+                    vec![SharedSlice::from("avatar1")]
+                );
+            }
+            _ => panic!("wrong model"),
+        }
+    }
+
+    #[test]
+    fn test_wal_replay_hashmap() {
+        let (path, old_path) = paths("hashmap");
+        let (ksid, tblid) = (
+            ObjectID::try_from_slice("walmaps").unwrap(),
+            ObjectID::try_from_slice("users").unwrap(),
+        );
+        let log = wal::WriteAheadLog::open_at(&path, &old_path, WalFsync::Never).unwrap();
+        log.log_ddl(|batch| {
+            batch.create_space(&ksid);
+            batch.create_model(
+                &ksid,
+                &tblid,
+                Table::map_model_code(true, true, false),
+                bytemarks::BYTEMARK_STORAGE_PERSISTENT,
+                None,
+                None,
+            );
+        });
+        log.log_table(Some((&ksid, &tblid)), |batch| {
+            batch.map_set(
+                b"sayan",
+                &[("email".into(), "a@b".into()), ("age".into(), "30".into())],
+            );
+            batch.map_set(b"sayan", &[("age".into(), "31".into())]);
+            batch.map_del(b"sayan", &["email".into()]);
+            batch.map_set(b"gone", &[("x".into(), "y".into())]);
+            batch.map_del(b"gone", &["x".into()]);
+        });
+        drop(log);
+        let store = Memstore::new_default();
+        assert_eq!(wal::replay_at(&path, &old_path, &store).unwrap(), 7);
+        with_kv(&store, "walmaps", "users", |model| match model {
+            DataModel::Hashmap(hm) => {
+                // removing the last field removes the map
+                assert_eq!(hm.len(), 1);
+                assert_eq!(
+                    hm.get_all(b"sayan").unwrap(),
+                    vec![(SharedSlice::from("age"), SharedSlice::from("31"))]
+                );
+            }
+            _ => panic!("wrong model"),
+        });
+    }
+
+    #[test]
+    fn test_wal_replay_sets() {
+        let (path, old_path) = paths("sets");
+        let ksid = ObjectID::try_from_slice("walsets").unwrap();
+        let (setid, zsetid) = (
+            ObjectID::try_from_slice("tags").unwrap(),
+            ObjectID::try_from_slice("board").unwrap(),
+        );
+        let log = wal::WriteAheadLog::open_at(&path, &old_path, WalFsync::Never).unwrap();
+        log.log_ddl(|batch| {
+            batch.create_space(&ksid);
+            batch.create_model(
+                &ksid,
+                &setid,
+                Table::set_model_code(true, true),
+                bytemarks::BYTEMARK_STORAGE_PERSISTENT,
+                None,
+                None,
+            );
+            batch.create_model(
+                &ksid,
+                &zsetid,
+                Table::sorted_set_model_code(true, true),
+                bytemarks::BYTEMARK_STORAGE_PERSISTENT,
+                None,
+                None,
+            );
+        });
+        log.log_table(Some((&ksid, &setid)), |batch| {
+            batch.set_add(b"post", &["rust".into(), "db".into()]);
+            batch.set_del(b"post", &["db".into()]);
+            batch.set_add(b"gone", &["x".into()]);
+            batch.set_del(b"gone", &["x".into()]);
+        });
+        log.log_table(Some((&ksid, &zsetid)), |batch| {
+            let score = |score: &[u8]| Score::parse(score).unwrap();
+            batch.zset_add(
+                b"top",
+                &[("a".into(), score(b"1")), ("b".into(), score(b"2"))],
+            );
+            batch.zset_add(b"top", &[("a".into(), score(b"3"))]);
+            batch.set_del(b"top", &["b".into()]);
+        });
+        drop(log);
+        let store = Memstore::new_default();
+        assert_eq!(wal::replay_at(&path, &old_path, &store).unwrap(), 10);
+        with_kv(&store, "walsets", "tags", |model| match model {
+            DataModel::Set(sets) => {
+                // removing the last member removes the set
+                assert_eq!(sets.len(), 1);
+                assert_eq!(
+                    sets.members(b"post").unwrap(),
+                    vec![SharedSlice::from("rust")]
+                );
+            }
+            _ => panic!("wrong model"),
+        });
+        with_kv(&store, "walsets", "board", |model| match model {
+            DataModel::SortedSet(zsets) => {
+                assert_eq!(zsets.set_len(b"top"), Some(1));
+                assert_eq!(zsets.score(b"top", b"a"), Score::parse(b"3"));
+            }
+            _ => panic!("wrong model"),
+        });
+    }
+
+    #[test]
+    fn test_wal_replay_queue() {
+        let (path, old_path) = paths("queue");
+        let (ksid, tblid) = (
+            ObjectID::try_from_slice("walqueues").unwrap(),
+            ObjectID::try_from_slice("jobs").unwrap(),
+        );
+        let queues = KVEQueuemap::init(true, true);
+        let log = wal::WriteAheadLog::open_at(&path, &old_path, WalFsync::Never).unwrap();
+        log.log_ddl(|batch| {
+            batch.create_space(&ksid);
+            batch.create_model(
+                &ksid,
+                &tblid,
+                Table::queue_model_code(true, true),
+                bytemarks::BYTEMARK_STORAGE_PERSISTENT,
+                None,
+                None,
+            );
+        });
+        log.log_table(Some((&ksid, &tblid)), |batch| {
+            let items = ["a".into(), "b".into(), "c".into()];
+            queues.push_items(b"q", &items, |queue| batch.queue(b"q", queue));
+            let (a, _) = queues
+                .reserve(b"q", 100, |queue| batch.queue(b"q", queue))
+                .unwrap();
+            queues.reserve(b"q", 100, |queue| batch.queue(b"q", queue));
+            queues.ack(b"q", a, |queue| batch.queue(b"q", queue));
+        });
+        drop(log);
+        let store = Memstore::new_default();
+        assert_eq!(wal::replay_at(&path, &old_path, &store).unwrap(), 6);
+        with_kv(&store, "walqueues", "jobs", |model| match model {
+            DataModel::Queue(replayed) => {
+                // the reservation of `b` survives
+                assert_eq!(replayed.counts(b"q"), Some((1, 1)));
+                let (id, item) = replayed.reserve(b"q", 100, |_| {}).unwrap();
+                assert_eq!(item, "c");
+                assert!(replayed.ack(b"q", id - 1, |_| {}));
+            }
+            _ => panic!("wrong model"),
+        });
+    }
+
+    #[test]
+    fn test_wal_replay_typed_kv() {
+        let (path, old_path) = paths("typedkv");
+        let (ksid, tblid) = (
+            ObjectID::try_from_slice("waltyped").unwrap(),
+            ObjectID::try_from_slice("counters").unwrap(),
+        );
+        let model_code = Table::typed_kv_model_code(FieldType::String, FieldType::SInt64);
+        let kve = KVEStandard::init_typed(FieldType::String, FieldType::SInt64);
+        let log = wal::WriteAheadLog::open_at(&path, &old_path, WalFsync::Never).unwrap();
+        log.log_ddl(|batch| {
+            batch.create_space(&ksid);
+            batch.create_model(
+                &ksid,
+                &tblid,
+                model_code,
+                bytemarks::BYTEMARK_STORAGE_PERSISTENT,
+                None,
+                None,
+            );
+        });
+        log.log_table(Some((&ksid, &tblid)), |batch| {
+            kve.incr_by("hits".into(), b"-3").unwrap().unwrap();
+            batch.kv(&kve, b"hits");
+        });
+        drop(log);
+        let store = Memstore::new_default();
+        assert_eq!(wal::replay_at(&path, &old_path, &store).unwrap(), 3);
+        let table = store
+            .get_keyspace_atomic_ref("waltyped".as_bytes())
+            .unwrap()
+            .get_table_atomic_ref("counters".as_bytes())
+            .unwrap();
+        assert_eq!(table.get_model_code(), model_code);
+        let rkve = table.get_kvstore().unwrap();
+        assert_eq!(rkve.get_cloned("hits").unwrap().unwrap(), "-3");
+        assert!(rkve.set("misses".into(), "none".into()).is_err());
+    }
+
+    #[test]
+    fn test_wal_torn_tail_is_discarded() {
+        let (path, old_path) = paths("torn");
+        let (ksid, tblid) = (
+            ObjectID::try_from_slice("walks").unwrap(),
+            ObjectID::try_from_slice("waltbl").unwrap(),
+        );
+        let store = store_with_kv("walks", "waltbl");
+        let log = wal::WriteAheadLog::open_at(&path, &old_path, WalFsync::Always).unwrap();
+        with_kv(&store, "walks", "waltbl", |model| {
+            if let DataModel::KV(kve) = model {
+                log.log_table(Some((&ksid, &tblid)), |batch| {
+                    kve.set("hello".into(), "world".into()).unwrap();
+                    batch.kv(kve, b"hello");
+                });
+            }
+        });
+        drop(log);
+        let valid_len = fs::metadata(&path).unwrap().len();
+        // simulate a crash in the middle of an append
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&[200, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3])
+            .unwrap();
+        drop(file);
+        let restored = store_with_kv("walks", "waltbl");
+        assert_eq!(wal::replay_at(&path, &old_path, &restored).unwrap(), 1);
+        assert_eq!(fs::metadata(&path).unwrap().len(), valid_len);
+        // a frame with a bad checksum is treated the same way
+        let mut data = fs::read(&path).unwrap();
+        let last = data.len() - 1;
+        data[last] ^= 0xFF;
+        fs::write(&path, data).unwrap();
+        assert_eq!(wal::replay_at(&path, &old_path, &restored).unwrap(), 0);
+        assert_eq!(fs::metadata(&path).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_wal_rotate() {
+        let (path, old_path) = paths("rotate");
+        let ksid = ObjectID::try_from_slice("rotks").unwrap();
+        let log = wal::WriteAheadLog::open_at(&path, &old_path, WalFsync::EverySecond).unwrap();
+        log.log_ddl(|batch| batch.create_space(&ksid));
+        log.rotate().unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().len(), 0);
+        log.log_ddl(|batch| batch.drop_space(&ksid));
+        log.sync_if_dirty().unwrap();
+        // pretend the flush failed; rotating again must keep the order of the records
+        log.rotate().unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().len(), 0);
+        let store = Memstore::new_default();
+        assert_eq!(wal::replay_at(&path, &old_path, &store).unwrap(), 2);
+        assert!(store.get_keyspace_atomic_ref("rotks".as_bytes()).is_none());
+        // the flush succeeded
+        log.discard_rotated().unwrap();
+        assert!(!std::path::Path::new(&old_path).exists());
+        assert_eq!(wal::replay_at(&path, &old_path, &store).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_wal_replay_model_with_data() {
+        let (path, old_path) = paths("withdata");
+        let (ksid, tblid, cacheid) = (
+            ObjectID::try_from_slice("walimport").unwrap(),
+            ObjectID::try_from_slice("imported").unwrap(),
+            ObjectID::try_from_slice("cache").unwrap(),
+        );
+        let table = Table::new_default_kve();
+        let kve = table.get_kvstore().unwrap();
+        kve.set("hello".into(), "world".into()).unwrap();
+        kve.set_with_ttl("session".into(), "token".into(), 100)
+            .unwrap();
+        let cache = Table::from_model_code(bytemarks::BYTEMARK_MODEL_KV_BIN_BIN, true).unwrap();
+        cache
+            .get_kvstore()
+            .unwrap()
+            .set("ephemeral".into(), "value".into())
+            .unwrap();
+        let log = wal::WriteAheadLog::open_at(&path, &old_path, WalFsync::Never).unwrap();
+        log.log_ddl(|batch| {
+            batch.create_space(&ksid);
+            batch.create_model_with_data(&ksid, &tblid, &table);
+            batch.create_model_with_data(&ksid, &cacheid, &cache);
+        });
+        drop(log);
+        let store = Memstore::new_default();
+        // the space, two models and two keys
+        assert_eq!(wal::replay_at(&path, &old_path, &store).unwrap(), 5);
+        with_kv(&store, "walimport", "imported", |model| {
+            let kve = match model {
+                DataModel::KV(kve) => kve,
+                _ => panic!("wrong model"),
+            };
+            assert_eq!(kve.len(), 2);
+            assert_eq!(kve.get_cloned("hello").unwrap().unwrap(), "world");
+            assert!(kve.get_expiry_ref().contains_key("session".as_bytes()));
+        });
+        // the data of volatile models isn't logged
+        with_kv(&store, "walimport", "cache", |model| {
+            assert!(matches!(model, DataModel::KV(kve) if kve.len() == 0));
+        });
+    }
+
+    fn create_table(store: &Memstore, ks: &str, tbl: &str, table: Table) {
+        assert!(store
+            .get_keyspace_atomic_ref(ks.as_bytes())
+            .unwrap()
+            .create_table(ObjectID::try_from_slice(tbl).unwrap(), table));
+    }
+
+    #[test]
+    fn test_replication_snapshot_restore() {
+        let leader = store_with_kv("replks", "kv");
+        with_kv(&leader, "replks", "kv", |model| {
+            if let DataModel::KV(kve) = model {
+                kve.set("hello".into(), "world".into()).unwrap();
+                kve.set_with_ttl("session".into(), "token".into(), 100)
+                    .unwrap();
+            }
+        });
+        create_table(
+            &leader,
+            "replks",
+            "lists",
+            Table::from_model_code(bytemarks::BYTEMARK_MODEL_KV_BINSTR_LIST_BINSTR, false).unwrap(),
+        );
+        with_kv(&leader, "replks", "lists", |model| {
+            if let DataModel::KVExtListmap(listmap) = model {
+                listmap.get_inner_ref().upsert(
+                    "mylist".into(),
+                    LockedVec::new(vec!["a".into(), "b".into()]),
+                );
+            }
+        });
+        create_table(
+            &leader,
+            "replks",
+            "cache",
+            Table::from_model_code(bytemarks::BYTEMARK_MODEL_KV_BIN_BIN, true).unwrap(),
+        );
+        with_kv(&leader, "replks", "cache", |model| {
+            if let DataModel::KV(kve) = model {
+                kve.set("ephemeral".into(), "value".into()).unwrap();
+            }
+        });
+        with_kv(&leader, "default", "default", |model| {
+            if let DataModel::KV(kve) = model {
+                kve.set("fresh".into(), "value".into()).unwrap();
+            }
+        });
+        let snapshot = wal::snapshot(&leader);
+
+        // the follower has stale data, a table with a different model and a keyspace that
+        // the leader doesn't have
+        let follower = store_with_kv("stale", "stale");
+        assert!(follower.create_keyspace(ObjectID::try_from_slice("replks").unwrap()));
+        create_table(
+            &follower,
+            "replks",
+            "kv",
+            Table::from_model_code(bytemarks::BYTEMARK_MODEL_KV_BINSTR_LIST_BINSTR, false).unwrap(),
+        );
+        // a connection using the default table
+        let default_table = follower
+            .get_keyspace_atomic_ref("default".as_bytes())
+            .unwrap()
+            .get_table_atomic_ref("default".as_bytes())
+            .unwrap();
+        default_table
+            .get_kvstore()
+            .unwrap()
+            .set("old".into(), "value".into())
+            .unwrap();
+        // 2 spaces, 4 models and 4 keys
+        assert_eq!(wal::restore_snapshot(&follower, &snapshot), Some(10));
+        assert!(follower
+            .get_keyspace_atomic_ref("stale".as_bytes())
+            .is_none());
+        // the connection sees the leader's data
+        let kve = default_table.get_kvstore().unwrap();
+        assert_eq!(kve.len(), 1);
+        assert_eq!(kve.get_cloned("fresh").unwrap().unwrap(), "value");
+        with_kv(&follower, "replks", "kv", |model| {
+            let kve = match model {
+                DataModel::KV(kve) => kve,
+                _ => panic!("wrong model"),
+            };
+            assert_eq!(kve.len(), 2);
+            assert_eq!(kve.get_cloned("hello").unwrap().unwrap(), "world");
+            assert!(kve.get_expiry_ref().contains_key("session".as_bytes()));
+        });
+        with_kv(&follower, "replks", "lists", |model| {
+            assert!(matches!(model, DataModel::KVExtListmap(listmap) if listmap.len() == 1));
+        });
+        // volatile tables are only defined
+        with_kv(&follower, "replks", "cache", |model| {
+            assert!(matches!(model, DataModel::KV(kve) if kve.len() == 0));
+        });
+        // restoring again is harmless
+        assert_eq!(wal::restore_snapshot(&follower, &snapshot), Some(10));
+        assert_eq!(kve.len(), 1);
+    }
+
+    #[test]
+    fn test_replication_bad_snapshot() {
+        let leader = store_with_kv("replbad", "kv");
+        let mut snapshot = wal::snapshot(&leader);
+        let last = snapshot.len() - 1;
+        snapshot[last] ^= 0xFF;
+        let follower = store_with_kv("untouched", "kv");
+        assert_eq!(wal::restore_snapshot(&follower, &snapshot), None);
+        assert!(follower
+            .get_keyspace_atomic_ref("untouched".as_bytes())
+            .is_some());
+    }
+
+    #[test]
+    fn test_replication_snapshot_stream() {
+        let leader = store_with_kv("replstream", "kv");
+        with_kv(&leader, "replstream", "kv", |model| {
+            if let DataModel::KV(kve) = model {
+                for i in 0..10_000 {
+                    let key = format!("key{i}");
+                    kve.set(key.as_str().into(), "a moderately long value".into())
+                        .unwrap();
+                }
+            }
+        });
+        let mut frames = Vec::new();
+        wal::snapshot_with(&leader, |frame| {
+            frames.push(frame);
+            true
+        });
+        // the definitions and more than one frame of keys
+        assert!(frames.len() > 2);
+        assert_eq!(frames.concat(), wal::snapshot(&leader));
+        // stop after the definitions
+        let mut emitted = 0;
+        wal::snapshot_with(&leader, |_| {
+            emitted += 1;
+            false
+        });
+        assert_eq!(emitted, 1);
+    }
+
+    #[test]
+    fn test_replication_feed() {
+        let (path, old_path) = paths("feed");
+        let (ksid, tblid) = (
+            ObjectID::try_from_slice("feedks").unwrap(),
+            ObjectID::try_from_slice("feedtbl").unwrap(),
+        );
+        let leader = store_with_kv("feedks", "feedtbl");
+        let follower = store_with_kv("feedks", "feedtbl");
+        let log = wal::WriteAheadLog::open_at(&path, &old_path, WalFsync::Never).unwrap();
+        let mut feed = log.subscribe().unwrap();
+        with_kv(&leader, "feedks", "feedtbl", |model| {
+            if let DataModel::KV(kve) = model {
+                log.log_table(Some((&ksid, &tblid)), |batch| {
+                    kve.set("hello".into(), "world".into()).unwrap();
+                    batch.kv(kve, b"hello");
+                    kve.set("other".into(), "value".into()).unwrap();
+                    batch.kv(kve, b"other");
+                });
+            }
+        });
+        // volatile tables are never logged, so followers don't get anything either
+        log.log_table(None, |batch| batch.truncate());
+        let frame = feed.try_recv().unwrap();
+        assert!(feed.try_recv().is_err());
+        assert_eq!(wal::apply_frame(&follower, &frame), Some(2));
+        with_kv(&follower, "feedks", "feedtbl", |model| {
+            assert!(matches!(model, DataModel::KV(kve) if kve.len() == 2));
+        });
+        // heartbeats are empty frames
+        assert_eq!(wal::apply_frame(&follower, &wal::heartbeat()), Some(0));
+        assert_eq!(wal::apply_frame(&follower, &frame[1..]), None);
+        log.close_feed();
+        assert!(log.subscribe().is_none());
+        assert!(matches!(
+            feed.try_recv(),
+            Err(tokio::sync::broadcast::error::TryRecvError::Closed)
+        ));
+    }
+}
+
+mod archive_tests {
+    use crate::{
+        corestore::{
+            memstore::{Keyspace, ObjectID},
+            table::{DataModel, Table},
+            SharedSlice,
+        },
+        kvengine::{
+            rowmap::{Field, Schema},
+            types::FieldType,
+            LockedVec,
+        },
+        storage::v1::{
+            archive::{self, Archive},
+            bytemarks,
+            error::StorageEngineError,
+            header,
+        },
+    };
+    use std::{
+        fs,
+        io::{Cursor, Write},
+        path::{Path, PathBuf},
+        sync::Arc,
+    };
+
+    fn path(name: &str) -> PathBuf {
+        let path = archive::resolve_path(&format!("tests/{name}")).unwrap();
+        let _ = fs::remove_file(&path);
+        path
+    }
+    fn kv_with_expiry() -> Table {
+        let table = Table::new_default_kve();
+        let kve = table.get_kvstore().unwrap();
+        kve.set("hello".into(), "world".into()).unwrap();
+        kve.set_with_ttl("session".into(), "token".into(), 100)
+            .unwrap();
+        table
+    }
+    fn import_model(path: &Path) -> Table {
+        match archive::import(path).unwrap() {
+            Archive::Model(table) => table,
+            Archive::Space(_) => panic!("imported a space"),
+        }
+    }
+
+    #[test]
+    fn test_resolve_path() {
+        assert_eq!(
+            archive::resolve_path("tweets.skya").unwrap(),
+            PathBuf::from("data/archives/tweets.skya")
+        );
+        assert_eq!(
+            archive::resolve_path("./tenants/acme.skya").unwrap(),
+            PathBuf::from("data/archives/tenants/acme.skya")
+        );
+        for illegal in [
+            "",
+            ".",
+            "..",
+            "../PRELOAD",
+            "tenants/../../PRELOAD",
+            "/etc/passwd",
+        ] {
+            assert!(archive::resolve_path(illegal).is_none(), "{illegal}");
+        }
+    }
+
+    #[test]
+    fn test_model_roundtrip() {
+        let path = path("model.skya");
+        let tblid = ObjectID::try_from_slice("sessions").unwrap();
+        archive::export_model(&path, &tblid, Arc::new(kv_with_expiry())).unwrap();
+        let table = import_model(&path);
+        assert!(!table.is_volatile());
+        let kve = table.get_kvstore().unwrap();
+        assert_eq!(kve.len(), 2);
+        assert_eq!(kve.get_cloned("hello").unwrap().unwrap(), "world");
+        assert_eq!(kve.get_expiry_ref().len(), 1);
+        assert!(kve.get_expiry_ref().contains_key("session".as_bytes()));
+    }
+
+    #[test]
+    fn test_space_roundtrip() {
+        let path = path("space.skya");
+        let keyspace = Keyspace::empty();
+        let schema = Schema::new(vec![
+            Field::new("username", FieldType::String),
+            Field::new("age", FieldType::UInt64),
+        ])
+        .unwrap();
+        let users = Table::new_rowmap(schema.clone(), false);
+        if let DataModel::Rowmap(rm) = users.get_model_ref() {
+            rm.rows()
+                .get_inner_ref()
+                .upsert("sayan".into(), LockedVec::new(vec!["21".into()]));
+        }
+        let lists =
+            Table::from_model_code(bytemarks::BYTEMARK_MODEL_KV_BINSTR_LIST_BINSTR, false).unwrap();
+        if let DataModel::KVExtListmap(listmap) = lists.get_model_ref() {
+            listmap.get_inner_ref().upsert(
+                "mylist".into(),
+                LockedVec::new(vec!["a".into(), "b".into()]),
+            );
+        }
+        let cache = Table::from_model_code(bytemarks::BYTEMARK_MODEL_KV_BIN_BIN, true).unwrap();
+        cache
+            .get_kvstore()
+            .unwrap()
+            .set("ephemeral".into(), "value".into())
+            .unwrap();
+        for (tblid, table) in [
+            ("sessions", kv_with_expiry()),
+            ("users", users),
+            ("lists", lists),
+            ("cache", cache),
+        ] {
+            assert!(keyspace.create_table(ObjectID::try_from_slice(tblid).unwrap(), table));
+        }
+        archive::export_space(&path, &keyspace).unwrap();
+        let keyspace = match archive::import(&path).unwrap() {
+            Archive::Space(keyspace) => keyspace,
+            Archive::Model(_) => panic!("imported a model"),
+        };
+        assert_eq!(keyspace.table_count(), 4);
+        let get = |tblid: &str| keyspace.get_table_atomic_ref(tblid.as_bytes()).unwrap();
+        assert_eq!(get("sessions").get_kvstore().unwrap().len(), 2);
+        let users = get("users");
+        assert_eq!(users.get_schema(), Some(&schema));
+        match users.get_model_ref() {
+            DataModel::Rowmap(rm) => assert_eq!(
+                rm.rows().list_cloned_full(b"sayan").unwrap().unwrap(),
+                vec![SharedSlice::from("21")]
+            ),
+            _ => panic!("wrong model"),
+        }
+        assert!(
+            matches!(get("lists").get_model_ref(), DataModel::KVExtListmap(kvl) if kvl.len() == 1)
+        );
+        // volatile models keep their definition but not their data
+        let cache = get("cache");
+        assert!(cache.is_volatile());
+        assert_eq!(cache.get_kvstore().unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_import_foreign_endian() {
+        // a binstr/binstr model with one key, as written on a machine with the other endian
+        let swapped = |size: usize| {
+            if cfg!(target_endian = "little") {
+                (size as u64).to_be_bytes()
+            } else {
+                (size as u64).to_le_bytes()
+            }
+        };
+        let foreign_mark = if cfg!(target_endian = "little") { 1 } else { 0 };
+        let mut data = Vec::new();
+        data.extend(swapped(1));
+        data.extend(swapped(5));
+        data.extend(swapped(5));
+        data.extend(b"helloworld");
+        let mut body = Vec::new();
+        body.extend(b"SKYA");
+        body.extend([1, foreign_mark, 1]);
+        body.extend(swapped(1));
+        body.extend(swapped(5));
+        body.extend(b"greet");
+        body.extend([bytemarks::BYTEMARK_STORAGE_PERSISTENT, 0]);
+        body.extend(swapped(data.len()));
+        body.extend(&data);
+        body.extend(swapped(0));
+        body.extend(swapped(0));
+        let mut file = Cursor::new(Vec::new());
+        header::write_with_header(&mut file, None, |w| w.write_all(&body)).unwrap();
+        let path = path("foreign.skya");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, file.into_inner()).unwrap();
+        let table = import_model(&path);
+        let kve = table.get_kvstore().unwrap();
+        assert_eq!(kve.len(), 1);
+        assert_eq!(kve.get_cloned("hello").unwrap().unwrap(), "world");
+    }
+
+    #[test]
+    fn test_import_bad_archive() {
+        let path = path("bad.skya");
+        assert!(matches!(
+            archive::import(&path),
+            Err(StorageEngineError::IoErrorExtra(..))
+        ));
+        let tblid = ObjectID::try_from_slice("sessions").unwrap();
+        archive::export_model(&path, &tblid, Arc::new(kv_with_expiry())).unwrap();
+        let mut file = fs::read(&path).unwrap();
+        // not an archive
+        fs::write(&path, b"hello").unwrap();
+        assert!(matches!(
+            archive::import(&path),
+            Err(StorageEngineError::BadMetadata(_))
+        ));
+        // damaged
+        let last = file.len() - 1;
+        file[last] ^= 0xFF;
+        fs::write(&path, &file).unwrap();
+        assert!(matches!(
+            archive::import(&path),
+            Err(StorageEngineError::BadHeader(..))
+        ));
+    }
+}
+
+mod encryption_tests {
+    use crate::{
+        config::WalFsync,
+        corestore::{
+            memstore::{Memstore, DEFAULT},
+            table::DataModel,
+        },
+        storage::v1::{
+            dump,
+            encryption::{self, EncryptionError, Key},
+            error::StorageEngineError,
+            flush::{self, StorageTarget},
+            header::{self, HeaderError, HEADER_SIZE},
+            verify, wal,
+        },
+        util::os::{self, EntryKind},
+    };
+    use std::{
+        fs,
+        io::{Cursor, Write},
+    };
+
+    const DATADIR: &str = "data/enctests";
+
+    /// A data directory (minus its WAL) that is encrypted with a key of its own, so that the
+    /// configured key of the test process isn't needed
+    struct SealedTarget(&'static Key);
+
+    impl StorageTarget for SealedTarget {
+        const NEEDS_TREE_INIT: bool = true;
+        const SHOULD_UNTRIP_PRELOAD_TRIPSWITCH: bool = false;
+        const IS_DATA_DIRECTORY: bool = false;
+        fn root(&self) -> String {
+            format!("{DATADIR}/ks")
+        }
+        fn key(&self) -> Option<&Key> {
+            Some(self.0)
+        }
+    }
+
+    fn leaked_key() -> &'static Key {
+        Box::leak(Box::new(Key::generate()))
+    }
+    fn data_files() -> Vec<Vec<u8>> {
+        os::rlistdir(format!("{DATADIR}/ks"))
+            .unwrap()
+            .into_iter()
+            .filter_map(|entry| match entry {
+                EntryKind::File(path) => Some(fs::read(path).unwrap()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_seal_and_open() {
+        let key = Key::generate();
+        let sealed = encryption::seal(&key, b"hello world").unwrap();
+        assert_eq!(sealed.len(), 11 + encryption::SEAL_OVERHEAD);
+        assert!(!sealed.windows(11).any(|w| w == b"hello world"));
+        assert_eq!(
+            encryption::open(Some(&key), &sealed).unwrap(),
+            b"hello world"
+        );
+        // every payload has its own salt
+        assert_ne!(encryption::seal(&key, b"hello world").unwrap(), sealed);
+        assert_eq!(
+            encryption::open(Some(&Key::generate()), &sealed),
+            Err(EncryptionError::WrongKey)
+        );
+        assert_eq!(encryption::open(None, &sealed), Err(EncryptionError::NoKey));
+        let mut tampered = sealed.clone();
+        tampered[encryption::KEY_ID_SIZE + 20] ^= 1;
+        assert_eq!(
+            encryption::open(Some(&key), &tampered),
+            Err(EncryptionError::Unauthentic)
+        );
+        assert_eq!(
+            encryption::open(Some(&key), &sealed[..sealed.len() - 1]),
+            Err(EncryptionError::Unauthentic)
+        );
+    }
+
+    #[test]
+    fn test_key_file() {
+        fs::create_dir_all(DATADIR).unwrap();
+        let path = format!("{DATADIR}/keyfile");
+        let hex = "00112233445566778899aabbccddeeff00112233445566778899AABBCCDDEEFF";
+        fs::write(&path, format!("{hex}\n")).unwrap();
+        let key = Key::from_file(&path).unwrap();
+        let again = Key::from_file(&path).unwrap();
+        assert_eq!(key.id(), again.id());
+        // the key itself is never printed
+        assert!(!format!("{key:?}").contains("00112233"));
+        for bad in [&hex[1..], "hello", &hex.replace('0', "g")] {
+            fs::write(&path, bad).unwrap();
+            assert!(Key::from_file(&path).is_err());
+        }
+    }
+
+    #[test]
+    fn test_encrypted_header() {
+        let key = Key::generate();
+        let mut file = Cursor::new(Vec::new());
+        header::write_with_header(&mut file, Some(&key), |w| {
+            w.write_all(b"hello ")?;
+            w.write_all(b"world")
+        })
+        .unwrap();
+        let file = file.into_inner();
+        assert!(header::is_encrypted(&file));
+        assert_eq!(file.len(), HEADER_SIZE + 11 + encryption::SEAL_OVERHEAD);
+        // the checksum can be verified without the key
+        assert!(header::payload(&file).is_ok());
+        assert_eq!(
+            header::open(&file, Some(&key)).unwrap().as_ref(),
+            b"hello world"
+        );
+        assert_eq!(
+            header::open(&file, None),
+            Err(HeaderError::Encryption(EncryptionError::NoKey))
+        );
+        let mut flipped = file.clone();
+        flipped[HEADER_SIZE + 30] ^= 1;
+        assert_eq!(
+            header::open(&flipped, Some(&key)),
+            Err(HeaderError::ChecksumMismatch)
+        );
+    }
+
+    #[test]
+    fn test_flush_replay_and_rekey() {
+        let _ = fs::remove_dir_all(DATADIR);
+        let key = leaked_key();
+        let store = Memstore::new_default();
+        let ks = store.get_keyspace_atomic_ref(&DEFAULT).unwrap();
+        let tbl = ks.get_table_atomic_ref(&DEFAULT).unwrap();
+        let kve = tbl.get_kvstore().unwrap();
+        kve.set("hello".into(), "world".into()).unwrap();
+        flush::flush_full(SealedTarget(key), &store).unwrap();
+        let files = data_files();
+        assert!(!files.is_empty());
+        assert!(files.iter().all(|file| header::is_encrypted(file)));
+        // without the key, nothing can be read
+        assert!(matches!(
+            dump::list(format!("{DATADIR}/ks")),
+            Err(StorageEngineError::Encryption(_, EncryptionError::NoKey))
+        ));
+        // but the checksums can still be verified
+        let report = verify::verify_datadir(DATADIR).unwrap();
+        assert!(report.damaged.is_empty());
+        assert_eq!(report.sealed.len(), files.len());
+        // the log is sealed too
+        let (path, old_path) = (format!("{DATADIR}/WAL"), format!("{DATADIR}/WAL.old"));
+        let log = wal::WriteAheadLog::open_at(&path, &old_path, WalFsync::Always)
+            .unwrap()
+            .with_key(Some(key));
+        log.log_table(Some((&DEFAULT, &DEFAULT)), |batch| {
+            kve.set("sealed".into(), "frame".into()).unwrap();
+            batch.kv(kve, b"sealed");
+        });
+        drop(log);
+        assert!(!fs::read(&path).unwrap().windows(6).any(|w| w == b"sealed"));
+        assert!(matches!(
+            wal::replay_at_with(&path, &old_path, Some(&Key::generate()), &store),
+            Err(StorageEngineError::Encryption(_, EncryptionError::WrongKey))
+        ));
+        // a wrong key isn't mistaken for a torn tail
+        assert_eq!(
+            wal::replay_at_with(&path, &old_path, Some(key), &store).unwrap(),
+            1
+        );
+        // decrypt everything
+        let decrypted = encryption::rekey(DATADIR, Some(key), None).unwrap();
+        assert_eq!(decrypted, files.len() + 1);
+        assert!(data_files().iter().all(|file| !header::is_encrypted(file)));
+        assert!(verify::verify_datadir(DATADIR).unwrap().sealed.is_empty());
+        assert_eq!(encryption::rekey(DATADIR, Some(key), None).unwrap(), 0);
+        let models = dump::list(format!("{DATADIR}/ks")).unwrap();
+        assert!(models.iter().any(|m| m.space == "default"));
+        let restored = Memstore::new_default();
+        assert_eq!(
+            wal::replay_at_with(&path, &old_path, None, &restored).unwrap(),
+            1
+        );
+        let ks = restored.get_keyspace_atomic_ref(&DEFAULT).unwrap();
+        let tbl = ks.get_table_atomic_ref(&DEFAULT).unwrap();
+        match tbl.get_model_ref() {
+            DataModel::KV(kve) => assert_eq!(kve.get_cloned("sealed").unwrap().unwrap(), "frame"),
+            _ => panic!("wrong model"),
+        }
+        // and encrypt it again with another key
+        let other = Key::generate();
+        assert_eq!(
+            encryption::rekey(DATADIR, None, Some(&other)).unwrap(),
+            decrypted
+        );
+        assert_eq!(encryption::rekey(DATADIR, None, Some(&other)).unwrap(), 0);
+        assert!(data_files().iter().all(|file| header::is_encrypted(file)));
+        assert!(matches!(
+            encryption::rekey(DATADIR, Some(key), None),
+            Err(StorageEngineError::Encryption(_, EncryptionError::WrongKey))
+        ));
+        assert_eq!(
+            wal::replay_at_with(&path, &old_path, Some(&other), &restored).unwrap(),
+            1
+        );
+    }
+}
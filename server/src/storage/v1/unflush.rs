@@ -31,57 +31,76 @@
 use {
     super::bytemarks,
     crate::{
+        auth::grants::Grants,
         corestore::{
             memstore::{Keyspace, Memstore, ObjectID, SystemKeyspace, SYSTEM},
-            table::{SystemTable, Table},
+            table::{DataModel, SystemTable, Table},
+            SharedSlice,
         },
+        kvengine::{rowmap::Schema, KVEValue, KVEngine},
+        registry,
         storage::v1::{
             de::DeserializeInto,
+            encryption,
             error::{ErrorContext, StorageEngineError, StorageEngineResult},
             flush::Autoflush,
-            interface::DIR_KSROOT,
+            header::{self, HeaderError},
+            interface::{DIR_ARCHIVES, DIR_KSROOT, EXPIRY_FILE_SUFFIX, SCHEMA_FILE_SUFFIX},
             preload::LoadedPartfile,
             Coremap,
         },
         util::Wrapper,
     },
     core::mem::transmute,
-    std::{fs, io::ErrorKind, path::Path, sync::Arc},
+    std::{borrow::Cow, fs, io::ErrorKind, path::Path, sync::Arc},
 };
 
 type PreloadSet = std::collections::HashSet<ObjectID>;
-const PRELOAD_PATH: &str = "data/ks/PRELOAD";
 
 /// A keyspace that can be restored from disk storage
 pub trait UnflushableKeyspace: Sized {
-    /// Unflush routine for a keyspace
-    fn unflush_keyspace(partmap: LoadedPartfile, ksid: &ObjectID) -> StorageEngineResult<Self>;
+    /// Unflush routine for a keyspace in the tree at `root`
+    fn unflush_keyspace(
+        root: &Path,
+        partmap: LoadedPartfile,
+        ksid: &ObjectID,
+    ) -> StorageEngineResult<Self>;
 }
 
 impl UnflushableKeyspace for Keyspace {
-    fn unflush_keyspace(partmap: LoadedPartfile, ksid: &ObjectID) -> StorageEngineResult<Self> {
+    fn unflush_keyspace(
+        root: &Path,
+        partmap: LoadedPartfile,
+        ksid: &ObjectID,
+    ) -> StorageEngineResult<Self> {
         let ks: Coremap<ObjectID, Arc<Table>> = Coremap::with_capacity(partmap.len());
         for (tableid, (table_storage_type, model_code)) in partmap.into_iter() {
-            if table_storage_type > 1 {
-                return Err(StorageEngineError::bad_metadata_in_table(ksid, &tableid));
-            }
-            let is_volatile = table_storage_type == bytemarks::BYTEMARK_STORAGE_VOLATILE;
-            let tbl = self::read_table::<Table>(ksid, &tableid, is_volatile, model_code)?;
+            let (is_volatile, policy) = Table::from_storage_type(table_storage_type)
+                .ok_or_else(|| StorageEngineError::bad_metadata_in_table(ksid, &tableid))?;
+            let tbl = self::read_table_in::<Table>(root, ksid, &tableid, is_volatile, model_code)?
+                .with_eviction(policy);
+            // the files we just read are the table; no need to write them again until it changes
+            tbl.mark_saved(tbl.mutation_count());
             ks.true_if_insert(tableid, Arc::new(tbl));
         }
-        Ok(Keyspace::init_with_all_def_strategy(ks))
+        Ok(Keyspace::init_with_tables(ks))
     }
 }
 
 impl UnflushableKeyspace for SystemKeyspace {
-    fn unflush_keyspace(partmap: LoadedPartfile, ksid: &ObjectID) -> StorageEngineResult<Self> {
+    fn unflush_keyspace(
+        root: &Path,
+        partmap: LoadedPartfile,
+        ksid: &ObjectID,
+    ) -> StorageEngineResult<Self> {
         let ks: Coremap<ObjectID, Wrapper<SystemTable>> = Coremap::with_capacity(partmap.len());
         for (tableid, (table_storage_type, model_code)) in partmap.into_iter() {
             if table_storage_type > 1 {
                 return Err(StorageEngineError::bad_metadata_in_table(ksid, &tableid));
             }
             let is_volatile = table_storage_type == bytemarks::BYTEMARK_STORAGE_VOLATILE;
-            let tbl = self::read_table::<SystemTable>(ksid, &tableid, is_volatile, model_code)?;
+            let tbl =
+                self::read_table_in::<SystemTable>(root, ksid, &tableid, is_volatile, model_code)?;
             ks.true_if_insert(tableid, Wrapper::new(tbl));
         }
         Ok(SystemKeyspace::new(ks))
@@ -95,31 +114,74 @@ pub trait UnflushableTable: Sized {
         filepath: impl AsRef<Path>,
         model_code: u8,
         volatile: bool,
+    ) -> StorageEngineResult<Self> {
+        Self::unflush_table_from(filepath.as_ref(), model_code, volatile)
+    }
+    /// Same as [`UnflushableTable::unflush_table`], but the parts of the table are read from
+    /// `source`
+    fn unflush_table_from<S: TableSource + ?Sized>(
+        source: &S,
+        model_code: u8,
+        volatile: bool,
     ) -> StorageEngineResult<Self>;
 }
 
+/// Where the serialized parts of a table are read from. A table is made up of its data and,
+/// depending on the model, its expiry deadlines and its schema
+pub trait TableSource {
+    /// Read the part of the table that is stored with `suffix` (an empty suffix for the data,
+    /// [`EXPIRY_FILE_SUFFIX`] or [`SCHEMA_FILE_SUFFIX`]). Returns `None` if the table doesn't
+    /// have that part
+    fn read_part(&self, suffix: &str) -> StorageEngineResult<Option<Cow<'_, [u8]>>>;
+    /// The name of a part, used in errors
+    fn part_name(&self, suffix: &str) -> String;
+}
+
+/// The parts of a table in a data directory are files next to each other
+impl TableSource for Path {
+    fn read_part(&self, suffix: &str) -> StorageEngineResult<Option<Cow<'_, [u8]>>> {
+        let mut filepath = self.as_os_str().to_owned();
+        filepath.push(suffix);
+        match fs::read(&filepath) {
+            Ok(data) => check_header(&filepath, data).map(|data| Some(Cow::Owned(data))),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(StorageEngineError::ioerror_extra(
+                e,
+                format!("reading file {}", filepath.to_string_lossy()),
+            )),
+        }
+    }
+    fn part_name(&self, suffix: &str) -> String {
+        let mut filepath = self.as_os_str().to_owned();
+        filepath.push(suffix);
+        filepath.to_string_lossy().to_string()
+    }
+}
+
 #[allow(clippy::transmute_int_to_bool)]
 impl UnflushableTable for Table {
-    fn unflush_table(
-        filepath: impl AsRef<Path>,
+    fn unflush_table_from<S: TableSource + ?Sized>(
+        source: &S,
         model_code: u8,
         volatile: bool,
     ) -> StorageEngineResult<Self> {
         let ret = match model_code {
             // pure KVEBlob: [0, 3]
             x if x < 4 => {
-                let data = decode(filepath, volatile)?;
+                let data = decode(source, volatile)?;
                 let (k_enc, v_enc) = unsafe {
                     // UNSAFE(@ohsayan): Safe because of the above match. Just a lil bitmagic
                     let key: bool = transmute(model_code >> 1);
                     let value: bool = transmute(((model_code >> 1) + (model_code & 1)) % 2);
                     (key, value)
                 };
-                Table::new_pure_kve_with_data(data, volatile, k_enc, v_enc)
+                let tbl = Table::new_pure_kve_with_data(data, volatile, k_enc, v_enc);
+                restore_expiry(&tbl, source, volatile)?;
+                tbl
             }
             // KVExtlistmap: [4, 7]
             x if x < 8 => {
-                let data = decode(filepath, volatile)?;
+                let data = decode(source, volatile)?;
                 let (k_enc, v_enc) = unsafe {
                     // UNSAFE(@ohsayan): Safe because of the above match. Just a lil bitmagic
                     let code = model_code - 4;
@@ -127,97 +189,347 @@ impl UnflushableTable for Table {
                     let value: bool = transmute(code % 2);
                     (key, value)
                 };
-                Table::new_kve_listmap_with_data(data, volatile, k_enc, v_enc)
+                let tbl = Table::new_kve_listmap_with_data(data, volatile, k_enc, v_enc);
+                match read_max_len(source)? {
+                    Some(max_len) => tbl.with_max_len(max_len).ok_or_else(|| {
+                        StorageEngineError::CorruptedFile(source.part_name(SCHEMA_FILE_SUFFIX))
+                    })?,
+                    None => tbl,
+                }
             }
-            _ => {
-                return Err(StorageEngineError::BadMetadata(
-                    filepath.as_ref().to_string_lossy().to_string(),
-                ))
+            bytemarks::BYTEMARK_MODEL_ROWMAP => {
+                let schema = read_schema(source)?;
+                let data = decode_with(source, volatile, |data| {
+                    super::de::deserialize_rows(data, &schema)
+                })?;
+                // every row must have a value of the right type for each field
+                if !data.iter().all(|row| schema.is_row_ok(&row.value().read())) {
+                    return Err(StorageEngineError::CorruptedFile(source.part_name("")));
+                }
+                Table::new_rowmap_with_data(schema, data, volatile)
             }
+            // ordered KVEBlob: [9, 12]
+            bytemarks::BYTEMARK_MODEL_KV_ORDERED_BIN_BIN
+                ..=bytemarks::BYTEMARK_MODEL_KV_ORDERED_STR_BIN => {
+                let data = decode(source, volatile)?;
+                let (k_enc, v_enc) = unsafe {
+                    // UNSAFE(@ohsayan): Safe because of the above match. Same as a pure KVEBlob
+                    let code = model_code - bytemarks::BYTEMARK_MODEL_KV_ORDERED_BIN_BIN;
+                    let key: bool = transmute(code >> 1);
+                    let value: bool = transmute(((code >> 1) + (code & 1)) % 2);
+                    (key, value)
+                };
+                let tbl = Table::new_ordered_kve_with_data(data, volatile, k_enc, v_enc);
+                restore_expiry(&tbl, source, volatile)?;
+                tbl
+            }
+            // queue: [144, 147]
+            x if x >= bytemarks::BYTEMARK_MODEL_KV_QUEUE => {
+                let (k_enc, i_enc) = Table::encodings_from_queue_model_code(model_code)
+                    .ok_or_else(|| StorageEngineError::BadMetadata(source.part_name("")))?;
+                let data = decode(source, volatile)?;
+                let tbl = Table::new_queue_with_data(data, volatile, k_enc, i_enc);
+                if let DataModel::Queue(queue) = tbl.get_model_ref() {
+                    check_member_encoding(queue, source)?;
+                }
+                tbl
+            }
+            // sorted set: [140, 143]
+            x if x >= bytemarks::BYTEMARK_MODEL_KV_SORTED_SET => {
+                let (k_enc, m_enc) = Table::encodings_from_sorted_set_model_code(model_code)
+                    .ok_or_else(|| StorageEngineError::BadMetadata(source.part_name("")))?;
+                let data = decode(source, volatile)?;
+                let tbl = Table::new_sorted_set_with_data(data, volatile, k_enc, m_enc);
+                if let DataModel::SortedSet(zset) = tbl.get_model_ref() {
+                    check_member_encoding(zset, source)?;
+                }
+                tbl
+            }
+            // set: [136, 139]
+            x if x >= bytemarks::BYTEMARK_MODEL_KV_SET => {
+                let (k_enc, m_enc) = Table::encodings_from_set_model_code(model_code)
+                    .ok_or_else(|| StorageEngineError::BadMetadata(source.part_name("")))?;
+                let data = decode(source, volatile)?;
+                let tbl = Table::new_set_with_data(data, volatile, k_enc, m_enc);
+                if let DataModel::Set(set) = tbl.get_model_ref() {
+                    check_member_encoding(set, source)?;
+                }
+                tbl
+            }
+            // hashmap: [128, 135]
+            x if x >= bytemarks::BYTEMARK_MODEL_KV_MAP => {
+                let (k_enc, f_enc, v_enc) = Table::encodings_from_map_model_code(model_code)
+                    .ok_or_else(|| StorageEngineError::BadMetadata(source.part_name("")))?;
+                let data = decode(source, volatile)?;
+                let tbl = Table::new_hashmap_with_data(data, volatile, k_enc, f_enc, v_enc);
+                // every field and value must have the right encoding
+                if let DataModel::Hashmap(hm) = tbl.get_model_ref() {
+                    let maps = hm.maps().get_inner_ref();
+                    if !maps.iter().all(|map| hm.is_map_ok(&map.value().read())) {
+                        return Err(StorageEngineError::CorruptedFile(source.part_name("")));
+                    }
+                }
+                tbl
+            }
+            // typed KVEBlob: [16, ..]
+            x if x >= bytemarks::BYTEMARK_MODEL_KV_TYPED => {
+                let types = Table::types_from_typed_kv_model_code(model_code)
+                    .ok_or_else(|| StorageEngineError::BadMetadata(source.part_name("")))?;
+                let data = decode_with(source, volatile, |data| {
+                    super::de::deserialize_typed_map(data, types)
+                })?;
+                let tbl = Table::new_typed_kve_with_data(data, volatile, types.0, types.1);
+                restore_expiry(&tbl, source, volatile)?;
+                tbl
+            }
+            _ => return Err(StorageEngineError::BadMetadata(source.part_name(""))),
         };
         Ok(ret)
     }
 }
 
 impl UnflushableTable for SystemTable {
-    fn unflush_table(
-        filepath: impl AsRef<Path>,
+    fn unflush_table_from<S: TableSource + ?Sized>(
+        source: &S,
         model_code: u8,
         volatile: bool,
     ) -> StorageEngineResult<Self> {
         match model_code {
             0 => {
                 // this is the authmap
-                let authmap = decode(filepath, volatile)?;
+                let authmap = decode(source, volatile)?;
                 Ok(SystemTable::new_auth(Arc::new(authmap)))
             }
-            _ => Err(StorageEngineError::BadMetadata(
-                filepath.as_ref().to_string_lossy().to_string(),
-            )),
+            1 => {
+                // this is the grantmap
+                let grantmap: Coremap<_, Grants> = decode(source, volatile)?;
+                if grantmap.iter().all(|kv| kv.value().is_valid()) {
+                    Ok(SystemTable::new_grants(Arc::new(grantmap)))
+                } else {
+                    Err(StorageEngineError::CorruptedFile(source.part_name("")))
+                }
+            }
+            _ => Err(StorageEngineError::BadMetadata(source.part_name(""))),
         }
     }
 }
 
 #[inline(always)]
-fn decode<T: DeserializeInto>(
-    filepath: impl AsRef<Path>,
+fn decode<T: DeserializeInto, S: TableSource + ?Sized>(
+    source: &S,
+    volatile: bool,
+) -> StorageEngineResult<T> {
+    decode_with(source, volatile, super::de::deserialize_into)
+}
+
+/// Same as [`decode`], but with a custom deserializer for data that needs more context (like
+/// the types of the keys and values) to be deserialized
+fn decode_with<T: DeserializeInto, S: TableSource + ?Sized>(
+    source: &S,
     volatile: bool,
+    deserializer: impl FnOnce(&[u8]) -> Option<T>,
 ) -> StorageEngineResult<T> {
     if volatile {
         Ok(T::new_empty())
     } else {
-        let data = fs::read(filepath.as_ref()).map_err_context(format!(
-            "reading file {}",
-            filepath.as_ref().to_string_lossy()
-        ))?;
-        super::de::deserialize_into(&data).ok_or_else(|| {
-            StorageEngineError::CorruptedFile(filepath.as_ref().to_string_lossy().to_string())
-        })
+        let data = read_required_part(source, "")?;
+        deserializer(&data).ok_or_else(|| StorageEngineError::CorruptedFile(source.part_name("")))
     }
 }
 
-/// Read a given table into a [`Table`] object
+/// Same as [`TableSource::read_part`], but for parts that every table of the model has
+fn read_required_part<'a, S: TableSource + ?Sized>(
+    source: &'a S,
+    suffix: &str,
+) -> StorageEngineResult<Cow<'a, [u8]>> {
+    source.read_part(suffix)?.ok_or_else(|| {
+        StorageEngineError::ioerror_extra(
+            ErrorKind::NotFound.into(),
+            format!("reading file {}", source.part_name(suffix)),
+        )
+    })
+}
+
+/// Check the header of a data file that was read from `filepath`, returning its payload
+/// (decrypted, if the file is encrypted)
+fn check_header(filepath: impl AsRef<Path>, mut data: Vec<u8>) -> StorageEngineResult<Vec<u8>> {
+    let filepath = || filepath.as_ref().to_string_lossy().to_string();
+    let start = match header::open(&data, encryption::key()) {
+        Ok(Cow::Owned(decrypted)) => return Ok(decrypted),
+        Ok(Cow::Borrowed(payload)) => data.len() - payload.len(),
+        Err(HeaderError::Encryption(e)) => {
+            return Err(StorageEngineError::Encryption(filepath(), e))
+        }
+        Err(e) => return Err(StorageEngineError::BadHeader(filepath(), e)),
+    };
+    data.drain(..start);
+    Ok(data)
+}
+
+/// Restore the expiry deadlines of a KV table, if it has any
+fn restore_expiry<S: TableSource + ?Sized>(
+    tbl: &Table,
+    source: &S,
+    volatile: bool,
+) -> StorageEngineResult<()> {
+    if !volatile {
+        if let Some(expiry) = read_expiry(source)? {
+            if let DataModel::KV(kve) | DataModel::KVOrdered(kve) = tbl.get_model_ref() {
+                expiry
+                    .into_iter()
+                    .for_each(|(key, deadline)| kve.get_expiry_ref().upsert(key, deadline));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check that every member (or item) of every value in a set, sorted set or queue table has
+/// the right encoding
+fn check_member_encoding<T: KVEValue, S: TableSource + ?Sized>(
+    kve: &KVEngine<T>,
+    source: &S,
+) -> StorageEngineResult<()> {
+    let (_, t_m) = kve.get_type_tuple();
+    if kve
+        .get_inner_ref()
+        .iter()
+        .all(|value| value.value().verify_encoding(t_m).is_ok())
+    {
+        Ok(())
+    } else {
+        Err(StorageEngineError::CorruptedFile(source.part_name("")))
+    }
+}
+
+/// Read the expiry deadlines of a table, if the table has any
+fn read_expiry<S: TableSource + ?Sized>(
+    source: &S,
+) -> StorageEngineResult<Option<Coremap<SharedSlice, u64>>> {
+    match source.read_part(EXPIRY_FILE_SUFFIX)? {
+        Some(data) => super::de::deserialize_expiry_map(&data)
+            .map(Some)
+            .ok_or_else(|| StorageEngineError::CorruptedFile(source.part_name(EXPIRY_FILE_SUFFIX))),
+        None => Ok(None),
+    }
+}
+
+/// Read the schema of a table. Unlike expiry deadlines, tables with named fields always have
+/// a schema
+pub(super) fn read_schema<S: TableSource + ?Sized>(source: &S) -> StorageEngineResult<Schema> {
+    let data = read_required_part(source, SCHEMA_FILE_SUFFIX)?;
+    super::de::deserialize_schema(&data)
+        .ok_or_else(|| StorageEngineError::CorruptedFile(source.part_name(SCHEMA_FILE_SUFFIX)))
+}
+
+/// Read the maximum length of the lists of a list model, if the model is capped
+pub(super) fn read_max_len<S: TableSource + ?Sized>(
+    source: &S,
+) -> StorageEngineResult<Option<usize>> {
+    match source.read_part(SCHEMA_FILE_SUFFIX)? {
+        Some(data) => super::de::deserialize_max_len(&data)
+            .map(Some)
+            .ok_or_else(|| StorageEngineError::CorruptedFile(source.part_name(SCHEMA_FILE_SUFFIX))),
+        None => Ok(None),
+    }
+}
+
+/// Read a given table in `data/ks` (see [`read_table_in`])
+#[cfg(test)]
+pub fn read_table<T: UnflushableTable>(
+    ksid: &ObjectID,
+    tblid: &ObjectID,
+    volatile: bool,
+    model_code: u8,
+) -> StorageEngineResult<T> {
+    self::read_table_in(Path::new(DIR_KSROOT), ksid, tblid, volatile, model_code)
+}
+
+/// Read a given table in the tree at `root` (like `data/ks` or a snapshot) into a [`Table`]
+/// object
 ///
 /// This will take care of volatility and the model_code. Just make sure that you pass the proper
 /// keyspace ID and a valid table ID
-pub fn read_table<T: UnflushableTable>(
+pub fn read_table_in<T: UnflushableTable>(
+    root: &Path,
     ksid: &ObjectID,
     tblid: &ObjectID,
     volatile: bool,
     model_code: u8,
 ) -> StorageEngineResult<T> {
-    let filepath = unsafe { concat_path!(DIR_KSROOT, ksid.as_str(), tblid.as_str()) };
+    let filepath = unsafe { root.join(ksid.as_str()).join(tblid.as_str()) };
     let tbl = T::unflush_table(filepath, model_code, volatile)?;
     Ok(tbl)
 }
 
 /// Read an entire keyspace into a Coremap. You'll need to initialize the rest
 pub fn read_keyspace<K: UnflushableKeyspace>(ksid: &ObjectID) -> StorageEngineResult<K> {
-    let partmap = self::read_partmap(ksid)?;
-    K::unflush_keyspace(partmap, ksid)
+    self::read_keyspace_in(Path::new(DIR_KSROOT), ksid)
 }
 
-/// Read the `PARTMAP` for a given keyspace
-pub fn read_partmap(ksid: &ObjectID) -> StorageEngineResult<LoadedPartfile> {
+/// Same as [`read_keyspace`], but for the tree at `root` (like `data/ks` or a snapshot)
+pub fn read_keyspace_in<K: UnflushableKeyspace>(
+    root: &Path,
+    ksid: &ObjectID,
+) -> StorageEngineResult<K> {
+    let partmap = self::read_partmap_in(root, ksid)?;
+    K::unflush_keyspace(root, partmap, ksid)
+}
+
+/// Read the user keyspaces in the snapshot at `root` so that they can replace the ones in a
+/// running server. Every table is marked unsaved since none of them are in the data directory
+pub fn read_snapshot(root: &Path) -> StorageEngineResult<Coremap<ObjectID, Arc<Keyspace>>> {
+    let (mut preload, _) = self::read_preload_in(root)?;
+    preload.remove(&SYSTEM);
+    let ksmap = Coremap::with_capacity(preload.len() + 1);
+    for ksid in preload {
+        let ks = self::read_keyspace_in::<Keyspace>(root, &ksid)?;
+        ks.tables.iter().for_each(|tbl| tbl.value().mark_unsaved());
+        ksmap.upsert(ksid, Arc::new(ks));
+    }
+    // like in `read_full`, the system keyspace has a placeholder here
+    ksmap.upsert(SYSTEM, Arc::new(Keyspace::empty()));
+    Ok(ksmap)
+}
+
+/// Read the `PARTMAP` for a given keyspace in the tree at `root` (like `data/ks` or a snapshot)
+pub fn read_partmap_in(
+    root: impl AsRef<Path>,
+    ksid: &ObjectID,
+) -> StorageEngineResult<LoadedPartfile> {
     let ksid_str = unsafe { ksid.as_str() };
-    let filepath = concat_path!(DIR_KSROOT, ksid_str, "PARTMAP");
+    let filepath = root.as_ref().join(ksid_str).join("PARTMAP");
     let partmap_raw = fs::read(&filepath)
         .map_err_context(format!("while reading {}", filepath.to_string_lossy()))?;
+    let partmap_raw = check_header(&filepath, partmap_raw)?;
     super::de::deserialize_set_ctype_bytemark(&partmap_raw)
         .ok_or_else(|| StorageEngineError::corrupted_partmap(ksid))
 }
 
-/// Read the `PRELOAD`
-pub fn read_preload() -> StorageEngineResult<PreloadSet> {
-    let read = fs::read(PRELOAD_PATH).map_err_context("reading PRELOAD")?;
-    super::preload::read_preload_raw(read)
+/// Read the `PRELOAD`. Also returns true if the data files need to be rewritten: either they
+/// were written by an older version that didn't checksum them, or they aren't encrypted
+/// although a key is configured
+pub fn read_preload() -> StorageEngineResult<(PreloadSet, bool)> {
+    self::read_preload_in(DIR_KSROOT)
+}
+
+/// Same as [`read_preload`], but for the tree at `root` (like `data/ks` or a snapshot)
+pub fn read_preload_in(root: impl AsRef<Path>) -> StorageEngineResult<(PreloadSet, bool)> {
+    let filepath = root.as_ref().join("PRELOAD");
+    let read =
+        fs::read(&filepath).map_err_context(format!("reading {}", filepath.to_string_lossy()))?;
+    let rewrite =
+        !header::has_header(&read) || (encryption::key().is_some() && !header::is_encrypted(&read));
+    let read = check_header(&filepath, read)?;
+    Ok((super::preload::read_preload_raw(&read)?, rewrite))
 }
 
 /// Read everything and return a [`Memstore`]
 ///
 /// If this is a new instance an empty store is returned while the directory tree
 /// is also created. If this is an already initialized instance then the store
-/// is read and returned (and any possible errors that are encountered are returned)
+/// is read, the write-ahead log is replayed on top of it and the store is returned
+/// (and any possible errors that are encountered are returned)
 pub fn read_full() -> StorageEngineResult<Memstore> {
     if is_new_instance()? {
         log::trace!("Detected new instance. Creating data directory");
@@ -239,7 +551,7 @@ pub fn read_full() -> StorageEngineResult<Memstore> {
         super::flush::flush_full(target, &store)?;
         return Ok(store);
     }
-    let mut preload = self::read_preload()?;
+    let (mut preload, rewrite) = self::read_preload()?;
     // HACK(@ohsayan): Pop off the preload from the serial read_keyspace list. It will fail
     assert!(preload.remove(&SYSTEM));
     let system_keyspace = self::read_keyspace::<SystemKeyspace>(&SYSTEM)?;
@@ -250,13 +562,32 @@ pub fn read_full() -> StorageEngineResult<Memstore> {
     }
     // HACK(@ohsayan): Now pop system back in here
     ksmap.upsert(SYSTEM, Arc::new(Keyspace::empty()));
-    Ok(Memstore::init_with_all(ksmap, system_keyspace))
+    let store = Memstore::init_with_all(ksmap, system_keyspace);
+    if rewrite {
+        // make sure that every file gets a checksum (and is encrypted) on the next flush
+        log::info!(
+            "Found data files without checksums or encryption. They will be rewritten on the next save"
+        );
+        registry::get_preload_tripswitch().trip();
+        store.keyspaces.iter().for_each(|ks| {
+            ks.value()
+                .tables
+                .iter()
+                .for_each(|tbl| tbl.value().mark_unsaved())
+        });
+    }
+    // now bring the store up to date with the writes made after the last flush
+    super::wal::replay(&store)?;
+    Ok(store)
 }
 
-/// Check if the `data` directory is non-empty (if not: we're on a new instance)
+/// Check if the `data` directory is non-empty (if not: we're on a new instance). Archives
+/// that were copied over before the first start don't count
 pub fn is_new_instance() -> StorageEngineResult<bool> {
     match fs::read_dir("data") {
-        Ok(mut dir) => Ok(dir.next().is_none()),
+        Ok(mut dir) => {
+            Ok(dir.all(|entry| entry.is_ok_and(|entry| entry.path() == Path::new(DIR_ARCHIVES))))
+        }
         Err(e) if e.kind().eq(&ErrorKind::NotFound) => Ok(true),
         Err(e) => Err(StorageEngineError::ioerror_extra(
             e,
@@ -0,0 +1,301 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Offline verification
+//!
+//! `skyd --verify <datadir>` checks the keyspaces in the data directory along with every
+//! snapshot, without starting the server. The header of every file is checked first (and
+//! encrypted files are decrypted, if an encryption key is configured). Then the `PRELOAD`,
+//! the `PARTMAP`s and the tables are decoded just like they would be on startup (skipping
+//! the files that already failed their header check). Without the key, encrypted files are
+//! only checked against their checksums
+
+use {
+    super::{
+        bytemarks, encryption,
+        error::StorageEngineError,
+        header,
+        interface::{EXPIRY_FILE_SUFFIX, SCHEMA_FILE_SUFFIX},
+        unflush::UnflushableTable,
+    },
+    crate::{
+        corestore::{
+            memstore::{ObjectID, SYSTEM},
+            table::{SystemTable, Table},
+        },
+        util::os::{self, EntryKind},
+        IoResult,
+    },
+    std::{
+        collections::HashSet,
+        fs,
+        io::ErrorKind,
+        path::{Path, PathBuf},
+        process,
+    },
+};
+
+/// The outcome of a verification run
+#[derive(Debug, Default)]
+pub struct Report {
+    /// the number of files that were checked
+    pub checked: usize,
+    /// files written by older versions, which have no checksum
+    pub unchecksummed: Vec<PathBuf>,
+    /// encrypted files that could only be checked against their checksums, since no key
+    /// was configured
+    pub sealed: Vec<PathBuf>,
+    /// damaged or missing files, along with what's wrong with them
+    pub damaged: Vec<(PathBuf, String)>,
+}
+
+impl Report {
+    fn damaged(&mut self, path: impl Into<PathBuf>, reason: impl ToString) {
+        self.damaged.push((path.into(), reason.to_string()));
+    }
+}
+
+/// Run `skyd --verify`: print the damaged files and exit with a non-zero code if there are any.
+/// Encrypted files are decrypted with the key in `keyfile`, if one is given
+pub fn verify_and_exit(datadir: &str, keyfile: Option<&str>) -> ! {
+    if let Some(keyfile) = keyfile {
+        match encryption::Key::from_file(keyfile) {
+            Ok(key) => {
+                encryption::set_key(key);
+            }
+            Err(e) => {
+                eprintln!("Failed to read the encryption key from `{keyfile}`: {e}");
+                crate::exit_error();
+            }
+        }
+    }
+    let report = match self::verify_datadir(datadir) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Failed to verify `{datadir}`: {e}");
+            crate::exit_error();
+        }
+    };
+    for (path, reason) in report.damaged.iter() {
+        println!("{}: {reason}", path.display());
+    }
+    if !report.unchecksummed.is_empty() {
+        println!(
+            "{} file(s) were written by an older version and have no checksum",
+            report.unchecksummed.len()
+        );
+    }
+    if !report.sealed.is_empty() {
+        println!(
+            "{} file(s) are encrypted and were only checked against their checksums. Pass \
+            --encryption-key-file to check them fully",
+            report.sealed.len()
+        );
+    }
+    println!(
+        "Checked {} file(s) in `{datadir}`: {} damaged",
+        report.checked,
+        report.damaged.len()
+    );
+    if report.damaged.is_empty() {
+        process::exit(0)
+    } else {
+        crate::exit_error()
+    }
+}
+
+/// Verify the keyspaces (in `ks`) and the snapshots (in `snaps` and `rsnap`) of a data directory
+pub fn verify_datadir(datadir: impl AsRef<Path>) -> IoResult<Report> {
+    let mut report = Report::default();
+    for root in self::trees(datadir.as_ref())? {
+        self::verify_tree(&root, &mut report)?;
+    }
+    Ok(report)
+}
+
+/// Returns the trees in a data directory: the keyspaces (in `ks`) followed by the snapshots
+/// (in `snaps` and `rsnap`)
+pub(super) fn trees(datadir: &Path) -> IoResult<Vec<PathBuf>> {
+    let mut roots = vec![datadir.join("ks")];
+    for snapdir in ["snaps", "rsnap"] {
+        let mut snapshots = Vec::new();
+        match fs::read_dir(datadir.join(snapdir)) {
+            Ok(dir) => {
+                for entry in dir {
+                    let path = entry?.path();
+                    if path.is_dir() {
+                        snapshots.push(path);
+                    }
+                }
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        snapshots.sort();
+        roots.extend(snapshots);
+    }
+    Ok(roots)
+}
+
+/// Verify a tree with a `PRELOAD` and a directory for each keyspace
+fn verify_tree(root: &Path, report: &mut Report) -> IoResult<()> {
+    // first check the headers of all the files. Files that fail (or can't be decrypted)
+    // aren't decoded
+    let mut skipped = HashSet::new();
+    for entry in os::rlistdir(root)? {
+        let path = match entry {
+            EntryKind::File(path) if !path.ends_with('_') => PathBuf::from(path),
+            // directories and temporary files left over by an interrupted flush
+            _ => continue,
+        };
+        report.checked += 1;
+        let data = fs::read(&path)?;
+        if !header::has_header(&data) {
+            report.unchecksummed.push(path);
+        } else if let Err(e) = header::payload(&data) {
+            report.damaged(&path, e);
+            skipped.insert(path);
+        } else if header::is_encrypted(&data) && encryption::key().is_none() {
+            report.sealed.push(path.clone());
+            skipped.insert(path);
+        } else if let Err(e) = header::open(&data, encryption::key()) {
+            report.damaged(&path, e);
+            skipped.insert(path);
+        }
+    }
+    // then decode everything, starting with the list of keyspaces
+    let preload = root.join("PRELOAD");
+    let keyspaces = match self::load(&preload, &skipped, report)? {
+        Some(data) => match super::preload::read_preload_raw(&data) {
+            Ok(keyspaces) => keyspaces,
+            Err(e) => {
+                report.damaged(&preload, reason(&e));
+                return Ok(());
+            }
+        },
+        None => return Ok(()),
+    };
+    for ksid in keyspaces {
+        let ks = unsafe { ksid.as_str() };
+        let partmap = root.join(ks).join("PARTMAP");
+        let tables = match self::load(&partmap, &skipped, report)? {
+            Some(data) => match super::de::deserialize_set_ctype_bytemark::<ObjectID>(&data) {
+                Some(tables) => tables,
+                None => {
+                    report.damaged(&partmap, "corrupted");
+                    continue;
+                }
+            },
+            None => continue,
+        };
+        for (tblid, (storage, model_code)) in tables {
+            let tbl = unsafe { tblid.as_str() };
+            let volatile = match Table::from_storage_type(storage) {
+                Some((volatile, _)) => volatile,
+                None => {
+                    report.damaged(&partmap, format!("bad metadata for table `{tbl}`"));
+                    continue;
+                }
+            };
+            let table = root.join(ks).join(tbl);
+            let with_suffix = |suffix| PathBuf::from(format!("{}{suffix}", table.display()));
+            // (file, must exist)
+            let mut files = vec![];
+            if !volatile {
+                files.push((table.clone(), true));
+                files.push((with_suffix(EXPIRY_FILE_SUFFIX), false));
+            }
+            if model_code == bytemarks::BYTEMARK_MODEL_ROWMAP {
+                files.push((with_suffix(SCHEMA_FILE_SUFFIX), true));
+            } else if (bytemarks::BYTEMARK_MODEL_KV_BINSTR_LIST_BINSTR
+                ..=bytemarks::BYTEMARK_MODEL_KV_STR_LIST_STR)
+                .contains(&model_code)
+            {
+                // only capped list models have one
+                files.push((with_suffix(SCHEMA_FILE_SUFFIX), false));
+            }
+            let mut skip = false;
+            for (file, required) in files {
+                if required && !file.exists() {
+                    report.damaged(file, "missing");
+                    skip = true;
+                } else {
+                    // already reported (or encrypted, without the key)
+                    skip |= skipped.contains(&file);
+                }
+            }
+            if skip {
+                continue;
+            }
+            let ret = if ksid == SYSTEM {
+                SystemTable::unflush_table(&table, model_code, volatile).map(|_| ())
+            } else {
+                Table::unflush_table(&table, model_code, volatile).map(|_| ())
+            };
+            if let Err(e) = ret {
+                match e {
+                    StorageEngineError::CorruptedFile(ref file)
+                    | StorageEngineError::BadMetadata(ref file)
+                    | StorageEngineError::BadHeader(ref file, _)
+                    | StorageEngineError::Encryption(ref file, _) => {
+                        report.damaged(file, reason(&e))
+                    }
+                    _ => report.damaged(&table, reason(&e)),
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Read the payload of a file that passed its header check. Returns `None` if the file has
+/// already been reported (or can't be decrypted)
+fn load(path: &Path, skipped: &HashSet<PathBuf>, report: &mut Report) -> IoResult<Option<Vec<u8>>> {
+    if skipped.contains(path) {
+        return Ok(None);
+    }
+    match fs::read(path) {
+        Ok(data) => Ok(header::open(&data, encryption::key())
+            .ok()
+            .map(|payload| payload.into_owned())),
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            report.damaged(path, "missing");
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// What's wrong with a file, without its path
+fn reason(e: &StorageEngineError) -> String {
+    match e {
+        StorageEngineError::CorruptedFile(_) => "corrupted".to_owned(),
+        StorageEngineError::BadMetadata(_) => "bad metadata".to_owned(),
+        StorageEngineError::BadHeader(_, e) => e.to_string(),
+        StorageEngineError::Encryption(_, e) => e.to_string(),
+        StorageEngineError::IoError(e) | StorageEngineError::IoErrorExtra(e, _) => e.to_string(),
+    }
+}
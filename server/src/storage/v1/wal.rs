@@ -0,0 +1,1462 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Write-ahead log
+//!
+//! Mutations on persistent tables (and DDL) are appended to `data/WAL` so that writes
+//! made between two BGSAVE cycles survive a crash. Every record is an _after-image_: it
+//! stores the state of a key (or object) right after the mutation rather than the
+//! mutation itself, which makes replaying a record more than once harmless. Maps and sets are
+//! the exception to storing a whole key: their records only hold the fields or members that
+//! were set, added or removed, which is still an after-image of those fields or members.
+//!
+//! ## Format
+//!
+//! The log is a sequence of frames: `[8B body len][body][4B CRC-32 of body]`. A body holds
+//! one or more records: `[1B opcode][8B kslen][ks][8B tbllen][tbl]` followed by the
+//! opcode-specific payload. All integers are little-endian.
+//!
+//! If an encryption key is configured, the body of every frame is sealed (see the
+//! [`encryption`] module) and the highest bit of its length is set. The checksum is that of
+//! the sealed body, so a torn tail is still detected without the key. Frames published to
+//! followers are never sealed.
+//!
+//! [`encryption`]: super::encryption
+//!
+//! ## Rotation
+//!
+//! Before BGSAVE flushes the store, the log is moved to `data/WAL.old` (or appended to it,
+//! if an earlier flush failed) and a fresh log is started. Once the flush succeeds, the old
+//! log is discarded. On startup, `WAL.old` and then `WAL` are replayed on top of the data
+//! files. A torn or corrupted tail is ignored (and truncated away).
+//!
+//! ## Replication
+//!
+//! Every frame that is appended is also published to the followers tailing the log (see
+//! [`WriteAheadLog::subscribe`]). A follower first pulls a [`snapshot`] which encodes the
+//! whole store as records in the same format, so both are applied with the same code.
+//! Since records are after-images, it doesn't matter if the snapshot already has some of
+//! the mutations that follow it.
+
+use {
+    super::{
+        bytemarks,
+        checksum::crc32,
+        encryption::{self, Key},
+    },
+    crate::{
+        config::WalFsync,
+        corestore::{
+            htable::Coremap,
+            memstore::{Memstore, ObjectID, SYSTEM},
+            table::{DataModel, Table},
+            SharedSlice,
+        },
+        kvengine::{
+            queue::{LockedQueue, Queue, Reservation},
+            rowmap::{Field, Schema},
+            sets::Score,
+            types::FieldType,
+            KVEStandard, LockedVec,
+        },
+        pubsub::KeyEvent,
+        registry,
+        storage::v1::error::{StorageEngineError, StorageEngineResult},
+        IoResult,
+    },
+    core::sync::atomic::{AtomicBool, Ordering},
+    parking_lot::{Mutex, RwLock},
+    std::{
+        borrow::Cow,
+        collections::{hash_map::DefaultHasher, BTreeMap, HashMap, VecDeque},
+        fs::{self, File, OpenOptions},
+        hash::{Hash, Hasher},
+        io::{ErrorKind, Write},
+        mem,
+        path::Path,
+        sync::Arc,
+    },
+    tokio::sync::broadcast,
+};
+
+/// The path to the active log
+pub const WAL_PATH: &str = "data/WAL";
+/// The path to the log that was rotated out before a flush
+pub const WAL_OLD_PATH: &str = "data/WAL.old";
+
+pub const FRAME_HEADER_SIZE: usize = 8;
+pub const FRAME_FOOTER_SIZE: usize = 4;
+/// Set in the length of a frame if its body is sealed
+const FRAME_SEALED: u64 = 1 << 63;
+/// The number of frames a follower can fall behind by before it is disconnected
+const FEED_CAPACITY: usize = 4096;
+/// The number of locks that order the mutations on tables
+const ORDER_STRIPES: usize = 64;
+/// A snapshot frame is closed once it grows past this size
+const SNAPSHOT_FRAME_SIZE: usize = 64 * 1024;
+/// The largest frame (including its header and footer) that a follower accepts from its
+/// leader. Anything longer is taken to be a corrupted length
+pub const MAX_FRAME_SIZE: u64 = 4 * 1024 * 1024 * 1024;
+
+/// A frame published to followers
+pub type Frame = Arc<[u8]>;
+
+// opcodes
+const OP_KV_PUT: u8 = 0;
+const OP_KV_DEL: u8 = 1;
+const OP_LIST_PUT: u8 = 2;
+const OP_TRUNCATE: u8 = 3;
+const OP_CREATE_SPACE: u8 = 4;
+const OP_DROP_SPACE: u8 = 5;
+const OP_CREATE_MODEL: u8 = 6;
+const OP_DROP_MODEL: u8 = 7;
+const OP_MAP_SET: u8 = 8;
+const OP_MAP_DEL: u8 = 9;
+const OP_SET_ADD: u8 = 10;
+/// Removes members from a set of either kind
+const OP_SET_DEL: u8 = 11;
+const OP_ZSET_ADD: u8 = 12;
+const OP_QUEUE_PUT: u8 = 13;
+/// Same as [`OP_CREATE_MODEL`], followed by the maximum length of the lists
+const OP_CREATE_CAPPED_MODEL: u8 = 14;
+
+/// A set of records that is appended to the log as a single frame. Records for a table are
+/// only emitted if the batch is bound to a persistent table
+pub struct WalBatch<'a> {
+    buf: Vec<u8>,
+    active: bool,
+    entity: Option<(&'a ObjectID, &'a ObjectID)>,
+}
+
+impl<'a> WalBatch<'a> {
+    fn new(entity: Option<(&'a ObjectID, &'a ObjectID)>) -> Self {
+        Self {
+            buf: vec![0; FRAME_HEADER_SIZE],
+            active: true,
+            entity,
+        }
+    }
+    /// A batch that drops everything (for volatile tables)
+    fn inactive() -> Self {
+        Self {
+            buf: Vec::new(),
+            active: false,
+            entity: None,
+        }
+    }
+    fn is_empty(&self) -> bool {
+        self.buf.len() <= FRAME_HEADER_SIZE
+    }
+    /// Drop everything that was recorded so far
+    pub fn discard(&mut self) {
+        self.buf.truncate(FRAME_HEADER_SIZE)
+    }
+    fn target(&self) -> Option<(&'a ObjectID, &'a ObjectID)> {
+        if self.active {
+            self.entity
+        } else {
+            None
+        }
+    }
+    fn put_u64(&mut self, int: u64) {
+        self.buf.extend_from_slice(&int.to_le_bytes());
+    }
+    fn put_slice(&mut self, slice: &[u8]) {
+        self.put_u64(slice.len() as u64);
+        self.buf.extend_from_slice(slice);
+    }
+    fn record(&mut self, opcode: u8, ks: &[u8], tbl: &[u8]) {
+        self.buf.push(opcode);
+        self.put_slice(ks);
+        self.put_slice(tbl);
+    }
+    /// Record the current state of `key` in a KV table (this also covers its expiry)
+    pub fn kv(&mut self, kve: &KVEStandard, key: &[u8]) {
+        if self.target().is_some() {
+            match kve.get_cloned_unchecked(key) {
+                Some(val) => self.kv_put(kve, key, &val),
+                None => self.del(key),
+            }
+        }
+    }
+    fn kv_put(&mut self, kve: &KVEStandard, key: &[u8], val: &[u8]) {
+        if let Some((ks, tbl)) = self.target() {
+            self.kv_put_in(ks, tbl, kve, key, val);
+        }
+    }
+    fn kv_put_in(&mut self, ks: &[u8], tbl: &[u8], kve: &KVEStandard, key: &[u8], val: &[u8]) {
+        let deadline = kve.get_expiry_ref().get(key).map(|d| *d).unwrap_or(0);
+        self.record(OP_KV_PUT, ks, tbl);
+        self.put_slice(key);
+        self.put_slice(val);
+        self.put_u64(deadline);
+    }
+    /// Record the contents of the list `key`. Pass in the list (rather than looking it up)
+    /// since callers usually hold a lock on it
+    pub fn list(&mut self, key: &[u8], list: &[SharedSlice]) {
+        if let Some((ks, tbl)) = self.target() {
+            self.list_in(ks, tbl, key, list);
+        }
+    }
+    fn list_in(&mut self, ks: &[u8], tbl: &[u8], key: &[u8], list: &[SharedSlice]) {
+        self.record(OP_LIST_PUT, ks, tbl);
+        self.put_slice(key);
+        self.put_u64(list.len() as u64);
+        list.iter().for_each(|item| self.put_slice(item));
+    }
+    /// Record that the given fields were set in the map `key`
+    pub fn map_set(&mut self, key: &[u8], pairs: &[(SharedSlice, SharedSlice)]) {
+        if let Some((ks, tbl)) = self.target() {
+            self.map_set_in(
+                ks,
+                tbl,
+                key,
+                pairs.iter().map(|(field, value)| (field, value)),
+            );
+        }
+    }
+    fn map_set_in<'b>(
+        &mut self,
+        ks: &[u8],
+        tbl: &[u8],
+        key: &[u8],
+        pairs: impl ExactSizeIterator<Item = (&'b SharedSlice, &'b SharedSlice)>,
+    ) {
+        self.record(OP_MAP_SET, ks, tbl);
+        self.put_slice(key);
+        self.put_u64(pairs.len() as u64);
+        for (field, value) in pairs {
+            self.put_slice(field);
+            self.put_slice(value);
+        }
+    }
+    /// Record that the given fields were removed from the map `key`
+    pub fn map_del(&mut self, key: &[u8], fields: &[SharedSlice]) {
+        if let Some((ks, tbl)) = self.target() {
+            self.record(OP_MAP_DEL, ks, tbl);
+            self.put_slice(key);
+            self.put_u64(fields.len() as u64);
+            fields.iter().for_each(|field| self.put_slice(field));
+        }
+    }
+    /// Record that the given members were added to the set `key`
+    pub fn set_add(&mut self, key: &[u8], members: &[SharedSlice]) {
+        if let Some((ks, tbl)) = self.target() {
+            self.set_add_in(ks, tbl, key, members.iter());
+        }
+    }
+    fn set_add_in<'b>(
+        &mut self,
+        ks: &[u8],
+        tbl: &[u8],
+        key: &[u8],
+        members: impl ExactSizeIterator<Item = &'b SharedSlice>,
+    ) {
+        self.record(OP_SET_ADD, ks, tbl);
+        self.put_slice(key);
+        self.put_u64(members.len() as u64);
+        members.for_each(|member| self.put_slice(member));
+    }
+    /// Record that the given members were removed from the set (or sorted set) `key`
+    pub fn set_del(&mut self, key: &[u8], members: &[SharedSlice]) {
+        if let Some((ks, tbl)) = self.target() {
+            self.record(OP_SET_DEL, ks, tbl);
+            self.put_slice(key);
+            self.put_u64(members.len() as u64);
+            members.iter().for_each(|member| self.put_slice(member));
+        }
+    }
+    /// Record the scores of the given members in the sorted set `key`
+    pub fn zset_add(&mut self, key: &[u8], members: &[(SharedSlice, Score)]) {
+        if let Some((ks, tbl)) = self.target() {
+            self.zset_add_in(
+                ks,
+                tbl,
+                key,
+                members.iter().map(|(member, score)| (member, *score)),
+            );
+        }
+    }
+    fn zset_add_in<'b>(
+        &mut self,
+        ks: &[u8],
+        tbl: &[u8],
+        key: &[u8],
+        members: impl ExactSizeIterator<Item = (&'b SharedSlice, Score)>,
+    ) {
+        self.record(OP_ZSET_ADD, ks, tbl);
+        self.put_slice(key);
+        self.put_u64(members.len() as u64);
+        for (member, score) in members {
+            self.put_slice(member);
+            self.buf.extend_from_slice(&score.to_le_bytes());
+        }
+    }
+    /// Record the contents of the queue `key`, including its reservations. Like with a list,
+    /// the caller passes in the queue that it holds a lock on
+    pub fn queue(&mut self, key: &[u8], queue: &Queue) {
+        if let Some((ks, tbl)) = self.target() {
+            self.queue_in(ks, tbl, key, queue);
+        }
+    }
+    fn queue_in(&mut self, ks: &[u8], tbl: &[u8], key: &[u8], queue: &Queue) {
+        self.record(OP_QUEUE_PUT, ks, tbl);
+        self.put_slice(key);
+        self.put_u64(queue.next_id());
+        self.put_u64(queue.ready().len() as u64);
+        queue.ready().iter().for_each(|item| self.put_slice(item));
+        self.put_u64(queue.reserved().len() as u64);
+        for (id, reservation) in queue.reserved() {
+            self.put_u64(*id);
+            self.put_u64(reservation.deadline);
+            self.put_slice(&reservation.item);
+        }
+    }
+    /// Record every key in `table`, calling `after_each` after every record
+    fn contents(
+        &mut self,
+        ks: &[u8],
+        tbl: &[u8],
+        table: &Table,
+        mut after_each: impl FnMut(&mut Self),
+    ) {
+        let rows = match table.get_model_ref() {
+            DataModel::KV(kve) | DataModel::KVOrdered(kve) => {
+                for entry in kve.get_inner_ref().iter() {
+                    self.kv_put_in(ks, tbl, kve, entry.key(), entry.value());
+                    after_each(self);
+                }
+                None
+            }
+            DataModel::KVExtListmap(listmap) => Some(listmap.get_inner_ref()),
+            DataModel::Rowmap(rowmap) => Some(rowmap.rows().get_inner_ref()),
+            DataModel::Hashmap(hm) => {
+                for entry in hm.maps().get_inner_ref().iter() {
+                    self.map_set_in(ks, tbl, entry.key(), entry.value().read().iter());
+                    after_each(self);
+                }
+                None
+            }
+            DataModel::Set(sets) => {
+                for entry in sets.get_inner_ref().iter() {
+                    self.set_add_in(ks, tbl, entry.key(), entry.value().read().iter());
+                    after_each(self);
+                }
+                None
+            }
+            DataModel::SortedSet(zsets) => {
+                for entry in zsets.get_inner_ref().iter() {
+                    self.zset_add_in(ks, tbl, entry.key(), entry.value().read().iter());
+                    after_each(self);
+                }
+                None
+            }
+            DataModel::Queue(queues) => {
+                for entry in queues.get_inner_ref().iter() {
+                    self.queue_in(ks, tbl, entry.key(), &entry.value().read());
+                    after_each(self);
+                }
+                None
+            }
+        };
+        for entry in rows.into_iter().flat_map(|rows| rows.iter()) {
+            self.list_in(ks, tbl, entry.key(), &entry.value().read());
+            after_each(self);
+        }
+    }
+    /// Record that `key` was removed
+    pub fn del(&mut self, key: &[u8]) {
+        if let Some((ks, tbl)) = self.target() {
+            self.record(OP_KV_DEL, ks, tbl);
+            self.put_slice(key);
+        }
+    }
+    /// Record that the table was truncated
+    pub fn truncate(&mut self) {
+        if let Some((ks, tbl)) = self.target() {
+            self.record(OP_TRUNCATE, ks, tbl);
+        }
+    }
+    pub fn create_space(&mut self, ks: &ObjectID) {
+        if self.active {
+            self.record(OP_CREATE_SPACE, ks, &[]);
+        }
+    }
+    pub fn drop_space(&mut self, ks: &ObjectID) {
+        if self.active {
+            self.record(OP_DROP_SPACE, ks, &[]);
+        }
+    }
+    /// Record that a model was created with the given storage type (see
+    /// [`Table::storage_type`]). Models with named fields also record their schema:
+    /// `[8B field count]([1B type][8B namelen][name])*` and capped list models their
+    /// maximum length: `[8B max len]`
+    pub fn create_model(
+        &mut self,
+        ks: &ObjectID,
+        tbl: &ObjectID,
+        model_code: u8,
+        storage: u8,
+        schema: Option<&Schema>,
+        max_len: Option<usize>,
+    ) {
+        if self.active {
+            let op = match max_len {
+                Some(_) => OP_CREATE_CAPPED_MODEL,
+                None => OP_CREATE_MODEL,
+            };
+            self.record(op, ks, tbl);
+            self.buf.push(model_code);
+            self.buf.push(storage);
+            if let Some(schema) = schema {
+                self.put_u64(schema.fields().len() as u64);
+                for field in schema.fields() {
+                    self.buf.push(field.ty().code());
+                    self.put_slice(field.name().as_bytes());
+                }
+            }
+            if let Some(max_len) = max_len {
+                self.put_u64(max_len as u64);
+            }
+        }
+    }
+    /// Record that `table` was created along with everything that's in it (for tables that
+    /// are created with data, like imported ones). The records end up in a single frame, so
+    /// they're replayed in full or not at all
+    pub fn create_model_with_data(&mut self, ks: &ObjectID, tbl: &ObjectID, table: &Table) {
+        if self.active {
+            self.create_model(
+                ks,
+                tbl,
+                table.get_model_code(),
+                table.storage_type(),
+                table.get_schema(),
+                table.max_len(),
+            );
+            if !table.is_volatile() {
+                self.contents(ks, tbl, table, |_| {});
+            }
+        }
+    }
+    pub fn drop_model(&mut self, ks: &ObjectID, tbl: &ObjectID) {
+        if self.active {
+            self.record(OP_DROP_MODEL, ks, tbl);
+        }
+    }
+    /// Fill in the frame header and append the checksum
+    fn finish(&mut self) -> &[u8] {
+        let body_len = (self.buf.len() - FRAME_HEADER_SIZE) as u64;
+        self.buf[..FRAME_HEADER_SIZE].copy_from_slice(&body_len.to_le_bytes());
+        let checksum = crc32(&self.buf[FRAME_HEADER_SIZE..]);
+        self.buf.extend_from_slice(&checksum.to_le_bytes());
+        &self.buf
+    }
+    /// Move the records into `out` as a frame once the batch has grown past `limit` bytes
+    fn spill(&mut self, out: &mut Vec<u8>, limit: usize) {
+        if !self.is_empty() && self.buf.len() >= limit {
+            out.extend_from_slice(self.finish());
+            self.buf.truncate(0);
+            self.buf.resize(FRAME_HEADER_SIZE, 0);
+        }
+    }
+}
+
+/// The write-ahead log
+///
+/// Mutations only hold the locks that order them while they run: DDL takes [`Self::order`]
+/// exclusively, while a mutation on a table shares it and locks the table's stripe, so that
+/// mutations on different tables run side by side. Each frame gets a sequence number while
+/// these locks are held and is queued; whoever writes next writes every queued frame (and
+/// whoever syncs next syncs for every frame written so far), once the ordering locks have
+/// been released
+#[derive(Debug)]
+pub struct WriteAheadLog {
+    policy: WalFsync,
+    path: String,
+    old_path: String,
+    /// orders DDL (and rotations) against the mutations on tables
+    order: RwLock<()>,
+    /// orders the mutations on a table; tables share these by hash
+    stripes: [Mutex<()>; ORDER_STRIPES],
+    /// the frames that haven't been written yet, in the order of the log
+    queue: Mutex<FrameQueue>,
+    /// the active log file
+    file: Mutex<ActiveLog>,
+    /// the sequence number of the last frame that has been synced
+    synced: Mutex<u64>,
+    /// set if there are appends that haven't been synced yet
+    dirty: AtomicBool,
+    /// the frames for followers; taken out on shutdown to end their streams
+    feed: Mutex<Option<broadcast::Sender<Frame>>>,
+    /// the key that appended frames are sealed with
+    key: Option<&'static Key>,
+}
+
+#[derive(Debug, Default)]
+struct FrameQueue {
+    frames: Vec<Vec<u8>>,
+    /// the sequence number of the last frame that was queued
+    last: u64,
+}
+
+#[derive(Debug)]
+struct ActiveLog {
+    file: File,
+    /// the sequence number of the last frame that has been written
+    written: u64,
+}
+
+impl WriteAheadLog {
+    /// Open the log in the data directory
+    pub fn open(policy: WalFsync) -> IoResult<Self> {
+        Self::open_at(WAL_PATH, WAL_OLD_PATH, policy)
+    }
+    pub fn open_at(path: &str, old_path: &str, policy: WalFsync) -> IoResult<Self> {
+        Ok(Self {
+            policy,
+            path: path.to_owned(),
+            old_path: old_path.to_owned(),
+            order: RwLock::new(()),
+            stripes: [(); ORDER_STRIPES].map(|_| Mutex::new(())),
+            queue: Mutex::new(FrameQueue::default()),
+            file: Mutex::new(ActiveLog {
+                file: open_append(path)?,
+                written: 0,
+            }),
+            synced: Mutex::new(0),
+            dirty: AtomicBool::new(false),
+            feed: Mutex::new(Some(broadcast::channel(FEED_CAPACITY).0)),
+            key: encryption::key(),
+        })
+    }
+    /// Seal appended frames with `key` instead of the configured key
+    #[cfg(test)]
+    pub fn with_key(self, key: Option<&'static Key>) -> Self {
+        Self { key, ..self }
+    }
+    /// Run a mutation on the table `entity` and append whatever it records to the log. Pass
+    /// `None` for volatile tables; their mutations are never logged
+    ///
+    /// Mutations on the same table (and DDL) are ordered while `f` runs, so that the order of
+    /// their records in the log is the order in which they were applied
+    pub fn log_table<T>(
+        &self,
+        entity: Option<(&ObjectID, &ObjectID)>,
+        f: impl FnOnce(&mut WalBatch) -> T,
+    ) -> T {
+        match entity {
+            Some(entity) => self.log(Some(entity), f),
+            None => f(&mut WalBatch::inactive()),
+        }
+    }
+    /// Run a DDL operation and append whatever it records to the log
+    pub fn log_ddl<T>(&self, f: impl FnOnce(&mut WalBatch) -> T) -> T {
+        self.log(None, f)
+    }
+    fn log<T>(
+        &self,
+        entity: Option<(&ObjectID, &ObjectID)>,
+        f: impl FnOnce(&mut WalBatch) -> T,
+    ) -> T {
+        let (ret, seq) = match entity {
+            Some((ks, tbl)) => {
+                let _order = self.order.read();
+                let _stripe = self.stripes[Self::stripe_of(ks, tbl)].lock();
+                self.record(entity, f)
+            }
+            None => {
+                let _order = self.order.write();
+                self.record(None, f)
+            }
+        };
+        if let Some(seq) = seq {
+            if let Err(e) = self.write_through(seq).and_then(|_| self.sync_through(seq)) {
+                // the mutation has been applied, but it isn't durable. refuse further writes
+                log::error!("Failed to append to the write-ahead log: {e}");
+                registry::poison();
+            }
+        }
+        ret
+    }
+    fn stripe_of(ks: &ObjectID, tbl: &ObjectID) -> usize {
+        let mut hasher = DefaultHasher::new();
+        (ks.as_slice(), tbl.as_slice()).hash(&mut hasher);
+        hasher.finish() as usize % ORDER_STRIPES
+    }
+    /// Run `f` and queue the frame it records, if any. Returns the frame's sequence number
+    /// along with what `f` returned
+    fn record<T>(
+        &self,
+        entity: Option<(&ObjectID, &ObjectID)>,
+        f: impl FnOnce(&mut WalBatch) -> T,
+    ) -> (T, Option<u64>) {
+        let mut batch = WalBatch::new(entity);
+        let ret = f(&mut batch);
+        if batch.is_empty() {
+            return (ret, None);
+        }
+        batch.finish();
+        let mut queue = self.queue.lock();
+        queue.frames.push(batch.buf);
+        queue.last += 1;
+        (ret, Some(queue.last))
+    }
+    /// Make sure that the frame `seq` has been written, writing all the queued frames if it
+    /// hasn't been
+    fn write_through(&self, seq: u64) -> IoResult<()> {
+        let mut log = self.file.lock();
+        if log.written < seq {
+            self.write_queued(&mut log)?;
+        }
+        Ok(())
+    }
+    fn write_queued(&self, log: &mut ActiveLog) -> IoResult<()> {
+        let (frames, last) = {
+            let mut queue = self.queue.lock();
+            (mem::take(&mut queue.frames), queue.last)
+        };
+        if frames.is_empty() {
+            return Ok(());
+        }
+        let mut out = Vec::with_capacity(frames.iter().map(Vec::len).sum());
+        for frame in frames.iter() {
+            match self.key {
+                Some(key) => {
+                    let body = &frame[FRAME_HEADER_SIZE..frame.len() - FRAME_FOOTER_SIZE];
+                    self::put_frame(&mut out, body, Some(key))?;
+                }
+                None => out.extend_from_slice(frame),
+            }
+        }
+        // followers get the frames even if the write fails, since the mutations were applied
+        let ret = log.file.write_all(&out);
+        log.written = last;
+        if self.policy == WalFsync::EverySecond {
+            self.dirty.store(true, Ordering::Release);
+        }
+        // still under the lock, so followers see frames in the order of the log
+        frames.iter().for_each(|frame| self.publish(frame));
+        ret
+    }
+    /// Make sure that the frame `seq` has been synced, if every append has to be synced
+    fn sync_through(&self, seq: u64) -> IoResult<()> {
+        if self.policy != WalFsync::Always {
+            return Ok(());
+        }
+        // this blocks, so let the runtime move other tasks off this worker
+        tokio::task::block_in_place(|| {
+            let mut synced = self.synced.lock();
+            if *synced < seq {
+                // clone the handle so that writers aren't blocked while we fsync
+                let (file, written) = {
+                    let log = self.file.lock();
+                    (log.file.try_clone()?, log.written)
+                };
+                file.sync_data()?;
+                *synced = written;
+            }
+            Ok(())
+        })
+    }
+    fn publish(&self, frame: &[u8]) {
+        if let Some(feed) = self.feed.lock().as_ref() {
+            if feed.receiver_count() != 0 {
+                // this only fails if the followers went away in the meantime
+                let _ = feed.send(Frame::from(frame));
+            }
+        }
+    }
+    /// Get a receiver for every frame appended from now on. Returns `None` once the feed
+    /// has been closed
+    pub fn subscribe(&self) -> Option<broadcast::Receiver<Frame>> {
+        self.feed.lock().as_ref().map(broadcast::Sender::subscribe)
+    }
+    /// Close the feed, ending the stream of every follower
+    pub fn close_feed(&self) {
+        self.feed.lock().take();
+    }
+    /// End the stream of every follower, but keep accepting new ones. Followers pull a fresh
+    /// snapshot when they reconnect
+    pub fn reset_feed(&self) {
+        let mut feed = self.feed.lock();
+        if feed.is_some() {
+            *feed = Some(broadcast::channel(FEED_CAPACITY).0);
+        }
+    }
+    /// Returns true if appends are synced in the background
+    pub fn needs_background_sync(&self) -> bool {
+        self.policy == WalFsync::EverySecond
+    }
+    /// Sync any appends made since the last sync
+    pub fn sync_if_dirty(&self) -> IoResult<()> {
+        if self.dirty.swap(false, Ordering::AcqRel) {
+            // clone the handle so that writers aren't blocked while we fsync
+            let file = self.file.lock().file.try_clone()?;
+            if let Err(e) = file.sync_data() {
+                self.dirty.store(true, Ordering::Release);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+    /// Move the active log out of the way and start a new one. Call this right before a
+    /// full flush and call [`WriteAheadLog::discard_rotated`] once it succeeds
+    pub fn rotate(&self) -> IoResult<()> {
+        self.rotate_with(|| {})
+    }
+    /// Same as [`WriteAheadLog::rotate`], but `f` is run once the log has been rotated and
+    /// before anything else is appended to the new log
+    pub fn rotate_with(&self, f: impl FnOnce()) -> IoResult<()> {
+        // no mutation can run while we hold this, and everything they queued goes to the
+        // rotated log
+        let _order = self.order.write();
+        let mut log = self.file.lock();
+        self.write_queued(&mut log)?;
+        let file = &mut log.file;
+        file.sync_data()?;
+        if Path::new(&self.old_path).exists() {
+            // an earlier flush failed and its log is still around; keep the order of records
+            let records = fs::read(&self.path)?;
+            let mut old = open_append(&self.old_path)?;
+            old.write_all(&records)?;
+            old.sync_data()?;
+            file.set_len(0)?;
+        } else {
+            fs::rename(&self.path, &self.old_path)?;
+            // if this fails, we keep appending to the rotated log which is replayed anyway
+            *file = open_append(&self.path)?;
+        }
+        f();
+        Ok(())
+    }
+    /// Remove the rotated log. Only call this once the data it covers has been flushed
+    pub fn discard_rotated(&self) -> IoResult<()> {
+        match fs::remove_file(&self.old_path) {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn open_append(path: &str) -> IoResult<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// Remove all logs (for example, when the data directory is replaced by a snapshot)
+pub fn remove_all() -> IoResult<()> {
+    for path in [WAL_PATH, WAL_OLD_PATH] {
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Replay the logs in the data directory on top of `store`, returning the number of
+/// records that were applied
+pub fn replay(store: &Memstore) -> StorageEngineResult<usize> {
+    replay_at(WAL_PATH, WAL_OLD_PATH, store)
+}
+
+pub fn replay_at(path: &str, old_path: &str, store: &Memstore) -> StorageEngineResult<usize> {
+    self::replay_at_with(path, old_path, encryption::key(), store)
+}
+
+/// Same as [`replay_at`], but sealed frames are opened with `key` instead of the configured key
+pub fn replay_at_with(
+    path: &str,
+    old_path: &str,
+    key: Option<&Key>,
+    store: &Memstore,
+) -> StorageEngineResult<usize> {
+    let replayed = self::replay_file(old_path, key, store)? + self::replay_file(path, key, store)?;
+    if replayed != 0 {
+        log::info!("Replayed {replayed} record(s) from the write-ahead log");
+    }
+    Ok(replayed)
+}
+
+fn replay_file(path: &str, key: Option<&Key>, store: &Memstore) -> StorageEngineResult<usize> {
+    let data = match fs::read(path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(0),
+        Err(e) => {
+            return Err(StorageEngineError::ioerror_extra(
+                e,
+                format!("reading {path}"),
+            ))
+        }
+    };
+    let mut frames = Reader::new(&data);
+    let mut replayed = 0;
+    while !frames.is_empty() {
+        let valid_len = data.len() - frames.buf.len();
+        let body = match frames.next_raw_frame() {
+            Some((body, false)) => Cow::Borrowed(body),
+            // a wrong key isn't a torn tail, so don't truncate anything
+            Some((sealed, true)) => encryption::open(key, sealed)
+                .map(Cow::Owned)
+                .map_err(|e| StorageEngineError::Encryption(path.to_owned(), e))?,
+            None => {
+                // a crash in the middle of an append; drop the tail so that new appends
+                // don't end up behind it
+                log::warn!("Discarding torn or corrupted tail of the write-ahead log at {path}");
+                OpenOptions::new()
+                    .write(true)
+                    .open(path)
+                    .and_then(|f| f.set_len(valid_len as u64))
+                    .map_err(|e| {
+                        StorageEngineError::ioerror_extra(e, format!("truncating {path}"))
+                    })?;
+                break;
+            }
+        };
+        replayed += self::apply_body(&body, store)
+            .ok_or_else(|| StorageEngineError::CorruptedFile(path.to_owned()))?;
+    }
+    Ok(replayed)
+}
+
+/// Append a frame with `body` to `out`, sealing the body if a key is given
+fn put_frame(out: &mut Vec<u8>, body: &[u8], key: Option<&Key>) -> IoResult<()> {
+    let body = match key {
+        Some(key) => Cow::Owned(encryption::seal(key, body)?),
+        None => Cow::Borrowed(body),
+    };
+    let mut len = body.len() as u64;
+    if key.is_some() {
+        len |= FRAME_SEALED;
+    }
+    out.extend_from_slice(&len.to_le_bytes());
+    out.extend_from_slice(&body);
+    out.extend_from_slice(&crc32(&body).to_le_bytes());
+    Ok(())
+}
+
+/// Rewrite the log at `path`, opening sealed frames with `old` and sealing every frame with
+/// `new` (or leaving them unsealed). Frames that are already sealed with `new` are kept as
+/// they are and a torn tail is dropped. Returns false if there was nothing to rewrite
+pub(super) fn rekey(
+    path: &Path,
+    old: Option<&Key>,
+    new: Option<&Key>,
+) -> StorageEngineResult<bool> {
+    let name = || path.to_string_lossy().to_string();
+    let data = match fs::read(path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(false),
+        Err(e) => {
+            return Err(StorageEngineError::ioerror_extra(
+                e,
+                format!("reading {}", name()),
+            ))
+        }
+    };
+    let mut frames = Reader::new(&data);
+    let mut rekeyed = Vec::with_capacity(data.len());
+    let mut changed = false;
+    while !frames.is_empty() {
+        let start = data.len() - frames.buf.len();
+        let (body, sealed) = match frames.next_raw_frame() {
+            Some(frame) => frame,
+            None => {
+                changed = true;
+                break;
+            }
+        };
+        let up_to_date = match new {
+            Some(new) => sealed && encryption::sealed_with(body) == Some(&new.id()[..]),
+            None => !sealed,
+        };
+        if up_to_date {
+            rekeyed.extend_from_slice(&data[start..data.len() - frames.buf.len()]);
+            continue;
+        }
+        changed = true;
+        let body = if sealed {
+            Cow::Owned(
+                encryption::open(old, body)
+                    .map_err(|e| StorageEngineError::Encryption(name(), e))?,
+            )
+        } else {
+            Cow::Borrowed(body)
+        };
+        self::put_frame(&mut rekeyed, &body, new)
+            .map_err(|e| StorageEngineError::ioerror_extra(e, format!("sealing {}", name())))?;
+    }
+    if changed {
+        super::encryption::replace_file(path, &rekeyed)?;
+    }
+    Ok(changed)
+}
+
+/// An empty frame. Leaders send one to mark the end of the snapshot and whenever the feed
+/// is idle
+pub fn heartbeat() -> Vec<u8> {
+    WalBatch::new(None).finish().to_owned()
+}
+
+/// Encode the user data in `store` as frames of after-images, with the definitions of all
+/// the objects coming first. Volatile tables are never logged, so only their definitions
+/// are encoded
+#[cfg(test)]
+pub fn snapshot(store: &Memstore) -> Vec<u8> {
+    let mut snapshot = Vec::new();
+    self::snapshot_with(store, |frames| {
+        snapshot.extend_from_slice(&frames);
+        true
+    });
+    snapshot
+}
+
+/// Encode the user data in `store` as frames of after-images (like `snapshot`), passing them
+/// to `emit` as soon as they are closed so that the whole snapshot is never held in memory.
+/// Stops early if `emit` returns false
+pub fn snapshot_with(store: &Memstore, mut emit: impl FnMut(Vec<u8>) -> bool) {
+    let mut live = true;
+    let mut spill = |batch: &mut WalBatch, limit: usize| {
+        if live {
+            let mut frames = Vec::new();
+            batch.spill(&mut frames, limit);
+            if !frames.is_empty() {
+                live = emit(frames);
+            }
+        }
+        live
+    };
+    let mut ddl = WalBatch::new(None);
+    for keyspace in store.keyspaces.iter().filter(|ks| ks.key().ne(&SYSTEM)) {
+        ddl.create_space(keyspace.key());
+        for table in keyspace.value().tables.iter() {
+            ddl.create_model(
+                keyspace.key(),
+                table.key(),
+                table.get_model_code(),
+                table.storage_type(),
+                table.get_schema(),
+                table.max_len(),
+            );
+        }
+    }
+    if !spill(&mut ddl, 0) {
+        return;
+    }
+    for keyspace in store.keyspaces.iter().filter(|ks| ks.key().ne(&SYSTEM)) {
+        for table in keyspace.value().tables.iter() {
+            if table.is_volatile() {
+                continue;
+            }
+            let mut batch = WalBatch::new(None);
+            batch.contents(keyspace.key(), table.key(), table.value(), |batch| {
+                spill(batch, SNAPSHOT_FRAME_SIZE);
+            });
+            if !spill(&mut batch, 0) {
+                return;
+            }
+        }
+    }
+}
+
+/// Replace the user data in `store` with a [`snapshot`], returning the number of records
+/// that were applied or `None` if the snapshot is corrupted
+///
+/// Tables that are defined the same way in the snapshot are truncated and refilled rather
+/// than replaced, so that connections using them don't end up with stale tables
+pub fn restore_snapshot(store: &Memstore, snapshot: &[u8]) -> Option<usize> {
+    // decode everything first so that a corrupted snapshot leaves the store untouched
+    let mut records = Vec::new();
+    let mut frames = Reader::new(snapshot);
+    while !frames.is_empty() {
+        let mut body = Reader::new(frames.next_frame()?);
+        while !body.is_empty() {
+            records.push(self::read_record(&mut body)?);
+        }
+    }
+    let mut layout: HashMap<&[u8], Vec<(&[u8], &Table)>> = HashMap::new();
+    for (ks, tbl, record) in records.iter() {
+        match record {
+            Record::CreateSpace => {
+                layout.entry(ks).or_default();
+            }
+            Record::CreateModel(model) => layout.entry(ks).or_default().push((tbl, model)),
+            _ => {}
+        }
+    }
+    let keyspaces: Vec<ObjectID> = store.keyspaces.iter().map(|ks| ks.key().clone()).collect();
+    for ksid in keyspaces {
+        let wanted = layout.get(ksid.as_slice());
+        if ksid.eq(&SYSTEM) || (wanted.is_none() && store.force_drop_keyspace(ksid.clone()).is_ok())
+        {
+            continue;
+        }
+        let keyspace = match store.get_keyspace_atomic_ref(ksid.as_slice()) {
+            Some(keyspace) => keyspace,
+            None => continue,
+        };
+        let mut stale = Vec::new();
+        for table in keyspace.tables.iter() {
+            let keep = wanted.into_iter().flatten().any(|(tbl, model)| {
+                table.key().as_slice() == *tbl && self::same_definition(table.value(), model)
+            });
+            if keep {
+                table.value().truncate_table();
+            } else {
+                stale.push(table.key().clone());
+            }
+        }
+        for tblid in stale {
+            if keyspace.drop_table(&tblid, true).is_err() {
+                log::warn!(
+                    "Could not drop table `{}` while restoring the leader's snapshot. Truncating it",
+                    String::from_utf8_lossy(tblid.as_slice())
+                );
+                if let Some(table) = keyspace.get_table_atomic_ref(&tblid) {
+                    table.truncate_table();
+                }
+            }
+        }
+    }
+    let restored = records.len();
+    for (ks, tbl, record) in records {
+        self::apply_record(store, ks, tbl, record)?;
+    }
+    Some(restored)
+}
+
+/// Apply a frame streamed from the leader, returning the number of records in it
+pub fn apply_frame(store: &Memstore, frame: &[u8]) -> Option<usize> {
+    let mut frames = Reader::new(frame);
+    let body = frames.next_frame()?;
+    if frames.is_empty() {
+        self::apply_body(body, store)
+    } else {
+        None
+    }
+}
+
+fn same_definition(table: &Table, model: &Table) -> bool {
+    table.get_model_code() == model.get_model_code()
+        && table.storage_type() == model.storage_type()
+        && table.get_schema() == model.get_schema()
+        && table.max_len() == model.max_len()
+}
+
+/// Apply all the records in the body of a frame, returning the number of records
+fn apply_body(body: &[u8], store: &Memstore) -> Option<usize> {
+    let mut records = Reader::new(body);
+    let mut applied = 0;
+    while !records.is_empty() {
+        let (ks, tbl, record) = self::read_record(&mut records)?;
+        self::apply_record(store, ks, tbl, record)?;
+        applied += 1;
+    }
+    Some(applied)
+}
+
+fn object_id(id: &[u8]) -> Option<ObjectID> {
+    ObjectID::try_from_slice(id)
+}
+
+/// A record read from the log
+enum Record<'a> {
+    KvPut {
+        key: &'a [u8],
+        val: &'a [u8],
+        deadline: u64,
+    },
+    KvDel(&'a [u8]),
+    ListPut(&'a [u8], Vec<SharedSlice>),
+    MapSet(&'a [u8], Vec<(SharedSlice, SharedSlice)>),
+    MapDel(&'a [u8], Vec<&'a [u8]>),
+    SetAdd(&'a [u8], Vec<SharedSlice>),
+    SetDel(&'a [u8], Vec<&'a [u8]>),
+    ZSetAdd(&'a [u8], Vec<(SharedSlice, Score)>),
+    QueuePut(&'a [u8], Queue),
+    Truncate,
+    CreateSpace,
+    DropSpace,
+    CreateModel(Table),
+    DropModel,
+}
+
+/// Read the next record along with the keyspace and table that it applies to
+fn read_record<'a>(records: &mut Reader<'a>) -> Option<(&'a [u8], &'a [u8], Record<'a>)> {
+    let opcode = records.next_u8()?;
+    let ks = records.next_slice()?;
+    let tbl = records.next_slice()?;
+    let record = match opcode {
+        OP_KV_PUT => Record::KvPut {
+            key: records.next_slice()?,
+            val: records.next_slice()?,
+            deadline: records.next_u64()?,
+        },
+        OP_KV_DEL => Record::KvDel(records.next_slice()?),
+        OP_LIST_PUT => {
+            let key = records.next_slice()?;
+            let count = records.next_u64()? as usize;
+            let mut list = Vec::with_capacity(count.min(records.buf.len()));
+            for _ in 0..count {
+                list.push(SharedSlice::new(records.next_slice()?));
+            }
+            Record::ListPut(key, list)
+        }
+        OP_MAP_SET => {
+            let key = records.next_slice()?;
+            let count = records.next_u64()? as usize;
+            let mut pairs = Vec::with_capacity(count.min(records.buf.len()));
+            for _ in 0..count {
+                let field = SharedSlice::new(records.next_slice()?);
+                pairs.push((field, SharedSlice::new(records.next_slice()?)));
+            }
+            Record::MapSet(key, pairs)
+        }
+        OP_MAP_DEL => {
+            let key = records.next_slice()?;
+            let count = records.next_u64()? as usize;
+            let mut fields = Vec::with_capacity(count.min(records.buf.len()));
+            for _ in 0..count {
+                fields.push(records.next_slice()?);
+            }
+            Record::MapDel(key, fields)
+        }
+        OP_SET_ADD => {
+            let key = records.next_slice()?;
+            let count = records.next_u64()? as usize;
+            let mut members = Vec::with_capacity(count.min(records.buf.len()));
+            for _ in 0..count {
+                members.push(SharedSlice::new(records.next_slice()?));
+            }
+            Record::SetAdd(key, members)
+        }
+        OP_SET_DEL => {
+            let key = records.next_slice()?;
+            let count = records.next_u64()? as usize;
+            let mut members = Vec::with_capacity(count.min(records.buf.len()));
+            for _ in 0..count {
+                members.push(records.next_slice()?);
+            }
+            Record::SetDel(key, members)
+        }
+        OP_ZSET_ADD => {
+            let key = records.next_slice()?;
+            let count = records.next_u64()? as usize;
+            let mut members = Vec::with_capacity(count.min(records.buf.len()));
+            for _ in 0..count {
+                let member = SharedSlice::new(records.next_slice()?);
+                let score = Score::from_le_bytes(records.next_u64()?.to_le_bytes())?;
+                members.push((member, score));
+            }
+            Record::ZSetAdd(key, members)
+        }
+        OP_QUEUE_PUT => {
+            let key = records.next_slice()?;
+            let next_id = records.next_u64()?;
+            let count = records.next_u64()? as usize;
+            let mut ready = VecDeque::with_capacity(count.min(records.buf.len()));
+            for _ in 0..count {
+                ready.push_back(SharedSlice::new(records.next_slice()?));
+            }
+            let mut reserved = BTreeMap::new();
+            for _ in 0..records.next_u64()? {
+                let id = records.next_u64()?;
+                let deadline = records.next_u64()?;
+                let item = SharedSlice::new(records.next_slice()?);
+                reserved.insert(id, Reservation { item, deadline });
+            }
+            Record::QueuePut(key, Queue::from_parts(ready, reserved, next_id)?)
+        }
+        OP_TRUNCATE => Record::Truncate,
+        OP_CREATE_SPACE => Record::CreateSpace,
+        OP_DROP_SPACE => Record::DropSpace,
+        OP_CREATE_MODEL | OP_CREATE_CAPPED_MODEL => {
+            let model_code = records.next_u8()?;
+            let (volatile, policy) = Table::from_storage_type(records.next_u8()?)?;
+            let model = if model_code == bytemarks::BYTEMARK_MODEL_ROWMAP {
+                let count = records.next_u64()? as usize;
+                let mut fields = Vec::with_capacity(count.min(records.buf.len()));
+                for _ in 0..count {
+                    let ty = FieldType::from_code(records.next_u8()?)?;
+                    let name = core::str::from_utf8(records.next_slice()?).ok()?;
+                    fields.push(Field::new(name, ty));
+                }
+                Table::new_rowmap(Schema::new(fields)?, volatile)
+            } else {
+                Table::from_model_code(model_code, volatile)?
+            };
+            let model = model.with_eviction(policy);
+            if opcode == OP_CREATE_CAPPED_MODEL {
+                Record::CreateModel(model.with_max_len(records.next_u64()? as usize)?)
+            } else {
+                Record::CreateModel(model)
+            }
+        }
+        OP_DROP_MODEL => Record::DropModel,
+        _ => return None,
+    };
+    Some((ks, tbl, record))
+}
+
+/// The event to notify watchers with when `key` is about to be written in `map`
+fn put_event<V>(map: &Coremap<SharedSlice, V>, key: &[u8]) -> KeyEvent {
+    if map.contains_key(key) {
+        KeyEvent::Update
+    } else {
+        KeyEvent::Set
+    }
+}
+
+/// Apply a single record. Records for objects that no longer exist are skipped. Watchers are
+/// notified just like for a live write, which also marks the table as changed
+fn apply_record(store: &Memstore, ks: &[u8], tbl: &[u8], record: Record) -> Option<()> {
+    let table = || {
+        store
+            .get_keyspace_atomic_ref(ks)
+            .and_then(|keyspace| keyspace.get_table_atomic_ref(tbl))
+    };
+    match record {
+        Record::KvPut { key, val, deadline } => {
+            if let Some(table) = table() {
+                if let DataModel::KV(kve) | DataModel::KVOrdered(kve) = table.get_model_ref() {
+                    let event = put_event(kve.get_inner_ref(), key);
+                    let key = SharedSlice::new(key);
+                    if deadline == 0 {
+                        kve.get_expiry_ref().remove(&key);
+                    } else {
+                        kve.get_expiry_ref().upsert(key.clone(), deadline);
+                    }
+                    kve.get_inner_ref()
+                        .upsert(key.clone(), SharedSlice::new(val));
+                    kve.notify(&key, event);
+                }
+            }
+        }
+        Record::KvDel(key) => {
+            if let Some(table) = table() {
+                match table.get_model_ref() {
+                    DataModel::KV(kve) | DataModel::KVOrdered(kve) => {
+                        kve.get_expiry_ref().remove(key);
+                        if kve.get_inner_ref().true_if_removed(key) {
+                            kve.notify(key, KeyEvent::Del);
+                        }
+                    }
+                    DataModel::KVExtListmap(listmap) => {
+                        if listmap.get_inner_ref().true_if_removed(key) {
+                            listmap.notify(key, KeyEvent::Del);
+                        }
+                    }
+                    DataModel::Rowmap(rowmap) => {
+                        if rowmap.rows().get_inner_ref().true_if_removed(key) {
+                            rowmap.rows().notify(key, KeyEvent::Del);
+                        }
+                    }
+                    DataModel::Hashmap(hm) => {
+                        if hm.maps().get_inner_ref().true_if_removed(key) {
+                            hm.maps().notify(key, KeyEvent::Del);
+                        }
+                    }
+                    DataModel::Set(sets) => {
+                        if sets.get_inner_ref().true_if_removed(key) {
+                            sets.notify(key, KeyEvent::Del);
+                        }
+                    }
+                    DataModel::SortedSet(zsets) => {
+                        if zsets.get_inner_ref().true_if_removed(key) {
+                            zsets.notify(key, KeyEvent::Del);
+                        }
+                    }
+                    DataModel::Queue(queues) => {
+                        if queues.get_inner_ref().true_if_removed(key) {
+                            queues.notify(key, KeyEvent::Del);
+                        }
+                    }
+                }
+            }
+        }
+        Record::ListPut(key, list) => {
+            if let Some(table) = table() {
+                match table.get_model_ref() {
+                    DataModel::KVExtListmap(listmap) => {
+                        let event = put_event(listmap.get_inner_ref(), key);
+                        listmap
+                            .get_inner_ref()
+                            .upsert(SharedSlice::new(key), LockedVec::new(list));
+                        listmap.notify(key, event);
+                    }
+                    DataModel::Rowmap(rowmap) => {
+                        let rows = rowmap.rows();
+                        let event = put_event(rows.get_inner_ref(), key);
+                        rows.get_inner_ref()
+                            .upsert(SharedSlice::new(key), LockedVec::new(list));
+                        rows.notify(key, event);
+                    }
+                    DataModel::KV(_)
+                    | DataModel::KVOrdered(_)
+                    | DataModel::Hashmap(_)
+                    | DataModel::Set(_)
+                    | DataModel::SortedSet(_)
+                    | DataModel::Queue(_) => {}
+                }
+            }
+        }
+        Record::MapSet(key, pairs) => {
+            if let Some(table) = table() {
+                if let DataModel::Hashmap(hm) = table.get_model_ref() {
+                    hm.set_fields(key, &pairs, |_| {});
+                }
+            }
+        }
+        Record::MapDel(key, fields) => {
+            if let Some(table) = table() {
+                if let DataModel::Hashmap(hm) = table.get_model_ref() {
+                    hm.remove_fields(key, &fields, |_| {});
+                }
+            }
+        }
+        Record::SetAdd(key, members) => {
+            if let Some(table) = table() {
+                if let DataModel::Set(sets) = table.get_model_ref() {
+                    sets.add_members(key, &members, |_| {});
+                }
+            }
+        }
+        Record::SetDel(key, members) => {
+            if let Some(table) = table() {
+                match table.get_model_ref() {
+                    DataModel::Set(sets) => {
+                        sets.remove_members(key, &members, |_| {});
+                    }
+                    DataModel::SortedSet(zsets) => {
+                        zsets.remove_members(key, &members, |_| {});
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Record::ZSetAdd(key, members) => {
+            if let Some(table) = table() {
+                if let DataModel::SortedSet(zsets) = table.get_model_ref() {
+                    zsets.add_scored(key, &members, |_| {});
+                }
+            }
+        }
+        Record::QueuePut(key, queue) => {
+            if let Some(table) = table() {
+                if let DataModel::Queue(queues) = table.get_model_ref() {
+                    let event = put_event(queues.get_inner_ref(), key);
+                    queues
+                        .get_inner_ref()
+                        .upsert(SharedSlice::new(key), LockedQueue::new(queue));
+                    queues.notify(key, event);
+                }
+            }
+        }
+        Record::Truncate => {
+            if let Some(table) = table() {
+                table.truncate_table();
+            }
+        }
+        Record::CreateSpace => {
+            if store.create_keyspace(object_id(ks)?) {
+                registry::get_preload_tripswitch().trip();
+            }
+        }
+        Record::DropSpace => {
+            // the keyspace may have been dropped before the last flush
+            let _ = store.force_drop_keyspace(object_id(ks)?);
+        }
+        Record::CreateModel(model) => {
+            if let Some(keyspace) = store.get_keyspace_atomic_ref(ks) {
+                if keyspace.create_table(object_id(tbl)?, model) {
+                    registry::get_preload_tripswitch().trip();
+                }
+            }
+        }
+        Record::DropModel => {
+            if let Some(keyspace) = store.get_keyspace_atomic_ref(ks) {
+                let _ = keyspace.drop_table(tbl, true);
+            }
+        }
+    }
+    Some(())
+}
+
+/// A bounds-checked reader over a log
+struct Reader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    const fn new(buf: &'a [u8]) -> Self {
+        Self { buf }
+    }
+    const fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+    fn next_bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.buf.len() < len {
+            None
+        } else {
+            let (ret, rem) = self.buf.split_at(len);
+            self.buf = rem;
+            Some(ret)
+        }
+    }
+    fn next_u8(&mut self) -> Option<u8> {
+        self.next_bytes(1).map(|b| b[0])
+    }
+    fn next_u64(&mut self) -> Option<u64> {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(self.next_bytes(8)?);
+        Some(u64::from_le_bytes(bytes))
+    }
+    fn next_slice(&mut self) -> Option<&'a [u8]> {
+        let len = self.next_u64()?;
+        self.next_bytes(usize::try_from(len).ok()?)
+    }
+    /// Get the body of the next frame if it is complete, its checksum matches and it isn't
+    /// sealed
+    fn next_frame(&mut self) -> Option<&'a [u8]> {
+        match self.next_raw_frame()? {
+            (body, false) => Some(body),
+            (_, true) => None,
+        }
+    }
+    /// Get the body of the next frame if it is complete and its checksum matches, along with
+    /// whether it is sealed
+    fn next_raw_frame(&mut self) -> Option<(&'a [u8], bool)> {
+        let len = self.next_u64()?;
+        let body_len = usize::try_from(len & !FRAME_SEALED).ok()?;
+        let body = self.next_bytes(body_len)?;
+        let mut checksum = [0u8; FRAME_FOOTER_SIZE];
+        checksum.copy_from_slice(self.next_bytes(FRAME_FOOTER_SIZE)?);
+        if u32::from_le_bytes(checksum) == crc32(body) {
+            Some((body, len & FRAME_SEALED != 0))
+        } else {
+            None
+        }
+    }
+}
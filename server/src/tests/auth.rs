@@ -26,7 +26,7 @@
 
 use {
     crate::auth::provider::testsuite_data,
-    skytable::{query, Element, RespCode},
+    skytable::{aio::Connection, query, Element, RespCode},
 };
 
 macro_rules! assert_autherror {
@@ -260,6 +260,129 @@ async fn listuser_okay_because_root() {
     assert!(ret.contains(&"testuser".to_owned()));
 }
 
+#[sky_macros::dbtest_func(port = 2005, auth_rootuser = true)]
+async fn listuser_grants_okay_because_root() {
+    let ret: Vec<String> = con
+        .run_query(query!("auth", "listuser", "testuser"))
+        .await
+        .unwrap();
+    assert_eq!(ret, vec!["admin".to_owned()]);
+    let ret: Vec<String> = con
+        .run_query(query!("auth", "listuser", "root"))
+        .await
+        .unwrap();
+    assert_eq!(ret, vec!["root".to_owned()]);
+}
+#[sky_macros::dbtest_func(port = 2005, auth_testuser = true)]
+async fn listuser_grants_fail_because_not_root() {
+    assert_auth_perm_error!(con, query!("auth", "listuser", "testuser"))
+}
+
+// auth grant and revoke
+macro_rules! assert_auth_illegal_grant {
+    ($con:expr, $query:expr) => {
+        assert_autherror!(
+            $con,
+            $query,
+            RespCode::ErrorString("err-auth-illegal-grant".to_owned())
+        )
+    };
+}
+#[sky_macros::dbtest_func]
+async fn grant_fail_because_disabled() {
+    assert_auth_disabled!(con, query!("auth", "grant", "someuser", "admin"))
+}
+#[sky_macros::dbtest_func(port = 2005, auth_testuser = true)]
+async fn grant_fail_because_not_root() {
+    assert_auth_perm_error!(con, query!("auth", "grant", "testuser", "admin"));
+    assert_auth_perm_error!(con, query!("auth", "revoke", "testuser", "admin"))
+}
+#[sky_macros::dbtest_func(port = 2005, auth_rootuser = true)]
+async fn grant_fail_because_illegal() {
+    runmatch!(
+        con,
+        query!("auth", "adduser", "grantuser1"),
+        Element::String
+    );
+    assert_auth_illegal_grant!(con, query!("auth", "grant", "grantuser1", "superuser"));
+    assert_auth_illegal_grant!(con, query!("auth", "grant", "grantuser1", "admin", "*"));
+    assert_auth_illegal_grant!(con, query!("auth", "grant", "grantuser1", "read"));
+    assert_auth_illegal_grant!(
+        con,
+        query!("auth", "grant", "grantuser1", "read", "twitter:tweets")
+    );
+    assert_auth_illegal_grant!(con, query!("auth", "grant", "root", "admin"));
+    assert_auth_bad_credentials!(con, query!("auth", "grant", "nosuchuser", "admin"))
+}
+#[sky_macros::dbtest_func(port = 2005, auth_rootuser = true)]
+async fn grant_revoke_okay_because_root() {
+    runmatch!(
+        con,
+        query!("auth", "adduser", "grantuser2"),
+        Element::String
+    );
+    let ret: Vec<String> = con
+        .run_query(query!("auth", "listuser", "grantuser2"))
+        .await
+        .unwrap();
+    assert!(ret.is_empty());
+    assert_okay!(
+        con,
+        query!("auth", "grant", "grantuser2", "write", "twitter")
+    );
+    assert_okay!(con, query!("auth", "grant", "grantuser2", "read", "*"));
+    let ret: Vec<String> = con
+        .run_query(query!("auth", "listuser", "grantuser2"))
+        .await
+        .unwrap();
+    assert_eq!(ret, vec!["write:twitter".to_owned(), "read:*".to_owned()]);
+    assert_okay!(
+        con,
+        query!("auth", "revoke", "grantuser2", "write", "twitter")
+    );
+    let ret: Vec<String> = con
+        .run_query(query!("auth", "listuser", "grantuser2"))
+        .await
+        .unwrap();
+    assert_eq!(ret, vec!["read:*".to_owned()]);
+}
+#[sky_macros::dbtest_func(port = 2005, auth_rootuser = true)]
+async fn grant_read_only_user() {
+    let key: String = con
+        .run_query(query!("auth", "adduser", "grantuser3"))
+        .await
+        .unwrap();
+    assert_okay!(con, query!("auth", "grant", "grantuser3", "read", "*"));
+    assert_okay!(con, query!("auth", "login", "grantuser3", key));
+    // reads and actions that need no grants are fine
+    runmatch!(con, query!("dbsize"), Element::UnsignedInt);
+    runmatch!(con, query!("heya"), Element::String);
+    // but writes and admin actions aren't
+    assert_auth_perm_error!(con, query!("set", "x", "100"));
+    assert_auth_perm_error!(con, query!("flushdb"));
+    assert_auth_perm_error!(con, query!("mksnap"));
+    assert_auth_perm_error!(con, query!("create space grantuser3space"));
+    assert_auth_perm_error!(con, query!("drop space default"));
+    assert_auth_perm_error!(con, query!("auth", "grant", "grantuser3", "admin"))
+}
+#[sky_macros::dbtest_func(port = 2005, auth_rootuser = true)]
+async fn deleted_user_loses_access() {
+    let key: String = con
+        .run_query(query!("auth", "adduser", "grantuser4"))
+        .await
+        .unwrap();
+    assert_okay!(con, query!("auth", "grant", "grantuser4", "read", "*"));
+    let mut session = Connection::new("127.0.0.1", 2005).await.unwrap();
+    assert_okay!(session, query!("auth", "login", "grantuser4", key));
+    runmatch!(session, query!("dbsize"), Element::UnsignedInt);
+    assert_okay!(con, query!("auth", "deluser", "grantuser4"));
+    // the session is still open, but the user is gone
+    assert_auth_perm_error!(session, query!("dbsize"));
+    assert_auth_perm_error!(session, query!("get", "x"));
+    assert_auth_perm_error!(session, query!("set", "x", "100"));
+    assert_auth_perm_error!(session, query!("flushdb"))
+}
+
 // auth whoami
 #[sky_macros::dbtest_func]
 async fn whoami_fail_because_disabled() {
@@ -0,0 +1,170 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! Tests for blocking list pops. The blocked client speaks raw Skyhash 2 so that we can look at
+//! what it gets (and when) while the main connection pushes
+
+use {
+    super::rawcon::{connect_to, expect, expect_nothing, send},
+    sky_macros::dbtest_func as dbtest,
+    skytable::{query, types::Array, Element, RespCode},
+    std::time::Duration,
+    tokio::time,
+};
+
+#[dbtest(table = "(string,list<string>)")]
+async fn blpop_nonempty_list() {
+    runeq!(
+        con,
+        query!("lset", "jobs", "a", "b"),
+        Element::RespCode(RespCode::Okay)
+    );
+    runeq!(
+        con,
+        query!("blpop", "missing", "jobs", "0"),
+        Element::Array(Array::NonNullStr(vec!["jobs".to_owned(), "b".to_owned()]))
+    );
+    runeq!(
+        con,
+        query!("lget", "jobs"),
+        Element::Array(Array::NonNullStr(vec!["a".to_owned()]))
+    );
+}
+
+#[dbtest(table = "(string,list<string>)")]
+async fn blpop_timeout() {
+    runeq!(
+        con,
+        query!("lset", "jobs"),
+        Element::RespCode(RespCode::Okay)
+    );
+    runeq!(
+        con,
+        query!("blpop", "jobs", "missing", "1"),
+        Element::RespCode(RespCode::NotFound)
+    );
+}
+
+#[dbtest(table = "(string,list<string>)")]
+async fn blpop_wakes_up_on_push() {
+    runeq!(
+        con,
+        query!("lset", "jobs"),
+        Element::RespCode(RespCode::Okay)
+    );
+    let mut worker = connect_to(&__MYENTITY__).await;
+    send(&mut worker, &["blpop", "jobs", "0"]).await;
+    expect_nothing(&mut worker).await;
+    runeq!(
+        con,
+        query!("lmod", "jobs", "push", "hello"),
+        Element::RespCode(RespCode::Okay)
+    );
+    expect(&mut worker, b"*^+2\n4\njobs5\nhello").await;
+    runeq!(
+        con,
+        query!("lget", "jobs"),
+        Element::Array(Array::NonNullStr(vec![]))
+    );
+}
+
+#[dbtest(table = "(string,list<string>)")]
+async fn blpop_wakes_up_on_any_list() {
+    let mut worker = connect_to(&__MYENTITY__).await;
+    send(&mut worker, &["blpop", "jobs", "urgent", "5"]).await;
+    expect_nothing(&mut worker).await;
+    // a list that is created with elements counts as a push too
+    runeq!(
+        con,
+        query!("lset", "urgent", "fire"),
+        Element::RespCode(RespCode::Okay)
+    );
+    expect(&mut worker, b"*^+2\n6\nurgent4\nfire").await;
+}
+
+#[dbtest(table = "(string,list<string>)")]
+async fn blpop_keeps_queries_sent_while_blocked() {
+    runeq!(
+        con,
+        query!("lset", "jobs"),
+        Element::RespCode(RespCode::Okay)
+    );
+    let mut worker = connect_to(&__MYENTITY__).await;
+    send(&mut worker, &["blpop", "jobs", "0"]).await;
+    send(&mut worker, &["heya"]).await;
+    expect_nothing(&mut worker).await;
+    runeq!(
+        con,
+        query!("lmod", "jobs", "push", "hello"),
+        Element::RespCode(RespCode::Okay)
+    );
+    expect(&mut worker, b"*^+2\n4\njobs5\nhello").await;
+    expect(&mut worker, b"*+4\nHEY!").await;
+}
+
+#[dbtest(table = "(string,list<string>)")]
+async fn blpop_cancelled_on_disconnect() {
+    runeq!(
+        con,
+        query!("lset", "jobs"),
+        Element::RespCode(RespCode::Okay)
+    );
+    let mut worker = connect_to(&__MYENTITY__).await;
+    send(&mut worker, &["blpop", "jobs", "0"]).await;
+    expect_nothing(&mut worker).await;
+    drop(worker);
+    time::sleep(Duration::from_millis(200)).await;
+    // the worker is gone, so nobody takes the element
+    runeq!(
+        con,
+        query!("lmod", "jobs", "push", "hello"),
+        Element::RespCode(RespCode::Okay)
+    );
+    runeq!(
+        con,
+        query!("lget", "jobs"),
+        Element::Array(Array::NonNullStr(vec!["hello".to_owned()]))
+    );
+}
+
+#[dbtest(table = "(string,list<string>)")]
+async fn blpop_errors() {
+    runeq!(
+        con,
+        query!("blpop", "jobs"),
+        Element::RespCode(RespCode::ActionError)
+    );
+    runeq!(
+        con,
+        query!("blpop", "jobs", "soon"),
+        Element::RespCode(RespCode::Wrongtype)
+    );
+    runeq!(
+        con,
+        query!("blpop", "jobs", "-1"),
+        Element::RespCode(RespCode::Wrongtype)
+    );
+}
@@ -73,6 +73,28 @@ mod __private {
             Element::RespCode(RespCode::Okay)
         );
     }
+    async fn test_create_capped_list() {
+        let mut rng = rand::thread_rng();
+        let tblname = utils::rand_alphastring(10, &mut rng);
+        query.push(format!(
+            "create model {tblname}(string, list<string>) maxlen 100 volatile"
+        ));
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+    }
+    async fn test_create_capped_non_list() {
+        let mut rng = rand::thread_rng();
+        let tblname = utils::rand_alphastring(10, &mut rng);
+        query.push(format!("create model {tblname}(string, string) maxlen 100"));
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::ErrorString(
+                "bql-unsupported-model-decl".to_owned()
+            ))
+        );
+    }
     async fn test_create_table_fully_qualified_entity() {
         let mut rng = rand::thread_rng();
         let tblname = utils::rand_alphastring(10, &mut rng);
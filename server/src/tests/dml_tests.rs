@@ -0,0 +1,253 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+mod kv {
+    #[sky_macros::dbtest_module]
+    mod __private {
+        use skytable::{query, Element, RespCode};
+
+        async fn test_insert_select() {
+            let q = query!(format!("insert into {__MYENTITY__} ('sayan', 'pass123')"));
+            runeq!(con, q, Element::RespCode(RespCode::Okay));
+            let q = query!(format!("select v from {__MYENTITY__} where k = 'sayan'"));
+            runeq!(con, q, Element::String("pass123".to_owned()));
+            let q = query!(format!("select k from {__MYENTITY__} where k = 'sayan'"));
+            runeq!(con, q, Element::String("sayan".to_owned()));
+        }
+        async fn test_insert_overwrite_error() {
+            let q = query!(format!("insert into {__MYENTITY__} ('sayan', 'pass123')"));
+            runeq!(con, q, Element::RespCode(RespCode::Okay));
+            let q = query!(format!("insert into {__MYENTITY__} ('sayan', 'pass321')"));
+            runeq!(con, q, Element::RespCode(RespCode::OverwriteError));
+        }
+        async fn test_insert_select_with_params() {
+            let q = query!(
+                format!("insert into {__MYENTITY__} (?, ?)"),
+                "sayan",
+                "pass123"
+            );
+            runeq!(con, q, Element::RespCode(RespCode::Okay));
+            let q = query!(format!("select v from {__MYENTITY__} where k = ?"), "sayan");
+            runeq!(con, q, Element::String("pass123".to_owned()));
+        }
+        async fn test_select_nil() {
+            let q = query!(format!("select v from {__MYENTITY__} where k = 'nope'"));
+            runeq!(con, q, Element::RespCode(RespCode::NotFound));
+        }
+        async fn test_select_unknown_field() {
+            let q = query!(format!("select x from {__MYENTITY__} where k = 'sayan'"));
+            runeq!(
+                con,
+                q,
+                Element::RespCode(RespCode::ErrorString("bql-unknown-field".to_owned()))
+            );
+        }
+        async fn test_update() {
+            let q = query!(format!("insert into {__MYENTITY__} ('sayan', 'pass123')"));
+            runeq!(con, q, Element::RespCode(RespCode::Okay));
+            let q = query!(format!(
+                "update {__MYENTITY__} set v = 'pass321' where k = 'sayan'"
+            ));
+            runeq!(con, q, Element::RespCode(RespCode::Okay));
+            let q = query!("GET", "sayan");
+            runeq!(con, q, Element::String("pass321".to_owned()));
+        }
+        async fn test_update_nil() {
+            let q = query!(format!(
+                "update {__MYENTITY__} set v = 'x' where k = 'nope'"
+            ));
+            runeq!(con, q, Element::RespCode(RespCode::NotFound));
+        }
+        async fn test_delete() {
+            let q = query!("SET", "sayan", "pass123");
+            runeq!(con, q, Element::RespCode(RespCode::Okay));
+            let q = query!(format!("delete from {__MYENTITY__} where k = 'sayan'"));
+            runeq!(con, q, Element::RespCode(RespCode::Okay));
+            let q = query!(format!("delete from {__MYENTITY__} where k = 'sayan'"));
+            runeq!(con, q, Element::RespCode(RespCode::NotFound));
+        }
+    }
+}
+
+mod list {
+    #[sky_macros::dbtest_module(table = "(string,list<string>)")]
+    mod __private {
+        use skytable::{query, types::Array, Element, RespCode};
+
+        async fn test_insert_select() {
+            let q = query!(format!("insert into {__MYENTITY__} ('mylist', 'a', 'b')"));
+            runeq!(con, q, Element::RespCode(RespCode::Okay));
+            let q = query!(format!("select v from {__MYENTITY__} where k = 'mylist'"));
+            assert_skyhash_arrayeq!(str, con, q, "a", "b");
+        }
+        async fn test_insert_empty() {
+            let q = query!(format!("insert into {__MYENTITY__} ('mylist')"));
+            runeq!(con, q, Element::RespCode(RespCode::Okay));
+            let q = query!(format!("select v from {__MYENTITY__} where k = 'mylist'"));
+            runeq!(con, q, Element::Array(Array::NonNullStr(vec![])));
+        }
+        async fn test_update_replaces_list() {
+            let q = query!(format!("insert into {__MYENTITY__} ('mylist', 'a', 'b')"));
+            runeq!(con, q, Element::RespCode(RespCode::Okay));
+            let q = query!(format!(
+                "update {__MYENTITY__} set v = ('c', 'd', 'e') where k = 'mylist'"
+            ));
+            runeq!(con, q, Element::RespCode(RespCode::Okay));
+            let q = query!("LGET", "mylist");
+            assert_skyhash_arrayeq!(str, con, q, "c", "d", "e");
+        }
+        async fn test_delete() {
+            let q = query!(format!("insert into {__MYENTITY__} ('mylist', 'a')"));
+            runeq!(con, q, Element::RespCode(RespCode::Okay));
+            let q = query!(format!("delete from {__MYENTITY__} where k = 'mylist'"));
+            runeq!(con, q, Element::RespCode(RespCode::Okay));
+            let q = query!(format!("select v from {__MYENTITY__} where k = 'mylist'"));
+            runeq!(con, q, Element::RespCode(RespCode::NotFound));
+        }
+    }
+}
+
+mod named_fields {
+    #[sky_macros::dbtest_module(table = "(username: string, email: string, avatar: binary)")]
+    mod __private {
+        use skytable::{query, Element, RespCode};
+
+        async fn test_inspect_model() {
+            let q = query!(format!("inspect model {__MYENTITY__}"));
+            runeq!(
+                con,
+                q,
+                Element::String(
+                    "Rowmap { data:(username:str,email:str,avatar:binstr), volatile:true }"
+                        .to_owned()
+                )
+            );
+        }
+        async fn test_insert_select() {
+            let q = query!(format!(
+                "insert into {__MYENTITY__} ('sayan', 'sayan@example.com', 'abcd')"
+            ));
+            runeq!(con, q, Element::RespCode(RespCode::Okay));
+            let q = query!(format!(
+                "select email from {__MYENTITY__} where username = 'sayan'"
+            ));
+            runeq!(con, q, Element::String("sayan@example.com".to_owned()));
+            let q = query!(format!(
+                "select avatar from {__MYENTITY__} where username = 'sayan'"
+            ));
+            runeq!(con, q, Element::Binstr(b"abcd".to_vec()));
+            let q = query!(format!(
+                "select username from {__MYENTITY__} where username = 'sayan'"
+            ));
+            runeq!(con, q, Element::String("sayan".to_owned()));
+        }
+        async fn test_insert_needs_every_field() {
+            let q = query!(format!(
+                "insert into {__MYENTITY__} ('sayan', 'sayan@example.com')"
+            ));
+            runeq!(con, q, Element::RespCode(RespCode::ActionError));
+        }
+        async fn test_select_by_unknown_field() {
+            let q = query!(format!(
+                "select email from {__MYENTITY__} where k = 'sayan'"
+            ));
+            runeq!(
+                con,
+                q,
+                Element::RespCode(RespCode::ErrorString("bql-unknown-field".to_owned()))
+            );
+        }
+        async fn test_update_field() {
+            let q = query!(format!(
+                "insert into {__MYENTITY__} ('sayan', 'sayan@example.com', 'abcd')"
+            ));
+            runeq!(con, q, Element::RespCode(RespCode::Okay));
+            let q = query!(format!(
+                "update {__MYENTITY__} set email = 'ohsayan@example.com' where username = 'sayan'"
+            ));
+            runeq!(con, q, Element::RespCode(RespCode::Okay));
+            let q = query!(format!(
+                "select email from {__MYENTITY__} where username = 'sayan'"
+            ));
+            runeq!(con, q, Element::String("ohsayan@example.com".to_owned()));
+            // the key can't be updated
+            let q = query!(format!(
+                "update {__MYENTITY__} set username = 'ohsayan' where username = 'sayan'"
+            ));
+            runeq!(
+                con,
+                q,
+                Element::RespCode(RespCode::ErrorString("bql-unknown-field".to_owned()))
+            );
+        }
+        async fn test_delete() {
+            let q = query!(format!(
+                "insert into {__MYENTITY__} ('sayan', 'sayan@example.com', 'abcd')"
+            ));
+            runeq!(con, q, Element::RespCode(RespCode::Okay));
+            let q = query!(format!(
+                "delete from {__MYENTITY__} where username = 'sayan'"
+            ));
+            runeq!(con, q, Element::RespCode(RespCode::Okay));
+            let q = query!(format!(
+                "select email from {__MYENTITY__} where username = 'sayan'"
+            ));
+            runeq!(con, q, Element::RespCode(RespCode::NotFound));
+        }
+    }
+}
+
+mod numeric_fields {
+    #[sky_macros::dbtest_module(table = "(id: uint64, name: string, balance: sint64)")]
+    mod __private {
+        use skytable::{query, Element, RespCode};
+
+        async fn test_inspect_model() {
+            let q = query!(format!("inspect model {__MYENTITY__}"));
+            runeq!(
+                con,
+                q,
+                Element::String(
+                    "Rowmap { data:(id:uint64,name:str,balance:sint64), volatile:true }".to_owned()
+                )
+            );
+        }
+        async fn test_insert_select() {
+            let q = query!(format!("insert into {__MYENTITY__} ('1', 'sayan', '-100')"));
+            runeq!(con, q, Element::RespCode(RespCode::Okay));
+            let q = query!(format!("select balance from {__MYENTITY__} where id = '1'"));
+            runeq!(con, q, Element::String("-100".to_owned()));
+        }
+        async fn test_insert_non_numeric() {
+            let q = query!(format!(
+                "insert into {__MYENTITY__} ('one', 'sayan', '-100')"
+            ));
+            runeq!(con, q, Element::RespCode(RespCode::EncodingError));
+            let q = query!(format!("insert into {__MYENTITY__} ('1', 'sayan', '1.5')"));
+            runeq!(con, q, Element::RespCode(RespCode::EncodingError));
+        }
+    }
+}
@@ -1130,6 +1130,114 @@ mod __private {
             Element::RespCode(RespCode::ActionError)
         );
     }
+    async fn test_scan_full() {
+        setkeys!(
+            con,
+            "x":"100",
+            "y":"200",
+            "z":"300",
+            "a":"apples",
+            "b":"burgers",
+            "c":"carrots"
+        );
+        let mut cursor = "0".to_owned();
+        let mut seen = Vec::new();
+        loop {
+            let mut query = Query::new();
+            query.push("scan");
+            query.push(&cursor);
+            query.push("count");
+            query.push("2");
+            match con.run_query_raw(&query).await.unwrap() {
+                Element::Array(Array::NonNullStr(mut arr)) => {
+                    cursor = arr.remove(0);
+                    seen.extend(arr);
+                }
+                _ => panic!("Expected flat string array"),
+            }
+            if cursor == "0" {
+                break;
+            }
+        }
+        seen.sort_unstable();
+        seen.dedup();
+        assert_eq!(seen, ["a", "b", "c", "x", "y", "z"]);
+    }
+    async fn test_scan_match() {
+        setkeys!(
+            con,
+            "user:1":"sayan",
+            "user:2":"ferris",
+            "session:1":"token"
+        );
+        query.push("scan");
+        query.push("0");
+        query.push("match");
+        query.push("user:*");
+        query.push("count");
+        query.push("100");
+        match con.run_query_raw(&query).await.unwrap() {
+            Element::Array(Array::NonNullStr(mut arr)) => {
+                assert_eq!(arr.remove(0), "0");
+                arr.sort_unstable();
+                assert_eq!(arr, ["user:1", "user:2"]);
+            }
+            _ => panic!("Expected flat string array"),
+        }
+    }
+    async fn test_scan_syntax_error() {
+        query.push("scan");
+        query.push("0");
+        query.push("match");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::ActionError)
+        );
+    }
+    async fn test_scan_bad_cursor() {
+        query.push("scan");
+        query.push("notanumber");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Wrongtype)
+        );
+    }
+    async fn test_range_unordered_model() {
+        query.push("range");
+        query.push("-");
+        query.push("+");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::ErrorString("wrong-model".to_owned()))
+        );
+    }
+    async fn test_hget_unmapped_model() {
+        query.push("hget");
+        query.push("x");
+        query.push("y");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::ErrorString("wrong-model".to_owned()))
+        );
+    }
+    async fn test_sadd_non_set_model() {
+        query.push("sadd");
+        query.push("x");
+        query.push("y");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::ErrorString("wrong-model".to_owned()))
+        );
+    }
+    async fn test_qpush_non_queue_model() {
+        query.push("qpush");
+        query.push("x");
+        query.push("y");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::ErrorString("wrong-model".to_owned()))
+        );
+    }
     async fn test_mpop_syntax_error() {
         query.push("mpop");
         assert_eq!(
@@ -1202,4 +1310,125 @@ mod __private {
             Element::RespCode(RespCode::NotFound)
         );
     }
+    async fn test_set_ex_okay() {
+        query.push("set");
+        query.push("x");
+        query.push("100");
+        query.push("ex");
+        query.push("100");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+        let mut query = Query::new();
+        query.push("ttl");
+        query.push("x");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::UnsignedInt(100)
+        );
+    }
+    async fn test_set_ex_syntax_error() {
+        query.push("set");
+        query.push("x");
+        query.push("100");
+        query.push("px");
+        query.push("100");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::ActionError)
+        );
+        let mut query = Query::new();
+        query.push("set");
+        query.push("x");
+        query.push("100");
+        query.push("ex");
+        query.push("notanumber");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Wrongtype)
+        );
+    }
+    async fn test_set_ex_expired_is_hidden() {
+        query.push("set");
+        query.push("x");
+        query.push("100");
+        query.push("ex");
+        query.push("0");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+        let mut query = Query::new();
+        query.push("get");
+        query.push("x");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::NotFound)
+        );
+        let mut query = Query::new();
+        query.push("exists");
+        query.push("x");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::UnsignedInt(0)
+        );
+    }
+    async fn test_expire_ttl_persist() {
+        setkeys!(
+            con,
+            "x":"100"
+        );
+        query.push("ttl");
+        query.push("x");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::ErrorString("no-expiry".to_owned()))
+        );
+        let mut query = Query::new();
+        query.push("expire");
+        query.push("x");
+        query.push("50");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+        let mut query = Query::new();
+        query.push("ttl");
+        query.push("x");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::UnsignedInt(50)
+        );
+        let mut query = Query::new();
+        query.push("persist");
+        query.push("x");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::Okay)
+        );
+        let mut query = Query::new();
+        query.push("persist");
+        query.push("x");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::NotFound)
+        );
+    }
+    async fn test_expire_nil() {
+        query.push("expire");
+        query.push("x");
+        query.push("50");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::NotFound)
+        );
+        let mut query = Query::new();
+        query.push("ttl");
+        query.push("x");
+        assert_eq!(
+            con.run_query_raw(&query).await.unwrap(),
+            Element::RespCode(RespCode::NotFound)
+        );
+    }
 }
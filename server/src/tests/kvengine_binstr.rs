@@ -0,0 +1,311 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! Tests for the `APPEND`, `GETRANGE`, `SETRANGE`, `GETBIT`, `SETBIT` and `BITCOUNT` actions
+
+#[sky_macros::dbtest_module(table = "(string, binary)")]
+mod __private {
+    use skytable::{query, types::RawString, Element, RespCode};
+
+    async fn test_append() {
+        runeq!(con, query!("append", "x", "hello"), Element::UnsignedInt(5));
+        runeq!(
+            con,
+            query!("append", "x", " world"),
+            Element::UnsignedInt(11)
+        );
+        runeq!(
+            con,
+            query!("get", "x"),
+            Element::Binstr(b"hello world".to_vec())
+        );
+        runeq!(con, query!("strlen", "x"), Element::UnsignedInt(11));
+    }
+    async fn test_append_keeps_ttl() {
+        runeq!(con, query!("append", "x", "a"), Element::UnsignedInt(1));
+        runeq!(
+            con,
+            query!("expire", "x", "100"),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(con, query!("append", "x", "b"), Element::UnsignedInt(2));
+        runeq!(con, query!("ttl", "x"), Element::UnsignedInt(100));
+    }
+    async fn test_getrange() {
+        setkeys!(
+            con,
+            "x": "hello world"
+        );
+        runeq!(
+            con,
+            query!("getrange", "x", "0", "5"),
+            Element::Binstr(b"hello".to_vec())
+        );
+        runeq!(
+            con,
+            query!("getrange", "x", "-5"),
+            Element::Binstr(b"world".to_vec())
+        );
+        runeq!(
+            con,
+            query!("getrange", "x", "6", "-1"),
+            Element::Binstr(b"worl".to_vec())
+        );
+        // ranges are cut down to fit the value
+        runeq!(
+            con,
+            query!("getrange", "x", "-100", "100"),
+            Element::Binstr(b"hello world".to_vec())
+        );
+        runeq!(
+            con,
+            query!("getrange", "x", "5", "2"),
+            Element::Binstr(vec![])
+        );
+        runeq!(
+            con,
+            query!("getrange", "y", "0"),
+            Element::RespCode(RespCode::NotFound)
+        );
+        runeq!(
+            con,
+            query!("getrange", "x", "0", "1a"),
+            Element::RespCode(RespCode::Wrongtype)
+        );
+    }
+    async fn test_setrange() {
+        setkeys!(
+            con,
+            "x": "hello world"
+        );
+        runeq!(
+            con,
+            query!("setrange", "x", "6", "there"),
+            Element::UnsignedInt(11)
+        );
+        runeq!(
+            con,
+            query!("get", "x"),
+            Element::Binstr(b"hello there".to_vec())
+        );
+        runeq!(
+            con,
+            query!("setrange", "x", "11", "!"),
+            Element::UnsignedInt(12)
+        );
+        runeq!(
+            con,
+            query!("get", "x"),
+            Element::Binstr(b"hello there!".to_vec())
+        );
+        // a missing key is padded with zeroes
+        runeq!(
+            con,
+            query!("setrange", "y", "2", "a"),
+            Element::UnsignedInt(3)
+        );
+        runeq!(con, query!("get", "y"), Element::Binstr(b"\0\0a".to_vec()));
+    }
+    async fn test_setrange_too_long() {
+        runeq!(
+            con,
+            query!("setrange", "x", "18446744073709551615", "a"),
+            Element::RespCode(RespCode::ErrorString("value-overflow".to_owned()))
+        );
+        runeq!(
+            con,
+            query!("setrange", "x", "-1", "a"),
+            Element::RespCode(RespCode::Wrongtype)
+        );
+        runeq!(con, query!("exists", "x"), Element::UnsignedInt(0));
+    }
+    async fn test_bits() {
+        runeq!(con, query!("getbit", "x", "7"), Element::UnsignedInt(0));
+        runeq!(
+            con,
+            query!("setbit", "x", "7", "1"),
+            Element::UnsignedInt(0)
+        );
+        runeq!(
+            con,
+            query!("setbit", "x", "7", "1"),
+            Element::UnsignedInt(1)
+        );
+        runeq!(con, query!("getbit", "x", "7"), Element::UnsignedInt(1));
+        runeq!(con, query!("get", "x"), Element::Binstr(vec![0x01]));
+        runeq!(
+            con,
+            query!("setbit", "x", "8", "1"),
+            Element::UnsignedInt(0)
+        );
+        runeq!(con, query!("get", "x"), Element::Binstr(vec![0x01, 0x80]));
+        runeq!(
+            con,
+            query!("setbit", "x", "7", "0"),
+            Element::UnsignedInt(1)
+        );
+        runeq!(con, query!("get", "x"), Element::Binstr(vec![0x00, 0x80]));
+        // bits past the end are zero
+        runeq!(con, query!("getbit", "x", "1000"), Element::UnsignedInt(0));
+        runeq!(
+            con,
+            query!("setbit", "x", "0", "2"),
+            Element::RespCode(RespCode::Wrongtype)
+        );
+    }
+    async fn test_bitcount() {
+        runeq!(con, query!("bitcount", "x"), Element::UnsignedInt(0));
+        query.push("set");
+        query.push("x");
+        query.push(RawString::from(vec![0xFF, 0x0F, 0x01]));
+        runeq!(con, query, Element::RespCode(RespCode::Okay));
+        runeq!(con, query!("bitcount", "x"), Element::UnsignedInt(13));
+        runeq!(con, query!("bitcount", "x", "1"), Element::UnsignedInt(5));
+        runeq!(
+            con,
+            query!("bitcount", "x", "0", "-1"),
+            Element::UnsignedInt(12)
+        );
+        runeq!(
+            con,
+            query!("bitcount", "x", "a"),
+            Element::RespCode(RespCode::Wrongtype)
+        );
+    }
+    async fn test_syntax_errors() {
+        runeq!(
+            con,
+            query!("append", "x"),
+            Element::RespCode(RespCode::ActionError)
+        );
+        runeq!(
+            con,
+            query!("getrange", "x", "0", "1", "2"),
+            Element::RespCode(RespCode::ActionError)
+        );
+        runeq!(
+            con,
+            query!("setbit", "x", "0"),
+            Element::RespCode(RespCode::ActionError)
+        );
+        runeq!(
+            con,
+            query!("bitcount"),
+            Element::RespCode(RespCode::ActionError)
+        );
+    }
+}
+
+#[sky_macros::dbtest_module(table = "(string, string)")]
+mod __private {
+    use skytable::{query, types::RawString, Element, RespCode};
+
+    async fn test_append_string() {
+        runeq!(con, query!("append", "x", "héllo"), Element::UnsignedInt(6));
+        runeq!(con, query!("get", "x"), Element::String("héllo".to_owned()));
+        query.push("append");
+        query.push("x");
+        query.push(RawString::from(b"\xF0\x90\x80".to_vec()));
+        runeq!(con, query, Element::RespCode(RespCode::EncodingError));
+    }
+    async fn test_getrange_string() {
+        setkeys!(
+            con,
+            "x": "héllo"
+        );
+        runeq!(
+            con,
+            query!("getrange", "x", "3"),
+            Element::String("llo".to_owned())
+        );
+        // half of a character isn't a string
+        runeq!(
+            con,
+            query!("getrange", "x", "0", "2"),
+            Element::Binstr(b"h\xC3".to_vec())
+        );
+    }
+    async fn test_setrange_string() {
+        setkeys!(
+            con,
+            "x": "héllo"
+        );
+        // this would overwrite half of the `é`
+        runeq!(
+            con,
+            query!("setrange", "x", "2", "e"),
+            Element::RespCode(RespCode::EncodingError)
+        );
+        runeq!(
+            con,
+            query!("setrange", "x", "1", "ey"),
+            Element::UnsignedInt(6)
+        );
+        runeq!(
+            con,
+            query!("get", "x"),
+            Element::String("heyllo".to_owned())
+        );
+    }
+    async fn test_setbit_string() {
+        setkeys!(
+            con,
+            "x": "a"
+        );
+        // `a` (0x61) to `c` (0x63)
+        runeq!(
+            con,
+            query!("setbit", "x", "6", "1"),
+            Element::UnsignedInt(0)
+        );
+        runeq!(con, query!("get", "x"), Element::String("c".to_owned()));
+        // 0xE3 isn't valid UTF-8 on its own
+        runeq!(
+            con,
+            query!("setbit", "x", "0", "1"),
+            Element::RespCode(RespCode::EncodingError)
+        );
+        runeq!(con, query!("get", "x"), Element::String("c".to_owned()));
+    }
+}
+
+#[sky_macros::dbtest_module(table = "(string, uint64)")]
+mod __private {
+    use skytable::{query, Element, RespCode};
+
+    async fn test_numeric_model() {
+        runeq!(
+            con,
+            query!("append", "x", "1"),
+            Element::RespCode(RespCode::ErrorString("wrong-model".to_owned()))
+        );
+        runeq!(
+            con,
+            query!("bitcount", "x"),
+            Element::RespCode(RespCode::ErrorString("wrong-model".to_owned()))
+        );
+    }
+}
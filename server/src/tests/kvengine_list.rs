@@ -365,6 +365,80 @@ mod __private {
         runeq!(con, q, Element::UnsignedInt(2));
     }
 
+    /// lget valueat (negative index)
+    async fn test_lget_with_valueat_negative_index() {
+        lset!(con, "mylist", "a", "b", "c");
+        let q = query!("lget", "mylist", "valueat", "-1");
+        runeq!(con, q, Element::String("c".to_owned()));
+        let q = query!("lget", "mylist", "valueat", "-3");
+        runeq!(con, q, Element::String("a".to_owned()));
+        let q = query!("lget", "mylist", "valueat", "-4");
+        runeq!(
+            con,
+            q,
+            Element::RespCode(RespCode::ErrorString("bad-list-index".to_owned()))
+        )
+    }
+
+    // lmod trim
+    /// lmod trim (range; okay)
+    async fn test_lmod_trim_range_okay() {
+        lset!(con, "mylist", "1", "2", "3", "4", "5");
+        let q = query!("lmod", "mylist", "trim", "1", "4");
+        runeq!(con, q, Element::RespCode(RespCode::Okay));
+        let q = query!("lget", "mylist");
+        assert_skyhash_arrayeq!(str, con, q, "2", "3", "4");
+    }
+    /// lmod trim (negative range; okay)
+    async fn test_lmod_trim_negative_range_okay() {
+        lset!(con, "mylist", "1", "2", "3", "4", "5");
+        let q = query!("lmod", "mylist", "trim", "-2");
+        runeq!(con, q, Element::RespCode(RespCode::Okay));
+        let q = query!("lget", "mylist");
+        assert_skyhash_arrayeq!(str, con, q, "4", "5");
+    }
+    /// lmod trim last (okay; more than the list has)
+    async fn test_lmod_trim_last_okay() {
+        lset!(con, "mylist", "1", "2", "3", "4", "5");
+        let q = query!("lmod", "mylist", "trim", "last", "2");
+        runeq!(con, q, Element::RespCode(RespCode::Okay));
+        let q = query!("lget", "mylist");
+        assert_skyhash_arrayeq!(str, con, q, "4", "5");
+        let q = query!("lmod", "mylist", "trim", "LAST", "10");
+        runeq!(con, q, Element::RespCode(RespCode::Okay));
+        let q = query!("lget", "mylist");
+        assert_skyhash_arrayeq!(str, con, q, "4", "5");
+    }
+    /// lmod trim (bad index)
+    async fn test_lmod_trim_bad_index() {
+        lset!(con, "mylist", "1", "2", "3");
+        let q = query!("lmod", "mylist", "trim", "0", "4");
+        runeq!(
+            con,
+            q,
+            Element::RespCode(RespCode::ErrorString("bad-list-index".to_owned()))
+        );
+        let q = query!("lmod", "mylist", "trim", "1a");
+        runeq!(con, q, Element::RespCode(RespCode::Wrongtype));
+        let q = query!("lmod", "mylist", "trim", "last", "-1");
+        runeq!(con, q, Element::RespCode(RespCode::Wrongtype));
+        // nothing was trimmed
+        let q = query!("lget", "mylist");
+        assert_skyhash_arrayeq!(str, con, q, "1", "2", "3");
+    }
+    /// lmod trim (nil)
+    async fn test_lmod_trim_nil() {
+        let q = query!("lmod", "mylist", "trim", "last", "2");
+        runeq!(con, q, Element::RespCode(RespCode::NotFound));
+    }
+    /// lmod trim (syntax error)
+    async fn test_lmod_trim_syntax_error() {
+        let q = query!("lmod", "mylist", "trim");
+        runeq!(con, q, Element::RespCode(RespCode::ActionError));
+        let q = query!("lmod", "mylist", "trim", "last", "1", "2");
+        runeq!(con, q, Element::RespCode(RespCode::ActionError));
+    }
+
     // tests for range
     async fn test_list_range_nil() {
         let q = query!("lget", "sayan", "range", "1", "10");
@@ -418,6 +492,30 @@ mod __private {
         runeq!(con, q, Element::RespCode(RespCode::Wrongtype));
     }
 
+    async fn test_list_range_negative_okay() {
+        lset!(con, "mylist", "1", "2", "3", "4", "5");
+        let q = query!("lget", "mylist", "range", "-2");
+        assert_skyhash_arrayeq!(str, con, q, "4", "5");
+        let q = query!("lget", "mylist", "range", "1", "-1");
+        assert_skyhash_arrayeq!(str, con, q, "2", "3", "4");
+    }
+
+    async fn test_list_range_negative_fail() {
+        lset!(con, "mylist", "1", "2", "3", "4", "5");
+        let q = query!("lget", "mylist", "range", "-6");
+        runeq!(
+            con,
+            q,
+            Element::RespCode(RespCode::ErrorString("bad-list-index".to_owned()))
+        );
+        let q = query!("lget", "mylist", "range", "-1", "-2");
+        runeq!(
+            con,
+            q,
+            Element::RespCode(RespCode::ErrorString("bad-list-index".to_owned()))
+        )
+    }
+
     // sanity tests
     async fn test_get_model_error() {
         query.push("GET");
@@ -449,3 +547,36 @@ mod __private {
         );
     }
 }
+
+#[sky_macros::dbtest_module(table = "(string,list<string>) maxlen 3")]
+mod __private {
+    use skytable::{query, Element, RespCode};
+
+    async fn test_capped_lset() {
+        lset!(con, "mylist", "1", "2", "3", "4");
+        let q = query!("lget", "mylist");
+        assert_skyhash_arrayeq!(str, con, q, "2", "3", "4");
+    }
+    async fn test_capped_push() {
+        lset!(con, "mylist", "1", "2");
+        let q = query!("lmod", "mylist", "push", "3", "4", "5");
+        runeq!(con, q, Element::RespCode(RespCode::Okay));
+        let q = query!("lget", "mylist");
+        assert_skyhash_arrayeq!(str, con, q, "3", "4", "5");
+    }
+    async fn test_capped_insert() {
+        lset!(con, "mylist", "1", "2", "3");
+        let q = query!("lmod", "mylist", "insert", "1", "x");
+        runeq!(con, q, Element::RespCode(RespCode::Okay));
+        let q = query!("lget", "mylist");
+        assert_skyhash_arrayeq!(str, con, q, "x", "2", "3");
+    }
+    async fn test_capped_inspect() {
+        query.push(format!("inspect model {__MYENTITY__}"));
+        runeq!(
+            con,
+            query,
+            Element::String("Keymap { data:(str,list<str>), volatile:true, maxlen:3 }".to_owned())
+        );
+    }
+}
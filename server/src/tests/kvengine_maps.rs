@@ -0,0 +1,105 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+#[sky_macros::dbtest_module(table = "(string, map<string, binary>)")]
+mod __private {
+    use skytable::{
+        query,
+        types::{Array, RawString},
+        Element, RespCode,
+    };
+
+    async fn test_hset_hget() {
+        let q = query!("hset", "sayan", "email", "sayan@example.com", "age", "30");
+        runeq!(con, q, Element::UnsignedInt(2));
+        let q = query!("hset", "sayan", "age", "31", "city", "kolkata");
+        runeq!(con, q, Element::UnsignedInt(1));
+        let q = query!("hget", "sayan", "age");
+        runeq!(con, q, Element::Binstr(b"31".to_vec()));
+    }
+    async fn test_hget_nil() {
+        let q = query!("hget", "sayan", "email");
+        runeq!(con, q, Element::RespCode(RespCode::NotFound));
+        let q = query!("hset", "sayan", "email", "sayan@example.com");
+        runeq!(con, q, Element::UnsignedInt(1));
+        let q = query!("hget", "sayan", "age");
+        runeq!(con, q, Element::RespCode(RespCode::NotFound));
+    }
+    async fn test_hgetall_sorted() {
+        let q = query!("hset", "sayan", "email", "sayan@example.com", "age", "30");
+        runeq!(con, q, Element::UnsignedInt(2));
+        let q = query!("hgetall", "sayan");
+        runeq!(
+            con,
+            q,
+            Element::Array(Array::NonNullBin(vec![
+                b"age".to_vec(),
+                b"30".to_vec(),
+                b"email".to_vec(),
+                b"sayan@example.com".to_vec()
+            ]))
+        );
+    }
+    async fn test_hgetall_nil() {
+        let q = query!("hgetall", "sayan");
+        runeq!(con, q, Element::RespCode(RespCode::NotFound));
+    }
+    async fn test_hdel_and_hlen() {
+        let q = query!("hset", "sayan", "email", "sayan@example.com", "age", "30");
+        runeq!(con, q, Element::UnsignedInt(2));
+        let q = query!("hdel", "sayan", "age", "nosuchfield");
+        runeq!(con, q, Element::UnsignedInt(1));
+        let q = query!("hlen", "sayan");
+        runeq!(con, q, Element::UnsignedInt(1));
+        // removing the last field removes the key
+        let q = query!("hdel", "sayan", "email");
+        runeq!(con, q, Element::UnsignedInt(1));
+        let q = query!("exists", "sayan");
+        runeq!(con, q, Element::UnsignedInt(0));
+        let q = query!("hlen", "sayan");
+        runeq!(con, q, Element::RespCode(RespCode::NotFound));
+    }
+    async fn test_hset_syntax_error() {
+        let q = query!("hset", "sayan", "email");
+        runeq!(con, q, Element::RespCode(RespCode::ActionError));
+        let q = query!("hset", "sayan");
+        runeq!(con, q, Element::RespCode(RespCode::ActionError));
+    }
+    async fn test_hset_encoding_error() {
+        let mut q = query!("hset", "sayan");
+        q.push(RawString::from(b"\xFF".to_vec()));
+        q.push("value");
+        runeq!(con, q, Element::RespCode(RespCode::EncodingError));
+    }
+    async fn test_del_map() {
+        let q = query!("hset", "sayan", "email", "sayan@example.com");
+        runeq!(con, q, Element::UnsignedInt(1));
+        let q = query!("del", "sayan");
+        runeq!(con, q, Element::UnsignedInt(1));
+        let q = query!("hgetall", "sayan");
+        runeq!(con, q, Element::RespCode(RespCode::NotFound));
+    }
+}
@@ -0,0 +1,137 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! Tests for models with numeric values and the `INCR`, `DECR` and `INCRBY` actions
+
+#[sky_macros::dbtest_module(table = "(string, sint64)")]
+mod __private {
+    use skytable::{query, Element, RespCode};
+
+    async fn test_incr_decr() {
+        runeq!(con, query!("incr", "x"), Element::String("1".to_owned()));
+        runeq!(con, query!("incr", "x"), Element::String("2".to_owned()));
+        runeq!(con, query!("decr", "x"), Element::String("1".to_owned()));
+        runeq!(con, query!("get", "x"), Element::String("1".to_owned()));
+    }
+    async fn test_incrby() {
+        runeq!(
+            con,
+            query!("incrby", "x", "-100"),
+            Element::String("-100".to_owned())
+        );
+        runeq!(
+            con,
+            query!("incrby", "x", "not a number"),
+            Element::RespCode(RespCode::Wrongtype)
+        );
+    }
+    async fn test_incr_overflow() {
+        setkeys!(
+            con,
+            "x": "9223372036854775807"
+        );
+        runeq!(
+            con,
+            query!("incr", "x"),
+            Element::RespCode(RespCode::ErrorString("value-overflow".to_owned()))
+        );
+        runeq!(
+            con,
+            query!("get", "x"),
+            Element::String("9223372036854775807".to_owned())
+        );
+    }
+    async fn test_set_non_numeric() {
+        runeq!(
+            con,
+            query!("set", "x", "ten"),
+            Element::RespCode(RespCode::EncodingError)
+        );
+        runeq!(
+            con,
+            query!("set", "x", "010"),
+            Element::RespCode(RespCode::EncodingError)
+        );
+    }
+    async fn test_incr_keeps_ttl() {
+        runeq!(con, query!("incr", "x"), Element::String("1".to_owned()));
+        runeq!(
+            con,
+            query!("expire", "x", "100"),
+            Element::RespCode(RespCode::Okay)
+        );
+        runeq!(con, query!("incr", "x"), Element::String("2".to_owned()));
+        runeq!(con, query!("ttl", "x"), Element::UnsignedInt(100));
+    }
+}
+
+#[sky_macros::dbtest_module(table = "(string, float64)")]
+mod __private {
+    use skytable::{query, Element, RespCode};
+
+    async fn test_incrby_float() {
+        runeq!(
+            con,
+            query!("incrby", "x", "1.5"),
+            Element::String("1.5".to_owned())
+        );
+        runeq!(
+            con,
+            query!("incrby", "x", "-0.5"),
+            Element::String("1".to_owned())
+        );
+        runeq!(
+            con,
+            query!("incrby", "x", "1e3"),
+            Element::String("1001".to_owned())
+        );
+    }
+    async fn test_incrby_float_non_finite_delta() {
+        runeq!(
+            con,
+            query!("incrby", "x", "NaN"),
+            Element::RespCode(RespCode::Wrongtype)
+        );
+        runeq!(
+            con,
+            query!("incrby", "x", "inf"),
+            Element::RespCode(RespCode::Wrongtype)
+        );
+    }
+}
+
+#[sky_macros::dbtest_module]
+mod __private {
+    use skytable::{query, Element, RespCode};
+
+    async fn test_incr_wrong_model() {
+        runeq!(
+            con,
+            query!("incr", "x"),
+            Element::RespCode(RespCode::ErrorString("wrong-model".to_owned()))
+        );
+    }
+}
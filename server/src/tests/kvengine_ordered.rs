@@ -0,0 +1,124 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+#[sky_macros::dbtest_module(table = "(string, string) ordered")]
+mod __private {
+    use skytable::{types::Array, Element, RespCode};
+
+    async fn test_range_all() {
+        setkeys!(
+            con,
+            "c":"carrots",
+            "a":"apples",
+            "b":"burgers"
+        );
+        push!(query, "range", "-", "+");
+        runeq!(
+            con,
+            query,
+            Element::Array(Array::NonNullStr(vec![
+                "a".to_owned(),
+                "b".to_owned(),
+                "c".to_owned()
+            ]))
+        );
+    }
+    async fn test_range_bounds() {
+        setkeys!(
+            con,
+            "a":"apples",
+            "b":"burgers",
+            "c":"carrots",
+            "d":"doughnuts"
+        );
+        push!(query, "range", "(a", "[c");
+        runeq!(
+            con,
+            query,
+            Element::Array(Array::NonNullStr(vec!["b".to_owned(), "c".to_owned()]))
+        );
+    }
+    async fn test_range_rev_limit_withvalues() {
+        setkeys!(
+            con,
+            "a":"apples",
+            "b":"burgers",
+            "c":"carrots"
+        );
+        push!(query, "range", "-", "+", "rev", "limit", "2", "withvalues");
+        runeq!(
+            con,
+            query,
+            Element::Array(Array::NonNullStr(vec![
+                "c".to_owned(),
+                "carrots".to_owned(),
+                "b".to_owned(),
+                "burgers".to_owned()
+            ]))
+        );
+    }
+    async fn test_range_prefix() {
+        setkeys!(
+            con,
+            "user:2":"ferris",
+            "user:1":"sayan",
+            "session:1":"token"
+        );
+        push!(query, "range", "prefix", "user:");
+        runeq!(
+            con,
+            query,
+            Element::Array(Array::NonNullStr(vec![
+                "user:1".to_owned(),
+                "user:2".to_owned()
+            ]))
+        );
+    }
+    async fn test_range_skips_deleted() {
+        setkeys!(
+            con,
+            "a":"apples",
+            "b":"burgers"
+        );
+        push!(query, "del", "a");
+        runeq!(con, query, Element::UnsignedInt(1));
+        let mut query = skytable::Query::new();
+        push!(query, "range", "-", "+");
+        runeq!(
+            con,
+            query,
+            Element::Array(Array::NonNullStr(vec!["b".to_owned()]))
+        );
+    }
+    async fn test_range_bad_bound() {
+        push!(query, "range", "a", "+");
+        runeq!(con, query, Element::RespCode(RespCode::ActionError));
+    }
+    async fn test_range_bad_limit() {
+        push!(query, "range", "-", "+", "limit", "many");
+        runeq!(con, query, Element::RespCode(RespCode::Wrongtype));
+    }
+}
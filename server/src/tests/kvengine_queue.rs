@@ -0,0 +1,147 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! Tests for queue models and the `QPUSH`, `QRESERVE`, `QACK`, `QNACK` and `QLEN` actions
+
+#[sky_macros::dbtest_module(table = "(string, queue<string>)")]
+mod __private {
+    use skytable::{
+        query,
+        types::{Array, RawString},
+        Element, RespCode,
+    };
+
+    fn reservation(id: &str, item: &str) -> Element {
+        Element::Array(Array::NonNullStr(vec![id.to_owned(), item.to_owned()]))
+    }
+
+    async fn test_qpush_qreserve() {
+        runeq!(
+            con,
+            query!("qpush", "jobs", "a", "b"),
+            Element::UnsignedInt(2)
+        );
+        runeq!(con, query!("qpush", "jobs", "c"), Element::UnsignedInt(3));
+        runeq!(con, query!("qreserve", "jobs"), reservation("0", "a"));
+        runeq!(con, query!("qreserve", "jobs", "60"), reservation("1", "b"));
+        runeq!(con, query!("qlen", "jobs"), Element::UnsignedInt(1));
+        runeq!(
+            con,
+            query!("qlen", "jobs", "reserved"),
+            Element::UnsignedInt(2)
+        );
+    }
+    async fn test_qreserve_empty() {
+        runeq!(
+            con,
+            query!("qreserve", "jobs"),
+            Element::RespCode(RespCode::NotFound)
+        );
+        runeq!(con, query!("qpush", "jobs", "a"), Element::UnsignedInt(1));
+        runeq!(con, query!("qreserve", "jobs"), reservation("0", "a"));
+        runeq!(
+            con,
+            query!("qreserve", "jobs"),
+            Element::RespCode(RespCode::NotFound)
+        );
+    }
+    async fn test_qack() {
+        runeq!(con, query!("qpush", "jobs", "a"), Element::UnsignedInt(1));
+        runeq!(con, query!("qreserve", "jobs"), reservation("0", "a"));
+        runeq!(
+            con,
+            query!("qack", "jobs", "0"),
+            Element::RespCode(RespCode::Okay)
+        );
+        // a reservation can only be acknowledged once
+        runeq!(
+            con,
+            query!("qack", "jobs", "0"),
+            Element::RespCode(RespCode::NotFound)
+        );
+        // the queue is still there
+        runeq!(con, query!("qlen", "jobs"), Element::UnsignedInt(0));
+        runeq!(
+            con,
+            query!("qlen", "jobs", "reserved"),
+            Element::UnsignedInt(0)
+        );
+    }
+    async fn test_qnack() {
+        runeq!(
+            con,
+            query!("qpush", "jobs", "a", "b"),
+            Element::UnsignedInt(2)
+        );
+        runeq!(con, query!("qreserve", "jobs"), reservation("0", "a"));
+        runeq!(
+            con,
+            query!("qnack", "jobs", "0"),
+            Element::RespCode(RespCode::Okay)
+        );
+        // the item goes back to the front, under a new reservation ID
+        runeq!(con, query!("qreserve", "jobs"), reservation("1", "a"));
+        runeq!(
+            con,
+            query!("qack", "jobs", "0"),
+            Element::RespCode(RespCode::NotFound)
+        );
+    }
+    async fn test_qlen_nil() {
+        runeq!(
+            con,
+            query!("qlen", "jobs"),
+            Element::RespCode(RespCode::NotFound)
+        );
+    }
+    async fn test_queue_syntax_errors() {
+        runeq!(
+            con,
+            query!("qpush", "jobs"),
+            Element::RespCode(RespCode::ActionError)
+        );
+        runeq!(
+            con,
+            query!("qlen", "jobs", "waiting"),
+            Element::RespCode(RespCode::ActionError)
+        );
+        runeq!(
+            con,
+            query!("qreserve", "jobs", "0"),
+            Element::RespCode(RespCode::Wrongtype)
+        );
+        runeq!(
+            con,
+            query!("qack", "jobs", "first"),
+            Element::RespCode(RespCode::Wrongtype)
+        );
+    }
+    async fn test_qpush_encoding_error() {
+        let mut q = query!("qpush", "jobs");
+        q.push(RawString::from(b"\xFF".to_vec()));
+        runeq!(con, q, Element::RespCode(RespCode::EncodingError));
+    }
+}
@@ -0,0 +1,162 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! Tests for set and sorted set models
+
+#[sky_macros::dbtest_module(table = "(string, set<string>)")]
+mod __private {
+    use skytable::{
+        query,
+        types::{Array, RawString},
+        Element, RespCode,
+    };
+
+    fn members(members: &[&str]) -> Element {
+        Element::Array(Array::NonNullStr(
+            members.iter().map(|member| member.to_string()).collect(),
+        ))
+    }
+
+    async fn test_sadd_smembers() {
+        let q = query!("sadd", "sayan", "rust", "db", "rust");
+        runeq!(con, q, Element::UnsignedInt(2));
+        let q = query!("sadd", "sayan", "db", "async");
+        runeq!(con, q, Element::UnsignedInt(1));
+        let q = query!("smembers", "sayan");
+        runeq!(con, q, members(&["async", "db", "rust"]));
+        let q = query!("smembers", "nosuchkey");
+        runeq!(con, q, Element::RespCode(RespCode::NotFound));
+    }
+    async fn test_sismember_scard() {
+        let q = query!("sadd", "sayan", "rust", "db");
+        runeq!(con, q, Element::UnsignedInt(2));
+        let q = query!("sismember", "sayan", "rust");
+        runeq!(con, q, Element::UnsignedInt(1));
+        let q = query!("sismember", "sayan", "go");
+        runeq!(con, q, Element::UnsignedInt(0));
+        let q = query!("scard", "sayan");
+        runeq!(con, q, Element::UnsignedInt(2));
+        let q = query!("scard", "nosuchkey");
+        runeq!(con, q, Element::RespCode(RespCode::NotFound));
+    }
+    async fn test_srem() {
+        let q = query!("sadd", "sayan", "rust", "db");
+        runeq!(con, q, Element::UnsignedInt(2));
+        let q = query!("srem", "sayan", "db", "go");
+        runeq!(con, q, Element::UnsignedInt(1));
+        // removing the last member removes the key
+        let q = query!("srem", "sayan", "rust");
+        runeq!(con, q, Element::UnsignedInt(1));
+        let q = query!("exists", "sayan");
+        runeq!(con, q, Element::UnsignedInt(0));
+    }
+    async fn test_sadd_syntax_error() {
+        let q = query!("sadd", "sayan");
+        runeq!(con, q, Element::RespCode(RespCode::ActionError));
+    }
+    async fn test_sadd_encoding_error() {
+        let mut q = query!("sadd", "sayan");
+        q.push(RawString::from(b"\xFF".to_vec()));
+        runeq!(con, q, Element::RespCode(RespCode::EncodingError));
+    }
+    async fn test_zadd_wrong_model() {
+        let q = query!("zadd", "sayan", "1", "rust");
+        runeq!(
+            con,
+            q,
+            Element::RespCode(RespCode::ErrorString("wrong-model".to_owned()))
+        );
+    }
+}
+
+#[sky_macros::dbtest_module(table = "(string, zset<string>)")]
+mod __private {
+    use skytable::{query, types::Array, Element, RespCode};
+
+    fn members(members: &[&str]) -> Element {
+        Element::Array(Array::NonNullStr(
+            members.iter().map(|member| member.to_string()).collect(),
+        ))
+    }
+
+    async fn test_zadd_zscore() {
+        let q = query!("zadd", "board", "1", "alice", "5", "bob");
+        runeq!(con, q, Element::UnsignedInt(2));
+        // updating a score isn't an addition
+        let q = query!("zadd", "board", "7.5", "bob", "3", "carol");
+        runeq!(con, q, Element::UnsignedInt(1));
+        let q = query!("zscore", "board", "bob");
+        runeq!(con, q, Element::String("7.5".to_owned()));
+        let q = query!("zscore", "board", "dave");
+        runeq!(con, q, Element::RespCode(RespCode::NotFound));
+        let q = query!("zcard", "board");
+        runeq!(con, q, Element::UnsignedInt(3));
+    }
+    async fn test_zrank() {
+        let q = query!("zadd", "board", "1", "alice", "5", "bob", "3", "carol");
+        runeq!(con, q, Element::UnsignedInt(3));
+        let q = query!("zrank", "board", "bob");
+        runeq!(con, q, Element::UnsignedInt(2));
+        let q = query!("zrank", "board", "bob", "rev");
+        runeq!(con, q, Element::UnsignedInt(0));
+        let q = query!("zrank", "board", "dave");
+        runeq!(con, q, Element::RespCode(RespCode::NotFound));
+    }
+    async fn test_zrangebyscore() {
+        let q = query!("zadd", "board", "1", "alice", "5", "bob", "3", "carol");
+        runeq!(con, q, Element::UnsignedInt(3));
+        let q = query!("zrangebyscore", "board", "-", "+");
+        runeq!(con, q, members(&["alice", "carol", "bob"]));
+        let q = query!("zrangebyscore", "board", "(1", "[5");
+        runeq!(con, q, members(&["carol", "bob"]));
+        let q = query!("zrangebyscore", "board", "-", "+", "limit", "2", "rev");
+        runeq!(con, q, members(&["bob", "carol"]));
+        let q = query!("zrangebyscore", "board", "[3", "(5", "withscores");
+        runeq!(con, q, members(&["carol", "3"]));
+    }
+    async fn test_zrangebyscore_bad_bound() {
+        let q = query!("zadd", "board", "1", "alice");
+        runeq!(con, q, Element::UnsignedInt(1));
+        let q = query!("zrangebyscore", "board", "1", "+");
+        runeq!(con, q, Element::RespCode(RespCode::ActionError));
+    }
+    async fn test_zadd_bad_score() {
+        let q = query!("zadd", "board", "one", "alice");
+        runeq!(con, q, Element::RespCode(RespCode::Wrongtype));
+        let q = query!("zadd", "board", "inf", "alice");
+        runeq!(con, q, Element::RespCode(RespCode::Wrongtype));
+        let q = query!("zadd", "board", "1");
+        runeq!(con, q, Element::RespCode(RespCode::ActionError));
+    }
+    async fn test_zrem() {
+        let q = query!("zadd", "board", "1", "alice", "5", "bob");
+        runeq!(con, q, Element::UnsignedInt(2));
+        let q = query!("zrem", "board", "alice", "dave");
+        runeq!(con, q, Element::UnsignedInt(1));
+        let q = query!("zcard", "board");
+        runeq!(con, q, Element::UnsignedInt(1));
+    }
+}
@@ -30,16 +30,89 @@
 mod macros;
 #[cfg(not(feature = "persist-suite"))]
 mod auth;
+mod blpop;
 mod ddl_tests;
+mod dml_tests;
 mod inspect_tests;
 mod kvengine;
+mod kvengine_binstr;
 mod kvengine_encoding;
 mod kvengine_list;
+mod kvengine_maps;
+mod kvengine_numeric;
+mod kvengine_ordered;
+mod kvengine_queue;
+mod kvengine_sets;
 mod persist;
 mod pipeline;
+mod pubsub;
+mod replication;
 mod snapshot;
+mod txn;
+mod watch;
 mod issue_tests;
 
+mod rawcon {
+    //! Raw Skyhash 2 connections. The client library doesn't know about push mode, so tests
+    //! that look at what the server pushes use these instead
+
+    use {
+        std::time::Duration,
+        tokio::{
+            io::{AsyncReadExt, AsyncWriteExt},
+            net::TcpStream,
+            time,
+        },
+    };
+
+    /// Connect to the default test server
+    pub async fn connect() -> TcpStream {
+        TcpStream::connect("127.0.0.1:2003").await.unwrap()
+    }
+
+    /// Connect to the default test server and use `entity`
+    pub async fn connect_to(entity: &str) -> TcpStream {
+        let mut stream = connect().await;
+        send(&mut stream, &["use", entity]).await;
+        expect(&mut stream, b"*!0\n").await;
+        stream
+    }
+
+    /// Send a simple query
+    pub async fn send(stream: &mut TcpStream, query: &[&str]) {
+        let mut buf = format!("*{}\n", query.len()).into_bytes();
+        for element in query {
+            buf.extend(format!("{}\n", element.len()).as_bytes());
+            buf.extend(element.as_bytes());
+        }
+        stream.write_all(&buf).await.unwrap();
+    }
+
+    /// Read exactly `expected` from the stream
+    pub async fn expect(stream: &mut TcpStream, expected: &[u8]) {
+        let mut buf = vec![0; expected.len()];
+        time::timeout(Duration::from_secs(5), stream.read_exact(&mut buf))
+            .await
+            .expect("timed out")
+            .unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&buf),
+            String::from_utf8_lossy(expected)
+        );
+    }
+
+    /// Make sure that nothing has been sent to the stream yet
+    pub async fn expect_nothing(stream: &mut TcpStream) {
+        let mut buf = [0; 1];
+        assert!(
+            time::timeout(Duration::from_millis(200), stream.read(&mut buf))
+                .await
+                .is_err(),
+            "the server sent something"
+        );
+    }
+}
+
 mod tls {
     use skytable::{query, Element};
     #[sky_macros::dbtest_func(tls_cert = "cert.pem", port = 2004)]
@@ -0,0 +1,122 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! Tests for pub/sub. Subscribers use raw connections since they're in push mode
+
+use {
+    super::rawcon::{connect, expect, send},
+    sky_macros::dbtest_func as dbtest,
+    skytable::{query, Element, RespCode},
+    std::time::Duration,
+    tokio::time,
+};
+
+#[dbtest]
+async fn publish_without_subscribers() {
+    runeq!(
+        con,
+        query!("publish", "pubsub-nobody", "hello"),
+        Element::UnsignedInt(0)
+    );
+}
+
+#[dbtest]
+async fn subscribe_and_publish() {
+    let mut sub = connect().await;
+    send(&mut sub, &["subscribe", "pubsub-news", "pubsub-sports"]).await;
+    expect(&mut sub, b"*:2\n").await;
+    runeq!(
+        con,
+        query!("publish", "pubsub-news", "hello"),
+        Element::UnsignedInt(1)
+    );
+    expect(&mut sub, b"*^?3\n7\nmessage11\npubsub-news5\nhello").await;
+    // only subscriptions can be changed in push mode
+    send(&mut sub, &["get", "x"]).await;
+    expect(&mut sub, b"*!err-subscribed\n").await;
+    send(&mut sub, &["unsubscribe", "pubsub-news"]).await;
+    expect(&mut sub, b"*:1\n").await;
+    runeq!(
+        con,
+        query!("publish", "pubsub-news", "hello"),
+        Element::UnsignedInt(0)
+    );
+    runeq!(
+        con,
+        query!("publish", "pubsub-sports", "goal"),
+        Element::UnsignedInt(1)
+    );
+    expect(&mut sub, b"*^?3\n7\nmessage13\npubsub-sports4\ngoal").await;
+    // leaving all channels ends push mode
+    send(&mut sub, &["unsubscribe"]).await;
+    expect(&mut sub, b"*:0\n").await;
+    send(&mut sub, &["heya"]).await;
+    expect(&mut sub, b"*+4\nHEY!").await;
+}
+
+#[dbtest]
+async fn fan_out() {
+    let mut subs = [connect().await, connect().await];
+    for sub in subs.iter_mut() {
+        send(sub, &["subscribe", "pubsub-fanout"]).await;
+        expect(sub, b"*:1\n").await;
+    }
+    runeq!(
+        con,
+        query!("publish", "pubsub-fanout", "hello"),
+        Element::UnsignedInt(2)
+    );
+    for sub in subs.iter_mut() {
+        expect(sub, b"*^?3\n7\nmessage13\npubsub-fanout5\nhello").await;
+    }
+    // a disconnected subscriber stops counting
+    drop(subs);
+    let mut attempts = 0;
+    while con
+        .run_query_raw(&query!("publish", "pubsub-fanout", "hello"))
+        .await
+        .unwrap()
+        != Element::UnsignedInt(0)
+    {
+        attempts += 1;
+        assert!(attempts < 50, "subscribers weren't released");
+        time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+#[dbtest]
+async fn subscribe_aerr() {
+    runeq!(
+        con,
+        query!("subscribe"),
+        Element::RespCode(RespCode::ActionError)
+    );
+    runeq!(
+        con,
+        query!("publish", "pubsub-news"),
+        Element::RespCode(RespCode::ActionError)
+    );
+}
@@ -0,0 +1,101 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! Tests for replication. `server4` in the harness is a follower of the server on port 2003
+
+use {
+    sky_macros::dbtest_func as dbtest,
+    skytable::{aio::Connection, query, Element, Query, RespCode},
+    std::time::Duration,
+};
+
+const FOLLOWER_PORT: u16 = 2009;
+const MAX_ATTEMPTS: usize = 100;
+
+/// Run `query` on `con` until it returns `expected`, giving up after `MAX_ATTEMPTS`
+async fn wait_for(con: &mut Connection, query: Query, expected: Element) {
+    let mut last = None;
+    for _ in 0..MAX_ATTEMPTS {
+        let ret = con.run_query_raw(&query).await.unwrap();
+        if ret == expected {
+            return;
+        }
+        last = Some(ret);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("Expected {expected:?} from the follower, got {last:?}");
+}
+
+#[dbtest(skip_if_cfg = "persist-suite", norun = true)]
+async fn follower_replicates_writes() {
+    let mut follower = Connection::new("127.0.0.1", FOLLOWER_PORT).await.unwrap();
+    wait_for(
+        &mut follower,
+        query!("sys", "metric", "replication"),
+        Element::String("follower-online".to_owned()),
+    )
+    .await;
+    assert_okay!(con, query!("create space replication"));
+    assert_okay!(con, query!("create model replication.kv(string,string)"));
+    switch_entity!(con, "replication.kv");
+    assert_okay!(con, query!("set", "x", "100"));
+    wait_for(
+        &mut follower,
+        query!("use replication.kv"),
+        Element::RespCode(RespCode::Okay),
+    )
+    .await;
+    wait_for(
+        &mut follower,
+        query!("get", "x"),
+        Element::String("100".to_owned()),
+    )
+    .await;
+    // writes are only accepted by the leader
+    runeq!(
+        follower,
+        query!("set", "y", "200"),
+        Element::RespCode(RespCode::ErrorString("err-read-only-follower".to_owned()))
+    );
+    assert_okay!(con, query!("update", "x", "200"));
+    wait_for(
+        &mut follower,
+        query!("get", "x"),
+        Element::String("200".to_owned()),
+    )
+    .await;
+    // dropping the model on the leader drops it on the follower
+    switch_entity!(con, "default");
+    switch_entity!(follower, "default");
+    assert_okay!(con, query!("drop model replication.kv force"));
+    assert_okay!(con, query!("drop space replication"));
+    wait_for(
+        &mut follower,
+        query!("use replication"),
+        Element::RespCode(RespCode::ErrorString("container-not-found".to_owned())),
+    )
+    .await;
+}
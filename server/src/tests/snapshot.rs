@@ -87,3 +87,18 @@ async fn remote_snapshot_fail_because_already_exists() {
         }
     }
 }
+
+#[dbtest]
+async fn snapshot_fail_because_illegal_name() {
+    for action in ["mksnap", "rmsnap", "restoresnap"] {
+        for name in ["", ".", "a/b", "../a"] {
+            runeq!(
+                con,
+                query!(action, name),
+                Element::RespCode(RespCode::ErrorString(
+                    "err-invalid-snapshot-name".to_owned()
+                ))
+            );
+        }
+    }
+}
@@ -0,0 +1,146 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! Tests for transactions (`MULTI` and `EXEC`)
+
+use {
+    sky_macros::dbtest_func as dbtest,
+    skytable::{query, types::Array, Element, Pipeline, Query, RespCode},
+};
+
+fn err(estr: &str) -> Element {
+    Element::RespCode(RespCode::ErrorString(estr.to_owned()))
+}
+
+#[dbtest]
+async fn txn_commit() {
+    assert_okay!(con, query!("set", "acct-a", "100"));
+    assert_okay!(con, query!("multi"));
+    assert_okay!(con, query!("check", "acct-a", "eq", "100"));
+    assert_okay!(con, query!("check", "acct-b", "absent"));
+    assert_okay!(con, query!("update", "acct-a", "50"));
+    assert_okay!(con, query!("set", "acct-b", "50"));
+    assert_okay!(con, query!("exec"));
+    runeq!(
+        con,
+        query!("mget", "acct-a", "acct-b"),
+        Element::Array(Array::Str(vec![
+            Some("50".to_owned()),
+            Some("50".to_owned())
+        ]))
+    );
+}
+
+#[dbtest]
+async fn txn_precondition_aborts() {
+    assert_okay!(con, query!("set", "acct-a", "100"));
+    assert_okay!(con, query!("multi"));
+    assert_okay!(con, query!("check", "acct-a", "eq", "200"));
+    assert_okay!(con, query!("del", "acct-a"));
+    runeq!(con, query!("exec"), err("err-txn-aborted"));
+    runeq!(con, query!("exists", "acct-a"), Element::UnsignedInt(1));
+    // a statement that can't be applied aborts too
+    assert_okay!(con, query!("multi"));
+    assert_okay!(con, query!("del", "acct-a"));
+    assert_okay!(con, query!("update", "acct-b", "100"));
+    runeq!(con, query!("exec"), err("err-txn-aborted"));
+    runeq!(con, query!("exists", "acct-a"), Element::UnsignedInt(1));
+}
+
+#[dbtest]
+async fn txn_unsupported_action_aborts() {
+    assert_okay!(con, query!("multi"));
+    assert_okay!(con, query!("set", "x", "100"));
+    runeq!(con, query!("get", "x"), err("err-txn-unsupported"));
+    runeq!(con, query!("exec"), err("err-txn-aborted"));
+    runeq!(con, query!("exists", "x"), Element::UnsignedInt(0));
+}
+
+#[dbtest]
+async fn txn_discard() {
+    assert_okay!(con, query!("multi"));
+    assert_okay!(con, query!("set", "x", "100"));
+    assert_okay!(con, query!("discard"));
+    runeq!(con, query!("exists", "x"), Element::UnsignedInt(0));
+}
+
+#[dbtest]
+async fn txn_not_in_txn() {
+    runeq!(con, query!("exec"), err("err-not-in-txn"));
+    runeq!(con, query!("discard"), err("err-not-in-txn"));
+    runeq!(con, query!("check", "x", "absent"), err("err-not-in-txn"));
+}
+
+#[dbtest]
+async fn txn_in_pipeline() {
+    let pipe = Pipeline::new()
+        .add(query!("multi"))
+        .add(query!("set", "x", "100"))
+        .add(query!("check", "x", "eq", "100"))
+        .add(query!("exec"))
+        .add(query!("get", "x"));
+    let ret = con.run_pipeline(pipe).await.unwrap();
+    let okay = Element::RespCode(RespCode::Okay);
+    assert_eq!(
+        ret,
+        vec![
+            okay.clone(),
+            okay.clone(),
+            okay.clone(),
+            okay,
+            Element::String("100".to_owned())
+        ]
+    );
+}
+
+#[dbtest(table = "(string,list<string>)")]
+async fn txn_lists() {
+    let mut q = Query::from("lset");
+    q.push("mylist");
+    q.push("a");
+    assert_okay!(con, q);
+    assert_okay!(con, query!("multi"));
+    assert_okay!(con, query!("lmod", "mylist", "push", "b", "c"));
+    assert_okay!(con, query!("lmod", "mylist", "remove", "0"));
+    runeq!(
+        con,
+        query!("lmod", "mylist", "pop"),
+        err("err-txn-unsupported")
+    );
+    assert_okay!(con, query!("discard"));
+    assert_okay!(con, query!("multi"));
+    assert_okay!(con, query!("lmod", "mylist", "push", "b", "c"));
+    assert_okay!(con, query!("lmod", "mylist", "remove", "0"));
+    assert_okay!(con, query!("exec"));
+    runeq!(
+        con,
+        query!("lget", "mylist"),
+        Element::Array(Array::NonNullStr(vec!["b".to_owned(), "c".to_owned()]))
+    );
+    assert_okay!(con, query!("multi"));
+    assert_okay!(con, query!("set", "x", "y"));
+    runeq!(con, query!("exec"), err("wrong-model"));
+}
@@ -0,0 +1,89 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! Tests for key watches. Like pub/sub subscribers, watchers speak raw Skyhash 2
+
+use {
+    super::rawcon::{connect_to, expect, send},
+    sky_macros::dbtest_func as dbtest,
+    skytable::{query, Element, RespCode},
+};
+
+#[dbtest]
+async fn watch_set_update_del() {
+    let mut watcher = connect_to(&__MYENTITY__).await;
+    send(&mut watcher, &["watch", "x", "y"]).await;
+    expect(&mut watcher, b"*:2\n").await;
+    assert_okay!(con, query!("set", "x", "100"));
+    expect(&mut watcher, b"*^?3\n5\nwatch1\nx3\nset").await;
+    assert_okay!(con, query!("update", "x", "200"));
+    expect(&mut watcher, b"*^?3\n5\nwatch1\nx6\nupdate").await;
+    // keys that aren't watched don't show up
+    assert_okay!(con, query!("set", "z", "100"));
+    runeq!(con, query!("del", "x", "y"), Element::UnsignedInt(1));
+    expect(&mut watcher, b"*^?3\n5\nwatch1\nx3\ndel").await;
+    send(&mut watcher, &["unwatch", "x"]).await;
+    expect(&mut watcher, b"*:1\n").await;
+    assert_okay!(con, query!("set", "x", "100"));
+    assert_okay!(con, query!("set", "y", "100"));
+    expect(&mut watcher, b"*^?3\n5\nwatch1\ny3\nset").await;
+    // leaving all keys ends push mode
+    send(&mut watcher, &["unwatch"]).await;
+    expect(&mut watcher, b"*:0\n").await;
+    send(&mut watcher, &["heya"]).await;
+    expect(&mut watcher, b"*+4\nHEY!").await;
+}
+
+#[dbtest]
+async fn watch_and_subscribe() {
+    let mut watcher = connect_to(&__MYENTITY__).await;
+    send(&mut watcher, &["watch", "x"]).await;
+    expect(&mut watcher, b"*:1\n").await;
+    send(&mut watcher, &["subscribe", "watch-news"]).await;
+    expect(&mut watcher, b"*:1\n").await;
+    runeq!(
+        con,
+        query!("publish", "watch-news", "hello"),
+        Element::UnsignedInt(1)
+    );
+    expect(&mut watcher, b"*^?3\n7\nmessage10\nwatch-news5\nhello").await;
+    assert_okay!(con, query!("set", "x", "100"));
+    expect(&mut watcher, b"*^?3\n5\nwatch1\nx3\nset").await;
+    // the watch keeps push mode on after leaving the channel
+    send(&mut watcher, &["unsubscribe"]).await;
+    expect(&mut watcher, b"*:0\n").await;
+    send(&mut watcher, &["get", "x"]).await;
+    expect(&mut watcher, b"*!err-subscribed\n").await;
+}
+
+#[dbtest]
+async fn watch_aerr() {
+    runeq!(
+        con,
+        query!("watch"),
+        Element::RespCode(RespCode::ActionError)
+    );
+}
@@ -0,0 +1,168 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! Glob-style matching for keys. The syntax is the one that most shells (and Redis) use:
+//! - `*` matches any run of bytes (including an empty one)
+//! - `?` matches exactly one byte
+//! - `[abc]`, `[a-z]` match one byte from the class; `[^abc]` (or `[!abc]`) negates it
+//! - `\` escapes the next byte so that it's matched literally
+
+/// Returns true if `text` matches the glob `pattern`
+pub fn matches(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // where to resume if we need to let the last star eat one more byte
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() {
+            match pattern[p] {
+                b'*' => {
+                    p += 1;
+                    backtrack = Some((p, t));
+                    continue;
+                }
+                b'?' => {
+                    p += 1;
+                    t += 1;
+                    continue;
+                }
+                b'[' => {
+                    if let Some((hit, next)) = match_class(&pattern[p + 1..], text[t]) {
+                        if hit {
+                            p += 1 + next;
+                            t += 1;
+                            continue;
+                        }
+                    } else if text[t] == b'[' {
+                        // an unterminated class is just a literal bracket
+                        p += 1;
+                        t += 1;
+                        continue;
+                    }
+                }
+                b'\\' if p + 1 < pattern.len() => {
+                    if pattern[p + 1] == text[t] {
+                        p += 2;
+                        t += 1;
+                        continue;
+                    }
+                }
+                byte => {
+                    if byte == text[t] {
+                        p += 1;
+                        t += 1;
+                        continue;
+                    }
+                }
+            }
+        }
+        match backtrack {
+            Some((star_p, star_t)) => {
+                p = star_p;
+                t = star_t + 1;
+                backtrack = Some((star_p, t));
+            }
+            None => return false,
+        }
+    }
+    pattern[p..].iter().all(|byte| *byte == b'*')
+}
+
+/// Match `byte` against the class whose body (everything after the `[`) is in `class`. Returns
+/// whether it matched along with the number of pattern bytes consumed (including the `]`), or
+/// `None` if the class is never closed
+fn match_class(class: &[u8], byte: u8) -> Option<(bool, usize)> {
+    let mut i = 0;
+    let negate = matches!(class.first(), Some(b'^' | b'!'));
+    if negate {
+        i += 1;
+    }
+    let mut hit = false;
+    let mut first = true;
+    loop {
+        let mut lo = *class.get(i)?;
+        if lo == b']' && !first {
+            return Some((hit != negate, i + 1));
+        }
+        first = false;
+        if lo == b'\\' {
+            i += 1;
+            lo = *class.get(i)?;
+        }
+        i += 1;
+        let mut hi = lo;
+        if class.get(i) == Some(&b'-') && !matches!(class.get(i + 1), None | Some(b']')) {
+            hi = class[i + 1];
+            if hi == b'\\' {
+                hi = *class.get(i + 2)?;
+                i += 1;
+            }
+            i += 2;
+        }
+        let (lo, hi) = if lo <= hi { (lo, hi) } else { (hi, lo) };
+        hit |= (lo..=hi).contains(&byte);
+    }
+}
+
+#[test]
+fn test_glob_literals_and_wildcards() {
+    assert!(matches(b"", b""));
+    assert!(!matches(b"", b"a"));
+    assert!(matches(b"user", b"user"));
+    assert!(!matches(b"user", b"users"));
+    assert!(matches(b"*", b""));
+    assert!(matches(b"*", b"anything"));
+    assert!(matches(b"user:*", b"user:1234"));
+    assert!(!matches(b"user:*", b"session:1234"));
+    assert!(matches(b"*:*:name", b"user:1:name"));
+    assert!(!matches(b"*:*:name", b"user:1:email"));
+    assert!(matches(b"a*b*c", b"aXXbYYbZc"));
+    assert!(matches(b"h?llo", b"hello"));
+    assert!(!matches(b"h?llo", b"hllo"));
+}
+
+#[test]
+fn test_glob_classes() {
+    assert!(matches(b"h[ae]llo", b"hallo"));
+    assert!(!matches(b"h[ae]llo", b"hillo"));
+    assert!(matches(b"key[0-9]", b"key7"));
+    assert!(!matches(b"key[0-9]", b"keyx"));
+    assert!(matches(b"key[^0-9]", b"keyx"));
+    assert!(matches(b"key[!0-9]", b"keyx"));
+    assert!(!matches(b"key[^0-9]", b"key7"));
+    assert!(matches(b"[]]", b"]"));
+    assert!(matches(b"[a-]", b"-"));
+    // unterminated classes are literal
+    assert!(matches(b"a[b", b"a[b"));
+}
+
+#[test]
+fn test_glob_escapes() {
+    assert!(matches(b"what\\?", b"what?"));
+    assert!(!matches(b"what\\?", b"whatx"));
+    assert!(matches(b"\\*", b"*"));
+    assert!(!matches(b"\\*", b"x"));
+    assert!(matches(b"[\\]]", b"]"));
+}
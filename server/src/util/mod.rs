@@ -28,6 +28,7 @@
 mod macros;
 pub mod compiler;
 pub mod error;
+pub mod glob;
 pub mod os;
 use {
     crate::{
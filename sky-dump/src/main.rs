@@ -0,0 +1,122 @@
+/*
+ * Created on Sun Oct 18 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use {
+    clap::{load_yaml, App, ArgMatches},
+    skyd::{
+        dump::{self, Format, ModelInfo},
+        encryption::{self, Key},
+    },
+    std::{
+        fmt::Display,
+        io::{self, BufWriter, Write},
+        process,
+    },
+};
+
+fn main() {
+    let cfg_layout = load_yaml!("cli.yml");
+    let matches = App::from_yaml(cfg_layout).get_matches();
+    let key = matches.value_of("keyfile").map(load_key);
+    if let ("rekey", Some(args)) = matches.subcommand() {
+        return rekey(key.as_ref(), args);
+    }
+    if let Some(key) = key {
+        encryption::set_key(key);
+    }
+    let dir = matches.value_of("dir").unwrap_or("data/ks");
+    let models = match dump::list(dir) {
+        Ok(models) => models,
+        Err(e) => err(format_args!("Failed to read `{dir}`: {e}")),
+    };
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    match matches.subcommand() {
+        ("list", _) => {
+            for model in &models {
+                let volatility = if model.volatile {
+                    "volatile"
+                } else {
+                    "persistent"
+                };
+                let _ = writeln!(
+                    out,
+                    "{}.{}\tcode={}\t{volatility}\t{}",
+                    model.space, model.model, model.model_code, model.description
+                );
+            }
+        }
+        ("dump", Some(args)) => {
+            let format = args
+                .value_of("format")
+                .and_then(Format::from_name)
+                .unwrap_or(Format::JsonLines);
+            let models: Vec<ModelInfo> = match args.value_of("model") {
+                Some(name) => {
+                    let found: Vec<ModelInfo> = models
+                        .into_iter()
+                        .filter(|m| format!("{}.{}", m.space, m.model) == name)
+                        .collect();
+                    if found.is_empty() {
+                        err(format_args!(
+                            "No model `{name}` in `{dir}`. Run `list` to see all models"
+                        ));
+                    }
+                    found
+                }
+                None => models,
+            };
+            if let Err(e) = dump::dump(dir, &models, format, &mut out) {
+                err(format_args!("Failed to dump `{dir}`: {e}"));
+            }
+        }
+        _ => err("Expected a subcommand: `list`, `dump` or `rekey`"),
+    }
+    if let Err(e) = out.flush() {
+        err(format_args!("Failed to write output: {e}"));
+    }
+}
+
+fn rekey(old: Option<&Key>, args: &ArgMatches) {
+    let new = args.value_of("newkeyfile").map(load_key);
+    let datadir = args.value_of("datadir").unwrap_or("data");
+    match encryption::rekey(datadir, old, new.as_ref()) {
+        Ok(rewritten) => println!("Rewrote {rewritten} file(s) in `{datadir}`"),
+        Err(e) => err(format_args!("Failed to rekey `{datadir}`: {e}")),
+    }
+}
+
+fn load_key(keyfile: &str) -> Key {
+    match Key::from_file(keyfile) {
+        Ok(key) => key,
+        Err(e) => err(format_args!("Failed to read the key from `{keyfile}`: {e}")),
+    }
+}
+
+fn err(msg: impl Display) -> ! {
+    eprintln!("error: {msg}");
+    process::exit(0x01)
+}